@@ -0,0 +1,1003 @@
+use super::lexer::parse_numeric;
+use super::*;
+use crate::error::*;
+use crate::msgpack::{self, MsgPack};
+use std::collections::HashMap;
+
+/// The BinaryCIF version this crate writes
+const VERSION: &str = "0.3.0";
+
+/// The type codes of the `ByteArray` encoding
+mod data_type {
+    /// Signed 8 bit integer
+    pub(super) const INT8: i64 = 1;
+    /// Signed 16 bit integer
+    pub(super) const INT16: i64 = 2;
+    /// Signed 32 bit integer
+    pub(super) const INT32: i64 = 3;
+    /// Unsigned 8 bit integer
+    pub(super) const UINT8: i64 = 4;
+    /// Unsigned 16 bit integer
+    pub(super) const UINT16: i64 = 5;
+    /// Unsigned 32 bit integer
+    pub(super) const UINT32: i64 = 6;
+    /// Single precision float
+    pub(super) const FLOAT32: i64 = 32;
+    /// Double precision float
+    pub(super) const FLOAT64: i64 = 33;
+}
+
+/// A column of values while it is being encoded or decoded
+#[derive(Debug, Clone, PartialEq)]
+enum Array {
+    /// The raw bytes
+    Bytes(Vec<u8>),
+    /// Integers
+    Int(Vec<i64>),
+    /// Floating point numbers
+    Float(Vec<f64>),
+    /// Strings, `None` for the index -1 used for missing strings
+    Str(Vec<Option<String>>),
+}
+
+/// Decode a BinaryCIF file into its data blocks. Every category with a single row is given as
+/// single data items, like in text CIF files, except for `atom_site` which is always given as a
+/// loop. Strings that can be read as a number are stored as numbers, like unquoted values in
+/// text CIF files.
+/// # Errors
+/// It fails if the bytes are not valid BinaryCIF.
+pub(crate) fn decode_bcif(bytes: &[u8]) -> Result<Vec<DataBlock>, PDBError> {
+    let file = msgpack::decode(bytes)?;
+    let blocks = file
+        .get("dataBlocks")
+        .and_then(MsgPack::as_array)
+        .ok_or_else(|| invalid("The file does not contain any data blocks.", Context::None))?;
+    blocks.iter().map(decode_block).collect()
+}
+
+/// Decode a single data block
+fn decode_block(block: &MsgPack) -> Result<DataBlock, PDBError> {
+    let name = block.get("header").and_then(MsgPack::as_str).unwrap_or("");
+    let mut output = DataBlock::new(name);
+    let categories = block
+        .get("categories")
+        .and_then(MsgPack::as_array)
+        .ok_or_else(|| {
+            invalid(
+                "The data block does not have categories.",
+                Context::show(format!("data_{name}")),
+            )
+        })?;
+    for category in categories {
+        let category_name = category.get("name").and_then(MsgPack::as_str).unwrap_or("");
+        let category_name = category_name.strip_prefix('_').unwrap_or(category_name);
+        let context = Context::show(format!("_{category_name}"));
+        let row_count = category
+            .get("rowCount")
+            .and_then(MsgPack::as_int)
+            .and_then(|n| usize::try_from(n).ok())
+            .ok_or_else(|| {
+                invalid(
+                    "The category does not have a valid row count.",
+                    context.clone(),
+                )
+            })?;
+        let mut header = Vec::new();
+        let mut columns = Vec::new();
+        for column in category
+            .get("columns")
+            .and_then(MsgPack::as_array)
+            .unwrap_or_default()
+        {
+            let column_name = column.get("name").and_then(MsgPack::as_str).unwrap_or("");
+            let tag = format!("{category_name}.{column_name}");
+            let values = decode_column(column, row_count)
+                .map_err(|message| invalid(&message, Context::show(format!("_{tag}"))))?;
+            header.push(tag);
+            columns.push(values);
+        }
+        if header.is_empty() {
+            continue;
+        }
+        if row_count == 1 && category_name != "atom_site" {
+            output
+                .items
+                .extend(header.into_iter().zip(columns).map(|(name, mut values)| {
+                    Item::DataItem(DataItem::Single(Single {
+                        name,
+                        content: values.remove(0),
+                    }))
+                }));
+        } else {
+            let mut data = vec![Vec::with_capacity(header.len()); row_count];
+            for values in columns {
+                for (row, value) in data.iter_mut().zip(values) {
+                    row.push(value);
+                }
+            }
+            output
+                .items
+                .push(Item::DataItem(DataItem::Loop(Loop { header, data })));
+        }
+    }
+    Ok(output)
+}
+
+/// Create the error for an invalid BinaryCIF file
+fn invalid(message: &str, context: Context) -> PDBError {
+    PDBError::new(
+        ErrorLevel::BreakingError,
+        "Invalid BinaryCIF",
+        message,
+        context,
+    )
+}
+
+/// Decode a single column, with its mask, into the values for every row
+#[allow(clippy::cast_precision_loss)]
+fn decode_column(column: &MsgPack, row_count: usize) -> Result<Vec<Value>, String> {
+    let data = decode_data(
+        column
+            .get("data")
+            .ok_or("The column does not contain data.")?,
+        row_count,
+    )?;
+    let mask = match column.get("mask") {
+        None | Some(MsgPack::Nil) => None,
+        Some(mask) => match decode_data(mask, row_count)? {
+            Array::Int(mask) => Some(mask),
+            _ => return Err("The mask of the column is not an integer array.".to_string()),
+        },
+    };
+    let values: Vec<Value> = match data {
+        Array::Int(values) => values
+            .into_iter()
+            .map(|v| Value::Numeric(v.to_string()))
+            .collect(),
+        Array::Float(values) => values.into_iter().map(Value::from).collect(),
+        Array::Str(values) => values
+            .into_iter()
+            .map(|v| match v {
+                None => Value::Unknown,
+                Some(s) if s.is_empty() => Value::Text(s),
+                Some(s) => parse_numeric(&s).unwrap_or(Value::Text(s)),
+            })
+            .collect(),
+        Array::Bytes(_) => return Err("The column data is not fully decoded.".to_string()),
+    };
+    if values.len() != row_count {
+        return Err(format!(
+            "The column has {} values, while the category has {row_count} rows.",
+            values.len()
+        ));
+    }
+    match mask {
+        Some(mask) if mask.len() != row_count => Err(format!(
+            "The mask has {} values, while the category has {row_count} rows.",
+            mask.len()
+        )),
+        Some(mask) => Ok(values
+            .into_iter()
+            .zip(mask)
+            .map(|(value, mask)| match mask {
+                1 => Value::Inapplicable,
+                2 => Value::Unknown,
+                _ => value,
+            })
+            .collect()),
+        None => Ok(values),
+    }
+}
+
+/// Decode encoded data, consisting of a binary blob and the list of encodings applied to it, for
+/// a category with the given number of rows
+fn decode_data(data: &MsgPack, row_count: usize) -> Result<Array, String> {
+    let bytes = data
+        .get("data")
+        .and_then(MsgPack::as_bin)
+        .ok_or("The encoded data is not a binary blob.")?;
+    let encodings = data
+        .get("encoding")
+        .and_then(MsgPack::as_array)
+        .ok_or("The encoded data does not list its encodings.")?;
+    decode_array(Array::Bytes(bytes.to_vec()), encodings, row_count)
+}
+
+/// Undo the given encodings, in reverse order
+fn decode_array(
+    mut array: Array,
+    encodings: &[MsgPack],
+    row_count: usize,
+) -> Result<Array, String> {
+    for encoding in encodings.iter().rev() {
+        array = decode_step(array, encoding, row_count)?;
+    }
+    Ok(array)
+}
+
+/// Get the given integer parameter of an encoding
+fn parameter(encoding: &MsgPack, name: &str) -> Result<i64, String> {
+    encoding
+        .get(name)
+        .and_then(MsgPack::as_int)
+        .ok_or_else(|| format!("The encoding does not have a valid '{name}' parameter."))
+}
+
+/// Undo a single encoding, the data belongs to a category with the given number of rows
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+fn decode_step(array: Array, encoding: &MsgPack, row_count: usize) -> Result<Array, String> {
+    let kind = encoding.get("kind").and_then(MsgPack::as_str).unwrap_or("");
+    match (kind, array) {
+        ("ByteArray", Array::Bytes(bytes)) => from_bytes(&bytes, parameter(encoding, "type")?),
+        ("FixedPoint", Array::Int(values)) => {
+            let factor = encoding
+                .get("factor")
+                .and_then(MsgPack::as_float)
+                .ok_or("The encoding does not have a valid 'factor' parameter.")?;
+            Ok(Array::Float(
+                values.into_iter().map(|v| v as f64 / factor).collect(),
+            ))
+        }
+        ("IntervalQuantization", Array::Int(values)) => {
+            let float = |name| {
+                encoding
+                    .get(name)
+                    .and_then(MsgPack::as_float)
+                    .ok_or_else(|| {
+                        format!("The encoding does not have a valid '{name}' parameter.")
+                    })
+            };
+            let min = float("min")?;
+            let max = float("max")?;
+            let steps = parameter(encoding, "numSteps")?;
+            let delta = if steps > 1 {
+                (max - min) / (steps - 1) as f64
+            } else {
+                0.0
+            };
+            Ok(Array::Float(
+                values.into_iter().map(|v| min + delta * v as f64).collect(),
+            ))
+        }
+        ("RunLength", Array::Int(values)) => {
+            if values.len() % 2 != 0 {
+                return Err("Run length encoded data should consist of pairs.".to_string());
+            }
+            // The size is given in the file, so it is capped by the number of rows, with one
+            // more for the offsets of string arrays
+            let size = usize::try_from(parameter(encoding, "srcSize")?)
+                .map_err(|_| "Negative run length size.")?
+                .min(row_count + 1);
+            let mut output = Vec::new();
+            for pair in values.chunks(2) {
+                let count = usize::try_from(pair[1]).map_err(|_| "Negative run length.")?;
+                if output.len() + count > size {
+                    return Err("Run length encoded data is longer than its size.".to_string());
+                }
+                output.extend(std::iter::repeat(pair[0]).take(count));
+            }
+            Ok(Array::Int(output))
+        }
+        ("Delta", Array::Int(values)) => {
+            let mut current = parameter(encoding, "origin")?;
+            Ok(Array::Int(
+                values
+                    .into_iter()
+                    .map(|v| {
+                        current = current.wrapping_add(v);
+                        current
+                    })
+                    .collect(),
+            ))
+        }
+        ("IntegerPacking", Array::Int(values)) => {
+            let unsigned = encoding
+                .get("isUnsigned")
+                .and_then(MsgPack::as_bool)
+                .unwrap_or(false);
+            let (upper, lower) = packing_limits(parameter(encoding, "byteCount")?, unsigned)?;
+            let mut output = Vec::with_capacity(values.len());
+            let mut value = 0_i64;
+            for v in values {
+                value += v;
+                if v != upper && v != lower {
+                    output.push(value);
+                    value = 0;
+                }
+            }
+            Ok(Array::Int(output))
+        }
+        ("StringArray", Array::Bytes(bytes)) => {
+            let text = encoding
+                .get("stringData")
+                .and_then(MsgPack::as_str)
+                .ok_or("The string array does not contain its strings.")?;
+            let offsets = encoding
+                .get("offsets")
+                .and_then(MsgPack::as_bin)
+                .ok_or("The string array does not contain its offsets.")?;
+            let list = |name| {
+                encoding
+                    .get(name)
+                    .and_then(MsgPack::as_array)
+                    .ok_or_else(|| format!("The string array does not list its '{name}'."))
+            };
+            let offsets = match decode_array(
+                Array::Bytes(offsets.to_vec()),
+                list("offsetEncoding")?,
+                row_count,
+            )? {
+                Array::Int(offsets) => offsets,
+                _ => return Err("The string offsets are not integers.".to_string()),
+            };
+            let strings = offsets
+                .windows(2)
+                .map(|w| {
+                    usize::try_from(w[0])
+                        .ok()
+                        .zip(usize::try_from(w[1]).ok())
+                        .and_then(|(start, end)| text.get(start..end))
+                        .ok_or("A string offset is out of bounds.")
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            match decode_array(Array::Bytes(bytes), list("dataEncoding")?, row_count)? {
+                Array::Int(indices) => indices
+                    .into_iter()
+                    .map(|i| match usize::try_from(i) {
+                        Ok(i) => strings
+                            .get(i)
+                            .map(|s| Some((*s).to_string()))
+                            .ok_or_else(|| "A string index is out of bounds.".to_string()),
+                        Err(_) => Ok(None),
+                    })
+                    .collect::<Result<_, _>>()
+                    .map(Array::Str),
+                _ => Err("The string indices are not integers.".to_string()),
+            }
+        }
+        (kind, _) => Err(format!(
+            "The encoding '{kind}' is not supported or is applied to the wrong type of data."
+        )),
+    }
+}
+
+/// The values used to signal continuation in the `IntegerPacking` encoding, for the given
+/// number of bytes. For unsigned packing the lower limit cannot be reached.
+fn packing_limits(byte_count: i64, unsigned: bool) -> Result<(i64, i64), String> {
+    match (byte_count, unsigned) {
+        (1, false) => Ok((i64::from(i8::MAX), i64::from(i8::MIN))),
+        (2, false) => Ok((i64::from(i16::MAX), i64::from(i16::MIN))),
+        (1, true) => Ok((i64::from(u8::MAX), -1)),
+        (2, true) => Ok((i64::from(u16::MAX), -1)),
+        _ => Err(format!(
+            "Integer packing with {byte_count} bytes is not supported."
+        )),
+    }
+}
+
+/// Read the little endian numbers of the given type from the bytes
+#[allow(clippy::cast_possible_truncation)]
+fn from_bytes(bytes: &[u8], data_type: i64) -> Result<Array, String> {
+    /// Convert the bytes to a vector of values, taking chunks of the size of the array
+    fn convert<const N: usize, T>(bytes: &[u8], f: fn([u8; N]) -> T) -> Result<Vec<T>, String> {
+        if bytes.len() % N == 0 {
+            Ok(bytes
+                .chunks_exact(N)
+                .map(|c| {
+                    let mut array = [0; N];
+                    array.copy_from_slice(c);
+                    f(array)
+                })
+                .collect())
+        } else {
+            Err(format!("The byte array length is not a multiple of {N}."))
+        }
+    }
+    match data_type {
+        data_type::INT8 => {
+            convert(bytes, |b: [u8; 1]| i64::from(i8::from_le_bytes(b))).map(Array::Int)
+        }
+        data_type::INT16 => convert(bytes, |b| i64::from(i16::from_le_bytes(b))).map(Array::Int),
+        data_type::INT32 => convert(bytes, |b| i64::from(i32::from_le_bytes(b))).map(Array::Int),
+        data_type::UINT8 => convert(bytes, |b: [u8; 1]| i64::from(b[0])).map(Array::Int),
+        data_type::UINT16 => convert(bytes, |b| i64::from(u16::from_le_bytes(b))).map(Array::Int),
+        data_type::UINT32 => convert(bytes, |b| i64::from(u32::from_le_bytes(b))).map(Array::Int),
+        data_type::FLOAT32 => {
+            convert(bytes, |b| f64::from(f32::from_le_bytes(b))).map(Array::Float)
+        }
+        data_type::FLOAT64 => convert(bytes, f64::from_le_bytes).map(Array::Float),
+        _ => Err(format!("The byte array type {data_type} is not supported.")),
+    }
+}
+
+/// Encode the given data blocks as a BinaryCIF file. Save frames are not supported by
+/// BinaryCIF and are left out. Integer columns are delta, run length, and integer packing
+/// encoded, decimal columns are stored as fixed point numbers if that is exact, and all other
+/// columns are stored as string arrays.
+pub(crate) fn encode_bcif(blocks: &[DataBlock]) -> Vec<u8> {
+    let blocks = blocks
+        .iter()
+        .map(|block| {
+            let categories = block
+                .category_names()
+                .into_iter()
+                .filter_map(|name| block.category(name))
+                .map(|category| encode_category(&category))
+                .collect();
+            MsgPack::map(vec![
+                ("header", block.name.as_str().into()),
+                ("categories", MsgPack::Array(categories)),
+            ])
+        })
+        .collect();
+    msgpack::encode(&MsgPack::map(vec![
+        ("version", VERSION.into()),
+        (
+            "encoder",
+            concat!("pdbtbx ", env!("CARGO_PKG_VERSION")).into(),
+        ),
+        ("dataBlocks", MsgPack::Array(blocks)),
+    ]))
+}
+
+/// Encode a single category
+#[allow(clippy::cast_possible_wrap)]
+fn encode_category(category: &Category<'_>) -> MsgPack {
+    let columns = category
+        .columns()
+        .iter()
+        .enumerate()
+        .map(|(index, name)| {
+            let values: Vec<&Value> = category.rows.iter().map(|row| row[index]).collect();
+            encode_column(name, &values)
+        })
+        .collect();
+    MsgPack::map(vec![
+        ("name", format!("_{}", category.name()).as_str().into()),
+        ("columns", MsgPack::Array(columns)),
+        ("rowCount", (category.row_count() as i64).into()),
+    ])
+}
+
+/// Encode a single column, with a mask if any values are missing
+#[allow(clippy::cast_possible_truncation, clippy::float_cmp)]
+fn encode_column(name: &str, values: &[&Value]) -> MsgPack {
+    let mask: Vec<i64> = values
+        .iter()
+        .map(|v| match v {
+            Value::Inapplicable => 1,
+            Value::Unknown => 2,
+            _ => 0,
+        })
+        .collect();
+    let numbers: Option<Vec<f64>> = values
+        .iter()
+        .map(|v| match v {
+            Value::Numeric(n) => n.parse().ok(),
+            Value::Inapplicable | Value::Unknown => Some(0.0),
+            _ => None,
+        })
+        .collect();
+    let data = match numbers {
+        Some(numbers) => encode_numbers(&numbers),
+        None => encode_strings(values),
+    };
+    let mask = if mask.iter().any(|m| *m != 0) {
+        encode_integers(&mask, Vec::new())
+    } else {
+        MsgPack::Nil
+    };
+    MsgPack::map(vec![("name", name.into()), ("data", data), ("mask", mask)])
+}
+
+/// Encode numbers, as integers if they are all integral and as fixed point numbers if that is
+/// exact, otherwise as double precision floats
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::float_cmp
+)]
+fn encode_numbers(numbers: &[f64]) -> MsgPack {
+    /// The largest value that can be stored in the encoded integers
+    const LIMIT: f64 = i32::MAX as f64;
+    let exponent = (0..=6).find(|e| {
+        let factor = 10_f64.powi(*e);
+        numbers.iter().all(|n| {
+            let scaled = (n * factor).round();
+            scaled.abs() <= LIMIT && scaled / factor == *n
+        })
+    });
+    match exponent {
+        Some(0) => {
+            let integers: Vec<i64> = numbers.iter().map(|n| *n as i64).collect();
+            encode_integers(&integers, Vec::new())
+        }
+        Some(exponent) => {
+            let factor = 10_f64.powi(exponent);
+            let integers: Vec<i64> = numbers
+                .iter()
+                .map(|n| (n * factor).round() as i64)
+                .collect();
+            let fixed_point = MsgPack::map(vec![
+                ("kind", "FixedPoint".into()),
+                ("factor", MsgPack::Float(factor)),
+                ("srcType", data_type::FLOAT64.into()),
+            ]);
+            encode_integers(&integers, vec![fixed_point])
+        }
+        None => MsgPack::map(vec![
+            (
+                "data",
+                MsgPack::Bin(numbers.iter().flat_map(|n| n.to_le_bytes()).collect()),
+            ),
+            (
+                "encoding",
+                MsgPack::Array(vec![byte_array(data_type::FLOAT64)]),
+            ),
+        ]),
+    }
+}
+
+/// Encode values as a string array, numbers are formatted as they would be written in text CIF
+#[allow(clippy::cast_possible_wrap)]
+fn encode_strings(values: &[&Value]) -> MsgPack {
+    let mut strings: HashMap<String, i64> = HashMap::new();
+    let mut string_data = String::new();
+    let mut offsets = vec![0_i64];
+    let indices: Vec<i64> = values
+        .iter()
+        .map(|value| {
+            let text = match value {
+                Value::Inapplicable | Value::Unknown => return -1,
+                Value::Text(t) | Value::Numeric(t) | Value::NumericWithUncertainty(t) => t.clone(),
+            };
+            let count = strings.len() as i64;
+            *strings.entry(text).or_insert_with_key(|text| {
+                string_data.push_str(text);
+                offsets.push(string_data.len() as i64);
+                count
+            })
+        })
+        .collect();
+    let mut offset_encoding = Vec::new();
+    let offsets = delta(&offsets, &mut offset_encoding);
+    let offsets = pack(&offsets, &mut offset_encoding);
+    let mut data_encoding = Vec::new();
+    let indices = run_length(indices, &mut data_encoding);
+    let data = pack(&indices, &mut data_encoding);
+    let string_array = MsgPack::map(vec![
+        ("kind", "StringArray".into()),
+        ("dataEncoding", MsgPack::Array(data_encoding)),
+        ("stringData", string_data.as_str().into()),
+        ("offsetEncoding", MsgPack::Array(offset_encoding)),
+        ("offsets", MsgPack::Bin(offsets)),
+    ]);
+    MsgPack::map(vec![
+        ("data", MsgPack::Bin(data)),
+        ("encoding", MsgPack::Array(vec![string_array])),
+    ])
+}
+
+/// Encode integers with delta and run length encoding, after the given encodings
+fn encode_integers(integers: &[i64], mut encoding: Vec<MsgPack>) -> MsgPack {
+    let deltas = delta(integers, &mut encoding);
+    let runs = run_length(deltas, &mut encoding);
+    let data = pack(&runs, &mut encoding);
+    MsgPack::map(vec![
+        ("data", MsgPack::Bin(data)),
+        ("encoding", MsgPack::Array(encoding)),
+    ])
+}
+
+/// Delta encode the integers, the first value is stored as the origin
+fn delta(integers: &[i64], encoding: &mut Vec<MsgPack>) -> Vec<i64> {
+    let origin = integers.first().copied().unwrap_or(0);
+    let mut previous = origin;
+    encoding.push(MsgPack::map(vec![
+        ("kind", "Delta".into()),
+        ("origin", origin.into()),
+        ("srcType", data_type::INT32.into()),
+    ]));
+    integers
+        .iter()
+        .map(|i| {
+            let delta = i - previous;
+            previous = *i;
+            delta
+        })
+        .collect()
+}
+
+/// Run length encode the integers, as pairs of value and number of repeats. This is only done
+/// if it makes the data shorter, otherwise the integers are returned unchanged.
+#[allow(clippy::cast_possible_wrap)]
+fn run_length(integers: Vec<i64>, encoding: &mut Vec<MsgPack>) -> Vec<i64> {
+    let mut runs: Vec<i64> = Vec::new();
+    for i in &integers {
+        match runs.len() {
+            len if len >= 2 && runs[len - 2] == *i => runs[len - 1] += 1,
+            _ => runs.extend([*i, 1]),
+        }
+    }
+    if runs.len() < integers.len() {
+        encoding.push(MsgPack::map(vec![
+            ("kind", "RunLength".into()),
+            ("srcType", data_type::INT32.into()),
+            ("srcSize", (integers.len() as i64).into()),
+        ]));
+        runs
+    } else {
+        integers
+    }
+}
+
+/// Encode the integers as bytes, adding the used encodings. They are integer packed into one or
+/// two bytes if that is smaller than storing them as 32 bit integers.
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+fn pack(integers: &[i64], encoding: &mut Vec<MsgPack>) -> Vec<u8> {
+    let unsigned = integers.iter().all(|i| *i >= 0);
+    let packed_size = |byte_count| -> Option<usize> {
+        let (upper, lower) = packing_limits(byte_count, unsigned).ok()?;
+        let count: i64 = integers
+            .iter()
+            .map(|i| {
+                if *i >= 0 {
+                    i / upper + 1
+                } else {
+                    i / lower + 1
+                }
+            })
+            .sum();
+        usize::try_from(count * byte_count).ok()
+    };
+    let best = [1, 2]
+        .into_iter()
+        .filter_map(|b| packed_size(b).map(|size| (b, size)))
+        .min_by_key(|(_, size)| *size)
+        .filter(|(_, size)| *size < integers.len() * 4);
+    if let Some((byte_count, _)) = best {
+        #[allow(clippy::unwrap_used)] // Checked when calculating the size above
+        let (upper, lower) = packing_limits(byte_count, unsigned).unwrap();
+        let mut bytes = Vec::new();
+        for i in integers {
+            let limit = if *i >= 0 { upper } else { lower };
+            let mut rest = *i;
+            while rest / limit != 0 {
+                push_le(&mut bytes, limit, byte_count);
+                rest -= limit;
+            }
+            push_le(&mut bytes, rest, byte_count);
+        }
+        let data_type = match (byte_count, unsigned) {
+            (1, true) => data_type::UINT8,
+            (1, false) => data_type::INT8,
+            (_, true) => data_type::UINT16,
+            (_, false) => data_type::INT16,
+        };
+        encoding.push(MsgPack::map(vec![
+            ("kind", "IntegerPacking".into()),
+            ("byteCount", byte_count.into()),
+            ("isUnsigned", MsgPack::Bool(unsigned)),
+            ("srcSize", (integers.len() as i64).into()),
+        ]));
+        encoding.push(byte_array(data_type));
+        bytes
+    } else {
+        encoding.push(byte_array(data_type::INT32));
+        integers
+            .iter()
+            .flat_map(|i| (*i as i32).to_le_bytes())
+            .collect()
+    }
+}
+
+/// Push the lowest bytes of the integer in little endian order
+#[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+fn push_le(bytes: &mut Vec<u8>, value: i64, byte_count: i64) {
+    bytes.extend(&value.to_le_bytes()[..byte_count as usize]);
+}
+
+/// The `ByteArray` encoding for the given type
+fn byte_array(data_type: i64) -> MsgPack {
+    MsgPack::map(vec![
+        ("kind", "ByteArray".into()),
+        ("type", data_type.into()),
+    ])
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    /// Encode and decode the given values as a single column
+    fn round_trip(values: &[Value]) -> Vec<Value> {
+        let encoded = encode_column("test", &values.iter().collect::<Vec<_>>());
+        decode_column(&encoded, values.len()).unwrap()
+    }
+
+    #[test]
+    fn integers() {
+        let values: Vec<Value> = [1, 2, 3, 3, 3, 500, -70000, 0, 127, 128, -128, -129]
+            .iter()
+            .map(|i| Value::from(f64::from(*i)))
+            .collect();
+        assert_eq!(round_trip(&values), values);
+        let values: Vec<Value> = (0..1000).map(|_| Value::from(1.0)).collect();
+        assert_eq!(round_trip(&values), values);
+    }
+
+    #[test]
+    fn decimals() {
+        let values = vec![
+            Value::from(12.345),
+            Value::from(-0.001),
+            Value::Unknown,
+            Value::from(1e-9),
+        ];
+        assert_eq!(round_trip(&values), values);
+        let values = vec![Value::from(1.5), Value::Inapplicable];
+        assert_eq!(round_trip(&values), values);
+    }
+
+    #[test]
+    fn strings() {
+        let values = vec![
+            Value::Text("ATOM".to_string()),
+            Value::Text("HETATM".to_string()),
+            Value::Text("ATOM".to_string()),
+            Value::Inapplicable,
+            Value::from(1.0),
+            Value::NumericWithUncertainty("1.5(2)".to_string()),
+            Value::Unknown,
+            Value::Text("multi\nline text".to_string()),
+        ];
+        assert_eq!(round_trip(&values), values);
+    }
+
+    #[test]
+    fn integer_packing() {
+        for unsigned in [true, false] {
+            let integers: Vec<i64> = if unsigned {
+                vec![0, 254, 255, 256, 70000, 1]
+            } else {
+                vec![0, 127, -128, 300, -300, -70000, 1]
+            };
+            let mut encoding = Vec::new();
+            let bytes = pack(&integers, &mut encoding);
+            assert!(bytes.len() < integers.len() * 4);
+            assert_eq!(
+                decode_array(Array::Bytes(bytes), &encoding, integers.len()).unwrap(),
+                Array::Int(integers)
+            );
+        }
+    }
+
+    #[test]
+    fn run_length() {
+        let encoding = |size: i64| {
+            MsgPack::map(vec![
+                ("kind", "RunLength".into()),
+                ("srcType", data_type::INT32.into()),
+                ("srcSize", size.into()),
+            ])
+        };
+        let pairs = || Array::Int(vec![7, 2, 8, 1]);
+        assert_eq!(
+            decode_step(pairs(), &encoding(3), 3).unwrap(),
+            Array::Int(vec![7, 7, 8])
+        );
+        // The size given in the file cannot be larger than the category
+        assert!(decode_step(
+            Array::Int(vec![7, i64::from(i32::MAX)]),
+            &encoding(i64::from(i32::MAX)),
+            3
+        )
+        .is_err());
+        assert!(decode_step(pairs(), &encoding(-1), 3).is_err());
+    }
+
+    #[test]
+    fn interval_quantization() {
+        let encoding = MsgPack::map(vec![
+            ("kind", "IntervalQuantization".into()),
+            ("min", MsgPack::Float(1.0)),
+            ("max", MsgPack::Float(2.0)),
+            ("numSteps", 3_i64.into()),
+            ("srcType", data_type::FLOAT32.into()),
+        ]);
+        assert_eq!(
+            decode_step(Array::Int(vec![0, 1, 2]), &encoding, 3).unwrap(),
+            Array::Float(vec![1.0, 1.5, 2.0])
+        );
+    }
+
+    /// Create encoded data from the given bytes and encodings, in the order they were applied
+    fn encoded(bytes: &[u8], encoding: Vec<MsgPack>) -> MsgPack {
+        MsgPack::map(vec![
+            ("data", MsgPack::Bin(bytes.to_vec())),
+            ("encoding", MsgPack::Array(encoding)),
+        ])
+    }
+
+    /// Create a `ByteArray` encoding of the given type
+    fn bytes_of(data_type: i64) -> MsgPack {
+        MsgPack::map(vec![
+            ("kind", "ByteArray".into()),
+            ("type", data_type.into()),
+        ])
+    }
+
+    #[test]
+    fn byte_array_types() {
+        let decode = |bytes: &[u8], data_type| {
+            decode_data(&encoded(bytes, vec![bytes_of(data_type)]), 2).unwrap()
+        };
+        assert_eq!(
+            decode(&[0x01, 0x00, 0xff, 0xff], data_type::UINT16),
+            Array::Int(vec![1, 65535])
+        );
+        assert_eq!(
+            decode(
+                &[0xff, 0xff, 0xff, 0xff, 0x02, 0x01, 0x00, 0x00],
+                data_type::UINT32
+            ),
+            Array::Int(vec![4_294_967_295, 258])
+        );
+        assert_eq!(
+            decode(
+                &[0x00, 0x00, 0xc0, 0x3f, 0x00, 0x00, 0x20, 0xc1],
+                data_type::FLOAT32
+            ),
+            Array::Float(vec![1.5, -10.0])
+        );
+        assert_eq!(
+            decode(&[0x9c, 0xff], data_type::INT16),
+            Array::Int(vec![-100])
+        );
+        assert!(decode_data(&encoded(&[0, 0, 0], vec![bytes_of(data_type::UINT16)]), 2).is_err());
+    }
+
+    #[test]
+    fn unsigned_integer_packing() {
+        // 300 does not fit in a byte, so it is stored as 255 + 45
+        let data = encoded(
+            &[255, 45, 3, 0],
+            vec![
+                MsgPack::map(vec![
+                    ("kind", "IntegerPacking".into()),
+                    ("byteCount", 1_i64.into()),
+                    ("isUnsigned", MsgPack::Bool(true)),
+                    ("srcSize", 3_i64.into()),
+                ]),
+                bytes_of(data_type::UINT8),
+            ],
+        );
+        assert_eq!(decode_data(&data, 3).unwrap(), Array::Int(vec![300, 3, 0]));
+    }
+
+    #[test]
+    fn quantized_coordinates() {
+        // The coordinates 0.5, 1.0, and 3.0 quantized in 11 steps between 0.0 and 5.0, stored
+        // as unsigned 16 bit integers
+        let data = encoded(
+            &[0x01, 0x00, 0x02, 0x00, 0x06, 0x00],
+            vec![
+                MsgPack::map(vec![
+                    ("kind", "IntervalQuantization".into()),
+                    ("min", MsgPack::Float(0.0)),
+                    ("max", MsgPack::Float(5.0)),
+                    ("numSteps", 11_i64.into()),
+                    ("srcType", data_type::FLOAT32.into()),
+                ]),
+                bytes_of(data_type::UINT16),
+            ],
+        );
+        assert_eq!(
+            decode_data(&data, 3).unwrap(),
+            Array::Float(vec![0.5, 1.0, 3.0])
+        );
+    }
+
+    #[test]
+    fn fixed_point_coordinates() {
+        // The coordinates 12.345, 12.5, and -3.0 as fixed point numbers with a factor of 1000,
+        // delta encoded from 12345 and stored as signed 16 bit integers
+        let column = MsgPack::map(vec![
+            ("name", "Cartn_x".into()),
+            (
+                "data",
+                encoded(
+                    &[0x00, 0x00, 0x9b, 0x00, 0x74, 0xc3],
+                    vec![
+                        MsgPack::map(vec![
+                            ("kind", "FixedPoint".into()),
+                            ("factor", MsgPack::Float(1000.0)),
+                            ("srcType", data_type::FLOAT32.into()),
+                        ]),
+                        MsgPack::map(vec![
+                            ("kind", "Delta".into()),
+                            ("origin", 12345_i64.into()),
+                            ("srcType", data_type::INT32.into()),
+                        ]),
+                        MsgPack::map(vec![
+                            ("kind", "IntegerPacking".into()),
+                            ("byteCount", 2_i64.into()),
+                            ("isUnsigned", MsgPack::Bool(false)),
+                            ("srcSize", 3_i64.into()),
+                        ]),
+                        bytes_of(data_type::INT16),
+                    ],
+                ),
+            ),
+            ("mask", MsgPack::Nil),
+        ]);
+        assert_eq!(
+            decode_column(&column, 3).unwrap(),
+            vec![Value::from(12.345), Value::from(12.5), Value::from(-3.0)]
+        );
+    }
+
+    #[test]
+    fn masked_strings() {
+        // The strings "ALA", "GLY", ".", "?", and "ALA", where the missing values are given by
+        // the mask and have index -1 in the string array
+        let column = MsgPack::map(vec![
+            ("name", "label_comp_id".into()),
+            (
+                "data",
+                encoded(
+                    &[0, 1, 0xff, 0xff, 0],
+                    vec![MsgPack::map(vec![
+                        ("kind", "StringArray".into()),
+                        (
+                            "dataEncoding",
+                            MsgPack::Array(vec![bytes_of(data_type::INT8)]),
+                        ),
+                        ("stringData", "ALAGLY".into()),
+                        (
+                            "offsetEncoding",
+                            MsgPack::Array(vec![bytes_of(data_type::UINT8)]),
+                        ),
+                        ("offsets", MsgPack::Bin(vec![0, 3, 6])),
+                    ])],
+                ),
+            ),
+            (
+                "mask",
+                encoded(&[0, 0, 1, 2, 0], vec![bytes_of(data_type::UINT8)]),
+            ),
+        ]);
+        assert_eq!(
+            decode_column(&column, 5).unwrap(),
+            vec![
+                Value::Text("ALA".to_string()),
+                Value::Text("GLY".to_string()),
+                Value::Inapplicable,
+                Value::Unknown,
+                Value::Text("ALA".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn block() {
+        let block = lex_cif(
+            "data_test
+_entry.id TEST
+loop_
+_atom_site.group_PDB
+_atom_site.Cartn_x
+HETATM 1.5
+",
+        )
+        .unwrap();
+        let decoded = decode_bcif(&encode_bcif(std::slice::from_ref(&block))).unwrap();
+        assert_eq!(decoded, vec![block]);
+        assert!(decode_bcif(&[0x80]).is_err());
+    }
+}
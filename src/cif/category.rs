@@ -0,0 +1,254 @@
+use super::*;
+use crate::error::*;
+
+/// A function to get the content of a value, like [`get_text`] or [`get_f64`]
+pub(crate) type GetValue<T> = fn(&Value, &Context, Option<&str>) -> Result<Option<T>, PDBError>;
+
+/// A view on a single category in a data block. A category can be given as a loop, or if it
+/// only contains a single row as a list of single data items, this view handles both cases.
+/// It can be found with [`DataBlock::category`].
+///
+/// ```rust
+/// use pdbtbx::cif::*;
+/// let document = Document::read("example-pdbs/1ubq.cif").unwrap();
+/// let refine = document.blocks[0].category("_refine").unwrap();
+/// assert_eq!(refine.row_count(), 1);
+/// assert_eq!(refine.get_f64(0, "ls_d_res_high").unwrap(), Some(1.8));
+/// assert_eq!(refine.get_text(0, "pdbx_refine_id").unwrap().as_deref(), Some("X-RAY DIFFRACTION"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Category<'a> {
+    /// The name of the category, eg 'struct_conf'
+    pub(crate) name: String,
+    /// The names of the columns, without the category prefix
+    pub(crate) columns: Vec<&'a str>,
+    /// The rows of values, each with the same length as the columns
+    pub(crate) rows: Vec<Vec<&'a Value>>,
+}
+
+impl<'a> Category<'a> {
+    /// Find the category with the given name (without the leading underscore) in the data block
+    pub(crate) fn find(input: &'a DataBlock, name: &str) -> Option<Self> {
+        let prefix = name.len() + 1;
+        let mut columns = Vec::new();
+        let mut row = Vec::new();
+        for item in &input.items {
+            match item {
+                Item::DataItem(DataItem::Loop(l))
+                    if l.header.first().map_or(false, |h| in_category(h, name)) =>
+                {
+                    return Some(Category {
+                        name: name.to_string(),
+                        columns: l.header.iter().map(|h| &h[prefix..]).collect(),
+                        rows: l.data.iter().map(|r| r.iter().collect()).collect(),
+                    });
+                }
+                Item::DataItem(DataItem::Single(single)) if in_category(&single.name, name) => {
+                    columns.push(&single.name[prefix..]);
+                    row.push(&single.content);
+                }
+                _ => (),
+            }
+        }
+        if columns.is_empty() {
+            None
+        } else {
+            Some(Category {
+                name: name.to_string(),
+                columns,
+                rows: vec![row],
+            })
+        }
+    }
+
+    /// Get the value of the given column in the given row using the given function to interpret it.
+    /// It returns `None` if the column does not exist, the value is missing, or if it could not be
+    /// interpreted, in which case the error is added to the given vec.
+    pub(crate) fn get<T>(
+        &self,
+        row: usize,
+        column: &str,
+        get: GetValue<T>,
+        errors: &mut Vec<PDBError>,
+    ) -> Option<T> {
+        let index = self.columns.iter().position(|c| *c == column)?;
+        let context = Context::show(format!("_{}.{} row: {}", self.name, column, row + 1));
+        match get(self.rows[row][index], &context, Some(column)) {
+            Ok(value) => value,
+            Err(e) => {
+                errors.push(e);
+                None
+            }
+        }
+    }
+
+    /// The name of this category, without the leading underscore, eg 'refine'
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The names of the columns of this category, without the category prefix, eg 'ls_d_res_high'
+    pub fn columns(&self) -> &[&'a str] {
+        &self.columns
+    }
+
+    /// The number of rows in this category, this is 1 if it is not given as a loop
+    pub fn row_count(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Get the value of the given column in the given row, if the column and row exist
+    pub fn value(&self, row: usize, column: &str) -> Option<&'a Value> {
+        let index = self.columns.iter().position(|c| *c == column)?;
+        self.rows.get(row).map(|r| r[index])
+    }
+
+    /// Get the value of the given column in the given row using the given function to interpret it.
+    fn typed<T>(&self, row: usize, column: &str, get: GetValue<T>) -> Result<Option<T>, PDBError> {
+        self.value(row, column).map_or(Ok(None), |value| {
+            let context = Context::show(format!("_{}.{} row: {}", self.name, column, row + 1));
+            get(value, &context, Some(column))
+        })
+    }
+
+    /// Get the value of the given column in the given row as text. Numbers are formatted as text.
+    /// It returns `None` if the column or row does not exist or if the value is missing ('.' or '?').
+    /// # Errors
+    /// This does not fail, it returns a result to be consistent with the other getters.
+    pub fn get_text(&self, row: usize, column: &str) -> Result<Option<String>, PDBError> {
+        self.typed(row, column, get_text)
+    }
+
+    /// Get the value of the given column in the given row as a number.
+    /// It returns `None` if the column or row does not exist or if the value is missing ('.' or '?').
+    /// # Errors
+    /// It fails if the value is not a number.
+    pub fn get_f64(&self, row: usize, column: &str) -> Result<Option<f64>, PDBError> {
+        self.typed(row, column, get_f64)
+    }
+
+    /// Get the value of the given column in the given row as an unsigned integer.
+    /// It returns `None` if the column or row does not exist or if the value is missing ('.' or '?').
+    /// # Errors
+    /// It fails if the value is not an unsigned integer.
+    pub fn get_usize(&self, row: usize, column: &str) -> Result<Option<usize>, PDBError> {
+        self.typed(row, column, get_usize)
+    }
+
+    /// Get the value of the given column in the given row as an integer.
+    /// It returns `None` if the column or row does not exist or if the value is missing ('.' or '?').
+    /// # Errors
+    /// It fails if the value is not an integer.
+    pub fn get_isize(&self, row: usize, column: &str) -> Result<Option<isize>, PDBError> {
+        self.typed(row, column, get_isize)
+    }
+}
+
+/// Get the Textual content of the value, if available
+pub(crate) fn get_text(
+    value: &Value,
+    _context: &Context,
+    _column: Option<&str>,
+) -> Result<Option<String>, PDBError> {
+    match value {
+        Value::Text(t) | Value::Numeric(t) | Value::NumericWithUncertainty(t) => {
+            Ok(Some(t.clone()))
+        }
+        Value::Inapplicable => Ok(None),
+        Value::Unknown => Ok(None),
+    }
+}
+
+/// Get the Numeric content of the value, if available, it also fails on NumericWithUncertainty.
+/// The number is parsed from its text here.
+pub(crate) fn get_f64(
+    value: &Value,
+    context: &Context,
+    column: Option<&str>,
+) -> Result<Option<f64>, PDBError> {
+    match value {
+        Value::Numeric(text) if text.parse::<f64>().is_ok() => Ok(text.parse().ok()),
+        Value::Inapplicable => Ok(None),
+        Value::Unknown => Ok(None),
+        _ => Err(PDBError::new(
+            ErrorLevel::InvalidatingError,
+            "Not a number",
+            column.map_or(String::new(), |v| {
+                format!("The '{v}' column should contain a number.")
+            }),
+            context.clone(),
+        )),
+    }
+}
+
+/// Get the Numeric content of the value, if available, as a usize
+pub(crate) fn get_usize(
+    value: &Value,
+    context: &Context,
+    column: Option<&str>,
+) -> Result<Option<usize>, PDBError> {
+    flatten_result(get_f64(value, context, column).map(|result| {
+        if let Some(num) = result {
+            #[allow(
+                clippy::cast_precision_loss,
+                clippy::cast_possible_truncation,
+                clippy::cast_sign_loss,
+                clippy::float_cmp
+            )]
+            if (0.0..usize::MAX as f64).contains(&num) && num.trunc() == num {
+                Ok(Some(num as usize))
+            } else {
+                Err(PDBError::new(
+                    ErrorLevel::InvalidatingError,
+                    "Not an unsigned integer",
+                    column.map_or(String::new(), |v| {
+                        format!("The '{v}' column should contain an unsigned integer.")
+                    }),
+                    context.clone(),
+                ))
+            }
+        } else {
+            Ok(None)
+        }
+    }))
+}
+
+/// Get the Numeric content of the value, if available, as an isize
+pub(crate) fn get_isize(
+    value: &Value,
+    context: &Context,
+    column: Option<&str>,
+) -> Result<Option<isize>, PDBError> {
+    flatten_result(get_f64(value, context, column).map(|result| {
+        if let Some(num) = result {
+            #[allow(
+                clippy::cast_precision_loss,
+                clippy::cast_possible_truncation,
+                clippy::float_cmp
+            )]
+            if (isize::MIN as f64..isize::MAX as f64).contains(&num) && num.trunc() == num {
+                Ok(Some(num as isize))
+            } else {
+                Err(PDBError::new(
+                    ErrorLevel::InvalidatingError,
+                    "Not an integer",
+                    column.map_or(String::new(), |v| {
+                        format!("The '{v}' column should a singed integer.")
+                    }),
+                    context.clone(),
+                ))
+            }
+        } else {
+            Ok(None)
+        }
+    }))
+}
+
+/// Flatten a Result of a Result with the same error type (#70142 is still unstable)
+fn flatten_result<T, E>(value: Result<Result<T, E>, E>) -> Result<T, E> {
+    match value {
+        Ok(Ok(t)) => Ok(t),
+        Ok(Err(e)) => Err(e),
+        Err(e) => Err(e),
+    }
+}
@@ -0,0 +1,104 @@
+use super::*;
+use crate::error::*;
+
+/// A CIF document, consisting of any number of data blocks. This gives access to all data in a
+/// CIF file, including the categories that are not used to build a [`crate::PDB`]. A document
+/// can be edited and written back to a valid CIF file.
+///
+/// ```rust
+/// use pdbtbx::cif::*;
+/// let mut document = Document::read("example-pdbs/1ubq.cif").unwrap();
+/// let block = &mut document.blocks[0];
+/// block.set_value("_refine.ls_d_res_high", 1.7);
+/// block.remove_category("_atom_site");
+/// let text = document.to_string();
+/// let reread = Document::parse(&text).unwrap();
+/// assert_eq!(reread.blocks[0].value("refine.ls_d_res_high"), Some(&Value::from(1.7)));
+/// assert!(reread.blocks[0].category("atom_site").is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Document {
+    /// The data blocks, in the order of the file
+    pub blocks: Vec<DataBlock>,
+}
+
+impl Document {
+    /// Create a new empty document
+    #[must_use]
+    pub fn new() -> Self {
+        Document::default()
+    }
+
+    /// Parse the given text as a CIF document.
+    /// # Errors
+    /// It fails if the text is not valid CIF, the error is tagged with the name of the data block
+    /// it was found in.
+    pub fn parse(text: &str) -> Result<Self, PDBError> {
+        lex_cif_document(text).map(|blocks| Document { blocks })
+    }
+
+    /// Decode the given bytes as a BinaryCIF document. Categories with a single row are given as
+    /// single data items and strings that look like numbers are read as numbers, so the result
+    /// is the same as reading the equivalent text CIF file.
+    /// # Errors
+    /// It fails if the bytes are not valid BinaryCIF.
+    pub fn parse_binary(bytes: &[u8]) -> Result<Self, PDBError> {
+        decode_bcif(bytes).map(|blocks| Document { blocks })
+    }
+
+    /// Encode this document as BinaryCIF. Save frames cannot be given in BinaryCIF, so these are
+    /// left out.
+    pub fn to_binary(&self) -> Vec<u8> {
+        encode_bcif(&self.blocks)
+    }
+
+    /// Read the CIF document at the given path. To read compressed files decompress the text and
+    /// use [`Document::parse`].
+    /// # Errors
+    /// It fails if the file could not be read or is not valid CIF.
+    pub fn read(path: impl AsRef<str>) -> Result<Self, PDBError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|_| {
+            PDBError::new(
+                ErrorLevel::BreakingError,
+                "Could not open file",
+                "Could not open the given file, make sure it exists and you have the correct permissions",
+                Context::show(path),
+            )
+        })?;
+        Document::parse(&text)
+    }
+
+    /// Save this document as a CIF file at the given path
+    /// # Errors
+    /// It fails if the file could not be written.
+    pub fn save(&self, path: impl AsRef<str>) -> Result<(), PDBError> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_string()).map_err(|_| {
+            PDBError::new(
+                ErrorLevel::BreakingError,
+                "Could not open file",
+                "Could not open the file for writing, make sure you have permission for this file and no other program is currently using it.",
+                Context::show(path),
+            )
+        })
+    }
+
+    /// Get the data block with the given name (without 'data_')
+    pub fn block(&self, name: &str) -> Option<&DataBlock> {
+        self.blocks.iter().find(|b| b.name == name)
+    }
+
+    /// Get the data block with the given name (without 'data_') mutably
+    pub fn block_mut(&mut self, name: &str) -> Option<&mut DataBlock> {
+        self.blocks.iter_mut().find(|b| b.name == name)
+    }
+}
+
+impl From<DataBlock> for Document {
+    fn from(block: DataBlock) -> Self {
+        Document {
+            blocks: vec![block],
+        }
+    }
+}
@@ -0,0 +1,329 @@
+use super::*;
+use crate::error::*;
+
+/// A top level data block for a CIF file. The names of all items are stored without the
+/// leading underscore, eg 'refine.ls_d_res_high'.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataBlock {
+    /// The name
+    pub name: String,
+    /// The Items
+    pub items: Vec<Item>,
+}
+
+/// An Item in a CIF file
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Item {
+    /// A data item
+    DataItem(DataItem),
+    /// A saveframe
+    SaveFrame(SaveFrame),
+}
+
+/// A save frame in a CIF file
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveFrame {
+    /// The name
+    pub name: String,
+    /// The Data Items
+    pub items: Vec<DataItem>,
+}
+
+/// A data item, either a Single data item or a Loop
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataItem {
+    /// A Single data item
+    Single(Single),
+    /// A Loop
+    Loop(Loop),
+}
+
+/// A single data item, consisting of a tag with a value
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Single {
+    /// The Tag or Name
+    pub name: String,
+    /// The value
+    pub content: Value,
+}
+
+/// A loop consisting of a header with tags and a body with values
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Loop {
+    /// The header with the names for the columns
+    pub header: Vec<String>,
+    /// The data itself, the length of each inner vec (can be seen as a row) should be equal to the length of the header
+    pub data: Vec<Vec<Value>>,
+}
+
+/// A value for a CIF record. Numbers keep the text they were read from, so their original
+/// formatting (eg trailing zeros) is retained when the value is written again. They are only
+/// parsed when requested as a number, eg with [`Category::get_f64`].
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// A value that is inapplicable
+    Inapplicable,
+    /// A value that is unknown
+    Unknown,
+    /// A numeric value, as the text of the number eg '1.50' or '1E5'
+    Numeric(String),
+    /// A numeric value with a set uncertainty, as the text 'number(uncertainty)' eg '42.0(9)'
+    NumericWithUncertainty(String),
+    /// A textual value, possibly containing whitespace and newlines
+    Text(String),
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Numeric(value.to_string())
+    }
+}
+
+impl From<isize> for Value {
+    fn from(value: isize) -> Self {
+        Value::Numeric(value.to_string())
+    }
+}
+
+impl From<usize> for Value {
+    fn from(value: usize) -> Self {
+        Value::Numeric(value.to_string())
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::Text(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::Text(value)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    /// A missing value is written as unknown ('?')
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::Unknown, Into::into)
+    }
+}
+
+impl DataItem {
+    /// Get the name of the category of this data item, eg 'refine' for '_refine.ls_d_res_high'
+    pub fn category(&self) -> Option<&str> {
+        let tag = match self {
+            DataItem::Single(single) => &single.name,
+            DataItem::Loop(l) => l.header.first()?,
+        };
+        tag.split('.').next()
+    }
+}
+
+/// Remove the leading underscore of a tag or category name, if present
+fn trim_underscore(name: &str) -> &str {
+    name.strip_prefix('_').unwrap_or(name)
+}
+
+/// Determine if the given tag is part of the given category
+pub(super) fn in_category(tag: &str, category: &str) -> bool {
+    tag.len() > category.len() + 1
+        && tag.starts_with(category)
+        && tag[category.len()..].starts_with('.')
+}
+
+impl DataBlock {
+    /// Create a new empty data block with the given name (without 'data_')
+    #[must_use]
+    pub fn new(name: impl Into<String>) -> Self {
+        DataBlock {
+            name: name.into(),
+            items: Vec::new(),
+        }
+    }
+
+    /// Get the category with the given name, eg '_refine' or 'refine'. It returns `None` if no
+    /// data item in this block is part of this category.
+    pub fn category(&self, name: &str) -> Option<Category<'_>> {
+        Category::find(self, trim_underscore(name))
+    }
+
+    /// Get the names of all categories in this block, in the order they are first found
+    pub fn category_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for item in &self.items {
+            if let Item::DataItem(item) = item {
+                if let Some(name) = item.category() {
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                }
+            }
+        }
+        names
+    }
+
+    /// Get the value of the given tag, eg '_entry.id'. If the tag is a column in a loop the value
+    /// of the first row is given.
+    pub fn value(&self, tag: &str) -> Option<&Value> {
+        let tag = trim_underscore(tag);
+        self.items.iter().find_map(|item| match item {
+            Item::DataItem(DataItem::Single(single)) if single.name == tag => Some(&single.content),
+            Item::DataItem(DataItem::Loop(l)) => l
+                .header
+                .iter()
+                .position(|h| h == tag)
+                .and_then(|index| l.data.first().map(|row| &row[index])),
+            _ => None,
+        })
+    }
+
+    /// Set the value of the given tag, eg '_entry.id'. If the tag is already present as a single
+    /// data item, or as a column in a loop with only one row, the value is replaced. Otherwise a
+    /// new single data item is added to the end of the block.
+    pub fn set_value(&mut self, tag: &str, value: impl Into<Value>) {
+        let tag = trim_underscore(tag);
+        let value = value.into();
+        for item in &mut self.items {
+            match item {
+                Item::DataItem(DataItem::Single(single)) if single.name == tag => {
+                    single.content = value;
+                    return;
+                }
+                Item::DataItem(DataItem::Loop(l)) if l.data.len() == 1 => {
+                    if let Some(index) = l.header.iter().position(|h| h == tag) {
+                        l.data[0][index] = value;
+                        return;
+                    }
+                }
+                _ => (),
+            }
+        }
+        self.items.push(Item::DataItem(DataItem::Single(Single {
+            name: tag.to_string(),
+            content: value,
+        })));
+    }
+
+    /// Remove all data items of the given category, eg '_refine'. It returns if any item was removed.
+    pub fn remove_category(&mut self, name: &str) -> bool {
+        let name = trim_underscore(name);
+        let before = self.items.len();
+        self.items.retain(|item| match item {
+            Item::DataItem(DataItem::Single(single)) => !in_category(&single.name, name),
+            Item::DataItem(DataItem::Loop(l)) => {
+                !l.header.first().map_or(false, |h| in_category(h, name))
+            }
+            Item::SaveFrame(_) => true,
+        });
+        before != self.items.len()
+    }
+
+    /// Set the content of the given category, eg '_refine', replacing any data items of this
+    /// category that were present. The columns are given without the category prefix. A category
+    /// with a single row is written as single data items, otherwise it is written as a loop.
+    /// A replaced category keeps its position in the block, a new category is added to the end.
+    /// # Errors
+    /// It fails if there are no columns, or if a row does not have the same length as the columns.
+    pub fn set_category(
+        &mut self,
+        name: &str,
+        columns: &[&str],
+        rows: Vec<Vec<Value>>,
+    ) -> Result<(), PDBError> {
+        let name = trim_underscore(name);
+        if columns.is_empty() {
+            return Err(PDBError::new(
+                ErrorLevel::BreakingError,
+                "Empty category",
+                format!("The category '_{name}' should have at least one column."),
+                Context::None,
+            ));
+        }
+        if let Some(index) = rows.iter().position(|row| row.len() != columns.len()) {
+            return Err(PDBError::new(
+                ErrorLevel::BreakingError,
+                "Row with wrong length",
+                format!(
+                    "Row {} of the category '_{name}' has {} values, while there are {} columns.",
+                    index + 1,
+                    rows[index].len(),
+                    columns.len()
+                ),
+                Context::None,
+            ));
+        }
+        let position = self.items.iter().position(|item| match item {
+            Item::DataItem(DataItem::Single(single)) => in_category(&single.name, name),
+            Item::DataItem(DataItem::Loop(l)) => {
+                l.header.first().map_or(false, |h| in_category(h, name))
+            }
+            Item::SaveFrame(_) => false,
+        });
+        self.remove_category(name);
+        let header = columns.iter().map(|c| format!("{name}.{c}"));
+        let items: Vec<Item> = if rows.len() == 1 {
+            header
+                .zip(rows.into_iter().flatten())
+                .map(|(name, content)| Item::DataItem(DataItem::Single(Single { name, content })))
+                .collect()
+        } else {
+            vec![Item::DataItem(DataItem::Loop(Loop {
+                header: header.collect(),
+                data: rows,
+            }))]
+        };
+        let position = position.unwrap_or(self.items.len());
+        self.items.splice(position..position, items);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_block() {
+        let mut block = DataBlock::new("test");
+        block.set_value("_entry.id", "TEST");
+        block.set_value("cell.length_a", 10.0);
+        assert_eq!(block.value("entry.id"), Some(&Value::from("TEST")));
+        block.set_value("_entry.id", "OTHER");
+        assert_eq!(block.items.len(), 2);
+        assert_eq!(block.value("_entry.id"), Some(&Value::from("OTHER")));
+        block
+            .set_category(
+                "_atom_type",
+                &["symbol"],
+                vec![vec!["C".into()], vec!["N".into()]],
+            )
+            .unwrap();
+        assert_eq!(block.category_names(), ["entry", "cell", "atom_type"]);
+        assert_eq!(block.category("atom_type").unwrap().row_count(), 2);
+        block
+            .set_category(
+                "_entry",
+                &["id", "title"],
+                vec![vec!["A".into(), None::<f64>.into()]],
+            )
+            .unwrap();
+        assert_eq!(block.category_names(), ["entry", "cell", "atom_type"]);
+        assert_eq!(block.value("entry.title"), Some(&Value::Unknown));
+        assert!(block
+            .set_category("_cell", &["length_b"], vec![vec![]])
+            .is_err());
+        assert!(block.remove_category("_cell"));
+        assert!(!block.remove_category("_cell"));
+        assert_eq!(block.category_names(), ["entry", "atom_type"]);
+    }
+}
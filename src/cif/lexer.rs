@@ -1,9 +1,9 @@
 #![allow(clippy::unwrap_used)]
-use super::lexitem::*;
+use crate::cif::*;
 use crate::error::*;
 
-/// Parse/lex a CIF file into CIF intermediate structure
-pub fn lex_cif(text: &str) -> Result<DataBlock, PDBError> {
+/// Parse/lex a CIF file into CIF intermediate structure, only the first data block is parsed
+pub(crate) fn lex_cif(text: &str) -> Result<DataBlock, PDBError> {
     parse_main(&mut Position {
         text,
         line: 1,
@@ -11,6 +11,34 @@ pub fn lex_cif(text: &str) -> Result<DataBlock, PDBError> {
     })
 }
 
+/// Parse/lex a CIF file with any number of data blocks into CIF intermediate structures.
+/// Any error is tagged with the name of the data block it was found in.
+pub(crate) fn lex_cif_document(text: &str) -> Result<Vec<DataBlock>, PDBError> {
+    let mut input = Position {
+        text,
+        line: 1,
+        column: 1,
+    };
+    let mut blocks = Vec::new();
+    loop {
+        trim_comments_and_whitespace(&mut input);
+        if input.text.is_empty() && !blocks.is_empty() {
+            return Ok(blocks);
+        }
+        let mut name = input;
+        let name = start_with(&mut name, "data_").map(|()| parse_identifier(&mut name));
+        match parse_data_block(&mut input) {
+            Ok(block) => blocks.push(block),
+            Err(mut error) => {
+                if let Some(name) = name {
+                    error.add_context_note(format!("In data block '{name}'"));
+                }
+                return Err(error);
+            }
+        }
+    }
+}
+
 /// Parse a CIF file
 fn parse_main(input: &mut Position<'_>) -> Result<DataBlock, PDBError> {
     trim_comments_and_whitespace(input);
@@ -34,7 +62,8 @@ fn parse_data_block(input: &mut Position<'_>) -> Result<DataBlock, PDBError> {
     };
     loop {
         trim_comments_and_whitespace(input);
-        if input.text.is_empty() {
+        // The block ends at the end of the file or at the start of the next block
+        if input.text.is_empty() || start_with(&mut input.clone(), "data_").is_some() {
             return Ok(block);
         }
         let item = parse_data_item_or_save_frame(input)?;
@@ -197,26 +226,19 @@ fn parse_value(input: &mut Position<'_>) -> Result<Value, PDBError> {
 }
 
 /// Parse a numeric value from a string which is expected to be of non zero length and not containing whitespace
-fn parse_numeric(text: &str) -> Option<Value> {
+pub(super) fn parse_numeric(text: &str) -> Option<Value> {
     let mut chars_to_remove = 0;
     let first_char = text.chars().next().unwrap();
     // Parse a possible sign
-    let mut minus = false;
-    if first_char == '-' {
-        minus = true;
-        chars_to_remove += 1;
-    } else if first_char == '+' {
+    if first_char == '-' || first_char == '+' {
         chars_to_remove += 1;
     }
 
     // Parse the integer part
     let mut integer_set = false;
-    let mut value = 0;
     for c in text.chars().skip(chars_to_remove) {
-        if let Some(num) = c.to_digit(10) {
+        if c.is_ascii_digit() {
             integer_set = true;
-            value *= 10;
-            value += num;
             chars_to_remove += 1;
         } else {
             break;
@@ -225,15 +247,11 @@ fn parse_numeric(text: &str) -> Option<Value> {
 
     // Now take the decimal part
     let mut decimal_set = false;
-    let mut decimal = 0.0;
     if text.len() > chars_to_remove && text.chars().nth(chars_to_remove).unwrap() == '.' {
         chars_to_remove += 1;
-        let mut power: f64 = 1.0;
         for c in text.chars().skip(chars_to_remove) {
             if c.is_ascii_digit() {
                 decimal_set = true;
-                power *= 10.0;
-                decimal += c.to_digit(10).unwrap() as f64 / power;
                 chars_to_remove += 1;
             } else {
                 break;
@@ -242,8 +260,6 @@ fn parse_numeric(text: &str) -> Option<Value> {
     }
 
     // Now take the exponent
-    let mut exponent_set = false;
-    let mut exponent = 0;
     if text.len() > chars_to_remove {
         let next_char = text.chars().nth(chars_to_remove).unwrap();
         if next_char == 'e' || next_char == 'E' {
@@ -253,45 +269,34 @@ fn parse_numeric(text: &str) -> Option<Value> {
                 return None; // No number after the exponent
             }
             let exp_first_char = text.chars().nth(chars_to_remove).unwrap();
-            let mut exp_minus = false;
-            if exp_first_char == '-' {
-                exp_minus = true;
-                chars_to_remove += 1;
-            } else if exp_first_char == '+' {
+            if exp_first_char == '-' || exp_first_char == '+' {
                 chars_to_remove += 1;
             }
 
             // Parse the integer part
-            let mut exp_value = 0;
+            let mut exponent_set = false;
             for c in text.chars().skip(chars_to_remove) {
-                if let Some(num) = c.to_digit(10) {
+                if c.is_ascii_digit() {
                     exponent_set = true;
-                    exp_value *= 10;
-                    exp_value += num;
                     chars_to_remove += 1;
                 } else {
                     break;
                 }
             }
-            #[allow(clippy::cast_possible_wrap)]
-            if exp_minus {
-                exponent = -(exp_value as i32);
-            } else {
-                exponent = exp_value as i32;
+            if !exponent_set {
+                return None; // No number after the exponent
             }
         }
     }
 
     // Take the uncertainty
+    let number_end = chars_to_remove;
     let mut uncertainty_set = false;
-    let mut uncertainty = 0;
     if text.len() > chars_to_remove && text.chars().nth(chars_to_remove).unwrap() == '(' {
         uncertainty_set = true;
         chars_to_remove += 1;
         for c in text.chars().skip(chars_to_remove) {
-            if let Some(num) = c.to_digit(10) {
-                uncertainty *= 10;
-                uncertainty += num;
+            if c.is_ascii_digit() {
                 chars_to_remove += 1;
             } else {
                 break;
@@ -306,17 +311,12 @@ fn parse_numeric(text: &str) -> Option<Value> {
     if (!integer_set && !decimal_set) || text.len() != chars_to_remove {
         None
     } else {
-        let mut number = value as f64 + decimal;
-        if minus {
-            number *= -1.0;
-        }
-        if exponent_set {
-            number *= 10_f64.powi(exponent);
-        }
+        // Check with the standard parser that the number itself is valid, the text is kept
+        text[..number_end].parse::<f64>().ok()?;
         if uncertainty_set {
-            Some(Value::NumericWithUncertainty(number, uncertainty))
+            Some(Value::NumericWithUncertainty(text.to_string()))
         } else {
-            Some(Value::Numeric(number))
+            Some(Value::Numeric(text.to_string()))
         }
     }
 }
@@ -538,6 +538,7 @@ mod tests {
     macro_rules! assert_numeric {
         ($res:expr, $exp:expr) => {
             if let Some(Value::Numeric(n)) = $res {
+                let n: f64 = n.parse().unwrap();
                 if !close(n, $exp) {
                     panic!("assertion failed: {} is not close to {}", n, $exp);
                 }
@@ -546,7 +547,9 @@ mod tests {
             }
         };
         ($res:expr, $exp:expr, $un:expr) => {
-            if let Some(Value::NumericWithUncertainty(n, u)) = $res {
+            if let Some(Value::NumericWithUncertainty(text)) = $res {
+                let (n, u) = text.trim_end_matches(')').split_once('(').unwrap();
+                let (n, u): (f64, u32) = (n.parse().unwrap(), u.parse().unwrap());
                 if !close(n, $exp) {
                     panic!("assertion failed: {} is not close to {}", n, $exp);
                 }
@@ -768,6 +771,14 @@ mod tests {
         assert_numeric!(res, 0.420 * 10000000000.0);
     }
 
+    #[test]
+    fn parse_numeric_correctly_rounded() {
+        assert_eq!(parse_numeric("0.176"), Some(Value::from(0.176)));
+        assert_eq!(parse_numeric("-0.97948"), Some(Value::from(-0.97948)));
+        assert_eq!(parse_numeric("1e-"), None);
+        assert_eq!(parse_numeric("1e"), None);
+    }
+
     #[test]
     fn parse_numeric_float_no_integer_positive_exp() {
         let res = parse_numeric(".42e+10");
@@ -946,7 +957,7 @@ mod tests {
             column: 1,
         };
         let res = parse_value(&mut pos);
-        assert_eq!(res, Ok(Value::Numeric(56.8)));
+        assert_eq!(res, Ok(Value::from(56.8)));
         assert_eq!(pos.text, " hello");
         assert_eq!(pos.line, 1);
         assert_eq!(pos.column, 5);
@@ -1054,7 +1065,7 @@ mod tests {
             res,
             Ok(DataItem::Single(Single {
                 name: "tag".to_string(),
-                content: Value::Numeric(42.3)
+                content: Value::from(42.3)
             }))
         );
         assert_eq!(pos.text, "");
@@ -1074,7 +1085,7 @@ mod tests {
             res,
             Ok(DataItem::Single(Single {
                 name: "tag".to_string(),
-                content: Value::Numeric(0.16)
+                content: Value::Numeric("+.16".to_string())
             }))
         );
         assert_eq!(pos.text, "");
@@ -1153,15 +1164,15 @@ mod tests {
                     "last".to_string()
                 ],
                 data: vec![
-                    vec![Value::Inapplicable, Value::Numeric(23.2), Value::Unknown,],
+                    vec![Value::Inapplicable, Value::from(23.2), Value::Unknown,],
                     vec![
                         Value::Text("Hello".to_string()),
-                        Value::Numeric(25.9),
+                        Value::from(25.9),
                         Value::Unknown,
                     ],
                     vec![
                         Value::Text("Hey".to_string()),
-                        Value::Numeric(30.3),
+                        Value::from(30.3),
                         Value::Text("N".to_string())
                     ]
                 ]
@@ -1283,6 +1294,22 @@ mod tests {
         )
     }
 
+    #[test]
+    fn lex_cif_document_test() {
+        let text = "data_1UBQ\n_entry.id   1UBQ\n#\ndata_3PDZ\n_entry.id   3PDZ\n";
+        let blocks = lex_cif_document(text).unwrap();
+        assert_eq!(
+            blocks.iter().map(|b| b.name.as_str()).collect::<Vec<_>>(),
+            ["1UBQ", "3PDZ"]
+        );
+        assert_eq!(blocks[1].items.len(), 1);
+        assert_eq!(lex_cif(text).unwrap(), blocks.into_iter().next().unwrap());
+        let error =
+            lex_cif_document("data_A\n_entry.id A\ndata_B\nloop_\n_a.b\n_a.c\n1\n").unwrap_err();
+        assert!(format!("{error}").contains("In data block 'B'"));
+        assert!(lex_cif_document("").is_err());
+    }
+
     fn close(a: f64, b: f64) -> bool {
         let dif = a / b;
         (1.0 - dif) > -0.000000000000001 && (dif - 1.0) < 0.000000000000001
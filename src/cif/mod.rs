@@ -0,0 +1,36 @@
+//! A generic model of CIF documents, giving access to all data in a CIF (or mmCIF) file. This is
+//! the intermediate structure that is used to read mmCIF files into a [`crate::PDB`], but it can
+//! also be used on its own to read categories that are not interpreted by this crate, to edit
+//! files, or to write new CIF files.
+//!
+//! ```rust
+//! use pdbtbx::cif::*;
+//! let document = Document::read("example-pdbs/1ubq.cif").unwrap();
+//! let block = document.block("1UBQ").unwrap();
+//! let refine = block.category("_refine").unwrap();
+//! assert_eq!(refine.get_f64(0, "ls_d_res_high").unwrap(), Some(1.8));
+//! let entity_poly_seq = block.category("_entity_poly_seq").unwrap();
+//! assert_eq!(entity_poly_seq.row_count(), 76);
+//! assert_eq!(entity_poly_seq.get_text(0, "mon_id").unwrap().as_deref(), Some("MET"));
+//! ```
+
+/// Read and write BinaryCIF files
+mod binary;
+/// A view on a single category
+mod category;
+/// The document, the top level structure
+mod document;
+/// The items of a CIF file
+mod items;
+/// Parse CIF files into the intermediate structure
+mod lexer;
+/// Write the intermediate structure as CIF
+mod write;
+
+pub(crate) use binary::{decode_bcif, encode_bcif};
+pub use category::Category;
+pub(crate) use category::{get_f64, get_isize, get_text, get_usize, GetValue};
+pub use document::Document;
+pub use items::*;
+pub(crate) use lexer::{lex_cif, lex_cif_document};
+pub(crate) use write::cif_text;
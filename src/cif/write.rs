@@ -0,0 +1,143 @@
+use super::lexer::parse_numeric;
+use super::*;
+use std::fmt;
+
+/// Format the given text as a CIF value, quoting it if needed. Multiline text (or text that
+/// contains both kinds of quotes) is written as a text field delimited by `<eol>;`, which starts
+/// on a new line. Empty text is kept empty, so it will be written as '?' in mmCIF tables.
+pub(crate) fn cif_text(text: &str) -> String {
+    let reserved = ["data_", "loop_", "save_", "global_", "stop_"];
+    let lowercase = text.to_ascii_lowercase();
+    if text.contains('\n') || (text.contains('\'') && text.contains('"')) {
+        if text.ends_with('\n') {
+            format!("\n;{text};")
+        } else {
+            format!("\n;{text}\n;")
+        }
+    } else if text == "?"
+        || text == "."
+        || text.contains(char::is_whitespace)
+        || text.starts_with(['_', '#', '$', '\'', '"', '[', ']', ';'])
+        || reserved.iter().any(|r| lowercase.starts_with(r))
+    {
+        if text.contains('\'') {
+            format!("\"{text}\"")
+        } else {
+            format!("'{text}'")
+        }
+    } else {
+        text.to_string()
+    }
+}
+
+impl fmt::Display for Value {
+    /// Write the value as it would be given in a CIF file. Text that could be read as a number is
+    /// quoted, so that it is read back as text.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Inapplicable => write!(f, "."),
+            Value::Unknown => write!(f, "?"),
+            Value::Numeric(n) | Value::NumericWithUncertainty(n) => write!(f, "{n}"),
+            Value::Text(t) if t.is_empty() => write!(f, "''"),
+            Value::Text(t) if parse_numeric(t).is_some() => write!(f, "'{t}'"),
+            Value::Text(t) => write!(f, "{}", cif_text(t)),
+        }
+    }
+}
+
+impl fmt::Display for DataItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataItem::Single(single) => writeln!(f, "_{} {}", single.name, single.content),
+            // A loop without any rows is not valid CIF, and does not contain any information
+            DataItem::Loop(l) if l.data.is_empty() => Ok(()),
+            DataItem::Loop(l) => {
+                writeln!(f, "loop_")?;
+                for name in &l.header {
+                    writeln!(f, "_{name}")?;
+                }
+                for row in &l.data {
+                    let row: Vec<String> = row.iter().map(ToString::to_string).collect();
+                    writeln!(f, "{}", row.join(" "))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for DataBlock {
+    /// Write the data block as valid CIF, with a '#' line between the data items
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "data_{}", self.name)?;
+        for item in &self.items {
+            writeln!(f, "#")?;
+            match item {
+                Item::DataItem(item) => write!(f, "{item}")?,
+                Item::SaveFrame(frame) => {
+                    writeln!(f, "save_{}", frame.name)?;
+                    for item in &frame.items {
+                        write!(f, "{item}")?;
+                    }
+                    writeln!(f, "save_")?;
+                }
+            }
+        }
+        writeln!(f, "#")
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for block in &self.blocks {
+            write!(f, "{block}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_values() {
+        let values = [
+            Value::Unknown,
+            Value::Inapplicable,
+            Value::from(1.5),
+            Value::NumericWithUncertainty("-2.50(3)".to_string()),
+            Value::Numeric("1E5".to_string()),
+            Value::Numeric("007".to_string()),
+            Value::from(""),
+            Value::from("1.0"),
+            Value::from("?"),
+            Value::from("data_x"),
+            Value::from("with space"),
+            Value::from("it's"),
+            Value::from("both ' and \""),
+            Value::from("multi\nline\n"),
+            Value::from("multi\nline"),
+        ];
+        let mut block = DataBlock::new("values");
+        let columns: Vec<String> = (0..values.len()).map(|i| format!("v{i}")).collect();
+        let columns: Vec<&str> = columns.iter().map(String::as_str).collect();
+        block
+            .set_category("test", &columns, vec![values.to_vec(), values.to_vec()])
+            .unwrap();
+        let document = Document::parse(&Document::from(block).to_string()).unwrap();
+        let category = document.blocks[0].category("test").unwrap();
+        assert_eq!(category.row_count(), 2);
+        for (index, value) in values.iter().enumerate() {
+            let expected = match value {
+                // A text field always ends with a newline
+                Value::Text(t) if cif_text(t).starts_with("\n;") && !t.ends_with('\n') => {
+                    Value::from(format!("{t}\n"))
+                }
+                other => other.clone(),
+            };
+            assert_eq!(category.value(1, columns[index]), Some(&expected));
+        }
+    }
+}
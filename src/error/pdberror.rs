@@ -64,6 +64,17 @@ impl PDBError {
     pub const fn context(&self) -> &Context {
         &self.context
     }
+
+    /// Add a note to the context of this error, eg the data block of a CIF file it was found in.
+    /// The context becomes a [`Context::Multiple`] with the note attached to the original context.
+    pub(crate) fn add_context_note(&mut self, note: impl std::string::ToString) {
+        self.context = match std::mem::replace(&mut self.context, Context::None) {
+            Context::None => Context::show(note),
+            context => Context::Multiple {
+                contexts: vec![(Some(note.to_string()), context)],
+            },
+        };
+    }
 }
 
 impl fmt::Debug for PDBError {
@@ -159,6 +170,24 @@ mod tests {
         assert_eq!(pos2.column, 13);
     }
 
+    #[test]
+    fn context_note() {
+        let mut a = PDBError::new(
+            ErrorLevel::StrictWarning,
+            "test",
+            "test",
+            Context::full_line(1, "testing line"),
+        );
+        a.add_context_note("In data block 'A'");
+        assert_eq!(
+            format!("{a}"),
+            "StrictWarning: test\n  ╷\n1 │ testing line\n  ╰In data block 'A'\ntest\n"
+        );
+        let mut b = PDBError::new(ErrorLevel::StrictWarning, "test", "test", Context::none());
+        b.add_context_note("In data block 'A'");
+        assert_eq!(b.context(), &Context::show("In data block 'A'"));
+    }
+
     #[test]
     fn ordering_and_equality() {
         let a = PDBError::new(ErrorLevel::GeneralWarning, "test", "test", Context::none());
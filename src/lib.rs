@@ -48,6 +48,69 @@
 //! implementations are gated behind the `rayon` [feature](https://doc.rust-lang.org/cargo/reference/features.html)
 //! which is enabled by default.
 //!
+//! ## Generic CIF files
+//! The [`cif`] module gives access to the full content of any CIF file, including the categories
+//! that are not used to build a [`PDB`]. These documents can be edited and saved as well.
+//!
+//! ## BinaryCIF
+//! BinaryCIF files, as served by RCSB and PDBe, are read with [`Format::BinaryCif`] (or any file
+//! ending in `.bcif`) and written with [`save_bcif`]. They are decoded into the same intermediate
+//! structure as mmCIF files, so they contain exactly the same information.
+//!
+//! ## MMTF
+//! MMTF files are read with [`Format::Mmtf`] (or any file ending in `.mmtf`) and written with
+//! [`save_mmtf`]. The groups, chains and models are converted to the hierarchy of the [`PDB`],
+//! and the bonds within and between groups are added to its bonds. Only the most common metadata
+//! (title, deposition date, experimental methods, resolution, R factors, unit cell, space group
+//! and entities) is kept, as MMTF does not store more.
+//!
+//! ## PQR
+//! PQR files, as used by electrostatics software like APBS, are read with [`Format::Pqr`] (or any
+//! file ending in `.pqr`) and written with [`save_pqr`]. The partial charge and radius of every
+//! atom are available with [`Atom::partial_charge`] and [`Atom::radius`].
+//!
+//! ## GROMACS
+//! GROMACS files are read with [`Format::Gro`] (or any file ending in `.gro`) and written with
+//! [`save_gro`]. Every frame becomes a model, the coordinates and velocities (see
+//! [`Atom::velocity`]) are converted from nm to Å, and the box vectors are converted to the
+//! [`UnitCell`]. As GROMACS files do not contain chains all atoms are placed in chain `A`.
+//!
+//! ## XYZ
+//! XYZ and extended XYZ files, as used by quantum chemistry software, are read with [`Format::Xyz`]
+//! (or any file ending in `.xyz` or `.extxyz`) and written with [`save_xyz`]. Every frame becomes
+//! a model. The `Lattice` of extended XYZ files is converted to the [`UnitCell`], and the atom
+//! name, residue, chain, charge, B factor, and occupancy columns in its `Properties` are read
+//! when given. Atoms without residue information are placed in residue `UNL` in chain `A`.
+//!
+//! ## SDF
+//! SDF and MOL files are read with [`Format::Sdf`] (or any file ending in `.sdf`, `.sd`, or
+//! `.mol`), every record becomes a model with a single residue. The bonds are added to the bonds
+//! of the [`PDB`] with their [`BondOrder`]. The residues of a record, like a docked pose, can be
+//! merged into another structure with [`PDB::add_ligand`]. Selected conformers are written with
+//! [`save_sdf`], eg `save_sdf(&pdb, Search::Single(Term::ConformerName("ATP".to_owned())), "atp.sdf", StrictnessLevel::Loose)`.
+//!
+//! ## MOL2
+//! Tripos MOL2 files are read with [`Format::Mol2`] (or any file ending in `.mol2`) and written
+//! with [`save_mol2`]. Every molecule becomes a model, with the substructures as residues and the
+//! bonds added to the bonds of the [`PDB`] with their [`BondOrder`]. The SYBYL atom types and
+//! partial charges are kept in [`Atom::atom_type`] and [`Atom::partial_charge`]. When saving,
+//! atoms without an atom type get a SYBYL atom type based on their element and bonds.
+//!
+//! ## PDBQT
+//! PDBQT files, as used by AutoDock and Vina, are read with [`Format::Pdbqt`] (or any file ending
+//! in `.pdbqt`) and written with [`save_pdbqt`]. The partial charges and AutoDock atom types are
+//! kept in [`Atom::partial_charge`] and [`Atom::atom_type`], and the `ROOT`, `BRANCH`, and
+//! `TORSDOF` records of flexible ligands as the [`TorsionTree`] of their model, together with the
+//! free text remarks like the `VINA RESULT` of every docked pose. When saving, atoms without an
+//! AutoDock atom type get one based on their element and bonds.
+//!
+//! ## Trajectories
+//! Molecular dynamics trajectories in the DCD, XTC, and TRR formats are read with [`Trajectory`],
+//! which uses a [`PDB`] as topology and reads the coordinates of the frames lazily. Every
+//! [`Frame`] contains the positions (and if available velocities and unit cell) of all atoms, and
+//! can be applied to the topology to run the same analyses on every frame of a simulation.
+//! Trajectories are written with [`TrajectoryWriter`].
+//!
 //! ## Serialization
 //! Enable the `serde` feature for [Serde](https://crates.io/crates/serde) support.
 //!
@@ -120,8 +183,12 @@ println!("There are {} backbone atoms within 3.5Aͦ of the atom at index 42", to
 #![allow(clippy::upper_case_acronyms)] // Allow PDB (and derived) names to be used
 #![cfg_attr(feature = "unstable-doc-cfg", feature(doc_cfg))]
 
+/// To read, edit, and write generic CIF documents
+pub mod cif;
 /// To save and display errors
 mod error;
+/// MessagePack encoding and decoding for the binary file formats
+mod msgpack;
 /// To open PDB files
 mod read;
 /// Reference tables for constants
@@ -131,6 +198,8 @@ mod save;
 /// To determine the level of scrutiny that a step should display
 mod strictness_level;
 mod structs;
+/// To read and write molecular dynamics trajectories
+mod trajectory;
 /// To handle transformations
 mod transformation;
 /// To validate certain invariants of PDB files
@@ -144,6 +213,7 @@ pub use read::*;
 pub use save::*;
 pub use strictness_level::StrictnessLevel;
 pub use structs::*;
+pub use trajectory::{Frame, Trajectory, TrajectoryFormat, TrajectoryWriter};
 pub use transformation::*;
 pub use validate::{validate, validate_pdb};
 
@@ -0,0 +1,421 @@
+use crate::error::*;
+
+/// A MessagePack value, as used by the binary file formats (BinaryCIF and MMTF).
+/// Extension types are not used by these formats and are not supported.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum MsgPack {
+    /// The nil value
+    Nil,
+    /// A boolean
+    Bool(bool),
+    /// An integer, all integers that are used by the binary formats fit in an i64
+    Int(i64),
+    /// A floating point number, single precision numbers are widened
+    Float(f64),
+    /// A UTF-8 string
+    Str(String),
+    /// A binary blob
+    Bin(Vec<u8>),
+    /// An array of values
+    Array(Vec<MsgPack>),
+    /// A map of values, in the order of the file
+    Map(Vec<(MsgPack, MsgPack)>),
+}
+
+impl MsgPack {
+    /// Get the value of the given key if this is a map with string keys
+    pub(crate) fn get(&self, key: &str) -> Option<&MsgPack> {
+        match self {
+            MsgPack::Map(map) => map.iter().find_map(|(k, v)| match k {
+                MsgPack::Str(k) if k == key => Some(v),
+                _ => None,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Get the content if this is a string
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            MsgPack::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Get the content if this is an integer, integral floats are accepted as well
+    #[allow(clippy::cast_possible_truncation, clippy::float_cmp)]
+    pub(crate) fn as_int(&self) -> Option<i64> {
+        match self {
+            MsgPack::Int(i) => Some(*i),
+            MsgPack::Float(f) if f.trunc() == *f => Some(*f as i64),
+            _ => None,
+        }
+    }
+
+    /// Get the content if this is a number
+    #[allow(clippy::cast_precision_loss)]
+    pub(crate) fn as_float(&self) -> Option<f64> {
+        match self {
+            MsgPack::Int(i) => Some(*i as f64),
+            MsgPack::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    /// Get the content if this is a boolean
+    pub(crate) const fn as_bool(&self) -> Option<bool> {
+        match self {
+            MsgPack::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Get the content if this is a binary blob
+    pub(crate) fn as_bin(&self) -> Option<&[u8]> {
+        match self {
+            MsgPack::Bin(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Get the content if this is an array
+    pub(crate) fn as_array(&self) -> Option<&[MsgPack]> {
+        match self {
+            MsgPack::Array(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    /// Create a map with string keys
+    pub(crate) fn map(items: Vec<(&str, MsgPack)>) -> Self {
+        MsgPack::Map(
+            items
+                .into_iter()
+                .map(|(k, v)| (MsgPack::Str(k.to_string()), v))
+                .collect(),
+        )
+    }
+}
+
+impl From<&str> for MsgPack {
+    fn from(value: &str) -> Self {
+        MsgPack::Str(value.to_string())
+    }
+}
+
+impl From<i64> for MsgPack {
+    fn from(value: i64) -> Self {
+        MsgPack::Int(value)
+    }
+}
+
+/// The maximal nesting depth of arrays and maps, deeper values are rejected instead of
+/// overflowing the stack
+const MAX_DEPTH: usize = 128;
+
+/// The reason a MessagePack value could not be decoded
+enum DecodeError {
+    /// The bytes are not valid MessagePack
+    Invalid,
+    /// The arrays and maps are nested deeper than [`MAX_DEPTH`]
+    TooDeep,
+}
+
+/// Decode a single MessagePack value from the given bytes.
+/// # Errors
+/// It fails if the bytes are not valid MessagePack, if arrays and maps are nested more than
+/// 128 levels deep, or if there are bytes left after the value.
+pub(crate) fn decode(bytes: &[u8]) -> Result<MsgPack, PDBError> {
+    let mut position = 0;
+    let value = decode_value(bytes, &mut position, 0).map_err(|error| match error {
+        DecodeError::Invalid => PDBError::new(
+            ErrorLevel::BreakingError,
+            "Invalid MessagePack",
+            format!("The data could not be decoded as MessagePack, the error occurred at byte {position}."),
+            Context::None,
+        ),
+        DecodeError::TooDeep => PDBError::new(
+            ErrorLevel::BreakingError,
+            "Invalid MessagePack",
+            format!("The data contains arrays or maps nested more than {MAX_DEPTH} levels deep, the error occurred at byte {position}."),
+            Context::None,
+        ),
+    })?;
+    if position == bytes.len() {
+        Ok(value)
+    } else {
+        Err(PDBError::new(
+            ErrorLevel::BreakingError,
+            "Invalid MessagePack",
+            format!(
+                "The data contains {} bytes after the end of the MessagePack value.",
+                bytes.len() - position
+            ),
+            Context::None,
+        ))
+    }
+}
+
+/// Take the given number of bytes, advancing the position
+fn take<'a>(bytes: &'a [u8], position: &mut usize, length: usize) -> Result<&'a [u8], DecodeError> {
+    let end = position.checked_add(length).ok_or(DecodeError::Invalid)?;
+    let slice = bytes.get(*position..end).ok_or(DecodeError::Invalid)?;
+    *position = end;
+    Ok(slice)
+}
+
+/// Take a big endian unsigned integer of the given number of bytes
+fn take_uint(bytes: &[u8], position: &mut usize, length: usize) -> Result<u64, DecodeError> {
+    Ok(take(bytes, position, length)?
+        .iter()
+        .fold(0, |acc, b| acc << 8 | u64::from(*b)))
+}
+
+/// Take a big endian signed integer of the given number of bytes
+#[allow(clippy::cast_possible_wrap)]
+fn take_int(bytes: &[u8], position: &mut usize, length: usize) -> Result<i64, DecodeError> {
+    let value = take_uint(bytes, position, length)?;
+    let shift = 64 - 8 * length;
+    Ok(((value << shift) as i64) >> shift)
+}
+
+/// Take a string of the given length
+fn take_str(bytes: &[u8], position: &mut usize, length: usize) -> Result<MsgPack, DecodeError> {
+    std::str::from_utf8(take(bytes, position, length)?)
+        .map(|s| MsgPack::Str(s.to_string()))
+        .map_err(|_| DecodeError::Invalid)
+}
+
+/// Take an array with the given number of elements, which are nested at the given depth
+fn take_array(
+    bytes: &[u8],
+    position: &mut usize,
+    length: usize,
+    depth: usize,
+) -> Result<MsgPack, DecodeError> {
+    if depth > MAX_DEPTH {
+        return Err(DecodeError::TooDeep);
+    }
+    // Every element is at least one byte, so this protects against bogus lengths
+    let mut array = Vec::with_capacity(length.min(bytes.len()));
+    for _ in 0..length {
+        array.push(decode_value(bytes, position, depth)?);
+    }
+    Ok(MsgPack::Array(array))
+}
+
+/// Take a map with the given number of pairs, which are nested at the given depth
+fn take_map(
+    bytes: &[u8],
+    position: &mut usize,
+    length: usize,
+    depth: usize,
+) -> Result<MsgPack, DecodeError> {
+    if depth > MAX_DEPTH {
+        return Err(DecodeError::TooDeep);
+    }
+    let mut map = Vec::with_capacity(length.min(bytes.len()));
+    for _ in 0..length {
+        let key = decode_value(bytes, position, depth)?;
+        let value = decode_value(bytes, position, depth)?;
+        map.push((key, value));
+    }
+    Ok(MsgPack::Map(map))
+}
+
+/// Decode the value starting at the given position, advancing the position. The depth is the
+/// number of arrays and maps the value is nested in.
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+fn decode_value(bytes: &[u8], position: &mut usize, depth: usize) -> Result<MsgPack, DecodeError> {
+    let marker = *bytes.get(*position).ok_or(DecodeError::Invalid)?;
+    *position += 1;
+    match marker {
+        0x00..=0x7f => Ok(MsgPack::Int(i64::from(marker))),
+        0x80..=0x8f => take_map(bytes, position, usize::from(marker & 0x0f), depth + 1),
+        0x90..=0x9f => take_array(bytes, position, usize::from(marker & 0x0f), depth + 1),
+        0xa0..=0xbf => take_str(bytes, position, usize::from(marker & 0x1f)),
+        0xc0 => Ok(MsgPack::Nil),
+        0xc2 => Ok(MsgPack::Bool(false)),
+        0xc3 => Ok(MsgPack::Bool(true)),
+        0xc4..=0xc6 => {
+            let length = take_uint(bytes, position, 1 << (marker - 0xc4))? as usize;
+            take(bytes, position, length).map(|b| MsgPack::Bin(b.to_vec()))
+        }
+        0xca => Ok(MsgPack::Float(f64::from(f32::from_bits(
+            take_uint(bytes, position, 4)? as u32,
+        )))),
+        0xcb => Ok(MsgPack::Float(f64::from_bits(take_uint(
+            bytes, position, 8,
+        )?))),
+        0xcc..=0xcf => {
+            let value = take_uint(bytes, position, 1 << (marker - 0xcc))?;
+            i64::try_from(value)
+                .map(MsgPack::Int)
+                .map_err(|_| DecodeError::Invalid)
+        }
+        0xd0..=0xd3 => take_int(bytes, position, 1 << (marker - 0xd0)).map(MsgPack::Int),
+        0xd9..=0xdb => {
+            let length = take_uint(bytes, position, 1 << (marker - 0xd9))? as usize;
+            take_str(bytes, position, length)
+        }
+        0xdc | 0xdd => {
+            let length = take_uint(bytes, position, 2 << (marker - 0xdc))? as usize;
+            take_array(bytes, position, length, depth + 1)
+        }
+        0xde | 0xdf => {
+            let length = take_uint(bytes, position, 2 << (marker - 0xde))? as usize;
+            take_map(bytes, position, length, depth + 1)
+        }
+        0xe0..=0xff => Ok(MsgPack::Int(i64::from(marker as i8))),
+        // Extension types (0xc7..=0xc9, 0xd4..=0xd8) and the never used 0xc1
+        _ => Err(DecodeError::Invalid),
+    }
+}
+
+/// Encode the given value as MessagePack, using the smallest representation for every value
+pub(crate) fn encode(value: &MsgPack) -> Vec<u8> {
+    let mut output = Vec::new();
+    encode_value(value, &mut output);
+    output
+}
+
+/// Write the marker and length for a string, binary, array, or map
+#[allow(clippy::cast_possible_truncation)]
+fn encode_length(length: usize, fixed: Option<u8>, markers: [u8; 3], output: &mut Vec<u8>) {
+    match (fixed, length) {
+        (Some(fixed), 0..=15) => output.push(fixed | length as u8),
+        (Some(0xa0), 16..=31) => output.push(0xa0 | length as u8),
+        (None, 0..=0xff) => output.extend([markers[0], length as u8]),
+        (Some(0xa0), 32..=0xff) => output.extend([markers[0], length as u8]),
+        (_, 0..=0xffff) => {
+            output.push(markers[1]);
+            output.extend((length as u16).to_be_bytes());
+        }
+        _ => {
+            output.push(markers[2]);
+            output.extend((length as u32).to_be_bytes());
+        }
+    }
+}
+
+/// Encode the given value, appending it to the output
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn encode_value(value: &MsgPack, output: &mut Vec<u8>) {
+    match value {
+        MsgPack::Nil => output.push(0xc0),
+        MsgPack::Bool(b) => output.push(if *b { 0xc3 } else { 0xc2 }),
+        MsgPack::Int(i) => match *i {
+            -32..=0x7f => output.push(*i as u8),
+            0x80..=0xff => output.extend([0xcc, *i as u8]),
+            0x100..=0xffff => {
+                output.push(0xcd);
+                output.extend((*i as u16).to_be_bytes());
+            }
+            0x1_0000..=0xffff_ffff => {
+                output.push(0xce);
+                output.extend((*i as u32).to_be_bytes());
+            }
+            -0x80..=-33 => output.extend([0xd0, *i as u8]),
+            -0x8000..=-0x81 => {
+                output.push(0xd1);
+                output.extend((*i as i16).to_be_bytes());
+            }
+            -0x8000_0000..=-0x8001 => {
+                output.push(0xd2);
+                output.extend((*i as i32).to_be_bytes());
+            }
+            _ => {
+                output.push(0xd3);
+                output.extend(i.to_be_bytes());
+            }
+        },
+        MsgPack::Float(f) => {
+            output.push(0xcb);
+            output.extend(f.to_bits().to_be_bytes());
+        }
+        MsgPack::Str(s) => {
+            encode_length(s.len(), Some(0xa0), [0xd9, 0xda, 0xdb], output);
+            output.extend(s.as_bytes());
+        }
+        MsgPack::Bin(b) => {
+            encode_length(b.len(), None, [0xc4, 0xc5, 0xc6], output);
+            output.extend(b);
+        }
+        MsgPack::Array(a) => {
+            encode_length(a.len(), Some(0x90), [0xdc, 0xdc, 0xdd], output);
+            for v in a {
+                encode_value(v, output);
+            }
+        }
+        MsgPack::Map(m) => {
+            encode_length(m.len(), Some(0x80), [0xde, 0xde, 0xdf], output);
+            for (k, v) in m {
+                encode_value(k, output);
+                encode_value(v, output);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let value = MsgPack::map(vec![
+            ("nil", MsgPack::Nil),
+            ("bool", MsgPack::Bool(true)),
+            (
+                "ints",
+                MsgPack::Array(
+                    [
+                        0, 1, 127, 128, 255, 256, 65535, 65536, -1, -32, -33, -128, -129,
+                    ]
+                    .iter()
+                    .chain(&[-32768, -32769, i64::from(i32::MIN), i64::MAX, i64::MIN])
+                    .map(|i| MsgPack::Int(*i))
+                    .collect(),
+                ),
+            ),
+            ("float", MsgPack::Float(-1.5e-7)),
+            ("short", "ab".into()),
+            ("long", MsgPack::Str("x".repeat(300))),
+            ("bin", MsgPack::Bin(vec![1, 2, 3])),
+            ("array", MsgPack::Array(vec![MsgPack::Nil; 20])),
+        ]);
+        let bytes = encode(&value);
+        assert_eq!(decode(&bytes).unwrap(), value);
+        assert_eq!(value.get("short").and_then(MsgPack::as_str), Some("ab"));
+        assert!(decode(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn known_encodings() {
+        assert_eq!(encode(&MsgPack::Int(-1)), [0xff]);
+        assert_eq!(encode(&MsgPack::Int(200)), [0xcc, 200]);
+        assert_eq!(encode(&"a".into()), [0xa1, b'a']);
+        assert_eq!(
+            decode(&[0x82, 0xa1, b'a', 0x01, 0xa1, b'b', 0xca, 0x3f, 0xc0, 0, 0]).unwrap(),
+            MsgPack::map(vec![("a", MsgPack::Int(1)), ("b", MsgPack::Float(1.5))])
+        );
+        assert_eq!(decode(&[0xd0, 0x80]).unwrap(), MsgPack::Int(-128));
+    }
+
+    #[test]
+    fn nesting_depth() {
+        let nested = |depth: usize| {
+            let mut bytes = vec![0x91; depth];
+            bytes.push(0xc0);
+            bytes
+        };
+        assert!(decode(&nested(MAX_DEPTH)).is_ok());
+        assert!(decode(&nested(MAX_DEPTH + 1)).is_err());
+        let error = decode(&nested(100_000)).unwrap_err();
+        assert!(error.long_description().contains("nested"));
+        let mut maps = [0x81, 0xa1, b'a'].repeat(200);
+        maps.push(0xc0);
+        assert!(decode(&maps).is_err());
+    }
+}
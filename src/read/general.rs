@@ -1,5 +1,5 @@
 use crate::error::*;
-use crate::structs::PDB;
+use crate::structs::{Element, PDB};
 use crate::StrictnessLevel;
 
 use super::*;
@@ -7,6 +7,35 @@ use super::*;
 /// Standard return type for reading a file.
 pub type ReadResult = Result<(PDB, Vec<PDBError>), Vec<PDBError>>;
 
+/// Read all bytes of the given stream, for the binary file formats
+pub(crate) fn read_bytes<T>(mut input: std::io::BufReader<T>) -> Result<Vec<u8>, Vec<PDBError>>
+where
+    T: std::io::Read,
+{
+    let mut contents = Vec::new();
+    std::io::Read::read_to_end(&mut input, &mut contents).map_err(|_| {
+        vec![PDBError::new(
+            ErrorLevel::BreakingError,
+            "Buffer could not be read",
+            "The buffer provided to read the binary file could not be read to end.",
+            Context::None,
+        )]
+    })?;
+    Ok(contents)
+}
+
+/// Guess the element of an atom from its name, for the file formats that do not contain elements
+/// (eg GROMACS and PQR). Atoms with the same name as their residue are seen as ions (eg 'NA' or
+/// 'CL'), the other atoms get the first letter of their name, as is the convention in force fields.
+pub(crate) fn guess_element<'a>(name: &'a str, residue_name: &str) -> &'a str {
+    let name = name.trim_start_matches(|c: char| c.is_ascii_digit());
+    if name.eq_ignore_ascii_case(residue_name) && Element::from_symbol(name).is_some() {
+        name
+    } else {
+        name.get(..1).unwrap_or_default()
+    }
+}
+
 /// Open an atomic data file, either PDB or mmCIF/PDBx.
 ///
 /// This function is equivalent to [`ReadOptions::read()`] with default options.
@@ -0,0 +1,4 @@
+/// Parse GROMACS files into PDB structures
+mod parser;
+
+pub(crate) use parser::*;
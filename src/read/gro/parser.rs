@@ -0,0 +1,332 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use crate::error::*;
+use crate::read::general::{guess_element, ReadResult};
+use crate::reference_tables;
+use crate::structs::*;
+use crate::validate::*;
+use crate::ReadOptions;
+
+/// The name of the single chain that all atoms are placed in, as GROMACS files do not have chains
+const GRO_CHAIN: &str = "A";
+
+/// Parse the given GROMACS file into a PDB struct with [`ReadOptions`].
+pub(crate) fn open_gro_with_options(
+    filename: impl AsRef<str>,
+    options: &ReadOptions,
+) -> ReadResult {
+    let filename = filename.as_ref();
+    let file = if let Ok(f) = File::open(filename) {
+        f
+    } else {
+        return Err(vec![PDBError::new(ErrorLevel::BreakingError, "Could not open file", "Could not open the specified file, make sure the path is correct, you have permission, and that it is not open in another program.", Context::show(filename))]);
+    };
+    open_gro_raw_with_options(BufReader::new(file), options)
+}
+
+/// Parse the given GROMACS stream into a [`PDB`] struct with [`ReadOptions`].
+///
+/// Every frame consists of a title line, the number of atoms, a fixed width line per atom, and the
+/// box vectors. Every frame is read as a separate model. The coordinates and velocities are
+/// converted from nm to Å, and the box of the first frame is used as the unit cell.
+pub(crate) fn open_gro_raw_with_options<T>(input: BufReader<T>, options: &ReadOptions) -> ReadResult
+where
+    T: std::io::Read,
+{
+    let mut lines = Vec::new();
+    for (linenumber, read_line) in input.lines().enumerate() {
+        if let Ok(l) = read_line {
+            lines.push(l);
+        } else {
+            return Err(vec![PDBError::new(
+                ErrorLevel::BreakingError,
+                "Could read line",
+                format!(
+                    "Could not read line {} while parsing the input file.",
+                    linenumber + 1
+                ),
+                Context::None,
+            )]);
+        }
+    }
+
+    let mut errors = Vec::new();
+    let mut pdb = PDB::new();
+    let mut index = 0;
+    while index < lines.len() && !lines[index..].iter().all(|l| l.trim().is_empty()) {
+        let (model, frame_errors) =
+            parse_frame(&lines, &mut index, pdb.model_count() + 1, &mut pdb, options)
+                .map_err(|e| vec![e])?;
+        errors.extend(frame_errors);
+        pdb.add_model(model);
+        if options.only_first_model {
+            break;
+        }
+    }
+    if pdb.model_count() == 0 {
+        return Err(vec![PDBError::new(
+            ErrorLevel::BreakingError,
+            "Empty GROMACS file",
+            "The file does not contain any frames.",
+            Context::None,
+        )]);
+    }
+
+    reshuffle_conformers(&mut pdb);
+    errors.extend(validate(&pdb));
+
+    if errors.iter().any(|e| e.fails(options.level)) {
+        Err(errors)
+    } else {
+        Ok((pdb, errors))
+    }
+}
+
+/// Parse a single frame starting at the line with the given index, the index is moved past the
+/// end of the frame. The title and box of the first frame are stored in the PDB.
+fn parse_frame(
+    lines: &[String],
+    index: &mut usize,
+    serial_number: usize,
+    pdb: &mut PDB,
+    options: &ReadOptions,
+) -> Result<(Model, Vec<PDBError>), PDBError> {
+    let mut errors = Vec::new();
+    let line_context =
+        |i: usize| Context::full_line(i + 1, lines.get(i).map_or("", String::as_str));
+    let title = lines[*index].trim();
+    if serial_number == 1 && !title.is_empty() {
+        pdb.header.get_or_insert_with(Header::new).title = Some(title.to_string());
+    }
+    let count_line = *index + 1;
+    let atom_count: usize = lines
+        .get(count_line)
+        .and_then(|l| l.trim().parse().ok())
+        .ok_or_else(|| {
+            PDBError::new(
+                ErrorLevel::BreakingError,
+                "Invalid atom count",
+                "The second line of a frame should contain the number of atoms.",
+                line_context(count_line),
+            )
+        })?;
+    let box_line = count_line + atom_count + 1;
+    if box_line >= lines.len() {
+        return Err(PDBError::new(
+            ErrorLevel::BreakingError,
+            "Incomplete frame",
+            format!("The frame should contain {atom_count} atoms followed by the box vectors."),
+            line_context(lines.len() - 1),
+        ));
+    }
+
+    let mut residues: Vec<Residue> = Vec::new();
+    let mut last_numbers = None;
+    let (mut residue_addition, mut atom_addition) = (0, 0);
+    for (i, line) in lines.iter().enumerate().take(box_line).skip(count_line + 1) {
+        let atom_line = parse_atom_line(line).map_err(|message| {
+            PDBError::new(
+                ErrorLevel::BreakingError,
+                "Invalid atom line",
+                message,
+                line_context(i),
+            )
+        })?;
+        let AtomLine {
+            residue_number,
+            residue_name,
+            name,
+            serial_number: atom_number,
+            pos,
+            velocity,
+        } = atom_line;
+
+        // Numbers are written modulo 100000 as the columns are five characters wide
+        if let Some((last_number, last_serial)) = last_numbers {
+            if residue_number == 0 && last_number == 99_999 {
+                residue_addition += 100_000;
+            }
+            if atom_number == 0 && last_serial == 99_999 {
+                atom_addition += 100_000;
+            }
+        }
+        last_numbers = Some((residue_number, atom_number));
+
+        let element = guess_element(name, residue_name);
+        if options.discard_hydrogens && element == "H" {
+            continue;
+        }
+        let hetero = reference_tables::get_one_letter_code(residue_name).is_none();
+        let atom = Atom::new(
+            hetero,
+            atom_number + atom_addition,
+            (atom_number + atom_addition).to_string(),
+            name,
+            pos.0 * 10.0,
+            pos.1 * 10.0,
+            pos.2 * 10.0,
+            1.0,
+            0.0,
+            element,
+            0,
+        );
+        let mut atom = if let Some(atom) = atom {
+            atom
+        } else {
+            errors.push(PDBError::new(
+                ErrorLevel::InvalidatingError,
+                "Atom definition incorrect",
+                "The atom name should only contain valid characters.",
+                line_context(i),
+            ));
+            continue;
+        };
+        if let Some((x, y, z)) = velocity {
+            if let Err(message) = atom.set_velocity((x * 10.0, y * 10.0, z * 10.0)) {
+                errors.push(PDBError::new(
+                    ErrorLevel::InvalidatingError,
+                    "Invalid velocity",
+                    message,
+                    line_context(i),
+                ));
+            }
+        }
+        // Consecutive atoms with the same residue number and name form a residue
+        #[allow(clippy::cast_possible_wrap)]
+        let residue_number = (residue_number + residue_addition) as isize;
+        if let Some(residue) = residues.last_mut().filter(|r| {
+            r.serial_number() == residue_number
+                && r.name()
+                    .map_or(false, |n| n.eq_ignore_ascii_case(residue_name))
+        }) {
+            residue.add_atom(atom, (residue_name, None));
+        } else if let Some(conformer) = Conformer::new(residue_name, None, Some(atom)) {
+            residues.extend(Residue::new(residue_number, None, Some(conformer)));
+        } else {
+            errors.push(PDBError::new(
+                ErrorLevel::InvalidatingError,
+                "Residue definition incorrect",
+                "The residue name should only contain valid characters.",
+                line_context(i),
+            ));
+        }
+    }
+    let model = Model::from_iter(
+        serial_number,
+        Chain::from_iter(GRO_CHAIN, residues.into_iter()).into_iter(),
+    );
+
+    let numbers: Result<Vec<f64>, _> = lines[box_line].split_whitespace().map(str::parse).collect();
+    match numbers.as_deref() {
+        Ok(&[x, y, z]) => set_box(
+            pdb,
+            serial_number,
+            [[x, 0.0, 0.0], [0.0, y, 0.0], [0.0, 0.0, z]],
+        ),
+        Ok(&[xx, yy, zz, xy, xz, yx, yz, zx, zy]) => {
+            set_box(
+                pdb,
+                serial_number,
+                [[xx, xy, xz], [yx, yy, yz], [zx, zy, zz]],
+            );
+        }
+        _ => errors.push(PDBError::new(
+            ErrorLevel::LooseWarning,
+            "Invalid box vectors",
+            "The last line of a frame should contain 3 or 9 numbers giving the box vectors.",
+            line_context(box_line),
+        )),
+    }
+    *index = box_line + 1;
+    Ok((model, errors))
+}
+
+/// Set the unit cell from the given box vectors (nm) of the first frame, a box of only zeros
+/// is seen as absent
+fn set_box(pdb: &mut PDB, serial_number: usize, vectors: [[f64; 3]; 3]) {
+    if serial_number == 1 && vectors.iter().flatten().any(|v| *v != 0.0) {
+        let [a, b, c] = vectors.map(|v| v.map(|n| n * 10.0));
+        pdb.unit_cell = Some(UnitCell::from_vectors(a, b, c));
+    }
+}
+
+/// The information given on a single atom line of a GROMACS file, in the units of the file
+struct AtomLine<'a> {
+    /// The residue number, modulo 100000
+    residue_number: usize,
+    /// The name of the residue
+    residue_name: &'a str,
+    /// The name of the atom
+    name: &'a str,
+    /// The serial number of the atom, modulo 100000
+    serial_number: usize,
+    /// The position (nm)
+    pos: (f64, f64, f64),
+    /// The velocity (nm/ps), if given
+    velocity: Option<(f64, f64, f64)>,
+}
+
+/// Parse an atom line, the first four fields are five characters wide. The width of the
+/// coordinate and velocity fields is the distance between the decimal points of the first
+/// two coordinates, which is eight for the standard precision of three decimals.
+fn parse_atom_line(line: &str) -> Result<AtomLine<'_>, String> {
+    let field = |range: std::ops::Range<usize>, name: &str| {
+        line.get(range)
+            .map(str::trim)
+            .ok_or_else(|| format!("The line is too short to contain the {name}."))
+    };
+    let number = |text: &str, name: &str| {
+        text.parse::<usize>()
+            .map_err(|_| format!("The {name} '{text}' is not a valid number."))
+    };
+    let residue_number = number(field(0..5, "residue number")?, "residue number")?;
+    let residue_name = field(5..10, "residue name")?;
+    let name = field(10..15, "atom name")?;
+    let serial_number = number(field(15..20, "atom number")?, "atom number")?;
+
+    let decimals: Vec<usize> = line
+        .char_indices()
+        .skip(20)
+        .filter(|(_, c)| *c == '.')
+        .map(|(i, _)| i)
+        .take(2)
+        .collect();
+    let width = match decimals.as_slice() {
+        [first, second] if second > first => second - first,
+        _ => 8,
+    };
+    let float = |n: usize, name: &str| -> Result<Option<f64>, String> {
+        match line
+            .get(20 + n * width..20 + (n + 1) * width)
+            .map(str::trim)
+        {
+            None | Some("") => Ok(None),
+            Some(text) => text
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("The {name} '{text}' is not a valid number.")),
+        }
+    };
+    let coordinate = |n: usize| {
+        float(n, "coordinate")?
+            .ok_or_else(|| "The line is too short to contain the coordinates.".to_string())
+    };
+    let pos = (coordinate(0)?, coordinate(1)?, coordinate(2)?);
+    let velocity = match (
+        float(3, "velocity")?,
+        float(4, "velocity")?,
+        float(5, "velocity")?,
+    ) {
+        (Some(x), Some(y), Some(z)) => Some((x, y, z)),
+        _ => None,
+    };
+    Ok(AtomLine {
+        residue_number,
+        residue_name,
+        name,
+        serial_number,
+        pos,
+        velocity,
+    })
+}
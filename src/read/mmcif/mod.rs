@@ -1,7 +1,3 @@
-/// Parse CIF files into intermediate structure
-mod lexer;
-/// Save the CIF intermediate structure
-mod lexitem;
 /// Parse intermediate structure to PDB structure
 mod parser;
 
@@ -1,5 +1,7 @@
-use super::lexitem::*;
+use crate::cif::*;
 use crate::error::*;
+use crate::read::general::{read_bytes, ReadResult};
+use crate::save::WRITTEN_CATEGORIES;
 use crate::structs::*;
 use crate::validate::*;
 use crate::ReadOptions;
@@ -73,7 +75,7 @@ pub fn open_mmcif_raw(
     input: &str,
     level: StrictnessLevel,
 ) -> Result<(PDB, Vec<PDBError>), Vec<PDBError>> {
-    match super::lexer::lex_cif(input) {
+    match crate::cif::lex_cif(input) {
         Ok(data_block) => parse_mmcif(&data_block, level),
         Err(e) => Err(vec![e]),
     }
@@ -89,7 +91,7 @@ where
 {
     let mut contents = String::new();
     if input.read_to_string(&mut contents).is_ok() {
-        match super::lexer::lex_cif(contents.as_str()) {
+        match crate::cif::lex_cif(contents.as_str()) {
             Ok(data_block) => parse_mmcif_with_options(&data_block, options),
             Err(e) => Err(vec![e]),
         }
@@ -103,6 +105,99 @@ where
     }
 }
 
+/// Parse every data block in the given stream into a separate [`PDB`] struct.
+/// Returns a PDBError if the stream could not be read or lexed, otherwise it returns the result of
+/// parsing every data block, with all errors tagged with the name of their data block.
+pub(crate) fn open_mmcif_blocks_raw_with_options<T>(
+    mut input: std::io::BufReader<T>,
+    options: &ReadOptions,
+) -> Result<Vec<ReadResult>, Vec<PDBError>>
+where
+    T: std::io::Read,
+{
+    let mut contents = String::new();
+    if input.read_to_string(&mut contents).is_err() {
+        return Err(vec![PDBError::new(
+            crate::ErrorLevel::BreakingError,
+            "Buffer could not be read",
+            "The buffer provided to `read_raw_blocks` could not be read to end.",
+            Context::None,
+        )]);
+    }
+    let blocks = crate::cif::lex_cif_document(contents.as_str()).map_err(|e| vec![e])?;
+    Ok(parse_blocks(&blocks, options))
+}
+
+/// Parse the given BinaryCIF file into a PDB struct with [`ReadOptions`].
+pub(crate) fn open_bcif_with_options(
+    filename: impl AsRef<str>,
+    options: &ReadOptions,
+) -> Result<(PDB, Vec<PDBError>), Vec<PDBError>> {
+    let filename = filename.as_ref();
+    let file = if let Ok(f) = File::open(filename) {
+        f
+    } else {
+        return Err(vec![PDBError::new(ErrorLevel::BreakingError, "Could not open file", "Could not open the specified file, make sure the path is correct, you have permission, and that it is not open in another program.", Context::show(filename))]);
+    };
+    open_bcif_raw_with_options(std::io::BufReader::new(file), options)
+}
+
+/// Parse the first data block of the given BinaryCIF stream into a [`PDB`] struct.
+/// The BinaryCIF file is decoded into the same intermediate structure as mmCIF files, so it is
+/// interpreted in exactly the same way.
+pub(crate) fn open_bcif_raw_with_options<T>(
+    input: std::io::BufReader<T>,
+    options: &ReadOptions,
+) -> Result<(PDB, Vec<PDBError>), Vec<PDBError>>
+where
+    T: std::io::Read,
+{
+    let blocks = crate::cif::decode_bcif(&read_bytes(input)?).map_err(|e| vec![e])?;
+    match blocks.first() {
+        Some(block) => parse_mmcif_with_options(block, options),
+        None => Err(vec![PDBError::new(
+            ErrorLevel::BreakingError,
+            "No data blocks",
+            "The BinaryCIF file does not contain any data blocks.",
+            Context::None,
+        )]),
+    }
+}
+
+/// Parse every data block in the given BinaryCIF stream into a separate [`PDB`] struct.
+/// It behaves the same as [`open_mmcif_blocks_raw_with_options`].
+pub(crate) fn open_bcif_blocks_raw_with_options<T>(
+    input: std::io::BufReader<T>,
+    options: &ReadOptions,
+) -> Result<Vec<ReadResult>, Vec<PDBError>>
+where
+    T: std::io::Read,
+{
+    let blocks = crate::cif::decode_bcif(&read_bytes(input)?).map_err(|e| vec![e])?;
+    Ok(parse_blocks(&blocks, options))
+}
+
+/// Parse every data block into a separate [`PDB`] struct, with all errors tagged with the name
+/// of their data block.
+fn parse_blocks(blocks: &[DataBlock], options: &ReadOptions) -> Vec<ReadResult> {
+    blocks
+        .iter()
+        .map(|block| {
+            let note = format!("In data block '{}'", block.name);
+            let tag = |mut errors: Vec<PDBError>| {
+                for error in &mut errors {
+                    error.add_context_note(&note);
+                }
+                errors
+            };
+            match parse_mmcif_with_options(block, options) {
+                Ok((pdb, errors)) => Ok((pdb, tag(errors))),
+                Err(errors) => Err(tag(errors)),
+            }
+        })
+        .collect()
+}
+
 /// Parse a CIF intermediate structure into a PDB
 fn parse_mmcif(
     input: &DataBlock,
@@ -271,6 +366,22 @@ fn parse_mmcif_with_options(
         pdb.unit_cell = Some(unit_cell);
     }
 
+    errors.extend(parse_header(input, &mut pdb));
+    errors.extend(parse_refinement(input, &mut pdb));
+    let label = options.label_identifiers;
+    errors.extend(parse_assemblies(input, &mut pdb, label));
+    errors.extend(parse_missing(input, &mut pdb, label));
+    errors.extend(parse_entities(input, &mut pdb, label));
+    errors.extend(parse_sequence_scheme(input, &mut pdb, label));
+    errors.extend(parse_chemical_components(input, &mut pdb));
+    errors.extend(parse_sites(input, &mut pdb, label));
+    errors.extend(parse_cis_peptides(input, &mut pdb, label));
+    errors.extend(parse_secondary_structure(input, &mut pdb, label));
+    errors.extend(parse_bonds(input, &mut pdb, label));
+    if options.keep_unknown_categories {
+        pdb.unknown_categories = Some(unknown_categories(input));
+    }
+
     reshuffle_conformers(&mut pdb);
     errors.extend(validate(&pdb));
     if errors.iter().any(|e| e.fails(options.level)) {
@@ -339,12 +450,22 @@ fn parse_matrix(
     }
 }
 
-/// Flatten a Result of a Result with the same error type (#70142 is still unstable)
-fn flatten_result<T, E>(value: Result<Result<T, E>, E>) -> Result<T, E> {
-    match value {
-        Ok(Ok(t)) => Ok(t),
-        Ok(Err(e)) => Err(e),
-        Err(e) => Err(e),
+/// Get all categories of the data block that are not interpreted, the interpreted categories are
+/// generated from the structure when saving
+fn unknown_categories(input: &DataBlock) -> DataBlock {
+    DataBlock {
+        name: input.name.clone(),
+        items: input
+            .items
+            .iter()
+            .filter(|item| match item {
+                Item::DataItem(item) => item
+                    .category()
+                    .map_or(false, |c| !WRITTEN_CATEGORIES.contains(&c)),
+                Item::SaveFrame(_) => false,
+            })
+            .cloned()
+            .collect(),
     }
 }
 
@@ -398,6 +519,7 @@ fn parse_atoms(input: &Loop, pdb: &mut PDB, options: &ReadOptions) -> Option<Vec
         24, ATOM_X, "atom_site.Cartn_x", Required;
         25, ATOM_Y, "atom_site.Cartn_y", Required;
         26, ATOM_Z, "atom_site.Cartn_z", Required;
+        27, ATOM_ENTITY_ID, "atom_site.label_entity_id", Optional;
     );
 
     let positions_: Vec<Result<Option<usize>, PDBError>> = COLUMNS
@@ -438,6 +560,8 @@ fn parse_atoms(input: &Loop, pdb: &mut PDB, options: &ReadOptions) -> Option<Vec
     #[allow(clippy::unwrap_used)]
     let positions: Vec<Option<usize>> = positions_.iter().map(|i| *i.as_ref().unwrap()).collect();
     let mut first_model_number: usize = 0;
+    // The model, chain, and residue id of the previous atom
+    let mut last_residue = None;
     for (index, row) in input.data.iter().enumerate() {
         let values: Vec<Option<&Value>> = positions.iter().map(|i| i.map(|x| &row[x])).collect();
         let context = Context::show(format!("Main atomic data loop row: {index}"));
@@ -479,14 +603,25 @@ fn parse_atoms(input: &Loop, pdb: &mut PDB, options: &ReadOptions) -> Option<Vec
         let id = parse_column!(get_text, ATOM_ID).expect("Atom ID should be provided");
         let residue_name =
             parse_column!(get_text, ATOM_COMP_ID).expect("Residue name should be provided");
+        let label_seq_id = parse_column!(get_isize, ATOM_SEQ_ID);
+        let auth_seq_id = parse_column!(get_isize, ATOM_AUTH_SEQ_ID);
+        let label_asym_id = parse_column!(get_text, ATOM_ASYM_ID);
+        let auth_asym_id = parse_column!(get_text, ATOM_AUTH_ASYM_ID);
+        let label_entity_id = parse_column!(get_text, ATOM_ENTITY_ID);
+        let (residue_number, chain_name) = if options.label_identifiers {
+            (
+                label_seq_id.or(auth_seq_id),
+                label_asym_id.clone().or_else(|| auth_asym_id.clone()),
+            )
+        } else {
+            (
+                auth_seq_id.or(label_seq_id),
+                auth_asym_id.clone().or_else(|| label_asym_id.clone()),
+            )
+        };
         #[allow(clippy::cast_possible_wrap)]
-        let residue_number = parse_column!(get_isize, ATOM_AUTH_SEQ_ID).unwrap_or_else(|| {
-            parse_column!(get_isize, ATOM_SEQ_ID)
-                .unwrap_or_else(|| pdb.total_residue_count() as isize)
-        });
-        let chain_name = parse_column!(get_text, ATOM_AUTH_ASYM_ID).unwrap_or_else(|| {
-            parse_column!(get_text, ATOM_ASYM_ID).expect("Chain name should be provided")
-        });
+        let residue_number = residue_number.unwrap_or_else(|| pdb.total_residue_count() as isize);
+        let chain_name = chain_name.expect("Chain name should be provided");
         let pos_x = parse_column!(get_f64, ATOM_X).expect("Atom X position should be provided");
         let pos_y = parse_column!(get_f64, ATOM_Y).expect("Atom Y position should be provided");
         let pos_z = parse_column!(get_f64, ATOM_Z).expect("Atom Z position should be provided");
@@ -614,12 +749,39 @@ fn parse_atoms(input: &Loop, pdb: &mut PDB, options: &ReadOptions) -> Option<Vec
 
             model_atom_counts.insert(model_number, current_model_atom_count + 1);
 
+            // Keep the identifiers of the other scheme on the residue, set once for every residue
+            let key = (
+                model_number,
+                chain_name.clone(),
+                residue_number,
+                insertion_code.clone(),
+            );
+            let new_residue = last_residue.as_ref() != Some(&key);
             model.add_atom(
                 atom,
-                chain_name,
+                chain_name.as_str(),
                 (residue_number, insertion_code.as_deref()),
                 (residue_name, alt_loc.as_deref()),
             );
+            if new_residue {
+                if let Some(residue) = model
+                    .chains_mut()
+                    .rev()
+                    .find(|c| c.id() == chain_name)
+                    .and_then(|c| {
+                        c.residues_mut()
+                            .rev()
+                            .find(|r| r.id() == (residue_number, insertion_code.as_deref()))
+                    })
+                {
+                    residue.set_label_asym_id(label_asym_id.as_deref());
+                    residue.set_label_seq_id(label_seq_id);
+                    residue.set_label_entity_id(label_entity_id.as_deref());
+                    residue.set_auth_asym_id(auth_asym_id.as_deref());
+                    residue.set_auth_seq_id(auth_seq_id);
+                }
+                last_residue = Some(key);
+            }
         } else {
             errors.push(PDBError::new(
                 ErrorLevel::InvalidatingError,
@@ -647,101 +809,1105 @@ fn parse_atoms(input: &Loop, pdb: &mut PDB, options: &ReadOptions) -> Option<Vec
     }
 }
 
-/// Get the Textual content of the value, if available
-fn get_text(
-    value: &Value,
-    _context: &Context,
-    _column: Option<&str>,
-) -> Result<Option<String>, PDBError> {
-    match value {
-        Value::Text(t) => Ok(Some(t.to_string())),
-        Value::Inapplicable => Ok(None),
-        Value::Unknown => Ok(None),
-        Value::Numeric(n) => Ok(Some(format!("{n}"))),
-        Value::NumericWithUncertainty(n, u) => Ok(Some(format!("{n}({u})"))),
+impl<'a> Category<'a> {
+    /// Get an identifier from the given row, given as the names of the author defined and the
+    /// label column. The author defined column is used if available, with the label column as
+    /// fall back, or the other way around if `label` is set (see [`ReadOptions::set_label_identifiers`]).
+    fn get_identifier<T>(
+        &self,
+        row: usize,
+        (auth_column, label_column): (&str, &str),
+        get: GetValue<T>,
+        label: bool,
+        errors: &mut Vec<PDBError>,
+    ) -> Option<T> {
+        let (first, second) = if label {
+            (label_column, auth_column)
+        } else {
+            (auth_column, label_column)
+        };
+        self.get(row, first, get, errors)
+            .or_else(|| self.get(row, second, get, errors))
     }
-}
 
-/// Get the Numeric content of the value, if available, it also fails on NumericWithUncertainty
-fn get_f64(
-    value: &Value,
-    context: &Context,
-    column: Option<&str>,
-) -> Result<Option<f64>, PDBError> {
-    match value {
-        Value::Numeric(num) => Ok(Some(*num)),
-        Value::Inapplicable => Ok(None),
-        Value::Unknown => Ok(None),
-        _ => Err(PDBError::new(
-            ErrorLevel::InvalidatingError,
-            "Not a number",
-            column.map_or(String::new(), |v| {
-                format!("The '{v}' column should contain a number.")
-            }),
-            context.clone(),
-        )),
+    /// Get a reference to a residue from the given row, using the author defined identifiers if
+    /// available, with the label identifiers as fall back, or the other way around if `label` is
+    /// set. The columns are expected to be named `{prefix}_auth_asym_id`, `{prefix}_auth_seq_id`,
+    /// and `{prefix}_auth_comp_id`.
+    fn get_residue(
+        &self,
+        row: usize,
+        prefix: &str,
+        insertion_code: &str,
+        label: bool,
+        errors: &mut Vec<PDBError>,
+    ) -> Option<ResidueReference> {
+        let mut get_either = |column: &str| {
+            self.get_identifier(
+                row,
+                (
+                    &format!("{prefix}_auth_{column}"),
+                    &format!("{prefix}_label_{column}"),
+                ),
+                get_text,
+                label,
+                errors,
+            )
+        };
+        let chain_id = get_either("asym_id")?;
+        let name = get_either("comp_id")?;
+        let serial_number = self.get_identifier(
+            row,
+            (
+                &format!("{prefix}_auth_seq_id"),
+                &format!("{prefix}_label_seq_id"),
+            ),
+            get_isize,
+            label,
+            errors,
+        )?;
+        let insertion_code = self.get(row, insertion_code, get_text, errors);
+        Some(ResidueReference {
+            chain_id,
+            serial_number,
+            insertion_code,
+            name,
+        })
     }
 }
 
-/// Get the Numeric content of the value, if available, as a usize
-fn get_usize(
-    value: &Value,
-    context: &Context,
-    column: Option<&str>,
-) -> Result<Option<usize>, PDBError> {
-    flatten_result(get_f64(value, context, column).map(|result| {
-        if let Some(num) = result {
-            #[allow(
-                clippy::cast_precision_loss,
-                clippy::cast_possible_truncation,
-                clippy::cast_sign_loss,
-                clippy::float_cmp
-            )]
-            if (0.0..usize::MAX as f64).contains(&num) && num.trunc() == num {
-                Ok(Some(num as usize))
+/// Parse the metadata of the entry from the `_struct`, `_struct_keywords`, `_exptl`,
+/// `_pdbx_database_status`, `_audit_author`, `_pdbx_audit_revision_history`, `_entity`,
+/// `_entity_poly`, `_entity_src_gen`, and `_entity_src_nat` categories.
+fn parse_header(input: &DataBlock, pdb: &mut PDB) -> Vec<PDBError> {
+    let mut errors = Vec::new();
+    let mut header = Header::new();
+    // Get all values of the given column in the given category
+    let mut column = |category: &str, column: &str| {
+        Category::find(input, category).map_or_else(Vec::new, |c| {
+            (0..c.rows.len())
+                .filter_map(|row| c.get(row, column, get_text, &mut errors))
+                .collect::<Vec<String>>()
+        })
+    };
+    header.title = column("struct", "title").pop();
+    header.classification = column("struct_keywords", "pdbx_keywords").pop();
+    header.keywords = column("struct_keywords", "text")
+        .pop()
+        .map_or_else(Vec::new, |text| {
+            text.split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(ToString::to_string)
+                .collect()
+        });
+    header.experimental_methods = column("exptl", "method");
+    header.deposition_date = column("pdbx_database_status", "recvd_initial_deposition_date").pop();
+    header.authors = column("audit_author", "name");
+
+    if let Some(history) = Category::find(input, "pdbx_audit_revision_history") {
+        for row in 0..history.rows.len() {
+            if let Some(number) = history.get(row, "ordinal", get_usize, &mut errors) {
+                header.revisions.push(Revision {
+                    number,
+                    date: history.get(row, "revision_date", get_text, &mut errors),
+                    identifier: pdb.identifier.clone(),
+                    modification_type: usize::from(number != 1),
+                    records: Vec::new(),
+                });
+            }
+        }
+    }
+
+    // Add the given mmCIF columns as specification tokens to the molecule with the given entity id
+    let mut add_tokens = |category: &str,
+                          columns: &[(&str, &str)],
+                          compound: bool,
+                          only_polymers: bool,
+                          header: &mut Header| {
+        let category = if let Some(c) = Category::find(input, category) {
+            c
+        } else {
+            return;
+        };
+        let id_column = if category.name == "entity" {
+            "id"
+        } else {
+            "entity_id"
+        };
+        for row in 0..category.rows.len() {
+            if only_polymers
+                && category
+                    .get(row, "type", get_text, &mut errors)
+                    .map_or(false, |t| t != "polymer")
+            {
+                continue;
+            }
+            let id = if let Some(id) = category.get(row, id_column, get_text, &mut errors) {
+                id
             } else {
-                Err(PDBError::new(
-                    ErrorLevel::InvalidatingError,
-                    "Not an unsigned integer",
-                    column.map_or(String::new(), |v| {
-                        format!("The '{v}' column should contain an unsigned integer.")
-                    }),
-                    context.clone(),
-                ))
+                continue;
+            };
+            let index = if let Some(index) = header.molecules.iter().position(|m| m.id == id) {
+                index
+            } else if category.name == "entity" {
+                header.molecules.push(MoleculeInfo::new(id));
+                header.molecules.len() - 1
+            } else {
+                continue;
+            };
+            for (column, token) in columns {
+                if let Some(value) = category.get(row, column, get_text, &mut errors) {
+                    let value = if *token == "CHAIN" {
+                        value
+                            .split(',')
+                            .map(str::trim)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    } else {
+                        value
+                    };
+                    let molecule = &mut header.molecules[index];
+                    if compound {
+                        molecule.compound.push((token.to_string(), value));
+                    } else {
+                        molecule.source.push((token.to_string(), value));
+                    }
+                }
+            }
+        }
+    };
+    add_tokens(
+        "entity",
+        &[
+            ("pdbx_description", "MOLECULE"),
+            ("pdbx_fragment", "FRAGMENT"),
+            ("pdbx_ec", "EC"),
+            ("pdbx_mutation", "MUTATION"),
+        ],
+        true,
+        true,
+        &mut header,
+    );
+    add_tokens(
+        "entity_poly",
+        &[("pdbx_strand_id", "CHAIN")],
+        true,
+        false,
+        &mut header,
+    );
+    add_tokens(
+        "entity_src_gen",
+        &[
+            ("pdbx_gene_src_scientific_name", "ORGANISM_SCIENTIFIC"),
+            ("gene_src_common_name", "ORGANISM_COMMON"),
+            ("pdbx_gene_src_ncbi_taxonomy_id", "ORGANISM_TAXID"),
+            ("gene_src_strain", "STRAIN"),
+            ("pdbx_gene_src_gene", "GENE"),
+            ("pdbx_host_org_scientific_name", "EXPRESSION_SYSTEM"),
+            ("pdbx_host_org_ncbi_taxonomy_id", "EXPRESSION_SYSTEM_TAXID"),
+        ],
+        false,
+        false,
+        &mut header,
+    );
+    add_tokens(
+        "entity_src_nat",
+        &[
+            ("pdbx_organism_scientific", "ORGANISM_SCIENTIFIC"),
+            ("common_name", "ORGANISM_COMMON"),
+            ("pdbx_ncbi_taxonomy_id", "ORGANISM_TAXID"),
+            ("strain", "STRAIN"),
+        ],
+        false,
+        false,
+        &mut header,
+    );
+
+    if header != Header::default() {
+        pdb.header = Some(header);
+    }
+    errors
+}
+
+/// Parse the resolution and refinement statistics from the `_refine`, `_refine_hist`, `_reflns`,
+/// and `_software` categories. The resolution is taken from `_em_3d_reconstruction` or `_reflns`
+/// if it is not given in `_refine`.
+fn parse_refinement(input: &DataBlock, pdb: &mut PDB) -> Vec<PDBError> {
+    let mut errors = Vec::new();
+    let mut refinement = RefinementInfo::new();
+    if let Some(refine) = Category::find(input, "refine") {
+        let mut f64 = |column: &str| refine.get(0, column, get_f64, &mut errors);
+        refinement.resolution = f64("ls_d_res_high");
+        refinement.resolution_low = f64("ls_d_res_low");
+        refinement.completeness = f64("ls_percent_reflns_obs");
+        refinement.r_all = f64("ls_R_factor_obs");
+        refinement.r_work = f64("ls_R_factor_R_work");
+        refinement.r_free = f64("ls_R_factor_R_free");
+        refinement.free_set_percentage = f64("ls_percent_reflns_R_free");
+        refinement.mean_b_factor = f64("B_iso_mean");
+        refinement.reflection_count = refine.get(0, "ls_number_reflns_obs", get_usize, &mut errors);
+        refinement.free_set_count =
+            refine.get(0, "ls_number_reflns_R_free", get_usize, &mut errors);
+    }
+    if let Some((history, row)) = Category::find(input, "refine_hist")
+        .and_then(|h| h.rows.len().checked_sub(1).map(|row| (h, row)))
+    {
+        // The last row is the last refinement cycle
+        let mut usize = |column: &str| history.get(row, column, get_usize, &mut errors);
+        refinement.protein_atoms = usize("pdbx_number_atoms_protein");
+        refinement.nucleic_acid_atoms = usize("pdbx_number_atoms_nucleic_acid");
+        refinement.heterogen_atoms = usize("pdbx_number_atoms_ligand");
+        refinement.solvent_atoms = usize("number_atoms_solvent");
+    }
+    if let Some(software) = Category::find(input, "software") {
+        refinement.program = (0..software.rows.len())
+            .find(|row| {
+                software
+                    .get(*row, "classification", get_text, &mut errors)
+                    .map_or(false, |c| c.eq_ignore_ascii_case("refinement"))
+            })
+            .and_then(|row| {
+                let name = software.get(row, "name", get_text, &mut errors)?;
+                Some(match software.get(row, "version", get_text, &mut errors) {
+                    Some(version) => format!("{name} {version}"),
+                    None => name,
+                })
+            });
+    }
+    if let Some(reflns) = Category::find(input, "reflns") {
+        let mut f64 = |column: &str| reflns.get(0, column, get_f64, &mut errors);
+        let reflections = ReflectionStatistics {
+            resolution_high: f64("d_resolution_high"),
+            resolution_low: f64("d_resolution_low"),
+            completeness: f64("percent_possible_obs"),
+            redundancy: f64("pdbx_redundancy"),
+            r_merge: f64("pdbx_Rmerge_I_obs"),
+            i_over_sigma: f64("pdbx_netI_over_sigmaI"),
+            unique_reflections: reflns.get(0, "number_obs", get_usize, &mut errors),
+        };
+        if reflections != ReflectionStatistics::default() {
+            refinement.reflections = Some(reflections);
+        }
+    }
+    if refinement.resolution.is_none() {
+        refinement.resolution = Category::find(input, "em_3d_reconstruction")
+            .and_then(|em| em.get(0, "resolution", get_f64, &mut errors))
+            .or_else(|| {
+                refinement
+                    .reflections
+                    .as_ref()
+                    .and_then(|r| r.resolution_high)
+            });
+    }
+    if !refinement.is_empty() {
+        pdb.refinement = Some(refinement);
+    }
+    errors
+}
+
+/// Parse the biological assemblies from the `_pdbx_struct_assembly`, `_pdbx_struct_assembly_gen`,
+/// and `_pdbx_struct_oper_list` categories. The chains in `asym_id_list` are label asym ids, these
+/// are converted to the (author) chain ids as used in the rest of the structure.
+fn parse_assemblies(input: &DataBlock, pdb: &mut PDB, label: bool) -> Vec<PDBError> {
+    let mut errors = Vec::new();
+    let (assemblies, generators, operators) = if let (Some(a), Some(g), Some(o)) = (
+        Category::find(input, "pdbx_struct_assembly"),
+        Category::find(input, "pdbx_struct_assembly_gen"),
+        Category::find(input, "pdbx_struct_oper_list"),
+    ) {
+        (a, g, o)
+    } else {
+        return errors;
+    };
+
+    let mut transformations = HashMap::new();
+    for row in 0..operators.rows.len() {
+        let mut matrix = [[0.0; 4]; 3];
+        let mut missing = Vec::new();
+        for (i, line) in matrix.iter_mut().enumerate() {
+            for (j, value) in line.iter_mut().enumerate() {
+                let column = if j == 3 {
+                    format!("vector[{}]", i + 1)
+                } else {
+                    format!("matrix[{}][{}]", i + 1, j + 1)
+                };
+                if let Some(number) = operators.get(row, &column, get_f64, &mut errors) {
+                    *value = number;
+                } else {
+                    missing.push(column);
+                }
+            }
+        }
+        let id = operators.get(row, "id", get_text, &mut errors);
+        if !missing.is_empty() {
+            errors.push(PDBError::new(
+                ErrorLevel::LooseWarning,
+                "Incomplete assembly operator",
+                format!(
+                    "The operator '{}' does not define {}, so it is ignored.",
+                    id.unwrap_or_default(),
+                    missing.join(", ")
+                ),
+                Context::show(format!("_pdbx_struct_oper_list row: {}", row + 1)),
+            ));
+            continue;
+        }
+        if let Some(id) = id {
+            transformations.insert(id, TransformationMatrix::from_matrix(matrix));
+        }
+    }
+
+    let mut chain_ids = HashMap::new();
+    if let Some(atoms) = Category::find(input, "atom_site").filter(|_| !label) {
+        if atoms.columns.contains(&"auth_asym_id") {
+            for row in 0..atoms.rows.len() {
+                if let (Some(label), Some(auth)) = (
+                    atoms.get(row, "label_asym_id", get_text, &mut errors),
+                    atoms.get(row, "auth_asym_id", get_text, &mut errors),
+                ) {
+                    chain_ids.entry(label).or_insert(auth);
+                }
+            }
+        }
+    }
+
+    for row in 0..assemblies.rows.len() {
+        let mut assembly = if let Some(id) = assemblies.get(row, "id", get_text, &mut errors) {
+            Assembly::new(id)
+        } else {
+            continue;
+        };
+        assembly.details = assemblies.get(row, "details", get_text, &mut errors);
+        assembly.oligomeric_details =
+            assemblies.get(row, "oligomeric_details", get_text, &mut errors);
+        assembly.oligomeric_count = assemblies.get(row, "oligomeric_count", get_usize, &mut errors);
+        for gen_row in 0..generators.rows.len() {
+            if generators.get(gen_row, "assembly_id", get_text, &mut errors)
+                != Some(assembly.id.clone())
+            {
+                continue;
+            }
+            let context = Context::show(format!("_pdbx_struct_assembly_gen row: {}", gen_row + 1));
+            let mut chains: Vec<String> = Vec::new();
+            for label in generators
+                .get(gen_row, "asym_id_list", get_text, &mut errors)
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|c| !c.is_empty())
+            {
+                let chain = chain_ids
+                    .get(label)
+                    .cloned()
+                    .unwrap_or_else(|| label.to_string());
+                if !chains.contains(&chain) {
+                    chains.push(chain);
+                }
+            }
+            let expression = generators
+                .get(gen_row, "oper_expression", get_text, &mut errors)
+                .unwrap_or_default();
+            // Every group of operators is applied after the group to the right of it
+            let mut products = vec![(String::new(), TransformationMatrix::identity())];
+            for group in operator_expression(&expression) {
+                let mut next = Vec::new();
+                for (id, transformation) in &products {
+                    for operator in &group {
+                        if let Some(matrix) = transformations.get(operator) {
+                            next.push((
+                                if id.is_empty() {
+                                    operator.clone()
+                                } else {
+                                    format!("{id}x{operator}")
+                                },
+                                matrix.combine(transformation),
+                            ));
+                        } else {
+                            errors.push(PDBError::new(
+                                ErrorLevel::LooseWarning,
+                                "Unknown assembly operator",
+                                format!("The operator '{operator}' is not defined in `_pdbx_struct_oper_list`, so it is ignored."),
+                                context.clone(),
+                            ));
+                        }
+                    }
+                }
+                products = next;
+            }
+            let operators = products
+                .into_iter()
+                .filter(|(id, _)| !id.is_empty())
+                .map(|(id, transformation)| AssemblyOperator::new(id, transformation))
+                .collect();
+            assembly
+                .generators
+                .push(AssemblyGenerator::new(chains, operators));
+        }
+        pdb.add_assembly(assembly);
+    }
+    errors
+}
+
+/// Split an operator expression, like '1,2,3', '(1-60)', or '(X0)(1-10,21-25)', into its groups
+/// of operator ids, with the ranges expanded.
+fn operator_expression(expression: &str) -> Vec<Vec<String>> {
+    let groups: Vec<&str> = if expression.contains('(') {
+        expression
+            .split(['(', ')'])
+            .map(str::trim)
+            .filter(|g| !g.is_empty())
+            .collect()
+    } else {
+        vec![expression]
+    };
+    groups
+        .iter()
+        .map(|group| {
+            group
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .flat_map(|item| {
+                    let range = item.split_once('-').and_then(|(start, end)| {
+                        Some((start.parse::<usize>().ok()?, end.parse::<usize>().ok()?))
+                    });
+                    match range {
+                        Some((start, end)) => (start..=end).map(|i| i.to_string()).collect(),
+                        None => vec![item.to_string()],
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Parse the missing residues and atoms from the `_pdbx_unobs_or_zero_occ_residues` and
+/// `_pdbx_unobs_or_zero_occ_atoms` categories and add them to the chains they belong to. Only
+/// unobserved residues and atoms are used, those with zero occupancy are present in the coordinates.
+fn parse_missing(input: &DataBlock, pdb: &mut PDB, label: bool) -> Vec<PDBError> {
+    let mut errors = Vec::new();
+    /// The model, chain id, residue name, and residue id of a row
+    type Location = (Option<usize>, String, String, (isize, Option<String>));
+    /// Get the location of the residue of the given row, if it is unobserved
+    fn location(
+        category: &Category<'_>,
+        row: usize,
+        label: bool,
+        errors: &mut Vec<PDBError>,
+    ) -> Option<Location> {
+        if category.get(row, "occupancy_flag", get_usize, errors) == Some(0) {
+            return None;
+        }
+        let chain = category.get_identifier(
+            row,
+            ("auth_asym_id", "label_asym_id"),
+            get_text,
+            label,
+            errors,
+        )?;
+        let name = category.get_identifier(
+            row,
+            ("auth_comp_id", "label_comp_id"),
+            get_text,
+            label,
+            errors,
+        )?;
+        let serial_number = category.get_identifier(
+            row,
+            ("auth_seq_id", "label_seq_id"),
+            get_isize,
+            label,
+            errors,
+        )?;
+        Some((
+            category.get(row, "PDB_model_num", get_usize, errors),
+            chain,
+            name,
+            (
+                serial_number,
+                category.get(row, "PDB_ins_code", get_text, errors),
+            ),
+        ))
+    }
+
+    let mut unknown = Vec::new();
+    if let Some(residues) = Category::find(input, "pdbx_unobs_or_zero_occ_residues") {
+        for row in 0..residues.rows.len() {
+            if let Some((model, chain, name, (serial, insertion_code))) =
+                location(&residues, row, label, &mut errors)
+            {
+                let missing = MissingResidue::new(name, (serial, insertion_code.as_deref()));
+                let mut found = false;
+                for c in pdb.chains_with_id_mut(model, &chain) {
+                    c.add_missing_residue(missing.clone());
+                    found = true;
+                }
+                if !found && !unknown.contains(&chain) {
+                    unknown.push(chain);
+                }
+            }
+        }
+    }
+    if let Some(atoms) = Category::find(input, "pdbx_unobs_or_zero_occ_atoms") {
+        // Group the atoms of consecutive rows of the same residue
+        let mut grouped: Vec<(Location, Vec<String>)> = Vec::new();
+        for row in 0..atoms.rows.len() {
+            let atom = atoms
+                .get(row, "auth_atom_id", get_text, &mut errors)
+                .or_else(|| atoms.get(row, "label_atom_id", get_text, &mut errors));
+            if let (Some(location), Some(atom)) = (location(&atoms, row, label, &mut errors), atom)
+            {
+                match grouped.last_mut() {
+                    Some((last, names)) if *last == location => names.push(atom),
+                    _ => grouped.push((location, vec![atom])),
+                }
+            }
+        }
+        for ((model, chain, name, (serial, insertion_code)), names) in grouped {
+            let missing = MissingAtoms::new(name, (serial, insertion_code.as_deref()), names);
+            let mut found = false;
+            for c in pdb.chains_with_id_mut(model, &chain) {
+                c.add_missing_atoms(missing.clone());
+                found = true;
+            }
+            if !found && !unknown.contains(&chain) {
+                unknown.push(chain);
+            }
+        }
+    }
+    for chain in unknown {
+        errors.push(PDBError::new(
+            ErrorLevel::LooseWarning,
+            "Missing residues of unknown chain",
+            format!("Missing residues or atoms of chain '{chain}' are listed, but this chain is not present in the coordinates, so these are ignored."),
+            Context::None,
+        ));
+    }
+    errors
+}
+
+/// Parse the entities from the `_entity` category, with the polymer details from `_entity_poly`
+/// and `_entity_poly_seq`, and the chains of non-polymers from `_pdbx_nonpoly_scheme`
+fn parse_entities(input: &DataBlock, pdb: &mut PDB, label: bool) -> Vec<PDBError> {
+    let mut errors = Vec::new();
+    let mut entities: Vec<Entity> = Vec::new();
+    if let Some(category) = Category::find(input, "entity") {
+        for row in 0..category.rows.len() {
+            if let Some(id) = category.get(row, "id", get_text, &mut errors) {
+                let mut entity = Entity::new(id);
+                entity.entity_type = category.get(row, "type", get_text, &mut errors);
+                entity.description = category.get(row, "pdbx_description", get_text, &mut errors);
+                entities.push(entity);
+            }
+        }
+    }
+    if let Some(category) = Category::find(input, "entity_poly") {
+        for row in 0..category.rows.len() {
+            let id = category.get(row, "entity_id", get_text, &mut errors);
+            if let Some(entity) = entities.iter_mut().find(|e| Some(&e.id) == id.as_ref()) {
+                entity.polymer_type = category.get(row, "type", get_text, &mut errors);
+                entity.canonical_sequence = category
+                    .get(row, "pdbx_seq_one_letter_code_can", get_text, &mut errors)
+                    .map(|s| s.split_whitespace().collect());
+                entity.chains = category
+                    .get(row, "pdbx_strand_id", get_text, &mut errors)
+                    .map(|chains| {
+                        chains
+                            .split(',')
+                            .map(str::trim)
+                            .filter(|s| !s.is_empty())
+                            .map(ToString::to_string)
+                            .collect()
+                    })
+                    .unwrap_or_default();
+            }
+        }
+    }
+    if let Some(category) = Category::find(input, "entity_poly_seq") {
+        let mut last = (None, None);
+        for row in 0..category.rows.len() {
+            let id = category.get(row, "entity_id", get_text, &mut errors);
+            let num = category.get(row, "num", get_usize, &mut errors);
+            let name = category.get(row, "mon_id", get_text, &mut errors);
+            // Only the first monomer is used for heterogeneous positions
+            if num.is_some() && (&id, num) == (&last.0, last.1) {
+                continue;
+            }
+            if let (Some(entity), Some(name)) = (
+                entities.iter_mut().find(|e| Some(&e.id) == id.as_ref()),
+                name,
+            ) {
+                entity.sequence.push(name);
             }
+            last = (id, num);
+        }
+    }
+    if let Some(category) = Category::find(input, "pdbx_nonpoly_scheme") {
+        for row in 0..category.rows.len() {
+            let id = category.get(row, "entity_id", get_text, &mut errors);
+            let chain = category.get_identifier(
+                row,
+                ("pdb_strand_id", "asym_id"),
+                get_text,
+                label,
+                &mut errors,
+            );
+            let name = category.get(row, "mon_id", get_text, &mut errors);
+            if let Some(entity) = entities.iter_mut().find(|e| Some(&e.id) == id.as_ref()) {
+                if let Some(chain) = chain.filter(|c| !entity.contains_chain(c)) {
+                    entity.chains.push(chain);
+                }
+                if let Some(name) = name.filter(|_| entity.sequence.is_empty()) {
+                    entity.sequence.push(name);
+                }
+            }
+        }
+    }
+    // The chains are given as author defined identifiers, so find the label chains from the atoms
+    if label {
+        for entity in &mut entities {
+            entity.chains = pdb
+                .chains()
+                .filter(|c| {
+                    c.residues()
+                        .any(|r| r.label_entity_id() == Some(entity.id.as_str()))
+                })
+                .map(|c| c.id().to_string())
+                .fold(Vec::new(), |mut chains, id| {
+                    if !chains.contains(&id) {
+                        chains.push(id);
+                    }
+                    chains
+                });
+        }
+    }
+    for entity in entities {
+        pdb.add_entity(entity);
+    }
+    errors
+}
+
+/// Parse the positions of the residues in the sequences of their entities from the
+/// `_pdbx_poly_seq_scheme` category. If this category is not given, the residues are aligned to
+/// the sequences instead.
+fn parse_sequence_scheme(input: &DataBlock, pdb: &mut PDB, label: bool) -> Vec<PDBError> {
+    let mut errors = Vec::new();
+    let category = if let Some(c) = Category::find(input, "pdbx_poly_seq_scheme") {
+        c
+    } else {
+        pdb.align_sequences();
+        return errors;
+    };
+    let mut indices = HashMap::new();
+    for row in 0..category.rows.len() {
+        // Residues absent from the coordinates do not have author defined identifiers
+        if category
+            .get(row, "auth_mon_id", get_text, &mut errors)
+            .is_none()
+        {
+            continue;
+        }
+        let chain = category.get_identifier(
+            row,
+            ("pdb_strand_id", "asym_id"),
+            get_text,
+            label,
+            &mut errors,
+        );
+        let serial_number = if label {
+            category.get(row, "seq_id", get_isize, &mut errors)
         } else {
-            Ok(None)
+            category
+                .get(row, "pdb_seq_num", get_isize, &mut errors)
+                .or_else(|| category.get(row, "auth_seq_num", get_isize, &mut errors))
+        };
+        let insertion_code = category.get(row, "pdb_ins_code", get_text, &mut errors);
+        if let (Some(chain), Some(serial_number), Some(seq_id)) = (
+            chain,
+            serial_number,
+            category.get(row, "seq_id", get_usize, &mut errors),
+        ) {
+            if seq_id > 0 {
+                indices.insert((chain, serial_number, insertion_code), seq_id - 1);
+            }
+        }
+    }
+    for chain in pdb.chains_mut() {
+        let id = chain.id().to_string();
+        for residue in chain.residues_mut() {
+            if let Some(index) = indices.get(&(
+                id.clone(),
+                residue.serial_number(),
+                residue.insertion_code().map(ToString::to_string),
+            )) {
+                residue.set_sequence_index(Some(*index));
+            }
         }
-    }))
+    }
+    errors
 }
 
-/// Get the Numeric content of the value, if available, as an isize
-fn get_isize(
-    value: &Value,
-    context: &Context,
-    column: Option<&str>,
-) -> Result<Option<isize>, PDBError> {
-    flatten_result(get_f64(value, context, column).map(|result| {
-        if let Some(num) = result {
-            #[allow(
-                clippy::cast_precision_loss,
-                clippy::cast_possible_truncation,
-                clippy::float_cmp
-            )]
-            if (isize::MIN as f64..isize::MAX as f64).contains(&num) && num.trunc() == num {
-                Ok(Some(num as isize))
+/// Parse the descriptions of the chemical components from the `_chem_comp` category
+fn parse_chemical_components(input: &DataBlock, pdb: &mut PDB) -> Vec<PDBError> {
+    let mut errors = Vec::new();
+    if let Some(components) = Category::find(input, "chem_comp") {
+        for row in 0..components.rows.len() {
+            if let Some(id) = components.get(row, "id", get_text, &mut errors) {
+                let mut component = ChemicalComponent::new(id);
+                component.name = components.get(row, "name", get_text, &mut errors);
+                component.synonyms = components
+                    .get(row, "pdbx_synonyms", get_text, &mut errors)
+                    .map(|synonyms| {
+                        synonyms
+                            .split(';')
+                            .map(str::trim)
+                            .filter(|s| !s.is_empty())
+                            .map(ToString::to_string)
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                component.formula = components.get(row, "formula", get_text, &mut errors);
+                pdb.add_chemical_component(component);
+            }
+        }
+    }
+    errors
+}
+
+/// Parse the sites from the `_struct_site` category with the residues from `_struct_site_gen`
+fn parse_sites(input: &DataBlock, pdb: &mut PDB, label: bool) -> Vec<PDBError> {
+    let mut errors = Vec::new();
+    let mut sites: Vec<Site> = Vec::new();
+    if let Some(category) = Category::find(input, "struct_site") {
+        for row in 0..category.rows.len() {
+            if let Some(id) = category.get(row, "id", get_text, &mut errors) {
+                let mut site = Site::new(id);
+                site.evidence = category.get(row, "pdbx_evidence_code", get_text, &mut errors);
+                site.details = category.get(row, "details", get_text, &mut errors);
+                sites.push(site);
+            }
+        }
+    }
+    if let Some(category) = Category::find(input, "struct_site_gen") {
+        for row in 0..category.rows.len() {
+            let mut get_either = |column: &str| {
+                category.get_identifier(
+                    row,
+                    (&format!("auth_{column}"), &format!("label_{column}")),
+                    get_text,
+                    label,
+                    &mut errors,
+                )
+            };
+            let chain = get_either("asym_id");
+            let name = get_either("comp_id");
+            let serial_number = category.get_identifier(
+                row,
+                ("auth_seq_id", "label_seq_id"),
+                get_isize,
+                label,
+                &mut errors,
+            );
+            let insertion_code = category.get(row, "pdbx_auth_ins_code", get_text, &mut errors);
+            if let (Some(id), Some(chain), Some(name), Some(serial_number)) = (
+                category.get(row, "site_id", get_text, &mut errors),
+                chain,
+                name,
+                serial_number,
+            ) {
+                let residue =
+                    ResidueReference::new(chain, (serial_number, insertion_code.as_deref()), name);
+                if let Some(site) = sites.iter_mut().find(|s| s.id == id) {
+                    site.residues.push(residue);
+                } else {
+                    let mut site = Site::new(id);
+                    site.residues.push(residue);
+                    sites.push(site);
+                }
+            }
+        }
+    }
+    for site in sites {
+        pdb.add_site(site);
+    }
+    errors
+}
+
+/// Parse the cis peptides from the `_struct_mon_prot_cis` category
+fn parse_cis_peptides(input: &DataBlock, pdb: &mut PDB, label: bool) -> Vec<PDBError> {
+    let mut errors = Vec::new();
+    /// Get the residue with the given suffix ('' for the first and '_2' for the second residue)
+    fn residue(
+        category: &Category<'_>,
+        row: usize,
+        suffix: &str,
+        label: bool,
+        errors: &mut Vec<PDBError>,
+    ) -> Option<ResidueReference> {
+        let prefix = if suffix.is_empty() { "" } else { "pdbx_" };
+        let columns = |column: &str| {
+            (
+                format!("{prefix}auth_{column}{suffix}"),
+                format!("{prefix}label_{column}{suffix}"),
+            )
+        };
+        let mut get_either = |column: &str| {
+            let (auth, label_column) = columns(column);
+            category.get_identifier(row, (&auth, &label_column), get_text, label, errors)
+        };
+        let chain = get_either("asym_id")?;
+        let name = get_either("comp_id")?;
+        let (auth, label_column) = columns("seq_id");
+        let serial_number =
+            category.get_identifier(row, (&auth, &label_column), get_isize, label, errors)?;
+        let insertion_code =
+            category.get(row, &format!("pdbx_PDB_ins_code{suffix}"), get_text, errors);
+        Some(ResidueReference::new(
+            chain,
+            (serial_number, insertion_code.as_deref()),
+            name,
+        ))
+    }
+    if let Some(category) = Category::find(input, "struct_mon_prot_cis") {
+        let first_model = pdb.models().next().map_or(1, Model::serial_number);
+        for row in 0..category.rows.len() {
+            if let (Some(start), Some(end)) = (
+                residue(&category, row, "", label, &mut errors),
+                residue(&category, row, "_2", label, &mut errors),
+            ) {
+                let mut cis_peptide = CisPeptide::new(
+                    category
+                        .get(row, "pdbx_id", get_usize, &mut errors)
+                        .unwrap_or(row + 1),
+                    category
+                        .get(row, "pdbx_PDB_model_num", get_usize, &mut errors)
+                        .unwrap_or(first_model),
+                    start,
+                    end,
+                );
+                cis_peptide.angle = category.get(row, "pdbx_omega_angle", get_f64, &mut errors);
+                pdb.add_cis_peptide(cis_peptide);
+            }
+        }
+    }
+    errors
+}
+
+/// Parse the secondary structure from the `_struct_conf` (helices) and `_struct_sheet_range` (strands)
+/// categories, with the additional information about the sheets from `_struct_sheet`,
+/// `_struct_sheet_order`, and `_pdbx_struct_sheet_hbond`.
+fn parse_secondary_structure(input: &DataBlock, pdb: &mut PDB, label: bool) -> Vec<PDBError> {
+    let mut errors = Vec::new();
+    let missing_residue = |category: &str, row: usize| {
+        PDBError::new(
+            ErrorLevel::LooseWarning,
+            "Secondary structure incomplete",
+            "The start or end residue of this secondary structure element could not be found, so the element is ignored.",
+            Context::show(format!("_{category} row: {}", row + 1)),
+        )
+    };
+
+    if let Some(conf) = Category::find(input, "struct_conf") {
+        for row in 0..conf.rows.len() {
+            // Only helices are of interest, turns are not saved
+            if !conf
+                .get(row, "conf_type_id", get_text, &mut errors)
+                .map_or(false, |t| t.starts_with("HELX"))
+            {
+                continue;
+            }
+            let start = conf.get_residue(row, "beg", "pdbx_beg_PDB_ins_code", label, &mut errors);
+            let end = conf.get_residue(row, "end", "pdbx_end_PDB_ins_code", label, &mut errors);
+            let (start, end) = if let (Some(start), Some(end)) = (start, end) {
+                (start, end)
             } else {
-                Err(PDBError::new(
-                    ErrorLevel::InvalidatingError,
-                    "Not an integer",
-                    column.map_or(String::new(), |v| {
-                        format!("The '{v}' column should a singed integer.")
-                    }),
-                    context.clone(),
-                ))
+                errors.push(missing_residue("struct_conf", row));
+                continue;
+            };
+            let id = conf
+                .get(row, "pdbx_PDB_helix_id", get_text, &mut errors)
+                .or_else(|| conf.get(row, "id", get_text, &mut errors))
+                .unwrap_or_default();
+            let class = conf
+                .get(row, "pdbx_PDB_helix_class", get_usize, &mut errors)
+                .and_then(HelixClass::from_number)
+                .unwrap_or_default();
+            let mut helix = Helix::new(row + 1, id, start, end, class);
+            helix.comment = conf.get(row, "details", get_text, &mut errors);
+            helix.length = conf.get(row, "pdbx_PDB_helix_length", get_usize, &mut errors);
+            pdb.add_secondary_structure(SecondaryStructure::Helix(helix));
+        }
+    }
+
+    if let Some(ranges) = Category::find(input, "struct_sheet_range") {
+        let sheets = Category::find(input, "struct_sheet");
+        let order = Category::find(input, "struct_sheet_order");
+        let hbonds = Category::find(input, "pdbx_struct_sheet_hbond");
+        // Find the row in the given category for the given sheet and (second) strand
+        let find_row = |category: &Option<Category<'_>>,
+                        strand_column: &str,
+                        sheet_id: &str,
+                        strand_id: &str,
+                        errors: &mut Vec<PDBError>| {
+            category.as_ref().and_then(|c| {
+                (0..c.rows.len()).find(|row| {
+                    c.get(*row, "sheet_id", get_text, errors).as_deref() == Some(sheet_id)
+                        && c.get(*row, strand_column, get_text, errors).as_deref()
+                            == Some(strand_id)
+                })
+            })
+        };
+
+        let sheet_ids: Vec<String> = (0..ranges.rows.len())
+            .map(|row| {
+                ranges
+                    .get(row, "sheet_id", get_text, &mut errors)
+                    .unwrap_or_default()
+            })
+            .collect();
+        for (row, sheet_id) in sheet_ids.iter().enumerate() {
+            let start = ranges.get_residue(row, "beg", "pdbx_beg_PDB_ins_code", label, &mut errors);
+            let end = ranges.get_residue(row, "end", "pdbx_end_PDB_ins_code", label, &mut errors);
+            let (start, end) = if let (Some(start), Some(end)) = (start, end) {
+                (start, end)
+            } else {
+                errors.push(missing_residue("struct_sheet_range", row));
+                continue;
+            };
+            let strand_id = ranges
+                .get(row, "id", get_text, &mut errors)
+                .unwrap_or_default();
+            let strand_number = strand_id
+                .parse()
+                .unwrap_or_else(|_| sheet_ids[..=row].iter().filter(|s| *s == sheet_id).count());
+            let strand_count = sheets
+                .as_ref()
+                .and_then(|sheets| {
+                    (0..sheets.rows.len())
+                        .find(|r| {
+                            sheets.get(*r, "id", get_text, &mut errors).as_ref() == Some(sheet_id)
+                        })
+                        .and_then(|r| sheets.get(r, "number_strands", get_usize, &mut errors))
+                })
+                .unwrap_or_else(|| sheet_ids.iter().filter(|s| *s == sheet_id).count());
+            let sense = find_row(&order, "range_id_2", sheet_id, &strand_id, &mut errors)
+                .and_then(|r| {
+                    order
+                        .as_ref()
+                        .and_then(|o| o.get(r, "sense", get_text, &mut errors))
+                })
+                .map_or(StrandSense::First, |sense| {
+                    if sense == "parallel" {
+                        StrandSense::Parallel
+                    } else {
+                        StrandSense::AntiParallel
+                    }
+                });
+            let mut strand = Strand::new(strand_number, sheet_id, strand_count, start, end, sense);
+            if let (Some(r), Some(hbonds)) = (
+                find_row(&hbonds, "range_id_2", sheet_id, &strand_id, &mut errors),
+                &hbonds,
+            ) {
+                if let (Some(current_atom), Some(current), Some(previous_atom), Some(previous)) = (
+                    hbonds.get(r, "range_2_auth_atom_id", get_text, &mut errors),
+                    hbonds.get_residue(r, "range_2", "range_2_PDB_ins_code", label, &mut errors),
+                    hbonds.get(r, "range_1_auth_atom_id", get_text, &mut errors),
+                    hbonds.get_residue(r, "range_1", "range_1_PDB_ins_code", label, &mut errors),
+                ) {
+                    strand.registration = Some(StrandRegistration {
+                        current_atom,
+                        current,
+                        previous_atom,
+                        previous,
+                    });
+                }
+            }
+            pdb.add_secondary_structure(SecondaryStructure::Strand(strand));
+        }
+    }
+    errors
+}
+
+/// Parse the bonds from the `_struct_conn` category. The type of every bond is given by its
+/// `conn_type_id`, the atoms are looked up in every model.
+fn parse_bonds(input: &DataBlock, pdb: &mut PDB, label: bool) -> Vec<PDBError> {
+    let mut errors = Vec::new();
+    let connections = if let Some(c) = Category::find(input, "struct_conn") {
+        c
+    } else {
+        return errors;
+    };
+    for row in 0..connections.rows.len() {
+        let context = Context::show(format!("_struct_conn row: {}", row + 1));
+        let bond = connections
+            .get(row, "conn_type_id", get_text, &mut errors)
+            .and_then(Bond::from_conn_type);
+        let bond = if let Some(bond) = bond {
+            bond
+        } else {
+            errors.push(PDBError::new(
+                ErrorLevel::LooseWarning,
+                "Unknown bond type",
+                "The conn_type_id of this bond is missing or not one of the known types, so the bond is ignored.",
+                context,
+            ));
+            continue;
+        };
+        let mut partner = |n: usize| {
+            let residue = connections.get_residue(
+                row,
+                &format!("ptnr{n}"),
+                &format!("pdbx_ptnr{n}_PDB_ins_code"),
+                label,
+                &mut errors,
+            );
+            let atom = connections.get(
+                row,
+                &format!("ptnr{n}_label_atom_id"),
+                get_text,
+                &mut errors,
+            );
+            let alt_loc = connections.get(
+                row,
+                &format!("pdbx_ptnr{n}_label_alt_id"),
+                get_text,
+                &mut errors,
+            );
+            (residue, atom, alt_loc)
+        };
+        let (partner1, partner2) = (partner(1), partner(2));
+        let details = BondDetails {
+            id: connections.get(row, "id", get_text, &mut errors),
+            symmetry_operator_1: connections.get(row, "ptnr1_symmetry", get_text, &mut errors),
+            symmetry_operator_2: connections.get(row, "ptnr2_symmetry", get_text, &mut errors),
+            distance: connections.get(row, "pdbx_dist_value", get_f64, &mut errors),
+            ..BondDetails::default()
+        };
+        let complete = if let (
+            (Some(residue1), Some(atom1), alt_loc1),
+            (Some(residue2), Some(atom2), alt_loc2),
+        ) = (partner1, partner2)
+        {
+            let (found, complete) = pdb.find_bond_partners(
+                (&atom1, alt_loc1.as_deref(), &residue1),
+                (&atom2, alt_loc2.as_deref(), &residue2),
+            );
+            for (counter1, counter2, _) in found {
+                pdb.add_bond_counters(counter1, counter2, bond, Some(details.clone()));
             }
+            complete
         } else {
-            Ok(None)
+            false
+        };
+        if !complete {
+            errors.push(PDBError::new(
+                ErrorLevel::GeneralWarning,
+                "Could not find a bond partner",
+                "One of the atoms could not be found while parsing a bond from the _struct_conn category.",
+                context,
+            ));
         }
-    }))
+    }
+    errors
 }
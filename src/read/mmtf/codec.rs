@@ -0,0 +1,506 @@
+use crate::msgpack::MsgPack;
+
+/// The content of an MMTF array after decoding it
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Array {
+    /// Integers, as given by the integer codecs
+    Int(Vec<i64>),
+    /// Floating point numbers, as given by the float and fixed point codecs
+    Float(Vec<f64>),
+    /// Strings, as given by the string codecs, the characters of codec 6 are given as separate
+    /// strings with the empty string for the null character
+    Str(Vec<String>),
+}
+
+impl Array {
+    /// Get the content as integers, fails for floats or strings
+    pub(crate) fn into_ints(self) -> Option<Vec<i64>> {
+        match self {
+            Array::Int(ints) => Some(ints),
+            _ => None,
+        }
+    }
+
+    /// Get the content as floats, integers are converted
+    #[allow(clippy::cast_precision_loss)]
+    pub(crate) fn into_floats(self) -> Option<Vec<f64>> {
+        match self {
+            Array::Int(ints) => Some(ints.into_iter().map(|i| i as f64).collect()),
+            Array::Float(floats) => Some(floats),
+            Array::Str(_) => None,
+        }
+    }
+
+    /// Get the content as strings, fails for numbers except for empty arrays
+    pub(crate) fn into_strings(self) -> Option<Vec<String>> {
+        match self {
+            Array::Str(strings) => Some(strings),
+            Array::Int(ints) if ints.is_empty() => Some(Vec::new()),
+            _ => None,
+        }
+    }
+}
+
+/// Decode an MMTF array, given either as a binary blob encoded with one of the MMTF codecs or as
+/// a plain MessagePack array. Returns an error message if the array could not be decoded.
+pub(crate) fn decode_array(value: &MsgPack) -> Result<Array, String> {
+    match value {
+        MsgPack::Bin(bytes) => decode_binary(bytes),
+        MsgPack::Array(items) => {
+            if let Some(ints) = items.iter().map(MsgPack::as_int).collect::<Option<_>>() {
+                Ok(Array::Int(ints))
+            } else if let Some(floats) = items.iter().map(MsgPack::as_float).collect::<Option<_>>()
+            {
+                Ok(Array::Float(floats))
+            } else if let Some(strings) = items
+                .iter()
+                .map(|i| i.as_str().map(ToString::to_string))
+                .collect::<Option<_>>()
+            {
+                Ok(Array::Str(strings))
+            } else {
+                Err("The array contains values of different types".to_string())
+            }
+        }
+        _ => Err("The value is not an array or binary encoded array".to_string()),
+    }
+}
+
+/// Decode a binary MMTF array, it starts with a header of three big endian 32 bit integers
+/// giving the codec, the length of the decoded array and a codec specific parameter.
+#[allow(clippy::cast_precision_loss)]
+fn decode_binary(bytes: &[u8]) -> Result<Array, String> {
+    if bytes.len() < 12 {
+        return Err("The binary array is shorter than its header".to_string());
+    }
+    let codec = read_i32(&bytes[0..4]);
+    let length = usize::try_from(read_i32(&bytes[4..8])).map_err(|_| "Negative array length")?;
+    let param = read_i32(&bytes[8..12]);
+    let data = &bytes[12..];
+    let divide = |ints: Vec<i64>| {
+        if param == 0 {
+            Err("The divisor of the array is zero".to_string())
+        } else {
+            Ok(Array::Float(
+                ints.into_iter()
+                    .map(|i| i as f64 / f64::from(param))
+                    .collect(),
+            ))
+        }
+    };
+    let array = match codec {
+        1 => Array::Float(
+            data.chunks_exact(4)
+                .map(|c| f64::from(f32::from_bits(read_u32(c))))
+                .collect(),
+        ),
+        2 => Array::Int(int8(data)),
+        3 => Array::Int(int16(data)),
+        4 => Array::Int(int32(data)),
+        5 => {
+            let size = usize::try_from(param)
+                .ok()
+                .filter(|s| *s > 0)
+                .ok_or("The string length of the array is not positive")?;
+            Array::Str(
+                data.chunks_exact(size)
+                    .map(|c| {
+                        String::from_utf8_lossy(c)
+                            .trim_end_matches('\0')
+                            .to_string()
+                    })
+                    .collect(),
+            )
+        }
+        6 => Array::Str(
+            run_length(int32(data), length)?
+                .into_iter()
+                .map(|c| {
+                    u32::try_from(c)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .filter(|c| *c != '\0')
+                        .map(String::from)
+                        .unwrap_or_default()
+                })
+                .collect(),
+        ),
+        7 => Array::Int(run_length(int32(data), length)?),
+        8 => Array::Int(delta(run_length(int32(data), length)?)),
+        9 => divide(run_length(int32(data), length)?)?,
+        10 => divide(delta(recursive_index(&int16(data), i16::MIN, i16::MAX)))?,
+        11 => divide(int16(data))?,
+        12 => divide(recursive_index(&int16(data), i16::MIN, i16::MAX))?,
+        13 => divide(recursive_index(&int8(data), i8::MIN, i8::MAX))?,
+        14 => Array::Int(recursive_index(&int16(data), i16::MIN, i16::MAX)),
+        15 => Array::Int(recursive_index(&int8(data), i8::MIN, i8::MAX)),
+        _ => return Err(format!("Unknown codec {codec}")),
+    };
+    let decoded_length = match &array {
+        Array::Int(a) => a.len(),
+        Array::Float(a) => a.len(),
+        Array::Str(a) => a.len(),
+    };
+    if decoded_length == length {
+        Ok(array)
+    } else {
+        Err(format!(
+            "The array has {decoded_length} values instead of the {length} given in its header"
+        ))
+    }
+}
+
+/// Read a big endian 32 bit unsigned integer from the given four bytes
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// Read a big endian 32 bit signed integer from the given four bytes
+fn read_i32(bytes: &[u8]) -> i32 {
+    i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// Read the bytes as signed 8 bit integers
+fn int8(data: &[u8]) -> Vec<i64> {
+    data.iter()
+        .map(|b| i64::from(i8::from_be_bytes([*b])))
+        .collect()
+}
+
+/// Read the bytes as big endian signed 16 bit integers
+fn int16(data: &[u8]) -> Vec<i64> {
+    data.chunks_exact(2)
+        .map(|c| i64::from(i16::from_be_bytes([c[0], c[1]])))
+        .collect()
+}
+
+/// Read the bytes as big endian signed 32 bit integers
+fn int32(data: &[u8]) -> Vec<i64> {
+    data.chunks_exact(4)
+        .map(|c| i64::from(read_i32(c)))
+        .collect()
+}
+
+/// Expand pairs of a value and the number of times it is repeated, the output cannot be longer
+/// than the given length of the array
+fn run_length(pairs: Vec<i64>, length: usize) -> Result<Vec<i64>, String> {
+    if pairs.len() % 2 != 0 {
+        return Err("The run length encoded array has an odd number of values".to_string());
+    }
+    let mut output = Vec::new();
+    for pair in pairs.chunks_exact(2) {
+        let count = usize::try_from(pair[1]).map_err(|_| "Negative run length")?;
+        if count > length - output.len() {
+            return Err(format!(
+                "The run length encoded array has more values than the {length} given in its header"
+            ));
+        }
+        output.extend(std::iter::repeat(pair[0]).take(count));
+    }
+    Ok(output)
+}
+
+/// Undo delta encoding, every value is given as the difference with the previous value
+fn delta(mut values: Vec<i64>) -> Vec<i64> {
+    for index in 1..values.len() {
+        values[index] += values[index - 1];
+    }
+    values
+}
+
+/// Undo recursive indexing, values outside of the range of the small integer type are given as
+/// a sum of values, where all but the last are the minimum or maximum of the type
+fn recursive_index(values: &[i64], min: impl Into<i64>, max: impl Into<i64>) -> Vec<i64> {
+    let (min, max) = (min.into(), max.into());
+    let mut output = Vec::new();
+    let mut sum = 0;
+    for value in values {
+        sum += value;
+        if *value != min && *value != max {
+            output.push(sum);
+            sum = 0;
+        }
+    }
+    output
+}
+
+/// Encode an array with the given MMTF codec and parameter, the data is given encoded already
+fn binary(codec: i32, length: usize, param: i32, data: &[u8]) -> MsgPack {
+    let mut bytes = Vec::with_capacity(data.len() + 12);
+    bytes.extend(codec.to_be_bytes());
+    bytes.extend(i32::try_from(length).unwrap_or(i32::MAX).to_be_bytes());
+    bytes.extend(param.to_be_bytes());
+    bytes.extend(data);
+    MsgPack::Bin(bytes)
+}
+
+/// Write the values as big endian 32 bit integers, values outside the range are clamped
+fn write_int32(values: &[i64]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|v| clamp(*v, i32::MIN, i32::MAX).to_be_bytes())
+        .collect()
+}
+
+/// Convert the value to a smaller integer type, values outside the range are clamped
+fn clamp<T: TryFrom<i64>>(value: i64, min: T, max: T) -> T {
+    T::try_from(value).unwrap_or(if value < 0 { min } else { max })
+}
+
+/// Run length encode the values into pairs of a value and the number of repeats
+fn to_run_length(values: &[i64]) -> Vec<i64> {
+    let mut pairs: Vec<i64> = Vec::new();
+    for value in values {
+        match pairs.len() {
+            n if n >= 2 && pairs[n - 2] == *value => pairs[n - 1] += 1,
+            _ => pairs.extend([*value, 1]),
+        }
+    }
+    pairs
+}
+
+/// Delta encode the values, every value is given as the difference with the previous value
+fn to_delta(values: &[i64]) -> Vec<i64> {
+    let mut previous = 0;
+    values
+        .iter()
+        .map(|v| {
+            let difference = v - previous;
+            previous = *v;
+            difference
+        })
+        .collect()
+}
+
+/// Recursively index the values into the range of 16 bit integers
+fn to_recursive_index(values: &[i64]) -> Vec<i16> {
+    let (min, max) = (i64::from(i16::MIN), i64::from(i16::MAX));
+    let mut output = Vec::new();
+    for value in values {
+        let mut value = *value;
+        while value >= max {
+            output.push(i16::MAX);
+            value -= max;
+        }
+        while value <= min {
+            output.push(i16::MIN);
+            value -= min;
+        }
+        output.push(clamp(value, i16::MIN, i16::MAX));
+    }
+    output
+}
+
+/// Convert the values to fixed point integers with the given divisor
+#[allow(clippy::cast_possible_truncation)]
+fn to_fixed_point(values: &[f64], divisor: i32) -> Vec<i64> {
+    values
+        .iter()
+        .map(|v| (v * f64::from(divisor)).round() as i64)
+        .collect()
+}
+
+/// Encode signed 8 bit integers (codec 2), values outside the range are clamped
+pub(crate) fn encode_int8(values: &[i64]) -> MsgPack {
+    let data: Vec<u8> = values
+        .iter()
+        .flat_map(|v| clamp(*v, i8::MIN, i8::MAX).to_be_bytes())
+        .collect();
+    binary(2, values.len(), 0, &data)
+}
+
+/// Encode signed 32 bit integers (codec 4), values outside the range are clamped
+pub(crate) fn encode_int32(values: &[i64]) -> MsgPack {
+    binary(4, values.len(), 0, &write_int32(values))
+}
+
+/// Encode strings with a fixed length (codec 5), shorter strings are padded with null
+/// characters and longer strings are cut
+pub(crate) fn encode_strings(values: &[String], size: usize) -> MsgPack {
+    let mut data = Vec::with_capacity(values.len() * size);
+    for value in values {
+        let mut bytes = value.as_bytes().to_vec();
+        bytes.resize(size, 0);
+        data.extend(bytes);
+    }
+    binary(
+        5,
+        values.len(),
+        i32::try_from(size).unwrap_or(i32::MAX),
+        &data,
+    )
+}
+
+/// Encode single characters with run length encoding (codec 6), `None` is given as the null
+/// character
+pub(crate) fn encode_chars(values: &[Option<char>]) -> MsgPack {
+    let codes: Vec<i64> = values
+        .iter()
+        .map(|c| i64::from(c.map_or(0, u32::from)))
+        .collect();
+    binary(6, values.len(), 0, &write_int32(&to_run_length(&codes)))
+}
+
+/// Encode integers with delta and run length encoding (codec 8)
+pub(crate) fn encode_delta_run_length(values: &[i64]) -> MsgPack {
+    binary(
+        8,
+        values.len(),
+        0,
+        &write_int32(&to_run_length(&to_delta(values))),
+    )
+}
+
+/// Encode floats as fixed point integers with the given divisor and run length encoding
+/// (codec 9)
+pub(crate) fn encode_fixed_run_length(values: &[f64], divisor: i32) -> MsgPack {
+    binary(
+        9,
+        values.len(),
+        divisor,
+        &write_int32(&to_run_length(&to_fixed_point(values, divisor))),
+    )
+}
+
+/// Encode floats as fixed point integers with the given divisor, delta encoding and recursive
+/// indexing into 16 bit integers (codec 10)
+pub(crate) fn encode_fixed_delta(values: &[f64], divisor: i32) -> MsgPack {
+    let data: Vec<u8> = to_recursive_index(&to_delta(&to_fixed_point(values, divisor)))
+        .into_iter()
+        .flat_map(i16::to_be_bytes)
+        .collect();
+    binary(10, values.len(), divisor, &data)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    /// Decode a binary array given its header and data
+    fn decode(codec: i32, length: i32, param: i32, data: &[u8]) -> Array {
+        let mut bytes = Vec::new();
+        bytes.extend(codec.to_be_bytes());
+        bytes.extend(length.to_be_bytes());
+        bytes.extend(param.to_be_bytes());
+        bytes.extend(data);
+        decode_array(&MsgPack::Bin(bytes)).unwrap()
+    }
+
+    #[test]
+    fn specification_examples() {
+        // The examples given in the MMTF specification
+        assert_eq!(
+            decode(4, 2, 0, &[0, 0, 0, 1, 0, 0, 0, 2]),
+            Array::Int(vec![1, 2])
+        );
+        assert_eq!(
+            decode(7, 5, 0, &[0, 0, 0, 1, 0, 0, 0, 4, 0, 0, 0, 2, 0, 0, 0, 1]),
+            Array::Int(vec![1, 1, 1, 1, 2])
+        );
+        assert_eq!(
+            decode(8, 7, 0, &[0, 0, 0, 1, 0, 0, 0, 7]),
+            Array::Int(vec![1, 2, 3, 4, 5, 6, 7])
+        );
+        assert_eq!(
+            decode(5, 2, 4, b"A\0\0\0BB\0\0"),
+            Array::Str(vec!["A".to_string(), "BB".to_string()])
+        );
+        assert_eq!(
+            decode(6, 3, 0, &[0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 65, 0, 0, 0, 1]),
+            Array::Str(vec![String::new(), String::new(), "A".to_string()])
+        );
+        assert_eq!(
+            decode(14, 3, 0, &[0x7f, 0xff, 0, 1, 0, 2, 0x80, 0, 0xff, 0xff]),
+            Array::Int(vec![32768, 2, -32769])
+        );
+        assert_eq!(
+            decode(10, 3, 100, &[0, 100, 0, 100, 0xff, 0x9c]),
+            Array::Float(vec![1.0, 2.0, 1.0])
+        );
+        assert_eq!(
+            decode(1, 1, 0, &1.5_f32.to_be_bytes()),
+            Array::Float(vec![1.5])
+        );
+    }
+
+    #[test]
+    fn codecs_not_written() {
+        // The codecs that are only found in files written by other software
+        assert_eq!(decode(2, 2, 0, &[0x01, 0xff]), Array::Int(vec![1, -1]));
+        assert_eq!(
+            decode(3, 2, 0, &[0x01, 0x00, 0xff, 0xfe]),
+            Array::Int(vec![256, -2])
+        );
+        assert_eq!(
+            decode(9, 3, 100, &[0, 0, 0, 50, 0, 0, 0, 3]),
+            Array::Float(vec![0.5, 0.5, 0.5])
+        );
+        assert_eq!(
+            decode(11, 2, 10, &[0x00, 0x0f, 0xff, 0xf1]),
+            Array::Float(vec![1.5, -1.5])
+        );
+        assert_eq!(
+            decode(12, 2, 10, &[0x7f, 0xff, 0x00, 0x05, 0x00, 0x01]),
+            Array::Float(vec![3277.2, 0.1])
+        );
+        assert_eq!(
+            decode(13, 2, 100, &[0x7f, 0x7f, 0x02, 0x80, 0xff]),
+            Array::Float(vec![2.56, -1.29])
+        );
+        assert_eq!(
+            decode(15, 2, 0, &[0x7f, 0x01, 0x80, 0x80, 0x00]),
+            Array::Int(vec![128, -256])
+        );
+    }
+
+    #[test]
+    fn plain_arrays() {
+        let array = |items: Vec<MsgPack>| decode_array(&MsgPack::Array(items)).unwrap();
+        assert_eq!(
+            array(vec![1_i64.into(), 2_i64.into()]),
+            Array::Int(vec![1, 2])
+        );
+        assert_eq!(
+            array(vec![MsgPack::Float(0.5), 2_i64.into()]),
+            Array::Float(vec![0.5, 2.0])
+        );
+        assert_eq!(array(vec!["A".into()]), Array::Str(vec!["A".to_string()]));
+        assert!(decode_array(&MsgPack::Array(vec!["A".into(), 1_i64.into()])).is_err());
+        assert!(decode_array(&MsgPack::Bin(vec![0, 0, 0, 99, 0, 0, 0, 0, 0, 0, 0, 0])).is_err());
+        // A run length that is longer than the array is not expanded
+        let mut bytes = vec![0, 0, 0, 7, 0, 0, 0, 2, 0, 0, 0, 0];
+        bytes.extend([0, 0, 0, 1, 0x7f, 0xff, 0xff, 0xff]);
+        assert!(decode_array(&MsgPack::Bin(bytes)).is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        let ints = vec![1, 1, 1, 5, -40000, 70000, 0, 0];
+        let decoded = |value: MsgPack| decode_array(&value).unwrap();
+        assert_eq!(decoded(encode_int32(&ints)), Array::Int(ints.clone()));
+        assert_eq!(
+            decoded(encode_delta_run_length(&ints)),
+            Array::Int(ints.clone())
+        );
+        assert_eq!(
+            decoded(encode_int8(&[1, -2, 300])),
+            Array::Int(vec![1, -2, 127])
+        );
+        let floats = vec![1.234, -56.789, 1000.5, 0.0, 32.767, -32.768];
+        assert_eq!(
+            decoded(encode_fixed_delta(&floats, 1000)),
+            Array::Float(floats.clone())
+        );
+        assert_eq!(
+            decoded(encode_fixed_run_length(&[0.5, 0.5, 1.0], 100)),
+            Array::Float(vec![0.5, 0.5, 1.0])
+        );
+        let strings = vec!["A".to_string(), "ABCD".to_string(), String::new()];
+        assert_eq!(decoded(encode_strings(&strings, 4)), Array::Str(strings));
+        assert_eq!(
+            decoded(encode_chars(&[None, Some('A'), Some('A')])),
+            Array::Str(vec![String::new(), "A".to_string(), "A".to_string()])
+        );
+    }
+}
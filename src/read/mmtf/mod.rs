@@ -0,0 +1,7 @@
+/// Encode and decode the binary arrays of MMTF files
+mod codec;
+/// Parse MMTF files into PDB structures
+mod parser;
+
+pub(crate) use codec::*;
+pub(crate) use parser::*;
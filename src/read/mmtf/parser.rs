@@ -0,0 +1,647 @@
+use super::codec::{decode_array, Array};
+use crate::error::*;
+use crate::msgpack::{self, MsgPack};
+use crate::read::general::{read_bytes, ReadResult};
+use crate::reference_tables;
+use crate::structs::*;
+use crate::validate::*;
+use crate::ReadOptions;
+use std::fs::File;
+
+/// Parse the given MMTF file into a PDB struct with [`ReadOptions`].
+pub(crate) fn open_mmtf_with_options(
+    filename: impl AsRef<str>,
+    options: &ReadOptions,
+) -> ReadResult {
+    let filename = filename.as_ref();
+    let file = if let Ok(f) = File::open(filename) {
+        f
+    } else {
+        return Err(vec![PDBError::new(ErrorLevel::BreakingError, "Could not open file", "Could not open the specified file, make sure the path is correct, you have permission, and that it is not open in another program.", Context::show(filename))]);
+    };
+    open_mmtf_raw_with_options(std::io::BufReader::new(file), options)
+}
+
+/// Parse the given MMTF stream into a [`PDB`] struct with [`ReadOptions`].
+pub(crate) fn open_mmtf_raw_with_options<T>(
+    input: std::io::BufReader<T>,
+    options: &ReadOptions,
+) -> ReadResult
+where
+    T: std::io::Read,
+{
+    let data = msgpack::decode(&read_bytes(input)?).map_err(|e| vec![e])?;
+    parse_mmtf(&data, options)
+}
+
+/// The description of a type of group (residue) in the `groupList` of an MMTF file
+struct GroupType {
+    /// The name of the group, eg 'ALA'
+    name: String,
+    /// The names of the atoms
+    atom_names: Vec<String>,
+    /// The elements of the atoms
+    elements: Vec<String>,
+    /// The formal charges of the atoms
+    charges: Vec<i64>,
+    /// Pairs of indices of bonded atoms in this group
+    bonds: Vec<i64>,
+    /// The chemical component type, eg 'L-PEPTIDE LINKING'
+    chem_comp_type: String,
+}
+
+/// An entity in the `entityList` of an MMTF file, together with the sequence of residue names
+/// that is build up while reading the groups
+struct EntityInfo {
+    /// The entity
+    entity: Entity,
+    /// The indices of the chains of this entity
+    chains: Vec<usize>,
+    /// The residue names at every index of the sequence, if observed
+    sequence: Vec<Option<String>>,
+}
+
+/// The error for a field that is missing or could not be read
+fn field_error(key: &str, message: impl std::fmt::Display) -> PDBError {
+    PDBError::new(
+        ErrorLevel::BreakingError,
+        "Invalid MMTF field",
+        format!("The field '{key}' could not be read: {message}"),
+        Context::None,
+    )
+}
+
+/// Decode the array with the given key and convert it with the given function, `None` is
+/// returned if the field is absent
+fn array<T>(
+    data: &MsgPack,
+    key: &str,
+    convert: impl FnOnce(Array) -> Option<Vec<T>>,
+) -> Result<Option<Vec<T>>, PDBError> {
+    match data.get(key) {
+        None | Some(MsgPack::Nil) => Ok(None),
+        Some(value) => decode_array(value)
+            .map_err(|e| field_error(key, e))
+            .and_then(|a| convert(a).ok_or_else(|| field_error(key, "it has the wrong type")))
+            .map(Some),
+    }
+}
+
+/// Decode the array with the given key and convert it with the given function, it fails if the
+/// field is absent
+fn required<T>(
+    data: &MsgPack,
+    key: &str,
+    convert: impl FnOnce(Array) -> Option<Vec<T>>,
+) -> Result<Vec<T>, PDBError> {
+    array(data, key, convert)?.ok_or_else(|| field_error(key, "it is missing"))
+}
+
+/// Get the string with the given key, empty strings are seen as absent
+fn string(data: &MsgPack, key: &str) -> Option<String> {
+    data.get(key)
+        .and_then(MsgPack::as_str)
+        .filter(|s| !s.is_empty())
+        .map(ToString::to_string)
+}
+
+/// Get the value at the given index, or the given error if the array is too short
+fn at<T: Copy>(values: &[T], index: usize, key: &str) -> Result<T, PDBError> {
+    values
+        .get(index)
+        .copied()
+        .ok_or_else(|| field_error(key, "it has fewer values than needed"))
+}
+
+/// Convert an index from an MMTF array into a usize
+fn index(value: i64, key: &str) -> Result<usize, PDBError> {
+    usize::try_from(value).map_err(|_| field_error(key, "it contains a negative index"))
+}
+
+/// Parse the group types from the `groupList`
+fn parse_group_types(data: &MsgPack) -> Result<Vec<GroupType>, PDBError> {
+    let key = "groupList";
+    let list = data
+        .get(key)
+        .and_then(MsgPack::as_array)
+        .ok_or_else(|| field_error(key, "it is missing"))?;
+    list.iter()
+        .map(|group| {
+            Ok(GroupType {
+                name: string(group, "groupName").unwrap_or_default(),
+                atom_names: required(group, "atomNameList", Array::into_strings)?,
+                elements: array(group, "elementList", Array::into_strings)?.unwrap_or_default(),
+                charges: array(group, "formalChargeList", Array::into_ints)?.unwrap_or_default(),
+                bonds: array(group, "bondAtomList", Array::into_ints)?.unwrap_or_default(),
+                chem_comp_type: string(group, "chemCompType").unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/// Parse the entities from the `entityList`, the identifiers are the (one based) index in the list
+fn parse_entities(data: &MsgPack) -> Result<Vec<EntityInfo>, PDBError> {
+    let key = "entityList";
+    let list = match data.get(key).and_then(MsgPack::as_array) {
+        Some(list) => list,
+        None => return Ok(Vec::new()),
+    };
+    list.iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let mut entity = Entity::new((i + 1).to_string());
+            entity.entity_type = string(item, "type");
+            entity.description = string(item, "description");
+            entity.canonical_sequence = string(item, "sequence");
+            let chains = required(item, "chainIndexList", Array::into_ints)?
+                .into_iter()
+                .map(|i| index(i, key))
+                .collect::<Result<_, _>>()?;
+            let length = entity.canonical_sequence.as_ref().map_or(0, String::len);
+            Ok(EntityInfo {
+                entity,
+                chains,
+                sequence: vec![None; length],
+            })
+        })
+        .collect()
+}
+
+/// Parse the metadata of the structure, the header, refinement statistics, unit cell and
+/// space group
+fn parse_metadata(data: &MsgPack, pdb: &mut PDB) {
+    let float = |key: &str| data.get(key).and_then(MsgPack::as_float);
+    let mut header = Header::new();
+    header.title = string(data, "title");
+    header.deposition_date = string(data, "depositionDate");
+    header.experimental_methods = data
+        .get("experimentalMethods")
+        .and_then(MsgPack::as_array)
+        .map_or_else(Vec::new, |methods| {
+            methods
+                .iter()
+                .filter_map(|m| m.as_str().map(ToString::to_string))
+                .collect()
+        });
+    if header != Header::default() {
+        pdb.header = Some(header);
+    }
+
+    let mut refinement = RefinementInfo::new();
+    refinement.resolution = float("resolution");
+    refinement.r_free = float("rFree");
+    refinement.r_work = float("rWork");
+    if refinement != RefinementInfo::new() {
+        pdb.refinement = Some(refinement);
+    }
+
+    if let Some(cell) = data.get("unitCell").and_then(MsgPack::as_array) {
+        if let Some(&[a, b, c, alpha, beta, gamma]) = cell
+            .iter()
+            .map(MsgPack::as_float)
+            .collect::<Option<Vec<_>>>()
+            .as_deref()
+        {
+            pdb.unit_cell = Some(UnitCell::new(a, b, c, alpha, beta, gamma));
+        }
+    }
+    pdb.symmetry = string(data, "spaceGroup").and_then(Symmetry::new);
+}
+
+/// Parse an MMTF structure into a PDB
+fn parse_mmtf(data: &MsgPack, options: &ReadOptions) -> ReadResult {
+    let mut errors = Vec::new();
+    let mut pdb = build_pdb(data, options, &mut errors).map_err(|e| vec![e])?;
+    reshuffle_conformers(&mut pdb);
+    errors.extend(validate(&pdb));
+    if errors.iter().any(|e| e.fails(options.level)) {
+        Err(errors)
+    } else {
+        Ok((pdb, errors))
+    }
+}
+
+/// Build the PDB from the MMTF fields, it fails if a required field is missing or invalid.
+/// Problems with single atoms are added to the given errors.
+#[allow(clippy::too_many_lines)]
+fn build_pdb(
+    data: &MsgPack,
+    options: &ReadOptions,
+    errors: &mut Vec<PDBError>,
+) -> Result<PDB, PDBError> {
+    let mut pdb = PDB::default();
+    pdb.identifier = string(data, "structureId");
+    if !options.only_atomic_coords {
+        parse_metadata(data, &mut pdb);
+    }
+
+    let group_types = parse_group_types(data)?;
+    let mut entities = parse_entities(data)?;
+    let x = required(data, "xCoordList", Array::into_floats)?;
+    let y = required(data, "yCoordList", Array::into_floats)?;
+    let z = required(data, "zCoordList", Array::into_floats)?;
+    let b_factors = array(data, "bFactorList", Array::into_floats)?;
+    let occupancies = array(data, "occupancyList", Array::into_floats)?;
+    let atom_ids = array(data, "atomIdList", Array::into_ints)?;
+    let alt_locs = array(data, "altLocList", Array::into_strings)?;
+    let group_ids = required(data, "groupIdList", Array::into_ints)?;
+    let group_type_list = required(data, "groupTypeList", Array::into_ints)?;
+    let sequence_indices = array(data, "sequenceIndexList", Array::into_ints)?;
+    let insertion_codes = array(data, "insCodeList", Array::into_strings)?;
+    let chain_ids = required(data, "chainIdList", Array::into_strings)?;
+    let chain_names = array(data, "chainNameList", Array::into_strings)?;
+    let groups_per_chain = required(data, "groupsPerChain", Array::into_ints)?;
+    let chains_per_model = required(data, "chainsPerModel", Array::into_ints)?;
+    let bonds = array(data, "bondAtomList", Array::into_ints)?.unwrap_or_default();
+
+    // The counter and element of every atom in the file, `None` for atoms that are left out
+    let mut atoms: Vec<Option<(usize, bool)>> = Vec::with_capacity(x.len());
+    let mut atom_index = 0;
+    let mut group_index = 0;
+    let mut chain_index = 0;
+    for (model_index, chain_count) in chains_per_model.iter().enumerate() {
+        if options.only_first_model && model_index > 0 {
+            break;
+        }
+        let mut model = Model::new(model_index + 1);
+        let mut model_atom_count = 0;
+        let first_chain = chain_index;
+        for _ in 0..index(*chain_count, "chainsPerModel")? {
+            let label_asym = chain_ids
+                .get(chain_index)
+                .ok_or_else(|| field_error("chainIdList", "it has fewer values than needed"))?
+                .clone();
+            let auth_asym = chain_names
+                .as_ref()
+                .and_then(|n| n.get(chain_index))
+                .cloned()
+                .unwrap_or_else(|| label_asym.clone());
+            let chain_name = if options.label_identifiers {
+                &label_asym
+            } else {
+                &auth_asym
+            };
+            // The entity list can give the chain indices of the first model only
+            let entity_index = entities
+                .iter()
+                .position(|e| e.chains.contains(&chain_index))
+                .or_else(|| {
+                    entities
+                        .iter()
+                        .position(|e| e.chains.contains(&(chain_index - first_chain)))
+                });
+            let polymer = entity_index.map(|e| entities[e].entity.is_polymer());
+            let entity_id = entity_index.map(|e| (e + 1).to_string());
+
+            let group_count = at(&groups_per_chain, chain_index, "groupsPerChain")?;
+            for _ in 0..index(group_count, "groupsPerChain")? {
+                let group_type = index(
+                    at(&group_type_list, group_index, "groupTypeList")?,
+                    "groupTypeList",
+                )?;
+                let group = group_types
+                    .get(group_type)
+                    .ok_or_else(|| field_error("groupTypeList", "it refers to an unknown group"))?;
+                let auth_seq = isize::try_from(at(&group_ids, group_index, "groupIdList")?)
+                    .map_err(|_| field_error("groupIdList", "the number is too big"))?;
+                let label_seq = sequence_indices
+                    .as_ref()
+                    .and_then(|s| s.get(group_index))
+                    .and_then(|s| usize::try_from(*s).ok());
+                #[allow(clippy::cast_possible_wrap)]
+                let label_seq_id = label_seq.map(|s| s as isize + 1);
+                let insertion_code = insertion_codes
+                    .as_ref()
+                    .and_then(|c| c.get(group_index))
+                    .filter(|c| !c.is_empty())
+                    .map(String::as_str);
+                let residue_number = if options.label_identifiers {
+                    label_seq_id.unwrap_or(auth_seq)
+                } else {
+                    auth_seq
+                };
+                // Standard residues in polymers are given as ATOM records in PDB files
+                let hetero = polymer.map_or_else(
+                    || {
+                        !group
+                            .chem_comp_type
+                            .to_ascii_uppercase()
+                            .contains("LINKING")
+                    },
+                    |p| !p,
+                ) || reference_tables::get_one_letter_code(&group.name).is_none();
+
+                let first_atom = atoms.len();
+                for (atom_in_group, name) in group.atom_names.iter().enumerate() {
+                    let element = group.elements.get(atom_in_group).map_or("", String::as_str);
+                    if options.discard_hydrogens && element == "H" {
+                        atoms.push(None);
+                        atom_index += 1;
+                        continue;
+                    }
+                    let id = atom_ids
+                        .as_ref()
+                        .and_then(|ids| ids.get(atom_index))
+                        .map_or_else(|| (atom_index + 1).to_string(), ToString::to_string);
+                    let charge =
+                        isize::try_from(group.charges.get(atom_in_group).copied().unwrap_or(0))
+                            .unwrap_or(0);
+                    let atom = Atom::new(
+                        hetero,
+                        model_atom_count,
+                        id,
+                        name,
+                        at(&x, atom_index, "xCoordList")?,
+                        at(&y, atom_index, "yCoordList")?,
+                        at(&z, atom_index, "zCoordList")?,
+                        occupancies
+                            .as_ref()
+                            .and_then(|o| o.get(atom_index).copied())
+                            .unwrap_or(1.0),
+                        b_factors
+                            .as_ref()
+                            .and_then(|b| b.get(atom_index).copied())
+                            .unwrap_or(1.0),
+                        element,
+                        charge,
+                    );
+                    let alt_loc = alt_locs
+                        .as_ref()
+                        .and_then(|a| a.get(atom_index))
+                        .filter(|a| !a.is_empty());
+                    let valid_identifiers = [
+                        Some(chain_name.as_str()),
+                        Some(group.name.as_str()),
+                        insertion_code,
+                        alt_loc.map(String::as_str),
+                    ]
+                    .into_iter()
+                    .flatten()
+                    .all(|identifier| prepare_identifier(identifier).is_some());
+                    if let (Some(atom), true) = (atom, valid_identifiers) {
+                        atoms.push(Some((atom.counter(), element.eq_ignore_ascii_case("S"))));
+                        model.add_atom(
+                            atom,
+                            chain_name,
+                            (residue_number, insertion_code),
+                            (&group.name, alt_loc.map(String::as_str)),
+                        );
+                        model_atom_count += 1;
+                    } else if !valid_identifiers {
+                        atoms.push(None);
+                        errors.push(PDBError::new(
+                            ErrorLevel::InvalidatingError,
+                            "Invalid identifiers",
+                            "The chain name, group name, insertion code, and alternative location should only contain valid characters.",
+                            Context::show(format!(
+                                "Atom {} in group {} of chain {}",
+                                name, group.name, chain_name
+                            )),
+                        ));
+                    } else {
+                        atoms.push(None);
+                        errors.push(PDBError::new(
+                            ErrorLevel::InvalidatingError,
+                            "Atom definition incorrect",
+                            "The atom name and element should only contain valid characters.",
+                            Context::show(format!("Atom {} in group {}", name, group.name)),
+                        ));
+                    }
+                    atom_index += 1;
+                }
+
+                if let Some(chain) = model.chains_mut().rev().find(|c| c.id() == chain_name) {
+                    if let Some(residue) = chain
+                        .residues_mut()
+                        .rev()
+                        .find(|r| r.id() == (residue_number, insertion_code))
+                    {
+                        if let Some(index) = label_seq.filter(|_| polymer == Some(true)) {
+                            residue.set_sequence_index(Some(index));
+                        }
+                        residue.set_label_asym_id(Some(&label_asym));
+                        residue.set_label_seq_id(label_seq_id);
+                        residue.set_label_entity_id(entity_id.as_deref());
+                        residue.set_auth_asym_id(Some(&auth_asym));
+                        residue.set_auth_seq_id(Some(auth_seq));
+                    }
+                }
+                if let (Some(entity), Some(index), 0) = (entity_index, label_seq, model_index) {
+                    if let Some(position) = entities[entity].sequence.get_mut(index) {
+                        position.get_or_insert_with(|| group.name.clone());
+                    }
+                }
+
+                // Bonds within the group
+                if !options.only_atomic_coords {
+                    for pair in group.bonds.chunks_exact(2) {
+                        let atom = |i: i64| {
+                            usize::try_from(i)
+                                .ok()
+                                .and_then(|i| atoms.get(first_atom + i).copied().flatten())
+                        };
+                        if let (Some((a, _)), Some((b, _))) = (atom(pair[0]), atom(pair[1])) {
+                            pdb.add_bond_counters(a, b, Bond::Covalent, None);
+                        }
+                    }
+                }
+                group_index += 1;
+            }
+            if !options.only_atomic_coords {
+                if let Some(entity) = entity_index {
+                    let entity = &mut entities[entity].entity;
+                    if !entity.contains_chain(chain_name) {
+                        entity.chains.push(chain_name.clone());
+                    }
+                }
+            }
+            chain_index += 1;
+        }
+        pdb.add_model(model);
+    }
+
+    // Bonds between groups, these are given as covalent or disulfide bonds as the bond type is
+    // not specified in MMTF
+    if !options.only_atomic_coords {
+        for pair in bonds.chunks_exact(2) {
+            let atom = |i: i64| {
+                usize::try_from(i)
+                    .ok()
+                    .and_then(|i| atoms.get(i).copied().flatten())
+            };
+            if let (Some((a, sulfur_a)), Some((b, sulfur_b))) = (atom(pair[0]), atom(pair[1])) {
+                let bond = if sulfur_a && sulfur_b {
+                    Bond::Disulfide
+                } else {
+                    Bond::Covalent
+                };
+                pdb.add_bond_counters(a, b, bond, None);
+            }
+        }
+        for mut info in entities {
+            if info.entity.is_polymer() {
+                info.entity.sequence = info
+                    .sequence
+                    .into_iter()
+                    .map(|name| name.unwrap_or_else(|| "UNK".to_string()))
+                    .collect();
+            } else if let Some(name) = pdb
+                .residues()
+                .find(|r| r.label_entity_id() == Some(info.entity.id.as_str()))
+                .and_then(Residue::name)
+            {
+                info.entity.sequence = vec![name.to_string()];
+            }
+            pdb.add_entity(info.entity);
+        }
+    }
+    Ok(pdb)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::StrictnessLevel;
+
+    /// Create a binary array with the given codec, length, and parameter from its encoded data
+    fn binary(codec: i32, length: i32, param: i32, data: &[u8]) -> MsgPack {
+        let mut bytes = Vec::new();
+        bytes.extend(codec.to_be_bytes());
+        bytes.extend(length.to_be_bytes());
+        bytes.extend(param.to_be_bytes());
+        bytes.extend(data);
+        MsgPack::Bin(bytes)
+    }
+
+    /// Encode the given numbers as big endian 16 bit integers
+    fn int16(values: &[i16]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_be_bytes()).collect()
+    }
+
+    /// Encode the given numbers as big endian 32 bit integers
+    fn int32(values: &[i32]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_be_bytes()).collect()
+    }
+
+    /// Create a list of strings
+    fn strings(values: &[&str]) -> MsgPack {
+        MsgPack::Array(values.iter().map(|s| (*s).into()).collect())
+    }
+
+    /// Create a list of integers
+    fn ints(values: &[i64]) -> MsgPack {
+        MsgPack::Array(values.iter().map(|i| MsgPack::Int(*i)).collect())
+    }
+
+    #[test]
+    fn hand_encoded_structure() {
+        // Two glycines, with the codecs used in the files of the RCSB
+        let glycine = MsgPack::map(vec![
+            ("groupName", "GLY".into()),
+            ("atomNameList", strings(&["N", "CA", "C", "O"])),
+            ("elementList", strings(&["N", "C", "C", "O"])),
+            ("formalChargeList", ints(&[0, 0, 0, 0])),
+            ("bondAtomList", ints(&[0, 1, 1, 2, 2, 3])),
+            ("bondOrderList", ints(&[1, 1, 2])),
+            ("chemCompType", "PEPTIDE LINKING".into()),
+            ("singleLetterCode", "G".into()),
+        ]);
+        let data = MsgPack::map(vec![
+            ("structureId", "TEST".into()),
+            ("numAtoms", 8_i64.into()),
+            ("groupList", MsgPack::Array(vec![glycine])),
+            (
+                "entityList",
+                MsgPack::Array(vec![MsgPack::map(vec![
+                    ("chainIndexList", ints(&[0])),
+                    ("description", "DIPEPTIDE".into()),
+                    ("sequence", "GG".into()),
+                    ("type", "polymer".into()),
+                ])]),
+            ),
+            // x: 1.0, 2.5, 3.0, 3.1, 4.2, 5.6, 6.0, 40.0; the last difference of 34.0 does not
+            // fit in a 16 bit integer so it is split into 32.767 and 1.233
+            (
+                "xCoordList",
+                binary(
+                    10,
+                    8,
+                    1000,
+                    &int16(&[1000, 1500, 500, 100, 1100, 1400, 400, 32767, 1233]),
+                ),
+            ),
+            ("yCoordList", binary(10, 8, 1000, &int16(&[0; 8]))),
+            (
+                "zCoordList",
+                binary(10, 8, 1000, &int16(&[-1000, 0, 0, 0, 0, 0, 0, 0])),
+            ),
+            (
+                "bFactorList",
+                binary(10, 8, 100, &int16(&[1000, 0, 0, 0, 0, 0, 0, 250])),
+            ),
+            ("occupancyList", binary(9, 8, 100, &int32(&[100, 7, 50, 1]))),
+            ("atomIdList", binary(8, 8, 0, &int32(&[1, 8]))),
+            ("altLocList", binary(6, 8, 0, &int32(&[0, 8]))),
+            ("insCodeList", binary(6, 2, 0, &int32(&[0, 2]))),
+            ("groupIdList", binary(8, 2, 0, &int32(&[1, 2]))),
+            ("groupTypeList", binary(4, 2, 0, &int32(&[0, 0]))),
+            ("secStructList", binary(2, 2, 0, &[0xff, 0xff])),
+            ("sequenceIndexList", binary(8, 2, 0, &int32(&[0, 1, 1, 1]))),
+            ("chainIdList", binary(5, 1, 4, b"A\0\0\0")),
+            ("chainNameList", binary(5, 1, 4, b"B\0\0\0")),
+            ("groupsPerChain", ints(&[2])),
+            ("chainsPerModel", ints(&[1])),
+            // The peptide bond between C of the first and N of the second glycine
+            ("bondAtomList", binary(4, 2, 0, &int32(&[2, 4]))),
+            ("bondOrderList", binary(2, 1, 0, &[1])),
+        ]);
+        let mut options = ReadOptions::default();
+        options.set_level(StrictnessLevel::Loose);
+        let (pdb, _) = parse_mmtf(&data, &options).unwrap();
+
+        assert_eq!(pdb.identifier.as_deref(), Some("TEST"));
+        assert_eq!(pdb.model_count(), 1);
+        assert_eq!(pdb.chain_count(), 1);
+        assert_eq!(pdb.residue_count(), 2);
+        assert_eq!(pdb.atom_count(), 8);
+        let chain = pdb.chains().next().unwrap();
+        assert_eq!(chain.id(), "B");
+        let residues: Vec<_> = chain.residues().collect();
+        assert_eq!(
+            residues.iter().map(|r| r.id()).collect::<Vec<_>>(),
+            [(1, None), (2, None)]
+        );
+        assert_eq!(residues[0].name(), Some("GLY"));
+        assert_eq!(residues[0].label_asym_id(), Some("A"));
+        assert_eq!(residues[1].sequence_index(), Some(1));
+
+        let atoms: Vec<&Atom> = pdb.atoms().collect();
+        assert_eq!(
+            atoms.iter().map(|a| a.id()).collect::<Vec<_>>(),
+            ["1", "2", "3", "4", "5", "6", "7", "8"]
+        );
+        assert_eq!(atoms[1].name(), "CA");
+        assert_eq!(atoms[3].element(), Some(&Element::O));
+        let expected_x = [1.0, 2.5, 3.0, 3.1, 4.2, 5.6, 6.0, 40.0];
+        for (atom, x) in atoms.iter().zip(expected_x) {
+            assert!((atom.x() - x).abs() < 1e-9, "{} {x}", atom.x());
+            assert!(atom.y().abs() < 1e-9);
+            assert!((atom.z() + 1.0).abs() < 1e-9);
+        }
+        assert!((atoms[0].b_factor() - 10.0).abs() < 1e-9);
+        assert!((atoms[7].b_factor() - 12.5).abs() < 1e-9);
+        assert!((atoms[6].occupancy() - 1.0).abs() < 1e-9);
+        assert!((atoms[7].occupancy() - 0.5).abs() < 1e-9);
+
+        // Three bonds within every glycine and the peptide bond
+        assert_eq!(pdb.bonds().count(), 7);
+        assert!(pdb
+            .bonds()
+            .any(|(a, b, bond)| { bond == Bond::Covalent && a.id() == "3" && b.id() == "5" }));
+
+        let entity = pdb.entities().next().unwrap();
+        assert_eq!(entity.sequence, ["GLY", "GLY"]);
+        assert!(entity.contains_chain("B"));
+    }
+}
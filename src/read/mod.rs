@@ -1,14 +1,32 @@
 pub use general::{open, open_gz};
 pub use mmcif::{open_mmcif, open_mmcif_bufread, open_mmcif_raw};
+pub(crate) use mmtf::{
+    encode_chars, encode_delta_run_length, encode_fixed_delta, encode_fixed_run_length,
+    encode_int32, encode_int8, encode_strings,
+};
 pub use pdb::{open_pdb, open_pdb_raw};
 pub use read_options::{Format, ReadOptions};
 
 /// Give a high level interface for users
 mod general;
+/// Parse GROMACS files
+mod gro;
 /// Parse mmCIF/PDBx files
 mod mmcif;
+/// Parse MMTF files
+mod mmtf;
+/// Parse Tripos MOL2 files
+mod mol2;
+/// Parse PDBQT files
+mod pdbqt;
+/// Parse PQR files
+mod pqr;
 /// Read options
 mod read_options;
+/// Parse SDF and MOL files
+mod sdf;
+/// Parse XYZ files
+mod xyz;
 
 /// Parse PDB files
 mod pdb;
@@ -0,0 +1,4 @@
+/// Parse Tripos MOL2 files into PDB structures
+mod parser;
+
+pub(crate) use parser::*;
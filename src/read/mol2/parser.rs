@@ -0,0 +1,416 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use crate::error::*;
+use crate::read::general::ReadResult;
+use crate::reference_tables;
+use crate::structs::*;
+use crate::validate::*;
+use crate::ReadOptions;
+
+/// The chain that atoms are placed in if their substructure does not give a chain
+const MOL2_CHAIN: &str = "A";
+
+/// Parse the given MOL2 file into a PDB struct with [`ReadOptions`].
+pub(crate) fn open_mol2_with_options(
+    filename: impl AsRef<str>,
+    options: &ReadOptions,
+) -> ReadResult {
+    let filename = filename.as_ref();
+    let file = if let Ok(f) = File::open(filename) {
+        f
+    } else {
+        return Err(vec![PDBError::new(ErrorLevel::BreakingError, "Could not open file", "Could not open the specified file, make sure the path is correct, you have permission, and that it is not open in another program.", Context::show(filename))]);
+    };
+    open_mol2_raw_with_options(BufReader::new(file), options)
+}
+
+/// Parse the given MOL2 stream into a [`PDB`] struct with [`ReadOptions`].
+///
+/// Every molecule (`@<TRIPOS>MOLECULE`) is read as a separate model, and the name of the first
+/// molecule is used as title. The atoms are grouped into residues by their substructure, the
+/// residue name and chain are taken from the `@<TRIPOS>SUBSTRUCTURE` section if given, otherwise
+/// the residue name and number are split from the substructure name (eg `ALA12`) and the atoms
+/// are placed in chain `A`. The SYBYL atom types and partial charges are stored in the atoms (see
+/// [`Atom::atom_type`] and [`Atom::partial_charge`]), and the element is derived from the atom
+/// type. The bonds are added as covalent bonds with their bond order (see [`BondDetails::order`]),
+/// and the `@<TRIPOS>CRYSIN` section of the first molecule gives the unit cell and space group.
+pub(crate) fn open_mol2_raw_with_options<T>(
+    input: BufReader<T>,
+    options: &ReadOptions,
+) -> ReadResult
+where
+    T: std::io::Read,
+{
+    let mut lines = Vec::new();
+    for (linenumber, read_line) in input.lines().enumerate() {
+        if let Ok(l) = read_line {
+            lines.push(l);
+        } else {
+            return Err(vec![PDBError::new(
+                ErrorLevel::BreakingError,
+                "Could read line",
+                format!(
+                    "Could not read line {} while parsing the input file.",
+                    linenumber + 1
+                ),
+                Context::None,
+            )]);
+        }
+    }
+
+    let starts: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| l.trim().eq_ignore_ascii_case("@<TRIPOS>MOLECULE"))
+        .map(|(i, _)| i)
+        .collect();
+    if starts.is_empty() {
+        return Err(vec![PDBError::new(
+            ErrorLevel::BreakingError,
+            "Empty MOL2 file",
+            "The file does not contain any molecules, these start with '@<TRIPOS>MOLECULE'.",
+            Context::None,
+        )]);
+    }
+
+    let mut errors = Vec::new();
+    let mut pdb = PDB::new();
+    for (index, start) in starts.iter().enumerate() {
+        let end = starts.get(index + 1).copied().unwrap_or(lines.len());
+        let (model, bonds, molecule_errors) =
+            parse_molecule(&lines, *start, end, index + 1, &mut pdb, options)
+                .map_err(|e| vec![e])?;
+        errors.extend(molecule_errors);
+        pdb.add_model(model);
+        for (counter1, counter2, order) in bonds {
+            let details = order.map(|order| BondDetails {
+                order: Some(order),
+                ..BondDetails::default()
+            });
+            pdb.add_bond_counters(counter1, counter2, Bond::Covalent, details);
+        }
+        if options.only_first_model {
+            break;
+        }
+    }
+
+    reshuffle_conformers(&mut pdb);
+    errors.extend(validate(&pdb));
+
+    if errors.iter().any(|e| e.fails(options.level)) {
+        Err(errors)
+    } else {
+        Ok((pdb, errors))
+    }
+}
+
+/// A substructure, as given in the `@<TRIPOS>SUBSTRUCTURE` section
+struct Substructure {
+    /// The name of the substructure, eg `ALA12`
+    name: String,
+    /// The chain, if given
+    chain: Option<String>,
+    /// The residue type, eg `ALA`, if given
+    residue_type: Option<String>,
+}
+
+/// The bonds of a molecule, given by the counters of the atoms and the bond order if known
+type MoleculeBonds = Vec<(usize, usize, Option<BondOrder>)>;
+
+/// Get the lines of every section in the molecule between the given start and end lines, keyed
+/// by the uppercase name of the section (eg `ATOM`), together with the index of every line.
+/// Empty lines and comments are skipped.
+fn sections(lines: &[String], start: usize, end: usize) -> HashMap<String, Vec<(usize, &str)>> {
+    let mut sections: HashMap<String, Vec<(usize, &str)>> = HashMap::new();
+    let mut current = String::new();
+    for (i, line) in lines.iter().enumerate().take(end).skip(start) {
+        let line = line.trim();
+        if let Some(name) = line.strip_prefix("@<TRIPOS>") {
+            current = name.to_ascii_uppercase();
+            sections.entry(current.clone()).or_default();
+        } else if !line.is_empty() && !line.starts_with('#') {
+            sections.entry(current.clone()).or_default().push((i, line));
+        }
+    }
+    sections
+}
+
+/// Split a substructure name like `ALA12` in the residue name and number
+fn split_substructure_name(name: &str) -> (&str, Option<isize>) {
+    let digits = name
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_ascii_digit())
+        .last()
+        .map_or(name.len(), |(i, _)| i);
+    let (residue_name, number) = name.split_at(digits);
+    (residue_name, number.parse().ok())
+}
+
+/// Derive the element from a SYBYL atom type, eg `C` from `C.ar`
+fn element_from_type(atom_type: &str) -> Option<Element> {
+    Element::from_symbol(atom_type.split('.').next().unwrap_or_default())
+}
+
+/// Parse the molecule between the given start and end lines (exclusive). The title, unit cell,
+/// and space group of the first molecule are stored in the PDB.
+#[allow(clippy::too_many_lines)]
+fn parse_molecule(
+    lines: &[String],
+    start: usize,
+    end: usize,
+    serial_number: usize,
+    pdb: &mut PDB,
+    options: &ReadOptions,
+) -> Result<(Model, MoleculeBonds, Vec<PDBError>), PDBError> {
+    let mut errors = Vec::new();
+    let line_context =
+        |i: usize| Context::full_line(i + 1, lines.get(i).map_or("", String::as_str));
+    let sections = sections(lines, start, end);
+    let molecule = sections.get("MOLECULE").map_or(&[][..], Vec::as_slice);
+    let charges = !molecule
+        .get(3)
+        .map_or(false, |(_, l)| l.eq_ignore_ascii_case("NO_CHARGES"));
+    if serial_number == 1 {
+        if let Some((_, name)) = molecule.first() {
+            pdb.header.get_or_insert_with(Header::new).title = Some((*name).to_string());
+        }
+        if let Some((i, line)) = sections.get("CRYSIN").and_then(|s| s.first()) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let numbers: Result<Vec<f64>, _> =
+                fields.iter().take(6).map(|f| f.parse::<f64>()).collect();
+            if let Ok(&[a, b, c, alpha, beta, gamma]) = numbers.as_deref() {
+                pdb.unit_cell = Some(UnitCell::new(a, b, c, alpha, beta, gamma));
+                pdb.symmetry = fields
+                    .get(6)
+                    .and_then(|f| f.parse().ok())
+                    .and_then(Symmetry::from_index);
+            } else {
+                errors.push(PDBError::new(
+                    ErrorLevel::LooseWarning,
+                    "Invalid crystal information",
+                    "The crystal information should start with the six unit cell parameters.",
+                    line_context(*i),
+                ));
+            }
+        }
+    }
+
+    let substructures: HashMap<&str, Substructure> = sections
+        .get("SUBSTRUCTURE")
+        .map(|lines| {
+            lines
+                .iter()
+                .filter_map(|(_, line)| {
+                    let fields: Vec<&str> = line.split_whitespace().collect();
+                    let optional = |index: usize| {
+                        fields
+                            .get(index)
+                            .filter(|f| !f.starts_with("****"))
+                            .map(|f| (*f).to_string())
+                    };
+                    Some((
+                        *fields.first()?,
+                        Substructure {
+                            name: (*fields.get(1)?).to_string(),
+                            chain: optional(5),
+                            residue_type: optional(6),
+                        },
+                    ))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut chains: Vec<(String, Vec<Residue>)> = Vec::new();
+    let mut counters: HashMap<&str, usize> = HashMap::new();
+    let mut last_substructure = None;
+    for (i, line) in sections.get("ATOM").map_or(&[][..], Vec::as_slice) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 6 {
+            return Err(PDBError::new(
+                ErrorLevel::BreakingError,
+                "Invalid atom line",
+                "An atom line should contain at least the atom ID, name, coordinates, and type.",
+                line_context(*i),
+            ));
+        }
+        let number = |index: usize, name: &str| {
+            fields[index].parse::<f64>().map_err(|_| {
+                PDBError::new(
+                    ErrorLevel::BreakingError,
+                    "Invalid atom line",
+                    format!("The {name} '{}' is not a valid number.", fields[index]),
+                    line_context(*i),
+                )
+            })
+        };
+        let (x, y, z) = (
+            number(2, "x coordinate")?,
+            number(3, "y coordinate")?,
+            number(4, "z coordinate")?,
+        );
+        let serial_number = fields[0].parse::<usize>().map_err(|_| {
+            PDBError::new(
+                ErrorLevel::BreakingError,
+                "Invalid atom line",
+                format!("The atom ID '{}' is not a valid number.", fields[0]),
+                line_context(*i),
+            )
+        })?;
+        let atom_type = fields[5];
+        let element = element_from_type(atom_type);
+        if options.discard_hydrogens && element == Some(Element::H) {
+            continue;
+        }
+
+        // Find the residue name, number, and chain from the substructure
+        let substructure_id = fields.get(6).copied().unwrap_or("1");
+        let substructure = substructures.get(substructure_id);
+        let substructure_name = fields
+            .get(7)
+            .copied()
+            .or_else(|| substructure.map(|s| s.name.as_str()))
+            .unwrap_or("UNL");
+        let (name_part, number_part) = split_substructure_name(substructure_name);
+        let residue_name = substructure
+            .and_then(|s| s.residue_type.as_deref())
+            .or_else(|| {
+                Some(name_part.trim_matches(|c: char| !c.is_ascii_alphanumeric()))
+                    .filter(|n| !n.is_empty())
+            })
+            .unwrap_or("UNL");
+        let residue_number = number_part
+            .or_else(|| substructure_id.parse().ok())
+            .unwrap_or(1);
+        let chain = substructure
+            .and_then(|s| s.chain.as_deref())
+            .unwrap_or(MOL2_CHAIN);
+
+        let hetero = reference_tables::get_one_letter_code(residue_name).is_none();
+        let atom = Atom::new(
+            hetero,
+            serial_number,
+            serial_number.to_string(),
+            fields[1],
+            x,
+            y,
+            z,
+            1.0,
+            0.0,
+            element.as_ref().map_or("", Element::symbol),
+            0,
+        );
+        let mut atom = if let Some(atom) = atom {
+            atom
+        } else {
+            errors.push(PDBError::new(
+                ErrorLevel::InvalidatingError,
+                "Atom definition incorrect",
+                "The atom name should only contain valid characters.",
+                line_context(*i),
+            ));
+            continue;
+        };
+        if let Err(message) = atom.set_atom_type(atom_type) {
+            errors.push(PDBError::new(
+                ErrorLevel::InvalidatingError,
+                "Invalid atom type",
+                message,
+                line_context(*i),
+            ));
+        }
+        if let (true, Some(charge)) = (charges, fields.get(8)) {
+            match charge.parse::<f64>() {
+                Ok(charge) => {
+                    if let Err(message) = atom.set_partial_charge(charge) {
+                        errors.push(PDBError::new(
+                            ErrorLevel::InvalidatingError,
+                            "Invalid charge",
+                            message,
+                            line_context(*i),
+                        ));
+                    }
+                }
+                Err(_) => errors.push(PDBError::new(
+                    ErrorLevel::InvalidatingError,
+                    "Invalid charge",
+                    format!("The charge '{charge}' is not a valid number."),
+                    line_context(*i),
+                )),
+            }
+        }
+        counters.insert(fields[0], atom.counter());
+
+        // Consecutive atoms of the same substructure form a residue
+        if chains.last().map_or(true, |(id, _)| id != chain) {
+            chains.push((chain.to_string(), Vec::new()));
+        }
+        let residues = &mut chains.last_mut().expect("A chain was just added").1;
+        if last_substructure == Some(substructure_id) && !residues.is_empty() {
+            residues
+                .last_mut()
+                .expect("A residue is present")
+                .add_atom(atom, (residue_name, None));
+        } else if let Some(conformer) = Conformer::new(residue_name, None, Some(atom)) {
+            residues.extend(Residue::new(residue_number, None, Some(conformer)));
+        } else {
+            errors.push(PDBError::new(
+                ErrorLevel::InvalidatingError,
+                "Residue definition incorrect",
+                "The substructure name should only contain valid characters.",
+                line_context(*i),
+            ));
+        }
+        last_substructure = Some(substructure_id);
+    }
+
+    let mut bonds = Vec::new();
+    for (i, line) in sections.get("BOND").map_or(&[][..], Vec::as_slice) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 4 {
+            return Err(PDBError::new(
+                ErrorLevel::BreakingError,
+                "Invalid bond line",
+                "A bond line should contain the bond ID, the IDs of both atoms, and the bond type.",
+                line_context(*i),
+            ));
+        }
+        // Not connected bonds are only given to keep the substructures together
+        if fields[3].eq_ignore_ascii_case("nc") {
+            continue;
+        }
+        match (counters.get(fields[1]), counters.get(fields[2])) {
+            (Some(a), Some(b)) => {
+                bonds.push((*a, *b, BondOrder::from_sybyl_bond_type(fields[3])));
+            }
+            // Bonds to discarded hydrogens are skipped
+            _ if options.discard_hydrogens => (),
+            _ => {
+                return Err(PDBError::new(
+                    ErrorLevel::BreakingError,
+                    "Invalid bond line",
+                    "The bond should be between two atoms in the molecule.",
+                    line_context(*i),
+                ))
+            }
+        }
+    }
+
+    let mut model = Model::new(serial_number);
+    for (id, residues) in chains {
+        if let Some(chain) = Chain::from_iter(&id, residues.into_iter()) {
+            model.add_chain(chain);
+        } else {
+            errors.push(PDBError::new(
+                ErrorLevel::InvalidatingError,
+                "Chain definition incorrect",
+                format!("The chain identifier '{id}' should only contain valid characters."),
+                line_context(start),
+            ));
+        }
+    }
+    Ok((model, bonds, errors))
+}
@@ -1,6 +1,7 @@
 use super::lexitem::*;
 use crate::error::*;
 use crate::reference_tables;
+use crate::structs::{HelixClass, StrandSense};
 use crate::ReadOptions;
 use crate::StrictnessLevel;
 
@@ -18,6 +19,17 @@ pub fn lex_line(
     match line.len() {
         len if len > 6 => match (options.only_atomic_coords, &line[..6]) {
             (false, "HEADER") => lex_header(linenumber, line),
+            (false, "TITLE " | "COMPND" | "SOURCE" | "KEYWDS" | "EXPDTA" | "AUTHOR") => Ok((
+                LexItem::TitleSection(
+                    line[..6].trim_end().to_string(),
+                    line.get(10..).unwrap_or_default().trim().to_string(),
+                ),
+                Vec::new(),
+            )),
+            (false, "REVDAT") => Ok(lex_revdat(linenumber, line)),
+            (false, "HET   ") => Ok(lex_heterogen(line, 7..10, 10)),
+            (false, "HETNAM" | "HETSYN") => Ok(lex_heterogen(line, 11..14, 15)),
+            (false, "FORMUL") => Ok(lex_heterogen(line, 12..15, 18)),
             (false, "REMARK") => lex_remark(linenumber, line, options.level),
             (_, "ATOM  ") => lex_atom(linenumber, line, false),
             (false, "ANISOU") => Ok(lex_anisou(linenumber, line)),
@@ -41,6 +53,13 @@ pub fn lex_line(
             (false, "SEQADV") => Ok(lex_seqadv(linenumber, line)),
             (false, "MODRES") => Ok(lex_modres(linenumber, line)),
             (false, "SSBOND") => Ok(lex_ssbond(linenumber, line)),
+            (false, "LINK  ") => Ok(lex_link(linenumber, line, false)),
+            (false, "LINKR ") => Ok(lex_link(linenumber, line, true)),
+            (false, "CONECT") => Ok(lex_connect(linenumber, line)),
+            (false, "HELIX ") => Ok(lex_helix(linenumber, line)),
+            (false, "SHEET ") => Ok(lex_sheet(linenumber, line)),
+            (false, "CISPEP") => Ok(lex_cispep(linenumber, line)),
+            (false, "SITE  ") => Ok(lex_site(linenumber, line)),
             (_, "ENDMDL") => Ok((LexItem::EndModel(), Vec::new())),
             (_, "TER   ") => Ok((LexItem::TER(), Vec::new())),
             (_, "END   ") => Ok((LexItem::End(), Vec::new())),
@@ -124,6 +143,41 @@ fn lex_header(linenumber: usize, line: &str) -> Result<(LexItem, Vec<PDBError>),
     }
 }
 
+/// Lex a REVDAT record
+fn lex_revdat(linenumber: usize, line: &str) -> (LexItem, Vec<PDBError>) {
+    let mut errors = Vec::new();
+    let number = parse(linenumber, line, 7..10, &mut errors);
+    let date = parse_optional(linenumber, line, 13..22, &mut errors);
+    let identifier = parse_optional(linenumber, line, 23..27, &mut errors);
+    let modification_type = parse_optional(linenumber, line, 31..32, &mut errors);
+    let records = (39..line.len())
+        .step_by(7)
+        .filter_map(|start| parse_optional(linenumber, line, start..start + 6, &mut errors))
+        .collect();
+    (
+        LexItem::Revdat(number, date, identifier, modification_type, records),
+        errors,
+    )
+}
+
+/// Lex a heterogen record (HET, HETNAM, HETSYN, or FORMUL), with the component id in the given
+/// columns and the text starting at the given column. The text of HET records is not used.
+fn lex_heterogen(line: &str, id: Range<usize>, text: usize) -> (LexItem, Vec<PDBError>) {
+    let record = line[..6].trim_end();
+    (
+        LexItem::Heterogen(
+            record.to_string(),
+            line.get(id).unwrap_or_default().trim().to_string(),
+            if record == "HET" {
+                String::new()
+            } else {
+                line.get(text..).unwrap_or_default().trim().to_string()
+            },
+        ),
+        Vec::new(),
+    )
+}
+
 /// Lex a MODEL
 /// ## Fails
 /// It fails on incorrect numbers for the serial number
@@ -662,8 +716,8 @@ fn lex_ssbond(linenumber: usize, line: &str) -> (LexItem, Vec<PDBError>) {
     let mut extra = None;
 
     if chars.len() >= 78 {
-        let sym1 = parse(linenumber, line, 59..65, &mut errors);
-        let sym2 = parse(linenumber, line, 66..72, &mut errors);
+        let sym1 = symmetry_operator(parse(linenumber, line, 59..65, &mut errors));
+        let sym2 = symmetry_operator(parse(linenumber, line, 66..72, &mut errors));
         let distance: f64 = parse(linenumber, line, 73..78, &mut errors);
         extra = Some((sym1, sym2, distance));
     }
@@ -678,6 +732,229 @@ fn lex_ssbond(linenumber: usize, line: &str) -> (LexItem, Vec<PDBError>) {
     )
 }
 
+/// Parse a LINK or LINKR line into the corresponding LexItem
+fn lex_link(linenumber: usize, line: &str, linkr: bool) -> (LexItem, Vec<PDBError>) {
+    let mut errors = Vec::new();
+    let atom = |name: usize, errors: &mut Vec<PDBError>| {
+        let alt_loc = match line.chars().nth(name + 4) {
+            Some(' ') | None => None,
+            Some(c) => Some(String::from(c)),
+        };
+        (
+            parse(linenumber, line, name..name + 4, errors),
+            alt_loc,
+            lex_residue(
+                linenumber,
+                line,
+                name + 5..name + 8,
+                name + 9,
+                name + 10..name + 14,
+                errors,
+            ),
+        )
+    };
+    let atom1 = atom(12, &mut errors);
+    let atom2 = atom(42, &mut errors);
+    let sym1 = parse_optional(linenumber, line, 59..65, &mut errors).map(symmetry_operator);
+    let sym2 = parse_optional(linenumber, line, 66..72, &mut errors).map(symmetry_operator);
+    // LINKR records (as written by REFMAC) use the place of the length for the link name
+    let (distance, name) = if linkr {
+        (
+            None,
+            parse_optional(linenumber, line, 72..line.len(), &mut errors),
+        )
+    } else {
+        (parse_optional(linenumber, line, 73..78, &mut errors), None)
+    };
+
+    (
+        LexItem::Link(atom1, atom2, sym1, sym2, distance, name),
+        errors,
+    )
+}
+
+/// Convert a symmetry operator from the PDB notation ('1555') to the mmCIF notation ('1_555')
+fn symmetry_operator(operator: String) -> String {
+    if operator.len() > 3 && !operator.contains('_') && operator.is_ascii() {
+        let (number, translation) = operator.split_at(operator.len() - 3);
+        format!("{number}_{translation}")
+    } else {
+        operator
+    }
+}
+
+/// Parse a CONECT line into the corresponding LexItem
+fn lex_connect(linenumber: usize, line: &str) -> (LexItem, Vec<PDBError>) {
+    let mut errors = Vec::new();
+    let chars: Vec<char> = line.chars().collect();
+
+    let serial_number = parse(linenumber, line, 6..11, &mut errors);
+    let mut bonded = Vec::new();
+    // The fields after the fourth bonded atom (hydrogen and salt bridge partners) are obsolete
+    for start in [11, 16, 21, 26] {
+        let end = cmp::min(start + 5, chars.len());
+        if start >= end || chars[start..end].iter().all(|c| *c == ' ') {
+            continue;
+        }
+        bonded.push(parse(linenumber, line, start..end, &mut errors));
+    }
+
+    (LexItem::Connect(serial_number, bonded), errors)
+}
+
+/// Parse a HELIX line into the corresponding LexItem
+fn lex_helix(linenumber: usize, line: &str) -> (LexItem, Vec<PDBError>) {
+    let mut errors = Vec::new();
+    let serial_number = parse(linenumber, line, 7..10, &mut errors);
+    let id = parse(linenumber, line, 11..14, &mut errors);
+    let start = lex_residue(linenumber, line, 15..18, 19, 21..25, &mut errors);
+    let end = lex_residue(linenumber, line, 27..30, 31, 33..37, &mut errors);
+    let class = parse_optional(linenumber, line, 38..40, &mut errors).unwrap_or(1);
+    if HelixClass::from_number(class).is_none() {
+        errors.push(PDBError::new(
+            ErrorLevel::LooseWarning,
+            "Invalid helix class",
+            "The helix class should be a number from 1 to 10, it is assumed to be a right-handed alpha helix (1).",
+            Context::line(linenumber, line, 38, 2),
+        ));
+    }
+    let comment = parse_optional(linenumber, line, 40..70, &mut errors);
+    let length = parse_optional(linenumber, line, 71..76, &mut errors);
+
+    (
+        LexItem::Helix(serial_number, id, start, end, class, comment, length),
+        errors,
+    )
+}
+
+/// Parse a SHEET line into the corresponding LexItem
+fn lex_sheet(linenumber: usize, line: &str) -> (LexItem, Vec<PDBError>) {
+    let mut errors = Vec::new();
+    let strand = parse(linenumber, line, 7..10, &mut errors);
+    let id = parse(linenumber, line, 11..14, &mut errors);
+    let strand_count = parse(linenumber, line, 14..16, &mut errors);
+    let start = lex_residue(linenumber, line, 17..20, 21, 22..26, &mut errors);
+    let end = lex_residue(linenumber, line, 28..31, 32, 33..37, &mut errors);
+    let sense = parse_optional(linenumber, line, 38..40, &mut errors).unwrap_or(0);
+    if StrandSense::from_number(sense).is_none() {
+        errors.push(PDBError::new(
+            ErrorLevel::LooseWarning,
+            "Invalid strand sense",
+            "The sense of a strand should be 0 (first strand), 1 (parallel), or -1 (anti-parallel), it is assumed to be the first strand (0).",
+            Context::line(linenumber, line, 38, 2),
+        ));
+    }
+    let registration = if parse_optional::<String>(linenumber, line, 41..70, &mut errors).is_some()
+    {
+        Some((
+            parse(linenumber, line, 41..45, &mut errors),
+            lex_residue(linenumber, line, 45..48, 49, 50..54, &mut errors),
+            parse(linenumber, line, 56..60, &mut errors),
+            lex_residue(linenumber, line, 60..63, 64, 65..69, &mut errors),
+        ))
+    } else {
+        None
+    };
+
+    (
+        LexItem::Sheet(strand, id, strand_count, start, end, sense, registration),
+        errors,
+    )
+}
+
+/// Parse a CISPEP line into the corresponding LexItem
+fn lex_cispep(linenumber: usize, line: &str) -> (LexItem, Vec<PDBError>) {
+    let mut errors = Vec::new();
+    let serial_number = parse(linenumber, line, 7..10, &mut errors);
+    let start = lex_residue(linenumber, line, 11..14, 15, 17..21, &mut errors);
+    let end = lex_residue(linenumber, line, 25..28, 29, 31..35, &mut errors);
+    let model = parse_optional(linenumber, line, 43..46, &mut errors).unwrap_or(0);
+    let angle = parse_optional(linenumber, line, 53..59, &mut errors);
+    (
+        LexItem::CisPeptide(serial_number, start, end, model, angle),
+        errors,
+    )
+}
+
+/// Parse a SITE line into the corresponding LexItem
+fn lex_site(linenumber: usize, line: &str) -> (LexItem, Vec<PDBError>) {
+    let mut errors = Vec::new();
+    let id = parse(linenumber, line, 11..14, &mut errors);
+    let residues = (18..line.len())
+        .step_by(11)
+        .take(4)
+        .filter(|start| {
+            !line
+                .get(*start..start + 3)
+                .unwrap_or_default()
+                .trim()
+                .is_empty()
+        })
+        .map(|start| {
+            lex_residue(
+                linenumber,
+                line,
+                start..start + 3,
+                start + 4,
+                start + 5..start + 9,
+                &mut errors,
+            )
+        })
+        .collect();
+    (LexItem::Site(id, residues), errors)
+}
+
+/// Parse a residue as used in many records, defined by its name, chain id, serial number, and
+/// insertion code. The insertion code is assumed to directly follow the serial number.
+fn lex_residue(
+    linenumber: usize,
+    line: &str,
+    name: Range<usize>,
+    chain_id: usize,
+    serial_number: Range<usize>,
+    errors: &mut Vec<PDBError>,
+) -> (String, isize, Option<String>, String) {
+    let insertion_code = match line.chars().nth(serial_number.end) {
+        Some(' ') | None => None,
+        Some(c) => Some(String::from(c)),
+    };
+    (
+        parse(linenumber, line, name, errors),
+        parse(linenumber, line, serial_number, errors),
+        insertion_code,
+        parse_char(linenumber, line, chain_id, errors).to_string(),
+    )
+}
+
+/// Parse an optional field from a line, it returns `None` if the line is too short or the field is empty.
+/// Errors are left in the given mutable vec.
+fn parse_optional<T: FromStr>(
+    linenumber: usize,
+    line: &str,
+    range: Range<usize>,
+    errors: &mut Vec<PDBError>,
+) -> Option<T> {
+    let end = cmp::min(range.end, line.len());
+    if range.start >= end || line[range.start..end].trim().is_empty() {
+        return None;
+    }
+    let context = Context::line(linenumber, line, range.start, end - range.start);
+    if let Ok(v) = line[range.start..end].trim().parse::<T>() {
+        Some(v)
+    } else {
+        errors.push(PDBError::new(
+            ErrorLevel::InvalidatingError,
+            "Invalid data in field",
+            format!(
+                "The text presented is not of the right kind ({}).",
+                std::any::type_name::<T>()
+            ),
+            context,
+        ));
+        None
+    }
+}
+
 /// Parse a field from a line, with T::default() as fall back, leave errors in the given mutable vec.
 fn parse<T: FromStr + Default>(
     linenumber: usize,
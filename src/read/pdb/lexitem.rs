@@ -1,3 +1,12 @@
+/// A residue as given in many records
+/// * name
+/// * serial number
+/// * insertion code
+/// * chain id
+pub type LexResidue = (String, isize, Option<String>, String);
+/// An atom as given in bond records: (name, alternative location, residue)
+pub type LexAtom = (String, Option<String>, LexResidue);
+
 /// A definition of all lines that a PDB file can contain (and can be parsed by this program)
 /// with all properties saved as primitive data types.
 ///
@@ -9,6 +18,29 @@ pub enum LexItem {
     /// * date of deposition
     /// * identification
     Header(String, String, String),
+    /// A line of a record from the title section consisting of free text which can be continued
+    /// over multiple lines (TITLE, COMPND, SOURCE, KEYWDS, EXPDTA, AUTHOR)
+    /// * record name
+    /// * text
+    TitleSection(String, String),
+    /// A heterogen record (HET, HETNAM, HETSYN, or FORMUL)
+    /// * record name
+    /// * component id
+    /// * text
+    Heterogen(String, String, String),
+    /// A REVDAT record
+    /// * modification number
+    /// * modification date
+    /// * identifier
+    /// * modification type
+    /// * names of the modified records
+    Revdat(
+        usize,
+        Option<String>,
+        Option<String>,
+        Option<usize>,
+        Vec<String>,
+    ),
     /// A REMARK saved as the remark-type-number and the remark line itself
     Remark(usize, String),
     /// An Atom with all its information, including the deprecated and rarely used fields.
@@ -188,6 +220,70 @@ pub enum LexItem {
         (String, isize, Option<String>, String),
         Option<(String, String, f64)>,
     ),
+    /// A HELIX record
+    /// * Serial number
+    /// * Helix id
+    /// * Initial residue (name, serial number, insertion code, chain id)
+    /// * Terminal residue (name, serial number, insertion code, chain id)
+    /// * Helix class
+    /// * Comment
+    /// * Length
+    Helix(
+        usize,
+        String,
+        LexResidue,
+        LexResidue,
+        usize,
+        Option<String>,
+        Option<usize>,
+    ),
+    /// A SHEET record, defining a single strand
+    /// * Strand number
+    /// * Sheet id
+    /// * Number of strands in the sheet
+    /// * Initial residue (name, serial number, insertion code, chain id)
+    /// * Terminal residue (name, serial number, insertion code, chain id)
+    /// * Sense
+    /// * Registration, the atom name and residue in the current strand followed by the atom name and residue in the previous strand
+    Sheet(
+        usize,
+        String,
+        usize,
+        LexResidue,
+        LexResidue,
+        isize,
+        Option<(String, LexResidue, String, LexResidue)>,
+    ),
+    /// A CISPEP record, specifying a cis peptide bond
+    /// * Serial number
+    /// * Residue before the peptide bond (name, serial number, insertion code, chain id)
+    /// * Residue after the peptide bond (name, serial number, insertion code, chain id)
+    /// * Model number, 0 if there is only a single model
+    /// * Omega angle
+    CisPeptide(usize, LexResidue, LexResidue, usize, Option<f64>),
+    /// A SITE record, listing (some of) the residues of a site
+    /// * Site id
+    /// * Residues (name, serial number, insertion code, chain id)
+    Site(String, Vec<LexResidue>),
+    /// A LINK or LINKR record, specifying a bond between two atoms
+    /// * Atom 1 (name, alternative location, residue)
+    /// * Atom 2 (name, alternative location, residue)
+    /// * Symmetry operator for atom 1, in the mmCIF notation eg '1_555'
+    /// * Symmetry operator for atom 2, in the mmCIF notation eg '1_555'
+    /// * Bond length
+    /// * Link name (only in LINKR records)
+    Link(
+        LexAtom,
+        LexAtom,
+        Option<String>,
+        Option<String>,
+        Option<f64>,
+        Option<String>,
+    ),
+    /// A CONECT record, specifying the connectivity between atoms
+    /// * Serial number of the central atom
+    /// * Serial numbers of the atoms bonded to the central atom
+    Connect(usize, Vec<usize>),
     /// ENDMODEL, end of the current model
     EndModel(),
     /// TER =, termination of ATOM lines to allow for HETATMs to be defined
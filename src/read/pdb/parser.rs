@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
@@ -9,12 +9,16 @@ use crate::structs::*;
 use crate::validate::*;
 use crate::ReadOptions;
 use crate::StrictnessLevel;
+use crate::TransformationMatrix;
 
 use super::lexer::*;
 use super::lexitem::*;
 use super::temporary_structs::*;
 use super::validate::*;
 
+/// The SEQRES records per chain id: (serial number, total number of residues, residue names)
+type SeqresMap = HashMap<String, Vec<(usize, usize, Vec<String>)>>;
+
 /// Parse the given file into a PDB struct.
 /// Returns a PDBError if a BreakingError is found. Otherwise it returns the PDB with all errors/warnings found while parsing it.
 ///
@@ -93,12 +97,17 @@ where
     let mut current_model_number = 0;
     let mut current_model: IndexMap<String, IndexMap<(isize, Option<String>), Residue>> =
         IndexMap::new();
-    let mut sequence: HashMap<String, Vec<(usize, usize, Vec<String>)>> = HashMap::new();
+    let mut sequence: SeqresMap = HashMap::new();
     let mut seqres_lines = Vec::new();
     let mut seqres_start_linenumber = usize::MAX;
     let mut database_references = Vec::new();
     let mut modifications = Vec::new();
     let mut bonds = Vec::new();
+    let mut connect_count = 0;
+    let mut header = Header::new();
+    let mut title_section: IndexMap<String, String> = IndexMap::new();
+    let mut heterogens: IndexMap<(String, String), String> = IndexMap::new();
+    let mut cis_peptides = Vec::new();
     let mut temp_scale = BuildUpMatrix::empty();
     let mut temp_origx = BuildUpMatrix::empty();
     let mut temp_mtrix: Vec<(usize, BuildUpMatrix, bool)> = Vec::new();
@@ -135,7 +144,35 @@ where
             Ok((result, line_errors)) => {
                 errors.extend(line_errors);
                 match result {
-                    LexItem::Header(_, _, identifier) => pdb.identifier = Some(identifier),
+                    LexItem::Header(classification, date, identifier) => {
+                        let classification = classification.trim();
+                        if !classification.is_empty() {
+                            header.classification = Some(classification.to_string());
+                        }
+                        header.deposition_date = date_from_pdb(&date);
+                        pdb.identifier = Some(identifier);
+                    }
+                    LexItem::TitleSection(record, text) => {
+                        append_continuation(title_section.entry(record).or_default(), &text);
+                    }
+                    LexItem::Heterogen(record, id, text) => {
+                        append_continuation(heterogens.entry((record, id)).or_default(), &text);
+                    }
+                    LexItem::Revdat(number, date, identifier, modification_type, records) => {
+                        if let Some(revision) =
+                            header.revisions.iter_mut().find(|r| r.number == number)
+                        {
+                            revision.records.extend(records);
+                        } else {
+                            header.revisions.push(Revision {
+                                number,
+                                date: date.as_deref().and_then(date_from_pdb),
+                                identifier,
+                                modification_type: modification_type.unwrap_or(1),
+                                records,
+                            });
+                        }
+                    }
                     LexItem::Remark(num, text) => {
                         let _ = pdb.add_remark(num, text.to_string()); // Better error messages are created downstream
                     }
@@ -367,19 +404,77 @@ where
                         }
                     }
                     item @ LexItem::Modres(..) => modifications.push((line_context.clone(), item)),
-                    item @ LexItem::SSBond(..) => bonds.push((line_context.clone(), item)),
+                    item @ (LexItem::SSBond(..) | LexItem::Link(..)) => {
+                        bonds.push((line_context.clone(), item));
+                    }
+                    item @ LexItem::Connect(..) => {
+                        connect_count += 1;
+                        bonds.push((line_context.clone(), item));
+                    }
+                    LexItem::Helix(serial_number, id, start, end, class, comment, length) => {
+                        let mut helix = Helix::new(
+                            serial_number,
+                            id,
+                            residue_reference(start),
+                            residue_reference(end),
+                            HelixClass::from_number(class).unwrap_or_default(),
+                        );
+                        helix.comment = comment;
+                        helix.length = length;
+                        pdb.add_secondary_structure(SecondaryStructure::Helix(helix));
+                    }
+                    LexItem::CisPeptide(serial_number, start, end, model, angle) => {
+                        let mut cis_peptide = CisPeptide::new(
+                            serial_number,
+                            model,
+                            residue_reference(start),
+                            residue_reference(end),
+                        );
+                        cis_peptide.angle = angle;
+                        cis_peptides.push(cis_peptide);
+                    }
+                    LexItem::Site(id, residues) => {
+                        let residues = residues.into_iter().map(residue_reference);
+                        if let Some(site) = pdb.site_mut(&id) {
+                            site.residues.extend(residues);
+                        } else {
+                            let mut site = Site::new(id);
+                            site.residues.extend(residues);
+                            pdb.add_site(site);
+                        }
+                    }
+                    LexItem::Sheet(strand, id, strand_count, start, end, sense, registration) => {
+                        let mut strand = Strand::new(
+                            strand,
+                            id,
+                            strand_count,
+                            residue_reference(start),
+                            residue_reference(end),
+                            StrandSense::from_number(sense).unwrap_or(StrandSense::First),
+                        );
+                        strand.registration =
+                            registration.map(|(current_atom, current, previous_atom, previous)| {
+                                StrandRegistration {
+                                    current_atom,
+                                    current: residue_reference(current),
+                                    previous_atom,
+                                    previous: residue_reference(previous),
+                                }
+                            });
+                        pdb.add_secondary_structure(SecondaryStructure::Strand(strand));
+                    }
                     LexItem::Master(
                         num_remark,
                         num_empty,
                         _num_het,
-                        _num_helix,
-                        _num_sheet,
+                        num_helix,
+                        num_sheet,
                         _num_turn,
                         _num_site,
                         num_xform,
                         num_coord,
                         _num_ter,
-                        _num_connect,
+                        num_connect,
                         _num_seq,
                     ) => {
                         // The last atoms need to be added to make the MASTER checksum work out
@@ -436,6 +531,38 @@ where
                             )
                         );
                         }
+                        let helices = pdb.secondary_structure().filter(|s| s.is_helix()).count();
+                        if num_helix != helices {
+                            errors.push(
+                            PDBError::new(
+                                ErrorLevel::StrictWarning,
+                                "MASTER checksum failed",
+                                format!("The number of HELIX records ({helices}) is different then posed in the MASTER Record ({num_helix})"),
+                                line_context.clone()
+                            )
+                        );
+                        }
+                        let strands = pdb.secondary_structure().filter(|s| s.is_strand()).count();
+                        if num_sheet != strands {
+                            errors.push(
+                            PDBError::new(
+                                ErrorLevel::StrictWarning,
+                                "MASTER checksum failed",
+                                format!("The number of SHEET records ({strands}) is different then posed in the MASTER Record ({num_sheet})"),
+                                line_context.clone()
+                            )
+                        );
+                        }
+                        if num_connect != connect_count {
+                            errors.push(
+                            PDBError::new(
+                                ErrorLevel::StrictWarning,
+                                "MASTER checksum failed",
+                                format!("The number of CONECT records ({connect_count}) is different then posed in the MASTER Record ({num_connect})"),
+                                line_context.clone()
+                            )
+                        );
+                        }
                         if num_coord != pdb.total_atom_count() {
                             errors.push(
                             PDBError::new(
@@ -464,6 +591,29 @@ where
         ));
     }
 
+    pdb.refinement = parse_refinement(pdb.remarks());
+    for assembly in parse_assemblies(pdb.remarks()) {
+        pdb.add_assembly(assembly);
+    }
+    errors.extend(parse_missing(&mut pdb));
+    parse_site_remarks(&mut pdb);
+    // Model number 0 is used for structures with a single model
+    let first_model = pdb.models().next().map_or(1, Model::serial_number);
+    for mut cis_peptide in cis_peptides {
+        if cis_peptide.model == 0 {
+            cis_peptide.model = first_model;
+        }
+        pdb.add_cis_peptide(cis_peptide);
+    }
+    parse_heterogens(&mut pdb, heterogens);
+    parse_title_section(&mut header, &title_section);
+    header.revisions.sort_by_key(|r| r.number);
+    if header != Header::default() {
+        pdb.header = Some(header);
+    }
+    parse_entities(&mut pdb, &sequence);
+    pdb.align_sequences();
+
     for (chain_id, reference, complete) in database_references {
         if !complete {
             errors.push(PDBError::new(
@@ -643,13 +793,549 @@ fn add_modifications(pdb: &mut PDB, modifications: Vec<(Context, LexItem)>) -> V
     errors
 }
 
+/// Append the text of a continuation line to the text of a record. Continued lines are joined with
+/// a space, except if a word was broken over the lines with a hyphen (eg 'CO-' and 'CRYSTAL').
+fn append_continuation(text: &mut String, next: &str) {
+    if !text.is_empty() && (!text.ends_with('-') || text.ends_with(" -")) {
+        text.push(' ');
+    }
+    text.push_str(next);
+}
+
+/// Interpret the site descriptions in REMARK 800, adding the evidence and description to the
+/// sites defined by the SITE records. Sites that are only described in the remarks are added as
+/// well.
+fn parse_site_remarks(pdb: &mut PDB) {
+    let mut sites: Vec<Site> = Vec::new();
+    // Whether the last line was the evidence (true) or description (false), to handle continuations
+    let mut continued = None;
+    for (_, text) in pdb.remarks().filter(|(number, _)| *number == 800) {
+        let text = text.trim();
+        if let Some(id) = text.strip_prefix("SITE_IDENTIFIER:") {
+            sites.push(Site::new(id.trim()));
+            continued = None;
+        } else if let Some(site) = sites.last_mut() {
+            if let Some(evidence) = text.strip_prefix("EVIDENCE_CODE:") {
+                site.evidence = Some(evidence.trim().to_string());
+                continued = Some(true);
+            } else if let Some(details) = text.strip_prefix("SITE_DESCRIPTION:") {
+                site.details = Some(details.trim().to_string());
+                continued = Some(false);
+            } else if text.is_empty() {
+                continued = None;
+            } else if let Some(value) = match continued {
+                Some(true) => site.evidence.as_mut(),
+                Some(false) => site.details.as_mut(),
+                None => None,
+            } {
+                append_continuation(value, text);
+            }
+        }
+    }
+    for described in sites {
+        if let Some(site) = pdb.site_mut(&described.id) {
+            site.evidence = described.evidence;
+            site.details = described.details;
+        } else {
+            pdb.add_site(described);
+        }
+    }
+}
+
+/// Interpret the heterogen records (HET, HETNAM, HETSYN, and FORMUL) as chemical components. The
+/// number of molecules is removed from the formula (eg '2(C4 H10 O3)' and '*425(H2 O)').
+fn parse_heterogens(pdb: &mut PDB, records: IndexMap<(String, String), String>) {
+    for ((record, id), text) in records {
+        if id.is_empty() {
+            continue;
+        }
+        let mut component = pdb
+            .chemical_component(&id)
+            .cloned()
+            .unwrap_or_else(|| ChemicalComponent::new(id.as_str()));
+        match record.as_str() {
+            "HETNAM" => component.name = Some(text),
+            "HETSYN" => {
+                component.synonyms = text
+                    .split(';')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(ToString::to_string)
+                    .collect();
+            }
+            "FORMUL" => {
+                let formula = text.trim_start_matches('*');
+                let formula = match (formula.find('('), formula.strip_suffix(')')) {
+                    (Some(start), Some(inner))
+                        if formula[..start].chars().all(|c| c.is_ascii_digit()) =>
+                    {
+                        &inner[start + 1..]
+                    }
+                    _ => formula,
+                };
+                component.formula = Some(formula.trim().to_string());
+            }
+            _ => (),
+        }
+        pdb.add_chemical_component(component);
+    }
+}
+
+/// Build the polymer entities from the SEQRES records. The molecules from the COMPND records give the
+/// entity ids, descriptions, and chains. Chains with a sequence that are not part of any molecule
+/// are grouped by their sequence into new entities.
+fn parse_entities(pdb: &mut PDB, sequence: &SeqresMap) {
+    if sequence.is_empty() {
+        return;
+    }
+    let mut chains: Vec<(String, Vec<String>)> = sequence
+        .iter()
+        .map(|(chain_id, data)| {
+            let mut data: Vec<_> = data.iter().collect();
+            data.sort_by_key(|(ser_num, _, _)| *ser_num);
+            let residues = data
+                .into_iter()
+                .flat_map(|(_, _, values)| values.iter().cloned())
+                .collect();
+            (chain_id.clone(), residues)
+        })
+        .collect();
+    // Keep the order of the chains in the structure, followed by any chains without coordinates
+    let order: Vec<String> = pdb.chains().map(|c| c.id().to_string()).collect();
+    chains.sort_by_key(|(chain_id, _)| {
+        (
+            order
+                .iter()
+                .position(|c| c == chain_id)
+                .unwrap_or(order.len()),
+            chain_id.clone(),
+        )
+    });
+
+    let mut entities: Vec<Entity> = Vec::new();
+    if let Some(header) = &pdb.header {
+        for molecule in &header.molecules {
+            let mut entity = Entity::new(molecule.id.as_str());
+            entity.description = molecule.name().map(ToString::to_string);
+            entity.chains = molecule
+                .chains()
+                .into_iter()
+                .map(ToString::to_string)
+                .collect();
+            if let Some((_, residues)) = chains.iter().find(|(c, _)| entity.contains_chain(c)) {
+                entity.sequence = residues.clone();
+                entities.push(entity);
+            }
+        }
+    }
+    let mut next_id = entities
+        .iter()
+        .filter_map(|e| e.id.parse::<usize>().ok())
+        .max()
+        .unwrap_or(0)
+        + 1;
+    for (chain_id, residues) in chains {
+        if entities.iter().any(|e| e.contains_chain(&chain_id)) {
+            continue;
+        }
+        if let Some(entity) = entities
+            .iter_mut()
+            .find(|e| e.description.is_none() && e.sequence == residues)
+        {
+            entity.chains.push(chain_id);
+        } else {
+            let mut entity = Entity::new(next_id.to_string());
+            entity.sequence = residues;
+            entity.chains.push(chain_id);
+            entities.push(entity);
+            next_id += 1;
+        }
+    }
+    for mut entity in entities {
+        entity.entity_type = Some("polymer".to_string());
+        entity.polymer_type = guess_polymer_type(&entity.sequence).map(ToString::to_string);
+        pdb.add_entity(entity);
+    }
+}
+
+/// Interpret the free text records of the title section (TITLE, COMPND, SOURCE, KEYWDS, EXPDTA, AUTHOR)
+fn parse_title_section(header: &mut Header, records: &IndexMap<String, String>) {
+    /// Split the text on the given separator, trimming the parts and leaving out empty parts
+    fn split(text: &str, separator: char) -> Vec<String> {
+        text.split(separator)
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(ToString::to_string)
+            .collect()
+    }
+    for (record, text) in records {
+        match record.as_str() {
+            "TITLE" => header.title = Some(text.clone()),
+            "KEYWDS" => header.keywords = split(text, ','),
+            "EXPDTA" => header.experimental_methods = split(text, ';'),
+            "AUTHOR" => header.authors = split(text, ','),
+            "COMPND" | "SOURCE" => {
+                let mut id = "1".to_string();
+                let mut tokens: Vec<(String, String, String)> = Vec::new();
+                for part in split(text, ';') {
+                    if let Some((token, value)) = part.split_once(':') {
+                        let (token, value) = (token.trim(), value.trim());
+                        if token == "MOL_ID" {
+                            id = value.to_string();
+                        } else {
+                            tokens.push((id.clone(), token.to_string(), value.to_string()));
+                        }
+                    } else if let Some((_, _, value)) = tokens.last_mut() {
+                        // A semicolon inside of a value
+                        value.push_str("; ");
+                        value.push_str(&part);
+                    }
+                }
+                for (id, token, value) in tokens {
+                    let index =
+                        if let Some(index) = header.molecules.iter().position(|m| m.id == id) {
+                            index
+                        } else {
+                            header.molecules.push(MoleculeInfo::new(id));
+                            header.molecules.len() - 1
+                        };
+                    let molecule = &mut header.molecules[index];
+                    if record == "COMPND" {
+                        molecule.compound.push((token, value));
+                    } else {
+                        molecule.source.push((token, value));
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Parse the missing residues (REMARK 465) and missing atoms (REMARK 470) and add them to the
+/// chains they belong to. Entries without a model number apply to all models.
+fn parse_missing(pdb: &mut PDB) -> Vec<PDBError> {
+    /// Get the trimmed text in the given columns of the remark text, empty if out of range
+    fn column(text: &str, start: usize, end: usize) -> &str {
+        text.get(start..end.min(text.len()))
+            .unwrap_or_default()
+            .trim()
+    }
+    /// The model number, residue name, chain id, and residue id of a missing residue
+    type Location = (Option<usize>, String, String, (isize, Option<String>));
+    /// Get the location of a missing residue from the columns of a remark
+    fn residue(text: &str, chain: usize, serial: (usize, usize)) -> Option<Location> {
+        let serial_number = column(text, serial.0, serial.1).parse().ok()?;
+        let insertion_code = column(text, serial.1, serial.1 + 1);
+        Some((
+            column(text, 0, 3).parse().ok(),
+            column(text, 4, 7).to_string(),
+            column(text, chain, chain + 1).to_string(),
+            (
+                serial_number,
+                (!insertion_code.is_empty()).then(|| insertion_code.to_string()),
+            ),
+        ))
+    }
+    let mut residues = Vec::new();
+    let mut atoms = Vec::new();
+    let mut in_table = (false, false);
+    for (number, text) in pdb.remarks() {
+        match number {
+            465 if in_table.0 => {
+                if let Some((model, name, chain, (serial, insertion_code))) =
+                    residue(text, 8, (10, 15))
+                {
+                    let missing = MissingResidue::new(name, (serial, insertion_code.as_deref()));
+                    residues.push((model, chain, missing));
+                }
+            }
+            465 => in_table.0 = text.contains("RES C SSSEQI"),
+            470 if in_table.1 => {
+                if let Some((model, name, chain, (serial, insertion_code))) =
+                    residue(text, 8, (9, 13))
+                {
+                    let names = column(text, 14, text.len())
+                        .split_whitespace()
+                        .map(str::to_string)
+                        .collect();
+                    let missing =
+                        MissingAtoms::new(name, (serial, insertion_code.as_deref()), names);
+                    atoms.push((model, chain, missing));
+                }
+            }
+            470 => in_table.1 = text.contains("RES CSSEQI"),
+            _ => (),
+        }
+    }
+
+    let mut unknown = Vec::new();
+    for (model, chain, missing) in residues {
+        let mut found = false;
+        for c in pdb.chains_with_id_mut(model, &chain) {
+            c.add_missing_residue(missing.clone());
+            found = true;
+        }
+        if !found && !unknown.contains(&chain) {
+            unknown.push(chain);
+        }
+    }
+    for (model, chain, missing) in atoms {
+        let mut found = false;
+        for c in pdb.chains_with_id_mut(model, &chain) {
+            c.add_missing_atoms(missing.clone());
+            found = true;
+        }
+        if !found && !unknown.contains(&chain) {
+            unknown.push(chain);
+        }
+    }
+    unknown
+        .into_iter()
+        .map(|chain| {
+            PDBError::new(
+                ErrorLevel::LooseWarning,
+                "Missing residues of unknown chain",
+                format!("REMARK 465 or 470 lists missing residues or atoms of chain '{chain}', but this chain is not present in the coordinates, so these are ignored."),
+                Context::None,
+            )
+        })
+        .collect()
+}
+
+/// Parse the biological assemblies from REMARK 350. Every 'APPLY THE FOLLOWING TO CHAINS' line
+/// starts a new generator, with its operators given in the BIOMT lines following it.
+fn parse_assemblies<'a>(remarks: impl Iterator<Item = &'a (usize, String)>) -> Vec<Assembly> {
+    /// The oligomeric states as written in words, the index is the number of chains minus one
+    const OLIGOMERS: [&str; 12] = [
+        "MONOMERIC",
+        "DIMERIC",
+        "TRIMERIC",
+        "TETRAMERIC",
+        "PENTAMERIC",
+        "HEXAMERIC",
+        "HEPTAMERIC",
+        "OCTAMERIC",
+        "NONAMERIC",
+        "DECAMERIC",
+        "UNDECAMERIC",
+        "DODECAMERIC",
+    ];
+    /// Get the chain ids from a list like 'A, B, C,'
+    fn chains(text: &str) -> impl Iterator<Item = String> + '_ {
+        text.split(',')
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .map(str::to_string)
+    }
+    let mut assemblies: Vec<Assembly> = Vec::new();
+    let mut determined = (false, false);
+    let mut matrix = [[0.0; 4]; 3];
+    for (_, text) in remarks.filter(|(number, _)| *number == 350) {
+        let text = text.trim();
+        if let Some(id) = text.strip_prefix("BIOMOLECULE:") {
+            assemblies.push(Assembly::new(id.trim()));
+            determined = (false, false);
+            continue;
+        }
+        let assembly = match assemblies.last_mut() {
+            Some(assembly) => assembly,
+            None => continue,
+        };
+        if let Some((key, value)) = text.split_once(':') {
+            let value = value.trim();
+            match key.trim() {
+                "AUTHOR DETERMINED BIOLOGICAL UNIT" => {
+                    determined.0 = true;
+                    assembly.oligomeric_details = Some(value.to_lowercase());
+                }
+                "SOFTWARE DETERMINED QUATERNARY STRUCTURE" => {
+                    determined.1 = true;
+                    if assembly.oligomeric_details.is_none() {
+                        assembly.oligomeric_details = Some(value.to_lowercase());
+                    }
+                }
+                "APPLY THE FOLLOWING TO CHAINS" => assembly
+                    .generators
+                    .push(AssemblyGenerator::new(chains(value).collect(), Vec::new())),
+                "AND CHAINS" => {
+                    if let Some(generator) = assembly.generators.last_mut() {
+                        generator.chains.extend(chains(value));
+                    }
+                }
+                _ => (),
+            }
+            assembly.details = match determined {
+                (true, true) => Some("author_and_software_defined_assembly".to_string()),
+                (true, false) => Some("author_defined_assembly".to_string()),
+                (false, true) => Some("software_defined_assembly".to_string()),
+                (false, false) => None,
+            };
+            assembly.oligomeric_count = assembly.oligomeric_details.as_ref().and_then(|d| {
+                let d = d.to_uppercase();
+                d.split('-')
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .or_else(|| {
+                        OLIGOMERS
+                            .iter()
+                            .position(|o| *o == d)
+                            .map(|index| index + 1)
+                    })
+            });
+        } else if text.starts_with("BIOMT") {
+            let fields: Vec<&str> = text.split_whitespace().collect();
+            let row = fields[0][5..]
+                .parse::<usize>()
+                .ok()
+                .filter(|r| (1..=3).contains(r));
+            let values: Option<Vec<f64>> = fields.iter().skip(2).map(|v| v.parse().ok()).collect();
+            if let (Some(row), Some(values), Some(generator)) =
+                (row, values, assembly.generators.last_mut())
+            {
+                if values.len() == 4 {
+                    matrix[row - 1] = [values[0], values[1], values[2], values[3]];
+                    if row == 3 {
+                        generator.operators.push(AssemblyOperator::new(
+                            fields[1],
+                            TransformationMatrix::from_matrix(matrix),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    assemblies
+}
+
+/// Parse the resolution (REMARK 2), refinement statistics (REMARK 3), and data collection
+/// statistics (REMARK 200) from the remarks. Returns `None` if none of the statistics are found.
+fn parse_refinement<'a>(
+    remarks: impl Iterator<Item = &'a (usize, String)>,
+) -> Option<RefinementInfo> {
+    /// Parse a value, where 'NULL' and unparsable values are seen as missing
+    fn value<T: std::str::FromStr>(text: &str) -> Option<T> {
+        text.trim().parse().ok()
+    }
+    /// Set the target to the value if it is not set yet, the first occurrence is the overall value
+    fn set<T>(target: &mut Option<T>, value: Option<T>) {
+        if target.is_none() {
+            *target = value;
+        }
+    }
+    let mut refinement = RefinementInfo::new();
+    let mut reflections = ReflectionStatistics::default();
+    let mut in_highest_shell = false;
+    for (number, text) in remarks {
+        if *number == 2 {
+            if let Some(resolution) = text.trim().strip_prefix("RESOLUTION.") {
+                refinement.resolution =
+                    value(resolution.trim().trim_end_matches("ANGSTROMS.").trim_end());
+            }
+            continue;
+        }
+        if *number == 200 && text.contains("IN THE HIGHEST RESOLUTION SHELL") {
+            in_highest_shell = true;
+        }
+        let (key, text) = if let Some((key, text)) = text.split_once(':') {
+            (key.split_whitespace().collect::<Vec<_>>().join(" "), text)
+        } else {
+            continue;
+        };
+        match (*number, key.as_str()) {
+            (3, "PROGRAM") => set(&mut refinement.program, Some(text.trim().to_string())),
+            (3, "RESOLUTION RANGE HIGH (ANGSTROMS)") => {
+                set(&mut refinement.resolution, value(text));
+            }
+            (3, "RESOLUTION RANGE LOW (ANGSTROMS)") => {
+                set(&mut refinement.resolution_low, value(text));
+            }
+            (3, "COMPLETENESS FOR RANGE (%)" | "COMPLETENESS (WORKING+TEST) (%)") => {
+                set(&mut refinement.completeness, value(text));
+            }
+            (3, "NUMBER OF REFLECTIONS") => set(&mut refinement.reflection_count, value(text)),
+            (3, "R VALUE (WORKING + TEST SET)" | "R VALUE (WORKING+TEST SET)") => {
+                set(&mut refinement.r_all, value(text));
+            }
+            (3, "R VALUE (WORKING SET)") => set(&mut refinement.r_work, value(text)),
+            (3, "FREE R VALUE") => set(&mut refinement.r_free, value(text)),
+            (3, "FREE R VALUE TEST SET SIZE (%)") => {
+                set(&mut refinement.free_set_percentage, value(text));
+            }
+            (3, "FREE R VALUE TEST SET COUNT") => {
+                set(&mut refinement.free_set_count, value(text));
+            }
+            (3, "MEAN B VALUE (OVERALL, A**2)") => {
+                set(&mut refinement.mean_b_factor, value(text));
+            }
+            (3, "PROTEIN ATOMS") => set(&mut refinement.protein_atoms, value(text)),
+            (3, "NUCLEIC ACID ATOMS") => set(&mut refinement.nucleic_acid_atoms, value(text)),
+            (3, "HETEROGEN ATOMS") => set(&mut refinement.heterogen_atoms, value(text)),
+            (3, "SOLVENT ATOMS") => set(&mut refinement.solvent_atoms, value(text)),
+            (200, _) if in_highest_shell => (),
+            (200, "RESOLUTION RANGE HIGH (A)") => {
+                set(&mut reflections.resolution_high, value(text));
+            }
+            (200, "RESOLUTION RANGE LOW (A)") => {
+                set(&mut reflections.resolution_low, value(text));
+            }
+            (200, "NUMBER OF UNIQUE REFLECTIONS") => {
+                set(&mut reflections.unique_reflections, value(text));
+            }
+            (200, "COMPLETENESS FOR RANGE (%)") => {
+                set(&mut reflections.completeness, value(text));
+            }
+            (200, "DATA REDUNDANCY") => set(&mut reflections.redundancy, value(text)),
+            (200, "R MERGE (I)") => set(&mut reflections.r_merge, value(text)),
+            (200, "<I/SIGMA(I)> FOR THE DATA SET") => {
+                set(&mut reflections.i_over_sigma, value(text));
+            }
+            _ => (),
+        }
+    }
+    if reflections != ReflectionStatistics::default() {
+        refinement.reflections = Some(reflections);
+    }
+    if refinement.is_empty() {
+        None
+    } else {
+        Some(refinement)
+    }
+}
+
+/// Create a residue reference from the lexed residue (name, serial number, insertion code, chain id)
+fn residue_reference(
+    (name, serial_number, insertion_code, chain_id): LexResidue,
+) -> ResidueReference {
+    ResidueReference {
+        chain_id,
+        serial_number,
+        insertion_code,
+        name,
+    }
+}
+
 /// Adds all bonds to the PDB, has to be done after all Atoms are already in place
 #[allow(clippy::unwrap_used)]
 fn add_bonds(pdb: &mut PDB, bonds: Vec<(Context, LexItem)>) -> Vec<PDBError> {
     let mut errors = Vec::new();
+    // CONECT records refer to atoms by serial number, which is only unique within a model
+    let serial_numbers: Vec<HashMap<usize, usize>> =
+        if bonds.iter().any(|(_, b)| matches!(b, LexItem::Connect(..))) {
+            pdb.models()
+                .map(|m| {
+                    m.atoms()
+                        .map(|a| (a.serial_number(), a.counter()))
+                        .collect()
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+    let mut connected = HashSet::new();
     for (context, bond) in bonds {
         match bond {
-            LexItem::SSBond(atom1, atom2, ..) => {
+            LexItem::SSBond(atom1, atom2, extra) => {
                 let find = |atom: (String, isize, Option<String>, String)| {
                     pdb.chains()
                         .find(|c| c.id() == atom.3)
@@ -672,7 +1358,14 @@ fn add_bonds(pdb: &mut PDB, bonds: Vec<(Context, LexItem)>) -> Vec<PDBError> {
                 let ref2 = find(atom2);
 
                 if let (Some(counter1), Some(counter2)) = (ref1, ref2) {
-                    pdb.add_bond_counters(counter1, counter2, Bond::Disulfide);
+                    connected.insert((counter1.min(counter2), counter1.max(counter2)));
+                    let details = extra.map(|(sym1, sym2, distance)| BondDetails {
+                        symmetry_operator_1: Some(sym1),
+                        symmetry_operator_2: Some(sym2),
+                        distance: Some(distance),
+                        ..BondDetails::default()
+                    });
+                    pdb.add_bond_counters(counter1, counter2, Bond::Disulfide, details);
                 } else {
                     errors.push(PDBError::new(
                         ErrorLevel::InvalidatingError,
@@ -682,6 +1375,70 @@ fn add_bonds(pdb: &mut PDB, bonds: Vec<(Context, LexItem)>) -> Vec<PDBError> {
                     ));
                 }
             }
+            LexItem::Link(atom1, atom2, sym1, sym2, distance, name) => {
+                let (name1, alt_loc1, residue1) = atom1;
+                let (name2, alt_loc2, residue2) = atom2;
+                let (residue1, residue2) =
+                    (residue_reference(residue1), residue_reference(residue2));
+                let (found, complete) = pdb.find_bond_partners(
+                    (&name1, alt_loc1.as_deref(), &residue1),
+                    (&name2, alt_loc2.as_deref(), &residue2),
+                );
+                let details = BondDetails {
+                    symmetry_operator_1: sym1,
+                    symmetry_operator_2: sym2,
+                    distance,
+                    link_name: name,
+                    ..BondDetails::default()
+                };
+                for (counter1, counter2, metal) in found {
+                    connected.insert((counter1.min(counter2), counter1.max(counter2)));
+                    let bond = if metal {
+                        Bond::MetalCoordination
+                    } else {
+                        Bond::Covalent
+                    };
+                    pdb.add_bond_counters(counter1, counter2, bond, Some(details.clone()));
+                }
+                if !complete {
+                    errors.push(PDBError::new(
+                        ErrorLevel::GeneralWarning,
+                        "Could not find a bond partner",
+                        "One of the atoms could not be found while parsing a LINK record.",
+                        context,
+                    ));
+                }
+            }
+            LexItem::Connect(serial_number, bonded) => {
+                let mut missing = false;
+                for model in &serial_numbers {
+                    let counter1 = if let Some(c) = model.get(&serial_number) {
+                        *c
+                    } else {
+                        missing = true;
+                        continue;
+                    };
+                    for partner in &bonded {
+                        if let Some(&counter2) = model.get(partner) {
+                            // Every bond is normally listed twice, once for each atom
+                            let key = (counter1.min(counter2), counter1.max(counter2));
+                            if connected.insert(key) {
+                                pdb.add_bond_counters(counter1, counter2, Bond::Covalent, None);
+                            }
+                        } else {
+                            missing = true;
+                        }
+                    }
+                }
+                if missing {
+                    errors.push(PDBError::new(
+                        ErrorLevel::GeneralWarning,
+                        "Could not find a bond partner",
+                        "One of the atoms could not be found while parsing a CONECT record.",
+                        context,
+                    ));
+                }
+            }
             _ => {
                 panic!(
                     "Found an invalid element in the bonds list, it is not a valid bond LexItem"
@@ -56,7 +56,9 @@ pub fn validate_seqres(
                     context.clone()
                 ));
             }
-            let mut offset = 0;
+            // Without a DBREF record the position of the sequence in the residue numbering is not
+            // known, so the residues cannot be compared and missing residues cannot be added
+            let mut offset;
             if let Some(db_ref) = chain.database_reference() {
                 offset = db_ref.pdb_position.start;
                 for dif in &db_ref.differences {
@@ -73,6 +75,8 @@ pub fn validate_seqres(
                         context.clone()
                     ));
                 }
+            } else {
+                continue;
             }
 
             let copy = chain.clone();
@@ -0,0 +1,4 @@
+/// Parse PDBQT files into PDB structures
+mod parser;
+
+pub(crate) use parser::*;
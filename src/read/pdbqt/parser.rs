@@ -0,0 +1,282 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use crate::error::*;
+use crate::read::general::ReadResult;
+use crate::structs::*;
+use crate::ReadOptions;
+
+/// Parse the given PDBQT file into a PDB struct with [`ReadOptions`].
+pub(crate) fn open_pdbqt_with_options(
+    filename: impl AsRef<str>,
+    options: &ReadOptions,
+) -> ReadResult {
+    let filename = filename.as_ref();
+    let file = if let Ok(f) = File::open(filename) {
+        f
+    } else {
+        return Err(vec![PDBError::new(ErrorLevel::BreakingError, "Could not open file", "Could not open the specified file, make sure the path is correct, you have permission, and that it is not open in another program.", Context::show(filename))]);
+    };
+    open_pdbqt_raw_with_options(BufReader::new(file), options)
+}
+
+/// Parse the given PDBQT stream into a [`PDB`] struct with [`ReadOptions`].
+///
+/// PDBQT files are PDB files with the partial charge (columns 71-76) and the AutoDock atom type
+/// (columns 78-79) instead of the segment identifier, element, and charge. The atom lines are
+/// rewritten with the element derived from the AutoDock atom type and lexed and parsed as PDB
+/// file, after which the partial charges and atom types are stored in the atoms (see
+/// [`Atom::partial_charge`] and [`Atom::atom_type`]). The `ROOT`, `BRANCH`, and `TORSDOF`
+/// records of every model, and the `REMARK` records (eg `REMARK VINA RESULT`), are
+/// stored as the [`TorsionTree`] of that model.
+pub(crate) fn open_pdbqt_raw_with_options<T>(
+    input: BufReader<T>,
+    options: &ReadOptions,
+) -> ReadResult
+where
+    T: std::io::Read,
+{
+    let mut errors = Vec::new();
+    let mut pdb_text = String::new();
+    // The partial charge and atom type of every atom in the order they are given to the PDB
+    // parser, so indexed by the atom ID it assigns
+    let mut columns: Vec<(Option<f64>, String, Context)> = Vec::new();
+    let mut trees = Vec::new();
+    let mut tree = TorsionTree::new(0);
+    let mut branches: Vec<TorsionBranch> = Vec::new();
+    let mut in_root = false;
+
+    for (mut linenumber, read_line) in input.lines().enumerate() {
+        linenumber += 1; // 1 based indexing in files
+
+        let line = if let Ok(l) = read_line {
+            l
+        } else {
+            return Err(vec![PDBError::new(
+                ErrorLevel::BreakingError,
+                "Could read line",
+                format!("Could not read line {linenumber} while parsing the input file."),
+                Context::None,
+            )]);
+        };
+        let context = Context::full_line(linenumber, &line);
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let mut pdb_line = line.clone();
+        match fields.first().copied().unwrap_or_default() {
+            "MODEL" => {
+                finish_tree(&mut trees, &mut tree, &mut branches, &mut errors, &context);
+                tree = TorsionTree::new(
+                    line.get(6..)
+                        .and_then(|n| n.trim().parse().ok())
+                        .unwrap_or(0),
+                );
+            }
+            "ENDMDL" => {
+                let number = tree.model;
+                finish_tree(&mut trees, &mut tree, &mut branches, &mut errors, &context);
+                tree = TorsionTree::new(number);
+            }
+            "ROOT" => in_root = true,
+            "ENDROOT" => in_root = false,
+            "BRANCH" | "ENDBRANCH" => {
+                let numbers: Vec<usize> =
+                    fields[1..].iter().filter_map(|f| f.parse().ok()).collect();
+                if numbers.len() != 2 {
+                    errors.push(PDBError::new(
+                        ErrorLevel::InvalidatingError,
+                        "Invalid branch",
+                        "A BRANCH or ENDBRANCH record should contain the serial numbers of the two atoms of the rotatable bond.",
+                        context,
+                    ));
+                } else if fields[0] == "BRANCH" {
+                    branches.push(TorsionBranch {
+                        parent: numbers[0],
+                        child: numbers[1],
+                        ..TorsionBranch::default()
+                    });
+                } else if let Some(branch) = branches.pop() {
+                    if branch.parent != numbers[0] || branch.child != numbers[1] {
+                        errors.push(PDBError::new(
+                            ErrorLevel::InvalidatingError,
+                            "Invalid branch",
+                            format!(
+                                "This ENDBRANCH record does not match the last opened BRANCH {} {}.",
+                                branch.parent, branch.child
+                            ),
+                            context,
+                        ));
+                    }
+                    if let Some(parent) = branches.last_mut() {
+                        parent.branches.push(branch);
+                    } else {
+                        tree.branches.push(branch);
+                    }
+                } else {
+                    errors.push(PDBError::new(
+                        ErrorLevel::InvalidatingError,
+                        "Invalid branch",
+                        "This ENDBRANCH record closes a branch that was not opened.",
+                        context,
+                    ));
+                }
+            }
+            "TORSDOF" => match fields.get(1).and_then(|n| n.parse().ok()) {
+                Some(torsdof) => tree.torsdof = Some(torsdof),
+                None => errors.push(PDBError::new(
+                    ErrorLevel::InvalidatingError,
+                    "Invalid TORSDOF",
+                    "The TORSDOF record should contain the number of torsional degrees of freedom.",
+                    context,
+                )),
+            },
+            // Remarks are free text in PDBQT files (eg the results written by Vina), even when
+            // they start with a number like the torsion descriptions of AutoDockTools
+            "REMARK" => {
+                tree.remarks
+                    .push(line.get(7..).unwrap_or_default().trim_end().to_string());
+                pdb_line = String::new();
+            }
+            _ if line.starts_with("ATOM") || line.starts_with("HETATM") => {
+                let serial_number = line.get(6..11).and_then(|n| n.trim().parse().ok());
+                if let Some(serial_number) = serial_number {
+                    if in_root {
+                        tree.root.push(serial_number);
+                    } else if let Some(branch) = branches.last_mut() {
+                        branch.atoms.push(serial_number);
+                    }
+                }
+                let charge = line.get(70..76).map_or("", str::trim);
+                let charge = if charge.is_empty() {
+                    None
+                } else if let Ok(charge) = charge.parse() {
+                    Some(charge)
+                } else {
+                    errors.push(PDBError::new(
+                        ErrorLevel::InvalidatingError,
+                        "Invalid partial charge",
+                        format!("The partial charge '{charge}' is not a valid number."),
+                        context.clone(),
+                    ));
+                    None
+                };
+                let atom_type = line.get(77..).map_or("", str::trim);
+                let element = element_from_autodock_type(atom_type);
+                // Lines that end before the coordinates are left to the PDB parser to report
+                if line.len() < 54 {
+                    errors.push(PDBError::new(
+                        ErrorLevel::InvalidatingError,
+                        "Line too short",
+                        "This atom line was too short to contain the coordinates (at 30 to 54).",
+                        context.clone(),
+                    ));
+                } else {
+                    pdb_line = format!(
+                        "{:66}{:10}{:>2}",
+                        line.get(..66).unwrap_or(&line),
+                        "",
+                        element.as_ref().map_or("", Element::symbol)
+                    );
+                }
+                if !(options.discard_hydrogens && element == Some(Element::H)) {
+                    columns.push((charge, atom_type.to_string(), context));
+                }
+            }
+            _ => (),
+        }
+        pdb_text.push_str(&pdb_line);
+        pdb_text.push('\n');
+    }
+    finish_tree(
+        &mut trees,
+        &mut tree,
+        &mut branches,
+        &mut errors,
+        &Context::None,
+    );
+
+    let (mut pdb, pdb_errors) = super::super::pdb::open_pdb_raw_with_options(
+        BufReader::new(pdb_text.as_bytes()),
+        Context::None,
+        options,
+    )?;
+    errors.extend(pdb_errors);
+
+    for atom in pdb.atoms_mut() {
+        let index: Option<usize> = atom.id().parse().ok();
+        if let Some((charge, atom_type, context)) = index.and_then(|i| columns.get(i)) {
+            if let Some(charge) = charge {
+                if let Err(message) = atom.set_partial_charge(*charge) {
+                    errors.push(PDBError::new(
+                        ErrorLevel::InvalidatingError,
+                        "Invalid partial charge",
+                        message,
+                        context.clone(),
+                    ));
+                }
+            }
+            if !atom_type.is_empty() {
+                if let Err(message) = atom.set_atom_type(atom_type) {
+                    errors.push(PDBError::new(
+                        ErrorLevel::InvalidatingError,
+                        "Invalid atom type",
+                        message,
+                        context.clone(),
+                    ));
+                }
+            }
+        }
+    }
+    for tree in trees {
+        pdb.add_torsion_tree(tree);
+    }
+
+    if errors.iter().any(|e| e.fails(options.level)) {
+        Err(errors)
+    } else {
+        Ok((pdb, errors))
+    }
+}
+
+/// Store the current torsion tree if it contains anything, and check that all branches are closed
+fn finish_tree(
+    trees: &mut Vec<TorsionTree>,
+    tree: &mut TorsionTree,
+    branches: &mut Vec<TorsionBranch>,
+    errors: &mut Vec<PDBError>,
+    context: &Context,
+) {
+    if !branches.is_empty() {
+        errors.push(PDBError::new(
+            ErrorLevel::InvalidatingError,
+            "Unclosed branch",
+            format!(
+                "{} BRANCH record(s) were not closed with an ENDBRANCH record before the end of the model.",
+                branches.len()
+            ),
+            context.clone(),
+        ));
+        branches.clear();
+    }
+    let tree = std::mem::take(tree);
+    if !tree.root.is_empty()
+        || !tree.branches.is_empty()
+        || tree.torsdof.is_some()
+        || !tree.remarks.is_empty()
+    {
+        trees.push(tree);
+    }
+}
+
+/// Derive the element from an AutoDock atom type, eg `O` from `OA`
+fn element_from_autodock_type(atom_type: &str) -> Option<Element> {
+    match atom_type {
+        "A" | "G" | "GA" | "J" | "Q" | "Z" => Some(Element::C),
+        "HD" | "HS" => Some(Element::H),
+        "NA" | "NS" => Some(Element::N),
+        "OA" | "OS" | "W" => Some(Element::O),
+        "SA" => Some(Element::S),
+        // The dummy atoms of flexible macrocycles, eg G0 and CG0
+        t if t.starts_with('G') || t.starts_with("CG") => Some(Element::C),
+        t => Element::from_symbol(t),
+    }
+}
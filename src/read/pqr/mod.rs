@@ -0,0 +1,4 @@
+/// Parse PQR files into PDB structures
+mod parser;
+
+pub(crate) use parser::*;
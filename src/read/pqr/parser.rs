@@ -0,0 +1,286 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::str::FromStr;
+
+use crate::error::*;
+use crate::read::general::{guess_element, ReadResult};
+use crate::structs::*;
+use crate::validate::*;
+use crate::ReadOptions;
+
+/// Parse the given PQR file into a PDB struct with [`ReadOptions`].
+pub(crate) fn open_pqr_with_options(
+    filename: impl AsRef<str>,
+    options: &ReadOptions,
+) -> ReadResult {
+    let filename = filename.as_ref();
+    let file = if let Ok(f) = File::open(filename) {
+        f
+    } else {
+        return Err(vec![PDBError::new(ErrorLevel::BreakingError, "Could not open file", "Could not open the specified file, make sure the path is correct, you have permission, and that it is not open in another program.", Context::show(filename))]);
+    };
+    open_pqr_raw_with_options(BufReader::new(file), options)
+}
+
+/// Parse the given PQR stream into a [`PDB`] struct with [`ReadOptions`].
+///
+/// PQR files are whitespace delimited, every `ATOM` or `HETATM` line contains the serial number,
+/// atom name, residue name, an optional chain identifier, residue number (with an optional
+/// insertion code), the coordinates, the partial charge, and the radius. `MODEL`, `ENDMDL` and
+/// `TER` records are used to build the hierarchy as in PDB files, all other records are ignored.
+pub(crate) fn open_pqr_raw_with_options<T>(input: BufReader<T>, options: &ReadOptions) -> ReadResult
+where
+    T: std::io::Read,
+{
+    let mut errors = Vec::new();
+    let mut pdb = PDB::new();
+    let mut model = Model::new(0);
+    let mut chain_iter = ('A'..='Z').cycle();
+    let mut chain_id_new = chain_iter.next();
+    let mut id_iter = (1usize..).map(|i| i.to_string());
+
+    for (mut linenumber, read_line) in input.lines().enumerate() {
+        linenumber += 1; // 1 based indexing in files
+
+        let line = if let Ok(l) = read_line {
+            l
+        } else {
+            return Err(vec![PDBError::new(
+                ErrorLevel::BreakingError,
+                "Could read line",
+                format!("Could not read line {linenumber} while parsing the input file."),
+                Context::None,
+            )]);
+        };
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let record = fields.first().map(|r| r.to_ascii_uppercase());
+        match record.as_deref() {
+            Some("MODEL") => {
+                if model.atom_count() > 0 {
+                    pdb.add_model(model);
+                    if options.only_first_model {
+                        model = Model::new(0);
+                        break;
+                    }
+                }
+                let number = fields
+                    .get(1)
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or(pdb.model_count() + 1);
+                model = Model::new(number);
+                chain_iter = ('A'..='Z').cycle();
+                chain_id_new = chain_iter.next();
+            }
+            Some("ENDMDL") if model.atom_count() > 0 => {
+                let number = model.serial_number() + 1;
+                pdb.add_model(std::mem::replace(&mut model, Model::new(number)));
+                if options.only_first_model {
+                    break;
+                }
+            }
+            Some("TER") => chain_id_new = chain_iter.next(),
+            Some(record) if record.starts_with("ATOM") || record.starts_with("HETATM") => {
+                match parse_atom(&fields, linenumber, &line) {
+                    Ok(atom_line) => {
+                        let AtomLine {
+                            hetero,
+                            serial_number,
+                            name,
+                            residue_name,
+                            chain_id,
+                            residue_number,
+                            insertion_code,
+                            pos: (x, y, z),
+                            partial_charge,
+                            radius,
+                        } = atom_line;
+                        let valid_identifiers = [
+                            Some(residue_name.as_str()),
+                            chain_id.as_deref(),
+                            insertion_code.as_deref(),
+                        ]
+                        .into_iter()
+                        .flatten()
+                        .all(|identifier| prepare_identifier(identifier).is_some());
+                        if !valid_identifiers {
+                            errors.push(PDBError::new(
+                                ErrorLevel::InvalidatingError,
+                                "Invalid identifiers",
+                                "The residue name, chain identifier, and insertion code should only contain valid characters.",
+                                Context::full_line(linenumber, &line),
+                            ));
+                            continue;
+                        }
+                        // PQR files do not contain elements
+                        let element = guess_element(&name, &residue_name).to_string();
+                        let atom = Atom::new(
+                            hetero,
+                            serial_number,
+                            id_iter.next().unwrap_or_default(),
+                            name,
+                            x,
+                            y,
+                            z,
+                            1.0,
+                            0.0,
+                            element,
+                            0,
+                        );
+                        let mut atom = if let Some(atom) = atom {
+                            atom
+                        } else {
+                            errors.push(PDBError::new(
+                                ErrorLevel::InvalidatingError,
+                                "Atom definition incorrect",
+                                "The atom name should only contain valid characters and the coordinates should be finite.",
+                                Context::full_line(linenumber, &line),
+                            ));
+                            continue;
+                        };
+                        if options.discard_hydrogens && atom.element() == Some(&Element::H) {
+                            continue;
+                        }
+                        for result in [
+                            atom.set_partial_charge(partial_charge),
+                            atom.set_radius(radius),
+                        ] {
+                            if let Err(message) = result {
+                                errors.push(PDBError::new(
+                                    ErrorLevel::InvalidatingError,
+                                    "Invalid charge or radius",
+                                    message,
+                                    Context::full_line(linenumber, &line),
+                                ));
+                            }
+                        }
+                        let chain_id = chain_id.unwrap_or_else(|| {
+                            chain_id_new
+                                .expect("Chain ID iterator is exhausted")
+                                .to_string()
+                        });
+                        model.add_atom(
+                            atom,
+                            chain_id,
+                            (residue_number, insertion_code.as_deref()),
+                            (residue_name, None),
+                        );
+                    }
+                    Err(error) => errors.push(error),
+                }
+            }
+            _ => (),
+        }
+    }
+    if model.atom_count() > 0 {
+        pdb.add_model(model);
+    }
+
+    reshuffle_conformers(&mut pdb);
+    errors.extend(validate(&pdb));
+
+    if errors.iter().any(|e| e.fails(options.level)) {
+        Err(errors)
+    } else {
+        Ok((pdb, errors))
+    }
+}
+
+/// The information given on a single `ATOM` or `HETATM` line of a PQR file
+struct AtomLine {
+    /// Whether this is a `HETATM` line
+    hetero: bool,
+    /// The serial number of the atom
+    serial_number: usize,
+    /// The name of the atom
+    name: String,
+    /// The name of the residue
+    residue_name: String,
+    /// The chain identifier, if given
+    chain_id: Option<String>,
+    /// The residue number
+    residue_number: isize,
+    /// The insertion code, if given
+    insertion_code: Option<String>,
+    /// The position (Å)
+    pos: (f64, f64, f64),
+    /// The partial charge (e)
+    partial_charge: f64,
+    /// The radius (Å)
+    radius: f64,
+}
+
+/// Parse the whitespace delimited fields of an `ATOM` or `HETATM` line. The serial number may be
+/// glued to the record name (eg `HETATM12345`), as happens for large serial numbers.
+fn parse_atom(fields: &[&str], linenumber: usize, line: &str) -> Result<AtomLine, PDBError> {
+    let error = |message: String| {
+        PDBError::new(
+            ErrorLevel::BreakingError,
+            "Invalid PQR atom line",
+            message,
+            Context::full_line(linenumber, line),
+        )
+    };
+    let record = fields[0].to_ascii_uppercase();
+    let hetero = record.starts_with("HETATM");
+    let glued = &record[if hetero { 6 } else { 4 }..];
+    let (serial_number, rest) = if glued.is_empty() {
+        (
+            fields.get(1).copied().unwrap_or_default(),
+            &fields[2.min(fields.len())..],
+        )
+    } else {
+        (glued, &fields[1..])
+    };
+    let (name, residue_name, chain_id, residue, numbers) = match rest {
+        [name, residue_name, residue, numbers @ ..] if numbers.len() == 5 => {
+            (name, residue_name, None, residue, numbers)
+        }
+        [name, residue_name, chain_id, residue, numbers @ ..] if numbers.len() == 5 => {
+            (name, residue_name, Some(chain_id), residue, numbers)
+        }
+        _ => {
+            return Err(error(format!(
+                "Expected 10 or 11 fields (the record name, serial number, atom name, residue name, optional chain identifier, residue number, x, y, z, charge, and radius) but found {}.",
+                fields.len() + usize::from(!glued.is_empty())
+            )))
+        }
+    };
+    let number = |text: &str, field: &str| {
+        f64::from_str(text)
+            .map_err(|_| error(format!("The {field} '{text}' is not a valid number.")))
+    };
+    let serial_number = serial_number.parse().map_err(|_| {
+        error(format!(
+            "The serial number '{serial_number}' is not a valid number."
+        ))
+    })?;
+    // The residue number can be followed by an insertion code, eg '52A'
+    let split = residue
+        .char_indices()
+        .find(|(i, c)| !(c.is_ascii_digit() || (*i == 0 && *c == '-')))
+        .map_or(residue.len(), |(i, _)| i);
+    let residue_number = residue[..split].parse().map_err(|_| {
+        error(format!(
+            "The residue number '{residue}' is not a valid number."
+        ))
+    })?;
+    let insertion_code = Some(&residue[split..])
+        .filter(|c| !c.is_empty())
+        .map(ToString::to_string);
+    Ok(AtomLine {
+        hetero,
+        serial_number,
+        name: (*name).to_string(),
+        residue_name: (*residue_name).to_string(),
+        chain_id: chain_id.map(|c| (*c).to_string()),
+        residue_number,
+        insertion_code,
+        pos: (
+            number(numbers[0], "x coordinate")?,
+            number(numbers[1], "y coordinate")?,
+            number(numbers[2], "z coordinate")?,
+        ),
+        partial_charge: number(numbers[3], "charge")?,
+        radius: number(numbers[4], "radius")?,
+    })
+}
@@ -11,6 +11,22 @@ pub enum Format {
     Pdb,
     /// Load mmCIF files
     Mmcif,
+    /// Load BinaryCIF files, the MessagePack based binary encoding of mmCIF files
+    BinaryCif,
+    /// Load MMTF files, the MessagePack based Macromolecular Transmission Format
+    Mmtf,
+    /// Load PQR files, the whitespace delimited PDB variant with partial charges and radii
+    Pqr,
+    /// Load GROMACS files, with the coordinates and velocities converted from nm to Å
+    Gro,
+    /// Load XYZ files, including the properties and lattice of extended XYZ files
+    Xyz,
+    /// Load SDF and MOL files, with every record as a separate model
+    Sdf,
+    /// Load Tripos MOL2 files, with the SYBYL atom types and partial charges
+    Mol2,
+    /// Load PDBQT files, with the partial charges, AutoDock atom types, and torsion trees
+    Pdbqt,
     /// Automatically detect the format
     #[default]
     Auto,
@@ -21,6 +37,14 @@ impl From<&str> for Format {
         match s {
             "pdb" => Self::Pdb,
             "mmcif" => Self::Mmcif,
+            "bcif" => Self::BinaryCif,
+            "mmtf" => Self::Mmtf,
+            "pqr" => Self::Pqr,
+            "gro" => Self::Gro,
+            "xyz" => Self::Xyz,
+            "sdf" => Self::Sdf,
+            "mol2" => Self::Mol2,
+            "pdbqt" => Self::Pdbqt,
             _ => panic!("Unknown format: {}", s),
         }
     }
@@ -74,6 +98,12 @@ pub struct ReadOptions {
 
     /// Only read atomic coordinates
     pub(crate) only_atomic_coords: bool,
+
+    /// Build the hierarchy from the label identifiers in mmCIF files
+    pub(crate) label_identifiers: bool,
+
+    /// Keep the mmCIF categories that are not interpreted
+    pub(crate) keep_unknown_categories: bool,
 }
 
 impl ReadOptions {
@@ -134,6 +164,26 @@ impl ReadOptions {
         self
     }
 
+    /// Sets whether to build the chains and residues from the label identifiers (`label_asym_id`
+    /// and `label_seq_id`) instead of the author defined identifiers (`auth_asym_id` and
+    /// `auth_seq_id`) in mmCIF files. Non-polymer residues, which do not have a `label_seq_id`,
+    /// keep their `auth_seq_id`. The other identifiers are kept on the residues in both cases,
+    /// see [`crate::Residue::label_asym_id`] and [`crate::Residue::auth_asym_id`].
+    pub fn set_label_identifiers(&mut self, label_identifiers: bool) -> &mut Self {
+        self.label_identifiers = label_identifiers;
+        self
+    }
+
+    /// Sets whether to keep the categories of mmCIF files that are not interpreted into the
+    /// [`PDB`] structure. These are stored in [`crate::PDB::unknown_categories`] and written back
+    /// unchanged by [`crate::save_mmcif`], so that no metadata is lost when a file is edited.
+    /// The categories that are generated from the [`PDB`] structure when saving are never kept.
+    /// This has no effect on PDB files.
+    pub fn set_keep_unknown_categories(&mut self, keep: bool) -> &mut Self {
+        self.keep_unknown_categories = keep;
+        self
+    }
+
     /// Open an atomic data file, either PDB or mmCIF/PDBx, into a [`PDB`] structure.
     /// The correct type will be determined based on the file extension.
     ///
@@ -153,6 +203,14 @@ impl ReadOptions {
             match self.format {
                 Format::Pdb => super::pdb::open_pdb_with_options(path, self),
                 Format::Mmcif => super::mmcif::open_mmcif_with_options(path, self),
+                Format::BinaryCif => super::mmcif::open_bcif_with_options(path, self),
+                Format::Mmtf => super::mmtf::open_mmtf_with_options(path, self),
+                Format::Pqr => super::pqr::open_pqr_with_options(path, self),
+                Format::Gro => super::gro::open_gro_with_options(path, self),
+                Format::Xyz => super::xyz::open_xyz_with_options(path, self),
+                Format::Sdf => super::sdf::open_sdf_with_options(path, self),
+                Format::Mol2 => super::mol2::open_mol2_with_options(path, self),
+                Format::Pdbqt => super::pdbqt::open_pdbqt_with_options(path, self),
                 Format::Auto => self.read_auto(path),
             }
         }
@@ -179,10 +237,18 @@ impl ReadOptions {
                         super::pdb::open_pdb_raw_with_options(reader, Context::None, self)
                     }
                     Format::Mmcif => super::mmcif::open_mmcif_raw_with_options(reader, self),
+                    Format::BinaryCif => super::mmcif::open_bcif_raw_with_options(reader, self),
+                    Format::Mmtf => super::mmtf::open_mmtf_raw_with_options(reader, self),
+                    Format::Pqr => super::pqr::open_pqr_raw_with_options(reader, self),
+                    Format::Gro => super::gro::open_gro_raw_with_options(reader, self),
+                    Format::Xyz => super::xyz::open_xyz_raw_with_options(reader, self),
+                    Format::Sdf => super::sdf::open_sdf_raw_with_options(reader, self),
+                    Format::Mol2 => super::mol2::open_mol2_raw_with_options(reader, self),
+                    Format::Pdbqt => super::pdbqt::open_pdbqt_raw_with_options(reader, self),
                     Format::Auto => Err(vec![PDBError::new(
                         crate::ErrorLevel::BreakingError,
                         "Could not determine file type",
-                        "Could not determine the type of the gzipped file, use .pdb.gz or .cif.gz",
+                        "Could not determine the type of the gzipped file, use .pdb.gz, .cif.gz, .bcif.gz, .mmtf.gz, .pqr.gz, .gro.gz, .xyz.gz, .sdf.gz, .mol2.gz, or .pdbqt.gz",
                         Context::show(filename),
                     )]),
                 }
@@ -190,10 +256,18 @@ impl ReadOptions {
                 match file_format {
                     Format::Pdb => super::pdb::open_pdb_with_options(path, self),
                     Format::Mmcif => super::mmcif::open_mmcif_with_options(path, self),
+                    Format::BinaryCif => super::mmcif::open_bcif_with_options(path, self),
+                    Format::Mmtf => super::mmtf::open_mmtf_with_options(path, self),
+                    Format::Pqr => super::pqr::open_pqr_with_options(path, self),
+                    Format::Gro => super::gro::open_gro_with_options(path, self),
+                    Format::Xyz => super::xyz::open_xyz_with_options(path, self),
+                    Format::Sdf => super::sdf::open_sdf_with_options(path, self),
+                    Format::Mol2 => super::mol2::open_mol2_with_options(path, self),
+                    Format::Pdbqt => super::pdbqt::open_pdbqt_with_options(path, self),
                     _ => Err(vec![PDBError::new(
                         crate::ErrorLevel::BreakingError,
                         "Incorrect extension",
-                        "Could not determine the type of the given file extension, make it .pdb or .cif",
+                        "Could not determine the type of the given file extension, make it .pdb, .cif, .bcif, .mmtf, .pqr, .gro, .xyz, .sdf, .mol2, or .pdbqt",
                         Context::show(path.as_ref()),
                     )])
                 }
@@ -202,7 +276,7 @@ impl ReadOptions {
             Err(vec![PDBError::new(
                 crate::ErrorLevel::BreakingError,
                 "Missing extension",
-                "The given file does not have an extension, make it .pdb or .cif",
+                "The given file does not have an extension, make it .pdb, .cif, .bcif, .mmtf, .pqr, .gro, .xyz, .sdf, .mol2, or .pdbqt",
                 Context::show(path.as_ref()),
             )])
         }
@@ -221,6 +295,14 @@ impl ReadOptions {
         match self.format {
             Format::Pdb => super::pdb::open_pdb_raw_with_options(input, Context::None, self),
             Format::Mmcif => super::mmcif::open_mmcif_raw_with_options(input, self),
+            Format::BinaryCif => super::mmcif::open_bcif_raw_with_options(input, self),
+            Format::Mmtf => super::mmtf::open_mmtf_raw_with_options(input, self),
+            Format::Pqr => super::pqr::open_pqr_raw_with_options(input, self),
+            Format::Gro => super::gro::open_gro_raw_with_options(input, self),
+            Format::Xyz => super::xyz::open_xyz_raw_with_options(input, self),
+            Format::Sdf => super::sdf::open_sdf_raw_with_options(input, self),
+            Format::Mol2 => super::mol2::open_mol2_raw_with_options(input, self),
+            Format::Pdbqt => super::pdbqt::open_pdbqt_raw_with_options(input, self),
             Format::Auto => Err(vec![PDBError::new(
                 crate::ErrorLevel::BreakingError,
                 "Could not determine file type",
@@ -229,6 +311,116 @@ impl ReadOptions {
             )]),
         }
     }
+
+    /// Open a CIF or BinaryCIF file containing any number of data blocks, eg a model archive, and
+    /// read every data block into a separate [`PDB`] structure. The identifier of every PDB is set
+    /// to the name of its data block. Gzipped files (`.cif.gz`, `.bcif.gz`) are decompressed
+    /// automatically.
+    ///
+    /// # Errors
+    /// Returns a `PDBError` if the file could not be opened or lexed, or if the format is set to PDB,
+    /// MMTF, PQR, GROMACS, XYZ, SDF, MOL2, or PDBQT.
+    /// Otherwise it returns the result of reading every data block, as [`ReadOptions::read`] does for
+    /// a single block, with the name of the data block added to the context of every error.
+    ///
+    /// # Related
+    /// If you want to read the blocks from memory, see [`ReadOptions::read_raw_blocks`].
+    pub fn read_blocks(&self, path: impl AsRef<str>) -> Result<Vec<ReadResult>, Vec<PDBError>> {
+        let filename = path.as_ref();
+        let guess = guess_format(filename);
+        if matches!(
+            (self.format, guess),
+            (
+                Format::Pdb
+                    | Format::Mmtf
+                    | Format::Pqr
+                    | Format::Gro
+                    | Format::Xyz
+                    | Format::Sdf
+                    | Format::Mol2
+                    | Format::Pdbqt,
+                _
+            ) | (
+                Format::Auto,
+                Some((
+                    Format::Pdb
+                        | Format::Mmtf
+                        | Format::Pqr
+                        | Format::Gro
+                        | Format::Xyz
+                        | Format::Sdf
+                        | Format::Mol2
+                        | Format::Pdbqt,
+                    _
+                ))
+            )
+        ) {
+            return Err(vec![no_data_blocks(Context::show(filename))]);
+        }
+        let file = std::fs::File::open(filename).map_err(|_| {
+            vec![PDBError::new(
+                crate::ErrorLevel::BreakingError,
+                "Could not open file",
+                "Could not open the given file, make sure it exists and you have the correct permissions",
+                Context::show(filename),
+            )]
+        })?;
+        let mut options = self.clone();
+        if let (Format::Auto, Some((format, _))) = (self.format, guess) {
+            options.set_format(format);
+        }
+        if guess.map_or(self.decompress, |(_, compressed)| compressed) {
+            let decompressor = flate2::read::GzDecoder::new(file);
+            options.read_raw_blocks(std::io::BufReader::new(decompressor))
+        } else {
+            options.read_raw_blocks(std::io::BufReader::new(file))
+        }
+    }
+
+    /// Parse every data block in the input stream of a CIF file into a separate [`PDB`] struct.
+    /// The file format does not have to be set for text CIF files, for BinaryCIF files it has to
+    /// be set to [`Format::BinaryCif`]. It cannot be set to PDB, MMTF, PQR, GROMACS, XYZ, SDF, MOL2, or PDBQT.
+    ///
+    /// # Errors
+    /// Returns a `PDBError` if the stream could not be read or lexed, or if the format is set to PDB,
+    /// MMTF, PQR, GROMACS, XYZ, SDF, MOL2, or PDBQT.
+    /// Otherwise it returns the result of reading every data block, with the name of the data block
+    /// added to the context of every error.
+    ///
+    /// # Related
+    /// If you want to open a file, see [`ReadOptions::read_blocks`].
+    pub fn read_raw_blocks<T>(
+        &self,
+        input: std::io::BufReader<T>,
+    ) -> Result<Vec<ReadResult>, Vec<PDBError>>
+    where
+        T: std::io::Read,
+    {
+        match self.format {
+            Format::Pdb
+            | Format::Mmtf
+            | Format::Pqr
+            | Format::Gro
+            | Format::Xyz
+            | Format::Sdf
+            | Format::Mol2
+            | Format::Pdbqt => Err(vec![no_data_blocks(Context::None)]),
+            Format::BinaryCif => super::mmcif::open_bcif_blocks_raw_with_options(input, self),
+            Format::Mmcif | Format::Auto => {
+                super::mmcif::open_mmcif_blocks_raw_with_options(input, self)
+            }
+        }
+    }
+}
+
+/// The error for trying to read the data blocks of a PDB, MMTF, PQR, GROMACS, XYZ, SDF, MOL2, or PDBQT file
+fn no_data_blocks(context: Context) -> PDBError {
+    PDBError::new(
+        crate::ErrorLevel::BreakingError,
+        "No data blocks in PDB, MMTF, PQR, GROMACS, XYZ, SDF, MOL2, or PDBQT files",
+        "Only CIF files consist of data blocks, use `read` or `read_raw` to read PDB, MMTF, PQR, GROMACS, XYZ, SDF, MOL2, or PDBQT files",
+        context,
+    )
 }
 
 /// Guess the file format based on the file name extensions.
@@ -238,11 +430,27 @@ fn guess_format(filename: &str) -> Option<(Format, bool)> {
     match path.extension().and_then(OsStr::to_str) {
         Some("pdb") | Some("pdb1") => Some((Format::Pdb, false)),
         Some("cif") | Some("mmcif") => Some((Format::Mmcif, false)),
+        Some("bcif") => Some((Format::BinaryCif, false)),
+        Some("mmtf") => Some((Format::Mmtf, false)),
+        Some("pqr") => Some((Format::Pqr, false)),
+        Some("gro") => Some((Format::Gro, false)),
+        Some("xyz") | Some("extxyz") => Some((Format::Xyz, false)),
+        Some("sdf") | Some("sd") | Some("mol") => Some((Format::Sdf, false)),
+        Some("mol2") => Some((Format::Mol2, false)),
+        Some("pdbqt") => Some((Format::Pdbqt, false)),
         Some("gz") => {
             let path_ext = Path::new(path.file_stem().and_then(OsStr::to_str).unwrap_or(""));
             match path_ext.extension().and_then(OsStr::to_str) {
                 Some("pdb") | Some("pdb1") => Some((Format::Pdb, true)),
                 Some("cif") | Some("mmcif") => Some((Format::Mmcif, true)),
+                Some("bcif") => Some((Format::BinaryCif, true)),
+                Some("mmtf") => Some((Format::Mmtf, true)),
+                Some("pqr") => Some((Format::Pqr, true)),
+                Some("gro") => Some((Format::Gro, true)),
+                Some("xyz") | Some("extxyz") => Some((Format::Xyz, true)),
+                Some("sdf") | Some("sd") | Some("mol") => Some((Format::Sdf, true)),
+                Some("mol2") => Some((Format::Mol2, true)),
+                Some("pdbqt") => Some((Format::Pdbqt, true)),
                 _ => None,
             }
         }
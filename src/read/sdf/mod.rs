@@ -0,0 +1,4 @@
+/// Parse SDF and MOL files into PDB structures
+mod parser;
+
+pub(crate) use parser::*;
@@ -0,0 +1,484 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use crate::error::*;
+use crate::read::general::ReadResult;
+use crate::reference_tables;
+use crate::structs::*;
+use crate::validate::*;
+use crate::ReadOptions;
+
+/// The name of the chain that the atoms of every record are placed in
+const SDF_CHAIN: &str = "A";
+
+/// Parse the given SDF or MOL file into a PDB struct with [`ReadOptions`].
+pub(crate) fn open_sdf_with_options(
+    filename: impl AsRef<str>,
+    options: &ReadOptions,
+) -> ReadResult {
+    let filename = filename.as_ref();
+    let file = if let Ok(f) = File::open(filename) {
+        f
+    } else {
+        return Err(vec![PDBError::new(ErrorLevel::BreakingError, "Could not open file", "Could not open the specified file, make sure the path is correct, you have permission, and that it is not open in another program.", Context::show(filename))]);
+    };
+    open_sdf_raw_with_options(BufReader::new(file), options)
+}
+
+/// Parse the given SDF or MOL stream into a [`PDB`] struct with [`ReadOptions`].
+///
+/// Every record (ended by `$$$$`) is read as a separate model, containing a single residue in
+/// chain `A`. The residue is named after the title of the record if that is a valid residue name
+/// of at most three characters, otherwise it is named `UNL`, and the title of the first record is
+/// used as title of the PDB. The atoms are named after their element and their index for that
+/// element (eg `C1`, `C2`, `O1`). Both V2000 and V3000 connection tables are supported, the formal
+/// charges are set on the atoms and the bonds are added as covalent bonds with their bond order
+/// (see [`BondDetails::order`]).
+pub(crate) fn open_sdf_raw_with_options<T>(input: BufReader<T>, options: &ReadOptions) -> ReadResult
+where
+    T: std::io::Read,
+{
+    let mut lines = Vec::new();
+    for (linenumber, read_line) in input.lines().enumerate() {
+        if let Ok(l) = read_line {
+            lines.push(l);
+        } else {
+            return Err(vec![PDBError::new(
+                ErrorLevel::BreakingError,
+                "Could read line",
+                format!(
+                    "Could not read line {} while parsing the input file.",
+                    linenumber + 1
+                ),
+                Context::None,
+            )]);
+        }
+    }
+
+    let mut errors = Vec::new();
+    let mut pdb = PDB::new();
+    let mut start = 0;
+    while start < lines.len() && !lines[start..].iter().all(|l| l.trim().is_empty()) {
+        let end = lines[start..]
+            .iter()
+            .position(|l| l.trim() == "$$$$")
+            .map_or(lines.len(), |i| start + i);
+        let (model, bonds, record_errors) =
+            parse_record(&lines, start, end, pdb.model_count() + 1, &mut pdb, options)
+                .map_err(|e| vec![e])?;
+        errors.extend(record_errors);
+        pdb.add_model(model);
+        for (counter1, counter2, order) in bonds {
+            let details = order.map(|order| BondDetails {
+                order: Some(order),
+                ..BondDetails::default()
+            });
+            pdb.add_bond_counters(counter1, counter2, Bond::Covalent, details);
+        }
+        start = end + 1;
+        if options.only_first_model {
+            break;
+        }
+    }
+    if pdb.model_count() == 0 {
+        return Err(vec![PDBError::new(
+            ErrorLevel::BreakingError,
+            "Empty SDF file",
+            "The file does not contain any records.",
+            Context::None,
+        )]);
+    }
+
+    reshuffle_conformers(&mut pdb);
+    errors.extend(validate(&pdb));
+
+    if errors.iter().any(|e| e.fails(options.level)) {
+        Err(errors)
+    } else {
+        Ok((pdb, errors))
+    }
+}
+
+/// An atom in the connection table of a record
+struct MolAtom {
+    /// The element symbol
+    symbol: String,
+    /// The position (Å)
+    pos: (f64, f64, f64),
+    /// The formal charge
+    charge: isize,
+}
+
+/// The atoms and bonds in a record, the bonds are given by the indices of the atoms in the list
+/// of atoms and the MDL bond type
+type ConnectionTable = (Vec<MolAtom>, Vec<(usize, usize, usize)>);
+
+/// The bonds of a record, given by the counters of the atoms and the bond order if known
+type RecordBonds = Vec<(usize, usize, Option<BondOrder>)>;
+
+/// Parse the record between the given start and end lines (exclusive). The title of the first
+/// record is stored in the PDB.
+fn parse_record(
+    lines: &[String],
+    start: usize,
+    end: usize,
+    serial_number: usize,
+    pdb: &mut PDB,
+    options: &ReadOptions,
+) -> Result<(Model, RecordBonds, Vec<PDBError>), PDBError> {
+    let mut errors = Vec::new();
+    let line_context =
+        |i: usize| Context::full_line(i + 1, lines.get(i).map_or("", String::as_str));
+    let counts_line = start + 3;
+    if counts_line >= end {
+        return Err(PDBError::new(
+            ErrorLevel::BreakingError,
+            "Incomplete record",
+            "A record should start with three header lines followed by the counts line.",
+            line_context(end.min(lines.len()).saturating_sub(1)),
+        ));
+    }
+    let title = lines[start].trim();
+    if serial_number == 1 && !title.is_empty() {
+        pdb.header.get_or_insert_with(Header::new).title = Some(title.to_string());
+    }
+    let (atoms, bonds) = if lines[counts_line].contains("V3000") {
+        parse_v3000(lines, counts_line + 1, end)?
+    } else {
+        parse_v2000(lines, counts_line, end)?
+    };
+
+    let residue_name = if !title.is_empty()
+        && title.len() <= 3
+        && title.chars().all(|c| c.is_ascii_alphanumeric())
+    {
+        title
+    } else {
+        "UNL"
+    };
+    let hetero = reference_tables::get_one_letter_code(residue_name).is_none();
+    let mut conformer = Conformer::new(residue_name, None, None).ok_or_else(|| {
+        PDBError::new(
+            ErrorLevel::BreakingError,
+            "Residue definition incorrect",
+            "The residue name should only contain valid characters.",
+            line_context(start),
+        )
+    })?;
+    let mut element_counts: HashMap<String, usize> = HashMap::new();
+    let mut counters = Vec::with_capacity(atoms.len());
+    for (index, mol_atom) in atoms.iter().enumerate() {
+        let element = Element::from_symbol(&mol_atom.symbol);
+        if options.discard_hydrogens && element == Some(Element::H) {
+            counters.push(None);
+            continue;
+        }
+        let base = element.map_or("X", |e| e.symbol()).to_string();
+        let count = element_counts.entry(base.clone()).or_insert(0);
+        *count += 1;
+        let atom = Atom::new(
+            hetero,
+            index + 1,
+            (index + 1).to_string(),
+            format!("{base}{count}"),
+            mol_atom.pos.0,
+            mol_atom.pos.1,
+            mol_atom.pos.2,
+            1.0,
+            0.0,
+            element.as_ref().map_or("", Element::symbol),
+            mol_atom.charge,
+        );
+        if let Some(atom) = atom {
+            counters.push(Some(atom.counter()));
+            conformer.add_atom(atom);
+        } else {
+            counters.push(None);
+            errors.push(PDBError::new(
+                ErrorLevel::InvalidatingError,
+                "Atom definition incorrect",
+                "The coordinates of the atom should be finite.",
+                line_context(start),
+            ));
+        }
+    }
+
+    let mut record_bonds = Vec::with_capacity(bonds.len());
+    for (a, b, bond_type) in bonds {
+        if let (Some(Some(counter1)), Some(Some(counter2))) = (counters.get(a), counters.get(b)) {
+            record_bonds.push((
+                *counter1,
+                *counter2,
+                BondOrder::from_mdl_bond_type(bond_type),
+            ));
+        }
+    }
+
+    let model = Model::from_iter(
+        serial_number,
+        Chain::from_iter(
+            SDF_CHAIN,
+            Residue::new(1, None, Some(conformer)).into_iter(),
+        )
+        .into_iter(),
+    );
+    Ok((model, record_bonds, errors))
+}
+
+/// Convert the charge code in the atom block of a V2000 connection table to the formal charge
+const fn charge_from_code(code: usize) -> isize {
+    match code {
+        1 => 3,
+        2 => 2,
+        3 => 1,
+        5 => -1,
+        6 => -2,
+        7 => -3,
+        _ => 0,
+    }
+}
+
+/// Parse a V2000 connection table starting at the given counts line
+fn parse_v2000(
+    lines: &[String],
+    counts_line: usize,
+    end: usize,
+) -> Result<ConnectionTable, PDBError> {
+    let line_context =
+        |i: usize| Context::full_line(i + 1, lines.get(i).map_or("", String::as_str));
+    let field = |line: &str, range: std::ops::Range<usize>| {
+        line.get(range.start..range.end.min(line.len()))
+            .unwrap_or_default()
+            .trim()
+            .to_string()
+    };
+    let counts = &lines[counts_line];
+    let (atom_count, bond_count) = match (
+        field(counts, 0..3).parse::<usize>(),
+        field(counts, 3..6).parse::<usize>(),
+    ) {
+        (Ok(atoms), Ok(bonds)) => (atoms, bonds),
+        _ => {
+            return Err(PDBError::new(
+                ErrorLevel::BreakingError,
+                "Invalid counts line",
+                "The counts line should start with the number of atoms and bonds, both three characters wide.",
+                line_context(counts_line),
+            ))
+        }
+    };
+    let first_atom = counts_line + 1;
+    let first_bond = first_atom + atom_count;
+    if first_bond + bond_count > end {
+        return Err(PDBError::new(
+            ErrorLevel::BreakingError,
+            "Incomplete record",
+            format!("The record should contain {atom_count} atoms and {bond_count} bonds."),
+            line_context(end.min(lines.len()).saturating_sub(1)),
+        ));
+    }
+
+    let mut atoms = Vec::with_capacity(atom_count);
+    for (i, line) in lines.iter().enumerate().take(first_bond).skip(first_atom) {
+        let coordinate = |range| field(line, range).parse::<f64>();
+        let (x, y, z) =
+            match (coordinate(0..10), coordinate(10..20), coordinate(20..30)) {
+                (Ok(x), Ok(y), Ok(z)) => (x, y, z),
+                _ => return Err(PDBError::new(
+                    ErrorLevel::BreakingError,
+                    "Invalid atom line",
+                    "The atom line should start with three coordinates, each ten characters wide.",
+                    line_context(i),
+                )),
+            };
+        atoms.push(MolAtom {
+            symbol: field(line, 31..34),
+            pos: (x, y, z),
+            charge: charge_from_code(field(line, 36..39).parse().unwrap_or(0)),
+        });
+    }
+
+    let mut bonds = Vec::with_capacity(bond_count);
+    for (i, line) in lines
+        .iter()
+        .enumerate()
+        .take(first_bond + bond_count)
+        .skip(first_bond)
+    {
+        match (
+            field(line, 0..3).parse::<usize>(),
+            field(line, 3..6).parse::<usize>(),
+            field(line, 6..9).parse::<usize>(),
+        ) {
+            (Ok(a), Ok(b), Ok(bond_type))
+                if (1..=atom_count).contains(&a) && (1..=atom_count).contains(&b) =>
+            {
+                bonds.push((a - 1, b - 1, bond_type));
+            }
+            _ => {
+                return Err(PDBError::new(
+                    ErrorLevel::BreakingError,
+                    "Invalid bond line",
+                    "The bond line should contain the indices of two atoms in the record and the bond type, each three characters wide.",
+                    line_context(i),
+                ))
+            }
+        }
+    }
+
+    // The charges in the properties block replace all charges in the atom block
+    let mut reset = false;
+    for (i, line) in lines
+        .iter()
+        .enumerate()
+        .take(end)
+        .skip(first_bond + bond_count)
+    {
+        if line.starts_with("M  END") {
+            break;
+        } else if let Some(charges) = line.strip_prefix("M  CHG") {
+            if !reset {
+                atoms.iter_mut().for_each(|a| a.charge = 0);
+                reset = true;
+            }
+            let numbers: Result<Vec<isize>, _> =
+                charges.split_whitespace().skip(1).map(str::parse).collect();
+            let numbers = numbers.map_err(|_| {
+                PDBError::new(
+                    ErrorLevel::BreakingError,
+                    "Invalid charge line",
+                    "The charge line should contain pairs of atom indices and charges.",
+                    line_context(i),
+                )
+            })?;
+            for pair in numbers.chunks_exact(2) {
+                #[allow(clippy::cast_sign_loss)]
+                if let Some(atom) = (pair[0] > 0)
+                    .then(|| atoms.get_mut(pair[0] as usize - 1))
+                    .flatten()
+                {
+                    atom.charge = pair[1];
+                }
+            }
+        }
+    }
+    Ok((atoms, bonds))
+}
+
+/// Parse a V3000 connection table, the lines from the given start line are read until `M  END`
+fn parse_v3000(lines: &[String], start: usize, end: usize) -> Result<ConnectionTable, PDBError> {
+    // Join the continued lines (ending in '-') and remove the 'M  V30 ' prefix
+    let mut v30_lines: Vec<(usize, String)> = Vec::new();
+    let mut previous_continues = false;
+    for (i, line) in lines.iter().enumerate().take(end).skip(start) {
+        if line.starts_with("M  END") {
+            break;
+        }
+        if let Some(content) = line.strip_prefix("M  V30 ") {
+            let (content, continues) = match content.trim_end().strip_suffix('-') {
+                Some(content) => (content, true),
+                None => (content.trim_end(), false),
+            };
+            match v30_lines.last_mut() {
+                Some((_, last)) if previous_continues => last.push_str(content),
+                _ => v30_lines.push((i, content.to_string())),
+            }
+            previous_continues = continues;
+        }
+    }
+
+    let mut atoms = Vec::new();
+    let mut indices = HashMap::new();
+    let mut bonds = Vec::new();
+    let mut counts = None;
+    let mut block = "";
+    for (i, line) in &v30_lines {
+        let error = |short: &str, long: &str| {
+            PDBError::new(
+                ErrorLevel::BreakingError,
+                short,
+                long,
+                Context::full_line(i + 1, &lines[*i]),
+            )
+        };
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            ["BEGIN", name, ..] => block = name,
+            ["END", ..] => block = "",
+            ["COUNTS", atom_count, bond_count, ..] => {
+                match (atom_count.parse::<usize>(), bond_count.parse::<usize>()) {
+                    (Ok(atom_count), Ok(bond_count)) => counts = Some((*i, atom_count, bond_count)),
+                    _ => {
+                        return Err(error(
+                            "Invalid counts line",
+                            "The counts line should start with the number of atoms and bonds.",
+                        ))
+                    }
+                }
+            }
+            [index, symbol, x, y, z, rest @ ..] if block == "ATOM" => {
+                let coordinate = |text: &str| {
+                    text.parse::<f64>().map_err(|_| {
+                        error(
+                            "Invalid atom line",
+                            "The coordinates of the atom should be valid numbers.",
+                        )
+                    })
+                };
+                let charge = rest
+                    .iter()
+                    .find_map(|f| f.strip_prefix("CHG="))
+                    .map_or(Ok(0), str::parse)
+                    .map_err(|_| {
+                        error("Invalid atom line", "The charge should be a valid number.")
+                    })?;
+                indices.insert(*index, atoms.len());
+                atoms.push(MolAtom {
+                    symbol: (*symbol).to_string(),
+                    pos: (coordinate(x)?, coordinate(y)?, coordinate(z)?),
+                    charge,
+                });
+            }
+            [_, bond_type, a, b, ..] if block == "BOND" => {
+                match (bond_type.parse(), indices.get(a), indices.get(b)) {
+                    (Ok(bond_type), Some(a), Some(b)) => bonds.push((*a, *b, bond_type)),
+                    _ => {
+                        return Err(error(
+                            "Invalid bond line",
+                            "The bond line should contain the bond type and the indices of two atoms in the record.",
+                        ))
+                    }
+                }
+            }
+            _ if block == "ATOM" => {
+                return Err(error(
+                    "Invalid atom line",
+                    "The atom line should contain the index, element, and three coordinates of the atom.",
+                ))
+            }
+            _ if block == "BOND" => {
+                return Err(error(
+                    "Invalid bond line",
+                    "The bond line should contain the bond type and the indices of two atoms in the record.",
+                ))
+            }
+            _ => (),
+        }
+    }
+    if let Some((i, atom_count, bond_count)) = counts {
+        if atoms.len() != atom_count || bonds.len() != bond_count {
+            return Err(PDBError::new(
+                ErrorLevel::BreakingError,
+                "Incomplete record",
+                format!(
+                    "The record should contain {atom_count} atoms and {bond_count} bonds, but {} atoms and {} bonds were found.",
+                    atoms.len(),
+                    bonds.len()
+                ),
+                Context::full_line(i + 1, &lines[i]),
+            ));
+        }
+    }
+    Ok((atoms, bonds))
+}
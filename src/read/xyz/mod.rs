@@ -0,0 +1,4 @@
+/// Parse XYZ and extended XYZ files into PDB structures
+mod parser;
+
+pub(crate) use parser::*;
@@ -0,0 +1,473 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use crate::error::*;
+use crate::read::general::ReadResult;
+use crate::reference_tables;
+use crate::structs::*;
+use crate::validate::*;
+use crate::ReadOptions;
+
+/// Parse the given XYZ file into a PDB struct with [`ReadOptions`].
+pub(crate) fn open_xyz_with_options(
+    filename: impl AsRef<str>,
+    options: &ReadOptions,
+) -> ReadResult {
+    let filename = filename.as_ref();
+    let file = if let Ok(f) = File::open(filename) {
+        f
+    } else {
+        return Err(vec![PDBError::new(ErrorLevel::BreakingError, "Could not open file", "Could not open the specified file, make sure the path is correct, you have permission, and that it is not open in another program.", Context::show(filename))]);
+    };
+    open_xyz_raw_with_options(BufReader::new(file), options)
+}
+
+/// Parse the given XYZ stream into a [`PDB`] struct with [`ReadOptions`].
+///
+/// Every frame consists of the number of atoms, a comment line, and a line per atom with its
+/// element and coordinates (Å). Every frame is read as a separate model. If the comment line
+/// contains extended XYZ properties these are used to read the extra columns per atom (see
+/// [`Column`]) and the lattice is used as the unit cell, otherwise the comment of the first
+/// frame is used as title. Atoms without residue information are placed in a single residue
+/// `UNL` in chain `A`.
+pub(crate) fn open_xyz_raw_with_options<T>(input: BufReader<T>, options: &ReadOptions) -> ReadResult
+where
+    T: std::io::Read,
+{
+    let mut lines = Vec::new();
+    for (linenumber, read_line) in input.lines().enumerate() {
+        if let Ok(l) = read_line {
+            lines.push(l);
+        } else {
+            return Err(vec![PDBError::new(
+                ErrorLevel::BreakingError,
+                "Could read line",
+                format!(
+                    "Could not read line {} while parsing the input file.",
+                    linenumber + 1
+                ),
+                Context::None,
+            )]);
+        }
+    }
+
+    let mut errors = Vec::new();
+    let mut pdb = PDB::new();
+    let mut index = 0;
+    loop {
+        // Frames can be separated by empty lines
+        while lines.get(index).map_or(false, |l| l.trim().is_empty()) {
+            index += 1;
+        }
+        if index >= lines.len() {
+            break;
+        }
+        let (model, frame_errors) =
+            parse_frame(&lines, &mut index, pdb.model_count() + 1, &mut pdb, options)
+                .map_err(|e| vec![e])?;
+        errors.extend(frame_errors);
+        pdb.add_model(model);
+        if options.only_first_model {
+            break;
+        }
+    }
+    if pdb.model_count() == 0 {
+        return Err(vec![PDBError::new(
+            ErrorLevel::BreakingError,
+            "Empty XYZ file",
+            "The file does not contain any frames.",
+            Context::None,
+        )]);
+    }
+
+    reshuffle_conformers(&mut pdb);
+    errors.extend(validate(&pdb));
+
+    if errors.iter().any(|e| e.fails(options.level)) {
+        Err(errors)
+    } else {
+        Ok((pdb, errors))
+    }
+}
+
+/// The type of the values in a column of an extended XYZ file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnType {
+    /// Text
+    String,
+    /// Floating point numbers
+    Real,
+    /// Integers
+    Integer,
+    /// Booleans, given as `T` or `F`
+    Logical,
+}
+
+/// A column in the `Properties` of an extended XYZ file. The columns that are used are
+/// `species` (the element), `pos` (the position), `name` (the atom name), `resname` (the residue
+/// name), `resid` (the residue number), `chain` (the chain identifier), `charge` (the partial
+/// charge), `bfactor` (the B factor), and `occupancy`, any other column is skipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Column {
+    /// The name of the property
+    name: String,
+    /// The type of the values
+    kind: ColumnType,
+    /// The number of values
+    count: usize,
+}
+
+/// The columns of a plain XYZ file
+fn plain_columns() -> Vec<Column> {
+    vec![
+        Column {
+            name: "species".to_string(),
+            kind: ColumnType::String,
+            count: 1,
+        },
+        Column {
+            name: "pos".to_string(),
+            kind: ColumnType::Real,
+            count: 3,
+        },
+    ]
+}
+
+/// Split the comment line of an extended XYZ file into key value pairs, values can be quoted
+/// with double quotes. Words without a value are given as keys with an empty value.
+fn parse_comment(comment: &str) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    let mut chars = comment.chars().peekable();
+    loop {
+        while chars.peek().map_or(false, |c| c.is_whitespace()) {
+            chars.next();
+        }
+        if chars.peek().is_none() {
+            break;
+        }
+        let mut key = String::new();
+        while let Some(c) = chars.peek().filter(|c| !c.is_whitespace() && **c != '=') {
+            key.push(*c);
+            chars.next();
+        }
+        let mut value = String::new();
+        if chars.peek() == Some(&'=') {
+            chars.next();
+            if chars.peek() == Some(&'"') {
+                chars.next();
+                for c in chars.by_ref() {
+                    if c == '"' {
+                        break;
+                    }
+                    value.push(c);
+                }
+            } else {
+                while let Some(c) = chars.peek().filter(|c| !c.is_whitespace()) {
+                    value.push(*c);
+                    chars.next();
+                }
+            }
+        }
+        pairs.push((key, value));
+    }
+    pairs
+}
+
+/// Parse the `Properties` value of an extended XYZ file, eg `species:S:1:pos:R:3`. The positions
+/// are required, so the value is invalid if there is no `pos:R:3` column.
+fn parse_properties(properties: &str) -> Option<Vec<Column>> {
+    let parts: Vec<&str> = properties.split(':').collect();
+    if parts.len() % 3 != 0 {
+        return None;
+    }
+    let columns: Vec<Column> = parts
+        .chunks_exact(3)
+        .map(|column| {
+            Some(Column {
+                name: column[0].to_ascii_lowercase(),
+                kind: match column[1].to_ascii_uppercase().as_str() {
+                    "S" => ColumnType::String,
+                    "R" => ColumnType::Real,
+                    "I" => ColumnType::Integer,
+                    "L" => ColumnType::Logical,
+                    _ => return None,
+                },
+                count: column[2].parse().ok()?,
+            })
+        })
+        .collect::<Option<_>>()?;
+    columns
+        .iter()
+        .any(|c| c.name == "pos" && c.kind == ColumnType::Real && c.count == 3)
+        .then_some(columns)
+}
+
+/// The information given for a single atom, with defaults for the columns that are absent
+struct AtomLine {
+    /// The element symbol or atomic number
+    species: Option<String>,
+    /// The position (Å)
+    pos: [f64; 3],
+    /// The name of the atom
+    name: Option<String>,
+    /// The name of the residue
+    residue_name: Option<String>,
+    /// The residue number
+    residue_number: isize,
+    /// The chain identifier
+    chain: Option<String>,
+    /// The partial charge
+    charge: Option<f64>,
+    /// The B factor
+    b_factor: f64,
+    /// The occupancy
+    occupancy: f64,
+}
+
+/// Parse the values of a single atom line using the given columns
+fn parse_atom_line(line: &str, columns: &[Column]) -> Result<AtomLine, String> {
+    let values: Vec<&str> = line.split_whitespace().collect();
+    let needed = columns.iter().map(|c| c.count).sum::<usize>();
+    if values.len() < needed {
+        return Err(format!(
+            "Expected {needed} values on this line but found {}.",
+            values.len()
+        ));
+    }
+    let mut atom = AtomLine {
+        species: None,
+        pos: [0.0; 3],
+        name: None,
+        residue_name: None,
+        residue_number: 1,
+        chain: None,
+        charge: None,
+        b_factor: 0.0,
+        occupancy: 1.0,
+    };
+    let real = |text: &str, name: &str| {
+        text.parse::<f64>()
+            .map_err(|_| format!("The {name} '{text}' is not a valid number."))
+    };
+    let mut offset = 0;
+    for column in columns {
+        let fields = &values[offset..offset + column.count];
+        offset += column.count;
+        match (column.name.as_str(), column.kind, fields) {
+            ("species" | "element", ColumnType::String | ColumnType::Integer, [s]) => {
+                atom.species = Some((*s).to_string());
+            }
+            ("pos", ColumnType::Real, [x, y, z]) => {
+                atom.pos = [
+                    real(x, "x coordinate")?,
+                    real(y, "y coordinate")?,
+                    real(z, "z coordinate")?,
+                ];
+            }
+            ("name", ColumnType::String, [s]) => atom.name = Some((*s).to_string()),
+            ("resname", ColumnType::String, [s]) => atom.residue_name = Some((*s).to_string()),
+            ("resid", ColumnType::Integer, [s]) => {
+                atom.residue_number = s
+                    .parse()
+                    .map_err(|_| format!("The residue number '{s}' is not a valid number."))?;
+            }
+            ("chain", ColumnType::String, [s]) => atom.chain = Some((*s).to_string()),
+            ("charge", ColumnType::Real, [s]) => atom.charge = Some(real(s, "charge")?),
+            ("bfactor", ColumnType::Real, [s]) => atom.b_factor = real(s, "B factor")?,
+            ("occupancy", ColumnType::Real, [s]) => atom.occupancy = real(s, "occupancy")?,
+            _ => (),
+        }
+    }
+    Ok(atom)
+}
+
+/// Parse a single frame starting at the line with the given index, the index is moved past the
+/// end of the frame. The title and lattice of the first frame are stored in the PDB.
+#[allow(clippy::too_many_lines)]
+fn parse_frame(
+    lines: &[String],
+    index: &mut usize,
+    serial_number: usize,
+    pdb: &mut PDB,
+    options: &ReadOptions,
+) -> Result<(Model, Vec<PDBError>), PDBError> {
+    let mut errors = Vec::new();
+    let line_context =
+        |i: usize| Context::full_line(i + 1, lines.get(i).map_or("", String::as_str));
+    let count_line = *index;
+    let atom_count: usize = lines[count_line].trim().parse().map_err(|_| {
+        PDBError::new(
+            ErrorLevel::BreakingError,
+            "Invalid atom count",
+            "The first line of a frame should contain the number of atoms.",
+            line_context(count_line),
+        )
+    })?;
+    let comment_line = count_line + 1;
+    if comment_line + atom_count >= lines.len() {
+        return Err(PDBError::new(
+            ErrorLevel::BreakingError,
+            "Incomplete frame",
+            format!("The frame should contain a comment line followed by {atom_count} atoms."),
+            line_context(lines.len() - 1),
+        ));
+    }
+
+    let comment = lines[comment_line].trim();
+    let pairs = parse_comment(comment);
+    let value = |key: &str| {
+        pairs
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    };
+    let columns = match value("Properties") {
+        Some(properties) => parse_properties(properties).ok_or_else(|| {
+            PDBError::new(
+                ErrorLevel::BreakingError,
+                "Invalid properties",
+                "The properties should be given as 'name:type:count' triplets, with type S, R, I, or L, including the positions as 'pos:R:3'.",
+                line_context(comment_line),
+            )
+        })?,
+        None => plain_columns(),
+    };
+    let lattice = value("Lattice");
+    if serial_number == 1 {
+        if let Some(lattice) = lattice {
+            let numbers: Result<Vec<f64>, _> = lattice.split_whitespace().map(str::parse).collect();
+            if let Ok(&[ax, ay, az, bx, by, bz, cx, cy, cz]) = numbers.as_deref() {
+                pdb.unit_cell = Some(UnitCell::from_vectors(
+                    [ax, ay, az],
+                    [bx, by, bz],
+                    [cx, cy, cz],
+                ));
+            } else {
+                errors.push(PDBError::new(
+                    ErrorLevel::LooseWarning,
+                    "Invalid lattice",
+                    "The lattice should consist of nine numbers, the three lattice vectors.",
+                    line_context(comment_line),
+                ));
+            }
+        } else if value("Properties").is_none() && !comment.is_empty() {
+            pdb.header.get_or_insert_with(Header::new).title = Some(comment.to_string());
+        }
+    }
+
+    let mut chains: Vec<(String, Vec<Residue>)> = Vec::new();
+    let first_atom = comment_line + 1;
+    for (i, line) in lines
+        .iter()
+        .enumerate()
+        .take(first_atom + atom_count)
+        .skip(first_atom)
+    {
+        let atom_line = parse_atom_line(line, &columns).map_err(|message| {
+            PDBError::new(
+                ErrorLevel::BreakingError,
+                "Invalid atom line",
+                message,
+                line_context(i),
+            )
+        })?;
+        // The species can also be given as atomic number
+        let element = atom_line.species.as_deref().and_then(|s| {
+            s.parse()
+                .ok()
+                .and_then(Element::new)
+                .or_else(|| Element::from_symbol(s))
+        });
+        if options.discard_hydrogens && element == Some(Element::H) {
+            continue;
+        }
+        let name = atom_line
+            .name
+            .as_deref()
+            .or_else(|| element.as_ref().map(Element::symbol))
+            .or(atom_line.species.as_deref())
+            .unwrap_or("X");
+        let hetero = atom_line
+            .residue_name
+            .as_deref()
+            .map_or(true, |r| reference_tables::get_one_letter_code(r).is_none());
+        let atom = Atom::new(
+            hetero,
+            i - comment_line,
+            (i - comment_line).to_string(),
+            name,
+            atom_line.pos[0],
+            atom_line.pos[1],
+            atom_line.pos[2],
+            atom_line.occupancy,
+            atom_line.b_factor,
+            element.as_ref().map_or("", Element::symbol),
+            0,
+        );
+        let mut atom = if let Some(atom) = atom {
+            atom
+        } else {
+            errors.push(PDBError::new(
+                ErrorLevel::InvalidatingError,
+                "Atom definition incorrect",
+                "The atom name should only contain valid characters and the numbers should be finite.",
+                line_context(i),
+            ));
+            continue;
+        };
+        if let Some(charge) = atom_line.charge {
+            if let Err(message) = atom.set_partial_charge(charge) {
+                errors.push(PDBError::new(
+                    ErrorLevel::InvalidatingError,
+                    "Invalid charge",
+                    message,
+                    line_context(i),
+                ));
+            }
+        }
+
+        // Consecutive atoms with the same chain, residue number, and residue name form a residue
+        let chain = atom_line.chain.unwrap_or_else(|| "A".to_string());
+        let residue_name = atom_line.residue_name.unwrap_or_else(|| "UNL".to_string());
+        if chains.last().map_or(true, |(id, _)| *id != chain) {
+            chains.push((chain, Vec::new()));
+        }
+        let residues = &mut chains.last_mut().expect("A chain was just added").1;
+        if let Some(residue) = residues.last_mut().filter(|r| {
+            r.serial_number() == atom_line.residue_number
+                && r.name()
+                    .map_or(false, |n| n.eq_ignore_ascii_case(&residue_name))
+        }) {
+            residue.add_atom(atom, (residue_name.as_str(), None));
+        } else if let Some(conformer) = Conformer::new(&residue_name, None, Some(atom)) {
+            residues.extend(Residue::new(
+                atom_line.residue_number,
+                None,
+                Some(conformer),
+            ));
+        } else {
+            errors.push(PDBError::new(
+                ErrorLevel::InvalidatingError,
+                "Residue definition incorrect",
+                "The residue name should only contain valid characters.",
+                line_context(i),
+            ));
+        }
+    }
+
+    let mut model = Model::new(serial_number);
+    for (id, residues) in chains {
+        if let Some(chain) = Chain::from_iter(&id, residues.into_iter()) {
+            model.add_chain(chain);
+        } else {
+            errors.push(PDBError::new(
+                ErrorLevel::InvalidatingError,
+                "Chain definition incorrect",
+                format!("The chain identifier '{id}' should only contain valid characters."),
+                line_context(first_atom),
+            ));
+        }
+    }
+    *index = first_atom + atom_count;
+    Ok((model, errors))
+}
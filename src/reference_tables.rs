@@ -53,6 +53,16 @@ pub fn is_amino_acid(aa: impl AsRef<str>) -> bool {
     AMINO_ACIDS.contains(&aa.as_ref())
 }
 
+/// Gets the one letter code for the given residue name, for the standard amino acids and
+/// nucleotides. Returns `None` for any other residue.
+pub fn get_one_letter_code(name: impl AsRef<str>) -> Option<char> {
+    let name = name.as_ref();
+    ONE_LETTER_CODES
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, code)| *code)
+}
+
 /// Returns if the given atom name is a name for an atom in the backbone of a protein
 pub fn is_backbone(name: impl AsRef<str>) -> bool {
     BACKBONE_NAMES.contains(&name.as_ref())
@@ -78,6 +88,48 @@ const AMINO_ACIDS: &[&str] = &[
     "TRP", "TYR", "VAL", "SEC", "PYL",
 ];
 
+/// The one letter codes of the standard amino acids and nucleotides, as used in the canonical
+/// sequences of mmCIF files.
+const ONE_LETTER_CODES: &[(&str, char)] = &[
+    ("ALA", 'A'),
+    ("ARG", 'R'),
+    ("ASN", 'N'),
+    ("ASP", 'D'),
+    ("ASX", 'B'),
+    ("CYS", 'C'),
+    ("GLN", 'Q'),
+    ("GLU", 'E'),
+    ("GLX", 'Z'),
+    ("GLY", 'G'),
+    ("HIS", 'H'),
+    ("ILE", 'I'),
+    ("LEU", 'L'),
+    ("LYS", 'K'),
+    ("MET", 'M'),
+    ("PHE", 'F'),
+    ("PRO", 'P'),
+    ("SER", 'S'),
+    ("THR", 'T'),
+    ("TRP", 'W'),
+    ("TYR", 'Y'),
+    ("VAL", 'V'),
+    ("SEC", 'U'),
+    ("PYL", 'O'),
+    ("UNK", 'X'),
+    ("A", 'A'),
+    ("C", 'C'),
+    ("G", 'G'),
+    ("U", 'U'),
+    ("I", 'I'),
+    ("DA", 'A'),
+    ("DC", 'C'),
+    ("DG", 'G'),
+    ("DT", 'T'),
+    ("DI", 'I'),
+    ("N", 'N'),
+    ("DN", 'N'),
+];
+
 /// The names of atom in the backbone of proteins
 const BACKBONE_NAMES: &[&str] = &[
     "N", "CA", "C", "O", "H", "H1", "H2", "H3", "HA", "HA2", "HA3",
@@ -0,0 +1,151 @@
+#[cfg(feature = "compression")]
+use flate2::{write::GzEncoder, Compression};
+
+use super::mmcif::save_mmcif_raw;
+use crate::cif::{encode_bcif, lex_cif_document};
+use crate::error::*;
+use crate::structs::*;
+use crate::validate;
+use crate::StrictnessLevel;
+
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::BufWriter;
+
+/// Save the given PDB struct to the given file as BinaryCIF.
+/// # Errors
+/// It validates the PDB. It fails if the validation fails with the given `level`, or if the file could not be opened.
+/// If validation gives rise to problems, use the `save_bcif_raw` function.
+pub fn save_bcif(
+    pdb: &PDB,
+    filename: impl AsRef<str>,
+    level: StrictnessLevel,
+) -> Result<(), Vec<PDBError>> {
+    save_bcif_(pdb, filename, level, BufWriter::new)
+}
+
+/// Save the given PDB struct to the given file as BinaryCIF and compresses to .gz
+/// # Errors
+/// It validates the PDB. It fails if the validation fails with the given `level`, or if the file could not be opened.
+/// If validation gives rise to problems, use the `save_bcif_raw` function.
+#[cfg(feature = "compression")]
+pub fn save_bcif_gz(
+    pdb: &PDB,
+    filename: impl AsRef<str>,
+    level: StrictnessLevel,
+    compression_level: Option<Compression>,
+) -> Result<(), Vec<PDBError>> {
+    save_bcif_(pdb, filename, level, |file| {
+        BufWriter::new(GzEncoder::new(file, compression_level.unwrap_or_default()))
+    })
+}
+
+/// Generic function to save the given PDB struct to the given file as BinaryCIF,
+/// to some writer function, e.g. a GzEncoder or BufWriter.
+fn save_bcif_<T, W>(
+    pdb: &PDB,
+    filename: impl AsRef<str>,
+    level: StrictnessLevel,
+    writer: W,
+) -> Result<(), Vec<PDBError>>
+where
+    T: Write,
+    W: FnOnce(File) -> BufWriter<T>,
+{
+    // Validates the PDB, and returns early if any errors are found
+    let filename = filename.as_ref();
+
+    let mut errors = validate(pdb);
+    for error in &errors {
+        if error.fails(level) {
+            return Err(errors);
+        }
+    }
+
+    // Creates a writer for the file
+    let file = match File::create(filename) {
+        Ok(f) => f,
+        Err(_e) => {
+            errors.push(PDBError::new(
+                ErrorLevel::BreakingError,
+                "Could not open file",
+                "Could not open the file for writing, make sure you have permission for this file and no other program is currently using it.",
+                Context::show(filename)
+            ));
+            return Err(errors);
+        }
+    };
+
+    save_bcif_raw(pdb, writer(file));
+
+    Ok(())
+}
+
+/// Save the given PDB struct to the given BufWriter as BinaryCIF.
+/// The structure is written with exactly the same categories as [`save_mmcif_raw`] uses for
+/// mmCIF files, so reading it back gives the same result as for the mmCIF file.
+/// It does not validate or renumber the PDB, so if that is needed that needs to be done in preparation.
+#[allow(clippy::unwrap_used)]
+pub fn save_bcif_raw<T: Write>(pdb: &PDB, mut sink: BufWriter<T>) {
+    let mut text = Vec::new();
+    save_mmcif_raw(pdb, BufWriter::new(&mut text));
+    // The mmCIF writer always generates valid CIF, so this cannot fail
+    let blocks = lex_cif_document(&String::from_utf8(text).unwrap()).unwrap();
+    sink.write_all(&encode_bcif(&blocks)).unwrap();
+    sink.flush().unwrap();
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::cif::{Category, Document, Value};
+    use crate::{Format, ReadOptions};
+
+    #[test]
+    fn same_categories_as_mmcif() {
+        let (pdb, _) = ReadOptions::new()
+            .set_level(StrictnessLevel::Loose)
+            .read("example-pdbs/1ubq.cif")
+            .unwrap();
+        let mut text = Vec::new();
+        save_mmcif_raw(&pdb, BufWriter::new(&mut text));
+        let mut binary = Vec::new();
+        save_bcif_raw(&pdb, BufWriter::new(&mut binary));
+        let text = Document::parse(&String::from_utf8(text).unwrap()).unwrap();
+        let binary = Document::parse_binary(&binary).unwrap();
+        // Categories with a single row are given as single data items in BinaryCIF
+        let (text, binary) = (&text.blocks[0], &binary.blocks[0]);
+        assert_eq!(binary.category_names(), text.category_names());
+        // BinaryCIF stores numbers as floats, so only their value is retained
+        let numbers = |category: Category<'_>| -> Vec<Vec<Value>> {
+            category
+                .rows
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|value| match value {
+                            Value::Numeric(n) => Value::from(n.parse::<f64>().unwrap()),
+                            value => value.clone(),
+                        })
+                        .collect()
+                })
+                .collect()
+        };
+        for name in text.category_names() {
+            let (b, t) = (binary.category(name).unwrap(), text.category(name).unwrap());
+            assert_eq!(b.columns, t.columns);
+            assert_eq!(numbers(b), numbers(t));
+        }
+        assert!(binary.category("atom_site").unwrap().row_count() > 600);
+
+        let (reread, _) = ReadOptions::new()
+            .set_format(Format::BinaryCif)
+            .set_level(StrictnessLevel::Loose)
+            .read_raw(std::io::BufReader::new(
+                &Document::from(binary.clone()).to_binary()[..],
+            ))
+            .unwrap();
+        assert_eq!(reread.atom_count(), pdb.atom_count());
+    }
+}
@@ -1,13 +1,13 @@
 use flate2::Compression;
 
 use super::*;
-use crate::structs::PDB;
+use crate::structs::{Search, PDB};
 use crate::StrictnessLevel;
 use crate::{check_extension, error::*};
 
 /// Save the given PDB struct to the given file, validating it beforehand.
 /// If validation gives rise to problems, use the `save_raw` function. The correct file
-/// type (pdb or mmCIF/PDBx) will be determined based on the given file extension.
+/// type (pdb, mmCIF/PDBx, BinaryCIF, MMTF, PQR, GROMACS, XYZ, SDF, MOL2, or PDBQT) will be determined based on the given file extension.
 /// # Errors
 /// Fails if the validation fails with the given `level`.
 pub fn save(
@@ -19,11 +19,27 @@ pub fn save(
         save_pdb(pdb, filename, level)
     } else if check_extension(&filename, "cif") {
         save_mmcif(pdb, filename, level)
+    } else if check_extension(&filename, "bcif") {
+        save_bcif(pdb, filename, level)
+    } else if check_extension(&filename, "mmtf") {
+        save_mmtf(pdb, filename, level)
+    } else if check_extension(&filename, "pqr") {
+        save_pqr(pdb, filename, level)
+    } else if check_extension(&filename, "gro") {
+        save_gro(pdb, filename, level)
+    } else if check_extension(&filename, "xyz") || check_extension(&filename, "extxyz") {
+        save_xyz(pdb, filename, level)
+    } else if check_extension(&filename, "sdf") || check_extension(&filename, "mol") {
+        save_sdf(pdb, Search::Known(true), filename, level)
+    } else if check_extension(&filename, "mol2") {
+        save_mol2(pdb, filename, level)
+    } else if check_extension(&filename, "pdbqt") {
+        save_pdbqt(pdb, filename, level)
     } else {
         Err(vec![PDBError::new(
             ErrorLevel::BreakingError,
             "Incorrect extension",
-            "Could not determine the type of the given file, make it .pdb or .cif",
+            "Could not determine the type of the given file, make it .pdb, .cif, .bcif, .mmtf, .pqr, .gro, .xyz, .sdf, .mol2, or .pdbqt",
             Context::show(filename.as_ref()),
         )])
     }
@@ -31,7 +47,7 @@ pub fn save(
 
 /// Save the given PDB struct to the given file and compressing to gz, validating it beforehand.
 /// If validation gives rise to problems, use the `save_raw` function. The correct file
-/// type (pdb or mmCIF/PDBx) will be determined based on the given file extension.
+/// type (pdb, mmCIF/PDBx, BinaryCIF, MMTF, PQR, GROMACS, XYZ, SDF, MOL2, or PDBQT) will be determined based on the given file extension.
 /// # Errors
 /// Fails if the validation fails with the given `level`.
 pub fn save_gz(
@@ -47,7 +63,7 @@ pub fn save_gz(
             return Err(vec![PDBError::new(
                 ErrorLevel::BreakingError,
                 "Filename too short",
-                "Could not determine the type of the given file, make it .pdb.gz or .cif.gz",
+                "Could not determine the type of the given file, make it .pdb.gz, .cif.gz, .bcif.gz, .mmtf.gz, .pqr.gz, .gro.gz, .xyz.gz, .sdf.gz, .mol2.gz, or .pdbqt.gz",
                 Context::show(filename),
             )]);
         }
@@ -56,11 +72,31 @@ pub fn save_gz(
             save_pdb_gz(pdb, filename, level, compression_level)
         } else if check_extension(&filename[..filename.len() - 3], "cif") {
             save_mmcif_gz(pdb, filename, level, compression_level)
+        } else if check_extension(&filename[..filename.len() - 3], "bcif") {
+            save_bcif_gz(pdb, filename, level, compression_level)
+        } else if check_extension(&filename[..filename.len() - 3], "mmtf") {
+            save_mmtf_gz(pdb, filename, level, compression_level)
+        } else if check_extension(&filename[..filename.len() - 3], "pqr") {
+            save_pqr_gz(pdb, filename, level, compression_level)
+        } else if check_extension(&filename[..filename.len() - 3], "gro") {
+            save_gro_gz(pdb, filename, level, compression_level)
+        } else if check_extension(&filename[..filename.len() - 3], "xyz")
+            || check_extension(&filename[..filename.len() - 3], "extxyz")
+        {
+            save_xyz_gz(pdb, filename, level, compression_level)
+        } else if check_extension(&filename[..filename.len() - 3], "sdf")
+            || check_extension(&filename[..filename.len() - 3], "mol")
+        {
+            save_sdf_gz(pdb, Search::Known(true), filename, level, compression_level)
+        } else if check_extension(&filename[..filename.len() - 3], "mol2") {
+            save_mol2_gz(pdb, filename, level, compression_level)
+        } else if check_extension(&filename[..filename.len() - 3], "pdbqt") {
+            save_pdbqt_gz(pdb, filename, level, compression_level)
         } else {
             Err(vec![PDBError::new(
                 ErrorLevel::BreakingError,
                 "Incorrect extension",
-                "Could not determine the type of the given file, make it .pdb.gz or .cif.gz",
+                "Could not determine the type of the given file, make it .pdb.gz, .cif.gz, .bcif.gz, .mmtf.gz, .pqr.gz, .gro.gz, .xyz.gz, .sdf.gz, .mol2.gz, or .pdbqt.gz",
                 Context::show(filename),
             )])
         }
@@ -68,7 +104,7 @@ pub fn save_gz(
         Err(vec![PDBError::new(
             ErrorLevel::BreakingError,
             "Incorrect extension",
-            "Could not determine the type of the given file, make it .pdb.gz or .cif.gz",
+            "Could not determine the type of the given file, make it .pdb.gz, .cif.gz, .bcif.gz, .mmtf.gz, .pqr.gz, .gro.gz, .xyz.gz, .sdf.gz, .mol2.gz, or .pdbqt.gz",
             Context::show(filename),
         )])
     }
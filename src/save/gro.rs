@@ -0,0 +1,144 @@
+#[cfg(feature = "compression")]
+use flate2::{write::GzEncoder, Compression};
+
+use crate::error::*;
+use crate::structs::*;
+use crate::validate;
+use crate::StrictnessLevel;
+
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::BufWriter;
+
+/// Save the given PDB struct to the given file as GROMACS (.gro), validating it beforehand.
+/// # Errors
+/// It validates the PDB. It fails if the validation fails with the given `level`, or if the file could not be opened.
+/// If validation gives rise to problems, use the `save_gro_raw` function.
+pub fn save_gro(
+    pdb: &PDB,
+    filename: impl AsRef<str>,
+    level: StrictnessLevel,
+) -> Result<(), Vec<PDBError>> {
+    save_gro_(pdb, filename, level, BufWriter::new)
+}
+
+/// Save the given PDB struct to the given file as GROMACS (.gro) and compresses to .gz
+/// # Errors
+/// It validates the PDB. It fails if the validation fails with the given `level`, or if the file could not be opened.
+/// If validation gives rise to problems, use the `save_gro_raw` function.
+#[cfg(feature = "compression")]
+pub fn save_gro_gz(
+    pdb: &PDB,
+    filename: impl AsRef<str>,
+    level: StrictnessLevel,
+    compression_level: Option<Compression>,
+) -> Result<(), Vec<PDBError>> {
+    save_gro_(pdb, filename, level, |file| {
+        BufWriter::new(GzEncoder::new(file, compression_level.unwrap_or_default()))
+    })
+}
+
+/// Generic function to save the given PDB struct to the given file as GROMACS,
+/// to some writer function, e.g. a GzEncoder or BufWriter.
+fn save_gro_<T, W>(
+    pdb: &PDB,
+    filename: impl AsRef<str>,
+    level: StrictnessLevel,
+    writer: W,
+) -> Result<(), Vec<PDBError>>
+where
+    T: Write,
+    W: FnOnce(File) -> BufWriter<T>,
+{
+    // Validates the PDB, and returns early if any errors are found
+    let filename = filename.as_ref();
+
+    let mut errors = validate(pdb);
+    for error in &errors {
+        if error.fails(level) {
+            return Err(errors);
+        }
+    }
+
+    // Creates a writer for the file
+    let file = match File::create(filename) {
+        Ok(f) => f,
+        Err(_e) => {
+            errors.push(PDBError::new(
+                ErrorLevel::BreakingError,
+                "Could not open file",
+                "Could not open the file for writing, make sure you have permission for this file and no other program is currently using it.",
+                Context::show(filename)
+            ));
+            return Err(errors);
+        }
+    };
+
+    save_gro_raw(pdb, writer(file));
+
+    Ok(())
+}
+
+/// Save the given PDB struct to the given BufWriter as GROMACS (.gro).
+/// It does not validate or renumber the PDB, so if that is needed, that needs to be done in preparation.
+///
+/// Every model is written as a separate frame, with the title of the header (or the identifier)
+/// as title. The coordinates are converted from Å to nm, and the velocities are written if all
+/// atoms in a model have one. The residue and atom numbers are written modulo 100000 as their
+/// columns are five characters wide, and the residue and atom names are cut to five characters.
+/// Chains cannot be given in GROMACS files, so these are lost. The box is given by the unit cell,
+/// or is zero if there is no unit cell.
+#[allow(clippy::unwrap_used)]
+pub fn save_gro_raw<T: Write>(pdb: &PDB, mut sink: BufWriter<T>) {
+    let mut print_line = |line: String| {
+        sink.write_all(line.as_bytes()).unwrap();
+        sink.write_all(b"\n").unwrap();
+    };
+    let title = pdb
+        .header
+        .as_ref()
+        .and_then(|h| h.title.as_deref())
+        .or(pdb.identifier.as_deref())
+        .unwrap_or("Written by pdbtbx");
+    // Cut a name to the first five characters
+    let cut = |name: &str| name.chars().take(5).collect::<String>();
+
+    for model in pdb.models() {
+        print_line(title.to_string());
+        print_line(format!("{:5}", model.atom_count()));
+        let velocities = model.atoms().all(|a| a.velocity().is_some());
+        for hierarchy in model.atoms_with_hierarchy() {
+            let (atom, residue) = (hierarchy.atom(), hierarchy.residue());
+            let (x, y, z) = atom.pos();
+            let mut line = format!(
+                "{:>5}{:<5}{:>5}{:>5}{:8.3}{:8.3}{:8.3}",
+                residue.serial_number().rem_euclid(100_000),
+                cut(hierarchy.conformer().name()),
+                cut(atom.name()),
+                atom.serial_number() % 100_000,
+                x / 10.0,
+                y / 10.0,
+                z / 10.0,
+            );
+            if let (true, Some((x, y, z))) = (velocities, atom.velocity()) {
+                line += &format!("{:8.4}{:8.4}{:8.4}", x / 10.0, y / 10.0, z / 10.0);
+            }
+            print_line(line);
+        }
+        let [a, b, c] = pdb
+            .unit_cell
+            .as_ref()
+            .map_or([[0.0; 3]; 3], UnitCell::vectors)
+            .map(|v| v.map(|n| n / 10.0));
+        let mut line = format!("{:10.5}{:10.5}{:10.5}", a[0], b[1], c[2]);
+        let off_diagonal = [a[1], a[2], b[0], b[2], c[0], c[1]];
+        if off_diagonal.iter().any(|n| n.abs() >= 0.000_005) {
+            for n in off_diagonal {
+                line += &format!("{n:10.5}");
+            }
+        }
+        print_line(line);
+    }
+
+    sink.flush().unwrap();
+}
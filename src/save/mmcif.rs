@@ -1,11 +1,14 @@
 #[cfg(feature = "compression")]
 use flate2::{write::GzEncoder, Compression};
 
+use crate::cif::{cif_text, DataBlock, Item};
 use crate::error::*;
 use crate::structs::*;
 use crate::validate;
 use crate::StrictnessLevel;
+use crate::TransformationMatrix;
 
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::prelude::*;
 use std::io::BufWriter;
@@ -111,6 +114,337 @@ _audit_conform.dict_location   http://mmcif.pdb.org/dictionaries/ascii/mmcif_pdb
         name
     );
 
+    // Entry metadata
+    if let Some(header) = &pdb.header {
+        write!("# Entry metadata");
+        let id = cif_text(name);
+        let optional = |value: Option<&String>| value.map_or_else(String::new, |v| cif_text(v));
+        if let Some(title) = &header.title {
+            write_items(
+                &mut sink,
+                "struct",
+                &[("entry_id", id.clone()), ("title", cif_text(title))],
+            );
+        }
+        if header.classification.is_some() || !header.keywords.is_empty() {
+            write_items(
+                &mut sink,
+                "struct_keywords",
+                &[
+                    ("entry_id", id.clone()),
+                    ("pdbx_keywords", optional(header.classification.as_ref())),
+                    ("text", cif_text(&header.keywords.join(", "))),
+                ],
+            );
+        }
+        write_loop(
+            &mut sink,
+            "exptl",
+            &["entry_id", "method"],
+            header
+                .experimental_methods
+                .iter()
+                .map(|m| vec![id.clone(), cif_text(m)])
+                .collect(),
+        );
+        if let Some(date) = &header.deposition_date {
+            write_items(
+                &mut sink,
+                "pdbx_database_status",
+                &[
+                    ("entry_id", id),
+                    ("recvd_initial_deposition_date", cif_text(date)),
+                ],
+            );
+        }
+        write_loop(
+            &mut sink,
+            "audit_author",
+            &["name", "pdbx_ordinal"],
+            header
+                .authors
+                .iter()
+                .enumerate()
+                .map(|(i, a)| vec![cif_text(&mmcif_author(a)), (i + 1).to_string()])
+                .collect(),
+        );
+        write_loop(
+            &mut sink,
+            "pdbx_audit_revision_history",
+            &[
+                "ordinal",
+                "data_content_type",
+                "major_revision",
+                "minor_revision",
+                "revision_date",
+            ],
+            header
+                .revisions
+                .iter()
+                .map(|r| {
+                    vec![
+                        r.number.to_string(),
+                        "'Structure model'".to_string(),
+                        String::new(),
+                        String::new(),
+                        optional(r.date.as_ref()),
+                    ]
+                })
+                .collect(),
+        );
+        let source = |m: &MoleculeInfo, token: &str| {
+            m.source_value(token).map_or_else(String::new, cif_text)
+        };
+        write_loop(
+            &mut sink,
+            "entity_src_gen",
+            &[
+                "entity_id",
+                "pdbx_gene_src_scientific_name",
+                "gene_src_common_name",
+                "pdbx_gene_src_ncbi_taxonomy_id",
+                "gene_src_strain",
+                "pdbx_gene_src_gene",
+                "pdbx_host_org_scientific_name",
+                "pdbx_host_org_ncbi_taxonomy_id",
+            ],
+            header
+                .molecules
+                .iter()
+                .filter(|m| !m.source.is_empty())
+                .map(|m| {
+                    vec![
+                        cif_text(&m.id),
+                        source(m, "ORGANISM_SCIENTIFIC"),
+                        source(m, "ORGANISM_COMMON"),
+                        source(m, "ORGANISM_TAXID"),
+                        source(m, "STRAIN"),
+                        source(m, "GENE"),
+                        source(m, "EXPRESSION_SYSTEM"),
+                        source(m, "EXPRESSION_SYSTEM_TAXID"),
+                    ]
+                })
+                .collect(),
+        );
+    }
+
+    // Entities, combined with the molecules from the header that do not have an entity
+    let molecules = pdb
+        .header
+        .as_ref()
+        .map_or(&[][..], |h| h.molecules.as_slice());
+    if pdb.entity_count() > 0 || !molecules.is_empty() {
+        write!("# Entities");
+        // The author defined chain ids of the given chains, which differ for chains read with label identifiers
+        let strand_ids = |chains: &[String]| {
+            let mut ids: Vec<String> = Vec::new();
+            for id in chains {
+                let auth = pdb
+                    .chains()
+                    .find(|c| c.id() == id)
+                    .and_then(|c| {
+                        c.residues()
+                            .next()
+                            .map(|r| residue_identifiers(pdb, (0, c), (0, r)).1[0].clone())
+                    })
+                    .unwrap_or_else(|| id.clone());
+                if !ids.contains(&auth) {
+                    ids.push(auth);
+                }
+            }
+            ids
+        };
+        let compound = |m: Option<&MoleculeInfo>, token: &str| {
+            m.and_then(|m| m.compound_value(token))
+                .map_or_else(String::new, cif_text)
+        };
+        let optional = |value: Option<&String>| value.map_or_else(String::new, |v| cif_text(v));
+        let entities: Vec<(Option<&Entity>, Option<&MoleculeInfo>)> = pdb
+            .entities()
+            .map(|e| (Some(e), molecules.iter().find(|m| m.id == e.id)))
+            .chain(
+                molecules
+                    .iter()
+                    .filter(|m| pdb.entity(&m.id).is_none())
+                    .map(|m| (None, Some(m))),
+            )
+            .collect();
+        write_loop(
+            &mut sink,
+            "entity",
+            &[
+                "id",
+                "type",
+                "pdbx_description",
+                "pdbx_fragment",
+                "pdbx_ec",
+                "pdbx_mutation",
+            ],
+            entities
+                .iter()
+                .map(|(e, m)| {
+                    vec![
+                        cif_text(e.map_or_else(|| m.map_or("", |m| &m.id), |e| &e.id)),
+                        e.and_then(|e| e.entity_type.as_ref())
+                            .map_or_else(|| "polymer".to_string(), |t| cif_text(t)),
+                        e.and_then(|e| e.description.as_ref())
+                            .map_or_else(|| compound(*m, "MOLECULE"), |d| cif_text(d)),
+                        compound(*m, "FRAGMENT"),
+                        compound(*m, "EC"),
+                        compound(*m, "MUTATION"),
+                    ]
+                })
+                .collect(),
+        );
+        write_loop(
+            &mut sink,
+            "entity_poly",
+            &[
+                "entity_id",
+                "type",
+                "pdbx_seq_one_letter_code_can",
+                "pdbx_strand_id",
+            ],
+            entities
+                .iter()
+                .filter_map(|(e, m)| match (e, m) {
+                    (Some(e), _) if e.is_polymer() => Some(vec![
+                        cif_text(&e.id),
+                        optional(e.polymer_type.as_ref()),
+                        if e.sequence.is_empty() && e.canonical_sequence.is_none() {
+                            String::new()
+                        } else {
+                            // Long sequences are wrapped in a text field
+                            let sequence = e.one_letter_sequence();
+                            let lines: Vec<&str> = (0..sequence.len())
+                                .step_by(80)
+                                .filter_map(|i| sequence.get(i..(i + 80).min(sequence.len())))
+                                .collect();
+                            cif_text(&lines.join("\n"))
+                        },
+                        cif_text(&strand_ids(&e.chains).join(",")),
+                    ]),
+                    (None, Some(m)) if !m.chains().is_empty() => Some(vec![
+                        cif_text(&m.id),
+                        String::new(),
+                        String::new(),
+                        cif_text(&m.chains().join(",")),
+                    ]),
+                    _ => None,
+                })
+                .collect(),
+        );
+        write_loop(
+            &mut sink,
+            "entity_poly_seq",
+            &["entity_id", "num", "mon_id", "hetero"],
+            pdb.entities()
+                .filter(|e| e.is_polymer())
+                .flat_map(|e| {
+                    e.sequence.iter().enumerate().map(move |(i, name)| {
+                        vec![
+                            cif_text(&e.id),
+                            (i + 1).to_string(),
+                            cif_text(name),
+                            "n".to_string(),
+                        ]
+                    })
+                })
+                .collect(),
+        );
+    }
+
+    // Refinement
+    if let Some(refinement) = &pdb.refinement {
+        write!("# Refinement statistics");
+        let id = cif_text(name);
+        let method = pdb
+            .header
+            .as_ref()
+            .and_then(|h| h.experimental_methods.first())
+            .map_or_else(|| "'X-RAY DIFFRACTION'".to_string(), |m| cif_text(m));
+        let value = |value: Option<f64>| value.map_or_else(String::new, print_float);
+        let count = |value: Option<usize>| value.map_or_else(String::new, |v| v.to_string());
+        write_items(
+            &mut sink,
+            "refine",
+            &[
+                ("entry_id", id.clone()),
+                ("pdbx_refine_id", method.clone()),
+                ("ls_d_res_high", value(refinement.resolution)),
+                ("ls_d_res_low", value(refinement.resolution_low)),
+                ("ls_percent_reflns_obs", value(refinement.completeness)),
+                ("ls_number_reflns_obs", count(refinement.reflection_count)),
+                ("ls_R_factor_obs", value(refinement.r_all)),
+                ("ls_R_factor_R_work", value(refinement.r_work)),
+                ("ls_R_factor_R_free", value(refinement.r_free)),
+                (
+                    "ls_percent_reflns_R_free",
+                    value(refinement.free_set_percentage),
+                ),
+                ("ls_number_reflns_R_free", count(refinement.free_set_count)),
+                ("B_iso_mean", value(refinement.mean_b_factor)),
+            ],
+        );
+        if refinement.protein_atoms.is_some()
+            || refinement.nucleic_acid_atoms.is_some()
+            || refinement.heterogen_atoms.is_some()
+            || refinement.solvent_atoms.is_some()
+        {
+            write_items(
+                &mut sink,
+                "refine_hist",
+                &[
+                    ("pdbx_refine_id", method),
+                    ("cycle_id", "LAST".to_string()),
+                    ("pdbx_number_atoms_protein", count(refinement.protein_atoms)),
+                    (
+                        "pdbx_number_atoms_nucleic_acid",
+                        count(refinement.nucleic_acid_atoms),
+                    ),
+                    (
+                        "pdbx_number_atoms_ligand",
+                        count(refinement.heterogen_atoms),
+                    ),
+                    ("number_atoms_solvent", count(refinement.solvent_atoms)),
+                ],
+            );
+        }
+        if let Some(reflections) = &refinement.reflections {
+            write_items(
+                &mut sink,
+                "reflns",
+                &[
+                    ("entry_id", id),
+                    ("d_resolution_high", value(reflections.resolution_high)),
+                    ("d_resolution_low", value(reflections.resolution_low)),
+                    ("number_obs", count(reflections.unique_reflections)),
+                    ("percent_possible_obs", value(reflections.completeness)),
+                    ("pdbx_Rmerge_I_obs", value(reflections.r_merge)),
+                    ("pdbx_redundancy", value(reflections.redundancy)),
+                    ("pdbx_netI_over_sigmaI", value(reflections.i_over_sigma)),
+                    ("pdbx_diffrn_id", "1".to_string()),
+                    ("pdbx_ordinal", "1".to_string()),
+                ],
+            );
+        }
+        if let Some(program) = &refinement.program {
+            let (program, version) = program
+                .split_once(' ')
+                .map_or((program.as_str(), ""), |(p, v)| (p, v.trim()));
+            write_items(
+                &mut sink,
+                "software",
+                &[
+                    ("name", cif_text(program)),
+                    ("version", cif_text(version)),
+                    ("classification", "refinement".to_string()),
+                    ("pdbx_ordinal", "1".to_string()),
+                ],
+            );
+        }
+    }
+
     // Cryst
     if let Some(unit_cell) = &pdb.unit_cell {
         write!(
@@ -260,6 +594,865 @@ _symmetry.Int_Tables_number                {}",
         );
     }
 
+    // Map residues to their identifiers, in the same way as they are written in the atom_site loop
+    let identifiers = pdb
+        .models()
+        .next()
+        .map(|model| {
+            model
+                .chains()
+                .enumerate()
+                .flat_map(|(chain_index, chain)| {
+                    chain
+                        .residues()
+                        .enumerate()
+                        .map(move |(residue_index, residue)| {
+                            (
+                                (chain.id(), residue.id()),
+                                residue_identifiers(
+                                    pdb,
+                                    (chain_index, chain),
+                                    (residue_index, residue),
+                                ),
+                            )
+                        })
+                })
+                .collect::<HashMap<_, _>>()
+        })
+        .unwrap_or_default();
+    // Map chains to their label asym ids, a chain read from mmCIF can contain multiple label asym ids
+    let mut label_asyms: HashMap<&str, Vec<String>> = HashMap::new();
+    if let Some(model) = pdb.models().next() {
+        for chain in model.chains() {
+            let asyms = label_asyms.entry(chain.id()).or_default();
+            for residue in chain.residues() {
+                if let Some(([asym, _, _], _)) = identifiers.get(&(chain.id(), residue.id())) {
+                    if !asyms.contains(asym) {
+                        asyms.push(asym.clone());
+                    }
+                }
+            }
+        }
+    }
+    let label_asym = |chain: &str| {
+        label_asyms
+            .get(chain)
+            .and_then(|asyms| asyms.first())
+            .map_or_else(|| cif_text(chain), |asym| cif_text(asym))
+    };
+
+    // The positions of the residues in the sequences of the polymer entities
+    if let Some(model) = pdb.models().next() {
+        let mut rows = Vec::new();
+        for chain in model.chains() {
+            let entity = if let Some(entity) = pdb
+                .entity_of_chain(chain.id())
+                .filter(|e| e.is_polymer() && !e.sequence.is_empty())
+            {
+                entity
+            } else {
+                continue;
+            };
+            // The residue at every position of the sequence, if it is in the coordinates
+            let mut residues = vec![None; entity.sequence.len()];
+            for residue in chain.residues() {
+                if let Some(slot) = residue
+                    .sequence_index()
+                    .and_then(|i| residues.get_mut(i))
+                    .filter(|slot| slot.is_none())
+                {
+                    *slot = Some(residue);
+                }
+            }
+            if residues.iter().all(Option::is_none) {
+                continue;
+            }
+            // The label asym id of the polymer, as the chain can also contain ligands and waters
+            let asym = chain
+                .residues()
+                .find(|r| r.sequence_index().is_some())
+                .and_then(|r| identifiers.get(&(chain.id(), r.id())))
+                .map_or_else(
+                    || label_asym(chain.id()),
+                    |([asym, _, _], _)| cif_text(asym),
+                );
+            for (index, (name, residue)) in entity.sequence.iter().zip(residues).enumerate() {
+                let auth = residue
+                    .and_then(|r| identifiers.get(&(chain.id(), r.id())).map(|(_, auth)| auth));
+                let serial_number = auth.map_or_else(String::new, |[_, seq]| seq.clone());
+                let residue_name = residue
+                    .and_then(Residue::name)
+                    .map_or_else(String::new, cif_text);
+                rows.push(vec![
+                    asym.clone(),
+                    cif_text(&entity.id),
+                    (index + 1).to_string(),
+                    cif_text(name),
+                    (index + 1).to_string(),
+                    serial_number.clone(),
+                    serial_number,
+                    residue_name.clone(),
+                    residue_name,
+                    auth.map_or_else(|| cif_text(chain.id()), |[asym, _]| cif_text(asym)),
+                    residue
+                        .and_then(Residue::insertion_code)
+                        .map_or_else(|| ".".to_string(), cif_text),
+                    "n".to_string(),
+                ]);
+            }
+        }
+        if !rows.is_empty() {
+            write!("# Polymer sequence scheme");
+            write_loop(
+                &mut sink,
+                "pdbx_poly_seq_scheme",
+                &[
+                    "asym_id",
+                    "entity_id",
+                    "seq_id",
+                    "mon_id",
+                    "ndb_seq_num",
+                    "pdb_seq_num",
+                    "auth_seq_num",
+                    "pdb_mon_id",
+                    "auth_mon_id",
+                    "pdb_strand_id",
+                    "pdb_ins_code",
+                    "hetero",
+                ],
+                rows,
+            );
+        }
+    }
+
+    // The residues of the non-polymer entities
+    if let Some(model) = pdb.models().next() {
+        let mut rows = Vec::new();
+        for entity in pdb.entities().filter(|e| !e.is_polymer()) {
+            let name = if let Some(name) = entity.sequence.first() {
+                name
+            } else {
+                continue;
+            };
+            for chain in model.chains().filter(|c| entity.contains_chain(c.id())) {
+                for (index, residue) in chain
+                    .residues()
+                    .filter(|r| r.name() == Some(name.as_str()))
+                    .enumerate()
+                {
+                    let ([asym, _, _], [auth_asym, auth_seq]) =
+                        if let Some(ids) = identifiers.get(&(chain.id(), residue.id())) {
+                            ids
+                        } else {
+                            continue;
+                        };
+                    rows.push(vec![
+                        cif_text(asym),
+                        cif_text(&entity.id),
+                        cif_text(name),
+                        (index + 1).to_string(),
+                        auth_seq.clone(),
+                        auth_seq.clone(),
+                        cif_text(name),
+                        cif_text(name),
+                        cif_text(auth_asym),
+                        residue
+                            .insertion_code()
+                            .map_or_else(|| ".".to_string(), cif_text),
+                    ]);
+                }
+            }
+        }
+        if !rows.is_empty() {
+            write!("# Non-polymer entities");
+            write_loop(
+                &mut sink,
+                "pdbx_nonpoly_scheme",
+                &[
+                    "asym_id",
+                    "entity_id",
+                    "mon_id",
+                    "ndb_seq_num",
+                    "pdb_seq_num",
+                    "auth_seq_num",
+                    "pdb_mon_id",
+                    "auth_mon_id",
+                    "pdb_strand_id",
+                    "pdb_ins_code",
+                ],
+                rows,
+            );
+        }
+    }
+
+    // Get the label (comp, asym, seq) and auth (comp, asym, seq) identifiers and the insertion code for a residue,
+    // formatted as CIF values
+    let residue_ids = |residue: &ResidueReference| {
+        let ([label_asym, label_seq, _], [auth_asym, auth_seq]) = identifiers
+            .get(&(residue.chain_id.as_str(), residue.id()))
+            .cloned()
+            .unwrap_or_else(|| {
+                (
+                    Default::default(),
+                    [residue.chain_id.clone(), residue.serial_number.to_string()],
+                )
+            });
+        (
+            [cif_text(&residue.name), cif_text(&label_asym), label_seq],
+            [cif_text(&residue.name), cif_text(&auth_asym), auth_seq],
+            residue
+                .insertion_code
+                .as_deref()
+                .map_or_else(String::new, cif_text),
+        )
+    };
+
+    // Get the label asym id and the author defined asym and seq id for a residue, also if it is
+    // not present in the structure
+    let reference_ids = |residue: &ResidueReference| {
+        identifiers
+            .get(&(residue.chain_id.as_str(), residue.id()))
+            .map_or_else(
+                || {
+                    (
+                        label_asym(&residue.chain_id),
+                        cif_text(&residue.chain_id),
+                        residue.serial_number.to_string(),
+                    )
+                },
+                |([asym, _, _], [auth_asym, auth_seq])| {
+                    (cif_text(asym), cif_text(auth_asym), auth_seq.clone())
+                },
+            )
+    };
+
+    // Secondary structure
+    let helices: Vec<&Helix> = pdb
+        .secondary_structure()
+        .filter_map(|s| match s {
+            SecondaryStructure::Helix(h) => Some(h),
+            SecondaryStructure::Strand(_) => None,
+        })
+        .collect();
+    if !helices.is_empty() {
+        let rows = helices
+            .iter()
+            .map(|helix| {
+                let (beg_label, beg_auth, beg_ins) = residue_ids(&helix.start);
+                let (end_label, end_auth, end_ins) = residue_ids(&helix.end);
+                let mut row = vec![
+                    "HELX_P".to_string(),
+                    format!("HELX_P{}", helix.serial_number),
+                    cif_text(&helix.id),
+                ];
+                row.extend(beg_label);
+                row.push(beg_ins);
+                row.extend(end_label);
+                row.push(end_ins);
+                row.extend(beg_auth);
+                row.extend(end_auth);
+                row.push(helix.class.number().to_string());
+                row.push(cif_text(helix.comment.as_deref().unwrap_or_default()));
+                row.push(helix.length.map_or_else(String::new, |l| l.to_string()));
+                row
+            })
+            .collect();
+        write!("# Secondary structure, helices");
+        write_loop(
+            &mut sink,
+            "struct_conf",
+            &[
+                "conf_type_id",
+                "id",
+                "pdbx_PDB_helix_id",
+                "beg_label_comp_id",
+                "beg_label_asym_id",
+                "beg_label_seq_id",
+                "pdbx_beg_PDB_ins_code",
+                "end_label_comp_id",
+                "end_label_asym_id",
+                "end_label_seq_id",
+                "pdbx_end_PDB_ins_code",
+                "beg_auth_comp_id",
+                "beg_auth_asym_id",
+                "beg_auth_seq_id",
+                "end_auth_comp_id",
+                "end_auth_asym_id",
+                "end_auth_seq_id",
+                "pdbx_PDB_helix_class",
+                "details",
+                "pdbx_PDB_helix_length",
+            ],
+            rows,
+        );
+        write!(
+            "_struct_conf_type.id          HELX_P
+_struct_conf_type.criteria    ?
+_struct_conf_type.reference   ?"
+        );
+    }
+
+    let strands: Vec<&Strand> = pdb
+        .secondary_structure()
+        .filter_map(|s| match s {
+            SecondaryStructure::Strand(s) => Some(s),
+            SecondaryStructure::Helix(_) => None,
+        })
+        .collect();
+    if !strands.is_empty() {
+        let mut sheets: Vec<(&str, usize)> = Vec::new();
+        for strand in &strands {
+            if !sheets.iter().any(|(id, _)| *id == strand.sheet_id) {
+                sheets.push((&strand.sheet_id, strand.strand_count));
+            }
+        }
+        write!("# Secondary structure, sheets");
+        write_loop(
+            &mut sink,
+            "struct_sheet",
+            &["id", "type", "number_strands", "details"],
+            sheets
+                .iter()
+                .map(|(id, count)| {
+                    vec![
+                        cif_text(id),
+                        String::new(),
+                        count.to_string(),
+                        String::new(),
+                    ]
+                })
+                .collect(),
+        );
+
+        // The sense is defined with respect to the previous strand in the same sheet
+        let previous = |strand: &Strand| {
+            strands
+                .iter()
+                .filter(|s| s.sheet_id == strand.sheet_id && s.strand_number < strand.strand_number)
+                .max_by_key(|s| s.strand_number)
+        };
+        let order: Vec<Vec<String>> = strands
+            .iter()
+            .filter(|s| s.sense != StrandSense::First)
+            .filter_map(|s| {
+                previous(s).map(|p| {
+                    vec![
+                        cif_text(&s.sheet_id),
+                        p.strand_number.to_string(),
+                        s.strand_number.to_string(),
+                        String::new(),
+                        if s.sense == StrandSense::Parallel {
+                            "parallel".to_string()
+                        } else {
+                            "anti-parallel".to_string()
+                        },
+                    ]
+                })
+            })
+            .collect();
+        if !order.is_empty() {
+            write_loop(
+                &mut sink,
+                "struct_sheet_order",
+                &["sheet_id", "range_id_1", "range_id_2", "offset", "sense"],
+                order,
+            );
+        }
+
+        write_loop(
+            &mut sink,
+            "struct_sheet_range",
+            &[
+                "sheet_id",
+                "id",
+                "beg_label_comp_id",
+                "beg_label_asym_id",
+                "beg_label_seq_id",
+                "pdbx_beg_PDB_ins_code",
+                "end_label_comp_id",
+                "end_label_asym_id",
+                "end_label_seq_id",
+                "pdbx_end_PDB_ins_code",
+                "beg_auth_comp_id",
+                "beg_auth_asym_id",
+                "beg_auth_seq_id",
+                "end_auth_comp_id",
+                "end_auth_asym_id",
+                "end_auth_seq_id",
+            ],
+            strands
+                .iter()
+                .map(|strand| {
+                    let (beg_label, beg_auth, beg_ins) = residue_ids(&strand.start);
+                    let (end_label, end_auth, end_ins) = residue_ids(&strand.end);
+                    let mut row =
+                        vec![cif_text(&strand.sheet_id), strand.strand_number.to_string()];
+                    row.extend(beg_label);
+                    row.push(beg_ins);
+                    row.extend(end_label);
+                    row.push(end_ins);
+                    row.extend(beg_auth);
+                    row.extend(end_auth);
+                    row
+                })
+                .collect(),
+        );
+
+        let hbonds: Vec<Vec<String>> = strands
+            .iter()
+            .filter_map(|s| {
+                let registration = s.registration.as_ref()?;
+                let p = previous(s)?;
+                let (label_1, auth_1, ins_1) = residue_ids(&registration.previous);
+                let (label_2, auth_2, ins_2) = residue_ids(&registration.current);
+                let mut row = vec![
+                    cif_text(&s.sheet_id),
+                    p.strand_number.to_string(),
+                    s.strand_number.to_string(),
+                    cif_text(&registration.previous_atom),
+                ];
+                row.extend(label_1);
+                row.push(ins_1);
+                row.push(cif_text(&registration.previous_atom));
+                row.extend(auth_1);
+                row.push(cif_text(&registration.current_atom));
+                row.extend(label_2);
+                row.push(ins_2);
+                row.push(cif_text(&registration.current_atom));
+                row.extend(auth_2);
+                Some(row)
+            })
+            .collect();
+        if !hbonds.is_empty() {
+            write_loop(
+                &mut sink,
+                "pdbx_struct_sheet_hbond",
+                &[
+                    "sheet_id",
+                    "range_id_1",
+                    "range_id_2",
+                    "range_1_label_atom_id",
+                    "range_1_label_comp_id",
+                    "range_1_label_asym_id",
+                    "range_1_label_seq_id",
+                    "range_1_PDB_ins_code",
+                    "range_1_auth_atom_id",
+                    "range_1_auth_comp_id",
+                    "range_1_auth_asym_id",
+                    "range_1_auth_seq_id",
+                    "range_2_label_atom_id",
+                    "range_2_label_comp_id",
+                    "range_2_label_asym_id",
+                    "range_2_label_seq_id",
+                    "range_2_PDB_ins_code",
+                    "range_2_auth_atom_id",
+                    "range_2_auth_comp_id",
+                    "range_2_auth_asym_id",
+                    "range_2_auth_seq_id",
+                ],
+                hbonds,
+            );
+        }
+    }
+
+    // Bonds
+    let mut bond_counts: HashMap<&str, usize> = HashMap::new();
+    let connections: Vec<Vec<String>> = pdb
+        .annotated_bonds()
+        .iter()
+        .map(|(a, b, bond, details)| {
+            let count = bond_counts.entry(bond.conn_type()).or_default();
+            *count += 1;
+            let id = details
+                .and_then(|d| d.id.clone())
+                .unwrap_or_else(|| format!("{}{count}", bond.conn_type()));
+            let mut row = vec![cif_text(&id), bond.conn_type().to_string()];
+            let mut auth = Vec::new();
+            for (h, symmetry) in [
+                (a, details.and_then(|d| d.symmetry_operator_1.as_ref())),
+                (b, details.and_then(|d| d.symmetry_operator_2.as_ref())),
+            ] {
+                let ([comp, asym, seq], [_, auth_asym, auth_seq], ins_code) =
+                    residue_ids(&ResidueReference::from_residue(h.chain(), h.residue()));
+                row.extend([
+                    asym,
+                    comp,
+                    seq,
+                    cif_text(h.atom().name()),
+                    h.conformer()
+                        .alternative_location()
+                        .map_or_else(String::new, cif_text),
+                    ins_code,
+                    symmetry.map_or_else(|| "1_555".to_string(), |s| cif_text(s)),
+                ]);
+                auth.extend([auth_asym, auth_seq]);
+            }
+            row.extend(auth);
+            row.push(format!(
+                "{:.3}",
+                details
+                    .and_then(|d| d.distance)
+                    .unwrap_or_else(|| a.atom().distance(b.atom()))
+            ));
+            row
+        })
+        .collect();
+    if !connections.is_empty() {
+        write!("# Bonds");
+        write_loop(
+            &mut sink,
+            "struct_conn",
+            &[
+                "id",
+                "conn_type_id",
+                "ptnr1_label_asym_id",
+                "ptnr1_label_comp_id",
+                "ptnr1_label_seq_id",
+                "ptnr1_label_atom_id",
+                "pdbx_ptnr1_label_alt_id",
+                "pdbx_ptnr1_PDB_ins_code",
+                "ptnr1_symmetry",
+                "ptnr2_label_asym_id",
+                "ptnr2_label_comp_id",
+                "ptnr2_label_seq_id",
+                "ptnr2_label_atom_id",
+                "pdbx_ptnr2_label_alt_id",
+                "pdbx_ptnr2_PDB_ins_code",
+                "ptnr2_symmetry",
+                "ptnr1_auth_asym_id",
+                "ptnr1_auth_seq_id",
+                "ptnr2_auth_asym_id",
+                "ptnr2_auth_seq_id",
+                "pdbx_dist_value",
+            ],
+            connections,
+        );
+    }
+
+    // Assemblies
+    if pdb.assembly_count() > 0 {
+        write!("# Biological assemblies");
+        // Operators are shared between assemblies, operators with the same id but a different
+        // transformation are given a new id
+        let mut operators: Vec<(String, &TransformationMatrix)> = Vec::new();
+        let mut generators = Vec::new();
+        for assembly in pdb.assemblies() {
+            for generator in &assembly.generators {
+                let mut expression = Vec::new();
+                for operator in &generator.operators {
+                    let id = if let Some((id, _)) = operators
+                        .iter()
+                        .find(|(_, t)| **t == operator.transformation)
+                    {
+                        id.clone()
+                    } else {
+                        let mut id = operator.id.clone();
+                        let mut index = operators.len();
+                        while operators.iter().any(|(i, _)| *i == id) {
+                            index += 1;
+                            id = index.to_string();
+                        }
+                        operators.push((id.clone(), &operator.transformation));
+                        id
+                    };
+                    expression.push(id);
+                }
+                let chains: Vec<&str> = generator
+                    .chains
+                    .iter()
+                    .flat_map(|c| {
+                        label_asyms
+                            .get(c.as_str())
+                            .filter(|asyms| !asyms.is_empty())
+                            .map_or_else(
+                                || vec![c.as_str()],
+                                |asyms| asyms.iter().map(String::as_str).collect(),
+                            )
+                    })
+                    .collect();
+                generators.push(vec![
+                    cif_text(&assembly.id),
+                    cif_text(&expression.join(",")),
+                    cif_text(&chains.join(",")),
+                ]);
+            }
+        }
+        let optional = |value: Option<&String>| value.map_or_else(String::new, |v| cif_text(v));
+        write_loop(
+            &mut sink,
+            "pdbx_struct_assembly",
+            &["id", "details", "oligomeric_details", "oligomeric_count"],
+            pdb.assemblies()
+                .map(|a| {
+                    vec![
+                        cif_text(&a.id),
+                        optional(a.details.as_ref()),
+                        optional(a.oligomeric_details.as_ref()),
+                        a.oligomeric_count
+                            .map_or_else(String::new, |c| c.to_string()),
+                    ]
+                })
+                .collect(),
+        );
+        write_loop(
+            &mut sink,
+            "pdbx_struct_assembly_gen",
+            &["assembly_id", "oper_expression", "asym_id_list"],
+            generators,
+        );
+        write_loop(
+            &mut sink,
+            "pdbx_struct_oper_list",
+            &[
+                "id",
+                "matrix[1][1]",
+                "matrix[1][2]",
+                "matrix[1][3]",
+                "vector[1]",
+                "matrix[2][1]",
+                "matrix[2][2]",
+                "matrix[2][3]",
+                "vector[2]",
+                "matrix[3][1]",
+                "matrix[3][2]",
+                "matrix[3][3]",
+                "vector[3]",
+            ],
+            operators
+                .iter()
+                .map(|(id, transformation)| {
+                    let mut row = vec![cif_text(id)];
+                    row.extend(
+                        transformation
+                            .matrix()
+                            .iter()
+                            .flatten()
+                            .map(|v| format!("{v:.10}")),
+                    );
+                    row
+                })
+                .collect(),
+        );
+    }
+
+    // Missing residues and atoms
+    let mut missing_residues = Vec::new();
+    let mut missing_atoms = Vec::new();
+    for model in pdb.models() {
+        for chain in model.chains() {
+            let asym = label_asym(chain.id());
+            // The author defined chain id, which differs if the chain was read with label identifiers
+            let auth_asym = chain
+                .residues()
+                .next()
+                .and_then(|r| identifiers.get(&(chain.id(), r.id())))
+                .map_or_else(|| cif_text(chain.id()), |(_, [asym, _])| cif_text(asym));
+            for missing in chain.missing_residues() {
+                missing_residues.push(vec![
+                    (missing_residues.len() + 1).to_string(),
+                    model.serial_number().to_string(),
+                    "1".to_string(),
+                    auth_asym.clone(),
+                    cif_text(&missing.name),
+                    missing.serial_number.to_string(),
+                    missing
+                        .insertion_code
+                        .as_deref()
+                        .map_or_else(String::new, cif_text),
+                    asym.clone(),
+                    cif_text(&missing.name),
+                ]);
+            }
+            for missing in chain.missing_atoms() {
+                for atom in &missing.atoms {
+                    missing_atoms.push(vec![
+                        (missing_atoms.len() + 1).to_string(),
+                        model.serial_number().to_string(),
+                        "1".to_string(),
+                        auth_asym.clone(),
+                        cif_text(&missing.name),
+                        missing.serial_number.to_string(),
+                        missing
+                            .insertion_code
+                            .as_deref()
+                            .map_or_else(String::new, cif_text),
+                        cif_text(atom),
+                        asym.clone(),
+                        cif_text(&missing.name),
+                        cif_text(atom),
+                    ]);
+                }
+            }
+        }
+    }
+    if !missing_residues.is_empty() || !missing_atoms.is_empty() {
+        write!("# Missing residues and atoms");
+        write_loop(
+            &mut sink,
+            "pdbx_unobs_or_zero_occ_residues",
+            &[
+                "id",
+                "PDB_model_num",
+                "occupancy_flag",
+                "auth_asym_id",
+                "auth_comp_id",
+                "auth_seq_id",
+                "PDB_ins_code",
+                "label_asym_id",
+                "label_comp_id",
+            ],
+            missing_residues,
+        );
+        write_loop(
+            &mut sink,
+            "pdbx_unobs_or_zero_occ_atoms",
+            &[
+                "id",
+                "PDB_model_num",
+                "occupancy_flag",
+                "auth_asym_id",
+                "auth_comp_id",
+                "auth_seq_id",
+                "PDB_ins_code",
+                "auth_atom_id",
+                "label_asym_id",
+                "label_comp_id",
+                "label_atom_id",
+            ],
+            missing_atoms,
+        );
+    }
+
+    // Chemical components
+    if pdb.chemical_component_count() > 0 {
+        write!("# Chemical components");
+        write_loop(
+            &mut sink,
+            "chem_comp",
+            &["id", "name", "pdbx_synonyms", "formula"],
+            pdb.chemical_components()
+                .map(|component| {
+                    vec![
+                        cif_text(&component.id),
+                        component.name.as_deref().map_or_else(String::new, cif_text),
+                        cif_text(&component.synonyms.join("; ")),
+                        component
+                            .formula
+                            .as_deref()
+                            .map_or_else(String::new, cif_text),
+                    ]
+                })
+                .collect(),
+        );
+    }
+
+    // Sites
+    if pdb.site_count() > 0 {
+        write!("# Sites");
+        write_loop(
+            &mut sink,
+            "struct_site",
+            &["id", "pdbx_evidence_code", "pdbx_num_residues", "details"],
+            pdb.sites()
+                .map(|site| {
+                    vec![
+                        cif_text(&site.id),
+                        site.evidence.as_deref().map_or_else(String::new, cif_text),
+                        site.residues.len().to_string(),
+                        site.details.as_deref().map_or_else(String::new, cif_text),
+                    ]
+                })
+                .collect(),
+        );
+        let mut residues = Vec::new();
+        for site in pdb.sites() {
+            for residue in &site.residues {
+                let (asym, auth_asym, auth_seq) = reference_ids(residue);
+                residues.push(vec![
+                    (residues.len() + 1).to_string(),
+                    cif_text(&site.id),
+                    cif_text(&residue.name),
+                    auth_asym,
+                    auth_seq,
+                    residue
+                        .insertion_code
+                        .as_deref()
+                        .map_or_else(String::new, cif_text),
+                    cif_text(&residue.name),
+                    asym,
+                ]);
+            }
+        }
+        write_loop(
+            &mut sink,
+            "struct_site_gen",
+            &[
+                "id",
+                "site_id",
+                "auth_comp_id",
+                "auth_asym_id",
+                "auth_seq_id",
+                "pdbx_auth_ins_code",
+                "label_comp_id",
+                "label_asym_id",
+            ],
+            residues,
+        );
+    }
+
+    // Cis peptides
+    if pdb.cis_peptide_count() > 0 {
+        write!("# Cis peptides");
+        let residue = |r: &ResidueReference| {
+            let (asym, auth_asym, auth_seq) = reference_ids(r);
+            vec![
+                cif_text(&r.name),
+                asym,
+                cif_text(&r.name),
+                auth_seq,
+                auth_asym,
+                r.insertion_code
+                    .as_deref()
+                    .map_or_else(String::new, cif_text),
+            ]
+        };
+        write_loop(
+            &mut sink,
+            "struct_mon_prot_cis",
+            &[
+                "pdbx_id",
+                "label_comp_id",
+                "label_asym_id",
+                "auth_comp_id",
+                "auth_seq_id",
+                "auth_asym_id",
+                "pdbx_PDB_ins_code",
+                "pdbx_label_comp_id_2",
+                "pdbx_label_asym_id_2",
+                "pdbx_auth_comp_id_2",
+                "pdbx_auth_seq_id_2",
+                "pdbx_auth_asym_id_2",
+                "pdbx_PDB_ins_code_2",
+                "pdbx_PDB_model_num",
+                "pdbx_omega_angle",
+            ],
+            pdb.cis_peptides()
+                .map(|cis_peptide| {
+                    let mut row = vec![cis_peptide.serial_number.to_string()];
+                    row.extend(residue(&cis_peptide.start));
+                    row.extend(residue(&cis_peptide.end));
+                    row.push(cis_peptide.model.to_string());
+                    row.push(cis_peptide.angle.map_or_else(String::new, print_float));
+                    row
+                })
+                .collect(),
+        );
+    }
+
+    if let Some(block) = &pdb.unknown_categories {
+        write_unknown_categories(&mut sink, block);
+    }
+
     let anisou = pdb
         .atoms()
         .any(|a| a.anisotropic_temperature_factors().is_some());
@@ -303,10 +1496,10 @@ _atom_site.aniso_U[3][3]"
     let mut lines = Vec::new();
 
     for model in pdb.models() {
-        let mut chain_index = 0;
-        for chain in model.chains() {
-            chain_index += 1;
+        for (chain_index, chain) in model.chains().enumerate() {
             for (residue_index, residue) in chain.residues().enumerate() {
+                let ([label_asym, label_seq, entity], [auth_asym, auth_seq]) =
+                    residue_identifiers(pdb, (chain_index, chain), (residue_index, residue));
                 for conformer in residue.conformers() {
                     for atom in conformer.atoms() {
                         let mut data = vec![
@@ -318,18 +1511,18 @@ _atom_site.aniso_U[3][3]"
                             atom.name().to_string(),                                     // Name
                             conformer.alternative_location().unwrap_or(".").to_string(), // Alternative location
                             conformer.name().to_string(), // Residue name
-                            number_to_base26(chain_index), // Label Chain name, defined to be without gaps
-                            chain.id().to_string(),        // Auth Chain name
-                            chain_index.to_string(),       // Entity ID, using chain serial number
-                            (residue_index + 1).to_string(), // `label_seq_id` defined to be [1-N] where N is the index
-                            residue.serial_number().to_string(), // Residue serial number
+                            label_asym.clone(),           // Label Chain name
+                            auth_asym.clone(),            // Auth Chain name
+                            entity.clone(),               // Entity ID
+                            label_seq.clone(),            // Label residue serial number
+                            auth_seq.clone(),             // Auth residue serial number
                             residue.insertion_code().unwrap_or(".").to_string(), // Insertion code
-                            print_float(atom.x()),           // X
-                            print_float(atom.y()),           // Y
-                            print_float(atom.z()),           // Z
-                            print_float(atom.occupancy()),   // OCC/Q
-                            print_float(atom.b_factor()),    // B
-                            atom.charge().to_string(),       // Charge
+                            print_float(atom.x()),        // X
+                            print_float(atom.y()),        // Y
+                            print_float(atom.z()),        // Z
+                            print_float(atom.occupancy()), // OCC/Q
+                            print_float(atom.b_factor()), // B
+                            atom.charge().to_string(),    // Charge
                             model.serial_number().to_string(), // Model serial number
                         ];
                         if anisou {
@@ -366,6 +1559,203 @@ _atom_site.aniso_U[3][3]"
             }
         }
     }
+    write_table(&mut sink, lines);
+
+    write!("#");
+
+    sink.flush().unwrap();
+}
+
+/// The label asym, seq, and entity ids and the author defined asym and seq ids of a residue
+type Identifiers = ([String; 3], [String; 2]);
+
+/// Get the identifiers of the given residue at the given indices in its model. The identifiers
+/// kept from an mmCIF file are used, the others are taken from the hierarchy or generated. The label
+/// asym id is generated from the chain index, and the label seq id is the index in the chain,
+/// or '.' for a residue read from mmCIF without one, as is the case for non polymers.
+pub(super) fn residue_identifiers(
+    pdb: &PDB,
+    (chain_index, chain): (usize, &Chain),
+    (residue_index, residue): (usize, &Residue),
+) -> Identifiers {
+    let serial_number = residue.serial_number();
+    // The hierarchy was built from the label identifiers, see `ReadOptions::set_label_identifiers`
+    let label_hierarchy = residue.label_asym_id() == Some(chain.id())
+        && residue.label_seq_id().map_or(true, |s| s == serial_number)
+        && (residue.auth_asym_id() != Some(chain.id())
+            || residue.auth_seq_id() != Some(serial_number));
+    let label_seq = residue.label_seq_id().map_or_else(
+        || {
+            if residue.label_asym_id().is_some() {
+                ".".to_string()
+            } else {
+                (residue_index + 1).to_string()
+            }
+        },
+        |s| s.to_string(),
+    );
+    let (label_asym, auth_asym, auth_seq) = if label_hierarchy {
+        (
+            chain.id().to_string(),
+            residue.auth_asym_id().unwrap_or(chain.id()).to_string(),
+            residue.auth_seq_id().unwrap_or(serial_number).to_string(),
+        )
+    } else {
+        (
+            residue
+                .label_asym_id()
+                .map_or_else(|| number_to_base26(chain_index + 1), ToString::to_string),
+            chain.id().to_string(),
+            serial_number.to_string(),
+        )
+    };
+    let entity = residue
+        .label_entity_id()
+        .map(ToString::to_string)
+        .or_else(|| {
+            let polymer = residue.sequence_index().is_some();
+            pdb.entities()
+                .find(|e| {
+                    e.contains_chain(chain.id())
+                        && if polymer {
+                            e.is_polymer()
+                        } else {
+                            !e.is_polymer()
+                                && residue
+                                    .name()
+                                    .map_or(false, |n| e.sequence.iter().any(|s| s == n))
+                        }
+                })
+                .map(|e| e.id.clone())
+        })
+        .unwrap_or_else(|| (chain_index + 1).to_string());
+    ([label_asym, label_seq, entity], [auth_asym, auth_seq])
+}
+
+/// The categories that are interpreted when reading and generated from the PDB structure when
+/// saving, so these are never kept from the original file. Other categories kept from the original
+/// file (see [`crate::ReadOptions::set_keep_unknown_categories`]) are written back unchanged.
+pub(crate) const WRITTEN_CATEGORIES: &[&str] = &[
+    "atom_site",
+    "atom_sites",
+    "audit_author",
+    "audit_conform",
+    "cell",
+    "chem_comp",
+    "database_PDB_matrix",
+    "entity",
+    "entity_poly",
+    "entity_poly_seq",
+    "entity_src_gen",
+    "entity_src_nat",
+    "entry",
+    "exptl",
+    "pdbx_audit_revision_history",
+    "pdbx_database_status",
+    "pdbx_nonpoly_scheme",
+    "pdbx_poly_seq_scheme",
+    "pdbx_struct_assembly",
+    "pdbx_struct_assembly_gen",
+    "pdbx_struct_oper_list",
+    "pdbx_struct_sheet_hbond",
+    "pdbx_unobs_or_zero_occ_atoms",
+    "pdbx_unobs_or_zero_occ_residues",
+    "refine",
+    "refine_hist",
+    "reflns",
+    "software",
+    "space_group",
+    "struct",
+    "struct_conf",
+    "struct_conf_type",
+    "struct_conn",
+    "struct_keywords",
+    "struct_mon_prot_cis",
+    "struct_ncs_oper",
+    "struct_sheet",
+    "struct_sheet_order",
+    "struct_sheet_range",
+    "struct_site",
+    "struct_site_gen",
+    "symmetry",
+];
+
+/// Write the data items of the categories that were kept from the original file, skipping the
+/// categories that are generated from the PDB structure. Each category is followed by a '#' line.
+#[allow(clippy::unwrap_used)]
+fn write_unknown_categories<T: Write>(sink: &mut BufWriter<T>, block: &DataBlock) {
+    let mut last = None;
+    for item in &block.items {
+        let item = if let Item::DataItem(item) = item {
+            item
+        } else {
+            continue;
+        };
+        let category = item.category();
+        if category.map_or(true, |c| WRITTEN_CATEGORIES.contains(&c)) {
+            continue;
+        }
+        if last.is_some() && last != category {
+            sink.write_all(b"#\n").unwrap();
+        }
+        sink.write_fmt(format_args!("{item}")).unwrap();
+        last = category;
+    }
+    if last.is_some() {
+        sink.write_all(b"#\n").unwrap();
+    }
+}
+
+/// Write a loop for the given category with the given columns (without the category prefix) and rows.
+/// Nothing is written if there are no rows.
+#[allow(clippy::unwrap_used)]
+fn write_loop<T: Write>(
+    sink: &mut BufWriter<T>,
+    category: &str,
+    columns: &[&str],
+    rows: Vec<Vec<String>>,
+) {
+    if rows.is_empty() {
+        return;
+    }
+    sink.write_all(b"loop_\n").unwrap();
+    for column in columns {
+        sink.write_fmt(format_args!("_{category}.{column}\n"))
+            .unwrap();
+    }
+    write_table(sink, rows);
+    sink.write_all(b"#\n").unwrap();
+}
+
+/// Write a category with a single row as a list of data items, with the values aligned
+#[allow(clippy::unwrap_used)]
+fn write_items<T: Write>(sink: &mut BufWriter<T>, category: &str, items: &[(&str, String)]) {
+    let width = items.iter().map(|(c, _)| c.len()).max().unwrap_or(0);
+    for (column, value) in items {
+        let value = if value.trim().is_empty() { "?" } else { value };
+        let separator = if value.starts_with('\n') { "" } else { " " };
+        sink.write_fmt(format_args!(
+            "_{category}.{column:width$}{separator}{value}\n"
+        ))
+        .unwrap();
+    }
+    sink.write_all(b"#\n").unwrap();
+}
+
+/// Convert an author name to the format used in mmCIF files, names given as 'F.LAST' (as used in
+/// PDB files) are converted to 'LAST, F.', other names are kept as is.
+fn mmcif_author(name: &str) -> String {
+    match name.rfind('.') {
+        Some(index) if !name.contains(", ") && index + 1 < name.len() => {
+            format!("{}, {}", &name[index + 1..], &name[..=index])
+        }
+        _ => name.to_string(),
+    }
+}
+
+/// Write the rows of a loop as an aligned table, empty values are written as '?'
+#[allow(clippy::unwrap_used)]
+fn write_table<T: Write>(sink: &mut BufWriter<T>, lines: Vec<Vec<String>>) {
     if !lines.is_empty() {
         // Now align the table
         let mut sizes = vec![1; lines[0].len()];
@@ -394,10 +1784,6 @@ _atom_site.aniso_U[3][3]"
             sink.write_all(output.as_bytes()).unwrap();
         }
     }
-
-    write!("#");
-
-    sink.flush().unwrap();
 }
 
 /// Print a floating point with at least 1 decimal place and at max 5 decimals
@@ -0,0 +1,508 @@
+#[cfg(feature = "compression")]
+use flate2::{write::GzEncoder, Compression};
+
+use super::mmcif::residue_identifiers;
+use crate::error::*;
+use crate::msgpack::{self, MsgPack};
+use crate::read::*;
+use crate::reference_tables;
+use crate::structs::*;
+use crate::validate;
+use crate::StrictnessLevel;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::BufWriter;
+
+/// Save the given PDB struct to the given file as MMTF.
+/// # Errors
+/// It validates the PDB. It fails if the validation fails with the given `level`, or if the file could not be opened.
+/// If validation gives rise to problems, use the `save_mmtf_raw` function.
+pub fn save_mmtf(
+    pdb: &PDB,
+    filename: impl AsRef<str>,
+    level: StrictnessLevel,
+) -> Result<(), Vec<PDBError>> {
+    save_mmtf_(pdb, filename, level, BufWriter::new)
+}
+
+/// Save the given PDB struct to the given file as MMTF and compresses to .gz
+/// # Errors
+/// It validates the PDB. It fails if the validation fails with the given `level`, or if the file could not be opened.
+/// If validation gives rise to problems, use the `save_mmtf_raw` function.
+#[cfg(feature = "compression")]
+pub fn save_mmtf_gz(
+    pdb: &PDB,
+    filename: impl AsRef<str>,
+    level: StrictnessLevel,
+    compression_level: Option<Compression>,
+) -> Result<(), Vec<PDBError>> {
+    save_mmtf_(pdb, filename, level, |file| {
+        BufWriter::new(GzEncoder::new(file, compression_level.unwrap_or_default()))
+    })
+}
+
+/// Generic function to save the given PDB struct to the given file as MMTF,
+/// to some writer function, e.g. a GzEncoder or BufWriter.
+fn save_mmtf_<T, W>(
+    pdb: &PDB,
+    filename: impl AsRef<str>,
+    level: StrictnessLevel,
+    writer: W,
+) -> Result<(), Vec<PDBError>>
+where
+    T: Write,
+    W: FnOnce(File) -> BufWriter<T>,
+{
+    // Validates the PDB, and returns early if any errors are found
+    let filename = filename.as_ref();
+
+    let mut errors = validate(pdb);
+    for error in &errors {
+        if error.fails(level) {
+            return Err(errors);
+        }
+    }
+
+    // Creates a writer for the file
+    let file = match File::create(filename) {
+        Ok(f) => f,
+        Err(_e) => {
+            errors.push(PDBError::new(
+                ErrorLevel::BreakingError,
+                "Could not open file",
+                "Could not open the file for writing, make sure you have permission for this file and no other program is currently using it.",
+                Context::show(filename)
+            ));
+            return Err(errors);
+        }
+    };
+
+    save_mmtf_raw(pdb, writer(file));
+
+    Ok(())
+}
+
+/// A type of group (residue) for the `groupList` of an MMTF file, all groups with the same atoms
+/// and bonds share a group type
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct GroupType {
+    /// The name of the group, eg 'ALA'
+    name: String,
+    /// The names of the atoms
+    atom_names: Vec<String>,
+    /// The elements of the atoms
+    elements: Vec<String>,
+    /// The formal charges of the atoms
+    charges: Vec<i64>,
+    /// Pairs of indices of bonded atoms in this group
+    bonds: Vec<i64>,
+    /// The chemical component type, eg 'L-PEPTIDE LINKING'
+    chem_comp_type: &'static str,
+}
+
+impl GroupType {
+    /// Give this group type as an item of the `groupList`
+    fn to_msgpack(&self) -> MsgPack {
+        let strings =
+            |list: &[String]| MsgPack::Array(list.iter().map(|s| s.as_str().into()).collect());
+        let ints = |list: &[i64]| MsgPack::Array(list.iter().map(|i| (*i).into()).collect());
+        let code = reference_tables::get_one_letter_code(&self.name)
+            .filter(|_| self.chem_comp_type != "NON-POLYMER")
+            .map_or_else(|| "?".to_string(), String::from);
+        MsgPack::map(vec![
+            ("groupName", self.name.as_str().into()),
+            ("atomNameList", strings(&self.atom_names)),
+            ("elementList", strings(&self.elements)),
+            ("formalChargeList", ints(&self.charges)),
+            ("bondAtomList", ints(&self.bonds)),
+            ("bondOrderList", ints(&vec![1; self.bonds.len() / 2])),
+            ("singleLetterCode", code.as_str().into()),
+            ("chemCompType", self.chem_comp_type.into()),
+        ])
+    }
+}
+
+/// The chemical component type of the residue with the given name, only the standard amino
+/// acids and nucleotides are known to be linked in polymers
+fn chem_comp_type(name: &str) -> &'static str {
+    match name {
+        "GLY" => "PEPTIDE LINKING",
+        "DA" | "DC" | "DG" | "DT" | "DI" | "DN" => "DNA LINKING",
+        _ if reference_tables::is_amino_acid(name) => "L-PEPTIDE LINKING",
+        _ if reference_tables::get_one_letter_code(name).is_some() => "RNA LINKING",
+        _ => "NON-POLYMER",
+    }
+}
+
+/// A chain of an MMTF file, a chain in the PDB structure is split into a chain for every label
+/// asym id
+struct MmtfChain {
+    /// The label asym id
+    id: String,
+    /// The author defined asym id
+    name: String,
+    /// The entity id
+    entity: String,
+    /// The number of groups
+    groups: usize,
+    /// If any group is part of the sequence, to determine the entity type
+    polymer: bool,
+    /// If all groups are waters, to determine the entity type
+    water: bool,
+    /// The one letter codes of the groups in the sequence, for the entity sequence
+    sequence: String,
+}
+
+/// Save the given PDB struct to the given BufWriter as MMTF.
+/// Every conformer of a residue is saved as a separate group, and only covalent and disulfide
+/// bonds are saved, as MMTF does not specify the type of bonds.
+/// It does not validate or renumber the PDB, so if that is needed that needs to be done in preparation.
+#[allow(
+    clippy::unwrap_used,
+    clippy::too_many_lines,
+    clippy::cast_possible_wrap
+)]
+pub fn save_mmtf_raw<T: Write>(pdb: &PDB, mut sink: BufWriter<T>) {
+    // The atoms of all groups, the properties shared by all groups of a type are kept separately
+    let mut groups: Vec<GroupType> = Vec::new();
+    let mut group_ids = Vec::new();
+    let mut sequence_indices = Vec::new();
+    let mut insertion_codes = Vec::new();
+    let mut chains: Vec<MmtfChain> = Vec::new();
+    let mut chains_per_model = Vec::new();
+    let (mut x, mut y, mut z) = (Vec::new(), Vec::new(), Vec::new());
+    let mut b_factors = Vec::new();
+    let mut occupancies = Vec::new();
+    let mut atom_ids = Vec::new();
+    let mut alt_locs = Vec::new();
+    // The index of the atom and the index of its group for every atom counter
+    let mut atoms: HashMap<usize, (usize, usize)> = HashMap::new();
+
+    for model in pdb.models() {
+        let first_chain = chains.len();
+        for (chain_index, chain) in model.chains().enumerate() {
+            let mut current: Option<MmtfChain> = None;
+            for (residue_index, residue) in chain.residues().enumerate() {
+                let ([label_asym, label_seq, entity], [auth_asym, auth_seq]) =
+                    residue_identifiers(pdb, (chain_index, chain), (residue_index, residue));
+                if current.as_ref().map_or(true, |c| c.id != label_asym) {
+                    chains.extend(current.take());
+                    current = Some(MmtfChain {
+                        id: label_asym,
+                        name: auth_asym,
+                        entity,
+                        groups: 0,
+                        polymer: false,
+                        water: true,
+                        sequence: String::new(),
+                    });
+                }
+                let mmtf_chain = current.as_mut().unwrap();
+                let sequence_index = label_seq.parse::<i64>().map_or(-1, |s| s - 1);
+                if sequence_index >= 0 {
+                    mmtf_chain.polymer = true;
+                    mmtf_chain.sequence.push(
+                        residue
+                            .name()
+                            .and_then(reference_tables::get_one_letter_code)
+                            .unwrap_or('X'),
+                    );
+                }
+                mmtf_chain.water &= matches!(residue.name(), Some("HOH" | "WAT" | "DOD"));
+                for conformer in residue.conformers() {
+                    let group_index = groups.len();
+                    let mut group = GroupType {
+                        name: conformer.name().to_string(),
+                        atom_names: Vec::new(),
+                        elements: Vec::new(),
+                        charges: Vec::new(),
+                        bonds: Vec::new(),
+                        chem_comp_type: chem_comp_type(conformer.name()),
+                    };
+                    for atom in conformer.atoms() {
+                        atoms
+                            .entry(atom.counter())
+                            .or_insert((x.len(), group_index));
+                        group.atom_names.push(atom.name().to_string());
+                        group.elements.push(
+                            atom.element()
+                                .map_or_else(String::new, |e| e.symbol().to_string()),
+                        );
+                        group.charges.push(atom.charge() as i64);
+                        x.push(atom.x());
+                        y.push(atom.y());
+                        z.push(atom.z());
+                        b_factors.push(atom.b_factor());
+                        occupancies.push(atom.occupancy());
+                        atom_ids.push(atom.id().parse().unwrap_or(x.len() as i64));
+                        alt_locs.push(
+                            conformer
+                                .alternative_location()
+                                .and_then(|a| a.chars().next()),
+                        );
+                    }
+                    groups.push(group);
+                    group_ids.push(auth_seq.parse().unwrap_or(residue.serial_number() as i64));
+                    sequence_indices.push(sequence_index);
+                    insertion_codes.push(residue.insertion_code().and_then(|i| i.chars().next()));
+                    mmtf_chain.groups += 1;
+                }
+            }
+            chains.extend(current);
+        }
+        chains_per_model.push(chains.len() - first_chain);
+    }
+
+    // Bonds within a group are given in its group type, the others in the global list
+    let first_atoms: Vec<usize> = {
+        let mut first = vec![0; groups.len()];
+        let mut count = 0;
+        for (index, group) in groups.iter().enumerate() {
+            first[index] = count;
+            count += group.atom_names.len();
+        }
+        first
+    };
+    let mut bonds = Vec::new();
+    for (a, b, bond, _) in pdb.bond_counters() {
+        if !(bond.is_covalent() || *bond == Bond::Disulfide) {
+            continue;
+        }
+        if let (Some((a, group_a)), Some((b, group_b))) = (atoms.get(a), atoms.get(b)) {
+            if group_a == group_b {
+                let first = first_atoms[*group_a];
+                groups[*group_a]
+                    .bonds
+                    .extend([(a - first) as i64, (b - first) as i64]);
+            } else {
+                bonds.extend([*a as i64, *b as i64]);
+            }
+        }
+    }
+    let bond_count = bonds.len() / 2 + groups.iter().map(|g| g.bonds.len() / 2).sum::<usize>();
+
+    // Deduplicate the group types
+    let mut group_types: Vec<&GroupType> = Vec::new();
+    let mut type_indices: HashMap<&GroupType, usize> = HashMap::new();
+    let group_type_list: Vec<i64> = groups
+        .iter()
+        .map(|group| {
+            *type_indices.entry(group).or_insert_with(|| {
+                group_types.push(group);
+                group_types.len() - 1
+            }) as i64
+        })
+        .collect();
+
+    // The entities, in the order of the PDB with generated entities for the remaining chains
+    let model_chains = &chains[..chains_per_model.first().copied().unwrap_or(0)];
+    let mut entity_ids: Vec<&str> = pdb.entities().map(|e| e.id.as_str()).collect();
+    for chain in model_chains {
+        if !entity_ids.contains(&chain.entity.as_str()) {
+            entity_ids.push(&chain.entity);
+        }
+    }
+    let entities = entity_ids
+        .iter()
+        .map(|id| {
+            let entity = pdb.entity(id);
+            let entity_chains: Vec<(usize, &MmtfChain)> = model_chains
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| c.entity == *id)
+                .collect();
+            let entity_type = entity
+                .and_then(|e| e.entity_type.clone())
+                .unwrap_or_else(|| {
+                    match entity_chains.first() {
+                        Some((_, c)) if c.polymer => "polymer",
+                        Some((_, c)) if c.water => "water",
+                        _ => "non-polymer",
+                    }
+                    .to_string()
+                });
+            let sequence = entity
+                .filter(|e| e.is_polymer())
+                .map(Entity::one_letter_sequence)
+                .or_else(|| entity_chains.first().map(|(_, c)| c.sequence.clone()))
+                .unwrap_or_default();
+            MsgPack::map(vec![
+                (
+                    "chainIndexList",
+                    MsgPack::Array(
+                        entity_chains
+                            .iter()
+                            .map(|(i, _)| (*i as i64).into())
+                            .collect(),
+                    ),
+                ),
+                (
+                    "description",
+                    entity
+                        .and_then(|e| e.description.as_deref())
+                        .unwrap_or_default()
+                        .into(),
+                ),
+                ("type", entity_type.as_str().into()),
+                ("sequence", sequence.as_str().into()),
+            ])
+        })
+        .collect();
+
+    let chain_ids: Vec<String> = chains.iter().map(|c| c.id.clone()).collect();
+    let chain_names: Vec<String> = chains.iter().map(|c| c.name.clone()).collect();
+    let chain_id_length = chain_ids
+        .iter()
+        .chain(&chain_names)
+        .map(String::len)
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    let mut items = vec![
+        ("mmtfVersion", "1.0.0".into()),
+        (
+            "mmtfProducer",
+            format!("pdbtbx {}", env!("CARGO_PKG_VERSION"))
+                .as_str()
+                .into(),
+        ),
+    ];
+    if let Some(identifier) = &pdb.identifier {
+        items.push(("structureId", identifier.as_str().into()));
+    }
+    if let Some(header) = &pdb.header {
+        if let Some(title) = &header.title {
+            items.push(("title", title.as_str().into()));
+        }
+        if let Some(date) = &header.deposition_date {
+            items.push(("depositionDate", date.as_str().into()));
+        }
+        items.push((
+            "experimentalMethods",
+            MsgPack::Array(
+                header
+                    .experimental_methods
+                    .iter()
+                    .map(|m| m.as_str().into())
+                    .collect(),
+            ),
+        ));
+    }
+    if let Some(refinement) = &pdb.refinement {
+        for (key, value) in [
+            ("resolution", refinement.resolution),
+            ("rFree", refinement.r_free),
+            ("rWork", refinement.r_work),
+        ] {
+            if let Some(value) = value {
+                items.push((key, MsgPack::Float(value)));
+            }
+        }
+    }
+    if let Some(cell) = &pdb.unit_cell {
+        items.push((
+            "unitCell",
+            MsgPack::Array(
+                [
+                    cell.a(),
+                    cell.b(),
+                    cell.c(),
+                    cell.alpha(),
+                    cell.beta(),
+                    cell.gamma(),
+                ]
+                .into_iter()
+                .map(MsgPack::Float)
+                .collect(),
+            ),
+        ));
+    }
+    if let Some(symmetry) = &pdb.symmetry {
+        items.push(("spaceGroup", symmetry.herman_mauguin_symbol().into()));
+    }
+    items.extend([
+        ("numBonds", (bond_count as i64).into()),
+        ("numAtoms", (x.len() as i64).into()),
+        ("numGroups", (groups.len() as i64).into()),
+        ("numChains", (chains.len() as i64).into()),
+        ("numModels", (chains_per_model.len() as i64).into()),
+        ("entityList", MsgPack::Array(entities)),
+        (
+            "groupList",
+            MsgPack::Array(group_types.iter().map(|g| g.to_msgpack()).collect()),
+        ),
+        ("xCoordList", encode_fixed_delta(&x, 1000)),
+        ("yCoordList", encode_fixed_delta(&y, 1000)),
+        ("zCoordList", encode_fixed_delta(&z, 1000)),
+        ("bFactorList", encode_fixed_delta(&b_factors, 100)),
+        ("occupancyList", encode_fixed_run_length(&occupancies, 100)),
+        ("atomIdList", encode_delta_run_length(&atom_ids)),
+        ("altLocList", encode_chars(&alt_locs)),
+        ("bondAtomList", encode_int32(&bonds)),
+        ("bondOrderList", encode_int8(&vec![1; bonds.len() / 2])),
+        ("groupIdList", encode_delta_run_length(&group_ids)),
+        ("groupTypeList", encode_int32(&group_type_list)),
+        (
+            "sequenceIndexList",
+            encode_delta_run_length(&sequence_indices),
+        ),
+        ("insCodeList", encode_chars(&insertion_codes)),
+        ("chainIdList", encode_strings(&chain_ids, chain_id_length)),
+        (
+            "chainNameList",
+            encode_strings(&chain_names, chain_id_length),
+        ),
+        (
+            "groupsPerChain",
+            encode_int32(&chains.iter().map(|c| c.groups as i64).collect::<Vec<_>>()),
+        ),
+        (
+            "chainsPerModel",
+            encode_int32(
+                &chains_per_model
+                    .iter()
+                    .map(|c| *c as i64)
+                    .collect::<Vec<_>>(),
+            ),
+        ),
+    ]);
+
+    sink.write_all(&msgpack::encode(&MsgPack::map(items)))
+        .unwrap();
+    sink.flush().unwrap();
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::{Format, ReadOptions};
+
+    #[test]
+    fn group_types_are_shared() {
+        let (pdb, _) = ReadOptions::new()
+            .set_level(StrictnessLevel::Loose)
+            .read("example-pdbs/1ubq.cif")
+            .unwrap();
+        let mut bytes = Vec::new();
+        save_mmtf_raw(&pdb, BufWriter::new(&mut bytes));
+        let data = msgpack::decode(&bytes).unwrap();
+        let groups = data.get("groupList").unwrap().as_array().unwrap();
+        // The 20 amino acids and water, with the C terminal oxygen of the last residue
+        assert!(groups.len() < 25);
+        assert_eq!(
+            data.get("numGroups").unwrap().as_int(),
+            i64::try_from(pdb.total_conformer_count()).ok()
+        );
+
+        let (reread, _) = ReadOptions::new()
+            .set_format(Format::Mmtf)
+            .set_level(StrictnessLevel::Loose)
+            .read_raw(std::io::BufReader::new(&bytes[..]))
+            .unwrap();
+        assert_eq!(reread.atom_count(), pdb.atom_count());
+        assert_eq!(reread.total_residue_count(), pdb.total_residue_count());
+    }
+}
@@ -1,10 +1,51 @@
+/// Save BinaryCIF files
+mod bcif;
 /// Give a high level interface for users
 mod general;
+/// Save GROMACS files
+mod gro;
 /// Save mmCIF/PDBx files
 mod mmcif;
+/// Save MMTF files
+mod mmtf;
+/// Save Tripos MOL2 files
+mod mol2;
 /// Save PDB files
 mod pdb;
+/// Save PDBQT files
+mod pdbqt;
+/// Save PQR files
+mod pqr;
+/// Save SDF files
+mod sdf;
+/// Save XYZ files
+mod xyz;
 
+#[cfg(feature = "compression")]
+pub use bcif::save_bcif_gz;
+pub use bcif::{save_bcif, save_bcif_raw};
 pub use general::{save, save_gz};
+#[cfg(feature = "compression")]
+pub use gro::save_gro_gz;
+pub use gro::{save_gro, save_gro_raw};
+pub(crate) use mmcif::WRITTEN_CATEGORIES;
 pub use mmcif::{save_mmcif, save_mmcif_gz, save_mmcif_raw};
+#[cfg(feature = "compression")]
+pub use mmtf::save_mmtf_gz;
+pub use mmtf::{save_mmtf, save_mmtf_raw};
+#[cfg(feature = "compression")]
+pub use mol2::save_mol2_gz;
+pub use mol2::{save_mol2, save_mol2_raw};
 pub use pdb::{save_pdb, save_pdb_gz, save_pdb_raw};
+#[cfg(feature = "compression")]
+pub use pdbqt::save_pdbqt_gz;
+pub use pdbqt::{save_pdbqt, save_pdbqt_raw};
+#[cfg(feature = "compression")]
+pub use pqr::save_pqr_gz;
+pub use pqr::{save_pqr, save_pqr_raw};
+#[cfg(feature = "compression")]
+pub use sdf::save_sdf_gz;
+pub use sdf::{save_sdf, save_sdf_raw};
+#[cfg(feature = "compression")]
+pub use xyz::save_xyz_gz;
+pub use xyz::{save_xyz, save_xyz_raw};
@@ -0,0 +1,420 @@
+#[cfg(feature = "compression")]
+use flate2::{write::GzEncoder, Compression};
+
+use crate::error::*;
+use crate::structs::*;
+use crate::validate;
+use crate::StrictnessLevel;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::BufWriter;
+
+/// Save the given PDB struct to the given file as Tripos MOL2 (.mol2), validating it beforehand.
+/// # Errors
+/// It validates the PDB. It fails if the validation fails with the given `level`, or if the file could not be opened.
+/// If validation gives rise to problems, use the `save_mol2_raw` function.
+pub fn save_mol2(
+    pdb: &PDB,
+    filename: impl AsRef<str>,
+    level: StrictnessLevel,
+) -> Result<(), Vec<PDBError>> {
+    save_mol2_(pdb, filename, level, BufWriter::new)
+}
+
+/// Save the given PDB struct to the given file as Tripos MOL2 (.mol2) and compresses to .gz
+/// # Errors
+/// It validates the PDB. It fails if the validation fails with the given `level`, or if the file could not be opened.
+/// If validation gives rise to problems, use the `save_mol2_raw` function.
+#[cfg(feature = "compression")]
+pub fn save_mol2_gz(
+    pdb: &PDB,
+    filename: impl AsRef<str>,
+    level: StrictnessLevel,
+    compression_level: Option<Compression>,
+) -> Result<(), Vec<PDBError>> {
+    save_mol2_(pdb, filename, level, |file| {
+        BufWriter::new(GzEncoder::new(file, compression_level.unwrap_or_default()))
+    })
+}
+
+/// Generic function to save the given PDB struct to the given file as MOL2,
+/// to some writer function, e.g. a GzEncoder or BufWriter.
+fn save_mol2_<T, W>(
+    pdb: &PDB,
+    filename: impl AsRef<str>,
+    level: StrictnessLevel,
+    writer: W,
+) -> Result<(), Vec<PDBError>>
+where
+    T: Write,
+    W: FnOnce(File) -> BufWriter<T>,
+{
+    // Validates the PDB, and returns early if any errors are found
+    let filename = filename.as_ref();
+
+    let mut errors = validate(pdb);
+    for error in &errors {
+        if error.fails(level) {
+            return Err(errors);
+        }
+    }
+
+    // Creates a writer for the file
+    let file = match File::create(filename) {
+        Ok(f) => f,
+        Err(_e) => {
+            errors.push(PDBError::new(
+                ErrorLevel::BreakingError,
+                "Could not open file",
+                "Could not open the file for writing, make sure you have permission for this file and no other program is currently using it.",
+                Context::show(filename)
+            ));
+            return Err(errors);
+        }
+    };
+
+    save_mol2_raw(pdb, writer(file));
+
+    Ok(())
+}
+
+/// Save the given PDB struct to the given BufWriter as Tripos MOL2 (.mol2).
+/// It does not validate or renumber the PDB, so if that is needed, that needs to be done in preparation.
+///
+/// Every model is written as a separate molecule, named after the title of the header (or the
+/// identifier). Every residue is written as a substructure named after its name and number (eg
+/// `ALA12`), with its chain. The atoms are numbered from one in every molecule, with their atom
+/// type (see [`Atom::atom_type`]) and partial charge (see [`Atom::partial_charge`], zero if
+/// absent). Atoms without an atom type get a SYBYL atom type derived from their element and their
+/// bonds, so bonds with a bond order (see [`BondDetails::order`]) give the best results. The
+/// covalent and disulfide bonds are written with their bond order, or as single bonds if the order
+/// is not known. The unit cell and space group are written in the `@<TRIPOS>CRYSIN` section.
+#[allow(clippy::unwrap_used)]
+pub fn save_mol2_raw<T: Write>(pdb: &PDB, mut sink: BufWriter<T>) {
+    let mut print_line = |line: String| {
+        sink.write_all(line.as_bytes()).unwrap();
+        sink.write_all(b"\n").unwrap();
+    };
+    let title = pdb
+        .header
+        .as_ref()
+        .and_then(|h| h.title.as_deref())
+        .or(pdb.identifier.as_deref())
+        .unwrap_or("Written by pdbtbx");
+    let bonds: Vec<(usize, usize, Option<BondOrder>)> = pdb
+        .bond_counters()
+        .filter(|(_, _, bond, _)| bond.is_covalent() || *bond == Bond::Disulfide)
+        .map(|(a, b, _, details)| (*a, *b, details.as_ref().and_then(|d| d.order)))
+        .collect();
+
+    for model in pdb.models() {
+        let hierarchies: Vec<_> = model.atoms_with_hierarchy().collect();
+        let indices: HashMap<usize, usize> = hierarchies
+            .iter()
+            .enumerate()
+            .map(|(index, h)| (h.atom().counter(), index + 1))
+            .collect();
+        let model_bonds: Vec<(usize, usize, Option<BondOrder>)> = bonds
+            .iter()
+            .filter_map(|(a, b, order)| Some((*indices.get(a)?, *indices.get(b)?, *order)))
+            .collect();
+        let connectivity = Connectivity::new(&hierarchies, &model_bonds);
+
+        // Every residue is a substructure, given by its index, root atom, and first hierarchy
+        let mut substructures: Vec<(usize, &AtomConformerResidueChain<'_>)> = Vec::new();
+        let mut substructure_of_atom = Vec::with_capacity(hierarchies.len());
+        for (index, hierarchy) in hierarchies.iter().enumerate() {
+            let new = substructures.last().map_or(true, |(_, first)| {
+                !std::ptr::eq(first.residue(), hierarchy.residue())
+            });
+            if new {
+                substructures.push((index + 1, hierarchy));
+            }
+            substructure_of_atom.push(substructures.len());
+        }
+        let substructure_name = |h: &AtomConformerResidueChain<'_>| {
+            format!("{}{}", h.conformer().name(), h.residue().serial_number())
+        };
+
+        print_line("@<TRIPOS>MOLECULE".to_string());
+        print_line(title.to_string());
+        print_line(format!(
+            "{} {} {} 0 0",
+            hierarchies.len(),
+            model_bonds.len(),
+            substructures.len()
+        ));
+        print_line(if hierarchies.iter().any(|h| !h.atom().hetero()) {
+            "PROTEIN".to_string()
+        } else {
+            "SMALL".to_string()
+        });
+        print_line(
+            if hierarchies
+                .iter()
+                .any(|h| h.atom().partial_charge().is_some())
+            {
+                "USER_CHARGES".to_string()
+            } else {
+                "NO_CHARGES".to_string()
+            },
+        );
+        print_line(String::new());
+
+        print_line("@<TRIPOS>ATOM".to_string());
+        for (index, hierarchy) in hierarchies.iter().enumerate() {
+            let atom = hierarchy.atom();
+            let (x, y, z) = atom.pos();
+            let atom_type = atom
+                .atom_type()
+                .map_or_else(|| connectivity.sybyl_type(index + 1), str::to_string);
+            print_line(format!(
+                "{:>7} {:<8} {:>10.4} {:>10.4} {:>10.4} {:<8} {:>5} {:<8} {:>9.4}",
+                index + 1,
+                atom.name(),
+                x,
+                y,
+                z,
+                atom_type,
+                substructure_of_atom[index],
+                substructure_name(hierarchy),
+                atom.partial_charge().unwrap_or(0.0),
+            ));
+        }
+
+        print_line("@<TRIPOS>BOND".to_string());
+        for (index, (a, b, order)) in model_bonds.iter().enumerate() {
+            print_line(format!(
+                "{:>6} {:>5} {:>5} {}",
+                index + 1,
+                a,
+                b,
+                order.map_or("1", |o| o.sybyl_bond_type())
+            ));
+        }
+
+        print_line("@<TRIPOS>SUBSTRUCTURE".to_string());
+        for (index, (root, hierarchy)) in substructures.iter().enumerate() {
+            let inter_bonds = model_bonds
+                .iter()
+                .filter(|(a, b, _)| {
+                    (substructure_of_atom[a - 1] == index + 1)
+                        != (substructure_of_atom[b - 1] == index + 1)
+                })
+                .count();
+            print_line(format!(
+                "{:>6} {:<8} {:>6} RESIDUE {:>4} {:<4} {:<8} {:>2}",
+                index + 1,
+                substructure_name(hierarchy),
+                root,
+                1,
+                hierarchy.chain().id(),
+                hierarchy.conformer().name(),
+                inter_bonds,
+            ));
+        }
+
+        if let Some(unit_cell) = &pdb.unit_cell {
+            print_line("@<TRIPOS>CRYSIN".to_string());
+            print_line(format!(
+                "{:10.4} {:10.4} {:10.4} {:8.3} {:8.3} {:8.3} {:>4} 1",
+                unit_cell.a(),
+                unit_cell.b(),
+                unit_cell.c(),
+                unit_cell.alpha(),
+                unit_cell.beta(),
+                unit_cell.gamma(),
+                pdb.symmetry.as_ref().map_or(1, Symmetry::index),
+            ));
+        }
+    }
+
+    sink.flush().unwrap();
+}
+
+/// The atoms of a molecule with their bonded neighbours, used to derive SYBYL atom types.
+/// The atoms are given by their index in the molecule, starting at one.
+struct Connectivity<'a> {
+    /// The atoms, the atom with index `i` is at position `i - 1`
+    atoms: Vec<&'a Atom>,
+    /// The bonded neighbours of every atom with the bond order if known
+    neighbours: Vec<Vec<(usize, Option<BondOrder>)>>,
+}
+
+impl<'a> Connectivity<'a> {
+    /// Create the connectivity from the atoms and the bonds between them
+    fn new(
+        hierarchies: &'a [AtomConformerResidueChain<'_>],
+        bonds: &[(usize, usize, Option<BondOrder>)],
+    ) -> Self {
+        let mut neighbours = vec![Vec::new(); hierarchies.len()];
+        for (a, b, order) in bonds {
+            neighbours[a - 1].push((*b, *order));
+            neighbours[b - 1].push((*a, *order));
+        }
+        Connectivity {
+            atoms: hierarchies
+                .iter()
+                .map(ContainsAtomConformer::atom)
+                .collect(),
+            neighbours,
+        }
+    }
+
+    /// The element of the atom with the given index
+    fn element(&self, index: usize) -> Option<Element> {
+        self.atoms[index - 1].element().copied()
+    }
+
+    /// The neighbours of the atom with the given index
+    fn neighbours(&self, index: usize) -> &[(usize, Option<BondOrder>)] {
+        &self.neighbours[index - 1]
+    }
+
+    /// The number of bonds of the given order of the atom with the given index
+    fn bond_count(&self, index: usize, order: BondOrder) -> usize {
+        self.neighbours(index)
+            .iter()
+            .filter(|(_, o)| *o == Some(order))
+            .count()
+    }
+
+    /// The neighbours of the atom with the given index that are of the given element
+    fn neighbours_of(&self, index: usize, element: Element) -> impl Iterator<Item = usize> + '_ {
+        self.neighbours(index)
+            .iter()
+            .map(|(n, _)| *n)
+            .filter(move |n| self.element(*n) == Some(element))
+    }
+
+    /// Determine if the atom with the given index is a carbon atom with a double bond to an oxygen
+    fn is_carbonyl_carbon(&self, index: usize) -> bool {
+        self.element(index) == Some(Element::C)
+            && self
+                .neighbours(index)
+                .iter()
+                .any(|(n, o)| *o == Some(BondOrder::Double) && self.element(*n) == Some(Element::O))
+    }
+
+    /// Determine if the atom with the given index is a carbon atom bonded to two oxygen atoms
+    /// that are not bonded to anything else, as in a carboxylate group
+    fn is_carboxylate_carbon(&self, index: usize) -> bool {
+        self.element(index) == Some(Element::C)
+            && self
+                .neighbours_of(index, Element::O)
+                .filter(|o| self.neighbours(*o).len() == 1)
+                .count()
+                == 2
+    }
+
+    /// Derive the SYBYL atom type of the atom with the given index from its element and bonds.
+    /// If none of the bonds of the atom have a known bond order, the backbone atoms of amino acids
+    /// are typed based on their name and the other atoms based on their number of bonds.
+    fn sybyl_type(&self, index: usize) -> String {
+        let atom = self.atoms[index - 1];
+        let element = if let Some(element) = atom.element() {
+            *element
+        } else {
+            return "Du".to_string();
+        };
+        let neighbours = self.neighbours(index);
+        let known_orders = neighbours.iter().any(|(_, o)| o.is_some());
+        if !known_orders && !atom.hetero() {
+            match atom.name() {
+                "N" => return "N.am".to_string(),
+                "C" => return "C.2".to_string(),
+                "O" => return "O.2".to_string(),
+                "OXT" => return "O.co2".to_string(),
+                _ => (),
+            }
+        }
+        let aromatic = self.bond_count(index, BondOrder::Aromatic) > 0;
+        let double = self.bond_count(index, BondOrder::Double);
+        let triple = self.bond_count(index, BondOrder::Triple) > 0;
+        match element {
+            Element::C => {
+                if aromatic {
+                    "C.ar"
+                } else if triple || double >= 2 {
+                    "C.1"
+                } else if double == 1 && self.neighbours_of(index, Element::N).count() == 3 {
+                    "C.cat"
+                } else if double == 1 || (!known_orders && neighbours.len() == 3) {
+                    "C.2"
+                } else {
+                    "C.3"
+                }
+            }
+            Element::N => {
+                if aromatic {
+                    "N.ar"
+                } else if triple {
+                    "N.1"
+                } else if neighbours.len() == 4 || (atom.charge() > 0 && double == 0) {
+                    "N.4"
+                } else if double > 0 {
+                    "N.2"
+                } else if neighbours.iter().any(|(n, _)| self.is_carbonyl_carbon(*n)) {
+                    "N.am"
+                } else if neighbours.iter().any(|(n, _)| {
+                    self.bond_count(*n, BondOrder::Aromatic) > 0
+                        || self.bond_count(*n, BondOrder::Double) > 0
+                }) {
+                    "N.pl3"
+                } else {
+                    "N.3"
+                }
+            }
+            Element::O => {
+                if neighbours.len() == 1 && self.is_carboxylate_carbon(neighbours[0].0) {
+                    "O.co2"
+                } else if double > 0 {
+                    "O.2"
+                } else {
+                    "O.3"
+                }
+            }
+            Element::S => {
+                let oxygens = self
+                    .neighbours_of(index, Element::O)
+                    .filter(|o| self.neighbours(*o).len() == 1)
+                    .count();
+                if oxygens >= 2 {
+                    "S.O2"
+                } else if oxygens == 1 {
+                    "S.O"
+                } else if double > 0 {
+                    "S.2"
+                } else {
+                    "S.3"
+                }
+            }
+            Element::P => "P.3",
+            Element::Cr => "Cr.oh",
+            Element::Co => "Co.oh",
+            Element::H
+            | Element::F
+            | Element::Cl
+            | Element::Br
+            | Element::I
+            | Element::Li
+            | Element::Na
+            | Element::Mg
+            | Element::Al
+            | Element::Si
+            | Element::K
+            | Element::Ca
+            | Element::Mn
+            | Element::Fe
+            | Element::Cu
+            | Element::Zn
+            | Element::Se
+            | Element::Mo
+            | Element::Sn => return element.title_case_symbol(),
+            _ => "Du",
+        }
+        .to_string()
+    }
+}
@@ -3,6 +3,7 @@ use crate::StrictnessLevel;
 use crate::TransformationMatrix;
 
 use std::cmp;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs::File;
 use std::io::BufWriter;
 use std::iter;
@@ -102,10 +103,405 @@ where
     Ok(())
 }
 
+/// Generate the REMARK 2 (resolution), REMARK 3 (refinement), and REMARK 200 (data collection)
+/// lines for the given refinement statistics, in the layout used by the wwPDB.
+fn refinement_remarks(refinement: &RefinementInfo) -> Vec<(usize, String)> {
+    /// Format a value with the given precision or 'NULL' if it is not known
+    fn float(value: Option<f64>, precision: usize) -> String {
+        value.map_or_else(|| "NULL".to_string(), |v| format!("{v:.precision$}"))
+    }
+    /// Format a count or 'NULL' if it is not known
+    fn count(value: Option<usize>) -> String {
+        value.map_or_else(|| "NULL".to_string(), |v| v.to_string())
+    }
+    let mut remarks = vec![
+        (2, String::new()),
+        (
+            2,
+            refinement.resolution.map_or_else(
+                || "RESOLUTION. NOT APPLICABLE.".to_string(),
+                |r| format!("RESOLUTION.{r:8.2} ANGSTROMS."),
+            ),
+        ),
+    ];
+    let r = refinement;
+    let lines = [
+        String::new(),
+        "REFINEMENT.".to_string(),
+        format!("  PROGRAM     : {}", r.program.as_deref().unwrap_or("NULL")),
+        String::new(),
+        " DATA USED IN REFINEMENT.".to_string(),
+        format!(
+            "  RESOLUTION RANGE HIGH (ANGSTROMS) : {}",
+            float(r.resolution, 2)
+        ),
+        format!(
+            "  RESOLUTION RANGE LOW  (ANGSTROMS) : {}",
+            float(r.resolution_low, 2)
+        ),
+        format!(
+            "  COMPLETENESS FOR RANGE        (%) : {}",
+            float(r.completeness, 1)
+        ),
+        format!(
+            "  NUMBER OF REFLECTIONS             : {}",
+            count(r.reflection_count)
+        ),
+        String::new(),
+        " FIT TO DATA USED IN REFINEMENT.".to_string(),
+        format!("  R VALUE     (WORKING + TEST SET) : {}", float(r.r_all, 3)),
+        format!(
+            "  R VALUE            (WORKING SET) : {}",
+            float(r.r_work, 3)
+        ),
+        format!(
+            "  FREE R VALUE                     : {}",
+            float(r.r_free, 3)
+        ),
+        format!(
+            "  FREE R VALUE TEST SET SIZE   (%) : {}",
+            float(r.free_set_percentage, 3)
+        ),
+        format!(
+            "  FREE R VALUE TEST SET COUNT      : {}",
+            count(r.free_set_count)
+        ),
+        String::new(),
+        " NUMBER OF NON-HYDROGEN ATOMS USED IN REFINEMENT.".to_string(),
+        format!("  PROTEIN ATOMS            : {}", count(r.protein_atoms)),
+        format!(
+            "  NUCLEIC ACID ATOMS       : {}",
+            count(r.nucleic_acid_atoms)
+        ),
+        format!("  HETEROGEN ATOMS          : {}", count(r.heterogen_atoms)),
+        format!("  SOLVENT ATOMS            : {}", count(r.solvent_atoms)),
+        String::new(),
+        " B VALUES.".to_string(),
+        format!(
+            "  MEAN B VALUE      (OVERALL, A**2) : {}",
+            float(r.mean_b_factor, 2)
+        ),
+    ];
+    remarks.extend(lines.into_iter().map(|l| (3, l)));
+    if let Some(d) = &refinement.reflections {
+        let lines = [
+            String::new(),
+            "EXPERIMENTAL DETAILS".to_string(),
+            format!(
+                " NUMBER OF UNIQUE REFLECTIONS   : {}",
+                count(d.unique_reflections)
+            ),
+            format!(
+                " RESOLUTION RANGE HIGH      (A) : {}",
+                float(d.resolution_high, 3)
+            ),
+            format!(
+                " RESOLUTION RANGE LOW       (A) : {}",
+                float(d.resolution_low, 3)
+            ),
+            String::new(),
+            "OVERALL.".to_string(),
+            format!(
+                " COMPLETENESS FOR RANGE     (%) : {}",
+                float(d.completeness, 1)
+            ),
+            format!(
+                " DATA REDUNDANCY                : {}",
+                float(d.redundancy, 3)
+            ),
+            format!(" R MERGE                    (I) : {}", float(d.r_merge, 5)),
+            format!(
+                " <I/SIGMA(I)> FOR THE DATA SET  : {}",
+                float(d.i_over_sigma, 4)
+            ),
+        ];
+        remarks.extend(lines.into_iter().map(|l| (200, l)));
+    }
+    remarks
+}
+
+/// Generate the REMARK 350 lines describing the given biological assemblies, with the operators
+/// given as BIOMT lines. Operators without a numeric id are numbered in order of appearance.
+fn assembly_remarks<'a>(assemblies: impl Iterator<Item = &'a Assembly>) -> Vec<(usize, String)> {
+    let mut lines = vec![
+        String::new(),
+        "COORDINATES FOR A COMPLETE MULTIMER REPRESENTING THE KNOWN".to_string(),
+        "BIOLOGICALLY SIGNIFICANT OLIGOMERIZATION STATE OF THE".to_string(),
+        "MOLECULE CAN BE GENERATED BY APPLYING BIOMT TRANSFORMATIONS".to_string(),
+        "GIVEN BELOW.  BOTH NON-CRYSTALLOGRAPHIC AND".to_string(),
+        "CRYSTALLOGRAPHIC OPERATIONS ARE GIVEN.".to_string(),
+    ];
+    for assembly in assemblies {
+        lines.push(String::new());
+        lines.push(format!("BIOMOLECULE: {}", assembly.id));
+        let details = assembly.details.as_deref().unwrap_or_default();
+        let oligomer = assembly
+            .oligomeric_details
+            .as_deref()
+            .unwrap_or_default()
+            .to_uppercase();
+        if details.contains("author") {
+            lines.push(format!("AUTHOR DETERMINED BIOLOGICAL UNIT: {oligomer}"));
+        }
+        if details.contains("software") {
+            lines.push(format!(
+                "SOFTWARE DETERMINED QUATERNARY STRUCTURE: {oligomer}"
+            ));
+        }
+        let mut serial = 0;
+        for generator in &assembly.generators {
+            // Wrap the list of chains over multiple lines if needed
+            let mut prefix = "APPLY THE FOLLOWING TO CHAINS: ";
+            let mut current = String::new();
+            for (index, chain) in generator.chains.iter().enumerate() {
+                current.push_str(chain);
+                if index + 1 < generator.chains.len() {
+                    current.push(',');
+                    if prefix.len() + current.len() > 64 {
+                        lines.push(format!("{prefix}{current}"));
+                        prefix = "                   AND CHAINS: ";
+                        current.clear();
+                        continue;
+                    }
+                    current.push(' ');
+                }
+            }
+            lines.push(format!("{prefix}{current}"));
+            for operator in &generator.operators {
+                serial = operator.id.parse().unwrap_or(serial + 1);
+                for (row, values) in operator.transformation.matrix().iter().enumerate() {
+                    lines.push(format!(
+                        "  BIOMT{}{serial:4}{:10.6}{:10.6}{:10.6}{:15.5}",
+                        row + 1,
+                        values[0],
+                        values[1],
+                        values[2],
+                        values[3]
+                    ));
+                }
+            }
+        }
+    }
+    lines.into_iter().map(|l| (350, l)).collect()
+}
+
+/// Generate the REMARK 800 lines describing the given sites.
+fn site_remarks<'a>(sites: impl Iterator<Item = &'a Site>) -> Vec<(usize, String)> {
+    let mut remarks = vec![(800, String::new()), (800, "SITE".to_string())];
+    for site in sites {
+        remarks.push((800, format!("SITE_IDENTIFIER: {}", site.id)));
+        if let Some(evidence) = &site.evidence {
+            remarks.push((800, format!("EVIDENCE_CODE: {evidence}")));
+        }
+        if let Some(details) = &site.details {
+            remarks.push((800, format!("SITE_DESCRIPTION: {details}")));
+        }
+        remarks.push((800, String::new()));
+    }
+    remarks
+}
+
+/// Generate the REMARK 465 (missing residues) and REMARK 470 (missing atoms) lines for the
+/// given PDB. The model numbers are only given if there are multiple models.
+fn missing_remarks(pdb: &PDB) -> Vec<(usize, String)> {
+    let mut residues = Vec::new();
+    let mut atoms = Vec::new();
+    for model in pdb.models() {
+        let number = if pdb.model_count() > 1 {
+            model.serial_number().to_string()
+        } else {
+            String::new()
+        };
+        for chain in model.chains() {
+            for missing in chain.missing_residues() {
+                residues.push(format!(
+                    "{number:>3} {:>3} {} {:>5}{}",
+                    missing.name,
+                    chain.id(),
+                    missing.serial_number,
+                    missing.insertion_code.as_deref().unwrap_or(" ")
+                ));
+            }
+            for missing in chain.missing_atoms() {
+                let names: String = missing
+                    .atoms
+                    .iter()
+                    .map(|a| {
+                        if a.len() < 4 {
+                            format!(" {a:<4}")
+                        } else {
+                            format!("{a:<5}")
+                        }
+                    })
+                    .collect();
+                atoms.push(format!(
+                    "{number:>3} {:>3} {}{:>4}{}  {}",
+                    missing.name,
+                    chain.id(),
+                    missing.serial_number,
+                    missing.insertion_code.as_deref().unwrap_or(" "),
+                    names.trim_end()
+                ));
+            }
+        }
+    }
+    let mut remarks = Vec::new();
+    if !residues.is_empty() {
+        let header = [
+            "",
+            "MISSING RESIDUES",
+            "THE FOLLOWING RESIDUES WERE NOT LOCATED IN THE",
+            "EXPERIMENT. (M=MODEL NUMBER; RES=RESIDUE NAME; C=CHAIN",
+            "IDENTIFIER; SSSEQ=SEQUENCE NUMBER; I=INSERTION CODE.)",
+            "",
+            "  M RES C SSSEQI",
+        ];
+        remarks.extend(header.iter().map(|l| (465, l.to_string())));
+        remarks.extend(residues.into_iter().map(|l| (465, l)));
+    }
+    if !atoms.is_empty() {
+        let header = [
+            "",
+            "MISSING ATOM",
+            "THE FOLLOWING RESIDUES HAVE MISSING ATOMS (M=MODEL NUMBER;",
+            "RES=RESIDUE NAME; C=CHAIN IDENTIFIER; SSEQ=SEQUENCE NUMBER;",
+            "I=INSERTION CODE):",
+            "  M RES CSSEQI  ATOMS",
+        ];
+        remarks.extend(header.iter().map(|l| (470, l.to_string())));
+        remarks.extend(atoms.into_iter().map(|l| (470, l)));
+    }
+    remarks
+}
+
+/// Generate the HET, HETNAM, HETSYN, and FORMUL lines for the hetero residues in the first model
+/// of the given PDB. The descriptions are taken from the chemical components of the PDB, the
+/// components are numbered after the molecules in the header. Returns the lines and the number
+/// of HET records.
+fn heterogen_lines(pdb: &PDB) -> (Vec<String>, usize) {
+    /// Split the text into lines of at most `width` characters, breaking at spaces
+    fn wrap(text: &str, width: usize) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut current = String::new();
+        for word in text.split(' ').filter(|w| !w.is_empty()) {
+            if !current.is_empty() && current.len() + 1 + word.len() > width {
+                lines.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(word);
+        }
+        if !current.is_empty() {
+            lines.push(current);
+        }
+        lines
+    }
+    /// The continuation number for the given line index, empty for the first line
+    fn continuation(index: usize) -> String {
+        if index == 0 {
+            String::new()
+        } else {
+            (index + 1).to_string()
+        }
+    }
+
+    let mut het = Vec::new();
+    // The component ids in order of appearance with the number of residues
+    let mut components: Vec<(&str, usize)> = Vec::new();
+    if let Some(model) = pdb.models().next() {
+        for chain in model.chains() {
+            for residue in chain.residues() {
+                let name = match residue.name() {
+                    Some(name) if residue.atoms().any(Atom::hetero) => name,
+                    _ => continue,
+                };
+                if name != "HOH" {
+                    het.push(format!(
+                        "HET    {name:>3}  {}{:>4}{}  {:>5}",
+                        chain.id(),
+                        residue.serial_number(),
+                        residue.insertion_code().unwrap_or(" "),
+                        residue.atom_count()
+                    ));
+                }
+                match components.iter_mut().find(|(id, _)| *id == name) {
+                    Some((_, count)) => *count += 1,
+                    None => components.push((name, 1)),
+                }
+            }
+        }
+    }
+    let het_count = het.len();
+    let mut lines = het;
+    let components: Vec<(&ChemicalComponent, usize)> = components
+        .into_iter()
+        .filter_map(|(id, count)| pdb.chemical_component(id).map(|c| (c, count)))
+        .collect();
+    for (component, _) in &components {
+        if let (Some(name), false) = (&component.name, component.id == "HOH") {
+            for (index, text) in wrap(name, 55).iter().enumerate() {
+                lines.push(format!(
+                    "HETNAM  {:>2} {:>3} {text}",
+                    continuation(index),
+                    component.id
+                ));
+            }
+        }
+    }
+    for (component, _) in &components {
+        if !component.synonyms.is_empty() {
+            for (index, text) in wrap(&component.synonyms.join("; "), 55).iter().enumerate() {
+                lines.push(format!(
+                    "HETSYN  {:>2} {:>3} {text}",
+                    continuation(index),
+                    component.id
+                ));
+            }
+        }
+    }
+    let first = pdb.header.as_ref().map_or(0, |h| h.molecules.len()) + 1;
+    for (number, (component, count)) in components.iter().enumerate() {
+        if let Some(formula) = &component.formula {
+            // Waters are marked with an asterisk in column 19
+            let (marker, formula) = if component.id == "HOH" {
+                ("*", format!("{count}({formula})"))
+            } else if *count > 1 {
+                (" ", format!("{count}({formula})"))
+            } else {
+                (" ", formula.clone())
+            };
+            for (index, text) in wrap(&formula, 51).iter().enumerate() {
+                lines.push(format!(
+                    "FORMUL  {:>2}  {:>3} {:>2}{}{text}",
+                    first + number,
+                    component.id,
+                    continuation(index),
+                    if index == 0 { marker } else { " " }
+                ));
+            }
+        }
+    }
+    (lines, het_count)
+}
+
+/// Convert an author name to the format used in PDB files, names given as 'Last, F.' (as used in
+/// mmCIF files) are converted to 'F.LAST', other names are only capitalised.
+fn pdb_author(name: &str) -> String {
+    if let Some((last, first)) = name.split_once(", ") {
+        format!("{first}{last}").to_uppercase()
+    } else {
+        name.to_uppercase()
+    }
+}
+
 /// Save the given PDB struct to the given BufWriter.
 /// It does not validate or renumber the PDB, so if that is needed, that needs to be done in preparation.
 /// It does change the output format based on the StrictnessLevel given.
 ///
+/// The sequences of polymer entities are saved as SEQRES records, and the entities are described
+/// in the COMPND records if the header does not contain any molecules.
+///
 /// ## Loose
 /// * Does not pad all lines to 70 chars length
 /// * Does not save the MASTER record
@@ -145,18 +541,166 @@ pub fn save_pdb_raw<T: Write>(pdb: &PDB, mut sink: BufWriter<T>, level: Strictne
         };
     }
 
-    if let Some(name) = &pdb.identifier {
-        print_line(vec![
-            (
-                0,
-                "HEADER                                                        ",
-            ),
-            (0, name),
-        ]);
+    if pdb.identifier.is_some() || pdb.header.is_some() {
+        let classification = pdb
+            .header
+            .as_ref()
+            .and_then(|h| h.classification.as_deref());
+        let date = pdb
+            .header
+            .as_ref()
+            .and_then(|h| h.deposition_date.as_deref())
+            .and_then(date_to_pdb);
+        let line = format!(
+            "HEADER    {:40.40}{:9}   {:4}",
+            classification.unwrap_or_default(),
+            date.unwrap_or_default(),
+            get_option!(pdb.identifier),
+        );
+        print_line(vec![(0, &line)]);
     }
 
-    // Remarks
-    for line in pdb.remarks() {
+    // Title section, without a header only the COMPND records describing the entities are saved
+    let no_header = Header::default();
+    {
+        let header = pdb.header.as_ref().unwrap_or(&no_header);
+        // Print the given pieces of text as a record with continuation lines, every piece starts on a new line
+        let mut continued = |record: &str, pieces: Vec<String>| {
+            let mut lines = Vec::new();
+            for piece in pieces {
+                let mut current = String::new();
+                for word in piece.split(' ').filter(|w| !w.is_empty()) {
+                    let max = if lines.is_empty() { 70 } else { 69 };
+                    if !current.is_empty() && current.len() + 1 + word.len() > max {
+                        lines.push(std::mem::take(&mut current));
+                    }
+                    if !current.is_empty() {
+                        current.push(' ');
+                    }
+                    current.push_str(word);
+                }
+                if !current.is_empty() {
+                    lines.push(current);
+                }
+            }
+            for (index, text) in lines.iter().enumerate() {
+                let line = if index == 0 {
+                    format!("{record:6}    {text}")
+                } else {
+                    format!("{record:6}  {:>2} {text}", index + 1)
+                };
+                print_line(vec![(0, &line)]);
+            }
+        };
+        if let Some(title) = &header.title {
+            continued("TITLE", vec![title.clone()]);
+        }
+        // Without molecules in the header, the polymer entities are described in the COMPND records
+        let entity_molecules: Vec<MoleculeInfo> = if header.molecules.is_empty() {
+            pdb.entities()
+                .filter(|e| e.is_polymer() && !e.chains.is_empty())
+                .map(|entity| {
+                    let mut molecule = MoleculeInfo::new(entity.id.as_str());
+                    if let Some(description) = &entity.description {
+                        molecule
+                            .compound
+                            .push(("MOLECULE".to_string(), description.clone()));
+                    }
+                    molecule
+                        .compound
+                        .push(("CHAIN".to_string(), entity.chains.join(", ")));
+                    molecule
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        for (record, compound) in [("COMPND", true), ("SOURCE", false)] {
+            let mut tokens = Vec::new();
+            for molecule in header.molecules.iter().chain(&entity_molecules) {
+                let specification = if compound {
+                    &molecule.compound
+                } else {
+                    &molecule.source
+                };
+                if specification.is_empty() {
+                    continue;
+                }
+                tokens.push(format!("MOL_ID: {}", molecule.id));
+                tokens.extend(specification.iter().map(|(t, v)| format!("{t}: {v}")));
+            }
+            let count = tokens.len();
+            let pieces = tokens
+                .into_iter()
+                .enumerate()
+                .map(|(i, t)| if i + 1 < count { t + ";" } else { t })
+                .collect::<Vec<_>>();
+            continued(record, pieces);
+        }
+        if !header.keywords.is_empty() {
+            continued("KEYWDS", vec![header.keywords.join(", ")]);
+        }
+        if !header.experimental_methods.is_empty() {
+            continued("EXPDTA", vec![header.experimental_methods.join("; ")]);
+        }
+        if !header.authors.is_empty() {
+            let authors: Vec<String> = header.authors.iter().map(|a| pdb_author(a)).collect();
+            continued("AUTHOR", vec![authors.join(",")]);
+        }
+        for revision in header.revisions.iter().rev() {
+            let records = if revision.records.is_empty() {
+                vec![&[][..]]
+            } else {
+                revision.records.chunks(4).collect()
+            };
+            for (index, chunk) in records.into_iter().enumerate() {
+                let (continuation, date, identifier) = if index == 0 {
+                    (
+                        String::new(),
+                        revision.date.as_deref().and_then(date_to_pdb),
+                        revision.identifier.as_deref(),
+                    )
+                } else {
+                    ((index + 1).to_string(), None, None)
+                };
+                let line = format!(
+                    "REVDAT {:>3}{:>2} {:9} {:4}    {}       {}",
+                    revision.number,
+                    continuation,
+                    date.unwrap_or_default(),
+                    identifier.unwrap_or_default(),
+                    revision.modification_type,
+                    chunk
+                        .iter()
+                        .map(|r| format!("{r:6}"))
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                );
+                print_line(vec![(0, line.trim_end())]);
+            }
+        }
+    }
+
+    // Remarks, with the refinement statistics if these are not given in the remarks already
+    let mut remarks: Vec<(usize, String)> = pdb.remarks().cloned().collect();
+    let mut generated = pdb
+        .refinement
+        .as_ref()
+        .map(refinement_remarks)
+        .unwrap_or_default();
+    if pdb.assembly_count() > 0 {
+        generated.extend(assembly_remarks(pdb.assemblies()));
+    }
+    generated.extend(missing_remarks(pdb));
+    if pdb.site_count() > 0 {
+        generated.extend(site_remarks(pdb.sites()));
+    }
+    generated.retain(|(number, _)| !pdb.remarks().any(|(n, _)| n == number));
+    if !generated.is_empty() {
+        remarks.extend(generated);
+        remarks.sort_by_key(|(number, _)| *number);
+    }
+    for line in &remarks {
         print_line(vec![
             (6, "REMARK"),
             (0, " "),
@@ -278,9 +822,34 @@ pub fn save_pdb_raw<T: Write>(pdb: &PDB, mut sink: BufWriter<T>, level: Strictne
             }
         }
 
-        // SEQRES
-        if seqres {
-            for chain in model.chains() {
+        // SEQRES, from the deposited sequence of the entity of the chain if known
+        for chain in model.chains() {
+            if let Some(entity) = pdb
+                .entity_of_chain(chain.id())
+                .filter(|e| e.is_polymer() && !e.sequence.is_empty())
+            {
+                let count = entity.sequence.len().to_string();
+                for (index, chunk) in entity
+                    .sequence
+                    .iter()
+                    .map(|name| format!("{name:>3}"))
+                    .collect::<Vec<String>>()
+                    .chunks(13)
+                    .enumerate()
+                {
+                    print_line(vec![
+                        (6, "SEQRES"),
+                        (0, " "),
+                        (3, (index + 1).to_string().as_str()),
+                        (0, " "),
+                        (1, chain.id()),
+                        (0, " "),
+                        (4, count.as_str()),
+                        (0, "  "),
+                        (0, &chunk.join(" ")),
+                    ]);
+                }
+            } else if seqres {
                 if let Some(dbref) = chain.database_reference() {
                     for (index, chunk) in chain
                         .residues()
@@ -386,6 +955,185 @@ pub fn save_pdb_raw<T: Write>(pdb: &PDB, mut sink: BufWriter<T>, level: Strictne
             }
         }
     }
+    // HET, HETNAM, HETSYN, and FORMUL
+    let (heterogens, het_count) = heterogen_lines(pdb);
+    for line in &heterogens {
+        print_line(vec![(0, line)]);
+    }
+
+    // HELIX and SHEET
+    let residue = |r: &ResidueReference, space: &str| {
+        format!(
+            "{:>3} {}{space}{:>4}{}",
+            r.name,
+            r.chain_id,
+            r.serial_number,
+            r.insertion_code.as_deref().unwrap_or(" ")
+        )
+    };
+    let atom_name = |name: &str| {
+        if name.len() < 4 {
+            format!(" {name:3}")
+        } else {
+            name.to_string()
+        }
+    };
+    for helix in pdb.secondary_structure().filter_map(|s| match s {
+        SecondaryStructure::Helix(h) => Some(h),
+        SecondaryStructure::Strand(_) => None,
+    }) {
+        let line = format!(
+            "HELIX  {:>3} {:>3} {} {}{:>2}{:30} {:>5}",
+            helix.serial_number,
+            helix.id,
+            residue(&helix.start, " "),
+            residue(&helix.end, " "),
+            helix.class.number(),
+            helix.comment.as_deref().unwrap_or(""),
+            helix.length.map_or_else(String::new, |l| l.to_string()),
+        );
+        print_line(vec![(0, line.trim_end())]);
+    }
+    for strand in pdb.secondary_structure().filter_map(|s| match s {
+        SecondaryStructure::Strand(s) => Some(s),
+        SecondaryStructure::Helix(_) => None,
+    }) {
+        let mut line = format!(
+            "SHEET  {:>3} {:>3}{:>2} {} {}{:>2}",
+            strand.strand_number,
+            strand.sheet_id,
+            strand.strand_count,
+            residue(&strand.start, ""),
+            residue(&strand.end, ""),
+            strand.sense.number(),
+        );
+        if let Some(registration) = &strand.registration {
+            line += &format!(
+                " {}{} {}{}",
+                atom_name(&registration.current_atom),
+                residue(&registration.current, ""),
+                atom_name(&registration.previous_atom),
+                residue(&registration.previous, ""),
+            );
+        }
+        print_line(vec![(0, &line)]);
+    }
+
+    // SSBOND and LINK
+    let bonds = pdb.annotated_bonds();
+    let symmetry = |operator: Option<&String>| {
+        operator.map_or_else(|| "1555".to_string(), |o| o.replace('_', ""))
+    };
+    let bond_details = |(a, b, _, details): &AnnotatedBond<'_>| {
+        format!(
+            "{:>6} {:>6} {:5.2}",
+            symmetry(details.and_then(|d| d.symmetry_operator_1.as_ref())),
+            symmetry(details.and_then(|d| d.symmetry_operator_2.as_ref())),
+            details
+                .and_then(|d| d.distance)
+                .unwrap_or_else(|| a.atom().distance(b.atom())),
+        )
+    };
+    for (index, bond) in bonds
+        .iter()
+        .filter(|(_, _, bond, _)| *bond == Bond::Disulfide)
+        .enumerate()
+    {
+        let line = format!(
+            "SSBOND {:>3} {}   {}{:23}{}",
+            index + 1,
+            residue(
+                &ResidueReference::from_residue(bond.0.chain(), bond.0.residue()),
+                " "
+            ),
+            residue(
+                &ResidueReference::from_residue(bond.1.chain(), bond.1.residue()),
+                " "
+            ),
+            "",
+            bond_details(bond),
+        );
+        print_line(vec![(0, &line)]);
+    }
+    let link_atom = |h: &AtomConformerResidueChain<'_>| {
+        format!(
+            "{}{}{}",
+            atom_name(h.atom().name()),
+            h.conformer().alternative_location().unwrap_or(" "),
+            residue(&ResidueReference::from_residue(h.chain(), h.residue()), ""),
+        )
+    };
+    for bond in bonds
+        .iter()
+        .filter(|(_, _, bond, _)| bond.is_covalent() || *bond == Bond::MetalCoordination)
+    {
+        // LINKR records give the link name in place of the distance
+        let link_name = bond.3.and_then(|d| d.link_name.as_ref());
+        let line = if let Some(name) = link_name {
+            format!(
+                "LINKR       {}{:15}{}  {:>6} {:>6}{}",
+                link_atom(&bond.0),
+                "",
+                link_atom(&bond.1),
+                symmetry(bond.3.and_then(|d| d.symmetry_operator_1.as_ref())),
+                symmetry(bond.3.and_then(|d| d.symmetry_operator_2.as_ref())),
+                name,
+            )
+        } else {
+            format!(
+                "LINK        {}{:15}{}  {}",
+                link_atom(&bond.0),
+                "",
+                link_atom(&bond.1),
+                bond_details(bond),
+            )
+        };
+        print_line(vec![(0, &line)]);
+    }
+
+    // CISPEP, the model number is 0 if there is only a single model
+    for cis_peptide in pdb.cis_peptides() {
+        let line = format!(
+            "CISPEP {:>3} {}   {}       {:>3}       {}",
+            cis_peptide.serial_number,
+            residue(&cis_peptide.start, " "),
+            residue(&cis_peptide.end, " "),
+            if pdb.model_count() > 1 {
+                cis_peptide.model
+            } else {
+                0
+            },
+            cis_peptide
+                .angle
+                .map_or_else(String::new, |angle| format!("{angle:6.2}")),
+        );
+        print_line(vec![(0, line.trim_end())]);
+    }
+
+    // SITE
+    let mut num_site = 0;
+    for site in pdb.sites() {
+        for (index, chunk) in site.residues.chunks(4).enumerate() {
+            let mut line = format!(
+                "SITE   {:>3} {:>3} {:>2}",
+                index + 1,
+                site.id,
+                site.residues.len()
+            );
+            for residue in chunk {
+                line += &format!(
+                    " {:>3} {}{:>4}{}",
+                    residue.name,
+                    residue.chain_id,
+                    residue.serial_number,
+                    residue.insertion_code.as_deref().unwrap_or(" ")
+                );
+            }
+            print_line(vec![(0, line.trim_end())]);
+            num_site += 1;
+        }
+    }
+
     // Cryst
     if let Some(unit_cell) = &pdb.unit_cell {
         let sym = if let Some(symmetry) = &pdb.symmetry {
@@ -583,6 +1331,34 @@ pub fn save_pdb_raw<T: Write>(pdb: &PDB, mut sink: BufWriter<T>, level: Strictne
             print_line(vec![(0, "ENDMDL")]);
         }
     }
+
+    // CONECT records, bonds are listed for both atoms and only once for all models
+    let serial_numbers: HashMap<usize, usize> = pdb
+        .atoms()
+        .map(|a| (a.counter(), a.serial_number()))
+        .collect();
+    let mut connections: BTreeMap<usize, BTreeSet<usize>> = BTreeMap::new();
+    for (a, b, bond, _) in pdb.bond_counters() {
+        if *bond == Bond::Hydrogen {
+            continue;
+        }
+        if let (Some(&a), Some(&b)) = (serial_numbers.get(a), serial_numbers.get(b)) {
+            connections.entry(a).or_default().insert(b);
+            connections.entry(b).or_default().insert(a);
+        }
+    }
+    let mut num_connect = 0;
+    for (serial_number, partners) in &connections {
+        let serial_number = format!("{serial_number:5}");
+        let partners: Vec<String> = partners.iter().map(|p| format!("{p:5}")).collect();
+        for chunk in partners.chunks(4) {
+            let mut fields = vec![(6, "CONECT"), (5, serial_number.as_str())];
+            fields.extend(chunk.iter().map(|p| (5, p.as_str())));
+            print_line(fields);
+            num_connect += 1;
+        }
+    }
+
     if level != StrictnessLevel::Loose {
         let mut xform = 0;
         if pdb.origx.is_some() || level == StrictnessLevel::Strict {
@@ -596,17 +1372,31 @@ pub fn save_pdb_raw<T: Write>(pdb: &PDB, mut sink: BufWriter<T>, level: Strictne
         }
         print_line(vec![
             (0, "MASTER    "),
-            (5, pdb.remark_count().to_string().as_str()),
+            (5, remarks.len().to_string().as_str()),
             (5, "0"), //defined to be empty
-            (5, "0"), //numHet
-            (5, "0"), //numHelix
-            (5, "0"), //numSheet
+            (5, het_count.to_string().as_str()),
+            (
+                5,
+                pdb.secondary_structure()
+                    .filter(|s| s.is_helix())
+                    .count()
+                    .to_string()
+                    .as_str(),
+            ),
+            (
+                5,
+                pdb.secondary_structure()
+                    .filter(|s| s.is_strand())
+                    .count()
+                    .to_string()
+                    .as_str(),
+            ),
             (5, "0"), //numTurn (deprecated)
-            (5, "0"), //numSite
+            (5, num_site.to_string().as_str()),
             (5, xform.to_string().as_str()),
             (5, pdb.total_atom_count().to_string().as_str()),
             (5, pdb.model_count().to_string().as_str()),
-            (5, "0"), //numConnect
+            (5, num_connect.to_string().as_str()),
             (5, "0"), //numSeq
         ]);
     }
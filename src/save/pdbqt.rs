@@ -0,0 +1,340 @@
+#[cfg(feature = "compression")]
+use flate2::{write::GzEncoder, Compression};
+
+use crate::error::*;
+use crate::save::save_pdb_raw;
+use crate::structs::*;
+use crate::validate;
+use crate::StrictnessLevel;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::BufWriter;
+
+/// The atom types known to AutoDock and Vina, stored atom types not in this list (eg SYBYL atom
+/// types) are replaced by a type derived from the element and bonds
+const AUTODOCK_TYPES: &[&str] = &[
+    "H", "HD", "HS", "C", "A", "N", "NA", "NS", "OA", "OS", "F", "Mg", "MG", "P", "SA", "S", "Cl",
+    "CL", "Ca", "CA", "Mn", "MN", "Fe", "FE", "Zn", "ZN", "Br", "BR", "I", "Si", "B", "G", "GA",
+    "J", "Q", "Z", "W", "G0", "G1", "G2", "G3", "CG0", "CG1", "CG2", "CG3",
+];
+
+/// The aromatic ring carbons of the standard amino acids, used to find aromatic carbons in
+/// receptors without bonds
+const AROMATIC_RING_CARBONS: &[(&str, &[&str])] = &[
+    ("PHE", &["CG", "CD1", "CD2", "CE1", "CE2", "CZ"]),
+    ("TYR", &["CG", "CD1", "CD2", "CE1", "CE2", "CZ"]),
+    (
+        "TRP",
+        &["CG", "CD1", "CD2", "CE2", "CE3", "CZ2", "CZ3", "CH2"],
+    ),
+    ("HIS", &["CG", "CD2", "CE1"]),
+];
+
+/// The maximal distance (in Å) between a hydrogen and the atom it is bonded to, used to find
+/// polar hydrogens if no bonds are given
+const HYDROGEN_BOND_LENGTH: f64 = 1.3;
+
+/// Save the given PDB struct to the given file as PDBQT (.pdbqt), validating it beforehand.
+/// # Errors
+/// It validates the PDB. It fails if the validation fails with the given `level`, or if the file could not be opened.
+/// If validation gives rise to problems, use the `save_pdbqt_raw` function.
+pub fn save_pdbqt(
+    pdb: &PDB,
+    filename: impl AsRef<str>,
+    level: StrictnessLevel,
+) -> Result<(), Vec<PDBError>> {
+    save_pdbqt_(pdb, filename, level, BufWriter::new)
+}
+
+/// Save the given PDB struct to the given file as PDBQT (.pdbqt) and compresses to .gz
+/// # Errors
+/// It validates the PDB. It fails if the validation fails with the given `level`, or if the file could not be opened.
+/// If validation gives rise to problems, use the `save_pdbqt_raw` function.
+#[cfg(feature = "compression")]
+pub fn save_pdbqt_gz(
+    pdb: &PDB,
+    filename: impl AsRef<str>,
+    level: StrictnessLevel,
+    compression_level: Option<Compression>,
+) -> Result<(), Vec<PDBError>> {
+    save_pdbqt_(pdb, filename, level, |file| {
+        BufWriter::new(GzEncoder::new(file, compression_level.unwrap_or_default()))
+    })
+}
+
+/// Generic function to save the given PDB struct to the given file as PDBQT,
+/// to some writer function, e.g. a GzEncoder or BufWriter.
+fn save_pdbqt_<T, W>(
+    pdb: &PDB,
+    filename: impl AsRef<str>,
+    level: StrictnessLevel,
+    writer: W,
+) -> Result<(), Vec<PDBError>>
+where
+    T: Write,
+    W: FnOnce(File) -> BufWriter<T>,
+{
+    // Validates the PDB, and returns early if any errors are found
+    let filename = filename.as_ref();
+
+    let mut errors = validate(pdb);
+    for error in &errors {
+        if error.fails(level) {
+            return Err(errors);
+        }
+    }
+
+    // Creates a writer for the file
+    let file = match File::create(filename) {
+        Ok(f) => f,
+        Err(_e) => {
+            errors.push(PDBError::new(
+                ErrorLevel::BreakingError,
+                "Could not open file",
+                "Could not open the file for writing, make sure you have permission for this file and no other program is currently using it.",
+                Context::show(filename)
+            ));
+            return Err(errors);
+        }
+    };
+
+    save_pdbqt_raw(pdb, writer(file));
+
+    Ok(())
+}
+
+/// Save the given PDB struct to the given BufWriter as PDBQT (.pdbqt).
+/// It does not validate or renumber the PDB, so if that is needed, that needs to be done in preparation.
+///
+/// The first 66 columns of the atom lines are written as in PDB files (see [`save_pdb_raw`]),
+/// followed by the partial charge (see [`Atom::partial_charge`], zero if absent) and the AutoDock
+/// atom type (see [`Atom::atom_type`]). Atoms without an AutoDock atom type get one based on
+/// their element and bonds: aromatic carbons (`A`), hydrogen bond accepting nitrogens (`NA`),
+/// oxygens (`OA`), sulfurs (`SA`), and polar hydrogens (`HD`) are recognised. Polar hydrogens are
+/// found from the bonds, or from the closest atom in their residue if they have no bonds.
+///
+/// Multiple models are written with `MODEL` and `ENDMDL` records, like the poses written by
+/// Vina. Models with a [`TorsionTree`] are written as flexible ligand, with the free text remarks
+/// of the tree, the atoms ordered in `ROOT` and `BRANCH` records, and the `TORSDOF` record. Other
+/// models are written as rigid receptor, with a `TER` record after every chain. Other records,
+/// like the header and `CONECT` records, are not written as AutoDock does not accept these.
+#[allow(clippy::unwrap_used)]
+pub fn save_pdbqt_raw<T: Write>(pdb: &PDB, mut sink: BufWriter<T>) {
+    let mut print_line = |line: String| {
+        sink.write_all(line.as_bytes()).unwrap();
+        sink.write_all(b"\n").unwrap();
+    };
+
+    // The fixed columns of the atoms are formatted by the PDB writer, in the same order as `atoms`
+    let mut buffer = Vec::new();
+    save_pdb_raw(pdb, BufWriter::new(&mut buffer), StrictnessLevel::Loose);
+    let text = String::from_utf8(buffer).unwrap();
+    let mut pdb_lines = text
+        .lines()
+        .filter(|line| line.starts_with("ATOM  ") || line.starts_with("HETATM"));
+
+    let mut neighbours: HashMap<usize, Vec<(usize, Option<BondOrder>)>> = HashMap::new();
+    for (a, b, bond, details) in pdb.bond_counters() {
+        if bond.is_covalent() || *bond == Bond::Disulfide {
+            let order = details.as_ref().and_then(|d| d.order);
+            neighbours.entry(*a).or_default().push((*b, order));
+            neighbours.entry(*b).or_default().push((*a, order));
+        }
+    }
+
+    let multiple_models = pdb.model_count() > 1;
+    for model in pdb.models() {
+        let atoms: HashMap<usize, &Atom> = model.atoms().map(|a| (a.counter(), a)).collect();
+        let typing = AutodockTyping {
+            atoms: &atoms,
+            neighbours: &neighbours,
+        };
+        let mut lines: Vec<(usize, String)> = Vec::new();
+        let mut chain_ends = Vec::new();
+        for chain in model.chains() {
+            for residue in chain.residues() {
+                for conformer in residue.conformers() {
+                    for atom in conformer.atoms() {
+                        let atom_type = atom
+                            .atom_type()
+                            .filter(|t| AUTODOCK_TYPES.contains(t))
+                            .map_or_else(|| typing.autodock_type(atom, conformer), str::to_string);
+                        let pdb_line = pdb_lines.next().unwrap();
+                        lines.push((
+                            atom.serial_number(),
+                            format!(
+                                "{:66}    {:+6.3} {:<2}",
+                                pdb_line.get(..66).unwrap_or(pdb_line),
+                                atom.partial_charge().unwrap_or(0.0),
+                                atom_type
+                            ),
+                        ));
+                    }
+                }
+            }
+            if chain.atom_count() > 0 {
+                chain_ends.push(lines.len());
+            }
+        }
+
+        if multiple_models {
+            print_line(format!("MODEL {:>8}", model.serial_number()));
+        }
+        if let Some(tree) = pdb.torsion_tree(model.serial_number()) {
+            for remark in &tree.remarks {
+                print_line(format!("REMARK {remark}"));
+            }
+            let mut by_serial: HashMap<usize, &String> =
+                lines.iter().map(|(serial, line)| (*serial, line)).collect();
+            let mut take_atoms = |serial_numbers: &[usize], print_line: &mut dyn FnMut(String)| {
+                for serial_number in serial_numbers {
+                    if let Some(line) = by_serial.remove(serial_number) {
+                        print_line(line.clone());
+                    }
+                }
+            };
+            print_line("ROOT".to_string());
+            take_atoms(&tree.root, &mut print_line);
+            print_line("ENDROOT".to_string());
+            let mut stack: Vec<(&TorsionBranch, bool)> =
+                tree.branches.iter().rev().map(|b| (b, false)).collect();
+            while let Some((branch, closing)) = stack.pop() {
+                if closing {
+                    print_line(format!(
+                        "ENDBRANCH {:>3} {:>3}",
+                        branch.parent, branch.child
+                    ));
+                } else {
+                    print_line(format!("BRANCH {:>3} {:>3}", branch.parent, branch.child));
+                    take_atoms(&branch.atoms, &mut print_line);
+                    stack.push((branch, true));
+                    stack.extend(branch.branches.iter().rev().map(|b| (b, false)));
+                }
+            }
+            // Atoms that are not part of the tree are written after it
+            for (serial_number, line) in &lines {
+                if by_serial.contains_key(serial_number) {
+                    print_line(line.clone());
+                }
+            }
+            print_line(format!(
+                "TORSDOF {}",
+                tree.torsdof.unwrap_or_else(|| tree.branch_count())
+            ));
+        } else {
+            let mut start = 0;
+            for end in chain_ends {
+                for (_, line) in &lines[start..end] {
+                    print_line(line.clone());
+                }
+                print_line("TER".to_string());
+                start = end;
+            }
+        }
+        if multiple_models {
+            print_line("ENDMDL".to_string());
+        }
+    }
+
+    sink.flush().unwrap();
+}
+
+/// The atoms of a model with their bonded neighbours, used to derive AutoDock atom types
+struct AutodockTyping<'a> {
+    /// The atoms of the model by their counter
+    atoms: &'a HashMap<usize, &'a Atom>,
+    /// The bonded neighbours of the atoms by their counter, with the bond order if known
+    neighbours: &'a HashMap<usize, Vec<(usize, Option<BondOrder>)>>,
+}
+
+impl<'a> AutodockTyping<'a> {
+    /// The bonded neighbours of the given atom in this model, with the bond order if known
+    fn neighbours(&self, atom: &Atom) -> Vec<(&'a Atom, Option<BondOrder>)> {
+        self.neighbours
+            .get(&atom.counter())
+            .map(|neighbours| {
+                neighbours
+                    .iter()
+                    .filter_map(|(counter, order)| Some((*self.atoms.get(counter)?, *order)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Derive the AutoDock atom type of the given atom in the given conformer from its element
+    /// and bonds
+    fn autodock_type(&self, atom: &Atom, conformer: &Conformer) -> String {
+        let element = if let Some(element) = atom.element() {
+            *element
+        } else {
+            return String::new();
+        };
+        let neighbours = self.neighbours(atom);
+        let bonded_to = |element: Element| {
+            neighbours
+                .iter()
+                .any(|(n, _)| n.element() == Some(&element))
+        };
+        match element {
+            Element::C => {
+                if neighbours
+                    .iter()
+                    .any(|(_, o)| *o == Some(BondOrder::Aromatic))
+                    || (neighbours.is_empty()
+                        && !atom.hetero()
+                        && AROMATIC_RING_CARBONS.iter().any(|(residue, atoms)| {
+                            *residue == conformer.name() && atoms.contains(&atom.name())
+                        }))
+                {
+                    "A"
+                } else {
+                    "C"
+                }
+            }
+            Element::N => {
+                let amide = neighbours.iter().any(|(carbon, _)| {
+                    carbon.element() == Some(&Element::C)
+                        && self.neighbours(carbon).iter().any(|(oxygen, order)| {
+                            oxygen.element() == Some(&Element::O)
+                                && *order == Some(BondOrder::Double)
+                        })
+                });
+                if neighbours.is_empty()
+                    || neighbours.len() >= 4
+                    || bonded_to(Element::H)
+                    || amide
+                    || (!atom.hetero() && atom.name() == "N")
+                {
+                    "N"
+                } else {
+                    "NA"
+                }
+            }
+            Element::O => "OA",
+            Element::S => "SA",
+            Element::H => {
+                let polar = |a: &Atom| matches!(a.element(), Some(Element::N | Element::O));
+                let bonded = if neighbours.is_empty() {
+                    conformer
+                        .atoms()
+                        .filter(|a| a.element() != Some(&Element::H))
+                        .map(|a| (a.distance(atom), a))
+                        .filter(|(d, _)| *d < HYDROGEN_BOND_LENGTH)
+                        .min_by(|a, b| a.0.total_cmp(&b.0))
+                        .map_or(false, |(_, a)| polar(a))
+                } else {
+                    neighbours.iter().any(|(n, _)| polar(n))
+                };
+                if bonded {
+                    "HD"
+                } else {
+                    "H"
+                }
+            }
+            _ => return element.title_case_symbol(),
+        }
+        .to_string()
+    }
+}
@@ -0,0 +1,156 @@
+#[cfg(feature = "compression")]
+use flate2::{write::GzEncoder, Compression};
+
+use crate::error::*;
+use crate::structs::*;
+use crate::validate;
+use crate::StrictnessLevel;
+
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::BufWriter;
+
+/// Save the given PDB struct to the given file as PQR, validating it beforehand.
+/// Atoms without a partial charge or radius are written with a value of zero.
+/// # Errors
+/// It validates the PDB. It fails if the validation fails with the given `level`, or if the file could not be opened.
+/// If validation gives rise to problems, use the `save_pqr_raw` function.
+pub fn save_pqr(
+    pdb: &PDB,
+    filename: impl AsRef<str>,
+    level: StrictnessLevel,
+) -> Result<(), Vec<PDBError>> {
+    save_pqr_(pdb, filename, level, BufWriter::new)
+}
+
+/// Save the given PDB struct to the given file as PQR and compresses to .gz
+/// # Errors
+/// It validates the PDB. It fails if the validation fails with the given `level`, or if the file could not be opened.
+/// If validation gives rise to problems, use the `save_pqr_raw` function.
+#[cfg(feature = "compression")]
+pub fn save_pqr_gz(
+    pdb: &PDB,
+    filename: impl AsRef<str>,
+    level: StrictnessLevel,
+    compression_level: Option<Compression>,
+) -> Result<(), Vec<PDBError>> {
+    save_pqr_(pdb, filename, level, |file| {
+        BufWriter::new(GzEncoder::new(file, compression_level.unwrap_or_default()))
+    })
+}
+
+/// Generic function to save the given PDB struct to the given file as PQR,
+/// to some writer function, e.g. a GzEncoder or BufWriter.
+fn save_pqr_<T, W>(
+    pdb: &PDB,
+    filename: impl AsRef<str>,
+    level: StrictnessLevel,
+    writer: W,
+) -> Result<(), Vec<PDBError>>
+where
+    T: Write,
+    W: FnOnce(File) -> BufWriter<T>,
+{
+    // Validates the PDB, and returns early if any errors are found
+    let filename = filename.as_ref();
+
+    let mut errors = validate(pdb);
+    for error in &errors {
+        if error.fails(level) {
+            return Err(errors);
+        }
+    }
+
+    // Creates a writer for the file
+    let file = match File::create(filename) {
+        Ok(f) => f,
+        Err(_e) => {
+            errors.push(PDBError::new(
+                ErrorLevel::BreakingError,
+                "Could not open file",
+                "Could not open the file for writing, make sure you have permission for this file and no other program is currently using it.",
+                Context::show(filename)
+            ));
+            return Err(errors);
+        }
+    };
+
+    save_pqr_raw(pdb, writer(file));
+
+    Ok(())
+}
+
+/// Save the given PDB struct to the given BufWriter as PQR.
+/// It does not validate or renumber the PDB, so if that is needed, that needs to be done in preparation.
+///
+/// The atoms are written in the column layout of PDB files where possible, but every field is
+/// separated by whitespace so that long serial numbers, chain identifiers or residue names do not
+/// run into each other. The occupancy, B factor, and element are replaced by the partial charge
+/// and radius, atoms without these are written with a value of zero. Alternative locations
+/// cannot be given in PQR files, so all conformers are written without them.
+#[allow(clippy::unwrap_used)]
+pub fn save_pqr_raw<T: Write>(pdb: &PDB, mut sink: BufWriter<T>) {
+    let mut print_line = |line: String| {
+        sink.write_all(line.trim_end().as_bytes()).unwrap();
+        sink.write_all(b"\n").unwrap();
+    };
+
+    let multiple_models = pdb.model_count() > 1;
+    for model in pdb.models() {
+        if multiple_models {
+            print_line(format!("MODEL {:>8}", model.serial_number()));
+        }
+        for chain in model.chains().filter(|c| c.atoms().next().is_some()) {
+            // A chain identifier is left out if it is empty, the reader then uses the TER records
+            let chain_id = if chain.id().is_empty() {
+                String::new()
+            } else {
+                format!("{} ", chain.id())
+            };
+            let mut last = None;
+            for residue in chain.residues() {
+                let residue_number = format!(
+                    "{}{}",
+                    residue.serial_number(),
+                    residue.insertion_code().unwrap_or_default()
+                );
+                for conformer in residue.conformers() {
+                    for atom in conformer.atoms() {
+                        // Atom names are aligned like in PDB files, with the element in the second column
+                        let name = if atom.name().len() < 4 {
+                            format!(" {}", atom.name())
+                        } else {
+                            atom.name().to_string()
+                        };
+                        let (x, y, z) = atom.pos();
+                        print_line(format!(
+                            "{:6}{:>5} {:4} {:>3} {chain_id}{residue_number:>3}    {x:8.3} {y:8.3} {z:8.3} {:7.4} {:6.4}",
+                            if atom.hetero() { "HETATM" } else { "ATOM" },
+                            atom.serial_number(),
+                            name,
+                            conformer.name(),
+                            atom.partial_charge().unwrap_or(0.0),
+                            atom.radius().unwrap_or(0.0),
+                        ));
+                        last = Some((
+                            atom.serial_number(),
+                            conformer.name(),
+                            residue_number.clone(),
+                        ));
+                    }
+                }
+            }
+            if let Some((serial_number, name, residue_number)) = last {
+                print_line(format!(
+                    "TER   {serial_number:>5}      {name:>3} {chain_id}{residue_number:>3}"
+                ));
+            }
+        }
+        if multiple_models {
+            print_line("ENDMDL".to_string());
+        }
+    }
+    print_line("END".to_string());
+
+    sink.flush().unwrap();
+}
@@ -0,0 +1,213 @@
+#[cfg(feature = "compression")]
+use flate2::{write::GzEncoder, Compression};
+
+use crate::error::*;
+use crate::structs::*;
+use crate::validate;
+use crate::StrictnessLevel;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::BufWriter;
+
+/// The maximal number of atoms or bonds that can be given in a V2000 connection table
+const V2000_LIMIT: usize = 999;
+
+/// Save the conformers matching the given search to the given file as SDF (.sdf), validating the
+/// PDB beforehand.
+/// # Errors
+/// It validates the PDB. It fails if the validation fails with the given `level`, or if the file could not be opened.
+/// If validation gives rise to problems, use the `save_sdf_raw` function.
+pub fn save_sdf(
+    pdb: &PDB,
+    selection: Search,
+    filename: impl AsRef<str>,
+    level: StrictnessLevel,
+) -> Result<(), Vec<PDBError>> {
+    save_sdf_(pdb, selection, filename, level, BufWriter::new)
+}
+
+/// Save the conformers matching the given search to the given file as SDF (.sdf) and compresses
+/// to .gz
+/// # Errors
+/// It validates the PDB. It fails if the validation fails with the given `level`, or if the file could not be opened.
+/// If validation gives rise to problems, use the `save_sdf_raw` function.
+#[cfg(feature = "compression")]
+pub fn save_sdf_gz(
+    pdb: &PDB,
+    selection: Search,
+    filename: impl AsRef<str>,
+    level: StrictnessLevel,
+    compression_level: Option<Compression>,
+) -> Result<(), Vec<PDBError>> {
+    save_sdf_(pdb, selection, filename, level, |file| {
+        BufWriter::new(GzEncoder::new(file, compression_level.unwrap_or_default()))
+    })
+}
+
+/// Generic function to save the given PDB struct to the given file as SDF,
+/// to some writer function, e.g. a GzEncoder or BufWriter.
+fn save_sdf_<T, W>(
+    pdb: &PDB,
+    selection: Search,
+    filename: impl AsRef<str>,
+    level: StrictnessLevel,
+    writer: W,
+) -> Result<(), Vec<PDBError>>
+where
+    T: Write,
+    W: FnOnce(File) -> BufWriter<T>,
+{
+    // Validates the PDB, and returns early if any errors are found
+    let filename = filename.as_ref();
+
+    let mut errors = validate(pdb);
+    for error in &errors {
+        if error.fails(level) {
+            return Err(errors);
+        }
+    }
+
+    // Creates a writer for the file
+    let file = match File::create(filename) {
+        Ok(f) => f,
+        Err(_e) => {
+            errors.push(PDBError::new(
+                ErrorLevel::BreakingError,
+                "Could not open file",
+                "Could not open the file for writing, make sure you have permission for this file and no other program is currently using it.",
+                Context::show(filename)
+            ));
+            return Err(errors);
+        }
+    };
+
+    save_sdf_raw(pdb, selection, writer(file));
+
+    Ok(())
+}
+
+/// Save the conformers matching the given search to the given BufWriter as SDF (.sdf).
+/// It does not validate or renumber the PDB, so if that is needed, that needs to be done in preparation.
+///
+/// Every conformer with atoms matching the search (eg `Search::Single(Term::ConformerName("ATP".to_owned()))`,
+/// or `Search::Known(true)` for all conformers) is written as a separate record, containing the
+/// matching atoms of that conformer. The record is named after the conformer. The covalent and
+/// disulfide bonds between the atoms in a record are written with their bond order (see
+/// [`BondDetails::order`]), or as single bonds if the order is not known. Records with more than
+/// 999 atoms or bonds are written as V3000 connection tables, the other records as V2000
+/// connection tables.
+#[allow(clippy::unwrap_used)]
+pub fn save_sdf_raw<T: Write>(pdb: &PDB, selection: Search, mut sink: BufWriter<T>) {
+    let mut print_line = |line: String| {
+        sink.write_all(line.as_bytes()).unwrap();
+        sink.write_all(b"\n").unwrap();
+    };
+    let bonds: Vec<(usize, usize, usize)> = pdb
+        .bond_counters()
+        .filter(|(_, _, bond, _)| bond.is_covalent() || *bond == Bond::Disulfide)
+        .map(|(a, b, _, details)| {
+            let order = details.as_ref().and_then(|d| d.order);
+            (*a, *b, order.map_or(1, |o| o.mdl_bond_type()))
+        })
+        .collect();
+
+    // Consecutive atoms from the same conformer form a record
+    let hierarchies: Vec<_> = pdb.find(selection).collect();
+    let mut records: Vec<(&Conformer, Vec<&Atom>)> = Vec::new();
+    for hierarchy in &hierarchies {
+        match records.last_mut() {
+            Some((conformer, atoms)) if std::ptr::eq(*conformer, hierarchy.conformer()) => {
+                atoms.push(hierarchy.atom());
+            }
+            _ => records.push((hierarchy.conformer(), vec![hierarchy.atom()])),
+        }
+    }
+
+    for (conformer, atoms) in records {
+        let indices: HashMap<usize, usize> = atoms
+            .iter()
+            .enumerate()
+            .map(|(index, atom)| (atom.counter(), index + 1))
+            .collect();
+        let record_bonds: Vec<(usize, usize, usize)> = bonds
+            .iter()
+            .filter_map(|(a, b, bond_type)| Some((*indices.get(a)?, *indices.get(b)?, *bond_type)))
+            .collect();
+        let symbol = |atom: &Atom| {
+            atom.element()
+                .map_or("*".to_string(), Element::title_case_symbol)
+        };
+
+        print_line(conformer.name().to_string());
+        print_line(format!("  {:<8}{:10}3D", "pdbtbx", ""));
+        print_line(String::new());
+        if atoms.len() > V2000_LIMIT || record_bonds.len() > V2000_LIMIT {
+            print_line("  0  0  0     0  0            999 V3000".to_string());
+            print_line("M  V30 BEGIN CTAB".to_string());
+            print_line(format!(
+                "M  V30 COUNTS {} {} 0 0 0",
+                atoms.len(),
+                record_bonds.len()
+            ));
+            print_line("M  V30 BEGIN ATOM".to_string());
+            for (index, atom) in atoms.iter().enumerate() {
+                let (x, y, z) = atom.pos();
+                let mut line = format!(
+                    "M  V30 {} {} {:.4} {:.4} {:.4} 0",
+                    index + 1,
+                    symbol(atom),
+                    x,
+                    y,
+                    z
+                );
+                if atom.charge() != 0 {
+                    line += &format!(" CHG={}", atom.charge());
+                }
+                print_line(line);
+            }
+            print_line("M  V30 END ATOM".to_string());
+            print_line("M  V30 BEGIN BOND".to_string());
+            for (index, (a, b, bond_type)) in record_bonds.iter().enumerate() {
+                print_line(format!("M  V30 {} {bond_type} {a} {b}", index + 1));
+            }
+            print_line("M  V30 END BOND".to_string());
+            print_line("M  V30 END CTAB".to_string());
+        } else {
+            print_line(format!(
+                "{:>3}{:>3}  0  0  0  0  0  0  0  0999 V2000",
+                atoms.len(),
+                record_bonds.len()
+            ));
+            for atom in &atoms {
+                let (x, y, z) = atom.pos();
+                print_line(format!(
+                    "{x:10.4}{y:10.4}{z:10.4} {:<3} 0  0  0  0  0  0  0  0  0  0  0  0",
+                    symbol(atom)
+                ));
+            }
+            for (a, b, bond_type) in &record_bonds {
+                print_line(format!("{a:>3}{b:>3}{bond_type:>3}  0"));
+            }
+            // The charges are given in the properties block, with at most eight per line
+            let charges: Vec<(usize, isize)> = atoms
+                .iter()
+                .enumerate()
+                .filter(|(_, atom)| atom.charge() != 0)
+                .map(|(index, atom)| (index + 1, atom.charge()))
+                .collect();
+            for chunk in charges.chunks(8) {
+                let mut line = format!("M  CHG{:>3}", chunk.len());
+                for (index, charge) in chunk {
+                    line += &format!(" {index:>3} {charge:>3}");
+                }
+                print_line(line);
+            }
+        }
+        print_line("M  END".to_string());
+        print_line("$$$$".to_string());
+    }
+
+    sink.flush().unwrap();
+}
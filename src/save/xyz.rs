@@ -0,0 +1,139 @@
+#[cfg(feature = "compression")]
+use flate2::{write::GzEncoder, Compression};
+
+use crate::error::*;
+use crate::structs::*;
+use crate::validate;
+use crate::StrictnessLevel;
+
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::BufWriter;
+
+/// The columns written for every atom, in the extended XYZ notation
+const PROPERTIES: &str =
+    "species:S:1:pos:R:3:name:S:1:resname:S:1:resid:I:1:chain:S:1:charge:R:1:bfactor:R:1:occupancy:R:1";
+
+/// Save the given PDB struct to the given file as extended XYZ (.xyz), validating it beforehand.
+/// # Errors
+/// It validates the PDB. It fails if the validation fails with the given `level`, or if the file could not be opened.
+/// If validation gives rise to problems, use the `save_xyz_raw` function.
+pub fn save_xyz(
+    pdb: &PDB,
+    filename: impl AsRef<str>,
+    level: StrictnessLevel,
+) -> Result<(), Vec<PDBError>> {
+    save_xyz_(pdb, filename, level, BufWriter::new)
+}
+
+/// Save the given PDB struct to the given file as extended XYZ (.xyz) and compresses to .gz
+/// # Errors
+/// It validates the PDB. It fails if the validation fails with the given `level`, or if the file could not be opened.
+/// If validation gives rise to problems, use the `save_xyz_raw` function.
+#[cfg(feature = "compression")]
+pub fn save_xyz_gz(
+    pdb: &PDB,
+    filename: impl AsRef<str>,
+    level: StrictnessLevel,
+    compression_level: Option<Compression>,
+) -> Result<(), Vec<PDBError>> {
+    save_xyz_(pdb, filename, level, |file| {
+        BufWriter::new(GzEncoder::new(file, compression_level.unwrap_or_default()))
+    })
+}
+
+/// Generic function to save the given PDB struct to the given file as extended XYZ,
+/// to some writer function, e.g. a GzEncoder or BufWriter.
+fn save_xyz_<T, W>(
+    pdb: &PDB,
+    filename: impl AsRef<str>,
+    level: StrictnessLevel,
+    writer: W,
+) -> Result<(), Vec<PDBError>>
+where
+    T: Write,
+    W: FnOnce(File) -> BufWriter<T>,
+{
+    // Validates the PDB, and returns early if any errors are found
+    let filename = filename.as_ref();
+
+    let mut errors = validate(pdb);
+    for error in &errors {
+        if error.fails(level) {
+            return Err(errors);
+        }
+    }
+
+    // Creates a writer for the file
+    let file = match File::create(filename) {
+        Ok(f) => f,
+        Err(_e) => {
+            errors.push(PDBError::new(
+                ErrorLevel::BreakingError,
+                "Could not open file",
+                "Could not open the file for writing, make sure you have permission for this file and no other program is currently using it.",
+                Context::show(filename)
+            ));
+            return Err(errors);
+        }
+    };
+
+    save_xyz_raw(pdb, writer(file));
+
+    Ok(())
+}
+
+/// Save the given PDB struct to the given BufWriter as extended XYZ (.xyz).
+/// It does not validate or renumber the PDB, so if that is needed, that needs to be done in preparation.
+///
+/// Every model is written as a separate frame. The first four columns of every atom line are the
+/// element symbol (`X` if the element is unknown) and the coordinates (Å), so the file can also be
+/// read as a plain XYZ file. The comment line gives the lattice vectors of the unit cell (if
+/// there is one) and the other columns: the atom name, residue name, residue number, chain, charge,
+/// B factor, and occupancy. The charge is the partial charge, or the formal charge if the atom has
+/// no partial charge. The insertion codes and alternative locations are lost.
+#[allow(clippy::unwrap_used, clippy::cast_precision_loss)]
+pub fn save_xyz_raw<T: Write>(pdb: &PDB, mut sink: BufWriter<T>) {
+    let mut print_line = |line: String| {
+        sink.write_all(line.as_bytes()).unwrap();
+        sink.write_all(b"\n").unwrap();
+    };
+    let mut comment = String::new();
+    if let Some(unit_cell) = &pdb.unit_cell {
+        let vectors = unit_cell
+            .vectors()
+            .iter()
+            .flatten()
+            .map(|n| format!("{n:.5}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        comment += &format!("Lattice=\"{vectors}\" pbc=\"T T T\" ");
+    }
+    comment += &format!("Properties={PROPERTIES}");
+
+    for model in pdb.models() {
+        print_line(model.atom_count().to_string());
+        print_line(comment.clone());
+        for hierarchy in model.atoms_with_hierarchy() {
+            let (atom, residue) = (hierarchy.atom(), hierarchy.residue());
+            let (x, y, z) = atom.pos();
+            print_line(format!(
+                "{:<2} {:12.6} {:12.6} {:12.6} {:<4} {:<4} {:>5} {} {:8.4} {:6.2} {:4.2}",
+                atom.element()
+                    .map_or("X".to_string(), Element::title_case_symbol),
+                x,
+                y,
+                z,
+                atom.name(),
+                hierarchy.conformer().name(),
+                residue.serial_number(),
+                hierarchy.chain().id(),
+                atom.partial_charge().unwrap_or(atom.charge() as f64),
+                atom.b_factor(),
+                atom.occupancy(),
+            ));
+        }
+    }
+
+    sink.flush().unwrap();
+}
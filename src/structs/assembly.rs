@@ -0,0 +1,124 @@
+use crate::transformation::TransformationMatrix;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+/// A biological assembly (biomolecule), as given in REMARK 350 of a PDB file or the
+/// `_pdbx_struct_assembly`, `_pdbx_struct_assembly_gen`, and `_pdbx_struct_oper_list` categories
+/// of an mmCIF file. The assembly is generated by applying the operators of each generator to the
+/// chains of that generator, see [`crate::PDB::build_assembly`].
+pub struct Assembly {
+    /// The identifier of the assembly, eg '1'
+    pub id: String,
+    /// How the assembly was determined, eg 'author_defined_assembly' or 'software_defined_assembly'
+    pub details: Option<String>,
+    /// The oligomeric state of the assembly, eg '24-meric' or 'dimeric'
+    pub oligomeric_details: Option<String>,
+    /// The number of chains in the assembly
+    pub oligomeric_count: Option<usize>,
+    /// The operators with the chains they apply to
+    pub generators: Vec<AssemblyGenerator>,
+}
+
+impl Assembly {
+    /// Create a new Assembly with the given identifier and without any generators
+    #[must_use]
+    pub fn new(id: impl Into<String>) -> Self {
+        Assembly {
+            id: id.into(),
+            details: None,
+            oligomeric_details: None,
+            oligomeric_count: None,
+            generators: Vec::new(),
+        }
+    }
+
+    /// Get all chain ids used in this assembly, without duplicates
+    pub fn chains(&self) -> Vec<&str> {
+        let mut chains = Vec::new();
+        for chain in self.generators.iter().flat_map(|g| g.chains.iter()) {
+            if !chains.contains(&chain.as_str()) {
+                chains.push(chain.as_str());
+            }
+        }
+        chains
+    }
+
+    /// Get the number of chain copies making up this assembly
+    pub fn copy_count(&self) -> usize {
+        self.generators
+            .iter()
+            .map(|g| g.chains.len() * g.operators.len())
+            .sum()
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+/// A set of operators that have to be applied to a set of chains to generate (part of) an assembly
+pub struct AssemblyGenerator {
+    /// The ids of the chains the operators apply to
+    pub chains: Vec<String>,
+    /// The operators, each generating a copy of all chains
+    pub operators: Vec<AssemblyOperator>,
+}
+
+impl AssemblyGenerator {
+    /// Create a new AssemblyGenerator with the given chains and operators
+    #[must_use]
+    pub fn new(chains: Vec<String>, operators: Vec<AssemblyOperator>) -> Self {
+        AssemblyGenerator { chains, operators }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+/// An operator used to generate a copy of chains in an assembly (a BIOMT transformation)
+pub struct AssemblyOperator {
+    /// The identifier of the operator, the serial number of the BIOMT records in PDB files or the
+    /// id in the `_pdbx_struct_oper_list` in mmCIF files. Products of operators as used in mmCIF
+    /// are named by joining the ids with 'x', eg '1x61'.
+    pub id: String,
+    /// The transformation
+    pub transformation: TransformationMatrix,
+}
+
+impl AssemblyOperator {
+    /// Create a new AssemblyOperator with the given id and transformation
+    #[must_use]
+    pub fn new(id: impl Into<String>, transformation: TransformationMatrix) -> Self {
+        AssemblyOperator {
+            id: id.into(),
+            transformation,
+        }
+    }
+
+    /// Determine if this operator does not change the position of atoms
+    pub fn is_identity(&self) -> bool {
+        self.transformation == TransformationMatrix::identity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chains_and_copies() {
+        let mut assembly = Assembly::new("1");
+        let operators = vec![
+            AssemblyOperator::new("1", TransformationMatrix::identity()),
+            AssemblyOperator::new("2", TransformationMatrix::translation(1.0, 0.0, 0.0)),
+        ];
+        assembly.generators.push(AssemblyGenerator::new(
+            vec!["A".to_string(), "B".to_string()],
+            operators.clone(),
+        ));
+        assembly
+            .generators
+            .push(AssemblyGenerator::new(vec!["B".to_string()], operators));
+        assert_eq!(assembly.chains(), vec!["A", "B"]);
+        assert_eq!(assembly.copy_count(), 6);
+        assert!(assembly.generators[0].operators[0].is_identity());
+        assert!(!assembly.generators[0].operators[1].is_identity());
+    }
+}
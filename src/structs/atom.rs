@@ -39,6 +39,14 @@ pub struct Atom {
     charge: isize,
     /// The anisotropic temperature factors, if applicable
     atf: Option<[[f64; 3]; 3]>,
+    /// The partial charge (in elementary charges), if applicable
+    partial_charge: Option<f64>,
+    /// The radius (Å), if applicable
+    radius: Option<f64>,
+    /// The velocity (Å/ps), if applicable
+    velocity: Option<(f64, f64, f64)>,
+    /// The force field or docking atom type, if applicable
+    atom_type: Option<String>,
 }
 
 impl Atom {
@@ -99,6 +107,10 @@ impl Atom {
                 element,
                 charge,
                 atf: None,
+                partial_charge: None,
+                radius: None,
+                velocity: None,
+                atom_type: None,
             })
         } else {
             None
@@ -378,6 +390,98 @@ impl Atom {
         self.atf = Some(factors);
     }
 
+    /// Get the partial charge of the atom in elementary charges, if available.
+    /// This is used in electrostatics calculations and given in PQR files.
+    pub const fn partial_charge(&self) -> Option<f64> {
+        self.partial_charge
+    }
+
+    /// Set the partial charge of the atom in elementary charges.
+    /// # Errors
+    /// It fails if `new_charge` is not finite (`f64.is_finite()`).
+    pub fn set_partial_charge(&mut self, new_charge: f64) -> Result<(), String> {
+        if new_charge.is_finite() {
+            self.partial_charge = Some(new_charge);
+            Ok(())
+        } else {
+            Err(format!(
+                "The value of the new partial charge is not finite for atom {} value {}",
+                self.serial_number, new_charge
+            ))
+        }
+    }
+
+    /// Get the radius of the atom (Å), if available.
+    /// This is used in electrostatics calculations and given in PQR files.
+    pub const fn radius(&self) -> Option<f64> {
+        self.radius
+    }
+
+    /// Set the radius of the atom (Å).
+    /// # Errors
+    /// It fails if `new_radius` is not finite (`f64.is_finite()`) or if it is negative.
+    pub fn set_radius(&mut self, new_radius: f64) -> Result<(), String> {
+        if new_radius.is_finite() {
+            if new_radius >= 0.0 {
+                self.radius = Some(new_radius);
+                Ok(())
+            } else {
+                Err(format!(
+                    "The value of the new radius is negative for atom {} value {}",
+                    self.serial_number, new_radius
+                ))
+            }
+        } else {
+            Err(format!(
+                "The value of the new radius is not finite for atom {} value {}",
+                self.serial_number, new_radius
+            ))
+        }
+    }
+
+    /// Get the velocity of the atom (Å/ps), if available.
+    /// This is given in the results of molecular dynamics simulations, eg in GROMACS files.
+    pub const fn velocity(&self) -> Option<(f64, f64, f64)> {
+        self.velocity
+    }
+
+    /// Set the velocity of the atom (Å/ps).
+    /// # Errors
+    /// It fails if one or more of the numbers are not finite (`f64.is_finite()`).
+    pub fn set_velocity(&mut self, new_velocity: (f64, f64, f64)) -> Result<(), String> {
+        if new_velocity.0.is_finite() && new_velocity.1.is_finite() && new_velocity.2.is_finite() {
+            self.velocity = Some(new_velocity);
+            Ok(())
+        } else {
+            Err(format!(
+                "One or more values of the new velocity are not finite for atom {} values {:?}",
+                self.serial_number, new_velocity
+            ))
+        }
+    }
+
+    /// Get the atom type of the atom, if available.
+    /// This is the force field or docking atom type, eg the SYBYL atom type (`C.ar`) in MOL2 files.
+    pub fn atom_type(&self) -> Option<&str> {
+        self.atom_type.as_deref()
+    }
+
+    /// Set the atom type of the atom.
+    /// # Errors
+    /// It fails if `new_type` contains invalid characters.
+    pub fn set_atom_type(&mut self, new_type: impl Into<String>) -> Result<(), String> {
+        let new_type = new_type.into();
+        if valid_identifier(&new_type) {
+            self.atom_type = Some(new_type);
+            Ok(())
+        } else {
+            Err(format!(
+                "New atom type has invalid characters for atom {} type {}",
+                self.serial_number, new_type
+            ))
+        }
+    }
+
     /// Determine whether this atom is likely to be a part of the backbone of a protein.
     /// This is based on this Atom only, for a more precise definition use [`hierarchy::ContainsAtomConformer::is_backbone`].
     pub fn is_backbone(&self) -> bool {
@@ -642,6 +746,10 @@ impl Clone for Atom {
         )
         .expect("Invalid Atom properties in a clone");
         atom.atf = self.atf;
+        atom.partial_charge = self.partial_charge;
+        atom.radius = self.radius;
+        atom.velocity = self.velocity;
+        atom.atom_type = self.atom_type.clone();
         atom
     }
 }
@@ -657,6 +765,10 @@ impl PartialEq for Atom {
             && self.pos() == other.pos()
             && self.occupancy == other.occupancy
             && self.b_factor == other.b_factor
+            && self.partial_charge == other.partial_charge
+            && self.radius == other.radius
+            && self.velocity == other.velocity
+            && self.atom_type == other.atom_type
     }
 }
 
@@ -816,6 +928,23 @@ mod tests {
         a.set_charge(-1);
         assert_eq!(a.charge(), -1);
         assert_eq!(a.pdb_charge(), "1-".to_string());
+        assert_eq!(a.partial_charge(), None);
+        assert!(a.set_partial_charge(-0.25).is_ok());
+        assert_eq!(a.partial_charge(), Some(-0.25));
+        assert!(a.set_partial_charge(f64::NAN).is_err());
+        assert_eq!(a.radius(), None);
+        assert!(a.set_radius(1.8).is_ok());
+        assert_eq!(a.radius(), Some(1.8));
+        assert!(a.set_radius(-1.0).is_err());
+        assert_eq!(a.clone().radius(), Some(1.8));
+        assert_eq!(a.velocity(), None);
+        assert!(a.set_velocity((1.0, -2.0, 0.5)).is_ok());
+        assert_eq!(a.velocity(), Some((1.0, -2.0, 0.5)));
+        assert!(a.set_velocity((f64::INFINITY, 0.0, 0.0)).is_err());
+        assert_eq!(a.atom_type(), None);
+        assert!(a.set_atom_type("C.ar").is_ok());
+        assert_eq!(a.clone().atom_type(), Some("C.ar"));
+        assert!(a.set_atom_type("C\u{e9}").is_err());
     }
 
     #[test]
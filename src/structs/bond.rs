@@ -1,4 +1,4 @@
-/// Bond types between two atoms
+/// Bond types between two atoms, these follow the connection types as used in the mmCIF `_struct_conn.conn_type_id`
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Bond {
@@ -8,18 +8,224 @@ pub enum Bond {
     Disulfide,
     /// A hydrogen bond H-H
     Hydrogen,
-    /// ?
+    /// A metal coordination bond
     MetalCoordination,
-    /// ?
+    /// A mismatched base pair
     MisMatchedBasePairs,
-    /// ?
+    /// A salt bridge
     SaltBridge,
-    /// ?
+    /// A covalent residue modification
     CovalentModificationResidue,
-    /// ?
+    /// A covalent modification of a nucleotide base
     CovalentModificationNucleotideBase,
-    /// ?
+    /// A covalent modification of a nucleotide sugar
     CovalentModificationNucleotideSugar,
-    /// ?
+    /// A covalent modification of a nucleotide phosphate
     CovalentModificationNucleotidePhosphate,
 }
+
+impl Bond {
+    /// Get the bond type from the mmCIF connection type (`_struct_conn.conn_type_id`), eg 'covale' or 'metalc'
+    pub fn from_conn_type(conn_type: impl AsRef<str>) -> Option<Self> {
+        match conn_type.as_ref().to_ascii_lowercase().as_str() {
+            "covale" => Some(Bond::Covalent),
+            "disulf" => Some(Bond::Disulfide),
+            "hydrog" => Some(Bond::Hydrogen),
+            "metalc" => Some(Bond::MetalCoordination),
+            "mismat" => Some(Bond::MisMatchedBasePairs),
+            "saltbr" => Some(Bond::SaltBridge),
+            "modres" => Some(Bond::CovalentModificationResidue),
+            "covale_base" => Some(Bond::CovalentModificationNucleotideBase),
+            "covale_sugar" => Some(Bond::CovalentModificationNucleotideSugar),
+            "covale_phosphate" => Some(Bond::CovalentModificationNucleotidePhosphate),
+            _ => None,
+        }
+    }
+
+    /// Get the mmCIF connection type (`_struct_conn.conn_type_id`) for this bond type
+    pub const fn conn_type(&self) -> &'static str {
+        match self {
+            Bond::Covalent => "covale",
+            Bond::Disulfide => "disulf",
+            Bond::Hydrogen => "hydrog",
+            Bond::MetalCoordination => "metalc",
+            Bond::MisMatchedBasePairs => "mismat",
+            Bond::SaltBridge => "saltbr",
+            Bond::CovalentModificationResidue => "modres",
+            Bond::CovalentModificationNucleotideBase => "covale_base",
+            Bond::CovalentModificationNucleotideSugar => "covale_sugar",
+            Bond::CovalentModificationNucleotidePhosphate => "covale_phosphate",
+        }
+    }
+
+    /// Determine if this bond is a covalent bond, including the covalent modifications but not disulfide bonds
+    pub const fn is_covalent(&self) -> bool {
+        matches!(
+            self,
+            Bond::Covalent
+                | Bond::CovalentModificationResidue
+                | Bond::CovalentModificationNucleotideBase
+                | Bond::CovalentModificationNucleotideSugar
+                | Bond::CovalentModificationNucleotidePhosphate
+        )
+    }
+}
+
+/// The order of a covalent bond, as given in files describing the chemistry of small molecules
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BondOrder {
+    /// A single bond
+    Single,
+    /// A double bond
+    Double,
+    /// A triple bond
+    Triple,
+    /// An aromatic bond
+    Aromatic,
+}
+
+impl BondOrder {
+    /// Get the bond order from the bond type in MDL (SDF/MOL) files, eg 2 for a double bond
+    pub const fn from_mdl_bond_type(bond_type: usize) -> Option<Self> {
+        match bond_type {
+            1 => Some(BondOrder::Single),
+            2 => Some(BondOrder::Double),
+            3 => Some(BondOrder::Triple),
+            4 => Some(BondOrder::Aromatic),
+            _ => None,
+        }
+    }
+
+    /// Get the bond type in MDL (SDF/MOL) files for this bond order
+    pub const fn mdl_bond_type(&self) -> usize {
+        match self {
+            BondOrder::Single => 1,
+            BondOrder::Double => 2,
+            BondOrder::Triple => 3,
+            BondOrder::Aromatic => 4,
+        }
+    }
+
+    /// Get the bond order from the SYBYL bond type in MOL2 files, eg 'ar' for an aromatic bond.
+    /// Amide bonds ('am') are seen as single bonds.
+    pub fn from_sybyl_bond_type(bond_type: impl AsRef<str>) -> Option<Self> {
+        match bond_type.as_ref().to_ascii_lowercase().as_str() {
+            "1" | "am" => Some(BondOrder::Single),
+            "2" => Some(BondOrder::Double),
+            "3" => Some(BondOrder::Triple),
+            "ar" => Some(BondOrder::Aromatic),
+            _ => None,
+        }
+    }
+
+    /// Get the SYBYL bond type in MOL2 files for this bond order
+    pub const fn sybyl_bond_type(&self) -> &'static str {
+        match self {
+            BondOrder::Single => "1",
+            BondOrder::Double => "2",
+            BondOrder::Triple => "3",
+            BondOrder::Aromatic => "ar",
+        }
+    }
+}
+
+/// Additional information on a bond, as given in LINK and SSBOND records or the `_struct_conn` mmCIF category
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct BondDetails {
+    /// The identifier of the connection, eg 'covale1' in mmCIF
+    pub id: Option<String>,
+    /// The symmetry operator to apply to the first atom, in mmCIF notation eg '1_555'
+    pub symmetry_operator_1: Option<String>,
+    /// The symmetry operator to apply to the second atom, in mmCIF notation eg '1_555'
+    pub symmetry_operator_2: Option<String>,
+    /// The distance between the atoms in Å, as given in the file
+    pub distance: Option<f64>,
+    /// The order of the bond, as given in SDF/MOL and MOL2 files
+    pub order: Option<BondOrder>,
+    /// The name of the link, as given in the LINKR records written by REFMAC
+    pub link_name: Option<String>,
+}
+
+impl BondDetails {
+    /// If these details contain anything given in the header of a file (LINK, LINKR, and SSBOND
+    /// records or the `_struct_conn` mmCIF category), the bond order alone is not such a detail
+    pub(crate) const fn is_annotation(&self) -> bool {
+        self.id.is_some()
+            || self.symmetry_operator_1.is_some()
+            || self.symmetry_operator_2.is_some()
+            || self.distance.is_some()
+            || self.link_name.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conn_type() {
+        for bond in [
+            Bond::Covalent,
+            Bond::Disulfide,
+            Bond::Hydrogen,
+            Bond::MetalCoordination,
+            Bond::MisMatchedBasePairs,
+            Bond::SaltBridge,
+            Bond::CovalentModificationResidue,
+            Bond::CovalentModificationNucleotideBase,
+            Bond::CovalentModificationNucleotideSugar,
+            Bond::CovalentModificationNucleotidePhosphate,
+        ] {
+            assert_eq!(Bond::from_conn_type(bond.conn_type()), Some(bond));
+        }
+        assert_eq!(
+            Bond::from_conn_type("METALC"),
+            Some(Bond::MetalCoordination)
+        );
+        assert_eq!(Bond::from_conn_type("unknown"), None);
+        assert!(Bond::CovalentModificationResidue.is_covalent());
+        assert!(!Bond::Disulfide.is_covalent());
+    }
+
+    #[test]
+    fn mdl_bond_type() {
+        for order in [
+            BondOrder::Single,
+            BondOrder::Double,
+            BondOrder::Triple,
+            BondOrder::Aromatic,
+        ] {
+            assert_eq!(
+                BondOrder::from_mdl_bond_type(order.mdl_bond_type()),
+                Some(order)
+            );
+        }
+        assert_eq!(BondOrder::from_mdl_bond_type(8), None);
+    }
+
+    #[test]
+    fn sybyl_bond_type() {
+        for order in [
+            BondOrder::Single,
+            BondOrder::Double,
+            BondOrder::Triple,
+            BondOrder::Aromatic,
+        ] {
+            assert_eq!(
+                BondOrder::from_sybyl_bond_type(order.sybyl_bond_type()),
+                Some(order)
+            );
+        }
+        assert_eq!(
+            BondOrder::from_sybyl_bond_type("am"),
+            Some(BondOrder::Single)
+        );
+        assert_eq!(
+            BondOrder::from_sybyl_bond_type("AR"),
+            Some(BondOrder::Aromatic)
+        );
+        assert_eq!(BondOrder::from_sybyl_bond_type("nc"), None);
+    }
+}
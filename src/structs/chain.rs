@@ -16,6 +16,10 @@ pub struct Chain {
     residues: Vec<Residue>,
     /// A possible reference to a database for this chain
     database_reference: Option<DatabaseReference>,
+    /// The residues of this Chain that are absent from the coordinates
+    missing_residues: Vec<MissingResidue>,
+    /// The atoms that are absent from the coordinates of residues in this Chain
+    missing_atoms: Vec<MissingAtoms>,
 }
 
 impl<'a> Chain {
@@ -32,6 +36,8 @@ impl<'a> Chain {
             id,
             residues: Vec::new(),
             database_reference: None,
+            missing_residues: Vec::new(),
+            missing_atoms: Vec::new(),
         })
     }
 
@@ -47,6 +53,8 @@ impl<'a> Chain {
             id,
             residues: residues.collect(),
             database_reference: None,
+            missing_residues: Vec::new(),
+            missing_atoms: Vec::new(),
         })
     }
 
@@ -306,6 +314,138 @@ impl<'a> Chain {
         self.residues.par_iter_mut()
     }
 
+    /// Get an iterator of references to the residues of this Chain that are part of the sequence
+    /// but absent from the coordinates (REMARK 465 or `_pdbx_unobs_or_zero_occ_residues`).
+    pub fn missing_residues(&self) -> impl DoubleEndedIterator<Item = &MissingResidue> + '_ {
+        self.missing_residues.iter()
+    }
+
+    /// Get an iterator of mutable references to the missing residues of this Chain.
+    pub fn missing_residues_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = &mut MissingResidue> + '_ {
+        self.missing_residues.iter_mut()
+    }
+
+    /// Get the number of missing residues of this Chain.
+    pub fn missing_residue_count(&self) -> usize {
+        self.missing_residues.len()
+    }
+
+    /// Add a missing residue to this Chain.
+    pub fn add_missing_residue(&mut self, residue: MissingResidue) {
+        self.missing_residues.push(residue);
+    }
+
+    /// Get an iterator of references to the atoms that are absent from the coordinates of the
+    /// residues of this Chain (REMARK 470 or `_pdbx_unobs_or_zero_occ_atoms`).
+    pub fn missing_atoms(&self) -> impl DoubleEndedIterator<Item = &MissingAtoms> + '_ {
+        self.missing_atoms.iter()
+    }
+
+    /// Get an iterator of mutable references to the missing atoms of this Chain.
+    pub fn missing_atoms_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut MissingAtoms> + '_ {
+        self.missing_atoms.iter_mut()
+    }
+
+    /// Add missing atoms of a residue to this Chain.
+    pub fn add_missing_atoms(&mut self, atoms: MissingAtoms) {
+        self.missing_atoms.push(atoms);
+    }
+
+    /// Get the names of the missing atoms of the given residue of this Chain, empty if no atoms
+    /// are missing.
+    pub fn missing_atoms_of(&self, residue: &Residue) -> &[String] {
+        self.missing_atoms
+            .iter()
+            .find(|m| m.id() == residue.id())
+            .map_or(&[], |m| &m.atoms)
+    }
+
+    /// Get the residue at the given (zero based) index in the deposited sequence of this Chain,
+    /// the reverse of [`Residue::sequence_index`]. It returns `None` if the residue at this
+    /// position is absent from the coordinates.
+    pub fn residue_at_sequence_index(&self, index: usize) -> Option<&Residue> {
+        self.residues
+            .iter()
+            .find(|r| r.sequence_index() == Some(index))
+    }
+
+    /// Map the residues of this Chain onto the given sequence of residue names (eg
+    /// [`Entity::sequence`]), replacing any previous mapping. The residue names are aligned with
+    /// a global alignment, where skipping part of the sequence is only free at the ends and where
+    /// the residue numbering jumps, so residues absent from the coordinates are placed where the
+    /// numbering indicates. Only residues with a name found in the sequence are mapped, which
+    /// leaves out ligands and waters.
+    pub fn align_sequence(&mut self, sequence: &[String]) {
+        let residues: Vec<(isize, usize, &str)> = self
+            .residues
+            .iter()
+            .enumerate()
+            .filter_map(|(position, r)| {
+                r.name()
+                    .filter(|name| sequence.iter().any(|s| s == name))
+                    .map(|name| (r.serial_number(), position, name))
+            })
+            .collect();
+        let (n, m) = (residues.len(), sequence.len());
+        // The cost of skipping part of the sequence after the first `i` residues
+        let gap = |i: usize| {
+            if i == 0 || i == n || !(0..=1).contains(&(residues[i].0 - residues[i - 1].0)) {
+                0
+            } else {
+                -1
+            }
+        };
+        // The scores of the previous row, and the steps taken (0: both, 1: residue, 2: sequence)
+        let mut previous = vec![0; m + 1];
+        let mut steps = vec![2_u8; (n + 1) * (m + 1)];
+        for i in 1..=n {
+            let mut current = vec![previous[0] - 2; m + 1];
+            steps[i * (m + 1)] = 1;
+            for j in 1..=m {
+                let both = previous[j - 1]
+                    + if residues[i - 1].2 == sequence[j - 1] {
+                        2
+                    } else {
+                        -1
+                    };
+                let skip_sequence = current[j - 1] + gap(i);
+                let skip_residue = previous[j] - 2;
+                let (score, step) = if both >= skip_sequence && both >= skip_residue {
+                    (both, 0)
+                } else if skip_sequence >= skip_residue {
+                    (skip_sequence, 2)
+                } else {
+                    (skip_residue, 1)
+                };
+                current[j] = score;
+                steps[i * (m + 1) + j] = step;
+            }
+            previous = current;
+        }
+
+        let mut indices = Vec::new();
+        let (mut i, mut j) = (n, m);
+        while i > 0 && j > 0 {
+            match steps[i * (m + 1) + j] {
+                0 => {
+                    indices.push((residues[i - 1].1, j - 1));
+                    i -= 1;
+                    j -= 1;
+                }
+                1 => i -= 1,
+                _ => j -= 1,
+            }
+        }
+        for residue in &mut self.residues {
+            residue.set_sequence_index(None);
+        }
+        for (position, index) in indices {
+            self.residues[position].set_sequence_index(Some(index));
+        }
+    }
+
     /// Get an iterator of references to Conformers making up this Chain.
     /// Double ended so iterating from the end is just as fast as from the start.
     pub fn conformers(&self) -> impl DoubleEndedIterator<Item = &Conformer> + '_ {
@@ -514,10 +654,12 @@ impl<'a> Chain {
             .for_each(|atom| atom.apply_transformation(transformation));
     }
 
-    /// Join this Chain with another Chain, this moves all atoms from the other Chain
-    /// to this Chain. All other (meta) data of this Chain will stay the same.
+    /// Join this Chain with another Chain, this moves all atoms (and missing residues and atoms)
+    /// from the other Chain to this Chain. All other (meta) data of this Chain will stay the same.
     pub fn join(&mut self, other: Chain) {
         self.residues.extend(other.residues);
+        self.missing_residues.extend(other.missing_residues);
+        self.missing_atoms.extend(other.missing_atoms);
     }
 
     /// Sort the residues of this chain
@@ -612,4 +754,26 @@ mod tests {
         assert_eq!(a.residue_count(), 0);
         assert!(!a.remove_residue_by_id((13, None)));
     }
+
+    #[test]
+    fn test_align_sequence() {
+        let mut a = Chain::new("A").unwrap();
+        for (serial, name) in [(1, "ALA"), (2, "GLY"), (6, "GLY"), (7, "LYS"), (101, "HOH")] {
+            let atom = Atom::new(false, 1, "", "CA", 0.0, 0.0, 0.0, 1.0, 0.0, "C", 0).unwrap();
+            a.add_atom(atom, (serial, None), (name, None));
+        }
+        let sequence: Vec<String> = ["ALA", "GLY", "SER", "GLY", "SER", "GLY", "LYS"]
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        a.align_sequence(&sequence);
+        let indices: Vec<Option<usize>> = a.residues().map(Residue::sequence_index).collect();
+        assert_eq!(indices, vec![Some(0), Some(1), Some(5), Some(6), None]);
+        assert_eq!(a.residue_at_sequence_index(5), a.residue(2));
+        assert_eq!(a.residue_at_sequence_index(3), None);
+        // Aligning again replaces the previous mapping
+        a.align_sequence(&sequence[6..]);
+        let indices: Vec<Option<usize>> = a.residues().map(Residue::sequence_index).collect();
+        assert_eq!(indices, vec![None, None, None, Some(0), None]);
+    }
 }
@@ -0,0 +1,27 @@
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// The description of a chemical component (a residue or ligand type), as given in the HETNAM,
+/// HETSYN, and FORMUL records of a PDB file or the `_chem_comp` category of an mmCIF file.
+/// The components are stored per structure, and can be found for a conformer with
+/// [`crate::PDB::chemical_component`] using the name of the conformer.
+pub struct ChemicalComponent {
+    /// The identifier of the component, which is the residue name, eg 'GOL'
+    pub id: String,
+    /// The full name of the component, eg 'GLYCEROL'
+    pub name: Option<String>,
+    /// The synonyms of the component, eg 'GLYCERIN' and 'PROPANE-1,2,3-TRIOL'
+    pub synonyms: Vec<String>,
+    /// The chemical formula of a single molecule of the component, eg 'C3 H8 O3'
+    pub formula: Option<String>,
+}
+
+impl ChemicalComponent {
+    /// Create a new ChemicalComponent with the given identifier and without any description
+    #[must_use]
+    pub fn new(id: impl Into<String>) -> Self {
+        ChemicalComponent {
+            id: id.into(),
+            ..ChemicalComponent::default()
+        }
+    }
+}
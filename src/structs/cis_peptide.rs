@@ -0,0 +1,52 @@
+use crate::structs::*;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+/// A peptide bond in the cis conformation, as given in the CISPEP records of a PDB file or the
+/// `_struct_mon_prot_cis` category of an mmCIF file. Cis peptides can also be detected from the
+/// geometry of the structure, see [`crate::PDB::detect_cis_peptides`].
+pub struct CisPeptide {
+    /// The serial number of this cis peptide
+    pub serial_number: usize,
+    /// The serial number of the model this cis peptide is found in
+    pub model: usize,
+    /// The residue before the peptide bond
+    pub start: ResidueReference,
+    /// The residue after the peptide bond
+    pub end: ResidueReference,
+    /// The omega angle of the peptide bond in degrees, if known
+    pub angle: Option<f64>,
+}
+
+impl CisPeptide {
+    /// Create a new CisPeptide without a known angle
+    ///
+    /// ## Arguments
+    /// * `serial_number` - the serial number of the cis peptide
+    /// * `model` - the serial number of the model
+    /// * `start` - the residue before the peptide bond
+    /// * `end` - the residue after the peptide bond
+    #[must_use]
+    pub fn new(
+        serial_number: usize,
+        model: usize,
+        start: ResidueReference,
+        end: ResidueReference,
+    ) -> Self {
+        CisPeptide {
+            serial_number,
+            model,
+            start,
+            end,
+            angle: None,
+        }
+    }
+
+    /// Determine if this cis peptide is the bond between the given residues in the given chain
+    /// of the given model. The names of the residues are not taken into account.
+    pub fn matches(&self, model: &Model, chain: &Chain, start: &Residue, end: &Residue) -> bool {
+        self.model == model.serial_number()
+            && self.start.matches(chain, start)
+            && self.end.matches(chain, end)
+    }
+}
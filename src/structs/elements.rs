@@ -384,6 +384,17 @@ impl Element {
         ELEMENT_SYMBOLS[self.atomic_number() - 1]
     }
 
+    /// Get the symbol for this element in the conventional casing, as used in small molecule
+    /// formats, eg "Cl" for Chlorine
+    pub(crate) fn title_case_symbol(&self) -> String {
+        let mut chars = self.symbol().chars();
+        chars
+            .next()
+            .into_iter()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect()
+    }
+
     /// Get the full name for an element, eg "Helium" for Element::He
     pub const fn full_name(&self) -> &'static str {
         ELEMENT_NAMES[self.atomic_number() - 1]
@@ -401,6 +412,14 @@ impl Element {
         ELEMENT_WEIGHT[self.atomic_number() - 1]
     }
 
+    /// Determine if this element is a metal. The metalloids (B, Si, Ge, As, Sb, Te, At) are not seen as metals.
+    pub const fn is_metal(&self) -> bool {
+        !matches!(
+            self.atomic_number(),
+            1 | 2 | 5..=10 | 14..=18 | 32..=36 | 51..=54 | 85 | 86 | 117 | 118
+        )
+    }
+
     /// Get the Pauling electronegativity for the element.
     /// Source: WM Haynes (ed), CRC Handbook of Chemistry and Physics, 95th Edition. CRC Press. Boca Raton, Florida, 2014-2015; Section 9, Molecular Structure and Spectroscopy; Electronegativity
     pub const fn electro_negativity(&self) -> Option<f64> {
@@ -0,0 +1,112 @@
+use crate::reference_tables;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// A distinct molecule in the structure, which can be present in multiple chains. As given in the
+/// `_entity`, `_entity_poly`, and `_entity_poly_seq` categories of an mmCIF file. For PDB files
+/// the polymer entities are built from the SEQRES records, combined with the COMPND records.
+///
+/// This can be used to find which chains are copies of the same molecule and what the deposited
+/// sequence of a polymer is, including the residues that are missing from the coordinates.
+pub struct Entity {
+    /// The identifier of the entity, eg '1'
+    pub id: String,
+    /// The type of the entity, eg 'polymer', 'non-polymer', 'branched', or 'water'
+    pub entity_type: Option<String>,
+    /// The type of the polymer, eg 'polypeptide(L)' or 'polydeoxyribonucleotide'
+    pub polymer_type: Option<String>,
+    /// The description of the entity, eg 'UBIQUITIN'
+    pub description: Option<String>,
+    /// The full sequence of a polymer as residue names, eg 'MET' and 'GLN', or the name of the
+    /// single component of a non-polymer, eg 'ADP'
+    pub sequence: Vec<String>,
+    /// The canonical sequence of a polymer in one letter codes, if given in the file
+    pub canonical_sequence: Option<String>,
+    /// The identifiers of the chains this entity is found in
+    pub chains: Vec<String>,
+}
+
+impl Entity {
+    /// Create a new Entity with the given identifier and without any description
+    #[must_use]
+    pub fn new(id: impl Into<String>) -> Self {
+        Entity {
+            id: id.into(),
+            ..Entity::default()
+        }
+    }
+
+    /// Determine if this entity is a polymer.
+    pub fn is_polymer(&self) -> bool {
+        self.entity_type.as_deref() == Some("polymer")
+            || (self.entity_type.is_none() && !self.sequence.is_empty())
+    }
+
+    /// Determine if this entity is found in the chain with the given id.
+    pub fn contains_chain(&self, chain_id: &str) -> bool {
+        self.chains.iter().any(|c| c == chain_id)
+    }
+
+    /// Get the sequence of this entity in one letter codes. This is the canonical sequence if it was
+    /// given, otherwise it is derived from the full sequence with 'X' for any non standard residue.
+    pub fn one_letter_sequence(&self) -> String {
+        self.canonical_sequence.clone().unwrap_or_else(|| {
+            self.sequence
+                .iter()
+                .map(|name| reference_tables::get_one_letter_code(name).unwrap_or('X'))
+                .collect()
+        })
+    }
+}
+
+/// Guess the type of a polymer from the names of its residues, in the same terms as the
+/// `_entity_poly.type` mmCIF item.
+pub(crate) fn guess_polymer_type(sequence: &[String]) -> Option<&'static str> {
+    let count = |names: &[&str]| {
+        sequence
+            .iter()
+            .filter(|n| names.contains(&n.as_str()))
+            .count()
+    };
+    let amino_acids = sequence
+        .iter()
+        .filter(|n| reference_tables::is_amino_acid(n.as_str()))
+        .count();
+    let dna = count(&["DA", "DC", "DG", "DT", "DI", "DU"]);
+    let rna = count(&["A", "C", "G", "U", "I"]);
+    if amino_acids > 0 && amino_acids >= dna + rna {
+        Some("polypeptide(L)")
+    } else if dna > 0 && rna > 0 {
+        Some("polydeoxyribonucleotide/polyribonucleotide hybrid")
+    } else if dna > 0 {
+        Some("polydeoxyribonucleotide")
+    } else if rna > 0 {
+        Some("polyribonucleotide")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entity_sequence() {
+        let mut entity = Entity::new("1");
+        entity.sequence = ["MET", "GLN", "MSE", "LYS"]
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert!(entity.is_polymer());
+        assert_eq!(entity.one_letter_sequence(), "MQXK");
+        entity.canonical_sequence = Some("MQMK".to_string());
+        assert_eq!(entity.one_letter_sequence(), "MQMK");
+        assert_eq!(guess_polymer_type(&entity.sequence), Some("polypeptide(L)"));
+        let dna: Vec<String> = ["DA", "DC", "DG"].iter().map(ToString::to_string).collect();
+        assert_eq!(guess_polymer_type(&dna), Some("polydeoxyribonucleotide"));
+        let water: Vec<String> = vec!["HOH".to_string()];
+        assert_eq!(guess_polymer_type(&water), None);
+    }
+}
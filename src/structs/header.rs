@@ -0,0 +1,218 @@
+/// The month abbreviations as used in dates in PDB files
+const MONTHS: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// The metadata of an entry, as given in the title section of a PDB file (HEADER, TITLE, COMPND,
+/// SOURCE, KEYWDS, EXPDTA, AUTHOR, and REVDAT) or the equivalent mmCIF categories (`_struct`,
+/// `_struct_keywords`, `_exptl`, `_pdbx_database_status`, `_entity`, `_entity_src_gen`,
+/// `_audit_author`, and `_pdbx_audit_revision_history`).
+pub struct Header {
+    /// The classification of the entry, eg 'CHAPERONE/HYDROLASE'
+    pub classification: Option<String>,
+    /// The date of deposition in the ISO format 'YYYY-MM-DD'
+    pub deposition_date: Option<String>,
+    /// The title of the entry
+    pub title: Option<String>,
+    /// The keywords describing the entry
+    pub keywords: Vec<String>,
+    /// The experimental methods used to determine the structure, eg 'X-RAY DIFFRACTION'
+    pub experimental_methods: Vec<String>,
+    /// The authors of the entry, in the order given in the file
+    pub authors: Vec<String>,
+    /// The description of the macromolecules in the entry
+    pub molecules: Vec<MoleculeInfo>,
+    /// The revision history of the entry
+    pub revisions: Vec<Revision>,
+}
+
+impl Header {
+    /// Create a new empty Header
+    #[must_use]
+    pub fn new() -> Self {
+        Header::default()
+    }
+
+    /// Determine if the entry was determined with the given experimental method, the comparison
+    /// is case insensitive, eg `header.has_method("x-ray diffraction")`.
+    pub fn has_method(&self, method: &str) -> bool {
+        self.experimental_methods
+            .iter()
+            .any(|m| m.eq_ignore_ascii_case(method))
+    }
+
+    /// Get all source organisms (scientific names) of the molecules in this entry, without duplicates
+    pub fn organisms(&self) -> Vec<&str> {
+        let mut organisms = Vec::new();
+        for organism in self.molecules.iter().filter_map(MoleculeInfo::organism) {
+            if !organisms.contains(&organism) {
+                organisms.push(organism);
+            }
+        }
+        organisms
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// The description of a single macromolecule, as given in the COMPND and SOURCE records of a PDB
+/// file. The information is kept as the list of specification tokens (eg `("MOLECULE", "HSLU")`),
+/// with mmCIF data mapped to the same tokens, see the [PDB documentation](https://www.wwpdb.org/documentation/file-format-content/format33/sect2.html#COMPND).
+pub struct MoleculeInfo {
+    /// The identifier of the molecule (MOL_ID), which is equal to the entity id in mmCIF
+    pub id: String,
+    /// The COMPND specification tokens (excluding MOL_ID) with their values
+    pub compound: Vec<(String, String)>,
+    /// The SOURCE specification tokens (excluding MOL_ID) with their values
+    pub source: Vec<(String, String)>,
+}
+
+impl MoleculeInfo {
+    /// Create a new MoleculeInfo with the given identifier and without any specifications
+    #[must_use]
+    pub fn new(id: impl Into<String>) -> Self {
+        MoleculeInfo {
+            id: id.into(),
+            ..MoleculeInfo::default()
+        }
+    }
+
+    /// Get the value of the given COMPND token, eg `compound_value("EC")`
+    pub fn compound_value(&self, token: &str) -> Option<&str> {
+        find_token(&self.compound, token)
+    }
+
+    /// Get the value of the given SOURCE token, eg `source_value("GENE")`
+    pub fn source_value(&self, token: &str) -> Option<&str> {
+        find_token(&self.source, token)
+    }
+
+    /// The name of the molecule (MOLECULE)
+    pub fn name(&self) -> Option<&str> {
+        self.compound_value("MOLECULE")
+    }
+
+    /// The chains consisting of this molecule (CHAIN)
+    pub fn chains(&self) -> Vec<&str> {
+        self.compound_value("CHAIN")
+            .map_or_else(Vec::new, |chains| {
+                chains
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|c| !c.is_empty())
+                    .collect()
+            })
+    }
+
+    /// The scientific name of the source organism (ORGANISM_SCIENTIFIC)
+    pub fn organism(&self) -> Option<&str> {
+        self.source_value("ORGANISM_SCIENTIFIC")
+    }
+
+    /// The NCBI taxonomy identifier of the source organism (ORGANISM_TAXID)
+    pub fn organism_taxonomy_id(&self) -> Option<&str> {
+        self.source_value("ORGANISM_TAXID")
+    }
+
+    /// The scientific name of the expression system (EXPRESSION_SYSTEM)
+    pub fn expression_system(&self) -> Option<&str> {
+        self.source_value("EXPRESSION_SYSTEM")
+    }
+}
+
+/// Find the value of the given token in the list of specifications, the token is compared case insensitive
+fn find_token<'a>(specification: &'a [(String, String)], token: &str) -> Option<&'a str> {
+    specification
+        .iter()
+        .find(|(t, _)| t.eq_ignore_ascii_case(token))
+        .map(|(_, v)| v.as_str())
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// A revision of an entry, see REVDAT documentation wwPDB v3.30 <https://www.wwpdb.org/documentation/file-format-content/format33/sect2.html#REVDAT>
+pub struct Revision {
+    /// The number of the revision, starting at 1 for the initial release
+    pub number: usize,
+    /// The date of the revision in the ISO format 'YYYY-MM-DD'
+    pub date: Option<String>,
+    /// The identifier of the entry at the time of the revision
+    pub identifier: Option<String>,
+    /// The type of the revision, 0 for the initial release and 1 for other modifications
+    pub modification_type: usize,
+    /// The names of the records that were changed in this revision
+    pub records: Vec<String>,
+}
+
+/// Convert a date in the PDB format ('24-FEB-05') to the ISO format ('2005-02-24').
+/// Years before 70 are assumed to be in the 21st century.
+pub(crate) fn date_from_pdb(date: &str) -> Option<String> {
+    let mut parts = date.trim().split('-');
+    let day: usize = parts.next()?.parse().ok()?;
+    let month = parts.next()?;
+    let month = MONTHS.iter().position(|m| m.eq_ignore_ascii_case(month))? + 1;
+    let year: usize = parts.next()?.parse().ok()?;
+    let year = match year {
+        0..=69 => year + 2000,
+        70..=99 => year + 1900,
+        _ => year,
+    };
+    Some(format!("{year:04}-{month:02}-{day:02}"))
+}
+
+/// Convert a date in the ISO format ('2005-02-24') to the PDB format ('24-FEB-05').
+pub(crate) fn date_to_pdb(date: &str) -> Option<String> {
+    let mut parts = date.trim().split('-');
+    let year: usize = parts.next()?.parse().ok()?;
+    let month: usize = parts.next()?.parse().ok()?;
+    let day: usize = parts.next()?.parse().ok()?;
+    Some(format!(
+        "{day:02}-{}-{:02}",
+        MONTHS.get(month.checked_sub(1)?)?,
+        year % 100
+    ))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates() {
+        assert_eq!(date_from_pdb("24-FEB-05").unwrap(), "2005-02-24");
+        assert_eq!(date_from_pdb("02-JAN-91").unwrap(), "1991-01-02");
+        assert_eq!(date_to_pdb("2005-02-24").unwrap(), "24-FEB-05");
+        assert_eq!(date_to_pdb("1991-01-02").unwrap(), "02-JAN-91");
+        assert_eq!(date_from_pdb("24-XXX-05"), None);
+        assert_eq!(date_to_pdb("2005-13-24"), None);
+    }
+
+    #[test]
+    fn molecule() {
+        let mut molecule = MoleculeInfo::new("1");
+        molecule
+            .compound
+            .push(("MOLECULE".to_string(), "HSLU".to_string()));
+        molecule
+            .compound
+            .push(("CHAIN".to_string(), "A, B".to_string()));
+        molecule.source.push((
+            "ORGANISM_SCIENTIFIC".to_string(),
+            "ESCHERICHIA COLI".to_string(),
+        ));
+        assert_eq!(molecule.name(), Some("HSLU"));
+        assert_eq!(molecule.chains(), vec!["A", "B"]);
+        assert_eq!(molecule.organism(), Some("ESCHERICHIA COLI"));
+        assert_eq!(molecule.expression_system(), None);
+        let header = Header {
+            molecules: vec![molecule.clone(), molecule],
+            experimental_methods: vec!["X-RAY DIFFRACTION".to_string()],
+            ..Header::default()
+        };
+        assert_eq!(header.organisms(), vec!["ESCHERICHIA COLI"]);
+        assert!(header.has_method("x-ray diffraction"));
+    }
+}
@@ -0,0 +1,78 @@
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A residue that is part of the sequence of a chain but was not located in the experiment, so it
+/// is absent from the coordinates. As given in REMARK 465 of a PDB file or the
+/// `_pdbx_unobs_or_zero_occ_residues` category of an mmCIF file. Missing residues are stored in
+/// the chain of the model they are missing from, see [`crate::Chain::missing_residues`].
+pub struct MissingResidue {
+    /// The name of the residue, eg 'ALA'
+    pub name: String,
+    /// The serial number of the residue
+    pub serial_number: isize,
+    /// The insertion code of the residue
+    pub insertion_code: Option<String>,
+}
+
+impl MissingResidue {
+    /// Create a new MissingResidue
+    ///
+    /// ## Arguments
+    /// * `name` - the name of the residue
+    /// * `residue_id` - the serial number and insertion code of the residue
+    #[must_use]
+    pub fn new(name: impl Into<String>, residue_id: (isize, Option<&str>)) -> Self {
+        MissingResidue {
+            name: name.into(),
+            serial_number: residue_id.0,
+            insertion_code: residue_id.1.map(ToString::to_string),
+        }
+    }
+
+    /// The serial number and insertion code of this residue, in the same form as [`crate::Residue::id`]
+    pub fn id(&self) -> (isize, Option<&str>) {
+        (self.serial_number, self.insertion_code.as_deref())
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The atoms of a residue that were not located in the experiment, while the residue itself is
+/// present in the coordinates. As given in REMARK 470 of a PDB file or the
+/// `_pdbx_unobs_or_zero_occ_atoms` category of an mmCIF file.
+pub struct MissingAtoms {
+    /// The name of the residue, eg 'LYS'
+    pub name: String,
+    /// The serial number of the residue
+    pub serial_number: isize,
+    /// The insertion code of the residue
+    pub insertion_code: Option<String>,
+    /// The names of the missing atoms, eg 'CE' and 'NZ'
+    pub atoms: Vec<String>,
+}
+
+impl MissingAtoms {
+    /// Create a new MissingAtoms
+    ///
+    /// ## Arguments
+    /// * `name` - the name of the residue
+    /// * `residue_id` - the serial number and insertion code of the residue
+    /// * `atoms` - the names of the missing atoms
+    #[must_use]
+    pub fn new(
+        name: impl Into<String>,
+        residue_id: (isize, Option<&str>),
+        atoms: Vec<String>,
+    ) -> Self {
+        MissingAtoms {
+            name: name.into(),
+            serial_number: residue_id.0,
+            insertion_code: residue_id.1.map(ToString::to_string),
+            atoms,
+        }
+    }
+
+    /// The serial number and insertion code of the residue, in the same form as [`crate::Residue::id`]
+    pub fn id(&self) -> (isize, Option<&str>) {
+        (self.serial_number, self.insertion_code.as_deref())
+    }
+}
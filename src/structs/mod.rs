@@ -1,32 +1,57 @@
 #![allow(clippy::missing_docs_in_private_items)]
+mod assembly;
 mod atom;
 mod bond;
 mod chain;
+mod chemical_component;
+mod cis_peptide;
 mod conformer;
 mod database_reference;
 mod elements;
+mod entity;
+mod header;
 mod helper;
 mod hierarchy;
+mod missing_residue;
 mod model;
 mod mtrix;
 mod pdb;
+mod refinement;
 mod residue;
+mod residue_reference;
 mod search;
+mod secondary_structure;
+mod site;
 mod symmetry;
+mod torsion_tree;
 mod unit_cell;
 
+pub use assembly::{Assembly, AssemblyGenerator, AssemblyOperator};
 pub use atom::Atom;
-pub use bond::Bond;
+pub use bond::{Bond, BondDetails, BondOrder};
 pub use chain::Chain;
+pub use chemical_component::ChemicalComponent;
+pub use cis_peptide::CisPeptide;
 pub use conformer::Conformer;
 pub use database_reference::*;
 pub use elements::{AtomicRadius, Element};
+pub(crate) use entity::guess_polymer_type;
+pub use entity::Entity;
+pub(crate) use header::{date_from_pdb, date_to_pdb};
+pub use header::{Header, MoleculeInfo, Revision};
 pub use helper::*;
 pub use hierarchy::*;
+pub use missing_residue::{MissingAtoms, MissingResidue};
 pub use model::Model;
 pub use mtrix::MtriX;
 pub use pdb::PDB;
+pub(crate) use pdb::{AnnotatedBond, CIS_PEPTIDE_ANGLE};
+pub use refinement::{RefinementInfo, ReflectionStatistics};
 pub use residue::Residue;
+pub use residue_reference::ResidueReference;
 pub use search::*;
+pub use secondary_structure::*;
+pub use site::Site;
 pub use symmetry::Symmetry;
+pub use torsion_tree::{TorsionBranch, TorsionTree};
 pub use unit_cell::UnitCell;
@@ -1,12 +1,13 @@
 #![allow(dead_code)]
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 
 use doc_cfg::doc_cfg;
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
 
+use crate::cif::DataBlock;
 use crate::structs::hierarchy::*;
 use crate::transformation::TransformationMatrix;
 use crate::{reference_tables, PDBError};
@@ -37,6 +38,10 @@ use crate::{structs::*, Context};
 pub struct PDB {
     /// The identifier as posed in the PDB Header or mmCIF entry.id, normally a 4 char string like '1UBQ'.
     pub identifier: Option<String>,
+    /// The metadata of the entry, like its title, experimental methods, authors, and source organisms.
+    pub header: Option<Header>,
+    /// The resolution and refinement statistics of the structure.
+    pub refinement: Option<RefinementInfo>,
     /// The remarks above the PDB file, containing the remark-type-number and a line of free text.
     remarks: Vec<(usize, String)>,
     /// The Scale needed to transform orthogonal coordinates to fractional coordinates. This is inversely related to the unit cell.
@@ -51,10 +56,42 @@ pub struct PDB {
     pub symmetry: Option<Symmetry>,
     /// The Models making up this PDB, containing all chain, residues, conformers, and atoms.
     models: Vec<Model>,
-    /// Bonds in this PDB.
-    bonds: Vec<(usize, usize, Bond)>,
+    /// Bonds in this PDB, with additional details if these are known.
+    bonds: Vec<(usize, usize, Bond, Option<BondDetails>)>,
+    /// The secondary structure elements (helices and strands) of this PDB.
+    secondary_structure: Vec<SecondaryStructure>,
+    /// The biological assemblies of this PDB.
+    assemblies: Vec<Assembly>,
+    /// The descriptions of the chemical components (residue and ligand types), sorted by id.
+    chemical_components: Vec<ChemicalComponent>,
+    /// The sites (eg binding sites) of this PDB.
+    sites: Vec<Site>,
+    /// The cis peptide bonds of this PDB.
+    cis_peptides: Vec<CisPeptide>,
+    /// The torsion trees of the flexible ligands in this PDB, one per model.
+    torsion_trees: Vec<TorsionTree>,
+    /// The entities (distinct molecules) of this PDB.
+    entities: Vec<Entity>,
+    /// The categories of an mmCIF file that are not interpreted by this crate, only kept if
+    /// requested with [`crate::ReadOptions::set_keep_unknown_categories`]. These are written back
+    /// unchanged when the structure is saved as mmCIF.
+    pub unknown_categories: Option<DataBlock>,
 }
 
+/// A bond between two atoms with their hierarchies, its type, and details if known
+pub(crate) type AnnotatedBond<'a> = (
+    AtomConformerResidueChain<'a>,
+    AtomConformerResidueChain<'a>,
+    Bond,
+    Option<&'a BondDetails>,
+);
+
+/// A peptide bond with the model, chain, and residues it is found in, and its omega angle
+pub(crate) type PeptideBond<'a> = (&'a Model, &'a Chain, &'a Residue, &'a Residue, f64);
+
+/// The maximal absolute omega angle (in degrees) of a peptide bond to be considered cis
+pub(crate) const CIS_PEPTIDE_ANGLE: f64 = 30.0;
+
 /// # Creators
 /// Creator functions for a PDB file
 impl PDB {
@@ -62,6 +99,8 @@ impl PDB {
     pub const fn new() -> PDB {
         PDB {
             identifier: None,
+            header: None,
+            refinement: None,
             remarks: Vec::new(),
             scale: None,
             origx: None,
@@ -70,6 +109,14 @@ impl PDB {
             symmetry: None,
             models: Vec::new(),
             bonds: Vec::new(),
+            secondary_structure: Vec::new(),
+            assemblies: Vec::new(),
+            chemical_components: Vec::new(),
+            sites: Vec::new(),
+            cis_peptides: Vec::new(),
+            torsion_trees: Vec::new(),
+            entities: Vec::new(),
+            unknown_categories: None,
         }
     }
 }
@@ -77,6 +124,22 @@ impl PDB {
 /// # Remarks
 /// Functionality for working with remarks.
 impl PDB {
+    /// Get the resolution of the structure in Ångström, as given in REMARK 2 (or REMARK 3) in PDB
+    /// files or in the `_refine` category in mmCIF files. Structures without a resolution, like
+    /// those determined by NMR, return `None`.
+    ///
+    /// ```rust
+    /// use pdbtbx::*;
+    /// let (pdb, _errors) = ReadOptions::default()
+    ///     .set_level(StrictnessLevel::Loose)
+    ///     .read("example-pdbs/1yyf.pdb")
+    ///     .unwrap();
+    /// assert_eq!(pdb.resolution(), Some(4.16));
+    /// ```
+    pub fn resolution(&self) -> Option<f64> {
+        self.refinement.as_ref().and_then(|r| r.resolution)
+    }
+
     /// Get the number of remark records in the PDB file.
     pub fn remark_count(&self) -> usize {
         self.remarks.len()
@@ -195,6 +258,618 @@ impl PDB {
     }
 }
 
+/// # Secondary structure
+/// Functionality for working with the secondary structure elements, the helices and strands,
+/// as defined in the HELIX and SHEET records or the `_struct_conf` and `_struct_sheet_range` mmCIF categories.
+impl PDB {
+    /// Get the number of secondary structure elements in this PDB.
+    pub fn secondary_structure_count(&self) -> usize {
+        self.secondary_structure.len()
+    }
+
+    /// Get an iterator of references to the secondary structure elements for this PDB.
+    pub fn secondary_structure(&self) -> impl DoubleEndedIterator<Item = &SecondaryStructure> + '_ {
+        self.secondary_structure.iter()
+    }
+
+    /// Get a parallel iterator of references to the secondary structure elements for this PDB.
+    #[doc_cfg(feature = "rayon")]
+    pub fn par_secondary_structure(
+        &self,
+    ) -> impl ParallelIterator<Item = &SecondaryStructure> + '_ {
+        self.secondary_structure.par_iter()
+    }
+
+    /// Get an iterator of mutable references to the secondary structure elements for this PDB.
+    pub fn secondary_structure_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = &mut SecondaryStructure> + '_ {
+        self.secondary_structure.iter_mut()
+    }
+
+    /// Get a parallel iterator of mutable references to the secondary structure elements for this PDB.
+    #[doc_cfg(feature = "rayon")]
+    pub fn par_secondary_structure_mut(
+        &mut self,
+    ) -> impl ParallelIterator<Item = &mut SecondaryStructure> + '_ {
+        self.secondary_structure.par_iter_mut()
+    }
+
+    /// Add a secondary structure element to this PDB.
+    pub fn add_secondary_structure(&mut self, element: SecondaryStructure) {
+        self.secondary_structure.push(element);
+    }
+
+    /// Delete the secondary structure elements matching the given predicate.
+    pub fn delete_secondary_structure_by<F>(&mut self, predicate: F)
+    where
+        F: Fn(&SecondaryStructure) -> bool,
+    {
+        self.secondary_structure.retain(|s| !predicate(s));
+    }
+
+    /// Get the secondary structure element the given residue in the given chain is part of.
+    /// Returns `None` if the residue is not part of any helix or strand, so it is in a coil.
+    /// To get the elements of all residues of a chain use [`PDB::secondary_structure_of_chain`].
+    ///
+    /// ```rust
+    /// use pdbtbx::*;
+    /// let (pdb, _errors) = ReadOptions::default().set_level(StrictnessLevel::Loose).read("example-pdbs/1yyf.pdb").unwrap();
+    /// let chain = pdb.chains().next().unwrap();
+    /// let residue = chain.residues().find(|r| r.serial_number() == 5).unwrap();
+    /// assert!(pdb.secondary_structure_of(chain, residue).unwrap().is_helix());
+    /// ```
+    pub fn secondary_structure_of(
+        &self,
+        chain: &Chain,
+        residue: &Residue,
+    ) -> Option<&SecondaryStructure> {
+        self.secondary_structure
+            .iter()
+            .find(|s| s.contains(chain, residue))
+    }
+
+    /// Get the secondary structure element of every residue of the given chain, in the order of
+    /// the residues. This gives the same elements as [`PDB::secondary_structure_of`] for every
+    /// residue, but only goes over the residues of the chain once for every element.
+    ///
+    /// ```rust
+    /// use pdbtbx::*;
+    /// let (pdb, _errors) = ReadOptions::default().set_level(StrictnessLevel::Loose).read("example-pdbs/1yyf.pdb").unwrap();
+    /// let chain = pdb.chains().next().unwrap();
+    /// let tags: String = pdb
+    ///     .secondary_structure_of_chain(chain)
+    ///     .into_iter()
+    ///     .map(|element| match element {
+    ///         Some(SecondaryStructure::Helix(_)) => 'H',
+    ///         Some(SecondaryStructure::Strand(_)) => 'E',
+    ///         None => 'C',
+    ///     })
+    ///     .collect();
+    /// assert_eq!(tags.len(), chain.residue_count());
+    /// ```
+    pub fn secondary_structure_of_chain(&self, chain: &Chain) -> Vec<Option<&SecondaryStructure>> {
+        let mut positions = HashMap::new();
+        for (index, residue) in chain.residues().enumerate() {
+            positions.entry(residue.id()).or_insert(index);
+        }
+        let mut assignments = vec![None; chain.residue_count()];
+        for element in &self.secondary_structure {
+            element.assign(chain, &positions, &mut assignments);
+        }
+        assignments
+    }
+}
+
+/// # Chemical components
+/// Functionality for working with the descriptions of the chemical components, as defined in the
+/// HETNAM, HETSYN, and FORMUL records or the `_chem_comp` mmCIF category.
+impl PDB {
+    /// Get the number of chemical components described in this PDB.
+    pub fn chemical_component_count(&self) -> usize {
+        self.chemical_components.len()
+    }
+
+    /// Get an iterator of references to the chemical components of this PDB, sorted by id.
+    pub fn chemical_components(&self) -> impl DoubleEndedIterator<Item = &ChemicalComponent> + '_ {
+        self.chemical_components.iter()
+    }
+
+    /// Get an iterator of mutable references to the chemical components of this PDB, sorted by id.
+    /// The ids should not be changed, as the components are found by id.
+    pub fn chemical_components_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = &mut ChemicalComponent> + '_ {
+        self.chemical_components.iter_mut()
+    }
+
+    /// Get the chemical component with the given id, which is the name of the conformers of this
+    /// component.
+    ///
+    /// ```rust
+    /// use pdbtbx::*;
+    /// let (pdb, _errors) = ReadOptions::default().set_level(StrictnessLevel::Loose).read("example-pdbs/7az6.pdb").unwrap();
+    /// for conformer in pdb.conformers() {
+    ///     if let Some(name) = pdb.chemical_component(conformer.name()).and_then(|c| c.name.as_ref()) {
+    ///         println!("{} is {}", conformer.name(), name);
+    ///     }
+    /// }
+    /// assert_eq!(pdb.chemical_component("GOL").unwrap().name.as_deref(), Some("GLYCEROL"));
+    /// ```
+    pub fn chemical_component(&self, id: &str) -> Option<&ChemicalComponent> {
+        self.chemical_components
+            .binary_search_by(|c| c.id.as_str().cmp(id))
+            .ok()
+            .map(|index| &self.chemical_components[index])
+    }
+
+    /// Get the chemical component with the given id mutably. The id should not be changed, as the
+    /// components are found by id.
+    pub fn chemical_component_mut(&mut self, id: &str) -> Option<&mut ChemicalComponent> {
+        self.chemical_components
+            .binary_search_by(|c| c.id.as_str().cmp(id))
+            .ok()
+            .map(|index| &mut self.chemical_components[index])
+    }
+
+    /// Add a chemical component to this PDB, replacing the component with the same id if present.
+    pub fn add_chemical_component(&mut self, component: ChemicalComponent) {
+        match self
+            .chemical_components
+            .binary_search_by(|c| c.id.cmp(&component.id))
+        {
+            Ok(index) => self.chemical_components[index] = component,
+            Err(index) => self.chemical_components.insert(index, component),
+        }
+    }
+
+    /// Delete the chemical components matching the given predicate.
+    pub fn delete_chemical_components_by<F>(&mut self, predicate: F)
+    where
+        F: Fn(&ChemicalComponent) -> bool,
+    {
+        self.chemical_components.retain(|c| !predicate(c));
+    }
+}
+
+/// # Sites
+/// Functionality for working with the sites, as defined in the SITE records or the `_struct_site`
+/// and `_struct_site_gen` mmCIF categories.
+impl PDB {
+    /// Get the number of sites in this PDB.
+    pub fn site_count(&self) -> usize {
+        self.sites.len()
+    }
+
+    /// Get an iterator of references to the sites for this PDB.
+    pub fn sites(&self) -> impl DoubleEndedIterator<Item = &Site> + '_ {
+        self.sites.iter()
+    }
+
+    /// Get a parallel iterator of references to the sites for this PDB.
+    #[doc_cfg(feature = "rayon")]
+    pub fn par_sites(&self) -> impl ParallelIterator<Item = &Site> + '_ {
+        self.sites.par_iter()
+    }
+
+    /// Get an iterator of mutable references to the sites for this PDB.
+    pub fn sites_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut Site> + '_ {
+        self.sites.iter_mut()
+    }
+
+    /// Get a parallel iterator of mutable references to the sites for this PDB.
+    #[doc_cfg(feature = "rayon")]
+    pub fn par_sites_mut(&mut self) -> impl ParallelIterator<Item = &mut Site> + '_ {
+        self.sites.par_iter_mut()
+    }
+
+    /// Get the site with the given id, eg `pdb.site("AC1")`.
+    pub fn site(&self, id: &str) -> Option<&Site> {
+        self.sites.iter().find(|s| s.id == id)
+    }
+
+    /// Get the site with the given id mutably.
+    pub fn site_mut(&mut self, id: &str) -> Option<&mut Site> {
+        self.sites.iter_mut().find(|s| s.id == id)
+    }
+
+    /// Add a site to this PDB.
+    pub fn add_site(&mut self, site: Site) {
+        self.sites.push(site);
+    }
+
+    /// Delete the sites matching the given predicate.
+    pub fn delete_sites_by<F>(&mut self, predicate: F)
+    where
+        F: Fn(&Site) -> bool,
+    {
+        self.sites.retain(|s| !predicate(s));
+    }
+
+    /// Get the sites the given residue in the given chain is part of.
+    pub fn sites_of<'b>(
+        &'b self,
+        chain: &'b Chain,
+        residue: &'b Residue,
+    ) -> impl DoubleEndedIterator<Item = &'b Site> + 'b {
+        self.sites.iter().filter(|s| s.contains(chain, residue))
+    }
+}
+
+/// # Cis peptides
+/// Functionality for working with the cis peptide bonds, as defined in the CISPEP records or the
+/// `_struct_mon_prot_cis` mmCIF category.
+impl PDB {
+    /// Get the number of cis peptides in this PDB.
+    pub fn cis_peptide_count(&self) -> usize {
+        self.cis_peptides.len()
+    }
+
+    /// Get an iterator of references to the cis peptides for this PDB.
+    pub fn cis_peptides(&self) -> impl DoubleEndedIterator<Item = &CisPeptide> + '_ {
+        self.cis_peptides.iter()
+    }
+
+    /// Get a parallel iterator of references to the cis peptides for this PDB.
+    #[doc_cfg(feature = "rayon")]
+    pub fn par_cis_peptides(&self) -> impl ParallelIterator<Item = &CisPeptide> + '_ {
+        self.cis_peptides.par_iter()
+    }
+
+    /// Get an iterator of mutable references to the cis peptides for this PDB.
+    pub fn cis_peptides_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut CisPeptide> + '_ {
+        self.cis_peptides.iter_mut()
+    }
+
+    /// Get a parallel iterator of mutable references to the cis peptides for this PDB.
+    #[doc_cfg(feature = "rayon")]
+    pub fn par_cis_peptides_mut(&mut self) -> impl ParallelIterator<Item = &mut CisPeptide> + '_ {
+        self.cis_peptides.par_iter_mut()
+    }
+
+    /// Add a cis peptide to this PDB.
+    pub fn add_cis_peptide(&mut self, cis_peptide: CisPeptide) {
+        self.cis_peptides.push(cis_peptide);
+    }
+
+    /// Delete the cis peptides matching the given predicate.
+    pub fn delete_cis_peptides_by<F>(&mut self, predicate: F)
+    where
+        F: Fn(&CisPeptide) -> bool,
+    {
+        self.cis_peptides.retain(|c| !predicate(c));
+    }
+
+    /// Get all peptide bonds between consecutive residues in all chains, with their omega angle.
+    /// The omega angle is the dihedral of the CA and C atoms of the first residue and the N and
+    /// CA atoms of the second residue, using the first conformer of both residues. Residues
+    /// without these atoms or with the C and N atoms too far apart to be bonded are skipped.
+    pub(crate) fn peptide_bonds(&self) -> Vec<PeptideBond<'_>> {
+        /// Find the atom with the given name in the first conformer of the residue
+        fn find<'a>(residue: &'a Residue, name: &str) -> Option<&'a Atom> {
+            residue.conformer(0)?.atoms().find(|a| a.name() == name)
+        }
+        let mut bonds = Vec::new();
+        for model in self.models() {
+            for chain in model.chains() {
+                let residues: Vec<&Residue> = chain.residues().collect();
+                for pair in residues.windows(2) {
+                    let (first, second) = (pair[0], pair[1]);
+                    if let (Some(ca1), Some(c), Some(n), Some(ca2)) = (
+                        find(first, "CA"),
+                        find(first, "C"),
+                        find(second, "N"),
+                        find(second, "CA"),
+                    ) {
+                        if c.distance(n) <= 2.0 {
+                            bonds.push((model, chain, first, second, ca1.dihedral(c, n, ca2)));
+                        }
+                    }
+                }
+            }
+        }
+        bonds
+    }
+
+    /// Detect the cis peptides from the geometry of the structure, these are the peptide bonds
+    /// with an omega angle between -30 and 30 degrees. The omega angle is calculated from the CA
+    /// and C atoms of the first residue and the N and CA atoms of the second residue using
+    /// [`Atom::dihedral`], so the angle of the detected cis peptides is not signed. The detected
+    /// cis peptides are numbered in order and are not added to the PDB.
+    ///
+    /// ```rust
+    /// use pdbtbx::*;
+    /// let (pdb, _errors) = ReadOptions::default().set_level(StrictnessLevel::Loose).read("example-pdbs/3nig.pdb").unwrap();
+    /// let detected = pdb.detect_cis_peptides();
+    /// assert_eq!(detected.len(), pdb.cis_peptide_count());
+    /// ```
+    pub fn detect_cis_peptides(&self) -> Vec<CisPeptide> {
+        self.peptide_bonds()
+            .into_iter()
+            .filter(|(_, _, _, _, omega)| omega.abs() < CIS_PEPTIDE_ANGLE)
+            .enumerate()
+            .map(|(index, (model, chain, first, second, omega))| {
+                let mut cis_peptide = CisPeptide::new(
+                    index + 1,
+                    model.serial_number(),
+                    ResidueReference::from_residue(chain, first),
+                    ResidueReference::from_residue(chain, second),
+                );
+                cis_peptide.angle = Some(omega);
+                cis_peptide
+            })
+            .collect()
+    }
+}
+
+/// # Torsion trees
+/// Functionality for working with the torsion trees of flexible ligands, as defined by the `ROOT`,
+/// `BRANCH`, and `TORSDOF` records of PDBQT files.
+impl PDB {
+    /// Get the number of torsion trees in this PDB.
+    pub fn torsion_tree_count(&self) -> usize {
+        self.torsion_trees.len()
+    }
+
+    /// Get an iterator of references to the torsion trees for this PDB.
+    pub fn torsion_trees(&self) -> impl DoubleEndedIterator<Item = &TorsionTree> + '_ {
+        self.torsion_trees.iter()
+    }
+
+    /// Get an iterator of mutable references to the torsion trees for this PDB.
+    pub fn torsion_trees_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut TorsionTree> + '_ {
+        self.torsion_trees.iter_mut()
+    }
+
+    /// Get the torsion tree of the model with the given serial number, if there is one.
+    pub fn torsion_tree(&self, model: usize) -> Option<&TorsionTree> {
+        self.torsion_trees.iter().find(|t| t.model == model)
+    }
+
+    /// Add a torsion tree to this PDB, replacing the torsion tree of the same model if present.
+    pub fn add_torsion_tree(&mut self, torsion_tree: TorsionTree) {
+        self.torsion_trees.retain(|t| t.model != torsion_tree.model);
+        self.torsion_trees.push(torsion_tree);
+    }
+
+    /// Delete the torsion trees matching the given predicate.
+    pub fn delete_torsion_trees_by<F>(&mut self, predicate: F)
+    where
+        F: Fn(&TorsionTree) -> bool,
+    {
+        self.torsion_trees.retain(|t| !predicate(t));
+    }
+}
+
+/// # Entities
+/// Functionality for working with the entities, as defined in the `_entity`, `_entity_poly`, and
+/// `_entity_poly_seq` mmCIF categories or built from the SEQRES and COMPND records.
+impl PDB {
+    /// Get the number of entities in this PDB.
+    pub fn entity_count(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Get an iterator of references to the entities for this PDB.
+    pub fn entities(&self) -> impl DoubleEndedIterator<Item = &Entity> + '_ {
+        self.entities.iter()
+    }
+
+    /// Get a parallel iterator of references to the entities for this PDB.
+    #[doc_cfg(feature = "rayon")]
+    pub fn par_entities(&self) -> impl ParallelIterator<Item = &Entity> + '_ {
+        self.entities.par_iter()
+    }
+
+    /// Get an iterator of mutable references to the entities for this PDB.
+    pub fn entities_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut Entity> + '_ {
+        self.entities.iter_mut()
+    }
+
+    /// Get a parallel iterator of mutable references to the entities for this PDB.
+    #[doc_cfg(feature = "rayon")]
+    pub fn par_entities_mut(&mut self) -> impl ParallelIterator<Item = &mut Entity> + '_ {
+        self.entities.par_iter_mut()
+    }
+
+    /// Get the entity with the given id, eg `pdb.entity("1")`.
+    pub fn entity(&self, id: &str) -> Option<&Entity> {
+        self.entities.iter().find(|e| e.id == id)
+    }
+
+    /// Get the entity with the given id mutably.
+    pub fn entity_mut(&mut self, id: &str) -> Option<&mut Entity> {
+        self.entities.iter_mut().find(|e| e.id == id)
+    }
+
+    /// Get the entity the chain with the given id is a copy of. As ligands and waters share the
+    /// chain id of the polymer they are bound to, the polymer entity is preferred.
+    ///
+    /// ```rust
+    /// use pdbtbx::*;
+    /// let (pdb, _errors) = ReadOptions::default().set_level(StrictnessLevel::Loose).read("example-pdbs/1ubq.cif").unwrap();
+    /// let entity = pdb.entity_of_chain("A").unwrap();
+    /// assert_eq!(entity.sequence.len(), 76);
+    /// assert!(entity.one_letter_sequence().starts_with("MQIFVKTLTG"));
+    /// ```
+    pub fn entity_of_chain(&self, chain_id: &str) -> Option<&Entity> {
+        self.entities
+            .iter()
+            .find(|e| e.is_polymer() && e.contains_chain(chain_id))
+            .or_else(|| self.entities.iter().find(|e| e.contains_chain(chain_id)))
+    }
+
+    /// Map the residues of every chain onto the sequence of its polymer entity, see
+    /// [`Chain::align_sequence`]. Chains without a polymer entity are left as is.
+    pub fn align_sequences(&mut self) {
+        for chain in self.models.iter_mut().flat_map(Model::chains_mut) {
+            if let Some(entity) = self
+                .entities
+                .iter()
+                .find(|e| e.is_polymer() && e.contains_chain(chain.id()))
+            {
+                chain.align_sequence(&entity.sequence);
+            }
+        }
+    }
+
+    /// Add an entity to this PDB.
+    pub fn add_entity(&mut self, entity: Entity) {
+        self.entities.push(entity);
+    }
+
+    /// Delete the entities matching the given predicate.
+    pub fn delete_entities_by<F>(&mut self, predicate: F)
+    where
+        F: Fn(&Entity) -> bool,
+    {
+        self.entities.retain(|e| !predicate(e));
+    }
+}
+
+/// # Assemblies
+/// Functionality for working with the biological assemblies, as defined in REMARK 350 or the
+/// `_pdbx_struct_assembly` and related mmCIF categories.
+impl PDB {
+    /// Get the number of assemblies in this PDB.
+    pub fn assembly_count(&self) -> usize {
+        self.assemblies.len()
+    }
+
+    /// Get an iterator of references to the assemblies for this PDB.
+    pub fn assemblies(&self) -> impl DoubleEndedIterator<Item = &Assembly> + '_ {
+        self.assemblies.iter()
+    }
+
+    /// Get a parallel iterator of references to the assemblies for this PDB.
+    #[doc_cfg(feature = "rayon")]
+    pub fn par_assemblies(&self) -> impl ParallelIterator<Item = &Assembly> + '_ {
+        self.assemblies.par_iter()
+    }
+
+    /// Get an iterator of mutable references to the assemblies for this PDB.
+    pub fn assemblies_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut Assembly> + '_ {
+        self.assemblies.iter_mut()
+    }
+
+    /// Get a parallel iterator of mutable references to the assemblies for this PDB.
+    #[doc_cfg(feature = "rayon")]
+    pub fn par_assemblies_mut(&mut self) -> impl ParallelIterator<Item = &mut Assembly> + '_ {
+        self.assemblies.par_iter_mut()
+    }
+
+    /// Get the assembly with the given id, eg `pdb.assembly("1")`.
+    pub fn assembly(&self, id: &str) -> Option<&Assembly> {
+        self.assemblies.iter().find(|a| a.id == id)
+    }
+
+    /// Add an assembly to this PDB.
+    pub fn add_assembly(&mut self, assembly: Assembly) {
+        self.assemblies.push(assembly);
+    }
+
+    /// Delete the assemblies matching the given predicate.
+    pub fn delete_assemblies_by<F>(&mut self, predicate: F)
+    where
+        F: Fn(&Assembly) -> bool,
+    {
+        self.assemblies.retain(|a| !predicate(a));
+    }
+
+    /// Build the assembly with the given id, by applying the operators of the assembly to copies of
+    /// its chains in every model. Returns `None` if there is no assembly with this id.
+    ///
+    /// The first copy of a chain keeps the id of the chain, later copies are named with the id of the
+    /// operator added, eg 'A-2'. The bonds within and between the copied chains are copied as well.
+    /// The metadata is kept, but the remarks and assemblies are not as they describe the original structure.
+    ///
+    /// ```rust
+    /// use pdbtbx::*;
+    /// let (pdb, _errors) = ReadOptions::default().set_level(StrictnessLevel::Loose).read("example-pdbs/1yyf.pdb").unwrap();
+    /// let assembly = pdb.build_assembly("1").unwrap();
+    /// assert_eq!(assembly.chain_count(), 24);
+    /// ```
+    pub fn build_assembly(&self, id: &str) -> Option<PDB> {
+        let assembly = self.assembly(id)?;
+        let mut pdb = PDB::new();
+        pdb.identifier = self.identifier.clone();
+        pdb.header = self.header.clone();
+        pdb.refinement = self.refinement.clone();
+        pdb.scale = self.scale.clone();
+        pdb.origx = self.origx.clone();
+        pdb.unit_cell = self.unit_cell.clone();
+        pdb.symmetry = self.symmetry.clone();
+        pdb.chemical_components = self.chemical_components.clone();
+        pdb.entities = self.entities.clone();
+
+        for model in self.models() {
+            let mut new_model = Model::new(model.serial_number());
+            let mut used = HashSet::new();
+            for generator in &assembly.generators {
+                for operator in &generator.operators {
+                    // Map the atoms to their copies to be able to copy the bonds
+                    let mut counters = HashMap::new();
+                    for chain in model
+                        .chains()
+                        .filter(|c| generator.chains.iter().any(|id| id == c.id()))
+                    {
+                        let mut copy = chain.clone();
+                        copy.apply_transformation(&operator.transformation);
+                        let mut name = chain.id().to_string();
+                        let mut index = 1;
+                        while used.contains(&name) {
+                            name = if index == 1 {
+                                format!("{}-{}", chain.id(), operator.id)
+                            } else {
+                                format!("{}-{}-{}", chain.id(), operator.id, index)
+                            };
+                            index += 1;
+                        }
+                        copy.set_id(&name);
+                        used.insert(name);
+                        for (original, new) in chain.atoms().zip(copy.atoms()) {
+                            counters.insert(original.counter(), new.counter());
+                        }
+                        new_model.add_chain(copy);
+                    }
+                    for (a, b, bond, details) in &self.bonds {
+                        if let (Some(a), Some(b)) = (counters.get(a), counters.get(b)) {
+                            pdb.bonds.push((*a, *b, *bond, details.clone()));
+                        }
+                    }
+                }
+            }
+            pdb.add_model(new_model);
+        }
+
+        // The secondary structure is still valid for the chains that kept their id
+        pdb.secondary_structure = self
+            .secondary_structure
+            .iter()
+            .filter(|s| assembly.chains().contains(&s.start().chain_id.as_str()))
+            .cloned()
+            .collect();
+        pdb.cis_peptides = self
+            .cis_peptides
+            .iter()
+            .filter(|c| assembly.chains().contains(&c.start.chain_id.as_str()))
+            .cloned()
+            .collect();
+        // As are the sites made up of residues of these chains only
+        pdb.sites = self
+            .sites
+            .iter()
+            .filter(|s| {
+                s.residues
+                    .iter()
+                    .all(|r| assembly.chains().contains(&r.chain_id.as_str()))
+            })
+            .cloned()
+            .collect();
+        Some(pdb)
+    }
+}
+
 impl<'a> PDB {
     /// Adds a Model to this PDB.
     pub fn add_model(&mut self, new_model: Model) {
@@ -982,7 +1657,18 @@ impl<'a> PDB {
     /// Get the bonds in this PDB file. Runtime is `O(bonds_count * 2 * atom_count)` because it
     /// has to iterate over all atoms to prevent borrowing problems.
     pub fn bonds(&self) -> impl DoubleEndedIterator<Item = (&Atom, &Atom, Bond)> + '_ {
-        self.bonds.iter().map(move |(a, b, bond)| {
+        self.bonds_with_details()
+            .map(|(atom1, atom2, bond, _)| (atom1, atom2, bond))
+    }
+
+    /// Get the bonds in this PDB file, with the additional details (like symmetry operators and
+    /// distances as given in LINK records and the `_struct_conn` mmCIF category) if these are known.
+    /// Runtime is `O(bonds_count * 2 * atom_count)` because it has to iterate over all atoms to
+    /// prevent borrowing problems.
+    pub fn bonds_with_details(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (&Atom, &Atom, Bond, Option<&BondDetails>)> + '_ {
+        self.bonds.iter().map(move |(a, b, bond, details)| {
             (
                 self.atoms()
                     .find(|atom| atom.counter() == *a)
@@ -991,6 +1677,7 @@ impl<'a> PDB {
                     .find(|atom| atom.counter() == *b)
                     .expect("Could not find an atom in the bonds list"),
                 *bond,
+                details.as_ref(),
             )
         })
     }
@@ -1010,14 +1697,188 @@ impl<'a> PDB {
             self.binary_find_atom(atom1.0, atom1.1)?.atom().counter(),
             self.binary_find_atom(atom2.0, atom2.1)?.atom().counter(),
             bond,
+            None,
         ));
         Some(())
     }
 
+    /// Add a bond of the given type with the given details to the list of bonds in this PDB.
+    /// The atoms are selected in the same way as in [`PDB::add_bond`].
+    /// If one of the atoms could not be found it returns `None` otherwise it
+    /// will return `Some(())`.
+    pub fn add_bond_with_details(
+        &mut self,
+        atom1: (usize, Option<&str>),
+        atom2: (usize, Option<&str>),
+        bond: Bond,
+        details: BondDetails,
+    ) -> Option<()> {
+        self.bonds.push((
+            self.binary_find_atom(atom1.0, atom1.1)?.atom().counter(),
+            self.binary_find_atom(atom2.0, atom2.1)?.atom().counter(),
+            bond,
+            Some(details),
+        ));
+        Some(())
+    }
+
+    /// Add the residues in the model at the given index of `ligand` (eg a docked pose read from an
+    /// SDF file) to the chain with the given identifier in every model of this PDB, together with
+    /// the bonds between their atoms. The chain is created if it does not exist yet, and a model
+    /// is created if this PDB has no models. The residues are numbered after the last residue of
+    /// the chain and the atoms after the highest atom serial number of the model. The residues are
+    /// copied, so `ligand` is not changed.
+    ///
+    /// ## Fails
+    /// It returns `None` if the model does not exist in `ligand` or if the chain identifier is
+    /// invalid, otherwise it returns `Some(())`.
+    pub fn add_ligand(
+        &mut self,
+        ligand: &PDB,
+        model_index: usize,
+        chain_id: impl AsRef<str>,
+    ) -> Option<()> {
+        let source = ligand.model(model_index)?;
+        let new_chain = Chain::new(chain_id)?;
+        if self.model_count() == 0 {
+            self.add_model(Model::new(1));
+        }
+        let mut new_bonds = Vec::new();
+        for model in self.models_mut() {
+            let mut atom_number = model.atoms().map(Atom::serial_number).max().unwrap_or(0);
+            if model.chains().all(|c| c.id() != new_chain.id()) {
+                model.add_chain(new_chain.clone());
+            }
+            let chain = model
+                .chains_mut()
+                .find(|c| c.id() == new_chain.id())
+                .expect("The chain was just added");
+            let mut residue_number = chain.residues().last().map_or(0, Residue::serial_number);
+            let mut counters = HashMap::new();
+            for original in source.residues() {
+                let mut residue = original.clone();
+                residue_number += 1;
+                residue.set_serial_number(residue_number);
+                // Cloning gives the atoms new counters, so keep track of these for the bonds
+                for (old, new) in original.atoms().zip(residue.atoms_mut()) {
+                    atom_number += 1;
+                    new.set_serial_number(atom_number);
+                    new.set_id(atom_number.to_string())
+                        .expect("A number is a valid atom ID");
+                    counters.insert(old.counter(), new.counter());
+                }
+                chain.add_residue(residue);
+            }
+            new_bonds.extend(ligand.bond_counters().filter_map(|(a, b, bond, details)| {
+                Some((*counters.get(a)?, *counters.get(b)?, *bond, details.clone()))
+            }));
+        }
+        self.bonds.extend(new_bonds);
+        Some(())
+    }
+
+    /// Get the bonds in this PDB with the raw counters of the atoms.
+    /// This is cheaper than [`PDB::bonds`] as it does not need to look up the atoms.
+    pub(crate) fn bond_counters(
+        &self,
+    ) -> impl Iterator<Item = &(usize, usize, Bond, Option<BondDetails>)> + '_ {
+        self.bonds.iter()
+    }
+
     /// Add a bond of the given type to the list of bonds in this PDB.
     /// The raw counters of the atoms are given.
-    pub(crate) fn add_bond_counters(&mut self, atom1: usize, atom2: usize, bond: Bond) {
-        self.bonds.push((atom1, atom2, bond));
+    pub(crate) fn add_bond_counters(
+        &mut self,
+        atom1: usize,
+        atom2: usize,
+        bond: Bond,
+        details: Option<BondDetails>,
+    ) {
+        self.bonds.push((atom1, atom2, bond, details));
+    }
+
+    /// Get the bonds between atoms in the first model that are listed in the header of a file (as
+    /// SSBOND or LINK records or in the `_struct_conn` mmCIF category), together with the hierarchy
+    /// of both atoms. These are the bonds with details other than the bond order or of a type other
+    /// than covalent or hydrogen.
+    pub(crate) fn annotated_bonds(&self) -> Vec<AnnotatedBond<'_>> {
+        let atoms: HashMap<usize, AtomConformerResidueChain<'_>> = self
+            .model(0)
+            .map(|model| {
+                model
+                    .atoms_with_hierarchy()
+                    .map(|h| (h.atom().counter(), h))
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.bonds
+            .iter()
+            .filter(|(_, _, bond, details)| {
+                details.as_ref().map_or(false, BondDetails::is_annotation)
+                    || !matches!(bond, Bond::Covalent | Bond::Hydrogen)
+            })
+            .filter_map(|(a, b, bond, details)| {
+                Some((
+                    atoms.get(a)?.clone(),
+                    atoms.get(b)?.clone(),
+                    *bond,
+                    details.as_ref(),
+                ))
+            })
+            .collect()
+    }
+
+    /// Get the chains with the given id in the model with the given serial number, or in all models
+    /// if no serial number is given, as used to place the annotations that apply to a chain.
+    pub(crate) fn chains_with_id_mut<'b>(
+        &'b mut self,
+        model: Option<usize>,
+        chain_id: &'b str,
+    ) -> impl Iterator<Item = &'b mut Chain> + 'b {
+        self.models
+            .iter_mut()
+            .filter(move |m| model.map_or(true, |serial| m.serial_number() == serial))
+            .flat_map(move |m| m.chains_mut().filter(move |c| c.id() == chain_id))
+    }
+
+    /// Find the atoms given by name, alternative location, and residue in every model, as used to
+    /// resolve bonds given in LINK records and the `_struct_conn` mmCIF category. For every model
+    /// in which both atoms could be found it returns the counters of the atoms and whether one of
+    /// the atoms is a metal. The boolean is false if any model is missing one of the atoms.
+    pub(crate) fn find_bond_partners(
+        &self,
+        atom1: (&str, Option<&str>, &ResidueReference),
+        atom2: (&str, Option<&str>, &ResidueReference),
+    ) -> (Vec<(usize, usize, bool)>, bool) {
+        let find =
+            |model: &Model, (name, alt_loc, residue): (&str, Option<&str>, &ResidueReference)| {
+                let mut search = Term::ChainId(residue.chain_id.clone())
+                    & Term::ResidueId(residue.serial_number, residue.insertion_code.clone())
+                    & Term::AtomName(name.to_string());
+                if alt_loc.is_some() {
+                    search = search
+                        & Term::ConformerAlternativeLocation(alt_loc.map(ToString::to_string));
+                }
+                model.find(search).next().map(|hierarchy| {
+                    let atom = hierarchy.atom();
+                    (
+                        atom.counter(),
+                        atom.element().map_or(false, Element::is_metal),
+                    )
+                })
+            };
+        let mut found = Vec::new();
+        let mut complete = true;
+        for model in self.models() {
+            if let (Some((counter1, metal1)), Some((counter2, metal2))) =
+                (find(model, atom1), find(model, atom2))
+            {
+                found.push((counter1, counter2, metal1 || metal2));
+            } else {
+                complete = false;
+            }
+        }
+        (found, complete)
     }
 
     /// Returns a HashMap with the chains in contact within a given distance.
@@ -1145,9 +2006,9 @@ impl<'a> PDB {
                     for &bond_len in lens {
                         if (dist - bond_len).abs() < BOND_LEN_THRESH {
                             if bond_len == LEN_C_O_DOUBLE {
-                                bonds.push((atom.counter(), other_atom.counter(), bond_type));
+                                bonds.push((atom.counter(), other_atom.counter(), bond_type, None));
                             }
-                            bonds.push((atom.counter(), other_atom.counter(), bond_type));
+                            bonds.push((atom.counter(), other_atom.counter(), bond_type, None));
                             break;
                         }
                     }
@@ -0,0 +1,72 @@
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Default)]
+/// The resolution and refinement statistics of a structure, as given in REMARK 2 and REMARK 3 of
+/// a PDB file or the `_refine` and `_refine_hist` categories of an mmCIF file.
+pub struct RefinementInfo {
+    /// The refinement program, eg 'REFMAC 5.1.24'
+    pub program: Option<String>,
+    /// The (highest) resolution in Ångström
+    pub resolution: Option<f64>,
+    /// The lowest resolution of the data used in refinement in Ångström
+    pub resolution_low: Option<f64>,
+    /// The completeness of the data used in refinement in percent
+    pub completeness: Option<f64>,
+    /// The number of reflections used in refinement
+    pub reflection_count: Option<usize>,
+    /// The R value for the working and test set together
+    pub r_all: Option<f64>,
+    /// The R value for the working set (R-work)
+    pub r_work: Option<f64>,
+    /// The R value for the test set (R-free)
+    pub r_free: Option<f64>,
+    /// The size of the test set in percent of all reflections
+    pub free_set_percentage: Option<f64>,
+    /// The number of reflections in the test set
+    pub free_set_count: Option<usize>,
+    /// The mean B value of the structure in Å²
+    pub mean_b_factor: Option<f64>,
+    /// The number of protein atoms used in refinement
+    pub protein_atoms: Option<usize>,
+    /// The number of nucleic acid atoms used in refinement
+    pub nucleic_acid_atoms: Option<usize>,
+    /// The number of heterogen (ligand) atoms used in refinement
+    pub heterogen_atoms: Option<usize>,
+    /// The number of solvent atoms used in refinement
+    pub solvent_atoms: Option<usize>,
+    /// The statistics of the collected data
+    pub reflections: Option<ReflectionStatistics>,
+}
+
+impl RefinementInfo {
+    /// Create a new RefinementInfo without any statistics
+    #[must_use]
+    pub fn new() -> Self {
+        RefinementInfo::default()
+    }
+
+    /// Determine if none of the statistics are known
+    pub fn is_empty(&self) -> bool {
+        self == &RefinementInfo::default()
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Default)]
+/// The overall statistics of the collected reflections, as given in REMARK 200 of a PDB file or the
+/// `_reflns` category of an mmCIF file.
+pub struct ReflectionStatistics {
+    /// The highest resolution of the data in Ångström
+    pub resolution_high: Option<f64>,
+    /// The lowest resolution of the data in Ångström
+    pub resolution_low: Option<f64>,
+    /// The number of unique reflections
+    pub unique_reflections: Option<usize>,
+    /// The completeness of the data in percent
+    pub completeness: Option<f64>,
+    /// The redundancy (multiplicity) of the data
+    pub redundancy: Option<f64>,
+    /// The merging R value (R-merge)
+    pub r_merge: Option<f64>,
+    /// The mean signal to noise ratio (<I/σ(I)>)
+    pub i_over_sigma: Option<f64>,
+}
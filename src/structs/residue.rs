@@ -17,6 +17,28 @@ pub struct Residue {
     insertion_code: Option<String>,
     /// The list of conformers making up this Residue
     conformers: Vec<Conformer>,
+    /// The label (`label_asym_id`, `label_seq_id`, and `label_entity_id`) and author defined
+    /// (`auth_asym_id` and `auth_seq_id`) identifiers of this Residue, if read from an mmCIF file
+    identifiers: ResidueIdentifiers,
+    /// The (zero based) index of this Residue in the deposited sequence of its chain, if it is
+    /// mapped onto that sequence
+    sequence_index: Option<usize>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// The identifiers of a residue in both the label and author defined schemes of mmCIF
+struct ResidueIdentifiers {
+    /// The `label_asym_id`
+    label_asym_id: Option<String>,
+    /// The `label_seq_id`, not defined for non-polymers
+    label_seq_id: Option<isize>,
+    /// The `label_entity_id`
+    label_entity_id: Option<String>,
+    /// The `auth_asym_id`
+    auth_asym_id: Option<String>,
+    /// The `auth_seq_id`
+    auth_seq_id: Option<isize>,
 }
 
 impl<'a> Residue {
@@ -39,6 +61,8 @@ impl<'a> Residue {
             serial_number: number,
             insertion_code: None,
             conformers: Vec::new(),
+            identifiers: ResidueIdentifiers::default(),
+            sequence_index: None,
         };
         if let Some(ic) = insertion_code {
             if !res.set_insertion_code(ic) {
@@ -90,6 +114,97 @@ impl<'a> Residue {
         (self.serial_number, self.insertion_code())
     }
 
+    /// Get the `label_asym_id` of the Residue, the chain identifier in the label scheme of mmCIF.
+    /// This is only known for residues read from mmCIF files. Note that an author defined chain
+    /// can consist of multiple label chains, eg one for the polymer and one for each ligand.
+    #[must_use]
+    pub fn label_asym_id(&self) -> Option<&str> {
+        self.identifiers.label_asym_id.as_deref()
+    }
+
+    /// Set the `label_asym_id` of the Residue.
+    pub fn set_label_asym_id(&mut self, id: Option<&str>) {
+        self.identifiers.label_asym_id = id.map(ToString::to_string);
+    }
+
+    /// Get the `label_seq_id` of the Residue, the position in the sequence of its entity counting
+    /// from 1. This is only known for polymer residues read from mmCIF files.
+    #[must_use]
+    pub const fn label_seq_id(&self) -> Option<isize> {
+        self.identifiers.label_seq_id
+    }
+
+    /// Set the `label_seq_id` of the Residue.
+    pub fn set_label_seq_id(&mut self, id: Option<isize>) {
+        self.identifiers.label_seq_id = id;
+    }
+
+    /// Get the `label_entity_id` of the Residue, the id of the [`Entity`] it is part of.
+    /// This is only known for residues read from mmCIF files.
+    #[must_use]
+    pub fn label_entity_id(&self) -> Option<&str> {
+        self.identifiers.label_entity_id.as_deref()
+    }
+
+    /// Set the `label_entity_id` of the Residue.
+    pub fn set_label_entity_id(&mut self, id: Option<&str>) {
+        self.identifiers.label_entity_id = id.map(ToString::to_string);
+    }
+
+    /// Get the `auth_asym_id` of the Residue, the author defined chain identifier. This is only
+    /// known for residues read from mmCIF files, and is equal to the id of the chain unless the
+    /// hierarchy was built from the label identifiers, see [`crate::ReadOptions::set_label_identifiers`].
+    #[must_use]
+    pub fn auth_asym_id(&self) -> Option<&str> {
+        self.identifiers.auth_asym_id.as_deref()
+    }
+
+    /// Set the `auth_asym_id` of the Residue.
+    pub fn set_auth_asym_id(&mut self, id: Option<&str>) {
+        self.identifiers.auth_asym_id = id.map(ToString::to_string);
+    }
+
+    /// Get the `auth_seq_id` of the Residue, the author defined serial number. This is only known
+    /// for residues read from mmCIF files, and is equal to the serial number of the residue unless
+    /// the hierarchy was built from the label identifiers.
+    #[must_use]
+    pub const fn auth_seq_id(&self) -> Option<isize> {
+        self.identifiers.auth_seq_id
+    }
+
+    /// Set the `auth_seq_id` of the Residue.
+    pub fn set_auth_seq_id(&mut self, id: Option<isize>) {
+        self.identifiers.auth_seq_id = id;
+    }
+
+    /// Get the index of the Residue in the deposited sequence of its chain (the sequence of its
+    /// [`Entity`], see [`crate::PDB::entity_of_chain`]). The index is zero based, so it is one
+    /// less than `_pdbx_poly_seq_scheme.seq_id`. It returns `None` if the residue is not mapped
+    /// onto the sequence, for example for ligands and waters.
+    ///
+    /// The mapping is taken from `_pdbx_poly_seq_scheme` in mmCIF files, otherwise it is made with
+    /// [`Chain::align_sequence`] when the structure is read.
+    ///
+    /// ```rust
+    /// use pdbtbx::*;
+    /// let (pdb, _errors) = ReadOptions::default().set_level(StrictnessLevel::Loose).read("example-pdbs/1yyf.pdb").unwrap();
+    /// let chain = pdb.chains().find(|c| c.id() == "A").unwrap();
+    /// let residue = chain.residues().find(|r| r.serial_number() == 210).unwrap();
+    /// assert_eq!(residue.sequence_index(), Some(209));
+    /// assert_eq!(chain.residue_at_sequence_index(209), Some(residue));
+    /// // Residues 175 to 209 are missing from the coordinates
+    /// assert_eq!(chain.residue_at_sequence_index(174), None);
+    /// ```
+    #[must_use]
+    pub const fn sequence_index(&self) -> Option<usize> {
+        self.sequence_index
+    }
+
+    /// Set the (zero based) index of the Residue in the deposited sequence of its chain.
+    pub fn set_sequence_index(&mut self, index: Option<usize>) {
+        self.sequence_index = index;
+    }
+
     /// The ID or name of the Residue, it will only give a value if there is only one conformer or if all conformers have the same name
     #[must_use]
     pub fn name(&self) -> Option<&str> {
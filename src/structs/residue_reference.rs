@@ -0,0 +1,74 @@
+use crate::structs::*;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+/// A reference to a residue as used in annotations on a structure, for example secondary structure elements.
+/// The residue is identified by its chain, serial number, and insertion code. The name is kept as well,
+/// as it is written out in most records.
+pub struct ResidueReference {
+    /// The identifier of the chain
+    pub chain_id: String,
+    /// The serial number of the residue
+    pub serial_number: isize,
+    /// The insertion code of the residue
+    pub insertion_code: Option<String>,
+    /// The name of the residue, eg 'ALA'
+    pub name: String,
+}
+
+impl ResidueReference {
+    /// Create a new ResidueReference
+    ///
+    /// ## Arguments
+    /// * `chain_id` - the identifier of the chain
+    /// * `residue_id` - the serial number and insertion code of the residue
+    /// * `name` - the name of the residue
+    #[must_use]
+    pub fn new(
+        chain_id: impl Into<String>,
+        residue_id: (isize, Option<&str>),
+        name: impl Into<String>,
+    ) -> Self {
+        ResidueReference {
+            chain_id: chain_id.into(),
+            serial_number: residue_id.0,
+            insertion_code: residue_id.1.map(ToString::to_string),
+            name: name.into(),
+        }
+    }
+
+    /// Create a new ResidueReference pointing to the given residue in the given chain
+    #[must_use]
+    pub fn from_residue(chain: &Chain, residue: &Residue) -> Self {
+        ResidueReference::new(chain.id(), residue.id(), residue.name().unwrap_or_default())
+    }
+
+    /// The serial number and insertion code of the referenced residue, in the same form as [`Residue::id`]
+    pub fn id(&self) -> (isize, Option<&str>) {
+        (self.serial_number, self.insertion_code.as_deref())
+    }
+
+    /// Determine if this reference points to the given residue in the given chain.
+    /// The name of the residue is not taken into account.
+    pub fn matches(&self, chain: &Chain, residue: &Residue) -> bool {
+        self.chain_id == chain.id() && self.id() == residue.id()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_residue_reference() {
+        let mut chain = Chain::new("A").unwrap();
+        let residue = Residue::new(12, Some("B"), None).unwrap();
+        chain.add_residue(residue.clone());
+        let a = ResidueReference::from_residue(&chain, &residue);
+        assert_eq!(a, ResidueReference::new("A", (12, Some("B")), ""));
+        assert_eq!(a.id(), (12, Some("B")));
+        assert!(a.matches(&chain, &residue));
+        assert!(!ResidueReference::new("A", (12, None), "").matches(&chain, &residue));
+    }
+}
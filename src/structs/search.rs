@@ -26,6 +26,16 @@ pub enum Term {
     ResidueInsertionCode(Option<String>),
     /// The residue serial number and insertion code combined, see [Residue::id].
     ResidueId(isize, Option<String>),
+    /// The label chain id of the residue eg `A`, only known for structures read from mmCIF, see [Residue::label_asym_id].
+    LabelAsymId(String),
+    /// The label residue number, only known for structures read from mmCIF, see [Residue::label_seq_id].
+    LabelSeqId(isize),
+    /// The label entity id of the residue eg `1`, only known for structures read from mmCIF, see [Residue::label_entity_id].
+    LabelEntityId(String),
+    /// The author defined chain id of the residue eg `A`, only known for structures read from mmCIF, see [Residue::auth_asym_id].
+    AuthAsymId(String),
+    /// The author defined residue number, only known for structures read from mmCIF, see [Residue::auth_seq_id].
+    AuthSeqId(isize),
     /// The conformer name eg `ALA`, see [Conformer::name].
     ConformerName(String),
     /// The conformer alternative location eg `Some("A")`, see [Conformer::alternative_location].
@@ -83,6 +93,11 @@ impl Term {
             }
             Self::ResidueInsertionCode(ic) => Some(ic.as_deref() == residue.insertion_code()),
             Self::ResidueId(s, ic) => Some((*s, ic.as_deref()) == residue.id()),
+            Self::LabelAsymId(id) => Some(Some(id.as_str()) == residue.label_asym_id()),
+            Self::LabelSeqId(s) => Some(Some(*s) == residue.label_seq_id()),
+            Self::LabelEntityId(id) => Some(Some(id.as_str()) == residue.label_entity_id()),
+            Self::AuthAsymId(id) => Some(Some(id.as_str()) == residue.auth_asym_id()),
+            Self::AuthSeqId(s) => Some(Some(*s) == residue.auth_seq_id()),
             _ => None,
         }
     }
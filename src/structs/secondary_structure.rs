@@ -0,0 +1,378 @@
+use crate::structs::*;
+use std::collections::HashMap;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// A secondary structure element as defined by the HELIX and SHEET records in PDB files
+/// or the `_struct_conf` and `_struct_sheet_range` categories in mmCIF files.
+pub enum SecondaryStructure {
+    /// A helix
+    Helix(Helix),
+    /// A single strand in a sheet
+    Strand(Strand),
+}
+
+impl SecondaryStructure {
+    /// The first residue of this secondary structure element
+    pub const fn start(&self) -> &ResidueReference {
+        match self {
+            SecondaryStructure::Helix(h) => &h.start,
+            SecondaryStructure::Strand(s) => &s.start,
+        }
+    }
+
+    /// The last residue of this secondary structure element
+    pub const fn end(&self) -> &ResidueReference {
+        match self {
+            SecondaryStructure::Helix(h) => &h.end,
+            SecondaryStructure::Strand(s) => &s.end,
+        }
+    }
+
+    /// Determine if this is a helix
+    pub const fn is_helix(&self) -> bool {
+        matches!(self, SecondaryStructure::Helix(_))
+    }
+
+    /// Determine if this is a strand
+    pub const fn is_strand(&self) -> bool {
+        matches!(self, SecondaryStructure::Strand(_))
+    }
+
+    /// Determine if the given residue in the given chain is part of this secondary structure element.
+    /// The residues are compared by their position in the chain, so residues that are not numbered
+    /// in order are handled correctly. If the first or last residue is not in the chain the residues
+    /// are compared by serial number and insertion code instead. To find the secondary structure of
+    /// all residues of a chain use [`PDB::secondary_structure_of_chain`].
+    pub fn contains(&self, chain: &Chain, residue: &Residue) -> bool {
+        let start = self.start();
+        let end = self.end();
+        if start.chain_id != chain.id() || end.chain_id != chain.id() {
+            return false;
+        }
+        let mut positions = [None; 3];
+        for (index, r) in chain.residues().enumerate() {
+            for (position, id) in positions
+                .iter_mut()
+                .zip([start.id(), residue.id(), end.id()])
+            {
+                if position.is_none() && r.id() == id {
+                    *position = Some(index);
+                }
+            }
+            if positions.iter().all(Option::is_some) {
+                break;
+            }
+        }
+        match positions {
+            [Some(start), Some(residue), Some(end)] => start <= residue && residue <= end,
+            _ => start.id() <= residue.id() && residue.id() <= end.id(),
+        }
+    }
+
+    /// Assign this element to the residues of the given chain that are part of it, in the same way
+    /// as [`SecondaryStructure::contains`]. There is one assignment for every residue of the chain,
+    /// residues that are already assigned are left as they are. The positions map the id of every
+    /// residue to its first position in the chain.
+    pub(crate) fn assign<'a>(
+        &'a self,
+        chain: &Chain,
+        positions: &HashMap<(isize, Option<&str>), usize>,
+        assignments: &mut [Option<&'a SecondaryStructure>],
+    ) {
+        let start = self.start();
+        let end = self.end();
+        if start.chain_id != chain.id() || end.chain_id != chain.id() {
+            return;
+        }
+        if let (Some(first), Some(last)) = (positions.get(&start.id()), positions.get(&end.id())) {
+            for (residue, assignment) in chain.residues().zip(assignments.iter_mut()) {
+                if positions
+                    .get(&residue.id())
+                    .map_or(false, |position| first <= position && position <= last)
+                {
+                    assignment.get_or_insert(self);
+                }
+            }
+        } else {
+            for (residue, assignment) in chain.residues().zip(assignments.iter_mut()) {
+                if start.id() <= residue.id() && residue.id() <= end.id() {
+                    assignment.get_or_insert(self);
+                }
+            }
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// A helix, see HELIX documentation wwPDB v3.30 <https://www.wwpdb.org/documentation/file-format-content/format33/sect5.html#HELIX>
+pub struct Helix {
+    /// The serial number of the helix
+    pub serial_number: usize,
+    /// The identifier of the helix, eg 'HELX_P1' or '1'
+    pub id: String,
+    /// The first residue of the helix
+    pub start: ResidueReference,
+    /// The last residue of the helix
+    pub end: ResidueReference,
+    /// The class of the helix
+    pub class: HelixClass,
+    /// A free text comment on the helix
+    pub comment: Option<String>,
+    /// The length of the helix in residues
+    pub length: Option<usize>,
+}
+
+impl Helix {
+    /// Create a new Helix, without a comment or length
+    #[must_use]
+    pub fn new(
+        serial_number: usize,
+        id: impl Into<String>,
+        start: ResidueReference,
+        end: ResidueReference,
+        class: HelixClass,
+    ) -> Self {
+        Helix {
+            serial_number,
+            id: id.into(),
+            start,
+            end,
+            class,
+            comment: None,
+            length: None,
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Default)]
+/// The class of a helix, as defined in the HELIX record
+pub enum HelixClass {
+    /// Right-handed alpha helix (1), the default
+    #[default]
+    RightHandedAlpha,
+    /// Right-handed omega helix (2)
+    RightHandedOmega,
+    /// Right-handed pi helix (3)
+    RightHandedPi,
+    /// Right-handed gamma helix (4)
+    RightHandedGamma,
+    /// Right-handed 3-10 helix (5)
+    RightHanded310,
+    /// Left-handed alpha helix (6)
+    LeftHandedAlpha,
+    /// Left-handed omega helix (7)
+    LeftHandedOmega,
+    /// Left-handed gamma helix (8)
+    LeftHandedGamma,
+    /// 2-7 ribbon/helix (9)
+    Ribbon27,
+    /// Polyproline helix (10)
+    Polyproline,
+}
+
+impl HelixClass {
+    /// Get the helix class from the number as used in PDB and mmCIF files (1-10)
+    pub const fn from_number(number: usize) -> Option<Self> {
+        match number {
+            1 => Some(HelixClass::RightHandedAlpha),
+            2 => Some(HelixClass::RightHandedOmega),
+            3 => Some(HelixClass::RightHandedPi),
+            4 => Some(HelixClass::RightHandedGamma),
+            5 => Some(HelixClass::RightHanded310),
+            6 => Some(HelixClass::LeftHandedAlpha),
+            7 => Some(HelixClass::LeftHandedOmega),
+            8 => Some(HelixClass::LeftHandedGamma),
+            9 => Some(HelixClass::Ribbon27),
+            10 => Some(HelixClass::Polyproline),
+            _ => None,
+        }
+    }
+
+    /// Get the number of this helix class as used in PDB and mmCIF files (1-10)
+    pub const fn number(self) -> usize {
+        match self {
+            HelixClass::RightHandedAlpha => 1,
+            HelixClass::RightHandedOmega => 2,
+            HelixClass::RightHandedPi => 3,
+            HelixClass::RightHandedGamma => 4,
+            HelixClass::RightHanded310 => 5,
+            HelixClass::LeftHandedAlpha => 6,
+            HelixClass::LeftHandedOmega => 7,
+            HelixClass::LeftHandedGamma => 8,
+            HelixClass::Ribbon27 => 9,
+            HelixClass::Polyproline => 10,
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// A strand in a sheet, see SHEET documentation wwPDB v3.30 <https://www.wwpdb.org/documentation/file-format-content/format33/sect5.html#SHEET>
+pub struct Strand {
+    /// The serial number of the strand within its sheet, starting at 1
+    pub strand_number: usize,
+    /// The identifier of the sheet this strand is part of
+    pub sheet_id: String,
+    /// The number of strands in the sheet
+    pub strand_count: usize,
+    /// The first residue of the strand
+    pub start: ResidueReference,
+    /// The last residue of the strand
+    pub end: ResidueReference,
+    /// The sense of the strand with respect to the previous strand in the sheet
+    pub sense: StrandSense,
+    /// The registration of this strand to the previous strand, if given
+    pub registration: Option<StrandRegistration>,
+}
+
+impl Strand {
+    /// Create a new Strand, without a registration
+    #[must_use]
+    pub fn new(
+        strand_number: usize,
+        sheet_id: impl Into<String>,
+        strand_count: usize,
+        start: ResidueReference,
+        end: ResidueReference,
+        sense: StrandSense,
+    ) -> Self {
+        Strand {
+            strand_number,
+            sheet_id: sheet_id.into(),
+            strand_count,
+            start,
+            end,
+            sense,
+            registration: None,
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+/// The sense of a strand with respect to the previous strand in the sheet
+pub enum StrandSense {
+    /// The first strand in a sheet (0)
+    First,
+    /// Parallel to the previous strand (1)
+    Parallel,
+    /// Anti-parallel to the previous strand (-1)
+    AntiParallel,
+}
+
+impl StrandSense {
+    /// Get the sense from the number as used in PDB files (-1, 0, or 1)
+    pub const fn from_number(number: isize) -> Option<Self> {
+        match number {
+            0 => Some(StrandSense::First),
+            1 => Some(StrandSense::Parallel),
+            -1 => Some(StrandSense::AntiParallel),
+            _ => None,
+        }
+    }
+
+    /// Get the number of this sense as used in PDB files (-1, 0, or 1)
+    pub const fn number(self) -> isize {
+        match self {
+            StrandSense::First => 0,
+            StrandSense::Parallel => 1,
+            StrandSense::AntiParallel => -1,
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq, Clone)]
+/// The registration of a strand to the previous strand in the sheet, given as
+/// a pair of atoms that form a hydrogen bond between the two strands.
+pub struct StrandRegistration {
+    /// The name of the atom in the current strand
+    pub current_atom: String,
+    /// The residue in the current strand
+    pub current: ResidueReference,
+    /// The name of the atom in the previous strand
+    pub previous_atom: String,
+    /// The residue in the previous strand
+    pub previous: ResidueReference,
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_helix_class() {
+        for number in 1..=10 {
+            assert_eq!(HelixClass::from_number(number).unwrap().number(), number);
+        }
+        assert_eq!(HelixClass::from_number(0), None);
+        assert_eq!(HelixClass::default(), HelixClass::RightHandedAlpha);
+    }
+
+    #[test]
+    fn contains_residues_out_of_order() {
+        let mut chain = Chain::new("A").unwrap();
+        for (serial_number, insertion_code) in [(5, None), (6, None), (1, None), (1, Some("A"))] {
+            let atom = Atom::new(false, 1, "", "CA", 0.0, 0.0, 0.0, 1.0, 0.0, "C", 0).unwrap();
+            chain.add_atom(atom, (serial_number, insertion_code), ("ALA", None));
+        }
+        let helix = SecondaryStructure::Helix(Helix::new(
+            1,
+            "1",
+            ResidueReference::new("A", (6, None), "ALA"),
+            ResidueReference::new("A", (1, Some("A")), "ALA"),
+            HelixClass::default(),
+        ));
+        let contained: Vec<bool> = chain
+            .residues()
+            .map(|r| helix.contains(&chain, r))
+            .collect();
+        assert_eq!(contained, [false, true, true, true]);
+        assert!(!helix.contains(&Chain::new("B").unwrap(), chain.residue(1).unwrap()));
+        let positions = chain
+            .residues()
+            .enumerate()
+            .map(|(index, r)| (r.id(), index))
+            .collect();
+        let mut assignments = vec![None; 4];
+        helix.assign(&chain, &positions, &mut assignments);
+        assert_eq!(
+            assignments,
+            [None, Some(&helix), Some(&helix), Some(&helix)]
+        );
+    }
+
+    #[test]
+    fn check_contains() {
+        let mut chain = Chain::new("A").unwrap();
+        let residues = [
+            Residue::new(9, None, None).unwrap(),
+            Residue::new(10, None, None).unwrap(),
+            Residue::new(10, Some("A"), None).unwrap(),
+            Residue::new(12, None, None).unwrap(),
+            Residue::new(13, None, None).unwrap(),
+        ];
+        for residue in &residues {
+            chain.add_residue(residue.clone());
+        }
+        let strand = SecondaryStructure::Strand(Strand::new(
+            1,
+            "A",
+            1,
+            ResidueReference::new("A", (10, None), "ALA"),
+            ResidueReference::new("A", (12, None), "ALA"),
+            StrandSense::First,
+        ));
+        assert!(strand.is_strand());
+        assert!(!strand.is_helix());
+        let contained: Vec<bool> = residues
+            .iter()
+            .map(|r| strand.contains(&chain, r))
+            .collect();
+        assert_eq!(contained, vec![false, true, true, true, false]);
+    }
+}
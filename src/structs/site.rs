@@ -0,0 +1,85 @@
+use crate::structs::*;
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// A site of interest in the structure, for example a binding site, listing the residues that
+/// make up the site. As given in the SITE records and REMARK 800 of a PDB file or the
+/// `_struct_site` and `_struct_site_gen` categories of an mmCIF file.
+///
+/// The residues can be found in the structure with [`crate::PDB::find`] using [`Site::search`].
+pub struct Site {
+    /// The identifier of the site, eg 'AC1'
+    pub id: String,
+    /// How the site was determined, eg 'SOFTWARE' or 'AUTHOR'
+    pub evidence: Option<String>,
+    /// The description of the site, eg 'BINDING SITE FOR RESIDUE ADP A 905'
+    pub details: Option<String>,
+    /// The residues that make up the site
+    pub residues: Vec<ResidueReference>,
+}
+
+impl Site {
+    /// Create a new Site with the given identifier and without any residues
+    #[must_use]
+    pub fn new(id: impl Into<String>) -> Self {
+        Site {
+            id: id.into(),
+            ..Site::default()
+        }
+    }
+
+    /// Determine if the given residue in the given chain is part of this site
+    pub fn contains(&self, chain: &Chain, residue: &Residue) -> bool {
+        self.residues.iter().any(|r| r.matches(chain, residue))
+    }
+
+    /// Get a search matching all atoms of the residues of this site, to be used in [`crate::PDB::find`].
+    ///
+    /// ```rust
+    /// use pdbtbx::*;
+    /// let (pdb, _errors) = ReadOptions::default().set_level(StrictnessLevel::Loose).read("example-pdbs/1kmk.pdb").unwrap();
+    /// let site = pdb.site("AC1").unwrap();
+    /// let mut residues = Vec::new();
+    /// for hierarchy in pdb.find(site.search()) {
+    ///     let residue = ResidueReference::from_residue(hierarchy.chain(), hierarchy.residue());
+    ///     if !residues.contains(&residue) {
+    ///         residues.push(residue);
+    ///     }
+    /// }
+    /// assert_eq!(residues, site.residues);
+    /// ```
+    pub fn search(&self) -> Search {
+        self.residues
+            .iter()
+            .fold(Search::Known(false), |search, residue| {
+                search
+                    | (Term::ChainId(residue.chain_id.clone())
+                        & Term::ResidueId(residue.serial_number, residue.insertion_code.clone()))
+            })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn site_search() {
+        let mut model = Model::new(1);
+        for (chain, serial_number) in [("A", 1), ("A", 2), ("B", 1)] {
+            let atom = Atom::new(false, 1, "", "CA", 0.0, 0.0, 0.0, 1.0, 0.0, "C", 0).unwrap();
+            model.add_atom(atom, chain, (serial_number, None), ("ALA", None));
+        }
+        let mut site = Site::new("AC1");
+        assert_eq!(model.find(site.search()).count(), 0);
+        site.residues
+            .push(ResidueReference::new("A", (2, None), "ALA"));
+        site.residues
+            .push(ResidueReference::new("B", (1, None), "ALA"));
+        assert_eq!(model.find(site.search()).count(), 2);
+        let chain = model.chain(0).unwrap();
+        assert!(!site.contains(chain, chain.residue(0).unwrap()));
+        assert!(site.contains(chain, chain.residue(1).unwrap()));
+    }
+}
@@ -0,0 +1,97 @@
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// The torsion tree of a flexible ligand, as given by the `ROOT`, `BRANCH`, and `TORSDOF` records
+/// of a PDBQT file. It describes which bonds AutoDock (Vina) can rotate while docking: the atoms
+/// in the rigid root, and the branches that rotate around the bond between an atom in their
+/// parent and their first atom. The atoms are given by their serial numbers in the model.
+///
+/// The free text `REMARK` records given with the ligand, like the docking score of a pose (see
+/// [`TorsionTree::vina_result`]), are kept with the tree as well.
+pub struct TorsionTree {
+    /// The serial number of the model this torsion tree belongs to
+    pub model: usize,
+    /// The serial numbers of the atoms in the rigid root
+    pub root: Vec<usize>,
+    /// The branches attached to the root
+    pub branches: Vec<TorsionBranch>,
+    /// The number of torsional degrees of freedom, if given
+    pub torsdof: Option<usize>,
+    /// The free text remarks given with this ligand, eg `VINA RESULT:    -7.2      0.000      0.000`
+    pub remarks: Vec<String>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// A branch of a [`TorsionTree`], which rotates around the bond between the `parent` and `child`
+/// atoms.
+pub struct TorsionBranch {
+    /// The serial number of the atom in the parent (the root or another branch) this branch is
+    /// attached to
+    pub parent: usize,
+    /// The serial number of the first atom of this branch
+    pub child: usize,
+    /// The serial numbers of the atoms in this branch, not including those in its sub branches
+    pub atoms: Vec<usize>,
+    /// The branches attached to this branch
+    pub branches: Vec<TorsionBranch>,
+}
+
+impl TorsionTree {
+    /// Create a new empty TorsionTree for the model with the given serial number
+    #[must_use]
+    pub fn new(model: usize) -> Self {
+        TorsionTree {
+            model,
+            ..TorsionTree::default()
+        }
+    }
+
+    /// Get the number of branches in this tree, including all sub branches. This is the number of
+    /// rotatable bonds.
+    pub fn branch_count(&self) -> usize {
+        fn count(branches: &[TorsionBranch]) -> usize {
+            branches.iter().map(|b| 1 + count(&b.branches)).sum()
+        }
+        count(&self.branches)
+    }
+
+    /// Get the serial numbers of all atoms in this tree, in the order they are given in a PDBQT
+    /// file: the root, followed by every branch before its sub branches.
+    pub fn atoms(&self) -> Vec<usize> {
+        fn add(branches: &[TorsionBranch], atoms: &mut Vec<usize>) {
+            for branch in branches {
+                atoms.extend(&branch.atoms);
+                add(&branch.branches, atoms);
+            }
+        }
+        let mut atoms = self.root.clone();
+        add(&self.branches, &mut atoms);
+        atoms
+    }
+
+    /// Get the result of AutoDock Vina for this pose, as given in the `VINA RESULT` remark: the
+    /// predicted binding affinity (kcal/mol), and the lower and upper bound of the RMSD to the best
+    /// pose.
+    ///
+    /// ```rust
+    /// use pdbtbx::*;
+    /// let mut tree = TorsionTree::new(1);
+    /// tree.remarks.push("VINA RESULT:    -7.2      1.500      2.250".to_string());
+    /// assert_eq!(tree.vina_result(), Some((-7.2, 1.5, 2.25)));
+    /// ```
+    pub fn vina_result(&self) -> Option<(f64, f64, f64)> {
+        self.remarks.iter().find_map(|remark| {
+            let values: Vec<f64> = remark
+                .trim()
+                .strip_prefix("VINA RESULT:")?
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<Result<_, _>>()
+                .ok()?;
+            match values.as_slice() {
+                [affinity, lower, upper] => Some((*affinity, *lower, *upper)),
+                _ => None,
+            }
+        })
+    }
+}
@@ -148,6 +148,47 @@ impl UnitCell {
     pub const fn size(&self) -> (f64, f64, f64) {
         (self.a, self.b, self.c)
     }
+
+    /// Create a `UnitCell` from its three box vectors, as used by simulation software.
+    /// Angles involving a vector of length zero are set to 90 degrees.
+    #[must_use]
+    pub fn from_vectors(a: [f64; 3], b: [f64; 3], c: [f64; 3]) -> Self {
+        let length = |v: [f64; 3]| (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+        let angle = |u: [f64; 3], v: [f64; 3]| {
+            let lengths = length(u) * length(v);
+            if lengths == 0.0 {
+                90.0
+            } else {
+                ((u[0] * v[0] + u[1] * v[1] + u[2] * v[2]) / lengths)
+                    .clamp(-1.0, 1.0)
+                    .acos()
+                    .to_degrees()
+            }
+        };
+        Self::new(
+            length(a),
+            length(b),
+            length(c),
+            angle(b, c),
+            angle(a, c),
+            angle(a, b),
+        )
+    }
+
+    /// Get the three box vectors of this `UnitCell`, with the a vector along the x axis and the b
+    /// vector in the xy plane. This is the convention used by PDB files and simulation software.
+    #[must_use]
+    pub fn vectors(&self) -> [[f64; 3]; 3] {
+        let (cos_alpha, cos_beta) = (self.alpha.to_radians().cos(), self.beta.to_radians().cos());
+        let (sin_gamma, cos_gamma) = self.gamma.to_radians().sin_cos();
+        let c_y = (cos_alpha - cos_beta * cos_gamma) / sin_gamma;
+        let c_z = (1.0 - cos_beta * cos_beta - c_y * c_y).max(0.0).sqrt();
+        [
+            [self.a, 0.0, 0.0],
+            [self.b * cos_gamma, self.b * sin_gamma, 0.0],
+            [self.c * cos_beta, self.c * c_y, self.c * c_z],
+        ]
+    }
 }
 
 impl Default for UnitCell {
@@ -169,4 +210,25 @@ mod tests {
         assert_eq!(a, b);
         assert_ne!(a, c);
     }
+
+    #[test]
+    fn vectors() {
+        let cell = UnitCell::new(10.0, 12.0, 15.0, 80.0, 95.0, 110.0);
+        let [a, b, c] = cell.vectors();
+        assert_eq!(a, [10.0, 0.0, 0.0]);
+        assert!(b[2] == 0.0 && c[2] > 0.0);
+        let back = UnitCell::from_vectors(a, b, c);
+        for (x, y) in [
+            (back.a(), 10.0),
+            (back.b(), 12.0),
+            (back.c(), 15.0),
+            (back.alpha(), 80.0),
+            (back.beta(), 95.0),
+            (back.gamma(), 110.0),
+        ] {
+            assert!((x - y).abs() < 1e-9);
+        }
+        let rectangular = UnitCell::from_vectors([2.0, 0.0, 0.0], [0.0, 3.0, 0.0], [0.0; 3]);
+        assert_eq!(rectangular, UnitCell::new(2.0, 3.0, 0.0, 90.0, 90.0, 90.0));
+    }
 }
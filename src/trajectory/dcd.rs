@@ -0,0 +1,363 @@
+use super::xdr::invalid;
+use super::Frame;
+use crate::structs::UnitCell;
+
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+/// The length of the AKMA time unit used for the time step in DCD files (ps)
+const AKMA_TIME: f64 = 0.048_888_21;
+
+/// The CHARMM version written in the header of new DCD files
+const CHARMM_VERSION: i32 = 24;
+
+/// The header of a DCD file, containing everything needed to read its frames
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct DcdHeader {
+    /// If the file is written in little endian byte order
+    little_endian: bool,
+    /// The number of atoms in every frame
+    pub(crate) atom_count: usize,
+    /// The step of the first frame
+    start: i32,
+    /// The number of steps between frames
+    interval: i32,
+    /// The length of a step (AKMA units)
+    timestep: f64,
+    /// If the frames contain a unit cell record
+    unit_cell: bool,
+    /// The size of the header, which is the offset of the first frame (bytes)
+    pub(crate) size: u64,
+}
+
+impl DcdHeader {
+    /// Read the header at the start of the given DCD file
+    pub(crate) fn read(input: &mut (impl Read + Seek)) -> io::Result<Self> {
+        let mut marker = [0; 4];
+        input.read_exact(&mut marker)?;
+        let little_endian = if i32::from_le_bytes(marker) == 84 {
+            true
+        } else if i32::from_be_bytes(marker) == 84 {
+            false
+        } else {
+            return Err(invalid(
+                "The file does not start with a DCD header record (DCD files with 64 bit record markers are not supported).",
+            ));
+        };
+        input.seek(SeekFrom::Start(0))?;
+
+        let control = read_record(input, little_endian)?;
+        if control.len() != 84 || &control[..4] != b"CORD" {
+            return Err(invalid(
+                "The first record of the file is not a coordinate (CORD) header.",
+            ));
+        }
+        let int = |index: usize| decode_i32(&control[4 + 4 * index..], little_endian);
+        let charmm = int(19) != 0;
+        if int(8) != 0 {
+            return Err(invalid("DCD files with fixed atoms are not supported."));
+        }
+        if charmm && int(11) != 0 {
+            return Err(invalid(
+                "DCD files with a fourth dimension are not supported.",
+            ));
+        }
+        let timestep = if charmm {
+            f64::from(decode_f32(&control[40..], little_endian))
+        } else {
+            decode_f64(&control[40..], little_endian)
+        };
+
+        // The titles are not used
+        read_record(input, little_endian)?;
+
+        let atoms = read_record(input, little_endian)?;
+        if atoms.len() != 4 {
+            return Err(invalid("The record with the number of atoms is invalid."));
+        }
+        let atom_count = usize::try_from(decode_i32(&atoms, little_endian))
+            .map_err(|_| invalid("The number of atoms is negative."))?;
+
+        Ok(Self {
+            little_endian,
+            atom_count,
+            start: int(1),
+            interval: int(2),
+            timestep,
+            unit_cell: charmm && int(10) != 0,
+            size: input.stream_position()?,
+        })
+    }
+
+    /// The size of a single frame (bytes)
+    pub(crate) const fn frame_size(&self) -> u64 {
+        let coordinates = 3 * (8 + 4 * self.atom_count as u64);
+        if self.unit_cell {
+            coordinates + 56
+        } else {
+            coordinates
+        }
+    }
+
+    /// The offset of the frame with the given index (bytes)
+    pub(crate) const fn frame_offset(&self, index: usize) -> u64 {
+        self.size + index as u64 * self.frame_size()
+    }
+
+    /// Read the frame with the given index, the input has to be positioned at the start of the frame
+    pub(crate) fn read_frame(&self, input: &mut impl Read, index: usize) -> io::Result<Frame> {
+        let unit_cell = if self.unit_cell {
+            let record = read_record(input, self.little_endian)?;
+            if record.len() != 48 {
+                return Err(invalid("The unit cell record of a frame is invalid."));
+            }
+            let value = |i: usize| decode_f64(&record[8 * i..], self.little_endian);
+            // CHARMM stores the cosines of the angles, while NAMD stores the angles in degrees
+            let mut angles = [value(4), value(3), value(1)];
+            if angles.iter().all(|angle| (-1.0..=1.0).contains(angle)) {
+                for angle in &mut angles {
+                    *angle = angle.acos().to_degrees();
+                }
+            }
+            Some(UnitCell::new(
+                value(0),
+                value(2),
+                value(5),
+                angles[0],
+                angles[1],
+                angles[2],
+            ))
+        } else {
+            None
+        };
+
+        let mut axes = Vec::with_capacity(3);
+        for _ in 0..3 {
+            let record = read_record(input, self.little_endian)?;
+            if record.len() != 4 * self.atom_count {
+                return Err(invalid(
+                    "The coordinate record of a frame does not have the right number of atoms.",
+                ));
+            }
+            axes.push(record);
+        }
+        let positions = (0..self.atom_count)
+            .map(|atom| {
+                let value = |axis: usize| {
+                    f64::from(decode_f32(&axes[axis][4 * atom..], self.little_endian))
+                };
+                (value(0), value(1), value(2))
+            })
+            .collect();
+
+        let step = usize::try_from(self.interval)
+            .ok()
+            .filter(|interval| *interval > 0)
+            .map(|interval| usize::try_from(self.start).unwrap_or(0) + index * interval);
+        #[allow(clippy::cast_precision_loss)]
+        let time = step
+            .filter(|_| self.timestep > 0.0)
+            .map(|step| step as f64 * self.timestep * AKMA_TIME);
+        Ok(Frame {
+            step,
+            time,
+            positions,
+            velocities: None,
+            unit_cell,
+        })
+    }
+}
+
+/// The state needed to write a DCD file, the header is written before the first frame and
+/// updated with the number of frames and timing when the file is finished
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct DcdWriter {
+    /// The number of atoms in every frame
+    atom_count: usize,
+    /// If the frames contain a unit cell, known after the first frame
+    unit_cell: Option<bool>,
+    /// The step and time of the first two frames
+    timing: Vec<(Option<usize>, Option<f64>)>,
+    /// The number of frames written
+    frames: usize,
+}
+
+impl DcdWriter {
+    /// Start a new DCD file with the given number of atoms
+    pub(crate) fn new(atom_count: usize) -> Self {
+        Self {
+            atom_count,
+            ..Self::default()
+        }
+    }
+
+    /// Write the given frame, preceded by the header if this is the first frame
+    pub(crate) fn write_frame(&mut self, output: &mut impl Write, frame: &Frame) -> io::Result<()> {
+        if frame.positions.len() != self.atom_count {
+            return Err(invalid(format!(
+                "The frame has {} positions but the DCD file has {} atoms.",
+                frame.positions.len(),
+                self.atom_count
+            )));
+        }
+        let unit_cell = *self
+            .unit_cell
+            .get_or_insert_with(|| frame.unit_cell.is_some());
+        if self.frames == 0 {
+            self.write_header(output)?;
+        }
+        if unit_cell {
+            let cell = frame.unit_cell.as_ref().ok_or_else(|| {
+                invalid("All frames of a DCD file need a unit cell if the first frame has one.")
+            })?;
+            let values = [
+                cell.a(),
+                cell.gamma(),
+                cell.b(),
+                cell.beta(),
+                cell.alpha(),
+                cell.c(),
+            ];
+            write_record(output, &values.map(f64::to_le_bytes).concat())?;
+        }
+        for axis in 0..3 {
+            let bytes: Vec<u8> = frame
+                .positions
+                .iter()
+                .flat_map(|pos| {
+                    #[allow(clippy::cast_possible_truncation)]
+                    let value = [pos.0, pos.1, pos.2][axis] as f32;
+                    value.to_le_bytes()
+                })
+                .collect();
+            write_record(output, &bytes)?;
+        }
+        if self.timing.len() < 2 {
+            self.timing.push((frame.step, frame.time));
+        }
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Finish the file, writing the header if no frames were written and updating the number
+    /// of frames and the timing in the header otherwise
+    pub(crate) fn finish(&mut self, output: &mut (impl Write + Seek)) -> io::Result<()> {
+        if self.frames == 0 {
+            return self.write_header(output);
+        }
+        let end = output.stream_position()?;
+        output.seek(SeekFrom::Start(0))?;
+        write_record(output, &self.control_record())?;
+        output.seek(SeekFrom::Start(end))?;
+        Ok(())
+    }
+
+    /// Write the complete header
+    fn write_header(&self, output: &mut impl Write) -> io::Result<()> {
+        write_record(output, &self.control_record())?;
+        let text = b"REMARKS Created by pdbtbx";
+        let mut title = [b' '; 80];
+        title[..text.len()].copy_from_slice(text);
+        write_record(output, &[1_i32.to_le_bytes().as_slice(), &title].concat())?;
+        #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+        write_record(output, &(self.atom_count as i32).to_le_bytes())
+    }
+
+    /// Create the first record of the header, with the number of frames and the timing
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap,
+        clippy::cast_precision_loss
+    )]
+    fn control_record(&self) -> Vec<u8> {
+        let mut control = [0_i32; 20];
+        control[0] = self.frames as i32;
+        control[2] = 1;
+        if let Some((Some(step), _)) = self.timing.first() {
+            control[1] = *step as i32;
+        }
+        let mut timestep = 0.0;
+        match self.timing.as_slice() {
+            [(Some(step0), Some(time0)), (Some(step1), Some(time1)), ..] if step1 > step0 => {
+                control[2] = (step1 - step0) as i32;
+                timestep = (time1 - time0) / (step1 - step0) as f64 / AKMA_TIME;
+            }
+            [(Some(step), Some(time)), ..] if *step > 0 => {
+                timestep = time / *step as f64 / AKMA_TIME;
+            }
+            _ => (),
+        }
+        control[3] = control[1] + control[0].saturating_sub(1) * control[2];
+        control[10] = i32::from(self.unit_cell == Some(true));
+        control[19] = CHARMM_VERSION;
+        let mut bytes = b"CORD".to_vec();
+        for (index, value) in control.iter().enumerate() {
+            if index == 9 {
+                bytes.extend((timestep as f32).to_le_bytes());
+            } else {
+                bytes.extend(value.to_le_bytes());
+            }
+        }
+        bytes
+    }
+}
+
+/// Read a Fortran unformatted record, which is enclosed in two markers with its length
+fn read_record(input: &mut impl Read, little_endian: bool) -> io::Result<Vec<u8>> {
+    let mut marker = [0; 4];
+    input.read_exact(&mut marker)?;
+    let length = usize::try_from(decode_i32(&marker, little_endian))
+        .map_err(|_| invalid("A record has a negative length."))?;
+    let mut record = Vec::new();
+    input
+        .by_ref()
+        .take(length as u64)
+        .read_to_end(&mut record)?;
+    if record.len() != length {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    input.read_exact(&mut marker)?;
+    if usize::try_from(decode_i32(&marker, little_endian)).ok() != Some(length) {
+        return Err(invalid("The markers around a record do not match."));
+    }
+    Ok(record)
+}
+
+/// Write a Fortran unformatted record in little endian byte order
+fn write_record(output: &mut impl Write, bytes: &[u8]) -> io::Result<()> {
+    #[allow(clippy::cast_possible_truncation)]
+    let marker = (bytes.len() as u32).to_le_bytes();
+    output.write_all(&marker)?;
+    output.write_all(bytes)?;
+    output.write_all(&marker)
+}
+
+/// Decode the 32 bit signed integer at the start of the given bytes
+fn decode_i32(bytes: &[u8], little_endian: bool) -> i32 {
+    let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+    if little_endian {
+        i32::from_le_bytes(bytes)
+    } else {
+        i32::from_be_bytes(bytes)
+    }
+}
+
+/// Decode the single precision float at the start of the given bytes
+fn decode_f32(bytes: &[u8], little_endian: bool) -> f32 {
+    let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+    if little_endian {
+        f32::from_le_bytes(bytes)
+    } else {
+        f32::from_be_bytes(bytes)
+    }
+}
+
+/// Decode the double precision float at the start of the given bytes
+fn decode_f64(bytes: &[u8], little_endian: bool) -> f64 {
+    let mut value = [0; 8];
+    value.copy_from_slice(&bytes[..8]);
+    if little_endian {
+        f64::from_le_bytes(value)
+    } else {
+        f64::from_be_bytes(value)
+    }
+}
@@ -0,0 +1,102 @@
+use crate::error::*;
+use crate::structs::*;
+
+/// A single frame of a trajectory, containing the coordinates of all atoms at one point in
+/// time. The atoms are in the same order as the atoms of the first model of the topology.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    /// The simulation step of this frame, if known
+    pub step: Option<usize>,
+    /// The simulation time of this frame (ps), if known
+    pub time: Option<f64>,
+    /// The positions of all atoms (Å), this is empty for TRR frames that only store velocities or forces
+    pub positions: Vec<(f64, f64, f64)>,
+    /// The velocities of all atoms (Å/ps), if stored in the trajectory
+    pub velocities: Option<Vec<(f64, f64, f64)>>,
+    /// The periodic box of this frame, if stored in the trajectory
+    pub unit_cell: Option<UnitCell>,
+}
+
+impl Frame {
+    /// Create a frame from the positions of the given atoms
+    #[must_use]
+    pub fn new(positions: Vec<(f64, f64, f64)>) -> Self {
+        Self {
+            step: None,
+            time: None,
+            positions,
+            velocities: None,
+            unit_cell: None,
+        }
+    }
+
+    /// Create a frame from the first model of the given PDB, with the velocities if all atoms
+    /// have a velocity and with the unit cell of the PDB. Returns `None` if the PDB has no models.
+    #[must_use]
+    pub fn from_pdb(pdb: &PDB) -> Option<Self> {
+        let model = pdb.model(0)?;
+        let velocities: Option<Vec<_>> = model.atoms().map(Atom::velocity).collect();
+        Some(Self {
+            step: None,
+            time: None,
+            positions: model.atoms().map(Atom::pos).collect(),
+            velocities,
+            unit_cell: pdb.unit_cell.clone(),
+        })
+    }
+
+    /// Get the number of atoms in this frame
+    #[must_use]
+    pub fn atom_count(&self) -> usize {
+        self.positions
+            .len()
+            .max(self.velocities.as_ref().map_or(0, Vec::len))
+    }
+
+    /// Set the coordinates of the atoms in the first model of the given PDB to the positions of
+    /// this frame, and the velocities and unit cell if they are stored in this frame.
+    /// # Errors
+    /// It fails if the PDB has no models, or if the number of atoms in the first model is not
+    /// the same as the number of atoms in this frame.
+    pub fn apply(&self, pdb: &mut PDB) -> Result<(), PDBError> {
+        let atom_count = self.atom_count();
+        let model = match pdb.model_mut(0) {
+            Some(model) if model.atom_count() == atom_count => model,
+            model => {
+                return Err(PDBError::new(
+                    ErrorLevel::BreakingError,
+                    "Atom count mismatch",
+                    format!(
+                        "The frame has {} atoms but the first model of the topology has {} atoms.",
+                        atom_count,
+                        model.map_or(0, |model| model.atom_count())
+                    ),
+                    Context::None,
+                ))
+            }
+        };
+        for (index, atom) in model.atoms_mut().enumerate() {
+            let result = self
+                .positions
+                .get(index)
+                .map_or(Ok(()), |pos| atom.set_pos(*pos))
+                .and_then(|_| {
+                    self.velocities
+                        .as_ref()
+                        .map_or(Ok(()), |velocities| atom.set_velocity(velocities[index]))
+                });
+            if let Err(message) = result {
+                return Err(PDBError::new(
+                    ErrorLevel::BreakingError,
+                    "Invalid coordinates",
+                    message,
+                    Context::None,
+                ));
+            }
+        }
+        if let Some(unit_cell) = &self.unit_cell {
+            pdb.unit_cell = Some(unit_cell.clone());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,524 @@
+/// Read and write CHARMM/NAMD DCD files
+mod dcd;
+/// The coordinates of a single frame
+mod frame;
+/// Read and write GROMACS TRR files
+mod trr;
+/// Read and write the big endian XDR primitives of the GROMACS formats
+mod xdr;
+/// Read and write GROMACS XTC files, including the compression of the coordinates
+mod xtc;
+
+pub use frame::Frame;
+
+use crate::error::*;
+use crate::structs::{Model, PDB};
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// The file formats of trajectories
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TrajectoryFormat {
+    /// CHARMM and NAMD DCD files, with single precision positions and optionally the unit cell
+    Dcd,
+    /// GROMACS XTC files, with compressed positions (precision of 0.01 Å when written by this crate)
+    Xtc,
+    /// GROMACS TRR files, with positions and optionally velocities and the unit cell
+    Trr,
+}
+
+impl TrajectoryFormat {
+    /// Determine the format from the extension of the given file name
+    pub fn from_filename(filename: impl AsRef<str>) -> Option<Self> {
+        match Path::new(filename.as_ref())
+            .extension()?
+            .to_str()?
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "dcd" => Some(Self::Dcd),
+            "xtc" => Some(Self::Xtc),
+            "trr" => Some(Self::Trr),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TrajectoryFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Dcd => "DCD",
+            Self::Xtc => "XTC",
+            Self::Trr => "TRR",
+        })
+    }
+}
+
+/// The format specific information needed to read frames
+#[derive(Debug, Clone, PartialEq)]
+enum FrameReader {
+    /// DCD files have a header with the layout of all frames
+    Dcd(dcd::DcdHeader),
+    /// XTC frames are self describing
+    Xtc,
+    /// TRR frames are self describing
+    Trr,
+}
+
+/// A trajectory file opened for reading, giving access to the coordinates of its frames. It
+/// is an iterator over the [`Frame`]s, and the frames can also be read into the topology with
+/// [`Trajectory::read_into`] or accessed directly with [`Trajectory::seek`] and
+/// [`Trajectory::read_frame`]. Frames are only read from the file when they are requested.
+///
+/// The topology is a [`PDB`] with the same atoms in the same order as the trajectory, for example
+/// read from the GROMACS or PDB file used to start the simulation. The coordinates of its first
+/// model are set to those of a frame with [`Trajectory::read_into`] or [`Frame::apply`], so any
+/// analysis of a `PDB` can be run on every frame of the simulation.
+///
+/// ```rust
+/// use pdbtbx::*;
+/// # let name = std::env::temp_dir().join("pdbtbx_trajectory_docs.dcd");
+/// # let name = name.to_str().unwrap();
+/// let (mut pdb, _errors) = ReadOptions::default()
+///     .set_level(StrictnessLevel::Loose)
+///     .read("example-pdbs/1ubq.pdb")
+///     .unwrap();
+///
+/// // Write a trajectory in which the protein moves along the x axis
+/// let mut writer = TrajectoryWriter::create(&pdb, name).unwrap();
+/// for step in 0..10 {
+///     let mut frame = Frame::from_pdb(&pdb).unwrap();
+///     for position in &mut frame.positions {
+///         position.0 += step as f64;
+///     }
+///     writer.write_frame(&frame).unwrap();
+/// }
+/// writer.finish().unwrap();
+///
+/// // Read the trajectory and update the coordinates of the topology with a frame
+/// let mut trajectory = Trajectory::open(&pdb, name).unwrap();
+/// assert_eq!(trajectory.frame_count(), 10);
+/// let x = pdb.atom(0).unwrap().x();
+/// trajectory.seek(4).unwrap();
+/// trajectory.read_into(&mut pdb).unwrap().unwrap();
+/// assert!((pdb.atom(0).unwrap().x() - x - 4.0).abs() < 0.001);
+///
+/// // Or iterate over the coordinates of all (remaining) frames
+/// for frame in trajectory {
+///     let frame = frame.unwrap();
+///     assert_eq!(frame.positions.len(), pdb.atom_count());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Trajectory<R> {
+    /// The file
+    input: R,
+    /// The format of the file
+    format: TrajectoryFormat,
+    /// The format specific information to read frames
+    reader: FrameReader,
+    /// The number of atoms in every frame
+    atom_count: usize,
+    /// The offset of every frame in the file (bytes)
+    offsets: Vec<u64>,
+    /// The index of the next frame
+    next: usize,
+    /// The context for errors
+    context: Context,
+}
+
+impl Trajectory<BufReader<File>> {
+    /// Open the trajectory with the given file name, the format is determined from the extension
+    /// (`.dcd`, `.xtc`, or `.trr`). The number of atoms has to be the same as the number of
+    /// atoms in the first model of the given topology.
+    /// # Errors
+    /// It fails if the extension is unknown, the file could not be opened, the header of the file
+    /// is invalid, or if the number of atoms does not match the topology.
+    pub fn open(topology: &PDB, filename: impl AsRef<str>) -> Result<Self, PDBError> {
+        let filename = filename.as_ref();
+        let format = TrajectoryFormat::from_filename(filename).ok_or_else(|| {
+            PDBError::new(
+                ErrorLevel::BreakingError,
+                "Incorrect extension",
+                "Could not determine the type of the given trajectory, make it .dcd, .xtc, or .trr",
+                Context::show(filename),
+            )
+        })?;
+        Self::open_with_format(topology, filename, format)
+    }
+
+    /// Open the trajectory with the given file name in the given format. The number of atoms has
+    /// to be the same as the number of atoms in the first model of the given topology.
+    /// # Errors
+    /// It fails if the file could not be opened, the header of the file is invalid, or if the
+    /// number of atoms does not match the topology.
+    pub fn open_with_format(
+        topology: &PDB,
+        filename: impl AsRef<str>,
+        format: TrajectoryFormat,
+    ) -> Result<Self, PDBError> {
+        let filename = filename.as_ref();
+        let file = File::open(filename).map_err(|_| {
+            PDBError::new(
+                ErrorLevel::BreakingError,
+                "Could not open file",
+                "Could not open the specified file, make sure the path is correct, you have permission, and that it is not open in another program.",
+                Context::show(filename),
+            )
+        })?;
+        Self::new(
+            topology,
+            BufReader::new(file),
+            format,
+            Context::show(filename),
+        )
+    }
+}
+
+impl<R: Read + Seek> Trajectory<R> {
+    /// Read the trajectory from the given input in the given format. The number of atoms has to
+    /// be the same as the number of atoms in the first model of the given topology.
+    /// # Errors
+    /// It fails if the header of the input is invalid, or if the number of atoms does not match
+    /// the topology.
+    pub fn from_reader(
+        topology: &PDB,
+        input: R,
+        format: TrajectoryFormat,
+    ) -> Result<Self, PDBError> {
+        Self::new(topology, input, format, Context::None)
+    }
+
+    /// Read the header and find all frames of the trajectory
+    fn new(
+        topology: &PDB,
+        mut input: R,
+        format: TrajectoryFormat,
+        context: Context,
+    ) -> Result<Self, PDBError> {
+        let index = |input: &mut R| -> io::Result<(FrameReader, usize, Vec<u64>)> {
+            match format {
+                TrajectoryFormat::Dcd => {
+                    let header = dcd::DcdHeader::read(input)?;
+                    let length = input.seek(SeekFrom::End(0))?;
+                    #[allow(clippy::cast_possible_truncation)]
+                    let frames =
+                        (length.saturating_sub(header.size) / header.frame_size()) as usize;
+                    let offsets = (0..frames).map(|i| header.frame_offset(i)).collect();
+                    let atom_count = header.atom_count;
+                    Ok((FrameReader::Dcd(header), atom_count, offsets))
+                }
+                TrajectoryFormat::Xtc => {
+                    let (atom_count, offsets) = xtc::scan(input)?;
+                    Ok((FrameReader::Xtc, atom_count, offsets))
+                }
+                TrajectoryFormat::Trr => {
+                    let (atom_count, offsets) = trr::scan(input)?;
+                    Ok((FrameReader::Trr, atom_count, offsets))
+                }
+            }
+        };
+        let (reader, atom_count, offsets) =
+            index(&mut input).map_err(|error| file_error(format, error, &context))?;
+
+        let topology_atoms = topology.model(0).map_or(0, Model::atom_count);
+        if topology_atoms != atom_count {
+            return Err(PDBError::new(
+                ErrorLevel::BreakingError,
+                "Atom count mismatch",
+                format!(
+                    "The trajectory has {atom_count} atoms but the first model of the topology has {topology_atoms} atoms."
+                ),
+                context,
+            ));
+        }
+        Ok(Self {
+            input,
+            format,
+            reader,
+            atom_count,
+            offsets,
+            next: 0,
+            context,
+        })
+    }
+
+    /// Get the format of this trajectory
+    pub const fn format(&self) -> TrajectoryFormat {
+        self.format
+    }
+
+    /// Get the number of frames in this trajectory
+    pub fn frame_count(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Get the number of atoms in every frame of this trajectory
+    pub const fn atom_count(&self) -> usize {
+        self.atom_count
+    }
+
+    /// Get the index of the frame that is read next
+    pub const fn position(&self) -> usize {
+        self.next
+    }
+
+    /// Go to the frame with the given index, so that it is the next frame read by the iterator
+    /// or [`Trajectory::read_into`]. Seeking to the frame count ends the iteration.
+    /// # Errors
+    /// It fails if the index is larger than the number of frames.
+    pub fn seek(&mut self, index: usize) -> Result<(), PDBError> {
+        if index > self.frame_count() {
+            return Err(PDBError::new(
+                ErrorLevel::BreakingError,
+                "Frame out of range",
+                format!(
+                    "Frame {index} was requested but the trajectory has {} frames.",
+                    self.frame_count()
+                ),
+                self.context.clone(),
+            ));
+        }
+        self.next = index;
+        Ok(())
+    }
+
+    /// Read the frame with the given index, after which the next frame is the one following it.
+    /// # Errors
+    /// It fails if the index is out of range or the frame is invalid.
+    pub fn read_frame(&mut self, index: usize) -> Result<Frame, PDBError> {
+        self.seek(index)?;
+        let offset = *self.offsets.get(index).ok_or_else(|| {
+            PDBError::new(
+                ErrorLevel::BreakingError,
+                "Frame out of range",
+                format!(
+                    "Frame {index} was requested but the trajectory has {} frames.",
+                    self.frame_count()
+                ),
+                self.context.clone(),
+            )
+        })?;
+        self.next = index + 1;
+        let input = &mut self.input;
+        let frame = input
+            .seek(SeekFrom::Start(offset))
+            .and_then(|_| match &self.reader {
+                FrameReader::Dcd(header) => header.read_frame(input, index),
+                FrameReader::Xtc => xtc::read_frame(input),
+                FrameReader::Trr => trr::read_frame(input),
+            });
+        frame.map_err(|error| file_error(self.format, error, &self.context))
+    }
+
+    /// Read the next frame and set the coordinates of the first model of the given PDB (see
+    /// [`Frame::apply`]). Returns `None` if there are no frames left.
+    /// # Errors
+    /// It fails if the frame is invalid or does not have the same number of atoms as the PDB.
+    pub fn read_into(&mut self, pdb: &mut PDB) -> Option<Result<(), PDBError>> {
+        self.next()
+            .map(|frame| frame.and_then(|frame| frame.apply(pdb)))
+    }
+}
+
+impl<R: Read + Seek> Iterator for Trajectory<R> {
+    type Item = Result<Frame, PDBError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        (self.next < self.frame_count()).then(|| self.read_frame(self.next))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.frame_count().saturating_sub(self.next);
+        (remaining, Some(remaining))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.next = self.next.saturating_add(n).min(self.frame_count());
+        self.next()
+    }
+}
+
+impl<R: Read + Seek> ExactSizeIterator for Trajectory<R> {}
+
+/// The format specific state needed to write frames
+#[derive(Debug, Clone, PartialEq)]
+enum FrameWriter {
+    /// DCD files have a header that is updated when the file is finished
+    Dcd(dcd::DcdWriter),
+    /// XTC frames are written with the given precision
+    Xtc(f32),
+    /// TRR frames are self describing
+    Trr,
+}
+
+/// A trajectory file opened for writing. Frames are written with [`TrajectoryWriter::write_frame`]
+/// or [`TrajectoryWriter::write_pdb`], and the file has to be completed with
+/// [`TrajectoryWriter::finish`] to write the number of frames in the header of DCD files.
+#[derive(Debug)]
+pub struct TrajectoryWriter<W: Write + Seek> {
+    /// The file
+    output: W,
+    /// The format of the file
+    format: TrajectoryFormat,
+    /// The format specific state
+    writer: FrameWriter,
+    /// The number of atoms in every frame
+    atom_count: usize,
+    /// The context for errors
+    context: Context,
+}
+
+impl TrajectoryWriter<BufWriter<File>> {
+    /// Create a trajectory file with the given name for the atoms of the first model of the given
+    /// topology, the format is determined from the extension (`.dcd`, `.xtc`, or `.trr`).
+    /// # Errors
+    /// It fails if the extension is unknown or if the file could not be created.
+    pub fn create(topology: &PDB, filename: impl AsRef<str>) -> Result<Self, PDBError> {
+        let filename = filename.as_ref();
+        let format = TrajectoryFormat::from_filename(filename).ok_or_else(|| {
+            PDBError::new(
+                ErrorLevel::BreakingError,
+                "Incorrect extension",
+                "Could not determine the type of the given trajectory, make it .dcd, .xtc, or .trr",
+                Context::show(filename),
+            )
+        })?;
+        Self::create_with_format(topology, filename, format)
+    }
+
+    /// Create a trajectory file with the given name in the given format for the atoms of the
+    /// first model of the given topology.
+    /// # Errors
+    /// It fails if the file could not be created.
+    pub fn create_with_format(
+        topology: &PDB,
+        filename: impl AsRef<str>,
+        format: TrajectoryFormat,
+    ) -> Result<Self, PDBError> {
+        let filename = filename.as_ref();
+        let file = File::create(filename).map_err(|_| {
+            PDBError::new(
+                ErrorLevel::BreakingError,
+                "Could not open file",
+                "Could not open the file for writing, make sure you have permission for this file and no other program is currently using it.",
+                Context::show(filename),
+            )
+        })?;
+        let atom_count = topology.model(0).map_or(0, Model::atom_count);
+        let mut writer = Self::new(BufWriter::new(file), format, atom_count);
+        writer.context = Context::show(filename);
+        Ok(writer)
+    }
+}
+
+impl<W: Write + Seek> TrajectoryWriter<W> {
+    /// Write a trajectory in the given format with the given number of atoms to the given output
+    pub fn new(output: W, format: TrajectoryFormat, atom_count: usize) -> Self {
+        let writer = match format {
+            TrajectoryFormat::Dcd => FrameWriter::Dcd(dcd::DcdWriter::new(atom_count)),
+            TrajectoryFormat::Xtc => FrameWriter::Xtc(xtc::DEFAULT_PRECISION),
+            TrajectoryFormat::Trr => FrameWriter::Trr,
+        };
+        Self {
+            output,
+            format,
+            writer,
+            atom_count,
+            context: Context::None,
+        }
+    }
+
+    /// Set the precision of the coordinates in XTC files, as the number of steps per nanometer.
+    /// The default is 1000, so 0.01 Å. This has no effect on other formats.
+    #[must_use]
+    pub fn with_precision(mut self, precision: f32) -> Self {
+        if let FrameWriter::Xtc(current) = &mut self.writer {
+            *current = precision;
+        }
+        self
+    }
+
+    /// Get the format of this trajectory
+    pub const fn format(&self) -> TrajectoryFormat {
+        self.format
+    }
+
+    /// Write the given frame. DCD files only store the positions and unit cell, and XTC files
+    /// only store the positions, unit cell, step, and time.
+    /// # Errors
+    /// It fails if the frame does not have the same number of atoms as the trajectory, or if it
+    /// could not be written.
+    pub fn write_frame(&mut self, frame: &Frame) -> Result<(), PDBError> {
+        if frame.atom_count() != self.atom_count
+            || (frame.positions.is_empty() && self.format != TrajectoryFormat::Trr)
+        {
+            return Err(PDBError::new(
+                ErrorLevel::BreakingError,
+                "Atom count mismatch",
+                format!(
+                    "The frame has {} positions but the trajectory has {} atoms.",
+                    frame.positions.len(),
+                    self.atom_count
+                ),
+                self.context.clone(),
+            ));
+        }
+        let output = &mut self.output;
+        match &mut self.writer {
+            FrameWriter::Dcd(writer) => writer.write_frame(output, frame),
+            FrameWriter::Xtc(precision) => xtc::write_frame(output, frame, *precision),
+            FrameWriter::Trr => trr::write_frame(output, frame),
+        }
+        .map_err(|error| file_error(self.format, error, &self.context))
+    }
+
+    /// Write the coordinates of the first model of the given PDB as a frame (see [`Frame::from_pdb`]).
+    /// # Errors
+    /// It fails if the PDB has no models, if it does not have the same number of atoms as the
+    /// trajectory, or if the frame could not be written.
+    pub fn write_pdb(&mut self, pdb: &PDB) -> Result<(), PDBError> {
+        let frame = Frame::from_pdb(pdb).ok_or_else(|| {
+            PDBError::new(
+                ErrorLevel::BreakingError,
+                "No models",
+                "The PDB does not contain any models to write as a frame.",
+                self.context.clone(),
+            )
+        })?;
+        self.write_frame(&frame)
+    }
+
+    /// Finish the trajectory by completing the header of DCD files and flushing the output, after
+    /// which the output is returned.
+    /// # Errors
+    /// It fails if the output could not be written.
+    pub fn finish(mut self) -> Result<W, PDBError> {
+        let output = &mut self.output;
+        match &mut self.writer {
+            FrameWriter::Dcd(writer) => writer.finish(output),
+            _ => Ok(()),
+        }
+        .and_then(|_| output.flush())
+        .map_err(|error| file_error(self.format, error, &self.context))?;
+        Ok(self.output)
+    }
+}
+
+/// Create the error for an invalid trajectory file or a failure to read or write it
+fn file_error(format: TrajectoryFormat, error: io::Error, context: &Context) -> PDBError {
+    let description = match error.kind() {
+        io::ErrorKind::UnexpectedEof => "The file ends in the middle of a frame.".to_string(),
+        io::ErrorKind::InvalidData => error.to_string(),
+        _ => format!("The file could not be read or written: {error}"),
+    };
+    PDBError::new(
+        ErrorLevel::BreakingError,
+        format!("Invalid {format} file"),
+        description,
+        context.clone(),
+    )
+}
@@ -0,0 +1,232 @@
+use super::xdr::*;
+use super::xtc::{read_box, write_box};
+use super::Frame;
+
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+/// The magic number at the start of every TRR frame
+const MAGIC: i32 = 1993;
+
+/// The version string in the header of every TRR frame
+const VERSION: &[u8] = b"GMX_trn_file";
+
+/// The header of a TRR frame
+struct Header {
+    /// The sizes of all blocks of data after the header (bytes)
+    data_size: u64,
+    /// If the real numbers are stored in double precision
+    double: bool,
+    /// The size of the periodic box (bytes)
+    box_size: usize,
+    /// The size of the virial (bytes)
+    virial_size: usize,
+    /// The size of the pressure (bytes)
+    pressure_size: usize,
+    /// The size of the positions (bytes)
+    positions_size: usize,
+    /// The size of the velocities (bytes)
+    velocities_size: usize,
+    /// The size of the forces (bytes)
+    forces_size: usize,
+    /// The number of atoms
+    atom_count: usize,
+    /// The simulation step
+    step: usize,
+    /// The simulation time (ps)
+    time: f64,
+}
+
+/// Read the header of a TRR frame
+fn read_header(input: &mut impl Read) -> io::Result<Header> {
+    let magic = read_i32(input)?;
+    if magic != MAGIC {
+        return Err(invalid(format!(
+            "The magic number of a TRR frame is {magic} instead of {MAGIC}."
+        )));
+    }
+    read_i32(input)?;
+    let length = usize::try_from(read_i32(input)?).unwrap_or(0);
+    if read_opaque(input, length)? != VERSION {
+        return Err(invalid("The version string of a TRR frame is invalid."));
+    }
+    let mut sizes = [0; 13];
+    for size in &mut sizes {
+        *size = usize::try_from(read_i32(input)?)
+            .map_err(|_| invalid("The header of a TRR frame contains a negative size."))?;
+    }
+    let [ir_size, e_size, box_size, virial_size, pressure_size, top_size, sym_size, positions_size, velocities_size, forces_size, atom_count, step, _] =
+        sizes;
+    let real_size = if box_size > 0 {
+        box_size / 9
+    } else if atom_count == 0 {
+        4
+    } else {
+        positions_size.max(velocities_size).max(forces_size) / (3 * atom_count)
+    };
+    let double =
+        match real_size {
+            4 => false,
+            8 => true,
+            _ => return Err(invalid(
+                "The sizes in the header of a TRR frame do not match single or double precision.",
+            )),
+        };
+    let time = read_real(input, double)?;
+    read_real(input, double)?;
+    let data_size = [
+        ir_size,
+        e_size,
+        box_size,
+        virial_size,
+        pressure_size,
+        top_size,
+        sym_size,
+        positions_size,
+        velocities_size,
+        forces_size,
+    ]
+    .iter()
+    .map(|size| *size as u64)
+    .sum();
+    Ok(Header {
+        data_size,
+        double,
+        box_size,
+        virial_size,
+        pressure_size,
+        positions_size,
+        velocities_size,
+        forces_size,
+        atom_count,
+        step,
+        time,
+    })
+}
+
+/// Find the offsets of all frames in the given TRR file, together with the number of atoms
+pub(crate) fn scan(input: &mut (impl Read + Seek)) -> io::Result<(usize, Vec<u64>)> {
+    let length = input.seek(SeekFrom::End(0))?;
+    input.seek(SeekFrom::Start(0))?;
+    let mut atom_count = None;
+    let mut offsets = Vec::new();
+    let mut offset = 0;
+    while offset < length {
+        offsets.push(offset);
+        let header = read_header(input)?;
+        if *atom_count.get_or_insert(header.atom_count) != header.atom_count {
+            return Err(invalid(
+                "The number of atoms changes between frames of the trajectory.",
+            ));
+        }
+        offset = input.seek(SeekFrom::Current(
+            i64::try_from(header.data_size).unwrap_or(i64::MAX),
+        ))?;
+        if offset > length {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+    }
+    Ok((atom_count.unwrap_or(0), offsets))
+}
+
+/// Read the frame at the current position of the input, the virial, pressure, and forces are skipped
+pub(crate) fn read_frame(input: &mut impl Read) -> io::Result<Frame> {
+    let header = read_header(input)?;
+    let unit_cell = if header.box_size > 0 {
+        read_box(input, header.double)?
+    } else {
+        None
+    };
+    skip(input, header.virial_size + header.pressure_size)?;
+    let mut read_vectors = |size: usize| {
+        if size == 0 {
+            return Ok(None);
+        }
+        (0..header.atom_count)
+            .map(|_| {
+                Ok((
+                    read_real(input, header.double)? * 10.0,
+                    read_real(input, header.double)? * 10.0,
+                    read_real(input, header.double)? * 10.0,
+                ))
+            })
+            .collect::<io::Result<Vec<_>>>()
+            .map(Some)
+    };
+    let positions = read_vectors(header.positions_size)?.unwrap_or_default();
+    let velocities = read_vectors(header.velocities_size)?;
+    skip(input, header.forces_size)?;
+    Ok(Frame {
+        step: Some(header.step),
+        time: Some(header.time),
+        positions,
+        velocities,
+        unit_cell,
+    })
+}
+
+/// Write the given frame in single precision, with the periodic box, positions, and velocities
+pub(crate) fn write_frame(output: &mut impl Write, frame: &Frame) -> io::Result<()> {
+    let atom_count = frame.atom_count();
+    let count = i32::try_from(atom_count)
+        .ok()
+        .filter(|count| *count <= i32::MAX / 12)
+        .ok_or_else(|| invalid("There are too many atoms for a TRR file."))?;
+    let vectors_size = |present: bool| if present { 12 * count } else { 0 };
+    if (!frame.positions.is_empty() && frame.positions.len() != atom_count)
+        || frame
+            .velocities
+            .as_ref()
+            .map_or(false, |v| v.len() != atom_count)
+    {
+        return Err(invalid(
+            "The positions and velocities of a frame do not have the same number of atoms.",
+        ));
+    }
+    write_i32(output, MAGIC)?;
+    write_i32(output, 13)?;
+    write_i32(output, 12)?;
+    write_opaque(output, VERSION)?;
+    for size in [
+        0,
+        0,
+        if frame.unit_cell.is_some() { 36 } else { 0 },
+        0,
+        0,
+        0,
+        0,
+        vectors_size(!frame.positions.is_empty()),
+        vectors_size(frame.velocities.is_some()),
+        0,
+        count,
+        i32::try_from(frame.step.unwrap_or(0)).unwrap_or(i32::MAX),
+        0,
+    ] {
+        write_i32(output, size)?;
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    write_f32(output, frame.time.unwrap_or(0.0) as f32)?;
+    write_f32(output, 0.0)?;
+    if frame.unit_cell.is_some() {
+        write_box(output, frame.unit_cell.as_ref())?;
+    }
+    let positions = (!frame.positions.is_empty()).then_some(&frame.positions);
+    for vectors in [positions, frame.velocities.as_ref()].into_iter().flatten() {
+        for vector in vectors {
+            for value in [vector.0, vector.1, vector.2] {
+                #[allow(clippy::cast_possible_truncation)]
+                write_f32(output, (value / 10.0) as f32)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Skip the given number of bytes
+fn skip(input: &mut impl Read, bytes: usize) -> io::Result<()> {
+    let skipped = io::copy(&mut input.by_ref().take(bytes as u64), &mut io::sink())?;
+    if skipped == bytes as u64 {
+        Ok(())
+    } else {
+        Err(io::ErrorKind::UnexpectedEof.into())
+    }
+}
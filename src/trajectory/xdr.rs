@@ -0,0 +1,89 @@
+use std::io::{self, Read, Write};
+
+/// Read a 32 bit signed integer
+pub(crate) fn read_i32(input: &mut impl Read) -> io::Result<i32> {
+    let mut bytes = [0; 4];
+    input.read_exact(&mut bytes)?;
+    Ok(i32::from_be_bytes(bytes))
+}
+
+/// Read a 32 bit unsigned integer
+pub(crate) fn read_u32(input: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    input.read_exact(&mut bytes)?;
+    Ok(u32::from_be_bytes(bytes))
+}
+
+/// Read a 64 bit unsigned integer
+pub(crate) fn read_u64(input: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    input.read_exact(&mut bytes)?;
+    Ok(u64::from_be_bytes(bytes))
+}
+
+/// Read a single precision float
+pub(crate) fn read_f32(input: &mut impl Read) -> io::Result<f32> {
+    let mut bytes = [0; 4];
+    input.read_exact(&mut bytes)?;
+    Ok(f32::from_be_bytes(bytes))
+}
+
+/// Read a double precision float
+pub(crate) fn read_f64(input: &mut impl Read) -> io::Result<f64> {
+    let mut bytes = [0; 8];
+    input.read_exact(&mut bytes)?;
+    Ok(f64::from_be_bytes(bytes))
+}
+
+/// Read a real number that is stored as a float or a double depending on the precision of the file
+pub(crate) fn read_real(input: &mut impl Read, double: bool) -> io::Result<f64> {
+    if double {
+        read_f64(input)
+    } else {
+        read_f32(input).map(f64::from)
+    }
+}
+
+/// Read opaque data of the given length, which is padded to a multiple of four bytes
+pub(crate) fn read_opaque(input: &mut impl Read, length: usize) -> io::Result<Vec<u8>> {
+    let mut bytes =
+        vec![0; padded(length).ok_or_else(|| invalid("The length of opaque data is too large."))?];
+    input.read_exact(&mut bytes)?;
+    bytes.truncate(length);
+    Ok(bytes)
+}
+
+/// Write a 32 bit signed integer
+pub(crate) fn write_i32(output: &mut impl Write, value: i32) -> io::Result<()> {
+    output.write_all(&value.to_be_bytes())
+}
+
+/// Write a 32 bit unsigned integer
+pub(crate) fn write_u32(output: &mut impl Write, value: u32) -> io::Result<()> {
+    output.write_all(&value.to_be_bytes())
+}
+
+/// Write a single precision float
+pub(crate) fn write_f32(output: &mut impl Write, value: f32) -> io::Result<()> {
+    output.write_all(&value.to_be_bytes())
+}
+
+/// Write opaque data, padded with zeros to a multiple of four bytes
+pub(crate) fn write_opaque(output: &mut impl Write, bytes: &[u8]) -> io::Result<()> {
+    output.write_all(bytes)?;
+    output.write_all(&[0; 3][..(4 - bytes.len() % 4) % 4])
+}
+
+/// The length of opaque data of the given length including its padding, or `None` if that
+/// does not fit in a `usize`
+pub(crate) const fn padded(length: usize) -> Option<usize> {
+    match length.checked_add(3) {
+        Some(length) => Some(length / 4 * 4),
+        None => None,
+    }
+}
+
+/// Create an error for data that does not follow the format
+pub(crate) fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
@@ -0,0 +1,659 @@
+use super::xdr::*;
+use super::Frame;
+use crate::structs::UnitCell;
+
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+/// The magic number at the start of every XTC frame
+const MAGIC: i32 = 1995;
+
+/// The magic number of XTC frames with a 64 bit length of the compressed data
+const MAGIC_LARGE: i32 = 2023;
+
+/// The precision used to store coordinates in new XTC files, as a factor of nanometers
+pub(crate) const DEFAULT_PRECISION: f32 = 1000.0;
+
+/// The sizes used for the compression of coordinates of nearby atoms, roughly increasing with
+/// a factor of 2^(1/3) so that three numbers take about as many bits as the index in this table
+const MAGIC_INTS: [u32; 73] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 8, 10, 12, 16, 20, 25, 32, 40, 50, 64, 80, 101, 128, 161, 203, 256,
+    322, 406, 512, 645, 812, 1024, 1290, 1625, 2048, 2580, 3250, 4096, 5060, 6501, 8192, 10321,
+    13003, 16384, 20642, 26007, 32768, 41285, 52015, 65536, 82570, 104_031, 131_072, 165_140,
+    208_063, 262_144, 330_280, 416_127, 524_287, 660_561, 832_255, 1_048_576, 1_321_122, 1_664_510,
+    2_097_152, 2_642_245, 3_329_021, 4_194_304, 5_284_491, 6_658_042, 8_388_607, 10_568_983,
+    13_316_085, 16_777_216,
+];
+
+/// The first useful index in [`MAGIC_INTS`]
+const FIRST_INDEX: usize = 9;
+
+/// The last index in [`MAGIC_INTS`]
+const LAST_INDEX: usize = MAGIC_INTS.len() - 1;
+
+/// Find the offsets of all frames in the given XTC file, together with the number of atoms
+pub(crate) fn scan(input: &mut (impl Read + Seek)) -> io::Result<(usize, Vec<u64>)> {
+    let length = input.seek(SeekFrom::End(0))?;
+    input.seek(SeekFrom::Start(0))?;
+    let mut atom_count = None;
+    let mut offsets = Vec::new();
+    let mut offset = 0;
+    while offset < length {
+        offsets.push(offset);
+        let header = read_header(input)?;
+        if *atom_count.get_or_insert(header.atom_count) != header.atom_count {
+            return Err(invalid(
+                "The number of atoms changes between frames of the trajectory.",
+            ));
+        }
+        let skip = if header.atom_count <= 9 {
+            12 * header.atom_count as u64
+        } else {
+            input.seek(SeekFrom::Current(32))?;
+            let bytes = if header.magic == MAGIC_LARGE {
+                read_u64(input)?
+            } else {
+                u64::from(read_u32(input)?)
+            };
+            bytes
+                .checked_add(3)
+                .ok_or_else(|| invalid("The length of the compressed coordinates is invalid."))?
+                / 4
+                * 4
+        };
+        offset = input.seek(SeekFrom::Current(i64::try_from(skip).unwrap_or(i64::MAX)))?;
+        if offset > length {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+    }
+    Ok((atom_count.unwrap_or(0), offsets))
+}
+
+/// The header of an XTC frame
+struct Header {
+    /// The magic number, which determines the size of the compressed data length
+    magic: i32,
+    /// The number of atoms
+    atom_count: usize,
+    /// The simulation step
+    step: usize,
+    /// The simulation time (ps)
+    time: f64,
+    /// The periodic box (Å)
+    unit_cell: Option<UnitCell>,
+}
+
+/// Read the header of an XTC frame, up to and including the second number of atoms
+fn read_header(input: &mut impl Read) -> io::Result<Header> {
+    let magic = read_i32(input)?;
+    if magic != MAGIC && magic != MAGIC_LARGE {
+        return Err(invalid(format!(
+            "The magic number of an XTC frame is {magic} instead of {MAGIC}."
+        )));
+    }
+    let atom_count = usize::try_from(read_i32(input)?)
+        .map_err(|_| invalid("The number of atoms is negative."))?;
+    let step = usize::try_from(read_i32(input)?).unwrap_or(0);
+    let time = f64::from(read_f32(input)?);
+    let unit_cell = read_box(input, false)?;
+    if usize::try_from(read_i32(input)?).ok() != Some(atom_count) {
+        return Err(invalid(
+            "The two numbers of atoms in the header of an XTC frame are not the same.",
+        ));
+    }
+    Ok(Header {
+        magic,
+        atom_count,
+        step,
+        time,
+        unit_cell,
+    })
+}
+
+/// Read the frame at the current position of the input
+pub(crate) fn read_frame(input: &mut impl Read) -> io::Result<Frame> {
+    let header = read_header(input)?;
+    let positions = if header.atom_count <= 9 {
+        (0..header.atom_count)
+            .map(|_| {
+                Ok((
+                    f64::from(read_f32(input)?) * 10.0,
+                    f64::from(read_f32(input)?) * 10.0,
+                    f64::from(read_f32(input)?) * 10.0,
+                ))
+            })
+            .collect::<io::Result<Vec<_>>>()?
+    } else {
+        decompress(input, header.atom_count, header.magic == MAGIC_LARGE)?
+    };
+    Ok(Frame {
+        step: Some(header.step),
+        time: Some(header.time),
+        positions,
+        velocities: None,
+        unit_cell: header.unit_cell,
+    })
+}
+
+/// Write the given frame with the given precision
+pub(crate) fn write_frame(
+    output: &mut impl Write,
+    frame: &Frame,
+    precision: f32,
+) -> io::Result<()> {
+    let atom_count = i32::try_from(frame.positions.len())
+        .map_err(|_| invalid("There are too many atoms for an XTC file."))?;
+    write_i32(output, MAGIC)?;
+    write_i32(output, atom_count)?;
+    write_i32(
+        output,
+        i32::try_from(frame.step.unwrap_or(0)).unwrap_or(i32::MAX),
+    )?;
+    #[allow(clippy::cast_possible_truncation)]
+    write_f32(output, frame.time.unwrap_or(0.0) as f32)?;
+    write_box(output, frame.unit_cell.as_ref())?;
+    write_i32(output, atom_count)?;
+    if frame.positions.len() <= 9 {
+        for pos in &frame.positions {
+            for value in [pos.0, pos.1, pos.2] {
+                #[allow(clippy::cast_possible_truncation)]
+                write_f32(output, (value / 10.0) as f32)?;
+            }
+        }
+        Ok(())
+    } else {
+        compress(output, &frame.positions, precision)
+    }
+}
+
+/// Read a periodic box of nine real numbers in nanometers, a box of only zeros means no box
+pub(crate) fn read_box(input: &mut impl Read, double: bool) -> io::Result<Option<UnitCell>> {
+    let mut vectors = [[0.0; 3]; 3];
+    for vector in &mut vectors {
+        for value in vector.iter_mut() {
+            *value = read_real(input, double)? * 10.0;
+        }
+    }
+    Ok((vectors.iter().flatten().any(|value| *value != 0.0))
+        .then(|| UnitCell::from_vectors(vectors[0], vectors[1], vectors[2])))
+}
+
+/// Write a periodic box as nine single precision floats in nanometers, or zeros if there is no box
+pub(crate) fn write_box(output: &mut impl Write, unit_cell: Option<&UnitCell>) -> io::Result<()> {
+    let vectors = unit_cell.map_or([[0.0; 3]; 3], UnitCell::vectors);
+    for value in vectors.iter().flatten() {
+        #[allow(clippy::cast_possible_truncation)]
+        write_f32(output, (value / 10.0) as f32)?;
+    }
+    Ok(())
+}
+
+/// Read the compressed coordinates of the given number of atoms
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss
+)]
+fn decompress(
+    input: &mut impl Read,
+    atom_count: usize,
+    large: bool,
+) -> io::Result<Vec<(f64, f64, f64)>> {
+    let precision = read_f32(input)?;
+    let mut minimum = [0; 3];
+    let mut maximum = [0; 3];
+    for value in &mut minimum {
+        *value = read_i32(input)?;
+    }
+    for value in &mut maximum {
+        *value = read_i32(input)?;
+    }
+    let mut sizes = [0; 3];
+    for axis in 0..3 {
+        sizes[axis] = u32::try_from(i64::from(maximum[axis]) - i64::from(minimum[axis]) + 1)
+            .ok()
+            .filter(|size| *size > 0)
+            .ok_or_else(|| invalid("The range of the compressed coordinates is invalid."))?;
+    }
+    let large_sizes = (sizes[0] | sizes[1] | sizes[2]) > 0x00ff_ffff;
+    let bit_sizes = sizes.map(size_of_int);
+    let bit_size = size_of_ints(&sizes);
+
+    let mut small_index = usize::try_from(read_i32(input)?)
+        .ok()
+        .filter(|index| (FIRST_INDEX..=LAST_INDEX).contains(index))
+        .ok_or_else(|| invalid("The compression level of an XTC frame is invalid."))?;
+    let mut smaller = MAGIC_INTS[FIRST_INDEX.max(small_index - 1)] as i32 / 2;
+    let mut small_number = MAGIC_INTS[small_index] as i32 / 2;
+
+    let length = if large {
+        usize::try_from(read_u64(input)?).unwrap_or(usize::MAX)
+    } else {
+        read_u32(input)? as usize
+    };
+    let padded_length = padded(length)
+        .ok_or_else(|| invalid("The length of the compressed coordinates is invalid."))?;
+    let mut bytes = Vec::new();
+    input
+        .by_ref()
+        .take(padded_length as u64)
+        .read_to_end(&mut bytes)?;
+    if bytes.len() != padded_length {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    bytes.truncate(length);
+    let mut bits = BitReader::new(&bytes);
+
+    let mut coordinates = Vec::with_capacity(atom_count);
+    let mut run = 0;
+    while coordinates.len() < atom_count {
+        let mut this = [0; 3];
+        if large_sizes {
+            for axis in 0..3 {
+                this[axis] = bits.receive(bit_sizes[axis])? as i32;
+            }
+        } else {
+            this = bits
+                .receive_ints(bit_size, sizes)?
+                .map(|value| value as i32);
+        }
+        for axis in 0..3 {
+            this[axis] = this[axis].checked_add(minimum[axis]).ok_or_else(overflow)?;
+        }
+        let mut previous = this;
+
+        let mut is_smaller = 0;
+        if bits.receive(1)? == 1 {
+            run = bits.receive(5)? as usize;
+            is_smaller = (run % 3) as i32 - 1;
+            run -= run % 3;
+        }
+        if run > 0 {
+            if coordinates.len() + run / 3 + 1 > atom_count {
+                return Err(invalid(
+                    "The compressed coordinates contain more atoms than the frame.",
+                ));
+            }
+            for k in (0..run).step_by(3) {
+                let small = bits.receive_ints(small_index as u32, [MAGIC_INTS[small_index]; 3])?;
+                for axis in 0..3 {
+                    this[axis] = (small[axis] as i32)
+                        .checked_add(previous[axis])
+                        .and_then(|value| value.checked_sub(small_number))
+                        .ok_or_else(overflow)?;
+                }
+                if k == 0 {
+                    // The first two atoms are interchanged for better compression of water
+                    std::mem::swap(&mut this, &mut previous);
+                    coordinates.push(previous);
+                } else {
+                    previous = this;
+                }
+                coordinates.push(this);
+            }
+        } else {
+            coordinates.push(this);
+        }
+
+        small_index = usize::try_from(small_index as i32 + is_smaller)
+            .ok()
+            .filter(|index| (FIRST_INDEX..=LAST_INDEX).contains(index))
+            .ok_or_else(|| invalid("The compression level of an XTC frame is invalid."))?;
+        if is_smaller < 0 {
+            small_number = smaller;
+            smaller = if small_index > FIRST_INDEX {
+                MAGIC_INTS[small_index - 1] as i32 / 2
+            } else {
+                0
+            };
+        } else if is_smaller > 0 {
+            smaller = small_number;
+            small_number = MAGIC_INTS[small_index] as i32 / 2;
+        }
+    }
+
+    let factor = 10.0 / f64::from(precision);
+    Ok(coordinates
+        .into_iter()
+        .map(|[x, y, z]| {
+            (
+                f64::from(x) * factor,
+                f64::from(y) * factor,
+                f64::from(z) * factor,
+            )
+        })
+        .collect())
+}
+
+/// Create the error for compressed coordinates that do not fit in 32 bit integers
+fn overflow() -> io::Error {
+    invalid("The compressed coordinates of an XTC frame are out of range.")
+}
+
+/// Write the given coordinates compressed with the given precision
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss
+)]
+fn compress(
+    output: &mut impl Write,
+    positions: &[(f64, f64, f64)],
+    precision: f32,
+) -> io::Result<()> {
+    let scale = f64::from(precision) / 10.0;
+    let mut coordinates = Vec::with_capacity(positions.len());
+    for pos in positions {
+        let mut point = [0; 3];
+        for (value, coordinate) in point.iter_mut().zip([pos.0, pos.1, pos.2]) {
+            let scaled = (coordinate * scale).round();
+            if !scaled.is_finite() || scaled.abs() > f64::from(i32::MAX - 2) {
+                return Err(invalid(
+                    "A coordinate is too large to be stored with the precision of the XTC file.",
+                ));
+            }
+            *value = scaled as i32;
+        }
+        coordinates.push(point);
+    }
+
+    let mut minimum = [i32::MAX; 3];
+    let mut maximum = [i32::MIN; 3];
+    let mut minimum_difference = i64::MAX;
+    for (index, point) in coordinates.iter().enumerate() {
+        for axis in 0..3 {
+            minimum[axis] = minimum[axis].min(point[axis]);
+            maximum[axis] = maximum[axis].max(point[axis]);
+        }
+        if index > 0 {
+            let previous = coordinates[index - 1];
+            let difference = (0..3)
+                .map(|axis| (i64::from(previous[axis]) - i64::from(point[axis])).abs())
+                .sum();
+            minimum_difference = minimum_difference.min(difference);
+        }
+    }
+    let mut sizes = [0; 3];
+    for axis in 0..3 {
+        sizes[axis] = u32::try_from(i64::from(maximum[axis]) - i64::from(minimum[axis]) + 1)
+            .ok()
+            .filter(|size| *size < i32::MAX as u32 - 2)
+            .ok_or_else(|| {
+                invalid("The coordinates span too large a range to be stored in an XTC file.")
+            })?;
+    }
+    let large_sizes = (sizes[0] | sizes[1] | sizes[2]) > 0x00ff_ffff;
+    let bit_sizes = sizes.map(size_of_int);
+    let bit_size = size_of_ints(&sizes);
+
+    let mut small_index = FIRST_INDEX;
+    while small_index < LAST_INDEX && i64::from(MAGIC_INTS[small_index]) < minimum_difference {
+        small_index += 1;
+    }
+    let first_small_index = small_index;
+    let max_index = LAST_INDEX.min(small_index + 8);
+    let min_index = max_index - 8;
+    let mut smaller = MAGIC_INTS[FIRST_INDEX.max(small_index - 1)] as i32 / 2;
+    let mut small_number = MAGIC_INTS[small_index] as i32 / 2;
+    let larger = MAGIC_INTS[max_index] as i32 / 2;
+
+    let mut bits = BitWriter::default();
+    let mut previous = [0; 3];
+    let mut previous_run = None;
+    let mut i = 0;
+    while i < coordinates.len() {
+        let this = coordinates[i];
+        let mut is_smaller = if small_index < max_index
+            && i >= 1
+            && (0..3).all(|axis| (this[axis] - previous[axis]).abs() < larger)
+        {
+            1
+        } else if small_index > min_index {
+            -1
+        } else {
+            0
+        };
+        let mut is_small = false;
+        if i + 1 < coordinates.len()
+            && (0..3).all(|axis| (this[axis] - coordinates[i + 1][axis]).abs() < small_number)
+        {
+            // Interchange the first with the second atom for better compression of water
+            coordinates.swap(i, i + 1);
+            is_small = true;
+        }
+        let this = coordinates[i];
+        let offset = [0, 1, 2].map(|axis| (this[axis] - minimum[axis]) as u32);
+        if large_sizes {
+            for axis in 0..3 {
+                bits.send(bit_sizes[axis], offset[axis]);
+            }
+        } else {
+            bits.send_ints(bit_size, sizes, offset);
+        }
+        previous = this;
+        i += 1;
+
+        let mut small = Vec::new();
+        if !is_small && is_smaller == -1 {
+            is_smaller = 0;
+        }
+        while is_small && small.len() < 8 {
+            let this = coordinates[i];
+            let distance: i64 = (0..3)
+                .map(|axis| i64::from(this[axis] - previous[axis]).pow(2))
+                .sum();
+            if is_smaller == -1 && distance >= i64::from(smaller).pow(2) {
+                is_smaller = 0;
+            }
+            small.push([0, 1, 2].map(|axis| (this[axis] - previous[axis] + small_number) as u32));
+            previous = this;
+            i += 1;
+            is_small = i < coordinates.len()
+                && (0..3).all(|axis| (coordinates[i][axis] - previous[axis]).abs() < small_number);
+        }
+        let run = small.len() * 3;
+        if previous_run != Some(run) || is_smaller != 0 {
+            previous_run = Some(run);
+            bits.send(1, 1);
+            bits.send(5, (run as i32 + is_smaller + 1) as u32);
+        } else {
+            bits.send(1, 0);
+        }
+        for values in small {
+            bits.send_ints(small_index as u32, [MAGIC_INTS[small_index]; 3], values);
+        }
+        if is_smaller != 0 {
+            small_index = (small_index as i32 + is_smaller) as usize;
+            if is_smaller < 0 {
+                small_number = smaller;
+                smaller = MAGIC_INTS[small_index - 1] as i32 / 2;
+            } else {
+                smaller = small_number;
+                small_number = MAGIC_INTS[small_index] as i32 / 2;
+            }
+        }
+    }
+
+    write_f32(output, precision)?;
+    for value in minimum.iter().chain(&maximum) {
+        write_i32(output, *value)?;
+    }
+    write_i32(output, first_small_index as i32)?;
+    let bytes = bits.finish();
+    write_u32(output, bytes.len() as u32)?;
+    write_opaque(output, &bytes)
+}
+
+/// The number of bits needed to store numbers smaller than the given size
+fn size_of_int(size: u32) -> u32 {
+    let mut bits = 0;
+    while u64::from(size) >= 1 << bits && bits < 32 {
+        bits += 1;
+    }
+    bits
+}
+
+/// The number of bits needed to store three numbers smaller than the given sizes as a single
+/// number, in which every next number is multiplied by the size of the previous numbers
+fn size_of_ints(sizes: &[u32; 3]) -> u32 {
+    let mut bytes = vec![1_u64];
+    for size in sizes {
+        let mut carry = 0;
+        for byte in &mut bytes {
+            carry += *byte * u64::from(*size);
+            *byte = carry & 0xff;
+            carry >>= 8;
+        }
+        while carry != 0 {
+            bytes.push(carry & 0xff);
+            carry >>= 8;
+        }
+    }
+    let last = bytes.pop().unwrap_or(0);
+    let mut bits = 0;
+    while last >= 1 << bits {
+        bits += 1;
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    let full_bytes = bytes.len() as u32;
+    bits + full_bytes * 8
+}
+
+/// Read numbers of any number of bits from a stream of bytes, most significant bit first
+struct BitReader<'a> {
+    /// The bytes
+    bytes: &'a [u8],
+    /// The number of bits read
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    /// Start reading at the first bit of the given bytes
+    const fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    /// Read a number of the given number of bits (at most 32)
+    fn receive(&mut self, bits: u32) -> io::Result<u32> {
+        let mut value = 0_u64;
+        for _ in 0..bits {
+            let byte = self.bytes.get(self.position / 8).ok_or_else(|| {
+                invalid("The compressed coordinates of an XTC frame end unexpectedly.")
+            })?;
+            let bit = (byte >> (7 - self.position % 8)) & 1;
+            value = value << 1 | u64::from(bit);
+            self.position += 1;
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        Ok(value as u32)
+    }
+
+    /// Read three numbers smaller than the given sizes stored as a single number of the given
+    /// number of bits, least significant byte first
+    fn receive_ints(&mut self, mut bits: u32, sizes: [u32; 3]) -> io::Result<[u32; 3]> {
+        let mut bytes = Vec::with_capacity(16);
+        while bits > 8 {
+            bytes.push(u64::from(self.receive(8)?));
+            bits -= 8;
+        }
+        if bits > 0 {
+            bytes.push(u64::from(self.receive(bits)?));
+        }
+        let mut numbers = [0; 3];
+        for index in (1..3).rev() {
+            let size = u64::from(sizes[index]);
+            let mut remainder = 0;
+            for byte in bytes.iter_mut().rev() {
+                let value = remainder << 8 | *byte;
+                *byte = value / size;
+                remainder = value % size;
+            }
+            #[allow(clippy::cast_possible_truncation)]
+            let number = remainder as u32;
+            numbers[index] = number;
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        let first = bytes
+            .iter()
+            .take(4)
+            .rev()
+            .fold(0, |value, byte| value << 8 | byte) as u32;
+        numbers[0] = first;
+        Ok(numbers)
+    }
+}
+
+/// Write numbers of any number of bits to a stream of bytes, most significant bit first
+#[derive(Default)]
+struct BitWriter {
+    /// The finished bytes
+    bytes: Vec<u8>,
+    /// The bits that do not yet fill a byte
+    last: u8,
+    /// The number of bits in `last`
+    bits: u32,
+}
+
+impl BitWriter {
+    /// Write the given number with the given number of bits (at most 32)
+    fn send(&mut self, bits: u32, value: u32) {
+        for index in (0..bits).rev() {
+            #[allow(clippy::cast_possible_truncation)]
+            let bit = ((u64::from(value) >> index) & 1) as u8;
+            self.last = self.last << 1 | bit;
+            self.bits += 1;
+            if self.bits == 8 {
+                self.bytes.push(self.last);
+                self.last = 0;
+                self.bits = 0;
+            }
+        }
+    }
+
+    /// Write three numbers smaller than the given sizes as a single number of the given number
+    /// of bits, least significant byte first
+    #[allow(clippy::cast_possible_truncation)]
+    fn send_ints(&mut self, bits: u32, sizes: [u32; 3], numbers: [u32; 3]) {
+        let mut bytes = Vec::with_capacity(16);
+        let mut carry = u64::from(numbers[0]);
+        loop {
+            bytes.push(carry & 0xff);
+            carry >>= 8;
+            if carry == 0 {
+                break;
+            }
+        }
+        for index in 1..3 {
+            let mut carry = u64::from(numbers[index]);
+            for byte in &mut bytes {
+                carry += *byte * u64::from(sizes[index]);
+                *byte = carry & 0xff;
+                carry >>= 8;
+            }
+            while carry != 0 {
+                bytes.push(carry & 0xff);
+                carry >>= 8;
+            }
+        }
+        let full_bits = bytes.len() as u32 * 8;
+        if bits >= full_bits {
+            for byte in &bytes {
+                self.send(8, *byte as u32);
+            }
+            self.send(bits - full_bits, 0);
+        } else {
+            let last = bytes.pop().unwrap_or(0);
+            for byte in &bytes {
+                self.send(8, *byte as u32);
+            }
+            self.send(bits + 8 - full_bits, last as u32);
+        }
+    }
+
+    /// Get all bytes, with the last partial byte padded with zeros
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.last << (8 - self.bits));
+        }
+        self.bytes
+    }
+}
@@ -6,6 +6,7 @@ use crate::structs::*;
 ///
 /// ## Invariants Tested
 /// * With multiple models the models should all contain atoms that correspond.
+/// * The cis peptides should agree with the omega angles of the peptide bonds, see [`PDB::detect_cis_peptides`].
 ///
 /// ## Invariants Not Tested
 /// * Numbering of all structs, serial numbers should be unique. To enforce this the `renumber()` function should be called on the PDB struct.
@@ -15,6 +16,7 @@ pub fn validate(pdb: &PDB) -> Vec<PDBError> {
     if pdb.model_count() > 1 {
         errors.append(&mut validate_models(pdb));
     }
+    errors.append(&mut validate_cis_peptides(pdb));
 
     if pdb.atoms().next().is_none() {
         errors.push(PDBError::new(
@@ -234,6 +236,60 @@ pub fn validate_pdb(pdb: &PDB) -> Vec<PDBError> {
     errors
 }
 
+/// Validate the cis peptides by comparing them to the omega angles of the peptide bonds. Annotated
+/// cis peptides with a trans geometry (an omega angle over 90 degrees) give a warning. If any cis
+/// peptides are annotated, peptide bonds with a cis geometry that are not annotated give a warning
+/// as well, structures without annotations are not expected to list them.
+fn validate_cis_peptides(pdb: &PDB) -> Vec<PDBError> {
+    let mut errors = Vec::new();
+    // Without annotations there is nothing to compare, so skip calculating the omega angles
+    if pdb.cis_peptide_count() == 0 {
+        return errors;
+    }
+    /// Format a residue for use in the error messages
+    fn name(residue: &Residue) -> String {
+        format!(
+            "{} {}{}",
+            residue.name().unwrap_or_default(),
+            residue.serial_number(),
+            residue.insertion_code().unwrap_or_default()
+        )
+    }
+    for (model, chain, first, second, omega) in pdb.peptide_bonds() {
+        let annotated = pdb
+            .cis_peptides()
+            .any(|c| c.matches(model, chain, first, second));
+        if annotated && omega.abs() > 90.0 {
+            errors.push(PDBError::new(
+                ErrorLevel::LooseWarning,
+                "Cis peptide with trans geometry",
+                format!(
+                    "The peptide bond between {} and {} in chain {} of model {} is annotated as cis, but its omega angle is {omega:.2} degrees.",
+                    name(first),
+                    name(second),
+                    chain.id(),
+                    model.serial_number()
+                ),
+                Context::None,
+            ));
+        } else if !annotated && omega.abs() < CIS_PEPTIDE_ANGLE {
+            errors.push(PDBError::new(
+                ErrorLevel::LooseWarning,
+                "Unannotated cis peptide",
+                format!(
+                    "The peptide bond between {} and {} in chain {} of model {} has a cis geometry with an omega angle of {omega:.2} degrees, but it is not annotated as cis peptide.",
+                    name(first),
+                    name(second),
+                    chain.id(),
+                    model.serial_number()
+                ),
+                Context::None,
+            ));
+        }
+    }
+    errors
+}
+
 /// Validate the models by enforcing that all models should contain the same atoms (with possibly different data).
 /// It checks this by matching all atoms (not hetatoms) for each model to see if they correspond (`Atom::correspond`).
 #[allow(clippy::unwrap_used)]
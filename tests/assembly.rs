@@ -0,0 +1,132 @@
+// Test reading, writing, and building biological assemblies (REMARK 350 and _pdbx_struct_assembly).
+
+mod common;
+
+use common::{read, round_trip};
+use pdbtbx::*;
+
+/// Check that the assemblies are equal, with the transformations compared with a tolerance
+fn assert_equivalent(a: &PDB, b: &PDB) {
+    assert_eq!(a.assembly_count(), b.assembly_count());
+    for (a, b) in a.assemblies().zip(b.assemblies()) {
+        assert_eq!(a.id, b.id);
+        assert_eq!(a.details, b.details);
+        assert_eq!(a.oligomeric_details, b.oligomeric_details);
+        assert_eq!(a.oligomeric_count, b.oligomeric_count);
+        assert_eq!(a.generators.len(), b.generators.len());
+        for (a, b) in a.generators.iter().zip(&b.generators) {
+            assert_eq!(a.chains, b.chains);
+            assert_eq!(a.operators.len(), b.operators.len());
+            for (a, b) in a.operators.iter().zip(&b.operators) {
+                for (a, b) in a
+                    .transformation
+                    .matrix()
+                    .iter()
+                    .flatten()
+                    .zip(b.transformation.matrix().iter().flatten())
+                {
+                    assert!((a - b).abs() < 1e-5);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn assembly_pdb_and_mmcif() {
+    let pdb = read("example-pdbs/1yyf.pdb");
+    let cif = read("example-pdbs/1yyf.cif");
+    for structure in [&pdb, &cif] {
+        let assembly = structure.assembly("1").unwrap();
+        assert_eq!(assembly.details.as_deref(), Some("author_defined_assembly"));
+        assert_eq!(assembly.oligomeric_details.as_deref(), Some("24-meric"));
+        assert_eq!(assembly.oligomeric_count, Some(24));
+        assert_eq!(assembly.chains(), vec!["A", "B", "D", "C"]);
+        assert_eq!(assembly.copy_count(), 24);
+        assert!(assembly.generators[0].operators[0].is_identity());
+    }
+    assert_equivalent(&pdb, &cif);
+
+    let assembly = read("example-pdbs/2btv.pdb");
+    let chains = &assembly.assembly("1").unwrap().generators[0].chains;
+    assert_eq!(chains.len(), 15);
+    assert_eq!(chains[14], "T");
+}
+
+#[test]
+fn incomplete_assembly_operator() {
+    let text = std::fs::read_to_string("example-pdbs/1yyf.cif")
+        .unwrap()
+        .replacen("181.1960000000", "?", 1);
+    let (pdb, errors) = ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .set_format(Format::Mmcif)
+        .read_raw(std::io::BufReader::new(text.as_bytes()))
+        .unwrap();
+    assert!(errors
+        .iter()
+        .any(|e| e.short_description() == "Incomplete assembly operator"));
+    let operators: Vec<&str> = pdb.assembly("1").unwrap().generators[0]
+        .operators
+        .iter()
+        .map(|o| o.id.as_str())
+        .collect();
+    assert_eq!(operators, ["1", "3", "4", "5", "6"]);
+}
+
+#[test]
+fn assembly_round_trip() {
+    let pdb = read("example-pdbs/1yyf.pdb");
+    assert_equivalent(&pdb, &round_trip(&pdb, Format::Pdb));
+    assert_equivalent(&pdb, &round_trip(&pdb, Format::Mmcif));
+    let cif = read("example-pdbs/1yyf.cif");
+    assert_equivalent(&cif, &round_trip(&cif, Format::Mmcif));
+    assert_equivalent(&cif, &round_trip(&cif, Format::Pdb));
+}
+
+#[test]
+fn build_assembly() {
+    let pdb = read("example-pdbs/1yyf.pdb");
+    assert!(pdb.build_assembly("2").is_none());
+    let assembly = pdb.build_assembly("1").unwrap();
+    assert_eq!(assembly.model_count(), pdb.model_count());
+    assert_eq!(assembly.chain_count(), 24);
+    assert_eq!(assembly.atom_count(), pdb.atom_count() * 6);
+    assert_eq!(assembly.assembly_count(), 0);
+
+    // The identity copy keeps the original ids and positions
+    let original = pdb.chains().find(|c| c.id() == "A").unwrap();
+    let copy = assembly.chains().find(|c| c.id() == "A").unwrap();
+    assert_eq!(original.atom(0).unwrap().pos(), copy.atom(0).unwrap().pos());
+    let copy = assembly.chains().find(|c| c.id() == "A-2").unwrap();
+    assert_ne!(original.atom(0).unwrap().pos(), copy.atom(0).unwrap().pos());
+
+    let mut ids: Vec<&str> = assembly.chains().map(Chain::id).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    assert_eq!(ids.len(), 24);
+}
+
+#[test]
+fn build_assembly_copies_bonds() {
+    let pdb = read("example-pdbs/3nig.cif");
+    let id = &pdb.assemblies().next().unwrap().id;
+    let assembly = pdb.build_assembly(id).unwrap();
+    assert!(assembly.bonds().count() > 0);
+    assert_eq!(
+        assembly.atom_count(),
+        pdb.assembly(id)
+            .unwrap()
+            .generators
+            .iter()
+            .map(|g| {
+                g.operators.len()
+                    * pdb
+                        .chains()
+                        .filter(|c| g.chains.iter().any(|id| id == c.id()))
+                        .map(Chain::atom_count)
+                        .sum::<usize>()
+            })
+            .sum::<usize>()
+    );
+}
@@ -0,0 +1,74 @@
+// Test reading and writing BinaryCIF files.
+
+use pdbtbx::cif::*;
+mod common;
+
+use common::{dump, read};
+use pdbtbx::*;
+
+/// Check that both structures are the same, bonds are compared by their atoms as the atom
+/// counters are different for every file read
+fn assert_same(a: &PDB, b: &PDB, name: &str) {
+    assert_eq!(a.identifier, b.identifier, "{name}");
+    assert_eq!(a.header, b.header, "{name}");
+    assert_eq!(a.refinement, b.refinement, "{name}");
+    assert_eq!(a.unit_cell, b.unit_cell, "{name}");
+    assert_eq!(a.symmetry, b.symmetry, "{name}");
+    assert!(a.models().eq(b.models()), "{name}");
+    assert!(a.bonds_with_details().eq(b.bonds_with_details()), "{name}");
+}
+
+#[test]
+fn same_structure_as_mmcif() {
+    for name in ["1ubq", "1yyf", "3pdz", "6wlb"] {
+        let pdb = read(&format!("example-pdbs/{name}.cif"));
+        let cif = dump(&format!("binary_{name}.cif"));
+        let bcif = dump(&format!("binary_{name}.bcif"));
+        save_mmcif(&pdb, &cif, StrictnessLevel::Loose).unwrap();
+        save(&pdb, &bcif, StrictnessLevel::Loose).unwrap();
+        assert_same(&read(&bcif), &read(&cif), name);
+        let size = |path: &str| std::fs::metadata(path).unwrap().len();
+        assert!(size(&bcif) < size(&cif), "{name}");
+    }
+}
+
+#[test]
+fn compressed() {
+    let pdb = read("example-pdbs/1ubq.cif");
+    let path = dump("binary_1ubq.bcif.gz");
+    save_gz(&pdb, &path, StrictnessLevel::Loose, None).unwrap();
+    let reread = read(&path);
+    assert_eq!(reread.atom_count(), pdb.atom_count());
+    assert_eq!(
+        reread.atoms().map(Atom::pos).collect::<Vec<_>>(),
+        pdb.atoms().map(Atom::pos).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn all_data_blocks() {
+    let mut document = Document::read("example-pdbs/1ubq.cif").unwrap();
+    document
+        .blocks
+        .extend(Document::read("example-pdbs/3pdz.cif").unwrap().blocks);
+    let path = dump("binary_blocks.bcif");
+    std::fs::write(&path, document.to_binary()).unwrap();
+    let blocks = ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .read_blocks(&path)
+        .unwrap();
+    assert_eq!(blocks.len(), 2);
+    for (block, name) in blocks.into_iter().zip(["1UBQ", "3PDZ"]) {
+        let (pdb, _) = block.unwrap();
+        assert_eq!(pdb.identifier.as_deref(), Some(name));
+        assert!(pdb.atom_count() > 0);
+    }
+}
+
+#[test]
+fn invalid_file() {
+    let result = ReadOptions::default()
+        .set_format(Format::BinaryCif)
+        .read_raw(std::io::BufReader::new(&b"data_test"[..]));
+    assert!(result.is_err());
+}
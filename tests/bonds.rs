@@ -0,0 +1,119 @@
+// Test reading and writing bonds from SSBOND/LINK records in PDB files and the _struct_conn category in mmCIF files.
+
+use pdbtbx::*;
+use std::io::{BufReader, BufWriter};
+
+/// Count the bonds of the given type
+fn count(pdb: &PDB, kind: Bond) -> usize {
+    pdb.bonds().filter(|(_, _, bond)| *bond == kind).count()
+}
+
+#[test]
+fn links_pdb_and_mmcif() {
+    let (pdb, _errors) = ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .read("example-pdbs/3nig.pdb")
+        .unwrap();
+    let (cif, _errors) = ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .read("example-pdbs/3nig.cif")
+        .unwrap();
+    for structure in [&pdb, &cif] {
+        assert_eq!(count(structure, Bond::Disulfide), 37);
+        assert_eq!(count(structure, Bond::MetalCoordination), 96);
+    }
+    assert_eq!(count(&cif, Bond::Covalent), 12);
+
+    let (a, b, bond, details) = cif
+        .bonds_with_details()
+        .find(|(_, _, bond, _)| *bond == Bond::MetalCoordination)
+        .unwrap();
+    assert_eq!(bond.conn_type(), "metalc");
+    assert!(a.element().unwrap().is_metal() || b.element().unwrap().is_metal());
+    let details = details.unwrap();
+    assert_eq!(details.id.as_deref(), Some("metalc1"));
+    assert_eq!(details.symmetry_operator_1.as_deref(), Some("1_555"));
+    assert_eq!(details.distance, Some(2.466));
+
+    let (_, _, _, details) = pdb
+        .bonds_with_details()
+        .find(|(_, _, bond, _)| *bond == Bond::Covalent)
+        .unwrap();
+    let details = details.unwrap();
+    assert_eq!(details.symmetry_operator_2.as_deref(), Some("1_555"));
+    assert_eq!(details.distance, Some(1.43));
+}
+
+#[test]
+fn links_round_trip() {
+    let (pdb, _errors) = ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .read("example-pdbs/3nig.cif")
+        .unwrap();
+    let original: Vec<(Bond, Option<BondDetails>)> = pdb
+        .bonds_with_details()
+        .map(|(_, _, bond, details)| (bond, details.cloned()))
+        .collect();
+
+    let mut buffer = Vec::new();
+    save_mmcif_raw(&pdb, BufWriter::new(&mut buffer));
+    let (cif, _errors) = ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .set_format(Format::Mmcif)
+        .read_raw(BufReader::new(buffer.as_slice()))
+        .unwrap();
+    let reread: Vec<(Bond, Option<BondDetails>)> = cif
+        .bonds_with_details()
+        .map(|(_, _, bond, details)| (bond, details.cloned()))
+        .collect();
+    assert_eq!(original, reread);
+
+    let mut buffer = Vec::new();
+    save_pdb_raw(&pdb, BufWriter::new(&mut buffer), StrictnessLevel::Loose);
+    let text = String::from_utf8(buffer).unwrap();
+    assert_eq!(text.lines().filter(|l| l.starts_with("SSBOND")).count(), 37);
+    assert_eq!(
+        text.lines().filter(|l| l.starts_with("LINK  ")).count(),
+        108
+    );
+    let (reread, _errors) = ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .set_format(Format::Pdb)
+        .read_raw(BufReader::new(text.as_bytes()))
+        .unwrap();
+    for kind in [Bond::Disulfide, Bond::MetalCoordination, Bond::Covalent] {
+        assert_eq!(count(&pdb, kind), count(&reread, kind));
+    }
+}
+
+#[test]
+fn linkr_round_trip() {
+    // Turn the first LINK record into a LINKR record as written by REFMAC
+    let text = std::fs::read_to_string("example-pdbs/3nig.pdb").unwrap();
+    let link = text.lines().find(|l| l.starts_with("LINK  ")).unwrap();
+    let linkr = format!("LINKR{}NAG-ASN", &link[5..72]);
+    let text = text.replacen(link, &linkr, 1);
+    let read = |text: &str| {
+        ReadOptions::default()
+            .set_level(StrictnessLevel::Loose)
+            .set_format(Format::Pdb)
+            .read_raw(BufReader::new(text.as_bytes()))
+            .unwrap()
+            .0
+    };
+    let link_names = |pdb: &PDB| {
+        pdb.bonds_with_details()
+            .filter_map(|(_, _, _, details)| details.and_then(|d| d.link_name.clone()))
+            .collect::<Vec<_>>()
+    };
+    let pdb = read(&text);
+    assert_eq!(link_names(&pdb), ["NAG-ASN"]);
+
+    let mut buffer = Vec::new();
+    save_pdb_raw(&pdb, BufWriter::new(&mut buffer), StrictnessLevel::Loose);
+    let saved = String::from_utf8(buffer).unwrap();
+    let lines: Vec<_> = saved.lines().filter(|l| l.starts_with("LINKR")).collect();
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].ends_with("NAG-ASN"));
+    assert_eq!(link_names(&read(&saved)), ["NAG-ASN"]);
+}
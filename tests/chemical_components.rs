@@ -0,0 +1,55 @@
+// Test reading and writing the chemical components (HETNAM, HETSYN, FORMUL and _chem_comp).
+
+mod common;
+
+use common::{read, round_trip};
+use pdbtbx::*;
+
+#[test]
+fn chemical_components_pdb() {
+    let pdb = read("example-pdbs/7az6.pdb");
+    let glycerol = pdb.chemical_component("GOL").unwrap();
+    assert_eq!(glycerol.name.as_deref(), Some("GLYCEROL"));
+    assert_eq!(glycerol.synonyms, vec!["GLYCERIN", "PROPANE-1,2,3-TRIOL"]);
+    assert_eq!(glycerol.formula.as_deref(), Some("C3 H8 O3"));
+    let peg = pdb.chemical_component("PEG").unwrap();
+    assert_eq!(peg.name.as_deref(), Some("DI(HYDROXYETHYL)ETHER"));
+    assert_eq!(peg.formula.as_deref(), Some("C4 H10 O3"));
+    let water = pdb.chemical_component("HOH").unwrap();
+    assert_eq!(water.name, None);
+    assert_eq!(water.formula.as_deref(), Some("H2 O"));
+    assert_eq!(pdb.chemical_component_count(), 9);
+}
+
+#[test]
+fn chemical_components_mmcif() {
+    let cif = read("example-pdbs/3nig.cif");
+    let glycerol = cif.chemical_component("GOL").unwrap();
+    assert_eq!(glycerol.name.as_deref(), Some("GLYCEROL"));
+    assert_eq!(glycerol.synonyms, vec!["GLYCERIN", "PROPANE-1,2,3-TRIOL"]);
+    assert_eq!(glycerol.formula.as_deref(), Some("C3 H8 O3"));
+    let alanine = cif.chemical_component("ALA").unwrap();
+    assert_eq!(alanine.name.as_deref(), Some("ALANINE"));
+    assert!(alanine.synonyms.is_empty());
+}
+
+#[test]
+fn chemical_components_round_trip() {
+    let pdb = read("example-pdbs/7az6.pdb");
+    for format in [Format::Pdb, Format::Mmcif] {
+        let saved = round_trip(&pdb, format);
+        assert!(pdb.chemical_components().eq(saved.chemical_components()));
+    }
+    let cif = read("example-pdbs/3nig.cif");
+    assert!(cif
+        .chemical_components()
+        .eq(round_trip(&cif, Format::Mmcif).chemical_components()));
+
+    // Only the components of hetero residues are saved in PDB files
+    let converted = round_trip(&cif, Format::Pdb);
+    assert!(converted.chemical_component("ALA").is_none());
+    assert_eq!(
+        converted.chemical_component("GOL"),
+        cif.chemical_component("GOL")
+    );
+}
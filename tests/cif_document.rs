@@ -0,0 +1,95 @@
+// Test reading, editing, and writing generic CIF documents.
+
+use pdbtbx::cif::*;
+use pdbtbx::*;
+use std::io::BufReader;
+
+/// Read the structure in the given CIF text
+fn read_structure(text: &str) -> PDB {
+    ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .set_format(Format::Mmcif)
+        .read_raw(BufReader::new(text.as_bytes()))
+        .unwrap()
+        .0
+}
+
+#[test]
+fn round_trip_examples() {
+    for entry in std::fs::read_dir("example-pdbs").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().and_then(|e| e.to_str()) != Some("cif") {
+            continue;
+        }
+        let path = path.to_str().unwrap();
+        let document = Document::read(path).unwrap();
+        let written = document.to_string();
+        assert_eq!(document, Document::parse(&written).unwrap(), "{path}");
+        let original = read_structure(&std::fs::read_to_string(path).unwrap());
+        let reread = read_structure(&written);
+        assert_eq!(original.atom_count(), reread.atom_count(), "{path}");
+        assert_eq!(original.identifier, reread.identifier, "{path}");
+    }
+}
+
+#[test]
+fn edit_and_save() {
+    let root = env!("CARGO_MANIFEST_DIR");
+    std::fs::create_dir_all(format!("{root}/dump")).unwrap();
+    let path = format!("{root}/dump/edited.cif");
+    let mut document = Document::read("example-pdbs/1ubq.cif").unwrap();
+    let block = document.block_mut("1UBQ").unwrap();
+    block.set_value("_struct.title", "An edited title");
+    block
+        .set_category(
+            "_pdbx_custom",
+            &["id", "note"],
+            vec![
+                vec![1_usize.into(), "first note".into()],
+                vec![2_usize.into(), "1.0".into()],
+            ],
+        )
+        .unwrap();
+    document.save(&path).unwrap();
+    let saved = Document::read(&path).unwrap();
+    assert_eq!(saved, document);
+    let block = saved.block("1UBQ").unwrap();
+    assert_eq!(
+        block
+            .category("struct")
+            .unwrap()
+            .get_text(0, "title")
+            .unwrap()
+            .as_deref(),
+        Some("An edited title")
+    );
+    let custom = block.category("_pdbx_custom").unwrap();
+    assert_eq!(custom.columns(), ["id", "note"]);
+    assert_eq!(custom.get_usize(1, "id").unwrap(), Some(2));
+    assert_eq!(custom.value(1, "note"), Some(&Value::from("1.0")));
+    assert!(custom.get_f64(0, "note").is_err());
+    assert_eq!(custom.get_f64(0, "missing").unwrap(), None);
+    assert_eq!(
+        read_structure(&saved.to_string()).atom_count(),
+        read_structure(&std::fs::read_to_string("example-pdbs/1ubq.cif").unwrap()).atom_count()
+    );
+}
+
+#[test]
+fn numbers_keep_their_text() {
+    let text = "data_numbers\n_test.exponent 1E5\n_test.padded 007\n_test.trailing 1.50\n_test.uncertain 42.0(9)\n";
+    let document = Document::parse(text).unwrap();
+    let written = document.to_string();
+    for line in text.lines() {
+        assert!(written.lines().any(|l| l == line), "{line}");
+    }
+    let test = document.blocks[0].category("test").unwrap();
+    assert_eq!(test.get_f64(0, "exponent").unwrap(), Some(100_000.0));
+    assert_eq!(test.get_usize(0, "padded").unwrap(), Some(7));
+    assert_eq!(test.get_f64(0, "trailing").unwrap(), Some(1.5));
+    assert_eq!(
+        test.get_text(0, "trailing").unwrap().as_deref(),
+        Some("1.50")
+    );
+    assert!(test.get_f64(0, "uncertain").is_err());
+}
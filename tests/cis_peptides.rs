@@ -0,0 +1,89 @@
+// Test reading, writing, and detecting cis peptides (CISPEP and _struct_mon_prot_cis).
+
+mod common;
+
+use common::{read, round_trip};
+use pdbtbx::*;
+
+/// Check that the cis peptides are equal, with the angles compared with a tolerance. The models
+/// are compared by position, as PDB files without MODEL records number the only model 0.
+fn assert_equivalent(a_pdb: &PDB, b_pdb: &PDB) {
+    let model_index = |pdb: &PDB, serial_number: usize| {
+        pdb.models()
+            .position(|m| m.serial_number() == serial_number)
+            .unwrap()
+    };
+    assert_eq!(a_pdb.cis_peptide_count(), b_pdb.cis_peptide_count());
+    for (a, b) in a_pdb.cis_peptides().zip(b_pdb.cis_peptides()) {
+        assert_eq!(a.serial_number, b.serial_number);
+        assert_eq!(model_index(a_pdb, a.model), model_index(b_pdb, b.model));
+        assert_eq!(a.start, b.start);
+        assert_eq!(a.end, b.end);
+        assert!((a.angle.unwrap() - b.angle.unwrap()).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn cis_peptides_pdb_and_mmcif() {
+    let pdb = read("example-pdbs/3nig.pdb");
+    let cif = read("example-pdbs/3nig.cif");
+    assert_eq!(pdb.cis_peptide_count(), 21);
+    let first = pdb.cis_peptides().next().unwrap();
+    assert_eq!(first.serial_number, 1);
+    assert_eq!(first.model, pdb.model(0).unwrap().serial_number());
+    assert_eq!(first.start, ResidueReference::new("B", (77, None), "SER"));
+    assert_eq!(first.end, ResidueReference::new("B", (78, None), "SER"));
+    assert_eq!(first.angle, Some(5.71));
+    assert_equivalent(&pdb, &cif);
+}
+
+#[test]
+fn cis_peptides_detection() {
+    for path in ["example-pdbs/3nig.pdb", "example-pdbs/2btv.pdb"] {
+        let pdb = read(path);
+        let detected = pdb.detect_cis_peptides();
+        assert_eq!(detected.len(), pdb.cis_peptide_count());
+        for (annotated, detected) in pdb.cis_peptides().zip(&detected) {
+            assert_eq!(annotated.start, detected.start);
+            assert_eq!(annotated.end, detected.end);
+            // The calculated dihedral is not signed
+            assert!((annotated.angle.unwrap().abs() - detected.angle.unwrap()).abs() < 0.1);
+        }
+        assert!(!validate(&pdb)
+            .iter()
+            .any(|e| e.short_description().contains("peptide")));
+    }
+}
+
+#[test]
+fn cis_peptides_validation() {
+    let mut pdb = read("example-pdbs/3nig.pdb");
+    // An annotated cis peptide with a trans geometry
+    let mut wrong = pdb.cis_peptides().next().unwrap().clone();
+    wrong.start.serial_number = 100;
+    wrong.end.serial_number = 101;
+    pdb.add_cis_peptide(wrong);
+    // A cis peptide that is no longer annotated
+    pdb.delete_cis_peptides_by(|c| c.serial_number == 2);
+    let errors = validate(&pdb);
+    let descriptions: Vec<&str> = errors
+        .iter()
+        .map(PDBError::short_description)
+        .filter(|d| d.contains("peptide"))
+        .collect();
+    assert_eq!(
+        descriptions,
+        vec!["Unannotated cis peptide", "Cis peptide with trans geometry"]
+    );
+}
+
+#[test]
+fn cis_peptides_round_trip() {
+    let pdb = read("example-pdbs/3nig.pdb");
+    assert_equivalent(&pdb, &round_trip(&pdb, Format::Pdb));
+    assert_equivalent(&pdb, &round_trip(&pdb, Format::Mmcif));
+    let cif = read("example-pdbs/6wlb.cif");
+    assert_eq!(cif.cis_peptide_count(), 6);
+    assert_equivalent(&cif, &round_trip(&cif, Format::Mmcif));
+    assert_equivalent(&cif, &round_trip(&cif, Format::Pdb));
+}
@@ -0,0 +1,43 @@
+// Helpers shared by the integration tests, not every test uses all of them.
+#![allow(dead_code)]
+
+use pdbtbx::*;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+/// The path to a new file with the given name in the dump folder
+pub fn dump(name: &str) -> String {
+    let folder = Path::new(env!("CARGO_MANIFEST_DIR")).join("dump");
+    std::fs::create_dir_all(&folder).unwrap();
+    folder.join(name).to_str().unwrap().to_string()
+}
+
+/// Read the file at the given path, the format is determined from the extension
+pub fn read(path: &str) -> PDB {
+    ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .read(path)
+        .unwrap()
+        .0
+}
+
+/// Write the given PDB in the given format and read it back
+pub fn round_trip(pdb: &PDB, format: Format) -> PDB {
+    let mut buffer = Vec::new();
+    if matches!(format, Format::Pdb) {
+        save_pdb_raw(pdb, BufWriter::new(&mut buffer), StrictnessLevel::Loose);
+    } else {
+        save_mmcif_raw(pdb, BufWriter::new(&mut buffer));
+    }
+    ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .set_format(format)
+        .read_raw(BufReader::new(buffer.as_slice()))
+        .unwrap()
+        .0
+}
+
+/// Check that two numbers are equal within the precision of the written files
+pub fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 0.001
+}
@@ -0,0 +1,73 @@
+// Test reading and writing CONECT records in PDB files.
+
+use pdbtbx::*;
+use std::io::BufWriter;
+
+#[test]
+fn connect_round_trip() {
+    let (pdb, _errors) = ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .read("example-pdbs/1kmk.pdb")
+        .unwrap();
+    let bonds = pdb.bonds().count();
+    assert!(bonds > 0);
+    assert!(pdb.bonds().all(|(_, _, bond)| bond == Bond::Covalent));
+
+    let mut buffer = Vec::new();
+    save_pdb_raw(&pdb, BufWriter::new(&mut buffer), StrictnessLevel::Medium);
+    let text = String::from_utf8(buffer).unwrap();
+    assert!(text.lines().any(|l| l.starts_with("CONECT")));
+
+    let (reread, errors) = ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .set_format(Format::Pdb)
+        .read_raw(std::io::BufReader::new(text.as_bytes()))
+        .unwrap();
+    assert!(!errors
+        .iter()
+        .any(|e| e.short_description() == "MASTER checksum failed"));
+    assert_eq!(bonds, reread.bonds().count());
+}
+
+#[test]
+fn connect_columns() {
+    let input = "\
+HETATM    1 C1   LIG A   1       0.000   0.000   0.000  1.00  0.00           C
+HETATM    2 C2   LIG A   1       1.500   0.000   0.000  1.00  0.00           C
+HETATM    3 C3   LIG A   1       3.000   0.000   0.000  1.00  0.00           C
+HETATM    4 C4   LIG A   1       4.500   0.000   0.000  1.00  0.00           C
+HETATM    5 C5   LIG A   1       6.000   0.000   0.000  1.00  0.00           C
+HETATM 1234 C6   LIG A   1       7.500   0.000   0.000  1.00  0.00           C
+CONECT    1    2    3    4    5
+CONECT    1 1234
+END
+";
+    let (pdb, _errors) = ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .set_format(Format::Pdb)
+        .read_raw(std::io::BufReader::new(input.as_bytes()))
+        .unwrap();
+    assert_eq!(pdb.bonds().count(), 5);
+
+    let mut buffer = Vec::new();
+    save_pdb_raw(&pdb, BufWriter::new(&mut buffer), StrictnessLevel::Loose);
+    let text = String::from_utf8(buffer).unwrap();
+    let connect: Vec<&str> = text
+        .lines()
+        .filter(|l| l.starts_with("CONECT"))
+        .map(str::trim_end)
+        .collect();
+    // The serial numbers are right aligned in columns 7-11, 12-16, 17-21, 22-26, and 27-31
+    assert_eq!(
+        connect,
+        [
+            "CONECT    1    2    3    4    5",
+            "CONECT    1 1234",
+            "CONECT    2    1",
+            "CONECT    3    1",
+            "CONECT    4    1",
+            "CONECT    5    1",
+            "CONECT 1234    1",
+        ]
+    );
+}
@@ -0,0 +1,88 @@
+// Test reading CIF files with multiple data blocks.
+
+mod common;
+
+use common::read;
+use pdbtbx::*;
+use std::io::BufReader;
+
+/// The result of reading a single data block
+type BlockResult = Result<(PDB, Vec<PDBError>), Vec<PDBError>>;
+
+/// Read every data block in the given text
+fn read_blocks(text: &str) -> Vec<BlockResult> {
+    ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .read_raw_blocks(BufReader::new(text.as_bytes()))
+        .unwrap()
+}
+
+/// The text of two example files combined into one document
+fn document() -> String {
+    std::fs::read_to_string("example-pdbs/1ubq.cif").unwrap()
+        + &std::fs::read_to_string("example-pdbs/3pdz.cif").unwrap()
+}
+
+#[test]
+fn read_all_blocks() {
+    let blocks = read_blocks(&document());
+    assert_eq!(blocks.len(), 2);
+    for (block, path) in blocks
+        .into_iter()
+        .zip(["example-pdbs/1ubq.cif", "example-pdbs/3pdz.cif"])
+    {
+        let (pdb, _) = block.unwrap();
+        let single = read(path);
+        assert_eq!(pdb.identifier, single.identifier);
+        assert_eq!(pdb.atom_count(), single.atom_count());
+        assert_eq!(pdb.chain_count(), single.chain_count());
+    }
+    // Reading a single structure only uses the first block
+    let (pdb, _) = ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .set_format(Format::Mmcif)
+        .read_raw(BufReader::new(document().as_bytes()))
+        .unwrap();
+    assert_eq!(pdb.identifier.as_deref(), Some("1UBQ"));
+    assert_eq!(pdb.atom_count(), read("example-pdbs/1ubq.cif").atom_count());
+}
+
+#[test]
+fn read_blocks_from_file() {
+    let root = env!("CARGO_MANIFEST_DIR");
+    std::fs::create_dir_all(format!("{root}/dump")).unwrap();
+    let path = format!("{root}/dump/blocks.cif");
+    std::fs::write(&path, document()).unwrap();
+    let identifiers: Vec<_> = ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .read_blocks(&path)
+        .unwrap()
+        .into_iter()
+        .map(|block| block.unwrap().0.identifier.unwrap())
+        .collect();
+    assert_eq!(identifiers, ["1UBQ", "3PDZ"]);
+    assert!(ReadOptions::default()
+        .read_blocks("example-pdbs/1ubq.pdb")
+        .is_err());
+}
+
+#[test]
+fn errors_tagged_with_block() {
+    let text =
+        document() + "data_BROKEN\n_cell.length_a abc\n_cell.length_b 10\n_cell.length_c 10\n";
+    let blocks = read_blocks(&text);
+    assert_eq!(blocks.len(), 3);
+    assert!(blocks[0].is_ok());
+    let errors = blocks[2].as_ref().unwrap_err();
+    assert!(!errors.is_empty());
+    for error in errors {
+        assert!(format!("{error}").contains("In data block 'BROKEN'"));
+    }
+    // Errors while lexing stop the whole document
+    let errors = ReadOptions::default()
+        .read_raw_blocks(BufReader::new(
+            (document() + "data_BROKEN\nloop_\n_a.b\n_a.c\n1\n").as_bytes(),
+        ))
+        .unwrap_err();
+    assert!(format!("{}", errors[0]).contains("In data block 'BROKEN'"));
+}
@@ -0,0 +1,134 @@
+// Test reading and writing entities (SEQRES, COMPND, _entity, _entity_poly, and _entity_poly_seq).
+
+mod common;
+
+use common::{read, round_trip};
+use pdbtbx::*;
+use std::io::{BufReader, BufWriter};
+
+#[test]
+fn entities_pdb() {
+    let pdb = read("example-pdbs/1yyf.pdb");
+    assert_eq!(pdb.entity_count(), 2);
+    let entity = pdb.entity("1").unwrap();
+    assert_eq!(entity.entity_type.as_deref(), Some("polymer"));
+    assert_eq!(entity.polymer_type.as_deref(), Some("polypeptide(L)"));
+    assert_eq!(
+        entity.description.as_deref(),
+        Some("ATP-DEPENDENT HSL PROTEASE ATP-BINDING SUBUNIT HSLU")
+    );
+    assert_eq!(entity.chains, vec!["A", "B"]);
+    assert_eq!(entity.sequence.len(), 443);
+    assert!(entity.one_letter_sequence().starts_with("MSEMTPREIVSEL"));
+    assert_eq!(pdb.entity_of_chain("C").unwrap().id, "2");
+    assert_eq!(pdb.entity_of_chain("D").unwrap().sequence.len(), 181);
+    assert!(pdb.entity_of_chain("Z").is_none());
+}
+
+#[test]
+fn entities_mmcif() {
+    let pdb = read("example-pdbs/1yyf.cif");
+    assert_eq!(pdb.entity_count(), 3);
+    let entity = pdb.entity("2").unwrap();
+    assert_eq!(entity.polymer_type.as_deref(), Some("polypeptide(L)"));
+    assert_eq!(
+        entity.description.as_deref(),
+        Some("ATP-dependent protease hslV")
+    );
+    assert_eq!(entity.chains, vec!["D", "C"]);
+    assert_eq!(entity.sequence.len(), 181);
+    assert_eq!(
+        entity.canonical_sequence.as_deref().map(str::len),
+        Some(181)
+    );
+    let ligand = pdb.entity("3").unwrap();
+    assert_eq!(ligand.entity_type.as_deref(), Some("non-polymer"));
+    assert!(!ligand.is_polymer());
+    assert_eq!(ligand.sequence, vec!["ADP"]);
+    assert_eq!(ligand.chains, vec!["A", "B"]);
+    // The polymer is preferred over the ligands in the same chain
+    assert_eq!(pdb.entity_of_chain("A").unwrap().id, "1");
+}
+
+#[test]
+fn entities_pdb_and_mmcif() {
+    for name in ["1yyf", "3nig"] {
+        let pdb = read(&format!("example-pdbs/{name}.pdb"));
+        let cif = read(&format!("example-pdbs/{name}.cif"));
+        for entity in pdb.entities() {
+            let other = cif.entity(&entity.id).unwrap();
+            assert_eq!(entity.sequence, other.sequence);
+            assert_eq!(entity.one_letter_sequence(), other.one_letter_sequence());
+            let mut chains = entity.chains.clone();
+            let mut other_chains = other.chains.clone();
+            chains.sort();
+            other_chains.sort();
+            assert_eq!(chains, other_chains);
+        }
+    }
+}
+
+#[test]
+fn entities_round_trip() {
+    for (path, format) in [
+        ("example-pdbs/1yyf.pdb", Format::Pdb),
+        ("example-pdbs/1yyf.pdb", Format::Mmcif),
+        ("example-pdbs/1yyf.cif", Format::Pdb),
+        ("example-pdbs/1yyf.cif", Format::Mmcif),
+    ] {
+        let pdb = read(path);
+        let result = round_trip(&pdb, format);
+        for entity in pdb.entities().filter(|e| e.is_polymer()) {
+            let other = result.entity(&entity.id).unwrap();
+            assert_eq!(entity.sequence, other.sequence);
+            assert_eq!(entity.chains, other.chains);
+            assert_eq!(entity.one_letter_sequence(), other.one_letter_sequence());
+        }
+    }
+    let pdb = read("example-pdbs/1yyf.cif");
+    let result = round_trip(&pdb, Format::Mmcif);
+    assert_eq!(
+        pdb.entities().collect::<Vec<_>>(),
+        result.entities().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn seqres_from_mmcif() {
+    // Without DBREF records the SEQRES records are not aligned to the residues when reading, so
+    // the polymer entities are kept without adding residues
+    for name in ["1ubq", "1yyf", "3nig"] {
+        let pdb = read(&format!("example-pdbs/{name}.cif"));
+        let mut buffer = Vec::new();
+        save_pdb_raw(&pdb, BufWriter::new(&mut buffer), StrictnessLevel::Loose);
+        let text = String::from_utf8(buffer).unwrap();
+        assert!(text.contains("SEQRES"), "{name}");
+        assert!(text.contains("COMPND"), "{name}");
+        let (result, errors) = ReadOptions::default()
+            .set_level(StrictnessLevel::Loose)
+            .set_format(Format::Pdb)
+            .read_raw(BufReader::new(text.as_bytes()))
+            .unwrap();
+        assert!(errors
+            .iter()
+            .all(|e| !e.short_description().contains("SEQRES")));
+        assert_eq!(result.residue_count(), pdb.residue_count());
+        let polymers: Vec<_> = pdb.entities().filter(|e| e.is_polymer()).collect();
+        assert_eq!(result.entity_count(), polymers.len(), "{name}");
+        for entity in polymers {
+            let other = result.entity(&entity.id).unwrap();
+            assert_eq!(entity.sequence, other.sequence, "{name}");
+            assert_eq!(entity.chains, other.chains, "{name}");
+        }
+    }
+
+    // Entities without molecules in the header are described in the COMPND records
+    let mut pdb = read("example-pdbs/1yyf.cif");
+    pdb.header = None;
+    let result = round_trip(&pdb, Format::Pdb);
+    for entity in pdb.entities().filter(|e| e.is_polymer()) {
+        let other = result.entity(&entity.id).unwrap();
+        assert_eq!(entity.description, other.description);
+        assert_eq!(entity.chains, other.chains);
+    }
+}
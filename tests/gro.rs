@@ -0,0 +1,132 @@
+// Test reading and writing GROMACS files.
+
+mod common;
+
+use common::{close, dump, read};
+use pdbtbx::*;
+
+#[test]
+fn read_gro() {
+    let pdb = read("example-pdbs/small.gro");
+    assert_eq!(
+        pdb.header.as_ref().unwrap().title.as_deref(),
+        Some("Small test system t=   0.00000")
+    );
+    assert_eq!(pdb.model_count(), 1);
+    assert_eq!(pdb.chain_count(), 1);
+    assert_eq!(pdb.residue_count(), 4);
+    assert_eq!(pdb.atom_count(), 7);
+    let atom = pdb.atoms().nth(1).unwrap();
+    assert_eq!(atom.name(), "CA");
+    assert_eq!(atom.element(), Some(&Element::C));
+    assert!(!atom.hetero());
+    assert!(close(atom.x(), 26.27) && close(atom.y(), 25.41) && close(atom.z(), 2.84));
+    assert_eq!(atom.velocity(), Some((0.0, 1.0, -1.0)));
+    let sodium = pdb.atoms().last().unwrap();
+    assert_eq!(sodium.element(), Some(&Element::Na));
+    assert!(sodium.hetero());
+    let water = pdb.residues().nth(2).unwrap();
+    assert_eq!(water.name(), Some("SOL"));
+    assert_eq!(water.atoms().nth(1).unwrap().element(), Some(&Element::H));
+    assert_eq!(
+        pdb.unit_cell,
+        Some(UnitCell::new(50.0, 50.0, 50.0, 90.0, 90.0, 90.0))
+    );
+    let without_hydrogens = ReadOptions::default()
+        .set_discard_hydrogens(true)
+        .read("example-pdbs/small.gro")
+        .unwrap()
+        .0;
+    assert_eq!(without_hydrogens.atom_count(), 5);
+}
+
+#[test]
+fn frames_and_precision() {
+    let text = "\
+Frame one
+    2
+    1ALA      N    1  0.10000  0.20000  0.30000
+    1ALA     CA    2  0.40000  0.50000  0.60000
+   1.00000   2.00000   3.00000   0.00000   0.00000   0.00000   0.00000   0.50000   0.50000
+Frame two
+    2
+    1ALA      N    1  0.11000  0.21000  0.31000
+    1ALA     CA    2  0.41000  0.51000  0.61000
+   1.00000   2.00000   3.00000   0.00000   0.00000   0.00000   0.00000   0.50000   0.50000
+";
+    let (pdb, _) = ReadOptions::default()
+        .set_format(Format::Gro)
+        .set_level(StrictnessLevel::Loose)
+        .read_raw(std::io::BufReader::new(text.as_bytes()))
+        .unwrap();
+    assert_eq!(pdb.model_count(), 2);
+    assert_eq!(pdb.model(1).unwrap().serial_number(), 2);
+    assert!(close(pdb.model(1).unwrap().atom(1).unwrap().z(), 6.1));
+    assert!(pdb.atoms().all(|a| a.velocity().is_none()));
+    let cell = pdb.unit_cell.as_ref().unwrap();
+    assert!(close(cell.a(), 10.0) && close(cell.b(), 20.0));
+    assert!(close(cell.c(), (25.0_f64 + 25.0 + 900.0).sqrt()));
+    assert!(cell.beta() < 90.0);
+    let [a, b, c] = cell.vectors();
+    assert!(close(a[0], 10.0) && close(b[1], 20.0) && close(c[0], 5.0) && close(c[1], 5.0));
+}
+
+#[test]
+fn save_and_read() {
+    let pdb = read("example-pdbs/small.gro");
+    let name = dump("small.gro");
+    save(&pdb, &name, StrictnessLevel::Loose).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&name).unwrap(),
+        std::fs::read_to_string("example-pdbs/small.gro").unwrap()
+    );
+}
+
+#[test]
+fn save_pdb_as_gro() {
+    let pdb = read("example-pdbs/1ubq.pdb");
+    let name = dump("1ubq.gro");
+    save_gro(&pdb, &name, StrictnessLevel::Loose).unwrap();
+    let saved = read(&name);
+    assert_eq!(pdb.atom_count(), saved.atom_count());
+    assert_eq!(pdb.residue_count(), saved.residue_count());
+    for (a, b) in pdb.atoms().zip(saved.atoms()) {
+        assert_eq!(a.name(), b.name());
+        assert!(a.distance(b) < 0.01);
+    }
+    let (a, b) = (pdb.unit_cell.unwrap(), saved.unit_cell.unwrap());
+    assert!(close(a.a(), b.a()) && close(a.gamma(), b.gamma()));
+}
+
+#[test]
+fn wrapping_numbers() {
+    let mut residues = Vec::new();
+    for i in 0..100_002 {
+        let atom = Atom::new(false, i + 1, "", "O", 0.0, 0.0, 0.0, 1.0, 0.0, "O", 0).unwrap();
+        #[allow(clippy::cast_possible_wrap)]
+        residues.push(
+            Residue::new(
+                (i + 1) as isize,
+                None,
+                Conformer::new("HOH", None, Some(atom)),
+            )
+            .unwrap(),
+        );
+    }
+    let mut pdb = PDB::new();
+    pdb.add_model(Model::from_iter(
+        1,
+        Chain::from_iter("A", residues.into_iter()).into_iter(),
+    ));
+    let name = dump("wrapping.gro");
+    save_gro(&pdb, &name, StrictnessLevel::Loose).unwrap();
+    let text = std::fs::read_to_string(&name).unwrap();
+    assert!(text.contains("99999HOH      O99999"));
+    assert!(text.contains("    0HOH      O    0"));
+    let saved = read(&name);
+    assert_eq!(saved.atom_count(), 100_002);
+    assert_eq!(saved.residue_count(), 100_002);
+    let last = saved.atoms_with_hierarchy().last().unwrap();
+    assert_eq!(last.atom().serial_number(), 100_002);
+    assert_eq!(last.residue().serial_number(), 100_002);
+}
@@ -0,0 +1,66 @@
+// Test reading and writing the entry metadata (HEADER, TITLE, COMPND, SOURCE, KEYWDS, EXPDTA, AUTHOR, REVDAT).
+
+mod common;
+
+use common::{read, round_trip};
+use pdbtbx::*;
+
+#[test]
+fn header_pdb_and_mmcif() {
+    let pdb = read("example-pdbs/1yyf.pdb");
+    let cif = read("example-pdbs/1yyf.cif");
+    for header in [pdb.header.as_ref().unwrap(), cif.header.as_ref().unwrap()] {
+        assert_eq!(
+            header.classification.as_deref(),
+            Some("CHAPERONE/HYDROLASE")
+        );
+        assert_eq!(header.deposition_date.as_deref(), Some("2005-02-24"));
+        assert!(header.has_method("X-RAY DIFFRACTION"));
+        assert_eq!(header.authors.len(), 4);
+        assert_eq!(header.keywords.len(), 5);
+        assert!(header
+            .title
+            .as_ref()
+            .unwrap()
+            .to_uppercase()
+            .starts_with("CORRECTION OF X-RAY INTENSITIES FROM AN HSLV-HSLU CO-CRYSTAL"));
+        assert_eq!(header.molecules.len(), 2);
+        assert_eq!(header.molecules[1].chains(), vec!["D", "C"]);
+        assert_eq!(header.molecules[1].organism_taxonomy_id(), Some("1423"));
+        let organisms: Vec<String> = header
+            .organisms()
+            .iter()
+            .map(|o| o.to_uppercase())
+            .collect();
+        assert_eq!(organisms, vec!["ESCHERICHIA COLI", "BACILLUS SUBTILIS"]);
+        assert_eq!(header.revisions[0].date.as_deref(), Some("2005-07-12"));
+    }
+    let header = pdb.header.as_ref().unwrap();
+    assert_eq!(header.authors[0], "J.WANG");
+    assert_eq!(header.revisions[1].records, vec!["VERSN"]);
+    assert_eq!(
+        header.molecules[0].compound_value("SYNONYM"),
+        Some("HEAT SHOCK PROTEIN HSLU")
+    );
+}
+
+#[test]
+fn header_round_trip() {
+    let pdb = read("example-pdbs/1yyf.pdb");
+    assert_eq!(pdb.header, round_trip(&pdb, Format::Pdb).header);
+    let cif = read("example-pdbs/1yyf.cif");
+    assert_eq!(cif.header, round_trip(&cif, Format::Mmcif).header);
+
+    // Convert between the formats, the authors are written in the native style of each format
+    let converted = round_trip(&cif, Format::Pdb);
+    let header = converted.header.as_ref().unwrap();
+    assert_eq!(header.authors[0], "J.WANG");
+    assert_eq!(
+        header.organisms(),
+        vec!["Escherichia coli", "Bacillus subtilis"]
+    );
+    assert_eq!(
+        header.deposition_date,
+        cif.header.as_ref().unwrap().deposition_date
+    );
+}
@@ -0,0 +1,204 @@
+// Test keeping both the label and author defined identifiers of mmCIF files.
+
+use pdbtbx::*;
+use std::io::{BufReader, BufWriter};
+
+/// Read the file at the given path, with the hierarchy built from the label identifiers if `label` is set
+fn read(path: &str, label: bool) -> PDB {
+    ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .set_label_identifiers(label)
+        .read(path)
+        .unwrap()
+        .0
+}
+
+/// Write the given PDB as mmCIF and read it back
+fn round_trip(pdb: &PDB, label: bool) -> PDB {
+    let mut buffer = Vec::new();
+    save_mmcif_raw(pdb, BufWriter::new(&mut buffer));
+    ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .set_format(Format::Mmcif)
+        .set_label_identifiers(label)
+        .read_raw(BufReader::new(buffer.as_slice()))
+        .unwrap()
+        .0
+}
+
+/// Get all identifiers of all residues in the structure
+#[allow(clippy::type_complexity)]
+fn identifiers(
+    pdb: &PDB,
+) -> Vec<(
+    String,
+    (isize, Option<String>),
+    Option<String>,
+    Option<isize>,
+    Option<String>,
+    Option<String>,
+    Option<isize>,
+)> {
+    pdb.chains()
+        .flat_map(|chain| {
+            chain.residues().map(move |r| {
+                (
+                    chain.id().to_string(),
+                    (
+                        r.serial_number(),
+                        r.insertion_code().map(ToString::to_string),
+                    ),
+                    r.label_asym_id().map(ToString::to_string),
+                    r.label_seq_id(),
+                    r.label_entity_id().map(ToString::to_string),
+                    r.auth_asym_id().map(ToString::to_string),
+                    r.auth_seq_id(),
+                )
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn author_identifiers() {
+    let pdb = read("example-pdbs/1yyf.cif", false);
+    assert_eq!(
+        pdb.chains().map(Chain::id).collect::<Vec<_>>(),
+        ["A", "B", "D", "C"]
+    );
+    // The label and auth chain ids of the second entity are swapped in this file
+    let chain = pdb.chains().find(|c| c.id() == "D").unwrap();
+    let residue = chain.residue(0).unwrap();
+    assert_eq!(residue.label_asym_id(), Some("C"));
+    assert_eq!(residue.label_entity_id(), Some("2"));
+    assert_eq!(residue.auth_asym_id(), Some("D"));
+    assert_eq!(residue.auth_seq_id(), Some(residue.serial_number()));
+    let ligand = pdb
+        .chains()
+        .find(|c| c.id() == "A")
+        .unwrap()
+        .residues()
+        .find(|r| r.name() == Some("ADP"))
+        .unwrap();
+    assert_eq!(ligand.label_asym_id(), Some("E"));
+    assert_eq!(ligand.label_seq_id(), None);
+    assert_eq!(ligand.label_entity_id(), Some("3"));
+    assert_eq!(ligand.auth_asym_id(), Some("A"));
+    assert_eq!(ligand.auth_seq_id(), Some(905));
+}
+
+#[test]
+fn label_identifiers() {
+    let pdb = read("example-pdbs/1yyf.cif", true);
+    assert_eq!(
+        pdb.chains().map(Chain::id).collect::<Vec<_>>(),
+        ["A", "B", "C", "D", "E", "F"]
+    );
+    let chain = pdb.chains().find(|c| c.id() == "C").unwrap();
+    let residue = chain.residue(0).unwrap();
+    assert_eq!(residue.label_seq_id(), Some(residue.serial_number()));
+    assert_eq!(residue.auth_asym_id(), Some("D"));
+    // Non polymers do not have a label seq id, so the auth seq id is used
+    let ligand = pdb.chains().find(|c| c.id() == "E").unwrap();
+    assert_eq!(ligand.residue_count(), 1);
+    assert_eq!(ligand.residue(0).unwrap().serial_number(), 905);
+    assert_eq!(ligand.residue(0).unwrap().auth_asym_id(), Some("A"));
+    // The references to residues use the label identifiers as well
+    assert_eq!(pdb.entity("2").unwrap().chains, ["C", "D"]);
+    assert_eq!(pdb.entity("3").unwrap().chains, ["E", "F"]);
+    for site in pdb.sites() {
+        for residue in &site.residues {
+            assert!(pdb
+                .chains()
+                .filter(|c| c.id() == residue.chain_id)
+                .any(|c| c.residues().any(|r| r.id() == residue.id())));
+        }
+    }
+    for chain in pdb.chains() {
+        for residue in chain.residues() {
+            assert_eq!(
+                residue.sequence_index(),
+                residue
+                    .label_seq_id()
+                    .filter(|_| chain.id() != "E" && chain.id() != "F")
+                    .map(|s| s as usize - 1)
+            );
+        }
+    }
+}
+
+#[test]
+fn search_identifiers() {
+    let pdb = read("example-pdbs/1yyf.cif", false);
+    let ligand = pdb
+        .find(Term::ChainId("A".to_string()) & Term::ConformerName("ADP".to_string()))
+        .count();
+    assert!(ligand > 0);
+    assert_eq!(
+        pdb.find(Search::Single(Term::LabelAsymId("E".to_string())))
+            .count(),
+        ligand
+    );
+    assert_eq!(
+        pdb.find(Search::Single(Term::LabelEntityId("3".to_string())))
+            .count(),
+        pdb.find(Search::Single(Term::ConformerName("ADP".to_string())))
+            .count()
+    );
+    assert_eq!(
+        pdb.find(Search::Single(Term::AuthAsymId("D".to_string())))
+            .count(),
+        pdb.find(Search::Single(Term::ChainId("D".to_string())))
+            .count()
+    );
+    assert_eq!(
+        pdb.find(Term::AuthSeqId(905) & Term::ChainId("A".to_string()))
+            .count(),
+        ligand
+    );
+    assert_eq!(
+        pdb.find(Term::LabelSeqId(1) & Term::ChainId("A".to_string()))
+            .count(),
+        pdb.find(Term::ResidueSerialNumber(1) & Term::ChainId("A".to_string()))
+            .count()
+    );
+    let pdb = read("example-pdbs/1yyf.pdb", false);
+    assert_eq!(
+        pdb.find(Search::Single(Term::LabelAsymId("A".to_string())))
+            .count(),
+        0
+    );
+}
+
+#[test]
+fn identifiers_round_trip() {
+    for label in [false, true] {
+        let pdb = read("example-pdbs/1yyf.cif", label);
+        let saved = round_trip(&pdb, label);
+        assert_eq!(identifiers(&pdb), identifiers(&saved));
+        // The order of the chains depends on the order of the atoms in the file
+        let mut expected = identifiers(&read("example-pdbs/1yyf.cif", !label));
+        let mut other = identifiers(&round_trip(&pdb, !label));
+        expected.sort();
+        other.sort();
+        assert_eq!(expected, other);
+    }
+}
+
+#[test]
+fn generated_identifiers() {
+    let pdb = read("example-pdbs/1yyf.pdb", false);
+    let saved = round_trip(&pdb, false);
+    for (chain, saved_chain) in pdb.chains().zip(saved.chains()) {
+        assert_eq!(chain.id(), saved_chain.id());
+        // Residues without atoms, as listed in REMARK 465, are not saved
+        let residues = chain.residues().filter(|r| r.atom_count() > 0);
+        for (residue, saved_residue) in residues.zip(saved_chain.residues()) {
+            assert_eq!(residue.label_asym_id(), None);
+            assert_eq!(saved_residue.auth_asym_id(), Some(chain.id()));
+            assert_eq!(saved_residue.auth_seq_id(), Some(residue.serial_number()));
+            assert!(saved_residue.label_asym_id().is_some());
+            assert!(saved_residue.label_entity_id().is_some());
+        }
+    }
+}
@@ -0,0 +1,63 @@
+// Test reading and writing missing residues and atoms (REMARK 465/470 and _pdbx_unobs_or_zero_occ_*).
+
+mod common;
+
+use common::{read, round_trip};
+use pdbtbx::*;
+
+/// Get all missing residues and atoms per chain
+fn missing(pdb: &PDB) -> Vec<(String, Vec<MissingResidue>, Vec<MissingAtoms>)> {
+    pdb.chains()
+        .map(|c| {
+            (
+                c.id().to_string(),
+                c.missing_residues().cloned().collect(),
+                c.missing_atoms().cloned().collect(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn missing_pdb_and_mmcif() {
+    let pdb = read("example-pdbs/1yyf.pdb");
+    let cif = read("example-pdbs/1yyf.cif");
+    for structure in [&pdb, &cif] {
+        let chain = structure.chains().find(|c| c.id() == "A").unwrap();
+        let first = chain.missing_residues().next().unwrap();
+        assert_eq!(first.name, "ILE");
+        assert_eq!(first.id(), (175, None));
+        let residue = chain.residues().find(|r| r.id() == (174, None)).unwrap();
+        assert_eq!(chain.missing_atoms_of(residue), ["O"]);
+    }
+    assert_eq!(missing(&pdb), missing(&cif));
+    assert_eq!(
+        pdb.chains()
+            .map(Chain::missing_residue_count)
+            .sum::<usize>(),
+        72
+    );
+
+    let pdb = read("example-pdbs/7az6.pdb");
+    let chain = pdb.chains().find(|c| c.id() == "A").unwrap();
+    assert_eq!(chain.missing_residues().next().unwrap().id(), (-19, None));
+    let arginine = chain
+        .missing_atoms()
+        .find(|m| m.id() == (24, None))
+        .unwrap();
+    assert_eq!(arginine.name, "ARG");
+    assert_eq!(arginine.atoms, ["CG", "CD", "NE", "CZ", "NH1", "NH2"]);
+}
+
+#[test]
+fn missing_round_trip() {
+    for path in ["example-pdbs/1yyf.pdb", "example-pdbs/7az6.pdb"] {
+        let pdb = read(path);
+        assert_eq!(missing(&pdb), missing(&round_trip(&pdb, Format::Pdb)));
+        assert_eq!(missing(&pdb), missing(&round_trip(&pdb, Format::Mmcif)));
+    }
+    let cif = read("example-pdbs/1yyf.cif");
+    assert_eq!(missing(&cif), missing(&round_trip(&cif, Format::Mmcif)));
+    // The PDB file does not contain the original remarks, so these are generated
+    assert_eq!(missing(&cif), missing(&round_trip(&cif, Format::Pdb)));
+}
@@ -0,0 +1,200 @@
+// Test reading and writing MMTF files.
+
+mod common;
+
+use common::{dump, read};
+use pdbtbx::*;
+
+/// Check that the hierarchy of both structures is the same, apart from the information that
+/// cannot be given in MMTF (missing residues, database references and modifications)
+fn assert_same_hierarchy(a: &PDB, b: &PDB, name: &str) {
+    assert_eq!(a.model_count(), b.model_count(), "{name}");
+    for (model_a, model_b) in a.models().zip(b.models()) {
+        assert_eq!(model_a.serial_number(), model_b.serial_number(), "{name}");
+        assert_eq!(model_a.chain_count(), model_b.chain_count(), "{name}");
+        for (chain_a, chain_b) in model_a.chains().zip(model_b.chains()) {
+            assert_eq!(chain_a.id(), chain_b.id(), "{name}");
+            assert_eq!(chain_a.residue_count(), chain_b.residue_count(), "{name}");
+            for (residue_a, residue_b) in chain_a.residues().zip(chain_b.residues()) {
+                let context = format!("{name} {} {:?}", chain_a.id(), residue_a.id());
+                assert_eq!(residue_a.id(), residue_b.id(), "{context}");
+                assert_eq!(
+                    residue_a.label_asym_id(),
+                    residue_b.label_asym_id(),
+                    "{context}"
+                );
+                assert_eq!(
+                    residue_a.label_seq_id(),
+                    residue_b.label_seq_id(),
+                    "{context}"
+                );
+                assert_eq!(
+                    residue_a.label_entity_id(),
+                    residue_b.label_entity_id(),
+                    "{context}"
+                );
+                assert_eq!(
+                    residue_a.auth_asym_id(),
+                    residue_b.auth_asym_id(),
+                    "{context}"
+                );
+                assert_eq!(
+                    residue_a.auth_seq_id(),
+                    residue_b.auth_seq_id(),
+                    "{context}"
+                );
+                assert_eq!(
+                    residue_a.sequence_index(),
+                    residue_b.sequence_index(),
+                    "{context}"
+                );
+                for (conformer_a, conformer_b) in residue_a.conformers().zip(residue_b.conformers())
+                {
+                    assert_eq!(conformer_a.id(), conformer_b.id(), "{context}");
+                    assert!(conformer_a.atoms().eq(conformer_b.atoms()), "{context}");
+                }
+            }
+        }
+    }
+}
+
+/// The covalent and disulfide bonds, the only bonds that can be given in MMTF
+fn covalent_bonds(pdb: &PDB) -> Vec<(&Atom, &Atom, Bond)> {
+    pdb.bonds()
+        .filter(|(_, _, bond)| bond.is_covalent() || *bond == Bond::Disulfide)
+        .map(|(a, b, bond)| {
+            let bond = if bond == Bond::Disulfide {
+                bond
+            } else {
+                Bond::Covalent
+            };
+            (a, b, bond)
+        })
+        .collect()
+}
+
+#[test]
+fn same_structure_as_mmcif() {
+    for name in ["1ubq", "1yyf", "3pdz", "6wlb"] {
+        // The atoms are numbered in the order of the file, which is the order of the hierarchy
+        // for both saved files
+        let original = read(&format!("example-pdbs/{name}.cif"));
+        let cif = dump(&format!("mmtf_{name}.cif"));
+        let path = dump(&format!("{name}.mmtf"));
+        save_mmcif(&original, &cif, StrictnessLevel::Loose).unwrap();
+        save(&original, &path, StrictnessLevel::Loose).unwrap();
+        let pdb = read(&cif);
+        let reread = read(&path);
+        assert_eq!(reread.identifier, pdb.identifier, "{name}");
+        assert_eq!(reread.unit_cell, pdb.unit_cell, "{name}");
+        assert_eq!(reread.symmetry, pdb.symmetry, "{name}");
+        assert_eq!(reread.resolution(), pdb.resolution(), "{name}");
+        let header = |p: &PDB| {
+            p.header.as_ref().map(|h| {
+                (
+                    h.title.clone(),
+                    h.deposition_date.clone(),
+                    h.experimental_methods.clone(),
+                )
+            })
+        };
+        assert_eq!(header(&reread), header(&pdb), "{name}");
+        assert_same_hierarchy(&reread, &pdb, name);
+        let size = |path: &str| std::fs::metadata(path).unwrap().len();
+        assert!(size(&path) < size(&cif), "{name}");
+    }
+}
+
+#[test]
+fn bonds() {
+    let pdb = read("example-pdbs/6wlb.cif");
+    let path = dump("6wlb_bonds.mmtf");
+    save_mmtf(&pdb, &path, StrictnessLevel::Loose).unwrap();
+    let reread = read(&path);
+    let expected = covalent_bonds(&pdb);
+    assert!(!expected.is_empty());
+    assert_eq!(covalent_bonds(&reread), expected);
+}
+
+#[test]
+fn entities() {
+    let pdb = read("example-pdbs/1ubq.cif");
+    let path = dump("1ubq_entities.mmtf");
+    save_mmtf(&pdb, &path, StrictnessLevel::Loose).unwrap();
+    let reread = read(&path);
+    assert_eq!(reread.entity_count(), pdb.entity_count());
+    for (a, b) in reread.entities().zip(pdb.entities()) {
+        assert_eq!(a.id, b.id);
+        assert_eq!(a.entity_type, b.entity_type);
+        assert_eq!(a.description, b.description);
+        assert_eq!(a.chains, b.chains);
+        assert_eq!(a.sequence, b.sequence);
+        assert_eq!(a.one_letter_sequence(), b.one_letter_sequence());
+    }
+}
+
+#[test]
+fn from_pdb_file() {
+    // Structures read from PDB files do not have label identifiers and entities
+    let pdb = read("example-pdbs/models.pdb");
+    let path = dump("models.mmtf.gz");
+    save_gz(&pdb, &path, StrictnessLevel::Loose, None).unwrap();
+    let reread = read(&path);
+    assert_eq!(reread.model_count(), pdb.model_count());
+    assert_eq!(reread.total_atom_count(), pdb.total_atom_count());
+    assert!(reread
+        .atoms()
+        .zip(pdb.atoms())
+        .all(|(a, b)| a.pos() == b.pos() && a.name() == b.name()));
+}
+
+#[test]
+fn options() {
+    let path = dump("1ubq_options.mmtf");
+    save(
+        &read("example-pdbs/1ubq.cif"),
+        &path,
+        StrictnessLevel::Loose,
+    )
+    .unwrap();
+    let (pdb, _) = ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .set_discard_hydrogens(true)
+        .set_label_identifiers(true)
+        .read(&path)
+        .unwrap();
+    assert!(pdb.atoms().all(|a| a.element() != Some(&Element::H)));
+    assert_eq!(pdb.chains().map(Chain::id).collect::<Vec<_>>(), ["A", "B"]);
+}
+
+#[test]
+fn invalid_file() {
+    let read = |bytes: &[u8]| {
+        ReadOptions::default()
+            .set_format(Format::Mmtf)
+            .read_raw(std::io::BufReader::new(bytes))
+    };
+    assert!(read(b"data_test").is_err());
+    // An empty map, missing all required fields
+    assert!(read(&[0x80]).is_err());
+
+    // A group name with invalid characters is an error instead of a panic
+    let mut bytes = Vec::new();
+    save_mmtf_raw(
+        &self::read("example-pdbs/1ubq.cif"),
+        std::io::BufWriter::new(&mut bytes),
+    );
+    let name = b"\xa3MET";
+    let position = bytes.windows(4).position(|w| w == name).unwrap();
+    bytes[position + 2] = 0x07;
+    let result = ReadOptions::default()
+        .set_format(Format::Mmtf)
+        .set_level(StrictnessLevel::Loose)
+        .read_raw(std::io::BufReader::new(bytes.as_slice()));
+    let errors = match result {
+        Ok((_, errors)) | Err(errors) => errors,
+    };
+    assert!(errors
+        .iter()
+        .any(|e| e.short_description() == "Invalid identifiers"));
+}
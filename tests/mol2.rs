@@ -0,0 +1,153 @@
+// Test reading and writing Tripos MOL2 files, including the automatic SYBYL atom types.
+
+mod common;
+
+use common::{dump, read};
+use pdbtbx::*;
+
+/// Get the names of the bonded atoms with the bond order of all bonds in the PDB
+fn bonds(pdb: &PDB) -> Vec<(String, String, Option<BondOrder>)> {
+    pdb.bonds_with_details()
+        .map(|(a, b, _, details)| {
+            (
+                a.name().to_string(),
+                b.name().to_string(),
+                details.and_then(|d| d.order),
+            )
+        })
+        .collect()
+}
+
+/// Get the atom types of the atoms in the given model, as written in the MOL2 file
+fn atom_types(pdb: &PDB, model: usize) -> Vec<String> {
+    pdb.model(model)
+        .unwrap()
+        .atoms()
+        .map(|a| a.atom_type().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn read_mol2() {
+    let pdb = read("example-pdbs/ligand.mol2");
+    assert_eq!(
+        pdb.header.as_ref().unwrap().title.as_deref(),
+        Some("PHENOL")
+    );
+    assert_eq!(pdb.model_count(), 2);
+    let phenol = pdb.model(0).unwrap();
+    assert_eq!(phenol.chain(0).unwrap().id(), "L");
+    let residue = phenol.residue(0).unwrap();
+    assert_eq!(residue.name(), Some("PHO"));
+    assert_eq!(residue.serial_number(), 1);
+    assert_eq!(residue.atom_count(), 7);
+    let oxygen = residue.atoms().last().unwrap();
+    assert_eq!(oxygen.element(), Some(&Element::O));
+    assert_eq!(oxygen.atom_type(), Some("O.3"));
+    assert!((oxygen.partial_charge().unwrap() + 0.44).abs() < 0.0001);
+    assert!((oxygen.x() - 2.75).abs() < 0.0001);
+
+    let acetate = pdb.model(1).unwrap();
+    assert_eq!(acetate.chain(0).unwrap().id(), "A");
+    let residue = acetate.residue(0).unwrap();
+    assert_eq!(residue.name(), Some("ACT"));
+    assert_eq!(residue.serial_number(), 5);
+    assert_eq!(atom_types(&pdb, 1), ["C.3", "C.2", "O.co2", "O.co2"]);
+
+    let bonds = bonds(&pdb);
+    assert_eq!(bonds.len(), 10);
+    assert_eq!(
+        bonds[0],
+        ("C1".into(), "C2".into(), Some(BondOrder::Aromatic))
+    );
+    assert_eq!(
+        bonds[6],
+        ("C1".into(), "O1".into(), Some(BondOrder::Single))
+    );
+    assert_eq!(
+        bonds[8],
+        ("C2".into(), "O1".into(), Some(BondOrder::Double))
+    );
+    assert!(pdb.unit_cell.is_none());
+}
+
+#[test]
+fn invalid_files() {
+    let read_text = |text: &str| {
+        ReadOptions::default()
+            .set_format(Format::Mol2)
+            .read_raw(std::io::BufReader::new(text.as_bytes()))
+    };
+    assert!(read_text("").is_err());
+    assert!(read_text("@<TRIPOS>ATOM\n1 C1 0 0 0 C.3\n").is_err());
+    assert!(read_text("@<TRIPOS>MOLECULE\nX\n@<TRIPOS>ATOM\n1 C1 0 0 C.3\n").is_err());
+    assert!(read_text("@<TRIPOS>MOLECULE\nX\n@<TRIPOS>ATOM\n1 C1 0 x 0 C.3\n").is_err());
+    assert!(read_text(
+        "@<TRIPOS>MOLECULE\nX\n@<TRIPOS>ATOM\n1 C1 0 0 0 C.3\n@<TRIPOS>BOND\n1 1 2 1\n"
+    )
+    .is_err());
+    let (pdb, _) = read_text("@<TRIPOS>MOLECULE\nX\n@<TRIPOS>ATOM\n1 C1 0 0 0 C.3\n").unwrap();
+    assert_eq!(pdb.atom_count(), 1);
+    assert_eq!(pdb.residue(0).unwrap().name(), Some("UNL"));
+}
+
+#[test]
+fn save_and_read() {
+    let pdb = read("example-pdbs/ligand.mol2");
+    let name = dump("ligand.mol2");
+    save(&pdb, &name, StrictnessLevel::Loose).unwrap();
+    let saved = read(&name);
+    assert_eq!(pdb.model_count(), saved.model_count());
+    for (a, b) in pdb.atoms().zip(saved.atoms()) {
+        assert_eq!(a.name(), b.name());
+        assert_eq!(a.element(), b.element());
+        assert_eq!(a.atom_type(), b.atom_type());
+        assert_eq!(a.partial_charge(), b.partial_charge());
+        assert!(a.distance(b) < 0.0001);
+    }
+    for (a, b) in pdb.residues().zip(saved.residues()) {
+        assert_eq!(a.name(), b.name());
+        assert_eq!(a.serial_number(), b.serial_number());
+    }
+    assert_eq!(saved.chains().next().unwrap().id(), "L");
+    assert_eq!(bonds(&pdb), bonds(&saved));
+}
+
+#[test]
+fn save_as_pdb() {
+    // The bonds inside the ligand are written as CONECT records, not as LINK records
+    let pdb = read("example-pdbs/ligand.mol2");
+    let name = dump("ligand_mol2.pdb");
+    save(&pdb, &name, StrictnessLevel::Loose).unwrap();
+    let text = std::fs::read_to_string(&name).unwrap();
+    assert!(!text.lines().any(|line| line.starts_with("LINK")));
+    assert!(text.lines().any(|line| line.starts_with("CONECT")));
+}
+
+#[test]
+fn automatic_atom_types() {
+    // The bond orders and connectivity of the SDF file give the atom types of acetate
+    let ligand = read("example-pdbs/ligand.sdf");
+    let name = dump("ligand_from_sdf.mol2");
+    save(&ligand, &name, StrictnessLevel::Loose).unwrap();
+    let saved = read(&name);
+    assert_eq!(atom_types(&saved, 0), ["C.3", "C.2", "O.co2", "O.co2"]);
+    assert_eq!(atom_types(&saved, 1), ["C.3", "N.4"]);
+
+    // Without bonds the backbone atoms of proteins are typed by their name
+    let protein = read("example-pdbs/1ubq.pdb");
+    let name = dump("1ubq.mol2");
+    save(&protein, &name, StrictnessLevel::Loose).unwrap();
+    let saved = read(&name);
+    assert_eq!(saved.atom_count(), protein.atom_count());
+    assert_eq!(saved.residue_count(), protein.residue_count());
+    let types: Vec<&str> = saved
+        .residue(0)
+        .unwrap()
+        .atoms()
+        .map(|a| a.atom_type().unwrap())
+        .collect();
+    assert_eq!(&types[..4], ["N.am", "C.3", "C.2", "O.2"]);
+    assert_eq!(saved.residue(0).unwrap().name(), Some("MET"));
+    assert!(saved.unit_cell.is_some());
+}
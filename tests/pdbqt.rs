@@ -0,0 +1,138 @@
+// Test reading and writing PDBQT files, including the torsion trees and automatic AutoDock atom types.
+
+mod common;
+
+use common::{dump, read};
+use pdbtbx::*;
+
+/// Get the atom types of the atoms in the given residue
+fn atom_types(residue: &Residue) -> Vec<&str> {
+    residue.atoms().map(|a| a.atom_type().unwrap()).collect()
+}
+
+#[test]
+fn read_pdbqt() {
+    let pdb = read("example-pdbs/ligand_out.pdbqt");
+    assert_eq!(pdb.model_count(), 2);
+    assert_eq!(pdb.torsion_tree_count(), 2);
+    let residue = pdb.residue(0).unwrap();
+    assert_eq!(residue.name(), Some("UNL"));
+    assert_eq!(atom_types(residue), ["C", "C", "OA", "HD"]);
+    let elements: Vec<_> = residue.atoms().map(|a| *a.element().unwrap()).collect();
+    assert_eq!(elements, [Element::C, Element::C, Element::O, Element::H]);
+    let oxygen = residue.atom(2).unwrap();
+    assert!((oxygen.partial_charge().unwrap() + 0.398).abs() < 0.0001);
+    assert!((pdb.model(1).unwrap().atom(2).unwrap().x() - 3.5).abs() < 0.0001);
+
+    let tree = pdb.torsion_tree(1).unwrap();
+    assert_eq!(tree.root, [1]);
+    assert_eq!(tree.branches.len(), 1);
+    assert_eq!((tree.branches[0].parent, tree.branches[0].child), (1, 2));
+    assert_eq!(tree.branches[0].atoms, [2]);
+    assert_eq!(tree.branches[0].branches[0].atoms, [3, 4]);
+    assert_eq!(tree.branch_count(), 2);
+    assert_eq!(tree.torsdof, Some(2));
+    assert_eq!(tree.atoms(), [1, 2, 3, 4]);
+    assert_eq!(tree.remarks.len(), 5);
+    assert_eq!(tree.vina_result(), Some((-4.2, 0.0, 0.0)));
+    assert_eq!(
+        pdb.torsion_tree(2).unwrap().vina_result(),
+        Some((-3.9, 1.234, 2.021))
+    );
+}
+
+#[test]
+fn invalid_files() {
+    let read_text = |text: &str| {
+        ReadOptions::default()
+            .set_format(Format::Pdbqt)
+            .read_raw(std::io::BufReader::new(text.as_bytes()))
+    };
+    let atom = "ATOM      1  C1  UNL     1       1.000   2.000   3.000  0.00  0.00    +0.044 C ";
+    let (pdb, _) = read_text(&format!("ROOT\n{atom}\nENDROOT\nTORSDOF 0\n")).unwrap();
+    assert_eq!(pdb.atom_count(), 1);
+    assert_eq!(pdb.torsion_tree(0).unwrap().torsdof, Some(0));
+    assert!(read_text(&format!("ROOT\n{atom}\nENDROOT\nBRANCH 1 2\n")).is_err());
+    assert!(read_text(&format!("ROOT\n{atom}\nENDROOT\nENDBRANCH 1 2\n")).is_err());
+    assert!(read_text(&format!("BRANCH 1 2\n{atom}\nENDBRANCH 1 3\n")).is_err());
+    assert!(read_text(&format!("ROOT\n{atom}\nENDROOT\nTORSDOF x\n")).is_err());
+    assert!(read_text(&atom.replace("+0.044", "charge")).is_err());
+    // Atom lines cut off before the end of the coordinates are errors, as in PDB files
+    for length in (4..7).chain(20..54) {
+        assert!(read_text(&atom[..length]).is_err(), "{length}");
+    }
+}
+
+#[test]
+fn save_and_read() {
+    let pdb = read("example-pdbs/ligand_out.pdbqt");
+    let name = dump("ligand_out.pdbqt");
+    save(&pdb, &name, StrictnessLevel::Loose).unwrap();
+    let saved = read(&name);
+    assert_eq!(saved.model_count(), 2);
+    for (a, b) in pdb.atoms().zip(saved.atoms()) {
+        assert_eq!(a.name(), b.name());
+        assert_eq!(a.element(), b.element());
+        assert_eq!(a.atom_type(), b.atom_type());
+        assert_eq!(a.partial_charge(), b.partial_charge());
+        assert!(a.distance(b) < 0.0001);
+    }
+    assert_eq!(
+        pdb.torsion_trees().collect::<Vec<_>>(),
+        saved.torsion_trees().collect::<Vec<_>>()
+    );
+    let text = std::fs::read_to_string(&name).unwrap();
+    for line in std::fs::read_to_string("example-pdbs/ligand_out.pdbqt")
+        .unwrap()
+        .lines()
+        .filter(|line| !line.starts_with("MODEL") && !line.starts_with("ATOM"))
+    {
+        assert!(text.contains(&format!("{line}\n")), "{line}");
+    }
+}
+
+#[test]
+fn automatic_atom_types() {
+    // Without bonds the atom types of proteins are derived from the element and names
+    let protein = read("example-pdbs/1ubq.pdb");
+    let name = dump("1ubq.pdbqt");
+    save(&protein, &name, StrictnessLevel::Loose).unwrap();
+    let saved = read(&name);
+    assert_eq!(saved.atom_count(), protein.atom_count());
+    assert_eq!(saved.torsion_tree_count(), 0);
+    let atom_type = |residue: usize, name: &str| {
+        let residue = saved.residue(residue).unwrap();
+        let atom = residue.atoms().find(|a| a.name() == name).unwrap();
+        atom.atom_type().unwrap().to_string()
+    };
+    assert_eq!(saved.residue(0).unwrap().name(), Some("MET"));
+    for (name, expected) in [
+        ("N", "N"),
+        ("CA", "C"),
+        ("O", "OA"),
+        ("SD", "SA"),
+        ("H1", "HD"),
+        ("HA", "H"),
+    ] {
+        assert_eq!(atom_type(0, name), expected, "{name}");
+    }
+    assert_eq!(saved.residue(1).unwrap().name(), Some("GLN"));
+    assert_eq!(atom_type(1, "OE1"), "OA");
+    assert_eq!(atom_type(1, "HE21"), "HD");
+    assert_eq!(saved.residue(3).unwrap().name(), Some("PHE"));
+    for (name, expected) in [("CB", "C"), ("CG", "A"), ("CZ", "A")] {
+        assert_eq!(atom_type(3, name), expected, "{name}");
+    }
+    assert!(std::fs::read_to_string(&name).unwrap().contains("\nTER\n"));
+
+    // With bonds the aromatic carbons, acceptors, and polar hydrogens are recognised
+    let mut phenol = read("example-pdbs/ligand.mol2");
+    phenol.remove_models_by(|model| model.serial_number() != 1);
+    let name = dump("phenol.pdbqt");
+    save(&phenol, &name, StrictnessLevel::Loose).unwrap();
+    let saved = read(&name);
+    assert_eq!(
+        atom_types(saved.residue(0).unwrap()),
+        ["A", "A", "A", "A", "A", "A", "OA"]
+    );
+}
@@ -0,0 +1,156 @@
+// Test reading and writing PQR files.
+
+mod common;
+
+use common::dump;
+use pdbtbx::*;
+
+/// Read the given PQR text
+fn read_text(text: &str, options: &mut ReadOptions) -> Result<(PDB, Vec<PDBError>), Vec<PDBError>> {
+    options
+        .set_format(Format::Pqr)
+        .read_raw(std::io::BufReader::new(text.as_bytes()))
+}
+
+#[test]
+fn read() {
+    let (pdb, _) = ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .read("example-pdbs/small.pqr")
+        .unwrap();
+    assert_eq!(pdb.model_count(), 1);
+    assert_eq!(pdb.chain_count(), 2);
+    assert_eq!(pdb.atom_count(), 9);
+    let atom = pdb.atoms().next().unwrap();
+    assert_eq!(atom.name(), "N");
+    assert_eq!(atom.element(), Some(&Element::N));
+    assert_eq!(atom.partial_charge(), Some(-0.3));
+    assert_eq!(atom.radius(), Some(1.824));
+    assert_eq!(atom.pos(), (27.340, 24.430, 2.614));
+    let residue = pdb.chains().next().unwrap().residues().nth(2).unwrap();
+    assert_eq!(residue.id(), (2, Some("A")));
+    let water = pdb.chain(1).unwrap().residues().next().unwrap();
+    assert_eq!(water.name(), Some("HOH"));
+    assert_eq!(water.serial_number(), 101);
+    assert!(water.atoms().all(Atom::hetero));
+    assert_eq!(water.atoms().nth(1).unwrap().serial_number(), 100_000);
+}
+
+#[test]
+fn read_without_chains() {
+    let text = "\
+MODEL        1
+ATOM      1  N   ALA     1      -0.677  -1.230  -0.491 -0.4157 1.8240
+ATOM      2  CA  ALA     1       0.000   0.000   0.000  0.0337 1.9080
+TER
+ATOM      3  N   GLY     1       1.000   0.000   0.000 -0.4157 1.8240
+ENDMDL
+MODEL        2
+ATOM      1  N   ALA     1      -0.577  -1.230  -0.491 -0.4157 1.8240
+ATOM      2  CA  ALA     1       0.100   0.000   0.000  0.0337 1.9080
+TER
+ATOM      3  N   GLY     1       1.100   0.000   0.000 -0.4157 1.8240
+ENDMDL
+";
+    let (pdb, _) = read_text(
+        text,
+        ReadOptions::default().set_level(StrictnessLevel::Loose),
+    )
+    .unwrap();
+    assert_eq!(pdb.model_count(), 2);
+    assert_eq!(pdb.model(1).unwrap().serial_number(), 2);
+    // The element is guessed from the name, a carbon alpha is not calcium
+    let alpha = pdb.atoms().nth(1).unwrap();
+    assert_eq!(alpha.name(), "CA");
+    assert_eq!(alpha.element(), Some(&Element::C));
+    for model in pdb.models() {
+        let chains: Vec<&str> = model.chains().map(Chain::id).collect();
+        assert_eq!(chains, ["A", "B"]);
+    }
+    let (first, _) = read_text(
+        text,
+        ReadOptions::default()
+            .set_level(StrictnessLevel::Loose)
+            .set_only_first_model(true),
+    )
+    .unwrap();
+    assert_eq!(first.model_count(), 1);
+    assert_eq!(first.atom_count(), 3);
+}
+
+#[test]
+fn invalid_lines() {
+    let errors = read_text(
+        "ATOM      1  N   ALA A   1      -0.677  -1.230  -0.491 -0.4157\n",
+        &mut ReadOptions::default(),
+    )
+    .unwrap_err();
+    assert_eq!(errors[0].short_description(), "Invalid PQR atom line");
+    let errors = read_text(
+        "ATOM      1  N   ALA A   1      -0.677  -1.230  -0.491 -0.4157 -1.0\n",
+        &mut ReadOptions::default(),
+    )
+    .unwrap_err();
+    assert_eq!(errors[0].short_description(), "Invalid charge or radius");
+    for line in [
+        "ATOM      1  N   ALÄ A   1      -0.677  -1.230  -0.491 -0.4157 1.8240\n",
+        "ATOM      1  N   ALA Ä   1      -0.677  -1.230  -0.491 -0.4157 1.8240\n",
+        "ATOM      1  N   ALA A   1Ä     -0.677  -1.230  -0.491 -0.4157 1.8240\n",
+    ] {
+        let errors = read_text(line, &mut ReadOptions::default()).unwrap_err();
+        assert_eq!(
+            errors[0].short_description(),
+            "Invalid identifiers",
+            "{line}"
+        );
+    }
+}
+
+#[test]
+fn save_and_read() {
+    let (pdb, _) = ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .read("example-pdbs/small.pqr")
+        .unwrap();
+    let name = dump("small.pqr");
+    save(&pdb, &name, StrictnessLevel::Loose).unwrap();
+    let (saved, _) = ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .read(&name)
+        .unwrap();
+    assert_eq!(pdb.atom_count(), saved.atom_count());
+    for (a, b) in pdb.atoms_with_hierarchy().zip(saved.atoms_with_hierarchy()) {
+        assert_eq!(a.chain().id(), b.chain().id());
+        assert_eq!(a.residue().id(), b.residue().id());
+        assert_eq!(a.conformer().name(), b.conformer().name());
+        assert_eq!(a.atom(), b.atom());
+    }
+}
+
+#[test]
+fn save_pdb_as_pqr() {
+    let (mut pdb, _) = ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .read("example-pdbs/1ubq.pdb")
+        .unwrap();
+    for atom in pdb.atoms_mut() {
+        atom.set_partial_charge(0.5).unwrap();
+        atom.set_radius(1.5).unwrap();
+    }
+    let name = dump("1ubq.pqr");
+    save_pqr(&pdb, &name, StrictnessLevel::Loose).unwrap();
+    let (saved, _) = ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .read(&name)
+        .unwrap();
+    assert_eq!(pdb.atom_count(), saved.atom_count());
+    assert_eq!(pdb.residue_count(), saved.residue_count());
+    assert!(saved
+        .atoms()
+        .all(|a| a.partial_charge() == Some(0.5) && a.radius() == Some(1.5)));
+    for (a, b) in pdb.atoms().zip(saved.atoms()) {
+        assert_eq!(a.name(), b.name());
+        assert_eq!(a.serial_number(), b.serial_number());
+        assert!(a.distance(b) < 0.001);
+    }
+}
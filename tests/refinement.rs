@@ -0,0 +1,49 @@
+// Test reading and writing the resolution and refinement statistics (REMARK 2, 3, and 200).
+
+mod common;
+
+use common::{read, round_trip};
+use pdbtbx::*;
+
+#[test]
+fn refinement_pdb_and_mmcif() {
+    let pdb = read("example-pdbs/1yyf.pdb");
+    assert_eq!(pdb.resolution(), Some(4.16));
+    let refinement = pdb.refinement.as_ref().unwrap();
+    assert_eq!(refinement.program.as_deref(), Some("REFMAC 5.1.24"));
+    assert_eq!(refinement.r_work, Some(0.277));
+    assert_eq!(refinement.r_free, Some(0.346));
+    assert_eq!(refinement.protein_atoms, Some(9161));
+
+    let cif = read("example-pdbs/1yyf.cif");
+    assert_eq!(cif.resolution(), Some(4.16));
+    let r_free = cif.refinement.as_ref().unwrap().r_free.unwrap();
+    assert!((r_free - 0.34572).abs() < 1e-6);
+
+    for path in ["example-pdbs/3nig.pdb", "example-pdbs/3nig.cif"] {
+        let structure = read(path);
+        let reflections = structure.refinement.unwrap().reflections.unwrap();
+        assert_eq!(reflections.redundancy, Some(6.63));
+    }
+}
+
+#[test]
+fn refinement_round_trip() {
+    let pdb = read("example-pdbs/1yyf.pdb");
+    assert_eq!(pdb.refinement, round_trip(&pdb, Format::Pdb).refinement);
+    let cif = read("example-pdbs/3nig.cif");
+    assert_eq!(cif.refinement, round_trip(&cif, Format::Mmcif).refinement);
+
+    // Without the original remarks the statistics are written as new REMARK 2, 3, and 200 records
+    let converted = round_trip(&cif, Format::Pdb);
+    let original = cif.refinement.as_ref().unwrap();
+    let refinement = converted.refinement.as_ref().unwrap();
+    assert_eq!(converted.resolution(), cif.resolution());
+    assert!((refinement.r_free.unwrap() - original.r_free.unwrap()).abs() < 1e-6);
+    assert_eq!(refinement.program, original.program);
+    assert_eq!(refinement.protein_atoms, original.protein_atoms);
+    assert_eq!(
+        refinement.reflections.as_ref().unwrap().redundancy,
+        original.reflections.as_ref().unwrap().redundancy
+    );
+}
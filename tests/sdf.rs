@@ -0,0 +1,183 @@
+// Test reading and writing SDF files, and merging ligands into structures.
+
+mod common;
+
+use common::{dump, read};
+use pdbtbx::*;
+
+/// Get the names of the bonded atoms with the bond order of all bonds in the PDB
+fn bonds(pdb: &PDB) -> Vec<(String, String, Option<BondOrder>)> {
+    pdb.bonds_with_details()
+        .map(|(a, b, bond, details)| {
+            assert_eq!(bond, Bond::Covalent);
+            (
+                a.name().to_string(),
+                b.name().to_string(),
+                details.and_then(|d| d.order),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn read_sdf() {
+    let pdb = read("example-pdbs/ligand.sdf");
+    assert_eq!(pdb.header.as_ref().unwrap().title.as_deref(), Some("ACT"));
+    assert_eq!(pdb.model_count(), 2);
+    let acetate = pdb.model(0).unwrap();
+    assert_eq!(acetate.residue_count(), 1);
+    let residue = acetate.residue(0).unwrap();
+    assert_eq!(residue.name(), Some("ACT"));
+    let names: Vec<&str> = residue.atoms().map(Atom::name).collect();
+    assert_eq!(names, ["C1", "C2", "O1", "O2"]);
+    let oxygen = residue.atoms().last().unwrap();
+    assert_eq!(oxygen.element(), Some(&Element::O));
+    assert_eq!(oxygen.charge(), -1);
+    assert!(oxygen.hetero());
+    assert!((oxygen.y() + 1.05).abs() < 0.0001);
+
+    let methylammonium = pdb.model(1).unwrap();
+    assert_eq!(methylammonium.residue(0).unwrap().name(), Some("UNL"));
+    let nitrogen = methylammonium.atom(1).unwrap();
+    assert_eq!(nitrogen.name(), "N1");
+    assert_eq!(nitrogen.charge(), 1);
+    assert!((nitrogen.x() - 1.47).abs() < 0.0001);
+
+    assert_eq!(
+        bonds(&pdb),
+        [
+            ("C1".into(), "C2".into(), Some(BondOrder::Single)),
+            ("C2".into(), "O1".into(), Some(BondOrder::Double)),
+            ("C2".into(), "O2".into(), Some(BondOrder::Single)),
+            ("C1".into(), "N1".into(), Some(BondOrder::Single)),
+        ]
+    );
+}
+
+#[test]
+fn invalid_files() {
+    let read_text = |text: &str| {
+        ReadOptions::default()
+            .set_format(Format::Sdf)
+            .read_raw(std::io::BufReader::new(text.as_bytes()))
+    };
+    assert!(read_text("").is_err());
+    assert!(read_text("title\n\n").is_err());
+    let errors = read_text("$$$$\n").unwrap_err();
+    assert_eq!(errors[0].short_description(), "Incomplete record");
+    assert!(read_text("title\n\n\n  x  0\nM  END\n").is_err());
+    assert!(
+        read_text("title\n\n\n  2  0\n    0.0000    0.0000    0.0000 C   0  0\nM  END\n").is_err()
+    );
+    assert!(read_text(
+        "title\n\n\n  1  1\n    0.0000    0.0000    0.0000 C   0  0\n  1  2  1  0\nM  END\n"
+    )
+    .is_err());
+    assert!(
+        read_text("title\n\n\n  1  0\n    0.0000    0.0000    0.0000 C   0  0\nM  END\n").is_ok()
+    );
+
+    // V3000 records with truncated lines or a different number of atoms or bonds
+    let v3000 = |counts: &str, atoms: &str, bonds: &str| {
+        format!(
+            "title\n\n\n  0  0  0     0  0            999 V3000\nM  V30 BEGIN CTAB\nM  V30 COUNTS {counts} 0 0 0\nM  V30 BEGIN ATOM\n{atoms}M  V30 END ATOM\nM  V30 BEGIN BOND\n{bonds}M  V30 END BOND\nM  V30 END CTAB\nM  END\n"
+        )
+    };
+    let atoms = "M  V30 1 C 0 0 0 0\nM  V30 2 O 1.2 0 0 0\n";
+    let bond = "M  V30 1 2 1 2\n";
+    assert!(read_text(&v3000("2 1", atoms, bond)).is_ok());
+    let errors =
+        read_text(&v3000("2 1", "M  V30 1 C 0 0 0 0\nM  V30 2 O 1.2\n", bond)).unwrap_err();
+    assert_eq!(errors[0].short_description(), "Invalid atom line");
+    let errors = read_text(&v3000("2 1", atoms, "M  V30 1 2 1\n")).unwrap_err();
+    assert_eq!(errors[0].short_description(), "Invalid bond line");
+    let errors = read_text(&v3000("3 1", atoms, bond)).unwrap_err();
+    assert_eq!(errors[0].short_description(), "Incomplete record");
+    assert!(read_text(&v3000("2 2", atoms, bond)).is_err());
+    assert!(read_text(&v3000("x 1", atoms, bond)).is_err());
+}
+
+#[test]
+fn save_and_read() {
+    let pdb = read("example-pdbs/ligand.sdf");
+    let name = dump("ligand.sdf");
+    save(&pdb, &name, StrictnessLevel::Loose).unwrap();
+    let saved = read(&name);
+    assert_eq!(pdb.model_count(), saved.model_count());
+    for (a, b) in pdb.atoms().zip(saved.atoms()) {
+        assert_eq!(a.name(), b.name());
+        assert_eq!(a.element(), b.element());
+        assert_eq!(a.charge(), b.charge());
+        assert!(a.distance(b) < 0.0001);
+    }
+    assert_eq!(bonds(&pdb), bonds(&saved));
+}
+
+#[test]
+fn save_as_pdb() {
+    // The bonds inside the ligand are written as CONECT records, not as LINK records
+    let pdb = read("example-pdbs/ligand.sdf");
+    let name = dump("ligand_sdf.pdb");
+    save(&pdb, &name, StrictnessLevel::Loose).unwrap();
+    let text = std::fs::read_to_string(&name).unwrap();
+    assert!(!text.lines().any(|line| line.starts_with("LINK")));
+    assert!(text.lines().any(|line| line.starts_with("CONECT")));
+}
+
+#[test]
+fn merge_and_export_ligand() {
+    let ligand = read("example-pdbs/ligand.sdf");
+    let mut receptor = read("example-pdbs/1ubq.pdb");
+    let atom_count = receptor.atom_count();
+    let last_serial = receptor.atoms().map(Atom::serial_number).max().unwrap();
+    receptor.add_ligand(&ligand, 0, "L").unwrap();
+    assert!(receptor.add_ligand(&ligand, 2, "L").is_none());
+    assert_eq!(receptor.atom_count(), atom_count + 4);
+    let chain = receptor.chains().find(|c| c.id() == "L").unwrap();
+    assert_eq!(chain.residue(0).unwrap().serial_number(), 1);
+    assert_eq!(
+        chain.atoms().next().unwrap().serial_number(),
+        last_serial + 1
+    );
+    assert_eq!(bonds(&receptor).len(), 3);
+    // The ligand is copied
+    assert_eq!(ligand.atoms().next().unwrap().serial_number(), 1);
+
+    let name = dump("merged_ligand.sdf");
+    save_sdf(
+        &receptor,
+        Search::Single(Term::ConformerName("ACT".to_owned())),
+        &name,
+        StrictnessLevel::Loose,
+    )
+    .unwrap();
+    let saved = read(&name);
+    assert_eq!(saved.model_count(), 1);
+    assert_eq!(saved.atom_count(), 4);
+    assert_eq!(bonds(&saved), bonds(&ligand)[..3]);
+}
+
+#[test]
+fn large_ligand() {
+    let mut conformer = Conformer::new("BIG", None, None).unwrap();
+    for i in 0..1000 {
+        let atom = Atom::new(true, i + 1, "", "C", i as f64, 0.0, 0.0, 1.0, 0.0, "C", 0).unwrap();
+        conformer.add_atom(atom);
+    }
+    let mut pdb = PDB::new();
+    pdb.add_model(Model::from_iter(
+        1,
+        Chain::from_iter("A", Residue::new(1, None, Some(conformer)).into_iter()).into_iter(),
+    ));
+    for i in 1..1000 {
+        pdb.add_bond((i, None), (i + 1, None), Bond::Covalent)
+            .unwrap();
+    }
+    let name = dump("large_ligand.sdf");
+    save_sdf(&pdb, Search::Known(true), &name, StrictnessLevel::Loose).unwrap();
+    assert!(std::fs::read_to_string(&name).unwrap().contains("V3000"));
+    let saved = read(&name);
+    assert_eq!(saved.atom_count(), 1000);
+    assert_eq!(saved.bonds().count(), 999);
+    assert!((saved.atom(999).unwrap().x() - 999.0).abs() < 0.0001);
+}
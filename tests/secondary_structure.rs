@@ -0,0 +1,142 @@
+// Test reading and writing the secondary structure (HELIX/SHEET and _struct_conf/_struct_sheet_range).
+
+use pdbtbx::cif::Document;
+use pdbtbx::*;
+use std::io::{BufReader, BufWriter};
+
+#[test]
+fn secondary_structure_pdb_and_mmcif() {
+    let pdb = open("example-pdbs/1yyf.pdb");
+    let cif = open("example-pdbs/1yyf.cif");
+    for structure in [&pdb, &cif] {
+        assert_eq!(96, structure.secondary_structure_count());
+        assert_eq!(
+            56,
+            structure
+                .secondary_structure()
+                .filter(|s| s.is_helix())
+                .count()
+        );
+    }
+    assert_eq!(
+        pdb.secondary_structure().collect::<Vec<_>>(),
+        cif.secondary_structure().collect::<Vec<_>>()
+    );
+
+    if let Some(SecondaryStructure::Strand(strand)) = pdb.secondary_structure().nth(57) {
+        assert_eq!(strand.sheet_id, "A");
+        assert_eq!(strand.strand_number, 2);
+        assert_eq!(strand.strand_count, 5);
+        assert_eq!(strand.sense, StrandSense::Parallel);
+        assert_eq!(strand.start, ResidueReference::new("A", (252, None), "ILE"));
+        let registration = strand.registration.as_ref().unwrap();
+        assert_eq!(registration.current_atom, "O");
+        assert_eq!(registration.previous.serial_number, 81);
+    } else {
+        panic!("Second strand of sheet A not found");
+    }
+
+    let chain = pdb.chains().next().unwrap();
+    let tag = |serial_number| {
+        let residue = chain
+            .residues()
+            .find(|r| r.serial_number() == serial_number)
+            .unwrap();
+        match pdb.secondary_structure_of(chain, residue) {
+            Some(SecondaryStructure::Helix(_)) => 'H',
+            Some(SecondaryStructure::Strand(_)) => 'E',
+            None => 'C',
+        }
+    };
+    assert_eq!(['C', 'H', 'H', 'C', 'E'], [4, 5, 14, 15, 78].map(tag));
+}
+
+#[test]
+fn secondary_structure_of_chain() {
+    for name in ["example-pdbs/1yyf.pdb", "example-pdbs/1yyf.cif"] {
+        let pdb = open(name);
+        let mut assigned = 0;
+        for chain in pdb.chains() {
+            let elements = pdb.secondary_structure_of_chain(chain);
+            assert_eq!(elements.len(), chain.residue_count());
+            for (residue, element) in chain.residues().zip(elements) {
+                assert_eq!(element, pdb.secondary_structure_of(chain, residue));
+                assigned += usize::from(element.is_some());
+            }
+        }
+        assert!(assigned > 0);
+    }
+}
+
+#[test]
+fn secondary_structure_round_trip() {
+    let original = open("example-pdbs/1yyf.pdb");
+
+    let mut buffer = Vec::new();
+    save_pdb_raw(
+        &original,
+        BufWriter::new(&mut buffer),
+        StrictnessLevel::Medium,
+    );
+    let (pdb, errors) = ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .set_format(Format::Pdb)
+        .read_raw(BufReader::new(buffer.as_slice()))
+        .unwrap();
+    assert!(!errors
+        .iter()
+        .any(|e| e.short_description() == "MASTER checksum failed"));
+    assert_eq!(
+        original.secondary_structure().collect::<Vec<_>>(),
+        pdb.secondary_structure().collect::<Vec<_>>()
+    );
+
+    let mut buffer = Vec::new();
+    save_mmcif_raw(&original, BufWriter::new(&mut buffer));
+    let (cif, _) = ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .set_format(Format::Mmcif)
+        .read_raw(BufReader::new(buffer.as_slice()))
+        .unwrap();
+    let mut expected: Vec<SecondaryStructure> = original.secondary_structure().cloned().collect();
+    // The helix serial numbers are not saved in mmCIF, they are numbered in order
+    for (index, element) in expected.iter_mut().enumerate() {
+        if let SecondaryStructure::Helix(helix) = element {
+            helix.serial_number = index + 1;
+        }
+    }
+    assert_eq!(
+        expected,
+        cif.secondary_structure().cloned().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn secondary_structure_mmcif_quoting() {
+    let mut pdb = open("example-pdbs/1yyf.pdb");
+    pdb.add_secondary_structure(SecondaryStructure::Helix(Helix::new(
+        99,
+        "Q1",
+        ResidueReference::new("A", (900, Some("#")), "A B"),
+        ResidueReference::new("A", (901, None), "it's"),
+        HelixClass::default(),
+    )));
+    let mut buffer = Vec::new();
+    save_mmcif_raw(&pdb, BufWriter::new(&mut buffer));
+    let document = Document::parse(&String::from_utf8(buffer).unwrap()).unwrap();
+    let helices = document.blocks[0].category("struct_conf").unwrap();
+    let row = helices.row_count() - 1;
+    let text = |column| helices.get_text(row, column).unwrap();
+    assert_eq!(text("beg_label_comp_id").as_deref(), Some("A B"));
+    assert_eq!(text("pdbx_beg_PDB_ins_code").as_deref(), Some("#"));
+    assert_eq!(text("end_auth_comp_id").as_deref(), Some("it's"));
+    assert_eq!(text("end_auth_seq_id").as_deref(), Some("901"));
+}
+
+fn open(filename: &str) -> PDB {
+    ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .read(filename)
+        .unwrap()
+        .0
+}
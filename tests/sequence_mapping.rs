@@ -0,0 +1,95 @@
+// Test mapping residues onto the deposited sequence (SEQRES alignment and _pdbx_poly_seq_scheme).
+
+mod common;
+
+use common::{read, round_trip};
+use pdbtbx::*;
+
+/// Check that all residues are mapped onto the same sequence positions in both structures
+fn assert_same_mapping(a: &PDB, b: &PDB) {
+    let mut mapped = 0;
+    for chain in a.chains() {
+        let other = b.chains().find(|c| c.id() == chain.id()).unwrap();
+        for residue in chain.residues() {
+            let index = residue.sequence_index();
+            let other_residue = if let Some(r) = other.residues().find(|r| r.id() == residue.id()) {
+                r
+            } else {
+                continue;
+            };
+            assert_eq!(
+                index,
+                other_residue.sequence_index(),
+                "chain {} residue {:?}",
+                chain.id(),
+                residue.id()
+            );
+            if let Some(index) = index {
+                assert_eq!(chain.residue_at_sequence_index(index), Some(residue));
+                mapped += 1;
+            }
+        }
+    }
+    assert!(mapped > 0);
+}
+
+#[test]
+fn alignment_matches_sequence_scheme() {
+    for name in ["1yyf", "3nig"] {
+        let pdb = read(&format!("example-pdbs/{name}.pdb"));
+        let cif = read(&format!("example-pdbs/{name}.cif"));
+        assert_same_mapping(&pdb, &cif);
+    }
+}
+
+#[test]
+fn ligands_and_waters_are_not_mapped() {
+    let pdb = read("example-pdbs/1yyf.cif");
+    let chain = pdb.chains().next().unwrap();
+    for residue in chain.residues() {
+        if matches!(residue.name(), Some("ADP" | "HOH")) {
+            assert_eq!(residue.sequence_index(), None);
+        } else {
+            let index = residue.sequence_index().unwrap();
+            assert_eq!(
+                pdb.entity_of_chain(chain.id()).unwrap().sequence[index],
+                residue.name().unwrap()
+            );
+        }
+    }
+}
+
+#[test]
+fn sequence_mapping_round_trip() {
+    let pdb = read("example-pdbs/3nig.cif");
+    assert_same_mapping(&pdb, &round_trip(&pdb, Format::Mmcif));
+    // The SEQRES records are only saved if they can be read back consistently, which needs the
+    // DBREF records of the PDB file
+    let pdb = read("example-pdbs/1yyf.pdb");
+    assert_same_mapping(&pdb, &round_trip(&pdb, Format::Pdb));
+}
+
+#[test]
+fn sequence_mapping_survives_renumbering() {
+    let pdb = read("example-pdbs/1yyf.cif");
+    let mut renumbered = pdb.clone();
+    renumbered.renumber();
+    let (chain, other) = (
+        pdb.chains().next().unwrap(),
+        renumbered.chains().next().unwrap(),
+    );
+    assert_eq!(chain.residue_count(), other.residue_count());
+    let mut mapped = 0;
+    for (residue, other_residue) in chain.residues().zip(other.residues()) {
+        assert_eq!(residue.sequence_index(), other_residue.sequence_index());
+        if let Some(index) = other_residue.sequence_index() {
+            assert_eq!(other.residue_at_sequence_index(index), Some(other_residue));
+            mapped += 1;
+        }
+    }
+    assert!(mapped > 0);
+    assert!(chain
+        .residues()
+        .zip(other.residues())
+        .any(|(a, b)| a.serial_number() != b.serial_number()));
+}
@@ -0,0 +1,80 @@
+// Test reading and writing sites (SITE, REMARK 800, _struct_site, and _struct_site_gen).
+
+mod common;
+
+use common::{read, round_trip};
+use pdbtbx::*;
+
+#[test]
+fn sites_pdb() {
+    let pdb = read("example-pdbs/1kmk.pdb");
+    assert_eq!(pdb.site_count(), 2);
+    let site = pdb.site("AC1").unwrap();
+    assert_eq!(site.evidence.as_deref(), Some("SOFTWARE"));
+    assert_eq!(
+        site.details.as_deref(),
+        Some("BINDING SITE FOR RESIDUE SEC A 502")
+    );
+    assert_eq!(site.residues.len(), 10);
+    assert_eq!(
+        site.residues[0],
+        ResidueReference::new("A", (30, None), "ALA")
+    );
+    assert_eq!(
+        site.residues[9],
+        ResidueReference::new("A", (732, None), "HOH")
+    );
+    assert_eq!(pdb.site("AC2").unwrap().residues.len(), 16);
+}
+
+#[test]
+fn sites_pdb_and_mmcif() {
+    let pdb = read("example-pdbs/1yyf.pdb");
+    let cif = read("example-pdbs/1yyf.cif");
+    let site = cif.site("AC1").unwrap();
+    assert_eq!(site.evidence.as_deref(), Some("Software"));
+    assert_eq!(
+        site.details.as_deref(),
+        Some("BINDING SITE FOR RESIDUE ADP A 905")
+    );
+    assert_eq!(site.residues.len(), 12);
+    assert_eq!(
+        site.residues[11],
+        ResidueReference::new("A", (393, None), "ARG")
+    );
+    assert_eq!(cif.site("AC2").unwrap().residues.len(), 14);
+    for (a, b) in pdb.sites().zip(cif.sites()) {
+        assert_eq!(a.id, b.id);
+        assert_eq!(a.residues, b.residues);
+    }
+}
+
+#[test]
+fn sites_find() {
+    let pdb = read("example-pdbs/1kmk.pdb");
+    for site in pdb.sites() {
+        let mut residues = Vec::new();
+        for hierarchy in pdb.find(site.search()) {
+            assert!(pdb
+                .sites_of(hierarchy.chain(), hierarchy.residue())
+                .any(|s| s.id == site.id));
+            let residue = ResidueReference::from_residue(hierarchy.chain(), hierarchy.residue());
+            if !residues.contains(&residue) {
+                residues.push(residue);
+            }
+        }
+        assert_eq!(residues, site.residues);
+    }
+}
+
+#[test]
+fn sites_round_trip() {
+    for path in ["example-pdbs/1kmk.pdb", "example-pdbs/1yyf.cif"] {
+        let pdb = read(path);
+        assert!(pdb.site_count() > 0);
+        for format in [Format::Pdb, Format::Mmcif] {
+            let saved = round_trip(&pdb, format);
+            assert!(pdb.sites().eq(saved.sites()));
+        }
+    }
+}
@@ -0,0 +1,637 @@
+// Test writing and reading DCD, XTC, and TRR trajectories over a topology.
+
+mod common;
+
+use common::{dump, read};
+use pdbtbx::*;
+use std::io::Cursor;
+
+/// Create frames in which the atoms of the given topology are moved and the unit cell grows
+fn frames(topology: &PDB, count: usize) -> Vec<Frame> {
+    let start = Frame::from_pdb(topology).unwrap();
+    (0..count)
+        .map(|index| {
+            let shift = index as f64 * 0.75;
+            let mut frame = start.clone();
+            for (atom, position) in frame.positions.iter_mut().enumerate() {
+                position.0 += shift;
+                position.1 -= shift * (atom % 3) as f64;
+                position.2 += (atom as f64 * 0.37 + shift).sin();
+            }
+            frame.step = Some(100 + 50 * index);
+            frame.time = Some(0.2 + 0.1 * index as f64);
+            frame.unit_cell = Some(UnitCell::new(50.0 + shift, 51.0, 52.0, 90.0, 90.0, 90.0));
+            frame
+        })
+        .collect()
+}
+
+/// Assert that the positions of the two frames are the same within the given tolerance (Å)
+fn assert_positions(a: &Frame, b: &Frame, tolerance: f64) {
+    assert_eq!(a.positions.len(), b.positions.len());
+    for (a, b) in a.positions.iter().zip(&b.positions) {
+        let distance = ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2) + (a.2 - b.2).powi(2)).sqrt();
+        assert!(distance < tolerance, "{a:?} {b:?}");
+    }
+}
+
+/// Assert that the unit cells of the two frames are the same
+fn assert_unit_cell(a: &Frame, b: &Frame) {
+    let (a, b) = (a.unit_cell.as_ref().unwrap(), b.unit_cell.as_ref().unwrap());
+    assert!((a.a() - b.a()).abs() < 0.001);
+    assert!((a.b() - b.b()).abs() < 0.001);
+    assert!((a.c() - b.c()).abs() < 0.001);
+    assert!((a.alpha() - b.alpha()).abs() < 0.001);
+    assert!((a.beta() - b.beta()).abs() < 0.001);
+    assert!((a.gamma() - b.gamma()).abs() < 0.001);
+}
+
+/// Write the frames to a file with the given name and read them back
+fn round_trip(
+    topology: &PDB,
+    frames: &[Frame],
+    name: &str,
+) -> Trajectory<std::io::BufReader<std::fs::File>> {
+    let name = dump(name);
+    let mut writer = TrajectoryWriter::create(topology, &name).unwrap();
+    for frame in frames {
+        writer.write_frame(frame).unwrap();
+    }
+    writer.finish().unwrap();
+    let trajectory = Trajectory::open(topology, &name).unwrap();
+    assert_eq!(trajectory.frame_count(), frames.len());
+    assert_eq!(trajectory.atom_count(), topology.atom_count());
+    trajectory
+}
+
+#[test]
+fn dcd() {
+    let topology = read("example-pdbs/1ubq.pdb");
+    let frames = frames(&topology, 5);
+    let trajectory = round_trip(&topology, &frames, "1ubq.dcd");
+    assert_eq!(trajectory.format(), TrajectoryFormat::Dcd);
+    for (expected, frame) in frames.iter().zip(trajectory) {
+        let frame = frame.unwrap();
+        assert_positions(expected, &frame, 0.0001);
+        assert_unit_cell(expected, &frame);
+        assert_eq!(frame.step, expected.step);
+        assert!((frame.time.unwrap() - expected.time.unwrap()).abs() < 0.0001);
+        assert!(frame.velocities.is_none());
+    }
+}
+
+#[test]
+fn xtc() {
+    let topology = read("example-pdbs/1ubq.pdb");
+    let frames = frames(&topology, 4);
+    let trajectory = round_trip(&topology, &frames, "1ubq.xtc");
+    assert_eq!(trajectory.format(), TrajectoryFormat::Xtc);
+    for (expected, frame) in frames.iter().zip(trajectory) {
+        let frame = frame.unwrap();
+        // The coordinates are stored with a precision of 0.01 Å
+        assert_positions(expected, &frame, 0.0087);
+        assert_unit_cell(expected, &frame);
+        assert_eq!(frame.step, expected.step);
+        assert!((frame.time.unwrap() - expected.time.unwrap()).abs() < 0.0001);
+    }
+
+    // Water molecules use the special compression of atoms close to each other
+    let water = read("example-pdbs/liquid.pdb");
+    let frames = vec![Frame::from_pdb(&water).unwrap()];
+    let mut trajectory = round_trip(&water, &frames, "water.xtc");
+    assert_positions(&frames[0], &trajectory.next().unwrap().unwrap(), 0.0087);
+
+    // Frames of at most nine atoms are not compressed
+    let mut small = topology.clone();
+    small.remove_atoms_by(|atom| atom.serial_number() > 9);
+    assert_eq!(small.atom_count(), 9);
+    let frames = self::frames(&small, 2);
+    let mut trajectory = round_trip(&small, &frames, "small.xtc");
+    assert_positions(&frames[1], &trajectory.read_frame(1).unwrap(), 0.0001);
+
+    // A higher precision
+    let name = dump("1ubq_precise.xtc");
+    let frame = Frame::from_pdb(&topology).unwrap();
+    let mut writer = TrajectoryWriter::create(&topology, &name)
+        .unwrap()
+        .with_precision(100_000.0);
+    writer.write_frame(&frame).unwrap();
+    writer.finish().unwrap();
+    let mut trajectory = Trajectory::open(&topology, &name).unwrap();
+    assert_positions(&frame, &trajectory.next().unwrap().unwrap(), 0.0001);
+}
+
+#[test]
+fn trr() {
+    let topology = read("example-pdbs/1ubq.pdb");
+    let mut frames = frames(&topology, 3);
+    for frame in &mut frames {
+        frame.velocities = Some(frame.positions.iter().map(|p| (p.2, p.0, -p.1)).collect());
+    }
+    frames[1].unit_cell = None;
+    frames[2].positions.clear();
+    let trajectory = round_trip(&topology, &frames, "1ubq.trr");
+    assert_eq!(trajectory.format(), TrajectoryFormat::Trr);
+    for (expected, frame) in frames.iter().zip(trajectory) {
+        let frame = frame.unwrap();
+        assert_positions(expected, &frame, 0.0001);
+        let velocities = frame.velocities.as_ref().unwrap();
+        for (a, b) in expected.velocities.as_ref().unwrap().iter().zip(velocities) {
+            assert!((a.0 - b.0).abs() < 0.0001 && (a.1 - b.1).abs() < 0.0001);
+        }
+        assert_eq!(frame.unit_cell.is_some(), expected.unit_cell.is_some());
+        assert_eq!(frame.step, expected.step);
+    }
+}
+
+#[test]
+fn update_topology() {
+    let mut topology = read("example-pdbs/1ubq.pdb");
+    let frames = frames(&topology, 6);
+    let mut trajectory = round_trip(&topology, &frames, "1ubq_update.dcd");
+
+    // Read frames into the topology
+    trajectory.read_into(&mut topology).unwrap().unwrap();
+    assert_eq!(trajectory.position(), 1);
+    assert!((topology.atom(1).unwrap().x() - frames[0].positions[1].0).abs() < 0.0001);
+    trajectory.seek(4).unwrap();
+    trajectory.read_into(&mut topology).unwrap().unwrap();
+    assert!((topology.atom(1).unwrap().x() - frames[4].positions[1].0).abs() < 0.0001);
+    assert!((topology.unit_cell.as_ref().unwrap().a() - 53.0).abs() < 0.0001);
+    assert_eq!(trajectory.len(), 1);
+    assert!(trajectory.read_into(&mut topology).unwrap().is_ok());
+    assert!(trajectory.read_into(&mut topology).is_none());
+
+    // Seek and random access
+    assert!(trajectory.seek(7).is_err());
+    assert!(trajectory.read_frame(6).is_err());
+    trajectory.seek(0).unwrap();
+    let frame = trajectory.nth(2).unwrap().unwrap();
+    assert_positions(&frames[2], &frame, 0.0001);
+    assert_eq!(trajectory.count(), 3);
+
+    // Frames can also be applied to other structures with the same atoms
+    let mut copy = read("example-pdbs/1ubq.pdb");
+    frames[3].apply(&mut copy).unwrap();
+    assert!((copy.atom(10).unwrap().pos().0 - frames[3].positions[10].0).abs() < 0.0001);
+    let mut other = read("example-pdbs/ligand.sdf");
+    assert!(frames[3].apply(&mut other).is_err());
+
+    // Write the topology as frames
+    let name = dump("1ubq_pdb.trr");
+    let mut writer = TrajectoryWriter::create(&topology, &name).unwrap();
+    writer.write_pdb(&topology).unwrap();
+    writer.finish().unwrap();
+    let mut trajectory = Trajectory::open(&topology, &name).unwrap();
+    assert_positions(
+        &Frame::from_pdb(&topology).unwrap(),
+        &trajectory.next().unwrap().unwrap(),
+        0.0001,
+    );
+}
+
+#[test]
+fn in_memory() {
+    let topology = read("example-pdbs/1ubq.pdb");
+    let frames = frames(&topology, 2);
+    for format in [
+        TrajectoryFormat::Dcd,
+        TrajectoryFormat::Xtc,
+        TrajectoryFormat::Trr,
+    ] {
+        let mut writer =
+            TrajectoryWriter::new(Cursor::new(Vec::new()), format, topology.atom_count());
+        for frame in &frames {
+            writer.write_frame(frame).unwrap();
+        }
+        let bytes = writer.finish().unwrap().into_inner();
+        let trajectory = Trajectory::from_reader(&topology, Cursor::new(&bytes), format).unwrap();
+        assert_eq!(trajectory.frame_count(), 2);
+        assert_eq!(trajectory.map(Result::unwrap).count(), 2);
+
+        // A truncated file is an error, except for DCD files where the frames have a fixed size
+        let truncated = Cursor::new(&bytes[..bytes.len() - 100]);
+        if format == TrajectoryFormat::Dcd {
+            let trajectory = Trajectory::from_reader(&topology, truncated, format).unwrap();
+            assert_eq!(trajectory.frame_count(), 1);
+        } else {
+            assert!(Trajectory::from_reader(&topology, truncated, format).is_err());
+        }
+    }
+}
+
+#[test]
+fn invalid_files() {
+    let topology = read("example-pdbs/1ubq.pdb");
+    assert!(Trajectory::open(&topology, "example-pdbs/1ubq.pdb").is_err());
+    assert!(Trajectory::open(&topology, "example-pdbs/missing.dcd").is_err());
+    assert!(TrajectoryWriter::create(&topology, dump("1ubq.traj")).is_err());
+    for format in [
+        TrajectoryFormat::Dcd,
+        TrajectoryFormat::Xtc,
+        TrajectoryFormat::Trr,
+    ] {
+        let text = std::fs::read("example-pdbs/1ubq.pdb").unwrap();
+        assert!(Trajectory::from_reader(&topology, Cursor::new(text), format).is_err());
+    }
+
+    // The number of atoms has to match the topology
+    let ligand = read("example-pdbs/ligand.sdf");
+    let name = dump("ligand.xtc");
+    let mut writer = TrajectoryWriter::create(&ligand, &name).unwrap();
+    writer.write_pdb(&ligand).unwrap();
+    assert!(writer.write_pdb(&topology).is_err());
+    writer.finish().unwrap();
+    assert!(Trajectory::open(&topology, &name).is_err());
+    assert!(Trajectory::open(&ligand, &name).is_ok());
+}
+
+/// Create an XTC file of one frame of the given number of atoms with the given compressed data
+fn xtc_frame(atom_count: i32, range: [i32; 2], small_index: i32, data: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for value in [1995, atom_count, 0, 0] {
+        bytes.extend(i32::to_be_bytes(value));
+    }
+    bytes.extend([0; 36]);
+    bytes.extend(atom_count.to_be_bytes());
+    bytes.extend(1000.0_f32.to_be_bytes());
+    for value in [range[0]; 3].iter().chain(&[range[1]; 3]) {
+        bytes.extend(value.to_be_bytes());
+    }
+    bytes.extend(small_index.to_be_bytes());
+    bytes.extend((data.len() as u32).to_be_bytes());
+    bytes.extend(data);
+    bytes.resize((bytes.len() + 3) / 4 * 4, 0);
+    bytes
+}
+
+#[test]
+fn corrupted_xtc() {
+    let mut topology = read("example-pdbs/1ubq.pdb");
+    topology.remove_atoms_by(|atom| atom.serial_number() > 10);
+    let read_xtc = |bytes: &[u8]| {
+        Trajectory::from_reader(&topology, Cursor::new(bytes), TrajectoryFormat::Xtc)
+            .and_then(|mut trajectory| trajectory.read_frame(0))
+    };
+
+    // A valid frame with all atoms at the same position
+    let valid = xtc_frame(10, [0, 0], 9, &[0, 0, 0]);
+    let frame = read_xtc(&valid).unwrap();
+    assert_eq!(frame.positions, vec![(0.0, 0.0, 0.0); 10]);
+
+    for (range, data) in [
+        // The first coordinate does not fit after adding the minimum
+        ([i32::MAX - 1, i32::MAX], [0b1111_0000, 0, 0]),
+        // A run of close atoms does not fit after adding their offset
+        ([i32::MAX, i32::MAX], [0b0100_1001, 0xff, 0]),
+        // The maximum is smaller than the minimum
+        ([1, -1], [0, 0, 0]),
+        ([1, 0], [0, 0, 0]),
+    ] {
+        let error = read_xtc(&xtc_frame(10, range, 9, &data)).unwrap_err();
+        assert_eq!(error.short_description(), "Invalid XTC file");
+    }
+    assert!(read_xtc(&xtc_frame(10, [0, 0], 8, &[0; 3])).is_err());
+    assert!(read_xtc(&xtc_frame(10, [0, 0], 73, &[0; 3])).is_err());
+
+    // The length of the compressed data overflows when it is padded
+    let mut large = valid.clone();
+    large[..4].copy_from_slice(&2023_i32.to_be_bytes());
+    large.splice(88..92, u64::MAX.to_be_bytes());
+    assert!(read_xtc(&large).is_err());
+
+    // Truncated and corrupted frames written by pdbtbx
+    let mut writer = TrajectoryWriter::new(Cursor::new(Vec::new()), TrajectoryFormat::Xtc, 10);
+    writer.write_pdb(&topology).unwrap();
+    let bytes = writer.finish().unwrap().into_inner();
+    assert!(read_xtc(&bytes).is_ok());
+    for length in 0..bytes.len() {
+        assert!(read_xtc(&bytes[..length]).is_err());
+    }
+    for index in 56..bytes.len() {
+        for flip in [0x01, 0x80, 0xff] {
+            let mut corrupted = bytes.clone();
+            corrupted[index] ^= flip;
+            let _ = read_xtc(&corrupted);
+        }
+    }
+}
+
+/// Assert that the positions are the given positions within 0.001 Å
+fn assert_known_positions(positions: &[(f64, f64, f64)], known: &[(f64, f64, f64)]) {
+    assert_eq!(positions.len(), known.len());
+    for (a, b) in positions.iter().zip(known) {
+        assert!(
+            (a.0 - b.0).abs() < 0.001 && (a.1 - b.1).abs() < 0.001 && (a.2 - b.2).abs() < 0.001,
+            "{a:?} {b:?}"
+        );
+    }
+}
+
+/// Assert that the unit cell has the given lengths (Å) and angles (°)
+fn assert_known_unit_cell(unit_cell: Option<&UnitCell>, known: [f64; 6]) {
+    let unit_cell = unit_cell.unwrap();
+    let values = [
+        unit_cell.a(),
+        unit_cell.b(),
+        unit_cell.c(),
+        unit_cell.alpha(),
+        unit_cell.beta(),
+        unit_cell.gamma(),
+    ];
+    for (value, known) in values.iter().zip(known) {
+        assert!((value - known).abs() < 0.001, "{values:?} {known}");
+    }
+}
+
+/// The topology of the first atoms of ubiquitin
+fn first_atoms(count: usize) -> PDB {
+    let mut topology = read("example-pdbs/1ubq.pdb");
+    topology.remove_atoms_by(|atom| atom.serial_number() > count);
+    topology
+}
+
+/// Add a Fortran unformatted record, the data surrounded by its length
+fn fortran_record(bytes: &mut Vec<u8>, data: &[u8], little_endian: bool) {
+    let marker = if little_endian {
+        (data.len() as i32).to_le_bytes()
+    } else {
+        (data.len() as i32).to_be_bytes()
+    };
+    bytes.extend(marker);
+    bytes.extend(data);
+    bytes.extend(marker);
+}
+
+/// Create a CHARMM DCD file with unit cells following the layout of the CHARMM `dcdio` source,
+/// the first frame has angles in degrees (NAMD) and the second cosines of angles (CHARMM)
+fn charmm_dcd(little_endian: bool) -> Vec<u8> {
+    let i32_bytes = |value: i32| {
+        if little_endian {
+            value.to_le_bytes()
+        } else {
+            value.to_be_bytes()
+        }
+    };
+    let f32_bytes = |value: f32| {
+        if little_endian {
+            value.to_le_bytes()
+        } else {
+            value.to_be_bytes()
+        }
+    };
+    let f64_bytes = |value: f64| {
+        if little_endian {
+            value.to_le_bytes()
+        } else {
+            value.to_be_bytes()
+        }
+    };
+    let mut bytes = Vec::new();
+
+    // NSET, ISTART, NSAVC, NSTEP, four zeros, NAMNF, DELTA, unit cell flag, 4D flag, version
+    let mut control = b"CORD".to_vec();
+    for value in [2, 1000, 10, 20, 0, 0, 0, 0, 0] {
+        control.extend(i32_bytes(value));
+    }
+    control.extend(f32_bytes(2.0));
+    control.extend(i32_bytes(1));
+    for _ in 11..19 {
+        control.extend(i32_bytes(0));
+    }
+    control.extend(i32_bytes(36));
+    fortran_record(&mut bytes, &control, little_endian);
+
+    let mut titles = i32_bytes(2).to_vec();
+    titles.extend(format!("{:80}", "* HAND WRITTEN DCD").as_bytes());
+    titles.extend(format!("{:80}", "* TWO ATOMS").as_bytes());
+    fortran_record(&mut bytes, &titles, little_endian);
+    fortran_record(&mut bytes, &i32_bytes(2), little_endian);
+
+    // A, gamma, B, beta, alpha, C
+    let cells = [
+        [30.0, 80.0, 40.0, 70.0, 60.0, 50.0],
+        [31.0, 0.0, 41.0, 0.5, 0.0, 51.0],
+    ];
+    let coordinates = [
+        [[1.5, -2.25], [3.0, 4.5], [-6.75, 8.0]],
+        [[1.75, -2.5], [3.25, 4.75], [-7.0, 8.25]],
+    ];
+    for (cell, axes) in cells.iter().zip(coordinates) {
+        let cell: Vec<u8> = cell.iter().flat_map(|value| f64_bytes(*value)).collect();
+        fortran_record(&mut bytes, &cell, little_endian);
+        for axis in axes {
+            let axis: Vec<u8> = axis.iter().flat_map(|value| f32_bytes(*value)).collect();
+            fortran_record(&mut bytes, &axis, little_endian);
+        }
+    }
+    bytes
+}
+
+#[test]
+fn known_dcd() {
+    let topology = first_atoms(2);
+    for little_endian in [true, false] {
+        let bytes = charmm_dcd(little_endian);
+        let mut trajectory =
+            Trajectory::from_reader(&topology, Cursor::new(&bytes), TrajectoryFormat::Dcd).unwrap();
+        assert_eq!(trajectory.frame_count(), 2);
+
+        let first = trajectory.read_frame(0).unwrap();
+        assert_eq!(first.step, Some(1000));
+        assert!((first.time.unwrap() - 1000.0 * 2.0 * 0.048_888_21).abs() < 0.001);
+        assert_known_positions(&first.positions, &[(1.5, 3.0, -6.75), (-2.25, 4.5, 8.0)]);
+        assert_known_unit_cell(
+            first.unit_cell.as_ref(),
+            [30.0, 40.0, 50.0, 60.0, 70.0, 80.0],
+        );
+
+        let second = trajectory.read_frame(1).unwrap();
+        assert_eq!(second.step, Some(1010));
+        assert_known_positions(&second.positions, &[(1.75, 3.25, -7.0), (-2.5, 4.75, 8.25)]);
+        assert_known_unit_cell(
+            second.unit_cell.as_ref(),
+            [31.0, 41.0, 51.0, 90.0, 60.0, 90.0],
+        );
+    }
+}
+
+/// Create a double precision TRR frame of two atoms following the layout of the GROMACS
+/// `trnio` source, with a triclinic box, positions, velocities, and forces
+fn gromacs_trr() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for value in [1993, 13, 12] {
+        bytes.extend(i32::to_be_bytes(value));
+    }
+    bytes.extend(b"GMX_trn_file");
+    // ir, e, box, vir, pres, top, sym, x, v, f, natoms, step, nre
+    for value in [0, 0, 72, 72, 0, 0, 0, 48, 48, 48, 2, 5000, 0] {
+        bytes.extend(i32::to_be_bytes(value));
+    }
+    let blocks: [&[f64]; 6] = [
+        // Time and lambda
+        &[10.0, 0.0],
+        // Box vectors a, b, and c
+        &[4.0, 0.0, 0.0, 0.0, 5.0, 0.0, 0.0, 3.0, 4.0],
+        // Virial
+        &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0],
+        // Positions
+        &[0.15, 0.25, 0.35, 1.5, 2.5, 3.5],
+        // Velocities
+        &[-0.1, 0.0, 0.1, 0.2, -0.2, 0.3],
+        // Forces
+        &[100.0, 200.0, 300.0, 400.0, 500.0, 600.0],
+    ];
+    for value in blocks.iter().copied().flatten() {
+        bytes.extend(value.to_be_bytes());
+    }
+    bytes
+}
+
+#[test]
+fn known_trr() {
+    let topology = first_atoms(2);
+    let bytes = gromacs_trr();
+    let mut trajectory =
+        Trajectory::from_reader(&topology, Cursor::new(&bytes), TrajectoryFormat::Trr).unwrap();
+    assert_eq!(trajectory.frame_count(), 1);
+    let frame = trajectory.read_frame(0).unwrap();
+    assert_eq!(frame.step, Some(5000));
+    assert_eq!(frame.time, Some(10.0));
+    assert_known_positions(&frame.positions, &[(1.5, 2.5, 3.5), (15.0, 25.0, 35.0)]);
+    assert_known_positions(
+        frame.velocities.as_ref().unwrap(),
+        &[(-1.0, 0.0, 1.0), (2.0, -2.0, 3.0)],
+    );
+    // The angle between b and c is acos(0.6)
+    assert_known_unit_cell(
+        frame.unit_cell.as_ref(),
+        [40.0, 50.0, 50.0, 53.130_102, 90.0, 90.0],
+    );
+    assert!(Trajectory::from_reader(
+        &topology,
+        Cursor::new(&bytes[..bytes.len() - 8]),
+        TrajectoryFormat::Trr
+    )
+    .is_err());
+}
+
+/// Create the header of an XTC frame with the given box (nm)
+fn xtc_header(atom_count: i32, step: i32, time: f32, periodic_box: [f32; 9]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for value in [1995, atom_count, step] {
+        bytes.extend(value.to_be_bytes());
+    }
+    bytes.extend(time.to_be_bytes());
+    for value in periodic_box {
+        bytes.extend(value.to_be_bytes());
+    }
+    bytes.extend(atom_count.to_be_bytes());
+    bytes
+}
+
+/// Pack the given numbers of the given numbers of bits, most significant bit first, into bytes
+fn pack_bits(numbers: &[(u32, u32)]) -> Vec<u8> {
+    let mut bits = Vec::new();
+    for (value, count) in numbers {
+        bits.extend((0..*count).rev().map(|bit| (value >> bit) & 1));
+    }
+    bits.chunks(8)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .chain(std::iter::repeat(&0))
+                .take(8)
+                .fold(0, |byte, bit| byte << 1 | *bit as u8)
+        })
+        .collect()
+}
+
+#[test]
+fn known_xtc() {
+    let periodic_box = [5.0, 0.0, 0.0, 0.0, 6.0, 0.0, 0.0, 0.0, 7.0];
+
+    // Frames of at most nine atoms are stored as plain floats
+    let topology = first_atoms(2);
+    let mut bytes = xtc_header(2, 250, 0.5, periodic_box);
+    for value in [0.125_f32, 0.25, 0.5, -1.0, 2.0, 4.25] {
+        bytes.extend(value.to_be_bytes());
+    }
+    let mut trajectory =
+        Trajectory::from_reader(&topology, Cursor::new(&bytes), TrajectoryFormat::Xtc).unwrap();
+    let frame = trajectory.read_frame(0).unwrap();
+    assert_eq!(frame.step, Some(250));
+    assert_eq!(frame.time, Some(0.5));
+    assert_known_positions(&frame.positions, &[(1.25, 2.5, 5.0), (-10.0, 20.0, 42.5)]);
+    assert_known_unit_cell(
+        frame.unit_cell.as_ref(),
+        [50.0, 60.0, 70.0, 90.0, 90.0, 90.0],
+    );
+
+    // A compressed frame, encoded by hand following `xdr3dfcoord` of the GROMACS xdrfile
+    // library. The coordinates lie between (100, 200, 300) and (103, 203, 303) so the full
+    // coordinates are three numbers of size 4 packed in 7 bits, nearby atoms are three numbers
+    // of size MAGIC_INTS[9] = 8 packed in 9 bits, low byte first, and are offset by 8 / 2.
+    let data = pack_bits(&[
+        // (101, 202, 303), then a run of two nearby atoms at the same compression level
+        (27, 7),
+        (1, 1),
+        (7, 5),
+        // (+1, 0, -1) and (0, +2, 0): 5 * 64 + 4 * 8 + 3 = 355 and 4 * 64 + 6 * 8 + 4 = 308
+        (355 & 0xff, 8),
+        (355 >> 8, 1),
+        (308 & 0xff, 8),
+        (308 >> 8, 1),
+        // (100, 200, 300), the previous run length is used again
+        (0, 7),
+        (0, 1),
+        // (0, 0, 0) and (+3, 0, 0): 292 and 484
+        (292 & 0xff, 8),
+        (292 >> 8, 1),
+        (484 & 0xff, 8),
+        (484 >> 8, 1),
+        // (103, 203, 303) without a run, and a larger compression level
+        (63, 7),
+        (1, 1),
+        (2, 5),
+        // (102, 201, 300) with one nearby atom and a smaller compression level afterwards
+        (36, 7),
+        (1, 1),
+        (3, 5),
+        // (0, 0, +1) at level 10, three numbers of size 10 offset by 5: 5 * 100 + 5 * 10 + 6
+        (556 & 0xff, 8),
+        (556 >> 8, 2),
+        // (101, 201, 301) without a run
+        (21, 7),
+        (1, 1),
+        (1, 5),
+    ]);
+    let topology = first_atoms(10);
+    let mut bytes = xtc_header(10, 1, 0.002, periodic_box);
+    bytes.extend(1000.0_f32.to_be_bytes());
+    for value in [100, 200, 300, 103, 203, 303, 9, data.len() as i32] {
+        bytes.extend(value.to_be_bytes());
+    }
+    bytes.extend(&data);
+    bytes.resize((bytes.len() + 3) / 4 * 4, 0);
+    let mut trajectory =
+        Trajectory::from_reader(&topology, Cursor::new(&bytes), TrajectoryFormat::Xtc).unwrap();
+    let frame = trajectory.read_frame(0).unwrap();
+    assert_eq!(frame.step, Some(1));
+    // The first two atoms of a run are interchanged
+    assert_known_positions(
+        &frame.positions,
+        &[
+            (1.02, 2.02, 3.02),
+            (1.01, 2.02, 3.03),
+            (1.02, 2.04, 3.02),
+            (1.00, 2.00, 3.00),
+            (1.00, 2.00, 3.00),
+            (1.03, 2.00, 3.00),
+            (1.03, 2.03, 3.03),
+            (1.02, 2.01, 3.01),
+            (1.02, 2.01, 3.00),
+            (1.01, 2.01, 3.01),
+        ],
+    );
+}
@@ -0,0 +1,109 @@
+// Test keeping the mmCIF categories that are not interpreted, and writing them back when saving.
+
+use pdbtbx::cif::*;
+use pdbtbx::*;
+use std::io::BufWriter;
+
+/// Read the file at the given path, keeping the unknown categories if `keep` is set
+fn read(path: &str, keep: bool) -> PDB {
+    ReadOptions::default()
+        .set_level(StrictnessLevel::Loose)
+        .set_keep_unknown_categories(keep)
+        .read(path)
+        .unwrap()
+        .0
+}
+
+/// Save the given PDB as mmCIF and read the text back as a CIF document
+fn save(pdb: &PDB) -> Document {
+    let mut buffer = Vec::new();
+    save_mmcif_raw(pdb, BufWriter::new(&mut buffer));
+    Document::parse(&String::from_utf8(buffer).unwrap()).unwrap()
+}
+
+#[test]
+fn keep_unknown_categories() {
+    for path in ["example-pdbs/1ubq.cif", "example-pdbs/1yyf.cif"] {
+        let original = Document::read(path).unwrap();
+        let original = &original.blocks[0];
+        let mut pdb = read(path, true);
+        let kept = pdb.unknown_categories.clone().unwrap();
+        assert!(kept.category("citation").is_some(), "{path}");
+        assert!(kept.category("atom_site").is_none(), "{path}");
+        // Editing the structure does not remove the kept metadata
+        pdb.remove_atoms_by(|atom| atom.element() == Some(&Element::H));
+        let saved = save(&pdb);
+        let saved = &saved.blocks[0];
+        for name in kept.category_names() {
+            assert_eq!(
+                saved.category(name),
+                original.category(name),
+                "{path} {name}"
+            );
+        }
+        // The categories generated from the structure are written only once
+        let mut tags: Vec<&str> = saved
+            .items
+            .iter()
+            .flat_map(|item| match item {
+                Item::DataItem(DataItem::Single(single)) => vec![single.name.as_str()],
+                Item::DataItem(DataItem::Loop(l)) => l.header.iter().map(String::as_str).collect(),
+                Item::SaveFrame(_) => Vec::new(),
+            })
+            .collect();
+        let count = tags.len();
+        tags.sort_unstable();
+        tags.dedup();
+        assert_eq!(tags.len(), count, "{path}");
+    }
+}
+
+#[test]
+fn unknown_categories_not_kept_by_default() {
+    let pdb = read("example-pdbs/1ubq.cif", false);
+    assert!(pdb.unknown_categories.is_none());
+    assert!(save(&pdb).blocks[0].category("citation").is_none());
+    assert!(read("example-pdbs/1ubq.pdb", true)
+        .unknown_categories
+        .is_none());
+}
+
+#[test]
+fn edit_unknown_categories() {
+    let mut pdb = read("example-pdbs/1ubq.cif", true);
+    let kept = pdb.unknown_categories.as_mut().unwrap();
+    kept.remove_category("citation");
+    kept.set_value("_pdbx_custom.note", "added");
+    // Categories generated from the structure are never written from the kept categories
+    kept.set_value("_refine.ls_d_res_high", 0.5);
+    let saved = save(&pdb);
+    let block = &saved.blocks[0];
+    assert!(block.category("citation").is_none());
+    assert_eq!(block.value("pdbx_custom.note"), Some(&Value::from("added")));
+    assert_eq!(
+        block
+            .category("refine")
+            .unwrap()
+            .get_f64(0, "ls_d_res_high")
+            .unwrap(),
+        Some(1.8)
+    );
+}
+
+#[test]
+fn edited_structure_replaces_interpreted_categories() {
+    let mut pdb = read("example-pdbs/1ubq.cif", true);
+    let kept = pdb.unknown_categories.as_ref().unwrap();
+    for name in ["struct", "struct_conf", "struct_sheet_range", "refine"] {
+        assert!(kept.category(name).is_none(), "{name}");
+    }
+    assert!(pdb.secondary_structure_count() > 0);
+    pdb.delete_secondary_structure_by(|_| true);
+    pdb.header.as_mut().unwrap().title = None;
+    let saved = save(&pdb);
+    let block = &saved.blocks[0];
+    assert!(block.category("struct_conf").is_none());
+    assert!(block.category("struct_sheet_range").is_none());
+    assert!(block.value("struct.title").is_none());
+    assert!(block.category("citation").is_some());
+}
@@ -0,0 +1,124 @@
+// Test reading and writing XYZ and extended XYZ files.
+
+mod common;
+
+use common::{close, dump, read};
+use pdbtbx::*;
+
+#[test]
+fn read_xyz() {
+    let pdb = read("example-pdbs/small.xyz");
+    assert_eq!(
+        pdb.header.as_ref().unwrap().title.as_deref(),
+        Some("Water molecule")
+    );
+    assert_eq!(pdb.model_count(), 2);
+    assert_eq!(pdb.model(0).unwrap().atom_count(), 3);
+    assert_eq!(pdb.residue_count(), 1);
+    let residue = pdb.residues().next().unwrap();
+    assert_eq!(residue.name(), Some("UNL"));
+    assert_eq!(pdb.chains().next().unwrap().id(), "A");
+    let atom = pdb.atoms().nth(1).unwrap();
+    assert_eq!(atom.element(), Some(&Element::H));
+    assert_eq!(atom.name(), "H");
+    assert!(atom.hetero());
+    assert!(close(atom.y(), 0.7572) && close(atom.z(), -0.4692));
+    // Atomic numbers are accepted as species
+    let last = pdb.model(1).unwrap().atoms().last().unwrap();
+    assert_eq!(last.element(), Some(&Element::H));
+    assert!(close(last.y(), -0.8572));
+    assert!(pdb.unit_cell.is_none());
+
+    let first = ReadOptions::default()
+        .set_only_first_model(true)
+        .set_discard_hydrogens(true)
+        .read("example-pdbs/small.xyz")
+        .unwrap()
+        .0;
+    assert_eq!(first.model_count(), 1);
+    assert_eq!(first.atom_count(), 1);
+}
+
+#[test]
+fn read_extended_xyz() {
+    let pdb = read("example-pdbs/small.extxyz");
+    assert!(pdb.header.is_none());
+    assert_eq!(pdb.chain_count(), 2);
+    assert_eq!(pdb.residue_count(), 3);
+    let alanine = pdb.residues().next().unwrap();
+    assert_eq!(alanine.name(), Some("ALA"));
+    assert_eq!(alanine.atom_count(), 2);
+    let atom = alanine.atoms().nth(1).unwrap();
+    assert_eq!(atom.name(), "CA");
+    assert!(!atom.hetero());
+    assert_eq!(atom.partial_charge(), Some(0.0337));
+    assert!(close(atom.b_factor(), 13.0));
+    let chloride = pdb.atoms().last().unwrap();
+    assert_eq!(chloride.element(), Some(&Element::Cl));
+    assert!(chloride.hetero());
+    let ions = pdb.chains().nth(1).unwrap();
+    assert_eq!(ions.id(), "B");
+    assert_eq!(ions.residues().last().unwrap().serial_number(), 102);
+    let cell = pdb.unit_cell.as_ref().unwrap();
+    assert!(close(cell.a(), 10.0) && close(cell.b(), 12.0) && close(cell.c(), 15.0));
+    assert!(close(cell.alpha(), 90.0) && close(cell.gamma(), 90.0));
+}
+
+#[test]
+fn invalid_files() {
+    let read_text = |text: &str| {
+        ReadOptions::default()
+            .set_format(Format::Xyz)
+            .read_raw(std::io::BufReader::new(text.as_bytes()))
+    };
+    assert!(read_text("").is_err());
+    assert!(read_text("three\n\nC 0 0 0\n").is_err());
+    assert!(read_text("2\n\nC 0 0 0\n").is_err());
+    assert!(read_text("1\n\nC 0 zero 0\n").is_err());
+    assert!(read_text("1\nProperties=species:S:1:pos\nC 0 0 0\n").is_err());
+    // The positions are required
+    assert!(read_text("1\nProperties=species:S:1\nC\n").is_err());
+    assert!(read_text("1\nProperties=species:S:1:pos:R:2\nC 0 0\n").is_err());
+    assert!(read_text("1\nProperties=species:S:1:pos:R:3\nC 0 0 0\n").is_ok());
+    assert!(read_text("1\n\nC 0 0 0\n").is_ok());
+}
+
+#[test]
+fn save_and_read() {
+    let pdb = read("example-pdbs/small.extxyz");
+    let name = dump("small.xyz");
+    save(&pdb, &name, StrictnessLevel::Loose).unwrap();
+    let text = std::fs::read_to_string(&name).unwrap();
+    assert!(text.contains("Lattice=\"10.00000 0.00000 0.00000 0.00000 12.00000"));
+    assert!(text.contains("\nCl "));
+    let saved = read(&name);
+    assert_eq!(pdb.atom_count(), saved.atom_count());
+    assert_eq!(pdb.residue_count(), saved.residue_count());
+    assert_eq!(pdb.chain_count(), saved.chain_count());
+    for (a, b) in pdb.atoms().zip(saved.atoms()) {
+        assert_eq!(a.name(), b.name());
+        assert_eq!(a.element(), b.element());
+        assert_eq!(a.partial_charge(), b.partial_charge());
+        assert!(close(a.b_factor(), b.b_factor()));
+        assert!(a.distance(b) < 0.0001);
+    }
+    let (a, b) = (pdb.unit_cell.unwrap(), saved.unit_cell.unwrap());
+    assert!(close(a.a(), b.a()) && close(a.gamma(), b.gamma()));
+}
+
+#[test]
+fn save_pdb_as_xyz() {
+    let pdb = read("example-pdbs/models.pdb");
+    let name = dump("models.xyz");
+    save_xyz(&pdb, &name, StrictnessLevel::Loose).unwrap();
+    let saved = read(&name);
+    assert_eq!(pdb.model_count(), saved.model_count());
+    assert_eq!(pdb.atom_count(), saved.atom_count());
+    for (a, b) in pdb.atoms_with_hierarchy().zip(saved.atoms_with_hierarchy()) {
+        assert_eq!(a.atom().name(), b.atom().name());
+        assert_eq!(a.atom().element(), b.atom().element());
+        assert_eq!(a.residue().serial_number(), b.residue().serial_number());
+        assert_eq!(a.chain().id(), b.chain().id());
+        assert!(a.atom().distance(b.atom()) < 0.0001);
+    }
+}